@@ -0,0 +1,19 @@
+#!/bin/sh
+# Download the Twemoji images listed in curated.txt into this directory.
+#
+# Images already here are kept. Rebuild afterwards to embed the new files.
+set -eu
+cd "$(dirname "$0")"
+CDN=https://cdn.jsdelivr.net/gh/twitter/twemoji@latest/assets/72x72
+
+grep -v '^#' curated.txt | while read -r codepoint name; do
+    [ -n "$codepoint" ] || continue
+    [ -f "$codepoint.png" ] && continue
+    if curl -fsSL -o "$codepoint.png.tmp" "$CDN/$codepoint.png"; then
+        mv "$codepoint.png.tmp" "$codepoint.png"
+        echo "fetched $codepoint ($name)"
+    else
+        rm -f "$codepoint.png.tmp"
+        echo "failed: $codepoint ($name)" >&2
+    fi
+done
@@ -1,6 +1,8 @@
 // claude-deck Configuration UI
 
 const API_BASE = '/api';
+// Sent on every change so other web pages can't drive the deck
+const API_TOKEN = document.querySelector('meta[name="deck-token"]').content;
 
 // State
 let profiles = [];
@@ -9,7 +11,7 @@ let currentButton = null;
 let colorPresets = [];
 let availableKeys = [];
 let builtinActions = [];  // Available built-in actions for Claude Code
-let currentCustomImage = null;  // Base64 data URL for custom image
+let currentCustomImage = null;  // asset:// reference (or base64 data URL) for custom image
 let installedApps = [];  // List of installed macOS apps
 let draggedButton = null;  // Currently dragged button position
 let selectedGifUrl = null;  // Currently selected GIF URL
@@ -37,6 +39,8 @@ const elements = {
     editDisplayType: document.getElementById('edit-display-type'),
     emojiDisplayGroup: document.getElementById('emoji-display-group'),
     imageDisplayGroup: document.getElementById('image-display-group'),
+    svgDisplayGroup: document.getElementById('svg-display-group'),
+    editIconSvg: document.getElementById('edit-icon-svg'),
     editEmojiImage: document.getElementById('edit-emoji-image'),
     imageDropZone: document.getElementById('image-drop-zone'),
     imageFileInput: document.getElementById('image-file-input'),
@@ -51,10 +55,17 @@ const elements = {
     modShift: document.getElementById('mod-shift'),
     autoSubmitGroup: document.getElementById('auto-submit-group'),
     editAutoSubmit: document.getElementById('edit-auto-submit'),
+    switchProfileGroup: document.getElementById('switch-profile-group'),
+    editSwitchProfile: document.getElementById('edit-switch-profile'),
     micIconHint: document.getElementById('mic-icon-hint'),
     btnCancel: document.getElementById('btn-cancel'),
     btnCopy: document.getElementById('btn-copy'),
     btnReload: document.getElementById('btn-reload'),
+    // Device controls
+    deviceInfo: document.getElementById('device-info'),
+    deviceBrightness: document.getElementById('device-brightness'),
+    btnDeviceIntro: document.getElementById('btn-device-intro'),
+    btnDeviceReset: document.getElementById('btn-device-reset'),
     // Profile management elements
     btnReset: document.getElementById('btn-reset'),
     btnDelete: document.getElementById('btn-delete'),
@@ -71,9 +82,15 @@ const elements = {
     gifSearchInput: document.getElementById('gif-search-input'),
     gifSearchBtn: document.getElementById('gif-search-btn'),
     gifResults: document.getElementById('gif-results'),
+    gifUploadInput: document.getElementById('gif-upload-input'),
     gifPreviewContainer: document.getElementById('gif-preview-container'),
     gifPreview: document.getElementById('gif-preview'),
     clearGifBtn: document.getElementById('clear-gif'),
+    // Simulator elements
+    simulator: document.getElementById('simulator'),
+    simButtons: document.getElementById('sim-buttons'),
+    simStrip: document.getElementById('sim-strip'),
+    simEncoders: document.getElementById('sim-encoders'),
 };
 
 // Initialize
@@ -88,6 +105,9 @@ async function init() {
         setConnected(true);
         setupEventListeners();
         startStatusPolling();
+        startEventStream();
+        startSimulator();
+        loadDeviceInfo().catch(() => {});
     } catch (error) {
         console.error('Failed to initialize:', error);
         setConnected(false, error.message);
@@ -203,14 +223,139 @@ function updateLcdDisplay(status) {
     if (taskQuadrant) taskQuadrant.classList.toggle('waiting', isWaiting);
 }
 
+// Live events: refresh when another client (or an edit to config.toml) changes the config
+function startEventStream() {
+    if (!window.EventSource) return;
+    const events = new EventSource(`${API_BASE}/events`);
+    events.addEventListener('config', refreshAfterConfigChange);
+    events.addEventListener('lagged', refreshAfterConfigChange);
+    events.addEventListener('device', () => loadDeviceInfo().catch(() => {}));
+}
+
+async function refreshAfterConfigChange() {
+    try {
+        profiles = await api('/profiles');
+        renderProfileTabs();
+        const name = currentProfile && currentProfile.name;
+        if (!profiles.some(p => p.name === name)) {
+            if (profiles.length > 0) await selectProfile(profiles[0].name);
+            return;
+        }
+        // Don't throw away a button edit in progress
+        if (currentButton) {
+            document.querySelectorAll('.profile-tab').forEach(tab => {
+                tab.classList.toggle('active', tab.dataset.profile === name);
+            });
+            return;
+        }
+        await selectProfile(name);
+    } catch (error) {
+        console.error('Failed to refresh after config change:', error);
+    }
+}
+
+// Simulator (--simulate): live device images and synthetic input
+let simulatorVersion = -1;
+
+async function startSimulator() {
+    let status;
+    try {
+        status = await api('/simulator');
+    } catch (error) {
+        return;
+    }
+    if (!status.enabled) return;
+
+    elements.simulator.classList.remove('hidden');
+
+    // Buttons 0-4 on top, 5-9 below, like the hardware
+    elements.simButtons.innerHTML = '';
+    for (let i = 0; i < 10; i++) {
+        const img = document.createElement('img');
+        img.className = 'sim-button';
+        img.dataset.button = i;
+        img.alt = `Button ${i}`;
+        img.addEventListener('mousedown', () => simulatorInput({ type: 'button_down', button: i }));
+        img.addEventListener('mouseup', () => simulatorInput({ type: 'button_up', button: i }));
+        elements.simButtons.appendChild(img);
+    }
+
+    elements.simEncoders.innerHTML = '';
+    for (let i = 0; i < 4; i++) {
+        const group = document.createElement('div');
+        group.className = 'sim-encoder';
+        group.innerHTML = `
+            <button type="button" data-dir="-1" title="Turn left">&#9664;</button>
+            <button type="button" data-press title="Press">&#9679;</button>
+            <button type="button" data-dir="1" title="Turn right">&#9654;</button>
+        `;
+        group.querySelectorAll('button').forEach(btn => {
+            btn.addEventListener('click', () => {
+                if (btn.dataset.press !== undefined) {
+                    simulatorInput({ type: 'encoder_press', encoder: i });
+                } else {
+                    simulatorInput({ type: 'rotate', encoder: i, direction: Number(btn.dataset.dir) });
+                }
+            });
+        });
+        elements.simEncoders.appendChild(group);
+    }
+
+    // Click the strip to touch a zone, drag along it to swipe
+    let stripDownX = null;
+    elements.simStrip.draggable = false;
+    elements.simStrip.addEventListener('mousedown', event => { stripDownX = event.offsetX; });
+    elements.simStrip.addEventListener('mouseup', event => {
+        if (stripDownX === null) return;
+        const moved = event.offsetX - stripDownX;
+        const width = elements.simStrip.clientWidth;
+        stripDownX = null;
+        if (Math.abs(moved) > width / 8) {
+            simulatorInput({ type: 'strip_swipe', direction: moved < 0 ? 'left' : 'right' });
+        } else {
+            const zone = Math.min(3, Math.floor(event.offsetX / width * 4));
+            simulatorInput({ type: 'strip_touch', zone });
+        }
+    });
+
+    pollSimulator();
+    setInterval(pollSimulator, 250);
+}
+
+async function pollSimulator() {
+    try {
+        const status = await api('/simulator');
+        if (status.version === simulatorVersion) return;
+        simulatorVersion = status.version;
+
+        elements.simStrip.src = `${API_BASE}/simulator/strip?v=${status.version}`;
+        elements.simButtons.querySelectorAll('.sim-button').forEach(img => {
+            img.src = `${API_BASE}/simulator/buttons/${img.dataset.button}?v=${status.version}`;
+        });
+        elements.simulator.style.filter = `brightness(${0.3 + 0.7 * status.brightness / 100})`;
+    } catch (error) {
+        // Ignore transient failures, next poll retries
+    }
+}
+
+async function simulatorInput(input) {
+    try {
+        await api('/simulator/input', { method: 'POST', body: JSON.stringify(input) });
+    } catch (error) {
+        console.error('Simulator input failed:', error);
+    }
+}
+
 // API Functions
 async function api(endpoint, options = {}) {
     const url = `${API_BASE}${endpoint}`;
     const response = await fetch(url, {
+        ...options,
         headers: {
             'Content-Type': 'application/json',
+            'X-Deck-Token': API_TOKEN,
+            ...options.headers,
         },
-        ...options,
     });
 
     const data = await response.json();
@@ -263,6 +408,22 @@ async function reloadConfig() {
     showToast('Config reloaded', 'success');
 }
 
+// Device control API functions
+async function loadDeviceInfo() {
+    const info = await api('/device/info');
+    const device = info.device;
+    elements.deviceInfo.textContent = device
+        ? device.model + (device.simulated ? ' (simulated)' : '')
+        : 'Disconnected';
+    elements.deviceBrightness.value = info.brightness;
+}
+
+async function deviceCommand(command, body) {
+    const options = { method: 'POST' };
+    if (body) options.body = JSON.stringify(body);
+    await api(`/device/${command}`, options);
+}
+
 // Profile management API functions
 async function loadApps() {
     const data = await api('/apps');
@@ -301,8 +462,47 @@ async function swapButtons(profileName, pos1, pos2) {
     });
 }
 
-async function searchGiphy(query) {
-    return await api(`/giphy/search?q=${encodeURIComponent(query)}&limit=12`);
+async function searchGifs(query) {
+    return await api(`/gifs/search?q=${encodeURIComponent(query)}&limit=12`);
+}
+
+async function uploadGif(name, data) {
+    return await api('/gifs/upload', {
+        method: 'POST',
+        body: JSON.stringify({ name, data }),
+    });
+}
+
+// Upload image files as assets (multipart), returning their asset:// references
+async function uploadAssets(files) {
+    const form = new FormData();
+    files.forEach(file => form.append('file', file));
+    const response = await fetch(`${API_BASE}/assets`, {
+        method: 'POST',
+        headers: { 'X-Deck-Token': API_TOKEN },
+        body: form,
+    });
+    const data = await response.json();
+    if (!data.success) {
+        throw new Error(data.error || 'Upload failed');
+    }
+    return data.data;
+}
+
+// Browser-loadable source for a custom image (asset:// references are served by the API)
+function imageSrc(customImage) {
+    if (customImage && customImage.startsWith('asset://')) {
+        return `${API_BASE}/assets/${customImage.slice('asset://'.length)}`;
+    }
+    return customImage;
+}
+
+// Browser-loadable source for a GIF URL (local library GIFs are file:// URLs)
+function gifSrc(url) {
+    if (url && url.startsWith('file://')) {
+        return `/api/gifs/local/${encodeURIComponent(url.split('/').pop())}`;
+    }
+    return url;
 }
 
 // Render Functions
@@ -361,7 +561,7 @@ function createButtonCell(button) {
     // Check if button has non-default config (not just "---" placeholder)
     // Include MIC buttons and any button with a meaningful action
     const hasAction = button.action && button.action.type === 'custom' && button.action.value && button.action.value !== '';
-    const hasConfig = button.label !== '---' || button.emoji_image || button.custom_image || button.gif_url || isMicButton || hasAction;
+    const hasConfig = button.label !== '---' || button.emoji_image || button.custom_image || button.icon_svg || button.gif_url || isMicButton || hasAction;
 
     // Build tooltip showing the action
     const tooltip = getButtonTooltip(button);
@@ -373,16 +573,21 @@ function createButtonCell(button) {
         content = `<span class="button-emoji">🎤</span>`;
     } else if (button.gif_url) {
         // Display GIF
-        content = `<img class="button-image" src="${button.gif_url}" alt="${button.label}">`;
+        content = `<img class="button-image" src="${gifSrc(button.gif_url)}" alt="${button.label}">`;
     } else if (button.emoji_image && isEmoji(button.emoji_image)) {
         // Display emoji
         content = `<span class="button-emoji">${button.emoji_image}</span>`;
     } else if (button.custom_image) {
         // Display custom uploaded image
-        content = `<img class="button-image" src="${button.custom_image}" alt="${button.label}">`;
+        content = `<img class="button-image" src="${imageSrc(button.custom_image)}" alt="${button.label}">`;
+    } else if (button.icon_svg && button.icon_svg.trim().startsWith('<')) {
+        // Display inline SVG icon (icons given as a file path show their label)
+        content = `<span class="button-svg">${button.icon_svg}</span>`;
     } else {
         // Display text label
-        content = `<span class="button-label">${button.label}</span>`;
+        // Typed `\n` and real newlines break lines like on the device
+        const lines = button.label.split(/\\n|\n/).join('<br>');
+        content = `<span class="button-label">${lines}</span>`;
     }
 
     return `
@@ -421,6 +626,51 @@ function getButtonTooltip(button) {
         case 'slack_emoji':
             actionDesc = `Send ${action.value}`;
             break;
+        case 'macro':
+            actionDesc = `Macro (${(action.steps || []).length} steps)`;
+            break;
+        case 'folder':
+            actionDesc = `Folder (${(action.buttons || []).length} buttons)`;
+            break;
+        case 'scene':
+            actionDesc = `Scene "${action.value}"`;
+            break;
+        case 'toggle':
+            actionDesc = `Toggle ${action.value}`;
+            break;
+        case 'applescript':
+            actionDesc = 'Run AppleScript';
+            break;
+        case 'open_url':
+            actionDesc = `Open ${action.url}`;
+            break;
+        case 'focus_app':
+            actionDesc = `Focus ${action.name}`;
+            break;
+        case 'launch_app':
+            actionDesc = `Launch ${action.path}`;
+            break;
+        case 'clipboard':
+            actionDesc = `Paste clipboard #${action.slot}`;
+            break;
+        case 'snippet':
+            actionDesc = `Snippet "${action.template}"`;
+            break;
+        case 'scroll':
+            actionDesc = `Scroll ${action.ticks < 0 ? 'up' : 'down'} ${Math.abs(action.ticks)}`;
+            break;
+        case 'obs':
+            actionDesc = `OBS ${action.command}`;
+            break;
+        case 'script':
+            actionDesc = `Script ${action.path}`;
+            break;
+        case 'monitor':
+            actionDesc = `Monitor ${action.name}`;
+            break;
+        case 'home_assistant':
+            actionDesc = `Home Assistant ${action.service} ${action.entity_id}`;
+            break;
         default:
             actionDesc = action.value || 'No action';
     }
@@ -594,6 +844,7 @@ function renderEditor() {
     const isMicAction = action.type === 'custom' && action.value === 'MIC';
 
     let displayType = 'text';
+    elements.editIconSvg.value = currentButton.icon_svg || '';
     if (isMicAction) {
         displayType = 'mic-icon';
         elements.micIconHint.classList.remove('hidden');
@@ -604,7 +855,7 @@ function renderEditor() {
         displayType = 'gif';
         selectedGifUrl = currentButton.gif_url;
         elements.gifUrlInput.value = currentButton.gif_url;
-        elements.gifPreview.src = currentButton.gif_url;
+        elements.gifPreview.src = gifSrc(currentButton.gif_url);
         elements.gifPreviewContainer.classList.remove('hidden');
         clearCustomImage();
         elements.editEmojiImage.value = '';
@@ -612,13 +863,19 @@ function renderEditor() {
     } else if (currentButton.custom_image) {
         displayType = 'image';
         currentCustomImage = currentButton.custom_image;
-        elements.imagePreview.src = currentCustomImage;
+        elements.imagePreview.src = imageSrc(currentCustomImage);
         elements.imagePreview.classList.remove('hidden');
         elements.clearImageBtn.classList.remove('hidden');
         const dropContent = elements.imageDropZone.querySelector('.drop-zone-content');
         if (dropContent) dropContent.classList.add('hidden');
         clearGif();
         elements.micIconHint.classList.add('hidden');
+    } else if (currentButton.icon_svg) {
+        displayType = 'svg';
+        clearCustomImage();
+        clearGif();
+        elements.editEmojiImage.value = '';
+        elements.micIconHint.classList.add('hidden');
     } else if (currentButton.emoji_image && isEmoji(currentButton.emoji_image)) {
         displayType = 'emoji';
         elements.editEmojiImage.value = currentButton.emoji_image;
@@ -640,9 +897,10 @@ function renderEditor() {
     const actionType = action.type === 'slack_emoji' ? 'emoji' : action.type;
     elements.editActionType.value = actionType;
     updateActionUI(actionType);
+    elements.editSwitchProfile.checked = !!action.switch_profile;
 
     // Populate action value
-    if (actionType === 'key') {
+    if (actionType === 'key' || actionType === 'toggle') {
         // Parse shortcut string to extract modifiers and key
         const shortcut = parseShortcut(action.value || '');
         elements.modCmd.checked = shortcut.cmd;
@@ -655,6 +913,47 @@ function renderEditor() {
         clearModifiers();
         elements.editActionBuiltin.value = action.value || '';
         elements.editAutoSubmit.checked = false;
+    } else if (actionType === 'applescript') {
+        clearModifiers();
+        elements.editActionValue.value = action.script || '';
+        elements.editAutoSubmit.checked = false;
+    } else if (actionType === 'open_url') {
+        clearModifiers();
+        elements.editActionValue.value = action.url || '';
+        elements.editAutoSubmit.checked = false;
+    } else if (actionType === 'focus_app' || actionType === 'launch_app') {
+        clearModifiers();
+        elements.editActionValue.value = action.name || action.path || '';
+        elements.editAutoSubmit.checked = false;
+    } else if (actionType === 'clipboard') {
+        clearModifiers();
+        elements.editActionValue.value = action.slot || 1;
+        elements.editAutoSubmit.checked = false;
+    } else if (actionType === 'snippet') {
+        clearModifiers();
+        elements.editActionValue.value = action.template || '';
+        elements.editAutoSubmit.checked = false;
+    } else if (actionType === 'scroll') {
+        clearModifiers();
+        elements.editActionValue.value = action.ticks || 1;
+        elements.editAutoSubmit.checked = false;
+    } else if (actionType === 'obs') {
+        clearModifiers();
+        elements.editActionValue.value = action.command || '';
+        elements.editAutoSubmit.checked = false;
+    } else if (actionType === 'script') {
+        clearModifiers();
+        elements.editActionValue.value = action.path || '';
+        elements.editAutoSubmit.checked = false;
+    } else if (actionType === 'monitor') {
+        clearModifiers();
+        elements.editActionValue.value = action.name || '';
+        elements.editAutoSubmit.checked = false;
+    } else if (actionType === 'home_assistant') {
+        clearModifiers();
+        elements.editActionValue.value = action.entity_id
+            ? `${action.service || 'toggle'} ${action.entity_id}` : '';
+        elements.editAutoSubmit.checked = false;
     } else {
         // Text or emoji action
         clearModifiers();
@@ -671,6 +970,7 @@ function updateDisplayUI(displayType) {
     const isText = displayType === 'text';
     const isEmoji = displayType === 'emoji';
     const isImage = displayType === 'image';
+    const isSvg = displayType === 'svg';
     const isGif = displayType === 'gif';
     const isMicIcon = displayType === 'mic-icon';
 
@@ -694,6 +994,12 @@ function updateDisplayUI(displayType) {
         elements.imageDisplayGroup.classList.add('hidden');
     }
 
+    if (isSvg) {
+        elements.svgDisplayGroup.classList.remove('hidden');
+    } else {
+        elements.svgDisplayGroup.classList.add('hidden');
+    }
+
     if (isGif) {
         elements.gifDisplayGroup.classList.remove('hidden');
     } else {
@@ -705,10 +1011,22 @@ function updateDisplayUI(displayType) {
 
 // Update action section UI based on action type
 function updateActionUI(actionType) {
-    const isKey = actionType === 'key';
+    const isKey = actionType === 'key' || actionType === 'toggle';
     const isCustom = actionType === 'custom';
     const isEmoji = actionType === 'emoji';
     const isText = actionType === 'text';
+    const isScene = actionType === 'scene';
+    const isAppleScript = actionType === 'applescript';
+    const isOpenUrl = actionType === 'open_url';
+    const isClipboard = actionType === 'clipboard';
+    const isObs = actionType === 'obs';
+    const isSnippet = actionType === 'snippet';
+    const isScroll = actionType === 'scroll';
+    const isScript = actionType === 'script';
+    const isMonitor = actionType === 'monitor';
+    const isHomeAssistant = actionType === 'home_assistant';
+    const isFocusApp = actionType === 'focus_app';
+    const isLaunchApp = actionType === 'launch_app';
 
     // Hide all inputs first
     elements.editActionValue.classList.add('hidden');
@@ -716,6 +1034,7 @@ function updateActionUI(actionType) {
     elements.editActionBuiltin.classList.add('hidden');
     elements.modifierGroup.classList.add('hidden');
     elements.autoSubmitGroup.classList.add('hidden');
+    elements.switchProfileGroup.classList.toggle('hidden', !isFocusApp && !isLaunchApp);
 
     // Show appropriate input
     if (isKey) {
@@ -723,6 +1042,10 @@ function updateActionUI(actionType) {
         elements.modifierGroup.classList.remove('hidden');
     } else if (isCustom) {
         elements.editActionBuiltin.classList.remove('hidden');
+    } else if (isScene || isAppleScript || isOpenUrl || isClipboard || isObs || isScript ||
+               isFocusApp || isLaunchApp || isMonitor || isHomeAssistant || isSnippet ||
+               isScroll) {
+        elements.editActionValue.classList.remove('hidden');
     } else {
         elements.editActionValue.classList.remove('hidden');
         // Show auto-submit option for text and emoji
@@ -741,6 +1064,42 @@ function updateActionUI(actionType) {
         elements.editActionValue.placeholder = ':+1:';
     } else if (isCustom) {
         label.textContent = 'Action';
+    } else if (isScene) {
+        label.textContent = 'Scene name';
+        elements.editActionValue.placeholder = 'Deep Work';
+    } else if (isAppleScript) {
+        label.textContent = 'Script';
+        elements.editActionValue.placeholder = 'tell application "Music" to playpause';
+    } else if (isOpenUrl) {
+        label.textContent = 'URL';
+        elements.editActionValue.placeholder = 'https://github.com/pulls';
+    } else if (isFocusApp) {
+        label.textContent = 'App name';
+        elements.editActionValue.placeholder = 'Slack';
+    } else if (isLaunchApp) {
+        label.textContent = 'App path';
+        elements.editActionValue.placeholder = '/Applications/Firefox Developer Edition.app';
+    } else if (isClipboard) {
+        label.textContent = 'History slot (1 = most recent)';
+        elements.editActionValue.placeholder = '1';
+    } else if (isSnippet) {
+        label.textContent = 'Template ({cursor} marks where the cursor ends up)';
+        elements.editActionValue.placeholder = 'Refactor {cursor} to use async/await';
+    } else if (isScroll) {
+        label.textContent = 'Lines to scroll (negative scrolls up)';
+        elements.editActionValue.placeholder = '3';
+    } else if (isObs) {
+        label.textContent = 'OBS command';
+        elements.editActionValue.placeholder = 'scene:Coding, toggle_recording, mute_mic';
+    } else if (isScript) {
+        label.textContent = 'Script (in ~/.config/claude-deck/scripts/)';
+        elements.editActionValue.placeholder = 'git-branch.sh';
+    } else if (isMonitor) {
+        label.textContent = 'Monitor name (from [[monitors]])';
+        elements.editActionValue.placeholder = 'ci';
+    } else if (isHomeAssistant) {
+        label.textContent = 'Service and entity';
+        elements.editActionValue.placeholder = 'toggle light.office, scene.turn_on scene.focus';
     }
 
     elements.editActionValue.disabled = false;
@@ -952,6 +1311,31 @@ function setupEventListeners() {
         }
     });
 
+    // Device controls
+    elements.deviceBrightness.addEventListener('change', async () => {
+        try {
+            await deviceCommand('brightness', { brightness: Number(elements.deviceBrightness.value) });
+        } catch (error) {
+            showToast(error.message, 'error');
+        }
+    });
+    elements.btnDeviceIntro.addEventListener('click', async () => {
+        try {
+            await deviceCommand('intro');
+        } catch (error) {
+            showToast(error.message, 'error');
+        }
+    });
+    elements.btnDeviceReset.addEventListener('click', async () => {
+        try {
+            await deviceCommand('reset');
+            await loadDeviceInfo();
+            showToast('Device reset', 'success');
+        } catch (error) {
+            showToast(error.message, 'error');
+        }
+    });
+
     // Emoji picker
     document.getElementById('emoji-picker').addEventListener('click', (e) => {
         const option = e.target.closest('.emoji-option');
@@ -1013,7 +1397,7 @@ function setupEventListeners() {
         const url = elements.gifUrlInput.value.trim();
         if (url) {
             selectedGifUrl = url;
-            elements.gifPreview.src = url;
+            elements.gifPreview.src = gifSrc(url);
             elements.gifPreviewContainer.classList.remove('hidden');
         } else {
             selectedGifUrl = null;
@@ -1021,6 +1405,14 @@ function setupEventListeners() {
         }
     });
 
+    // GIF library upload
+    elements.gifUploadInput.addEventListener('change', () => {
+        const file = elements.gifUploadInput.files[0];
+        if (file) {
+            addGifToLibrary(file);
+        }
+    });
+
     // GIF search
     elements.gifSearchBtn.addEventListener('click', performGifSearch);
     elements.gifSearchInput.addEventListener('keypress', (e) => {
@@ -1150,18 +1542,27 @@ function processImageFile(file) {
             // Draw resized/cropped image
             ctx.drawImage(img, srcX, srcY, srcSize, srcSize, 0, 0, size, size);
 
-            // Convert to base64
-            currentCustomImage = canvas.toDataURL('image/png');
+            // Store as an asset so the config only holds a reference,
+            // falling back to inline base64 if the upload fails
+            canvas.toBlob(async (blob) => {
+                try {
+                    const [asset] = await uploadAssets([new File([blob], file.name)]);
+                    currentCustomImage = asset.reference;
+                } catch (error) {
+                    showToast(`Image stored inline: ${error.message}`, 'error');
+                    currentCustomImage = canvas.toDataURL('image/png');
+                }
 
-            // Show preview
-            elements.imagePreview.src = currentCustomImage;
-            elements.imagePreview.classList.remove('hidden');
-            elements.clearImageBtn.classList.remove('hidden');
-            const dropContent = elements.imageDropZone.querySelector('.drop-zone-content');
-            if (dropContent) dropContent.classList.add('hidden');
+                // Show preview
+                elements.imagePreview.src = imageSrc(currentCustomImage);
+                elements.imagePreview.classList.remove('hidden');
+                elements.clearImageBtn.classList.remove('hidden');
+                const dropContent = elements.imageDropZone.querySelector('.drop-zone-content');
+                if (dropContent) dropContent.classList.add('hidden');
 
-            // Clear emoji when custom image is set
-            elements.editEmojiImage.value = '';
+                // Clear emoji when custom image is set
+                elements.editEmojiImage.value = '';
+            }, 'image/png');
         };
         img.src = e.target.result;
     };
@@ -1199,13 +1600,30 @@ async function performGifSearch() {
     elements.gifResults.innerHTML = '<div class="gif-loading">Searching...</div>';
 
     try {
-        const data = await searchGiphy(query);
+        const data = await searchGifs(query);
         renderGifResults(data.gifs);
     } catch (error) {
         elements.gifResults.innerHTML = `<div class="gif-error">${error.message}</div>`;
     }
 }
 
+// Save a GIF file to the local library and use it for this button
+function addGifToLibrary(file) {
+    const reader = new FileReader();
+    reader.onload = async () => {
+        try {
+            const gif = await uploadGif(file.name, reader.result);
+            elements.gifUrlInput.value = gif.url;
+            selectGif(gif.url, gif.preview_url);
+            showToast(`Added ${gif.title} to the GIF library`, 'success');
+        } catch (error) {
+            showToast(`Failed to upload GIF: ${error.message}`, 'error');
+        }
+        elements.gifUploadInput.value = '';
+    };
+    reader.readAsDataURL(file);
+}
+
 // Render GIF search results
 function renderGifResults(gifs) {
     if (!gifs || gifs.length === 0) {
@@ -1281,6 +1699,7 @@ async function saveButton() {
     let label = elements.editLabel.value;
     let emoji_image = '';
     let custom_image = '';
+    let icon_svg = '';
     let gif_url = '';
 
     if (displayType === 'emoji') {
@@ -1289,6 +1708,8 @@ async function saveButton() {
         label = displayEmoji || label;
     } else if (displayType === 'image') {
         custom_image = currentCustomImage || '';
+    } else if (displayType === 'svg') {
+        icon_svg = elements.editIconSvg.value.trim();
     } else if (displayType === 'gif') {
         gif_url = selectedGifUrl || '';
     }
@@ -1300,7 +1721,7 @@ async function saveButton() {
     // Get action value based on action type
     let actionValue;
     let autoSubmit = false;
-    if (actionType === 'key') {
+    if (actionType === 'key' || actionType === 'toggle') {
         // Build shortcut string from modifiers and key
         const key = elements.editActionKey.value;
         const cmd = elements.modCmd.checked;
@@ -1317,7 +1738,37 @@ async function saveButton() {
     }
 
     // Build action object
-    const action = { type: actionType, value: actionValue };
+    let action;
+    if (actionType === 'applescript') {
+        action = { type: actionType, script: actionValue };
+    } else if (actionType === 'open_url') {
+        action = { type: actionType, url: actionValue };
+    } else if (actionType === 'focus_app') {
+        action = { type: actionType, name: actionValue.trim(),
+                   switch_profile: elements.editSwitchProfile.checked };
+    } else if (actionType === 'launch_app') {
+        action = { type: actionType, path: actionValue.trim(),
+                   switch_profile: elements.editSwitchProfile.checked };
+    } else if (actionType === 'clipboard') {
+        action = { type: actionType, slot: Math.max(1, parseInt(actionValue, 10) || 1) };
+    } else if (actionType === 'snippet') {
+        action = { type: actionType, template: actionValue };
+    } else if (actionType === 'scroll') {
+        action = { type: actionType, ticks: parseInt(actionValue, 10) || 1 };
+    } else if (actionType === 'obs') {
+        action = { type: actionType, command: actionValue.trim() };
+    } else if (actionType === 'script') {
+        action = { type: actionType, path: actionValue.trim() };
+    } else if (actionType === 'monitor') {
+        action = { type: actionType, name: actionValue.trim() };
+    } else if (actionType === 'home_assistant') {
+        // "toggle light.office", or just the entity to toggle it
+        const parts = actionValue.trim().split(/\s+/);
+        const entityId = parts.pop() || '';
+        action = { type: actionType, entity_id: entityId, service: parts[0] || 'toggle' };
+    } else {
+        action = { type: actionType, value: actionValue };
+    }
     if (actionType === 'text' || actionType === 'emoji') {
         action.auto_submit = autoSubmit;
     }
@@ -1329,6 +1780,7 @@ async function saveButton() {
         action: action,
         emoji_image: emoji_image,
         custom_image: custom_image,
+        icon_svg: icon_svg,
         gif_url: gif_url,
     };
 
@@ -1391,6 +1843,7 @@ async function copyButtonToPosition() {
         action: currentButton.action,
         emoji_image: currentButton.emoji_image || '',
         custom_image: currentButton.custom_image || '',
+        icon_svg: currentButton.icon_svg || '',
         gif_url: currentButton.gif_url || '',
     };
 
@@ -0,0 +1,36 @@
+//! Build script: embeds the emoji bundle
+//!
+//! Every `assets/emoji/<codepoint>.png` is compiled into the binary, so
+//! adding an emoji to the bundle is just adding its file
+//! (`assets/emoji/fetch.sh` downloads the curated set).
+
+use std::fmt::Write;
+use std::path::PathBuf;
+
+fn main() {
+    println!("cargo:rerun-if-changed=assets/emoji");
+
+    let dir = PathBuf::from(std::env::var("CARGO_MANIFEST_DIR").unwrap()).join("assets/emoji");
+    let mut emoji: Vec<(String, PathBuf)> = std::fs::read_dir(&dir)
+        .expect("assets/emoji is missing")
+        .filter_map(|entry| {
+            let path = entry.ok()?.path();
+            if path.extension()? != "png" {
+                return None;
+            }
+            let codepoint = path.file_stem()?.to_str()?.to_string();
+            let valid = codepoint.split('-').all(|c| u32::from_str_radix(c, 16).is_ok());
+            valid.then_some((codepoint, path))
+        })
+        .collect();
+    emoji.sort();
+
+    let mut bundle = String::from("&[\n");
+    for (codepoint, path) in &emoji {
+        writeln!(bundle, "    ({:?}, include_bytes!({:?})),", codepoint, path).unwrap();
+    }
+    bundle.push_str("]\n");
+
+    let out = PathBuf::from(std::env::var("OUT_DIR").unwrap()).join("emoji_bundle.rs");
+    std::fs::write(out, bundle).unwrap();
+}
@@ -3,6 +3,8 @@
 # This script receives hook events from Claude Code and updates the status file
 
 STATUS_FILE="$HOME/.claude-deck/state.json"
+# claude-deck listens here for push updates (status file is the fallback)
+HOOK_SOCKET="$HOME/.claude-deck/hook.sock"
 
 # Ensure directory exists
 mkdir -p "$(dirname "$STATUS_FILE")"
@@ -40,6 +42,12 @@ if command -v jq &> /dev/null; then
         esac
     fi
 
+    # Raw tool input for claude-deck's own per-tool formatting (long strings such as
+    # file contents are capped to keep the status update small)
+    TOOL_INPUT_JSON=$(echo "$INPUT" | jq -c '(.tool_input // null) | walk(if type == "string" then .[0:10000] else . end)' 2>/dev/null)
+    CWD_JSON=$(echo "$INPUT" | jq -c '.cwd // null')
+    SESSION_JSON=$(echo "$INPUT" | jq -c '.session_id // null')
+
     # Try to get model from hook input first (most accurate)
     MODEL=$(echo "$INPUT" | jq -r '.model // empty')
 
@@ -60,6 +68,60 @@ if command -v jq &> /dev/null; then
             MODEL=$(jq -r '.model // empty' "$PROJECT_SETTINGS")
         fi
     fi
+
+    # Session cost (only present in some payloads)
+    COST=$(echo "$INPUT" | jq -r '.cost.total_cost_usd // empty')
+
+    # Token usage and context fill from the session transcript.
+    # Assistant messages can span several transcript lines with the same id, so dedupe;
+    # context = last message's prompt size (incl. cache) against a 200k window.
+    # The transcript only grows, so only the lines added since the last call are read:
+    # the byte offset reached, the running total, the context fill and the last message
+    # id are kept per transcript in ~/.claude-deck/usage/.
+    TRANSCRIPT=$(echo "$INPUT" | jq -r '.transcript_path // empty')
+    if [ -n "$TRANSCRIPT" ] && [ -f "$TRANSCRIPT" ]; then
+        USAGE_DIR="$(dirname "$STATUS_FILE")/usage"
+        USAGE_FILE="$USAGE_DIR/$(basename "$TRANSCRIPT" .jsonl)"
+        if [ -f "$USAGE_FILE" ]; then
+            read -r OFFSET TOTAL PCT LAST_ID < "$USAGE_FILE"
+        else
+            mkdir -p "$USAGE_DIR"
+            # Forget sessions nobody has touched for a week
+            find "$USAGE_DIR" -type f -mtime +7 -delete 2>/dev/null
+        fi
+        SIZE=$(wc -c < "$TRANSCRIPT" | tr -d ' ')
+        # Start over on a new or rewritten transcript
+        if ! [[ "$OFFSET" =~ ^[0-9]+$ && "$TOTAL" =~ ^[0-9]+$ ]] || [ "$OFFSET" -gt "$SIZE" ]; then
+            OFFSET=0
+            TOTAL=0
+            PCT=""
+            LAST_ID=""
+        fi
+        # Wait for a line still being written to end before reading up to it
+        LAST_BYTE=$(tail -c +"$SIZE" "$TRANSCRIPT" 2>/dev/null | head -c 1 | od -An -tx1 | tr -d ' \n')
+        if [ "$SIZE" -gt "$OFFSET" ] && [ "$LAST_BYTE" = "0a" ]; then
+            NEW=$(tail -c +$((OFFSET + 1)) "$TRANSCRIPT" | head -c $((SIZE - OFFSET)) \
+                | jq -c 'select(.type == "assistant" and .message.usage != null) | {id: (.message.id // ""), u: .message.usage}' 2>/dev/null \
+                | jq -s -r --arg last "$LAST_ID" '
+                if length == 0 then empty else
+                    (last.u) as $l
+                    | (map(select(.id != $last)) | unique_by(.id) | map((.u.input_tokens // 0) + (.u.output_tokens // 0)) | add // 0) as $tokens
+                    | ((($l.input_tokens // 0) + ($l.cache_read_input_tokens // 0) + ($l.cache_creation_input_tokens // 0)) * 100 / 200000 | floor) as $pct
+                    | "\($tokens) \(if $pct > 100 then 100 else $pct end) \(last.id)"
+                end' 2>/dev/null)
+            if [ -n "$NEW" ]; then
+                read -r NEW_TOKENS PCT LAST_ID <<< "$NEW"
+                TOTAL=$((TOTAL + NEW_TOKENS))
+            fi
+            OFFSET=$SIZE
+            echo "$OFFSET $TOTAL $PCT $LAST_ID" > "$USAGE_FILE"
+        fi
+        # No figures until the first assistant message
+        if [ -n "$PCT" ]; then
+            TOKENS=$TOTAL
+            CONTEXT_PCT=$PCT
+        fi
+    fi
 else
     # Fallback: basic grep parsing
     EVENT=$(echo "$INPUT" | grep -o '"hook_event_name":"[^"]*"' | cut -d'"' -f4)
@@ -69,7 +131,24 @@ else
     MODEL=""
 fi
 
+[ -n "$TOOL_INPUT_JSON" ] || TOOL_INPUT_JSON="null"
+[ -n "$CWD_JSON" ] || CWD_JSON="null"
+[ -n "$SESSION_JSON" ] || SESSION_JSON="null"
+
+# Terminal of the Claude process, so claude-deck can focus the right tab
+TTY=$(ps -o tty= -p "$PPID" 2>/dev/null | tr -d ' ')
+case "$TTY" in
+    ""|"?"|"??") TTY_JSON="null" ;;
+    *) TTY_JSON="\"/dev/$TTY\"" ;;
+esac
+
+# Usage fields must be plain numbers to keep the JSON valid
+[[ "$TOKENS" =~ ^[0-9]+$ ]] || TOKENS="null"
+[[ "$CONTEXT_PCT" =~ ^[0-9]+$ ]] || CONTEXT_PCT="null"
+[[ "$COST" =~ ^[0-9]+(\.[0-9]+)?$ ]] || COST="null"
+
 TIMESTAMP=$(date +%s)
+ERROR=""
 
 # Determine task and state based on event type
 case "$EVENT" in
@@ -102,9 +181,17 @@ case "$EVENT" in
             PROCESSING="false"
             WAITING="true"
             INPUT_TYPE='"permission"'
+        elif echo "$MESSAGE" | grep -qi "rate limit\|usage limit\|429\|overloaded"; then
+            TASK="RATE LIMITED"
+            TOOL_DETAIL=""
+            ERROR="$MESSAGE"
+            PROCESSING="false"
+            WAITING="false"
+            INPUT_TYPE="null"
         elif echo "$MESSAGE" | grep -qi "error\|failed"; then
             TASK="ERROR"
             TOOL_DETAIL=""
+            ERROR="$MESSAGE"
             PROCESSING="false"
             WAITING="false"
             INPUT_TYPE="null"
@@ -155,21 +242,46 @@ else
     fi
 fi
 
+# The error message, for the strip's banner
+ERROR=$(echo "$ERROR" | tr -d '\000-\037' | cut -c1-200)
+if [ -n "$ERROR" ]; then
+    ERROR_JSON=$(echo -n "$ERROR" | jq -Rs '.')
+else
+    ERROR_JSON="null"
+fi
+
 # Sanitize and truncate task (allow longer names for display)
 TASK=$(echo "$TASK" | tr -d '\000-\037' | cut -c1-50)
 
-# Write status file
-cat > "$STATUS_FILE" << EOF
+# Build status JSON
+STATUS_JSON=$(cat << EOF
 {
+  "event": "$EVENT",
   "task": "$TASK",
   "tool_detail": $TOOL_DETAIL_JSON,
+  "tool_input": $TOOL_INPUT_JSON,
+  "cwd": $CWD_JSON,
+  "session_id": $SESSION_JSON,
+  "tty": $TTY_JSON,
   "waiting_for_input": $WAITING,
   "input_type": $INPUT_TYPE,
   "model": $MODEL_JSON,
   "processing": $PROCESSING,
-  "error": null,
+  "error": $ERROR_JSON,
+  "tokens": $TOKENS,
+  "cost_usd": $COST,
+  "context_pct": $CONTEXT_PCT,
   "timestamp": $TIMESTAMP
 }
 EOF
+)
+
+# Write status file (always, so the web UI and file-poll fallback stay current)
+echo "$STATUS_JSON" > "$STATUS_FILE"
+
+# Push the update over the socket as a single line of JSON
+if [ -S "$HOOK_SOCKET" ] && command -v nc &> /dev/null; then
+    echo "$STATUS_JSON" | tr -d '\n' | { cat; echo; } | nc -U -w 1 "$HOOK_SOCKET" 2>/dev/null || true
+fi
 
 exit 0
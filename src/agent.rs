@@ -0,0 +1,218 @@
+//! System daemon / per-user agent split
+//!
+//! In daemon mode claude-deck runs as a LaunchDaemon that owns the device, so
+//! status keeps showing on the login screen and across fast user switching.
+//! A daemon can't inject keystrokes into a user's session, so it queues them
+//! and a per-user agent (`--agent`) long-polls the local API and replays them.
+//!
+//! Queued keystrokes can include text typed by macros, so an agent has to
+//! prove which user it runs as: the daemon writes each console user a token
+//! in `~/.claude-deck/agent-token`, readable only by them, and hands
+//! keystrokes only to a poll carrying the console user's token.
+
+use anyhow::Result;
+use std::collections::{HashMap, VecDeque};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tokio::sync::Notify;
+use tracing::{debug, info, warn};
+
+use crate::config::KeystrokeConfig;
+use crate::input::keystrokes::KeyOp;
+use crate::input::KeystrokeSender;
+use crate::system;
+
+/// Keystrokes older than this are dropped rather than replayed late
+const MAX_OP_AGE: Duration = Duration::from_secs(2);
+
+/// How long an agent poll waits for keystrokes before returning empty
+pub const POLL_TIMEOUT: Duration = Duration::from_secs(25);
+
+/// Header an agent sends its token in
+pub const AGENT_TOKEN_HEADER: &str = "x-agent-token";
+
+/// Where a user's agent token is kept, under their home directory
+pub fn agent_token_path(home: &Path) -> PathBuf {
+    home.join(".claude-deck/agent-token")
+}
+
+/// Keystrokes queued by the daemon for whichever agent owns the console
+#[derive(Default)]
+pub struct KeystrokeQueue {
+    ops: Mutex<VecDeque<(Instant, KeyOp)>>,
+    notify: Notify,
+    /// Agent token issued to each user, by user name
+    tokens: Mutex<HashMap<String, String>>,
+}
+
+impl KeystrokeQueue {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Queue an operation and wake any waiting agent
+    pub fn push(&self, op: KeyOp) {
+        self.ops.lock().unwrap().push_back((Instant::now(), op));
+        self.notify.notify_one();
+    }
+
+    /// Take all fresh operations, dropping stale ones
+    pub fn drain(&self) -> Vec<KeyOp> {
+        let mut ops = self.ops.lock().unwrap();
+        let dropped = ops.iter().filter(|(t, _)| t.elapsed() > MAX_OP_AGE).count();
+        if dropped > 0 {
+            debug!("Dropping {} stale keystroke ops (no agent connected?)", dropped);
+        }
+        ops.drain(..)
+            .filter(|(t, _)| t.elapsed() <= MAX_OP_AGE)
+            .map(|(_, op)| op)
+            .collect()
+    }
+
+    /// Whether `token` is the one issued to `user`, issuing it if need be
+    ///
+    /// The token is (re)written to the user's `agent-token` file whenever
+    /// that's missing, so their agent can pick it up and retry.
+    pub fn check_agent(&self, user: &str, home: &Path, token: Option<&str>) -> Result<bool> {
+        let path = agent_token_path(home);
+        let mut tokens = self.tokens.lock().unwrap();
+        let issued = match tokens.get(user) {
+            Some(issued) => issued.clone(),
+            None => {
+                let issued = system::random_token()?;
+                tokens.insert(user.to_string(), issued.clone());
+                issued
+            }
+        };
+        if !path.exists() {
+            system::write_secret_file(&path, &issued)?;
+            info!("Issued an agent token to '{}' in {}", user, path.display());
+        }
+        Ok(token.is_some_and(|token| token.trim() == issued))
+    }
+
+    /// Wait up to `timeout` for operations, then drain them
+    pub async fn wait_drain(&self, timeout: Duration) -> Vec<KeyOp> {
+        let ops = self.drain();
+        if !ops.is_empty() {
+            return ops;
+        }
+        let _ = tokio::time::timeout(timeout, self.notify.notified()).await;
+        self.drain()
+    }
+}
+
+/// Run the per-user agent: poll the daemon for keystrokes and inject them locally
+pub async fn run_agent(port: u16, timing: KeystrokeConfig) -> Result<()> {
+    let user = std::env::var("USER").unwrap_or_default();
+    let home = std::env::var("HOME").unwrap_or_default();
+    let token_path = agent_token_path(Path::new(&home));
+    let url = format!(
+        "http://127.0.0.1:{}/api/agent/keystrokes?user={}",
+        port,
+        urlencoding::encode(&user)
+    );
+    let client = reqwest::Client::builder()
+        .timeout(POLL_TIMEOUT + Duration::from_secs(10))
+        .build()?;
+    let mut sender = KeystrokeSender::with_timing(timing);
+
+    info!("Agent for user '{}' connecting to daemon on port {}", user, port);
+
+    loop {
+        // Only the user at the console should receive keystrokes
+        if system::console_user().as_deref() != Some(user.as_str()) {
+            tokio::time::sleep(Duration::from_secs(1)).await;
+            continue;
+        }
+
+        // Re-read each time: the daemon issues a new token when it restarts
+        let token = std::fs::read_to_string(&token_path).unwrap_or_default();
+        let request = client.get(&url).header(AGENT_TOKEN_HEADER, token.trim());
+        let response = match request.send().await {
+            Ok(r) => r,
+            Err(e) => {
+                warn!("Daemon not reachable: {}", e);
+                tokio::time::sleep(Duration::from_secs(2)).await;
+                continue;
+            }
+        };
+
+        match response
+            .json::<crate::web::ApiResponse<Vec<KeyOp>>>()
+            .await
+        {
+            Ok(body) if body.success => {
+                let ops = body.data.unwrap_or_default();
+                if !ops.is_empty() {
+                    debug!("Replaying {} keystroke ops", ops.len());
+                    sender.replay(&ops);
+                }
+            }
+            // The daemon writes the token on our first poll
+            Ok(_) if token.is_empty() => {
+                debug!("Waiting for the daemon to issue {}", token_path.display());
+                tokio::time::sleep(Duration::from_secs(1)).await;
+            }
+            Ok(body) => {
+                warn!("Daemon refused agent: {}", body.error.unwrap_or_default());
+                tokio::time::sleep(Duration::from_secs(5)).await;
+            }
+            Err(e) => {
+                warn!("Invalid response from daemon: {}", e);
+                tokio::time::sleep(Duration::from_secs(2)).await;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_queue_drain_preserves_order() {
+        let queue = KeystrokeQueue::new();
+        queue.push(KeyOp::Text {
+            text: "y".to_string(),
+        });
+        queue.push(KeyOp::Sleep { ms: 10 });
+
+        let ops = queue.drain();
+        assert_eq!(ops.len(), 2);
+        assert_eq!(
+            ops[0],
+            KeyOp::Text {
+                text: "y".to_string()
+            }
+        );
+        assert!(queue.drain().is_empty());
+    }
+
+    #[test]
+    fn test_agent_tokens() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let home = std::env::temp_dir().join(format!("claude-deck-agent-{}", std::process::id()));
+        let queue = KeystrokeQueue::new();
+        // The first poll has no token yet, and gets one written for it
+        assert!(!queue.check_agent("alice", &home, None).unwrap());
+        let path = agent_token_path(&home);
+        let token = std::fs::read_to_string(&path).unwrap();
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+
+        assert!(queue.check_agent("alice", &home, Some(&token)).unwrap());
+        assert!(!queue.check_agent("alice", &home, Some("guess")).unwrap());
+        // Tokens are per user
+        let other = home.join("bob");
+        assert!(!queue.check_agent("bob", &other, Some(&token)).unwrap());
+
+        // A deleted file is written again with the same token
+        std::fs::remove_file(&path).unwrap();
+        assert!(queue.check_agent("alice", &home, Some(&token)).unwrap());
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), token);
+        std::fs::remove_dir_all(home).ok();
+    }
+}
@@ -1,9 +1,16 @@
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
 
 use crate::profiles::store::ProfileConfig;
 
+mod watcher;
+mod writer;
+
+pub use watcher::watch_config_file;
+pub use writer::{bump_version, version, ConfigWriter};
+
 /// Application configuration
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 #[serde(default)]
@@ -15,6 +22,36 @@ pub struct Config {
     pub models: ModelsConfig,
     pub web: WebConfig,
     pub giphy: GiphyConfig,
+    pub gifs: GifsConfig,
+    pub keystrokes: KeystrokeConfig,
+    pub target: SendTarget,
+    pub power: PowerConfig,
+    pub slack: SlackConfig,
+    pub hue: HueConfig,
+    pub strip: StripConfig,
+    pub permissions: PermissionsConfig,
+    pub sound: SoundConfig,
+    pub notifications: NotificationsConfig,
+    pub attention: AttentionConfig,
+    pub timer: TimerConfig,
+    pub mqtt: MqttConfig,
+    pub midi: MidiConfig,
+    pub obs: ObsConfig,
+    pub focus: FocusConfig,
+    pub profile_switch: ProfileSwitchConfig,
+    pub hooks: HooksConfig,
+    pub security: SecurityConfig,
+    pub git: GitConfig,
+    pub home_assistant: HomeAssistantConfig,
+    /// Profiles by Claude Code's working directory (e.g. "~/work/api" = "api")
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub projects: BTreeMap<String, String>,
+    #[serde(default)]
+    pub monitors: Vec<MonitorConfig>,
+    #[serde(default)]
+    pub decks: Vec<DeckConfig>,
+    #[serde(default)]
+    pub scenes: Vec<SceneConfig>,
     #[serde(default)]
     pub profiles: Vec<ProfileConfig>,
 }
@@ -25,11 +62,7 @@ impl Config {
         let config_path = Self::config_path()?;
 
         if config_path.exists() {
-            let contents = std::fs::read_to_string(&config_path)
-                .with_context(|| format!("Failed to read config file at {:?}", config_path))?;
-            let config: Config = toml::from_str(&contents)
-                .with_context(|| format!("Failed to parse config file at {:?}", config_path))?;
-            Ok(config)
+            Self::load_from(&config_path)
         } else {
             // Create default config
             let config = Config::default();
@@ -38,21 +71,54 @@ impl Config {
         }
     }
 
+    /// Load configuration from an existing file at `path`
+    pub fn load_from(config_path: &Path) -> Result<Self> {
+        let contents = std::fs::read_to_string(config_path)
+            .with_context(|| format!("Failed to read config file at {:?}", config_path))?;
+        toml::from_str(&contents)
+            .with_context(|| format!("Failed to parse config file at {:?}", config_path))
+    }
+
     /// Save configuration to file
     pub fn save(&self) -> Result<()> {
-        let config_path = Self::config_path()?;
+        self.save_to(&Self::config_path()?)
+    }
 
+    /// Save configuration to `path`, written atomically via rename
+    pub fn save_to(&self, config_path: &Path) -> Result<()> {
         if let Some(parent) = config_path.parent() {
             std::fs::create_dir_all(parent)
                 .with_context(|| format!("Failed to create config directory at {:?}", parent))?;
         }
 
         let contents = toml::to_string_pretty(self).context("Failed to serialize config")?;
-        std::fs::write(&config_path, contents)
-            .with_context(|| format!("Failed to write config file at {:?}", config_path))?;
+        let tmp = config_path.with_extension("toml.tmp");
+        std::fs::write(&tmp, contents)
+            .with_context(|| format!("Failed to write config file at {:?}", tmp))?;
+        std::fs::rename(&tmp, config_path)
+            .with_context(|| format!("Failed to replace config file at {:?}", config_path))?;
         Ok(())
     }
 
+    /// Copy the file at `config_path` to `<name>.bak-<unix seconds>`
+    ///
+    /// Returns the backup's path, or None if there is no file to back up.
+    pub fn backup(config_path: &Path) -> Result<Option<PathBuf>> {
+        if !config_path.exists() {
+            return Ok(None);
+        }
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+        let mut name = config_path.file_name().unwrap_or_default().to_os_string();
+        name.push(format!(".bak-{}", timestamp));
+        let backup = config_path.with_file_name(name);
+        std::fs::copy(config_path, &backup)
+            .with_context(|| format!("Failed to back up config to {:?}", backup))?;
+        Ok(Some(backup))
+    }
+
     /// Get config file path
     pub fn config_path() -> Result<PathBuf> {
         let home = std::env::var("HOME").context("HOME environment variable not set")?;
@@ -71,8 +137,23 @@ impl Config {
 pub struct DeviceConfig {
     /// Device brightness (0-100)
     pub brightness: u8,
-    /// Seconds before dimming display
+    /// Seconds before dimming display (0 = never dim)
     pub idle_timeout: u32,
+    /// Brightness used while idle (0-100)
+    pub idle_brightness: u8,
+    /// Briefly brighten the idle display when a hook reports a new task
+    pub wake_on_hook: bool,
+    /// How long the wake flash stays bright before dimming again (milliseconds)
+    pub wake_flash_ms: u64,
+    /// Animate buttons as pushed in while held down
+    pub press_animation: bool,
+    /// Memory for decoded GIF frames before least recently used GIFs are dropped (MB)
+    pub gif_cache_mb: usize,
+    /// JPEG quality of GIF frames (1-100); static images always use 90
+    pub animation_quality: u8,
+    /// Raw HID event code overrides for firmware variants (e.g. "0x0b" = "button:9")
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub input_map: BTreeMap<String, String>,
 }
 
 impl Default for DeviceConfig {
@@ -80,6 +161,13 @@ impl Default for DeviceConfig {
         Self {
             brightness: 80,
             idle_timeout: 300,
+            idle_brightness: 10,
+            wake_on_hook: true,
+            wake_flash_ms: 4000,
+            press_animation: true,
+            gif_cache_mb: 64,
+            animation_quality: 70,
+            input_map: BTreeMap::new(),
         }
     }
 }
@@ -124,6 +212,8 @@ pub struct AppearanceConfig {
     pub theme: String,
     /// Accent color (hex)
     pub accent_color: String,
+    /// TTF/OTF font for labels and the strip (empty = built-in JetBrains Mono)
+    pub font_path: String,
 }
 
 impl Default for AppearanceConfig {
@@ -131,6 +221,7 @@ impl Default for AppearanceConfig {
         Self {
             theme: "dark".to_string(),
             accent_color: "#00ff88".to_string(),
+            font_path: String::new(),
         }
     }
 }
@@ -142,6 +233,12 @@ pub struct ModelsConfig {
     pub available: Vec<String>,
     /// Default model
     pub default: String,
+    /// Also offer the `model` from ~/.claude/settings.json and the models
+    /// used in recent Claude Code sessions
+    pub from_claude_code: bool,
+    /// Labels shown instead of model names (e.g. "claude-opus-4-6" = "Opus 4.6")
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub labels: BTreeMap<String, String>,
 }
 
 impl Default for ModelsConfig {
@@ -153,6 +250,8 @@ impl Default for ModelsConfig {
                 "haiku".to_string(),
             ],
             default: "opus".to_string(),
+            from_claude_code: false,
+            labels: BTreeMap::new(),
         }
     }
 }
@@ -191,3 +290,656 @@ impl Default for GiphyConfig {
         }
     }
 }
+
+/// Where the web UI's GIF search looks
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GifProvider {
+    #[default]
+    Giphy,
+    Tenor,
+    /// GIFs saved in `~/.config/claude-deck/gifs`
+    Local,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GifsConfig {
+    /// Provider used by GIF search
+    pub provider: GifProvider,
+    /// Tenor API key (from Google Cloud), needed for `provider = "tenor"`
+    pub tenor_api_key: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct KeystrokeConfig {
+    /// Delay before the first keystroke of an action (milliseconds)
+    pub pre_delay_ms: u64,
+    /// Delay between modifier/key presses within a shortcut (milliseconds)
+    pub inter_key_delay_ms: u64,
+    /// Random extra delay (0..=jitter_ms) added to each pause
+    pub jitter_ms: u64,
+}
+
+impl Default for KeystrokeConfig {
+    fn default() -> Self {
+        Self {
+            pre_delay_ms: 0,
+            inter_key_delay_ms: 10,
+            jitter_ms: 0,
+        }
+    }
+}
+
+/// Where keystrokes are delivered
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum SendTarget {
+    /// Whatever window is focused (synthesized key events)
+    #[default]
+    Focused,
+    /// A tmux pane, by `send-keys -t` target (e.g. "claude:0.1")
+    Tmux { pane: String },
+    /// An iTerm2 session, by id (the part of `$ITERM_SESSION_ID` after the colon)
+    Iterm { session: String },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HooksConfig {
+    /// How often to poll the status file while the hook socket is quiet (milliseconds)
+    pub poll_interval_ms: u64,
+}
+
+impl Default for HooksConfig {
+    fn default() -> Self {
+        Self {
+            poll_interval_ms: 200,
+        }
+    }
+}
+
+impl HooksConfig {
+    /// Poll interval, at least 10ms
+    pub fn poll_interval(&self) -> std::time::Duration {
+        std::time::Duration::from_millis(self.poll_interval_ms.max(10))
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PowerConfig {
+    /// Reduce brightness, GIF frame rate and polling while on battery
+    pub low_power_on_battery: bool,
+    /// Brightness points subtracted on battery (0-100)
+    pub battery_brightness_reduction: u8,
+    /// GIF animation tick interval on battery (milliseconds)
+    pub battery_gif_interval_ms: u64,
+    /// Status file poll interval on battery (milliseconds)
+    pub battery_status_poll_ms: u64,
+}
+
+impl Default for PowerConfig {
+    fn default() -> Self {
+        Self {
+            low_power_on_battery: true,
+            battery_brightness_reduction: 30,
+            battery_gif_interval_ms: 100,
+            battery_status_poll_ms: 1000,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SlackConfig {
+    /// Slack user token (xoxp-...) with users.profile:write, used by scenes
+    pub token: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HueConfig {
+    /// Philips Hue bridge address (e.g., "192.168.1.20")
+    pub bridge: String,
+    /// Hue API username (application key) registered on the bridge
+    pub username: String,
+}
+
+/// What an LCD strip quadrant shows
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StripWidget {
+    /// Current task, flashing while Claude waits for input
+    Task,
+    /// Current tool detail
+    Detail,
+    /// Selected model
+    Model,
+    /// Connection status, or session usage once idle
+    Status,
+    /// Local time
+    Clock,
+    /// System output volume
+    Volume,
+    /// CPU usage across all cores
+    Cpu,
+    /// Session cost, tokens and context used
+    TokenCost,
+    /// Frontmost application
+    FocusedApp,
+    /// Track reported by the active media player
+    NowPlaying,
+    /// Countdown timer started by `TIMER` buttons
+    Timer,
+    /// CPU, memory and network throughput bars
+    System,
+    /// Health of each `[[monitors]]` entry
+    Monitors,
+    /// Branch and dirty/ahead/behind counts of the `[git]` repository
+    Git,
+    /// Sub-agents running and waiting, or the one scrolled to
+    Agents,
+    /// State of the `[home_assistant] sensor` entity
+    HomeAssistant,
+    /// A widget registered by a plugin (`{ plugin = "name" }`)
+    Plugin(String),
+    /// Nothing
+    #[default]
+    Empty,
+}
+
+impl StripWidget {
+    /// Whether the widget changes on its own rather than on hook or input events
+    pub fn is_live(&self) -> bool {
+        matches!(
+            self,
+            Self::Clock | Self::Volume | Self::Cpu | Self::FocusedApp | Self::NowPlaying
+        )
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StripConfig {
+    pub top_left: StripWidget,
+    pub top_right: StripWidget,
+    pub bottom_left: StripWidget,
+    pub bottom_right: StripWidget,
+}
+
+impl StripConfig {
+    /// Widgets in quadrant order: top-left, top-right, bottom-left, bottom-right
+    pub fn widgets(&self) -> [StripWidget; 4] {
+        [
+            self.top_left.clone(),
+            self.top_right.clone(),
+            self.bottom_left.clone(),
+            self.bottom_right.clone(),
+        ]
+    }
+}
+
+impl Default for StripConfig {
+    fn default() -> Self {
+        Self {
+            top_left: StripWidget::Task,
+            top_right: StripWidget::Detail,
+            bottom_left: StripWidget::Model,
+            bottom_right: StripWidget::Status,
+        }
+    }
+}
+
+/// Quick actions shown on the top row while a permission prompt is open
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PermissionsConfig {
+    /// Replace the top row with the quick actions during permission prompts
+    pub quick_actions: bool,
+    /// Key that allows the tool call once
+    pub allow_once: String,
+    /// Key that allows it and stops asking
+    pub allow_always: String,
+    /// Key that denies it
+    pub deny: String,
+    /// Key that expands the pending change
+    pub show_diff: String,
+}
+
+impl Default for PermissionsConfig {
+    fn default() -> Self {
+        Self {
+            quick_actions: true,
+            allow_once: "1".to_string(),
+            allow_always: "2".to_string(),
+            deny: "Escape".to_string(),
+            show_diff: "Ctrl+R".to_string(),
+        }
+    }
+}
+
+/// Audio cues for button presses and Claude events
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SoundConfig {
+    /// Master switch for all cues
+    pub enabled: bool,
+    /// Volume (0.0-1.0) for cues that don't set their own
+    pub volume: f32,
+    /// Played on every button press
+    pub press: SoundCue,
+    /// Played when Claude starts waiting for input or permission
+    pub waiting: SoundCue,
+    /// Played when Claude finishes its turn
+    pub complete: SoundCue,
+    /// Played when Claude reports an error
+    pub error: SoundCue,
+}
+
+impl Default for SoundConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            volume: 0.5,
+            press: SoundCue::new("/System/Library/Sounds/Tink.aiff", false),
+            waiting: SoundCue::new("/System/Library/Sounds/Glass.aiff", true),
+            complete: SoundCue::new("/System/Library/Sounds/Hero.aiff", true),
+            error: SoundCue::new("/System/Library/Sounds/Basso.aiff", true),
+        }
+    }
+}
+
+/// One audio cue
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SoundCue {
+    pub enabled: bool,
+    /// Audio file to play (anything `afplay` accepts)
+    pub file: String,
+    /// Overrides `[sound] volume` for this cue
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub volume: Option<f32>,
+}
+
+impl SoundCue {
+    fn new(file: &str, enabled: bool) -> Self {
+        Self {
+            enabled,
+            file: file.to_string(),
+            volume: None,
+        }
+    }
+}
+
+/// Notification Center alerts for Claude events
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NotificationsConfig {
+    /// Master switch for all alerts
+    pub enabled: bool,
+    /// Alert when Claude starts waiting for input or permission
+    pub waiting: bool,
+    /// Alert when Claude reports an error
+    pub error: bool,
+    /// Alert when a long turn finishes
+    pub complete: bool,
+    /// Turns shorter than this (seconds) finish without an alert
+    pub long_task_secs: u64,
+}
+
+impl Default for NotificationsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            waiting: true,
+            error: true,
+            complete: true,
+            long_task_secs: 60,
+        }
+    }
+}
+
+/// Deck-wide glow while Claude waits for input or permission
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AttentionConfig {
+    /// Pulse an amber glow around every button and the strip
+    pub glow: bool,
+    /// Peak strength of the glow (0-100)
+    pub intensity: u8,
+}
+
+impl Default for AttentionConfig {
+    fn default() -> Self {
+        Self {
+            glow: false,
+            intensity: 60,
+        }
+    }
+}
+
+/// Countdown timer started by `TIMER` buttons
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TimerConfig {
+    /// Countdown length in minutes
+    pub minutes: u64,
+    /// Post a notification when the countdown reaches zero
+    pub notify: bool,
+    /// Blink the timer widget and `TIMER` buttons when it reaches zero
+    pub flash: bool,
+}
+
+impl Default for TimerConfig {
+    fn default() -> Self {
+        Self {
+            minutes: 25,
+            notify: true,
+            flash: true,
+        }
+    }
+}
+
+impl TimerConfig {
+    pub fn duration(&self) -> std::time::Duration {
+        std::time::Duration::from_secs(self.minutes * 60)
+    }
+}
+
+/// MQTT bridge for publishing deck events and accepting display updates
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MqttConfig {
+    pub enabled: bool,
+    /// Broker host name or address
+    pub host: String,
+    pub port: u16,
+    pub client_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub username: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub password: Option<String>,
+    /// Prepended to every topic (e.g., `claude-deck/button/3/press`)
+    pub topic_prefix: String,
+}
+
+impl Default for MqttConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            host: "localhost".to_string(),
+            port: 1883,
+            client_id: "claude-deck".to_string(),
+            username: None,
+            password: None,
+            topic_prefix: "claude-deck".to_string(),
+        }
+    }
+}
+
+/// OBS Studio WebSocket server (Tools → WebSocket Server Settings in OBS)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ObsConfig {
+    pub host: String,
+    pub port: u16,
+    /// Server password, if authentication is enabled in OBS
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub password: Option<String>,
+    /// Audio input toggled by the `mute_mic` command
+    pub mic_input: String,
+}
+
+impl Default for ObsConfig {
+    fn default() -> Self {
+        Self {
+            host: "localhost".to_string(),
+            port: 4455,
+            password: None,
+            mic_input: "Mic/Aux".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SecurityConfig {
+    /// Digits keyed in on the buttons to unlock after `LOCK_DECK` (1-9 then 0
+    /// in reading order); the deck can't be locked while empty
+    pub pin: String,
+}
+
+/// Behaviour while a macOS Focus mode (e.g. Do Not Disturb) is on
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FocusConfig {
+    /// Hold back the waiting-for-input flash and wake flash during Focus
+    pub quiet_animations: bool,
+    /// Shortcuts.app shortcut the DND button runs to toggle Do Not Disturb
+    pub toggle_shortcut: String,
+}
+
+impl Default for FocusConfig {
+    fn default() -> Self {
+        Self {
+            quiet_animations: true,
+            toggle_shortcut: "Toggle Do Not Disturb".to_string(),
+        }
+    }
+}
+
+/// How the deck follows the focused app
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProfileSwitchConfig {
+    /// Milliseconds an app must stay in front before its profile is shown, so a
+    /// brief switch (Spotlight, a notification click) doesn't flicker; 0 is at once
+    pub delay_ms: u64,
+}
+
+/// Repository shown by the `git` strip widget and used by GIT_PULL/GIT_PUSH
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GitConfig {
+    /// Repository path (`~/` expands to the home directory); empty follows the
+    /// directory of the Claude Code session
+    pub repo: String,
+    /// Seconds between `git status` checks
+    pub interval_secs: u64,
+}
+
+impl Default for GitConfig {
+    fn default() -> Self {
+        Self {
+            repo: String::new(),
+            interval_secs: 5,
+        }
+    }
+}
+
+impl GitConfig {
+    /// The configured repository, if any
+    pub fn repo_path(&self) -> Option<std::path::PathBuf> {
+        if self.repo.is_empty() {
+            return None;
+        }
+        match (self.repo.strip_prefix("~/"), std::env::var("HOME")) {
+            (Some(rest), Ok(home)) => Some(std::path::Path::new(&home).join(rest)),
+            _ => Some(self.repo.clone().into()),
+        }
+    }
+
+    /// Time between checks (at least 1 second)
+    pub fn interval(&self) -> std::time::Duration {
+        std::time::Duration::from_secs(self.interval_secs.max(1))
+    }
+}
+
+/// Home Assistant instance called by `home_assistant` actions
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HomeAssistantConfig {
+    /// Base URL (e.g., "http://homeassistant.local:8123")
+    pub url: String,
+    /// Long-lived access token (Profile → Security in Home Assistant)
+    pub token: String,
+    /// Entity shown by the `home_assistant` strip widget (e.g., "sensor.office_temperature")
+    pub sensor: String,
+    /// Seconds between sensor checks
+    pub interval_secs: u64,
+}
+
+impl Default for HomeAssistantConfig {
+    fn default() -> Self {
+        Self {
+            url: String::new(),
+            token: String::new(),
+            sensor: String::new(),
+            interval_secs: 30,
+        }
+    }
+}
+
+impl HomeAssistantConfig {
+    /// `url` without a trailing slash
+    pub fn base_url(&self) -> Result<&str> {
+        if self.url.is_empty() || self.token.is_empty() {
+            anyhow::bail!("no [home_assistant] url/token configured");
+        }
+        Ok(self.url.trim_end_matches('/'))
+    }
+
+    /// Time between sensor checks (at least 5 seconds)
+    pub fn interval(&self) -> std::time::Duration {
+        std::time::Duration::from_secs(self.interval_secs.max(5))
+    }
+}
+
+/// Virtual MIDI port the deck sends its presses and knob turns to
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MidiConfig {
+    pub enabled: bool,
+    /// Name other apps see the port as
+    pub port_name: String,
+    /// MIDI channel (1-16)
+    pub channel: u8,
+    /// Note for button 0; buttons and then encoder presses count up from it
+    pub base_note: u8,
+    /// Controller number for encoder 0 (the others follow)
+    pub base_cc: u8,
+    pub encoder_mode: MidiEncoderMode,
+}
+
+impl Default for MidiConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port_name: "Claude Deck".to_string(),
+            channel: 1,
+            base_note: 36,
+            base_cc: 20,
+            encoder_mode: MidiEncoderMode::Relative,
+        }
+    }
+}
+
+/// What an encoder's control change carries
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MidiEncoderMode {
+    /// 64 plus the detents turned (65 clockwise, 63 counter-clockwise)
+    #[default]
+    Relative,
+    /// A 0-127 value that each turn moves, starting at 64
+    Absolute,
+}
+
+/// An HTTP endpoint polled for a status monitor
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MonitorConfig {
+    /// Name used by `monitor` buttons and on the strip (e.g., "ci")
+    pub name: String,
+    pub url: String,
+    /// Seconds between checks
+    #[serde(default = "default_monitor_interval")]
+    pub interval_secs: u64,
+    /// Page opened by a monitor button (defaults to `url`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub open_url: Option<String>,
+    /// JSON pointer to the status field (e.g., "/workflow_runs/0/conclusion")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub json_pointer: Option<String>,
+    /// Extra request headers (e.g., Authorization for a private repository)
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub headers: BTreeMap<String, String>,
+}
+
+fn default_monitor_interval() -> u64 {
+    60
+}
+
+impl MonitorConfig {
+    /// Time between checks (at least 5 seconds)
+    pub fn interval(&self) -> std::time::Duration {
+        std::time::Duration::from_secs(self.interval_secs.max(5))
+    }
+}
+
+/// An additional deck, claimed by serial number and bound to one profile
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DeckConfig {
+    /// Name used in logs (e.g., "slack")
+    pub id: String,
+    /// Serial number of the device (see `claude-deck --status`)
+    pub serial: String,
+    /// Profile always shown on this deck, regardless of the focused app
+    pub profile: String,
+}
+
+/// A named deck-wide state applied in one go (e.g., "Deep Work", "Meeting")
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SceneConfig {
+    /// Scene name (used by scene buttons and the API)
+    pub name: String,
+    /// Profile to pin while the scene is active (None = follow focused app)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub profile: Option<String>,
+    /// Device brightness (0-100)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub brightness: Option<u8>,
+    /// LCD strip layout: "default" or "minimal" (task + status only)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub strip_layout: Option<String>,
+    /// Slack status to set (requires [slack] token)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub slack_status: Option<SlackStatus>,
+    /// Hue scene to recall (requires [hue] bridge + username)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hue_scene: Option<HueScene>,
+    /// Local times ("HH:MM") at which to activate the scene automatically
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub schedule: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SlackStatus {
+    /// Status text (empty clears the status)
+    pub text: String,
+    /// Status emoji shortcode (e.g., ":headphones:")
+    pub emoji: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HueScene {
+    /// Hue group (room/zone) ID
+    pub group: String,
+    /// Hue scene ID
+    pub scene: String,
+}
@@ -0,0 +1,51 @@
+//! Picks up edits to `config.toml` made outside the app
+//!
+//! Polls the file's modification time and size once a second rather than
+//! using filesystem events (`notify`). Editors that save by writing a new
+//! file and renaming it over the old one replace the inode a file watch is
+//! on, so an event watch has to cover the whole config directory and filter
+//! and debounce what it reports.
+//! One `stat` a second does the same job with no extra dependency, at the
+//! cost of up to a second's delay.
+
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use tokio::sync::mpsc;
+use tracing::warn;
+
+use super::Config;
+
+/// How often the config file is checked
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Watch the config file at `path` and send each new version that parses
+///
+/// Versions that fail to parse (e.g., a half-finished edit) are logged and
+/// skipped, leaving the running config in place.
+pub async fn watch_config_file(path: PathBuf, tx: mpsc::Sender<Config>) {
+    let mut last_seen = version(&path);
+    loop {
+        tokio::time::sleep(POLL_INTERVAL).await;
+        let current = version(&path);
+        if current.is_none() || current == last_seen {
+            continue;
+        }
+        last_seen = current;
+
+        match Config::load_from(&path) {
+            Ok(config) => {
+                if tx.send(config).await.is_err() {
+                    return;
+                }
+            }
+            Err(e) => warn!("Ignoring config file change: {:#}", e),
+        }
+    }
+}
+
+/// Modification time and size; the size catches a second save within a
+/// coarse mtime tick
+fn version(path: &Path) -> Option<(SystemTime, u64)> {
+    let metadata = std::fs::metadata(path).ok()?;
+    Some((metadata.modified().ok()?, metadata.len()))
+}
@@ -0,0 +1,127 @@
+//! Single writer for `config.toml`
+//!
+//! Saves from the web handlers are queued to one task so concurrent requests
+//! can't interleave writes. The config version used for `ETag`/`If-Match`
+//! checks is process-wide, so reloads of external edits bump it too.
+
+use anyhow::{anyhow, Result};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use tokio::sync::{mpsc, oneshot, Mutex, MutexGuard};
+
+use super::Config;
+
+/// A config snapshot waiting to be written
+struct WriteRequest {
+    config: Box<Config>,
+    done: oneshot::Sender<Result<(), String>>,
+}
+
+/// Version of the in-memory config, bumped on every change
+static VERSION: AtomicU64 = AtomicU64::new(1);
+
+/// Current config version
+pub fn version() -> u64 {
+    VERSION.load(Ordering::SeqCst)
+}
+
+/// Mark the config as changed (saved, or reloaded from disk)
+pub fn bump_version() -> u64 {
+    VERSION.fetch_add(1, Ordering::SeqCst) + 1
+}
+
+/// Handle to the config writer task
+pub struct ConfigWriter {
+    tx: mpsc::Sender<WriteRequest>,
+    mutations: Mutex<()>,
+}
+
+impl ConfigWriter {
+    /// Start the writer task for the config file at `path`
+    pub fn spawn(path: PathBuf) -> Self {
+        let (tx, rx) = mpsc::channel(32);
+        tokio::spawn(run_writer(path, rx));
+        Self {
+            tx,
+            mutations: Mutex::new(()),
+        }
+    }
+
+    /// Hold while checking a version and applying the change it guards
+    pub async fn lock(&self) -> MutexGuard<'_, ()> {
+        self.mutations.lock().await
+    }
+
+    /// Queue `config` for writing and wait until it is on disk
+    pub async fn save(&self, config: Config) -> Result<()> {
+        bump_version();
+        let (done, result) = oneshot::channel();
+        let request = WriteRequest {
+            config: Box::new(config),
+            done,
+        };
+        self.tx
+            .send(request)
+            .await
+            .map_err(|_| anyhow!("Config writer has stopped"))?;
+        result
+            .await
+            .map_err(|_| anyhow!("Config writer has stopped"))?
+            .map_err(|e| anyhow!(e))
+    }
+}
+
+async fn run_writer(path: PathBuf, mut rx: mpsc::Receiver<WriteRequest>) {
+    while let Some(mut request) = rx.recv().await {
+        // Only the newest snapshot matters when several saves are queued
+        let mut waiters = Vec::new();
+        while let Ok(next) = rx.try_recv() {
+            waiters.push(std::mem::replace(&mut request, next).done);
+        }
+        waiters.push(request.done);
+
+        let result = write(&path, *request.config).await;
+        for waiter in waiters {
+            let _ = waiter.send(result.clone());
+        }
+    }
+}
+
+async fn write(path: &Path, config: Config) -> Result<(), String> {
+    let path = path.to_path_buf();
+    tokio::task::spawn_blocking(move || config.save_to(&path))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| format!("{:#}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_saves_are_serialized_and_versioned() {
+        let dir = std::env::temp_dir().join(format!("claude-deck-writer-{}", std::process::id()));
+        let path = dir.join("config.toml");
+        let writer = std::sync::Arc::new(ConfigWriter::spawn(path.clone()));
+        let before = version();
+
+        let saves: Vec<_> = (0..8u16)
+            .map(|port| {
+                let writer = writer.clone();
+                let mut config = Config::default();
+                config.web.port = 9000 + port;
+                tokio::spawn(async move { writer.save(config).await })
+            })
+            .collect();
+        for save in saves {
+            save.await.unwrap().unwrap();
+        }
+        assert!(version() >= before + 8);
+
+        let written: Config = toml::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert!((9000..9008).contains(&written.web.port));
+        assert!(!path.with_extension("toml.tmp").exists());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,314 @@
+//! Control socket: one running instance, driven by the CLI
+//!
+//! A running deck binds `~/.claude-deck/control.sock` (next to the hook
+//! socket). A second instance finds it answering and exits instead of
+//! fighting over the HID device, and CLI flags like `--brightness`,
+//! `--status`, `--press` and `--reload` are sent to the running instance
+//! over it. Each connection carries one JSON request line and gets one JSON
+//! response line back.
+//!
+//! Running as root (daemon mode), the socket has to be connectable by the
+//! console user, so it's world-writable and each connection's peer
+//! credentials are checked instead: only root, our own user and the user at
+//! the console are answered.
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock as StdRwLock};
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{UnixListener, UnixStream};
+use tokio::sync::{mpsc, oneshot, RwLock as TokioRwLock};
+use tracing::{debug, info, warn};
+
+use crate::config::Config;
+use crate::device::DISPLAY_BUTTON_COUNT;
+use crate::hooks::status_file_path;
+use crate::profiles::ProfileManager;
+use crate::state::AppState;
+use crate::web::{self, ConfigChangeEvent, DeviceReport};
+use crate::AppCommand;
+
+/// How long the CLI waits for the running instance to answer
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Control socket location (next to the hook socket)
+pub fn socket_path() -> PathBuf {
+    status_file_path().with_file_name("control.sock")
+}
+
+/// What the CLI asks the running instance to do
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "command", rename_all = "snake_case")]
+pub enum Request {
+    /// Describe the connected deck
+    Status,
+    Brightness { percent: u8 },
+    Press { button: u8, long_press: bool },
+    /// Re-read config.toml
+    Reload,
+}
+
+/// The running instance's answer
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Response {
+    pub ok: bool,
+    pub message: String,
+    /// For `status`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub device: Option<DeviceReport>,
+}
+
+impl Response {
+    fn ok(message: impl Into<String>) -> Self {
+        Self {
+            ok: true,
+            message: message.into(),
+            device: None,
+        }
+    }
+
+    fn error(message: impl Into<String>) -> Self {
+        Self {
+            ok: false,
+            message: message.into(),
+            device: None,
+        }
+    }
+}
+
+/// What requests act on
+pub struct Control {
+    pub config: Arc<TokioRwLock<Config>>,
+    pub profile_manager: Arc<StdRwLock<ProfileManager>>,
+    pub change_tx: mpsc::Sender<ConfigChangeEvent>,
+    pub device_state: Arc<TokioRwLock<AppState>>,
+    pub commands: mpsc::Sender<AppCommand>,
+}
+
+/// Send a request to the running instance
+///
+/// Returns None when no instance is running (no socket, or nothing listening).
+pub async fn send(request: &Request) -> Result<Option<Response>> {
+    send_to(&socket_path(), request).await
+}
+
+async fn send_to(path: &Path, request: &Request) -> Result<Option<Response>> {
+    let mut stream = match UnixStream::connect(path).await {
+        Ok(stream) => stream,
+        Err(e)
+            if matches!(
+                e.kind(),
+                std::io::ErrorKind::NotFound | std::io::ErrorKind::ConnectionRefused
+            ) =>
+        {
+            return Ok(None)
+        }
+        Err(e) => return Err(e).with_context(|| format!("Failed to connect to {:?}", path)),
+    };
+    let exchange = async {
+        let mut line = serde_json::to_string(request)?;
+        line.push('\n');
+        stream.write_all(line.as_bytes()).await?;
+        let mut reply = String::new();
+        BufReader::new(stream).read_line(&mut reply).await?;
+        anyhow::Ok(serde_json::from_str::<Response>(&reply)?)
+    };
+    match tokio::time::timeout(TIMEOUT, exchange).await {
+        Ok(response) => response.map(Some).context("Invalid reply from the running instance"),
+        Err(_) => bail!("The running instance didn't answer within {:?}", TIMEOUT),
+    }
+}
+
+/// Claim the control socket, failing if another instance holds it
+///
+/// A socket file nobody is listening on is left over from a crash and is
+/// replaced.
+pub async fn bind() -> Result<UnixListener> {
+    let path = socket_path();
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create {:?}", parent))?;
+    }
+    if path.exists() {
+        if UnixStream::connect(&path).await.is_ok() {
+            bail!("claude-deck is already running (control socket {:?})", path);
+        }
+        std::fs::remove_file(&path).with_context(|| format!("Failed to remove stale {:?}", path))?;
+    }
+
+    let listener = UnixListener::bind(&path)
+        .with_context(|| format!("Failed to bind control socket {:?}", path))?;
+    // In daemon mode we run as root but the CLI runs as the console user;
+    // `peer_allowed` keeps other users out
+    let mode = if crate::system::is_root() { 0o666 } else { 0o600 };
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(mode))
+        .with_context(|| format!("Failed to set permissions on {:?}", path))?;
+    info!("Listening for CLI commands on {:?}", path);
+    Ok(listener)
+}
+
+/// Answer requests on a bound control socket
+pub fn serve(listener: UnixListener, control: Control) {
+    let control = Arc::new(control);
+    tokio::spawn(async move {
+        loop {
+            match listener.accept().await {
+                Ok((stream, _)) => {
+                    tokio::spawn(handle_connection(stream, Arc::clone(&control)));
+                }
+                Err(e) => {
+                    warn!("Control socket accept failed: {}", e);
+                    tokio::time::sleep(Duration::from_millis(500)).await;
+                }
+            }
+        }
+    });
+}
+
+/// Whether the user `peer` may use the control socket
+///
+/// Root and our own user always may; the console user may when we're a daemon
+/// running as root.
+fn peer_allowed(peer: u32, own: u32, console: Option<u32>) -> bool {
+    peer == 0 || peer == own || (own == 0 && console == Some(peer))
+}
+
+async fn handle_connection(stream: UnixStream, control: Arc<Control>) {
+    let peer = stream.peer_cred().map(|cred| cred.uid());
+    // SAFETY: geteuid has no preconditions and cannot fail
+    let own = unsafe { libc::geteuid() };
+    let allowed = peer
+        .as_ref()
+        .is_ok_and(|&uid| peer_allowed(uid, own, crate::system::console_uid()));
+
+    let (reader, mut writer) = stream.into_split();
+    let mut line = String::new();
+    if let Err(e) = BufReader::new(reader).read_line(&mut line).await {
+        debug!("Control socket read failed: {}", e);
+        return;
+    }
+    let response = match serde_json::from_str::<Request>(&line) {
+        Ok(_) if !allowed => {
+            warn!("Refused a control command from uid {:?}", peer.ok());
+            Response::error("Permission denied")
+        }
+        Ok(request) => {
+            info!("CLI command: {:?}", request);
+            handle(request, &control).await
+        }
+        Err(e) => Response::error(format!("Invalid request: {}", e)),
+    };
+    let Ok(mut reply) = serde_json::to_string(&response) else {
+        return;
+    };
+    reply.push('\n');
+    if let Err(e) = writer.write_all(reply.as_bytes()).await {
+        debug!("Control socket write failed: {}", e);
+    }
+}
+
+async fn handle(request: Request, control: &Control) -> Response {
+    match request {
+        Request::Status => {
+            let (reply, report) = oneshot::channel();
+            if control.commands.send(AppCommand::DeviceInfo(reply)).await.is_err() {
+                return Response::error("Deck is not running");
+            }
+            match report.await {
+                Ok(report) => Response {
+                    device: Some(report),
+                    ..Response::ok("Running")
+                },
+                Err(_) => Response::error("Deck is not running"),
+            }
+        }
+        Request::Brightness { percent } if percent > 100 => {
+            Response::error("Brightness must be 0-100")
+        }
+        Request::Brightness { percent } => {
+            send_command(control, AppCommand::SetBrightness(percent), "Brightness set").await
+        }
+        Request::Press { button, .. } if button >= DISPLAY_BUTTON_COUNT => {
+            Response::error(format!("Button must be 0-{}", DISPLAY_BUTTON_COUNT - 1))
+        }
+        Request::Press { button, long_press } => {
+            let command = AppCommand::Press { button, long_press };
+            send_command(control, command, "Pressed").await
+        }
+        Request::Reload => match Config::load() {
+            Ok(new_config) => {
+                web::server::apply_config(&control.config, &control.profile_manager, new_config)
+                    .await;
+                if let Err(e) = control.change_tx.send(ConfigChangeEvent::Reload).await {
+                    warn!("Failed to send config change event: {}", e);
+                }
+                Response::ok("Config reloaded")
+            }
+            Err(e) => Response::error(format!("Failed to reload config: {:#}", e)),
+        },
+    }
+}
+
+/// Queue a command for the main loop, as the REST API does (refused while locked)
+async fn send_command(control: &Control, command: AppCommand, done: &str) -> Response {
+    let locked = {
+        let state = control.device_state.read().await;
+        state.screen_locked || state.deck_lock.is_locked()
+    };
+    if locked {
+        return Response::error("Deck is locked");
+    }
+    match control.commands.send(command).await {
+        Ok(()) => Response::ok(done),
+        Err(_) => Response::error("Deck is not running"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_send() {
+        let dir = std::env::temp_dir().join(format!("claude-deck-control-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("control.sock");
+        let _ = std::fs::remove_file(&path);
+        assert!(send_to(&path, &Request::Reload).await.unwrap().is_none());
+
+        let listener = UnixListener::bind(&path).unwrap();
+        tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let (reader, mut writer) = stream.into_split();
+            let mut line = String::new();
+            BufReader::new(reader).read_line(&mut line).await.unwrap();
+            let request: Request = serde_json::from_str(&line).unwrap();
+            assert_eq!(request, Request::Brightness { percent: 40 });
+            let reply = serde_json::to_string(&Response::ok("Brightness set")).unwrap();
+            writer.write_all(format!("{}\n", reply).as_bytes()).await.unwrap();
+        });
+        let response = send_to(&path, &Request::Brightness { percent: 40 }).await.unwrap().unwrap();
+        assert!(response.ok);
+        assert_eq!(response.message, "Brightness set");
+
+        let json = serde_json::to_string(&Request::Press { button: 3, long_press: false }).unwrap();
+        assert_eq!(json, r#"{"command":"press","button":3,"long_press":false}"#);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_peer_allowed() {
+        // A user instance answers only its own user (and root)
+        assert!(peer_allowed(501, 501, Some(502)));
+        assert!(peer_allowed(0, 501, None));
+        assert!(!peer_allowed(502, 501, Some(502)));
+
+        // A root daemon also answers the console user, but no one else
+        assert!(peer_allowed(501, 0, Some(501)));
+        assert!(!peer_allowed(502, 0, Some(501)));
+        assert!(!peer_allowed(502, 0, None));
+    }
+}
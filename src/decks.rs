@@ -0,0 +1,306 @@
+//! Additional decks bound to a fixed profile
+//!
+//! The first device found follows the focused app as before. Each `[[decks]]`
+//! entry claims another device by serial number and always shows its bound
+//! profile, with its own renderer, input handler and reconnect timer. Claude
+//! status, toggles and scenes are shared with the main deck.
+
+use anyhow::Result;
+use std::sync::{Arc, RwLock as StdRwLock};
+use std::time::{Duration, Instant};
+use tokio::sync::{mpsc, RwLock as TokioRwLock};
+use tracing::{info, warn};
+
+use crate::config::{Config, DeckConfig};
+use crate::device::{button_to_display_key, DeviceManager, FrameBuffer, InputEvent};
+use crate::display::DisplayRenderer;
+use crate::input::{InputHandler, KeystrokeSender};
+use crate::profiles::ProfileManager;
+use crate::state::AppState;
+use crate::AppCommand;
+
+const RECONNECT_INTERVAL: Duration = Duration::from_secs(5);
+const KEEPALIVE_INTERVAL: Duration = Duration::from_secs(10);
+
+/// An extra device showing one profile
+pub struct Deck {
+    config: DeckConfig,
+    device: Option<DeviceManager>,
+    display: DisplayRenderer,
+    input: InputHandler,
+    /// This deck's copy of the shared profiles, pinned to its profile
+    profiles: Arc<StdRwLock<ProfileManager>>,
+    /// Display mirror (not shown in the web UI, which mirrors the main deck)
+    frames: Arc<FrameBuffer>,
+    last_connect_attempt: Option<Instant>,
+    last_keepalive: Instant,
+}
+
+impl Deck {
+    pub fn new(
+        deck: DeckConfig,
+        config: &Config,
+        shared: &ProfileManager,
+        state: Arc<TokioRwLock<AppState>>,
+        keystrokes: KeystrokeSender,
+        commands: mpsc::Sender<AppCommand>,
+    ) -> Result<Self> {
+        let profiles = Arc::new(StdRwLock::new(pinned_view(shared, &deck.profile)));
+        // GIF animation state is keyed by button, so only the main deck animates
+        let display = DisplayRenderer::new(config, Arc::clone(&profiles))?.without_gif_animation();
+        let input = InputHandler::new(state, Arc::clone(&profiles), keystrokes, commands);
+
+        Ok(Self {
+            config: deck,
+            device: None,
+            display,
+            input,
+            profiles,
+            frames: Arc::new(FrameBuffer::new()),
+            last_connect_attempt: None,
+            last_keepalive: Instant::now(),
+        })
+    }
+
+    /// Name from the config
+    pub fn id(&self) -> &str {
+        &self.config.id
+    }
+
+    /// Serial number of the device this deck claims
+    pub fn serial(&self) -> &str {
+        &self.config.serial
+    }
+
+    /// Pick up profile edits made to the shared profiles
+    pub fn sync_profiles(&self, shared: &ProfileManager) {
+        let view = pinned_view(shared, &self.config.profile);
+        *self.profiles.write().unwrap() = view;
+    }
+
+    /// Try to connect if disconnected, at most every few seconds
+    ///
+    /// Returns true when the device was (re)connected and needs a full redraw.
+    pub async fn ensure_connected(&mut self, brightness: u8) -> bool {
+        if self.device.is_some()
+            || self
+                .last_connect_attempt
+                .is_some_and(|at| at.elapsed() < RECONNECT_INTERVAL)
+        {
+            return false;
+        }
+        self.last_connect_attempt = Some(Instant::now());
+
+        let serial = self.config.serial.clone();
+        let frames = Arc::clone(&self.frames);
+        let Ok(device) = DeviceManager::connect_matching(|s| s == serial, frames).await else {
+            return false;
+        };
+        info!("Connected deck '{}' ({})", self.config.id, self.config.serial);
+
+        device.reset().await.ok();
+        if let Err(e) = device.set_brightness(brightness).await {
+            warn!("Set brightness failed on deck '{}': {}", self.config.id, e);
+        }
+        self.display.invalidate();
+        self.last_keepalive = Instant::now();
+        self.device = Some(device);
+        true
+    }
+
+    /// Send a keep-alive if one is due
+    pub async fn keep_alive(&mut self) {
+        let Some(ref device) = self.device else {
+            return;
+        };
+        if self.last_keepalive.elapsed() >= KEEPALIVE_INTERVAL {
+            if let Err(e) = device.keep_alive().await {
+                warn!("Keep-alive failed on deck '{}': {}", self.config.id, e);
+            }
+            self.last_keepalive = Instant::now();
+        }
+    }
+
+    /// Wait for the next input event (never resolves while disconnected)
+    ///
+    /// Cancel-safe, so the main loop can wait on it alongside everything else.
+    /// An error means the device is gone; pass it to `disconnected`.
+    pub async fn next_event(&mut self) -> Result<InputEvent> {
+        match self.device.as_mut() {
+            Some(device) => device.next_event().await,
+            None => std::future::pending().await,
+        }
+    }
+
+    /// Drop the device after a failed read; `ensure_connected` brings it back
+    pub fn disconnected(&mut self) {
+        warn!("Deck '{}' disconnected, will try to reconnect...", self.config.id);
+        self.device = None;
+    }
+
+    /// Whether a button is held, so long-press and repeat timing need the fast tick
+    pub fn is_holding(&self) -> bool {
+        self.input.is_holding()
+    }
+
+    /// Run the bound profile's action for an input event
+    pub async fn handle_event(&mut self, event: InputEvent) {
+        if let Err(e) = self.input.handle_event(event).await {
+            warn!("Failed to handle input on deck '{}': {}", self.config.id, e);
+        }
+    }
+
+    /// Fire any long-press action that is due, returning true if one fired
+    pub async fn check_long_press(&mut self) -> bool {
+        match self.input.check_long_press().await {
+            Ok(fired) => fired,
+            Err(e) => {
+                warn!("Failed to check long-press on deck '{}': {}", self.config.id, e);
+                false
+            }
+        }
+    }
+
+    /// Set device brightness (0-100)
+    pub async fn set_brightness(&self, level: u8) {
+        if let Some(ref device) = self.device {
+            device.set_brightness(level).await.ok();
+        }
+    }
+
+    /// Send the strip if it changed
+    pub async fn update_strip(&self, state: &AppState) -> Result<()> {
+        let Some(ref device) = self.device else {
+            return Ok(());
+        };
+        if !device.model().has_strip() {
+            return Ok(());
+        }
+
+        let strip = self.display.render_strip(state)?;
+        if self.display.strip_changed(&strip) {
+            if let Err(e) = device.set_strip_image(strip).await {
+                self.display.invalidate();
+                return Err(e);
+            }
+            device.flush().await?;
+        }
+        Ok(())
+    }
+
+    /// Send every button that changed and the strip, in one batch
+    pub async fn redraw(&self, state: &AppState) -> Result<()> {
+        let Some(ref device) = self.device else {
+            return Ok(());
+        };
+
+        let mut buttons = Vec::new();
+        for button_id in 0..10u8 {
+            let image = self.display.render_button(button_id, false, state)?;
+            if self.display.button_changed(button_id, &image) {
+                buttons.push((button_to_display_key(button_id), image));
+            }
+        }
+        let strip = if device.model().has_strip() {
+            let strip = self.display.render_strip(state)?;
+            self.display.strip_changed(&strip).then_some(strip)
+        } else {
+            None
+        };
+
+        if buttons.is_empty() && strip.is_none() {
+            return Ok(());
+        }
+        if let Err(e) = device.set_images_batch(buttons, strip).await {
+            self.display.invalidate();
+            return Err(e);
+        }
+        Ok(())
+    }
+
+    /// Release the device
+    pub async fn disconnect(&mut self) {
+        if let Some(device) = self.device.take() {
+            device.disconnect().await;
+        }
+    }
+}
+
+/// Copy of the shared profiles with `profile` pinned
+/// Next input from any of `decks`, with its index (never resolves without one)
+pub async fn next_input(decks: &mut [Deck]) -> (usize, Result<InputEvent>) {
+    if decks.is_empty() {
+        return std::future::pending().await;
+    }
+    let waits = decks
+        .iter_mut()
+        .enumerate()
+        .map(|(index, deck)| Box::pin(async move { (index, deck.next_event().await) }));
+    futures_util::future::select_all(waits).await.0
+}
+
+fn pinned_view(shared: &ProfileManager, profile: &str) -> ProfileManager {
+    let mut view = shared.clone();
+    view.pin_profile(Some(profile.to_string()));
+    view
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::KeystrokeQueue;
+    use crate::config::KeystrokeConfig;
+    use crate::device::VirtualDevice;
+    use crate::profiles::{generate_default_profiles, FocusedWindow};
+
+    fn deck(shared: &ProfileManager) -> Deck {
+        let queue = Arc::new(KeystrokeQueue::new());
+        let keystrokes = KeystrokeSender::remote(KeystrokeConfig::default(), queue);
+        let state = Arc::new(TokioRwLock::new(AppState::new()));
+        let (commands, _) = mpsc::channel(8);
+        let config = DeckConfig {
+            profile: shared.get_profiles()[0].name.clone(),
+            ..Default::default()
+        };
+        Deck::new(config, &Config::default(), shared, state, keystrokes, commands).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_next_input_waits_for_a_deck() {
+        let shared = ProfileManager::new(generate_default_profiles());
+        let mut decks = vec![deck(&shared), deck(&shared)];
+        let wait = Duration::from_millis(20);
+
+        // Nothing connected: no events and no busy loop
+        assert!(tokio::time::timeout(wait, next_input(&mut [])).await.is_err());
+        assert!(tokio::time::timeout(wait, next_input(&mut decks)).await.is_err());
+
+        let sim = Arc::new(VirtualDevice::new());
+        let frames = Arc::clone(&decks[1].frames);
+        decks[1].device = Some(DeviceManager::simulated(Arc::clone(&sim), frames));
+        assert!(tokio::time::timeout(wait, next_input(&mut decks)).await.is_err());
+
+        sim.push_input(InputEvent::ButtonDown(3)).unwrap();
+        let (index, event) = next_input(&mut decks).await;
+        assert_eq!(index, 1);
+        assert!(matches!(event, Ok(InputEvent::ButtonDown(3))));
+    }
+
+    #[test]
+    fn test_pinned_view_ignores_focused_app() {
+        let shared = ProfileManager::new(generate_default_profiles());
+        let terminal = FocusedWindow::app("Terminal");
+        let other = shared
+            .get_profiles()
+            .iter()
+            .map(|p| p.name.clone())
+            .find(|name| Some(name) != shared.profile_name_for_app(&terminal).as_ref())
+            .unwrap();
+
+        let view = pinned_view(&shared, &other);
+        assert_eq!(view.profile_name_for_app(&terminal), Some(other.clone()));
+        assert_eq!(view.profile_name_for_app(&FocusedWindow::app("Slack")), Some(other));
+        // The shared manager keeps following the focused app
+        assert_ne!(shared.profile_name_for_app(&terminal), view.profile_name_for_app(&terminal));
+    }
+}
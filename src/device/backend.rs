@@ -0,0 +1,129 @@
+//! The deck interface the app drives
+//!
+//! `App` is generic over `DeckDevice`, so the same main loop runs on a
+//! `DeviceManager` (hardware, or the `--simulate` virtual device) and on a
+//! `MockDevice` in the integration tests.
+
+use anyhow::Result;
+use image::RgbImage;
+use std::future::Future;
+use std::sync::Arc;
+
+use super::frames::FrameBuffer;
+use super::manager::{DeviceManager, InputEvent};
+use super::models::DeviceModel;
+
+/// A deck the app draws on and reads input from
+///
+/// Button images are addressed by AKP05E display key, as in `DeviceManager`.
+/// The futures aren't required to be `Send`: they're only awaited on the
+/// app's own task.
+#[allow(async_fn_in_trait)]
+pub trait DeckDevice: Sized + Send + 'static {
+    /// Connect to a deck whose serial isn't in `claimed`, for reconnects
+    fn connect(
+        claimed: Vec<String>,
+        frames: Arc<FrameBuffer>,
+    ) -> impl Future<Output = Result<Self>> + Send;
+
+    fn model(&self) -> &'static DeviceModel;
+
+    /// Serial number (None if it has none, e.g. when simulated)
+    fn serial(&self) -> Option<&str>;
+
+    /// Whether there's no hardware behind it
+    fn is_simulated(&self) -> bool;
+
+    async fn set_button_image(&self, button: u8, image: RgbImage) -> Result<()>;
+
+    /// A GIF frame for a button (may be sent at lower quality)
+    async fn set_button_frame(&self, button: u8, image: RgbImage) -> Result<()>;
+
+    async fn set_strip_image(&self, image: RgbImage) -> Result<()>;
+
+    /// Send several button images and the strip, flushing once at the end
+    async fn set_images_batch(
+        &self,
+        buttons: Vec<(u8, RgbImage)>,
+        strip: Option<RgbImage>,
+    ) -> Result<()>;
+
+    /// Show the images sent since the last flush
+    async fn flush(&self) -> Result<()>;
+
+    /// Clear the display
+    async fn reset(&self) -> Result<()>;
+
+    async fn keep_alive(&self) -> Result<()>;
+
+    async fn set_brightness(&self, percent: u8) -> Result<()>;
+
+    /// Wait for the next input event (an error means the deck is gone)
+    async fn next_event(&mut self) -> Result<InputEvent>;
+
+    /// Release the deck on shutdown
+    async fn disconnect(self) {}
+}
+
+impl DeckDevice for DeviceManager {
+    async fn connect(claimed: Vec<String>, frames: Arc<FrameBuffer>) -> Result<Self> {
+        let unclaimed = |serial: &str| !claimed.iter().any(|c| c == serial);
+        DeviceManager::connect_matching(unclaimed, frames).await
+    }
+
+    fn model(&self) -> &'static DeviceModel {
+        DeviceManager::model(self)
+    }
+
+    fn serial(&self) -> Option<&str> {
+        DeviceManager::serial(self)
+    }
+
+    fn is_simulated(&self) -> bool {
+        DeviceManager::is_simulated(self)
+    }
+
+    async fn set_button_image(&self, button: u8, image: RgbImage) -> Result<()> {
+        DeviceManager::set_button_image(self, button, image).await
+    }
+
+    async fn set_button_frame(&self, button: u8, image: RgbImage) -> Result<()> {
+        DeviceManager::set_button_frame(self, button, image).await
+    }
+
+    async fn set_strip_image(&self, image: RgbImage) -> Result<()> {
+        DeviceManager::set_strip_image(self, image).await
+    }
+
+    async fn set_images_batch(
+        &self,
+        buttons: Vec<(u8, RgbImage)>,
+        strip: Option<RgbImage>,
+    ) -> Result<()> {
+        DeviceManager::set_images_batch(self, buttons, strip).await
+    }
+
+    async fn flush(&self) -> Result<()> {
+        DeviceManager::flush(self).await
+    }
+
+    async fn reset(&self) -> Result<()> {
+        DeviceManager::reset(self).await
+    }
+
+    async fn keep_alive(&self) -> Result<()> {
+        DeviceManager::keep_alive(self).await
+    }
+
+    async fn set_brightness(&self, percent: u8) -> Result<()> {
+        DeviceManager::set_brightness(self, percent).await
+    }
+
+    async fn next_event(&mut self) -> Result<InputEvent> {
+        DeviceManager::next_event(self).await
+    }
+
+    async fn disconnect(self) {
+        DeviceManager::disconnect(self).await
+    }
+}
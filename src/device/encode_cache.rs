@@ -0,0 +1,186 @@
+//! Pre-encoded display images, keyed by a hash of the rendered pixels
+//!
+//! Redraws often resend images the device already shows (unchanged buttons
+//! during a full redraw), or flip between a few images (pressed/released,
+//! toggles). The cache skips the write entirely in the first case and the
+//! JPEG encode in the second.
+//!
+//! GIF frames are encoded at a lower quality (`[device] animation_quality`)
+//! than everything else: they are on screen for a few milliseconds, and the
+//! smaller payload keeps fast animation ticks clear of the device cooldown.
+
+use anyhow::Result;
+use image::codecs::{bmp::BmpEncoder, jpeg::JpegEncoder};
+use image::{imageops::FilterType, DynamicImage, ExtendedColorType, RgbImage};
+use mirajazz::types::{ImageFormat, ImageMirroring, ImageMode, ImageRotation};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::Arc;
+
+/// Encoded images kept before old ones are dropped
+const MAX_ENCODED: usize = 64;
+
+/// JPEG quality for static renders (the same as mirajazz uses)
+pub const STATIC_QUALITY: u8 = 90;
+
+static FRAME_QUALITY: AtomicU8 = AtomicU8::new(70);
+
+/// Set the JPEG quality of animation frames (1-100)
+pub fn set_frame_quality(quality: u8) {
+    FRAME_QUALITY.store(quality.clamp(1, 100), Ordering::Relaxed);
+}
+
+/// JPEG quality of animation frames
+pub fn frame_quality() -> u8 {
+    FRAME_QUALITY.load(Ordering::Relaxed)
+}
+
+/// A place on the device an image is written to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Slot {
+    Button(u8),
+    StripButton(u8),
+    Strip,
+}
+
+/// Content hash of an image as it will be encoded
+pub fn image_hash(image: &RgbImage, format: ImageFormat, quality: u8) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    image.dimensions().hash(&mut hasher);
+    image.as_raw().hash(&mut hasher);
+    format.hash(&mut hasher);
+    quality.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Encode an image for the device, producing the same bytes as
+/// `mirajazz::images::convert_image_with_format` at `STATIC_QUALITY`
+///
+/// That one is async and blocks the calling tokio worker; this can run on
+/// any thread, so batches can be encoded in parallel.
+pub fn encode(format: ImageFormat, image: DynamicImage, quality: u8) -> Result<Vec<u8>> {
+    let (width, height) = (format.size.0 as u32, format.size.1 as u32);
+    let image = image.resize_exact(width, height, FilterType::Nearest);
+    let image = match format.rotation {
+        ImageRotation::Rot0 => image,
+        ImageRotation::Rot90 => image.rotate90(),
+        ImageRotation::Rot180 => image.rotate180(),
+        ImageRotation::Rot270 => image.rotate270(),
+    };
+    let image = match format.mirror {
+        ImageMirroring::None => image,
+        ImageMirroring::X => image.fliph(),
+        ImageMirroring::Y => image.flipv(),
+        ImageMirroring::Both => image.fliph().flipv(),
+    };
+    let pixels = image.into_rgb8().into_raw();
+
+    let mut buf = Vec::new();
+    match format.mode {
+        ImageMode::None => {}
+        ImageMode::BMP => {
+            BmpEncoder::new(&mut buf).encode(&pixels, width, height, ExtendedColorType::Rgb8)?
+        }
+        ImageMode::JPEG => JpegEncoder::new_with_quality(&mut buf, quality).encode(
+            &pixels,
+            width,
+            height,
+            ExtendedColorType::Rgb8,
+        )?,
+    }
+    Ok(buf)
+}
+
+#[derive(Debug, Default)]
+pub struct EncodeCache {
+    encoded: HashMap<u64, Arc<Vec<u8>>>,
+    /// Hash of the image each slot last had written
+    shown: HashMap<Slot, u64>,
+}
+
+impl EncodeCache {
+    /// Whether `slot` already shows the image with this hash
+    pub fn is_shown(&self, slot: Slot, hash: u64) -> bool {
+        self.shown.get(&slot) == Some(&hash)
+    }
+
+    pub fn get(&self, hash: u64) -> Option<Arc<Vec<u8>>> {
+        self.encoded.get(&hash).cloned()
+    }
+
+    /// Keep an encoded image, dropping ones no slot shows once full
+    pub fn insert(&mut self, hash: u64, data: Arc<Vec<u8>>) {
+        if self.encoded.len() >= MAX_ENCODED {
+            let shown: Vec<u64> = self.shown.values().copied().collect();
+            self.encoded.retain(|hash, _| shown.contains(hash));
+        }
+        self.encoded.insert(hash, data);
+    }
+
+    pub fn mark_shown(&mut self, slot: Slot, hash: u64) {
+        self.shown.insert(slot, hash);
+    }
+
+    /// Forget what the device shows (after a reset or failed flush)
+    pub fn clear_shown(&mut self) {
+        self.shown.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_skips_shown_and_keeps_shown_when_full() {
+        let format = ImageFormat {
+            mode: ImageMode::JPEG,
+            size: (112, 112),
+            rotation: ImageRotation::Rot0,
+            mirror: ImageMirroring::None,
+        };
+        let black = RgbImage::new(112, 112);
+        let red = RgbImage::from_pixel(112, 112, image::Rgb([255, 0, 0]));
+        let hash = |image: &RgbImage| image_hash(image, format, STATIC_QUALITY);
+        let (black_hash, red_hash) = (hash(&black), hash(&red));
+        assert_ne!(black_hash, red_hash);
+        assert_eq!(black_hash, hash(&black.clone()));
+        assert_ne!(black_hash, image_hash(&black, format, 50));
+
+        let mut cache = EncodeCache::default();
+        cache.insert(red_hash, Arc::new(vec![1]));
+        cache.mark_shown(Slot::Button(0), red_hash);
+        assert!(cache.is_shown(Slot::Button(0), red_hash));
+        assert!(!cache.is_shown(Slot::Button(1), red_hash));
+
+        for hash in 0..MAX_ENCODED as u64 {
+            cache.insert(hash, Arc::new(vec![0]));
+        }
+        assert!(cache.get(red_hash).is_some());
+        assert!(cache.encoded.len() <= MAX_ENCODED);
+
+        cache.clear_shown();
+        assert!(!cache.is_shown(Slot::Button(0), red_hash));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_encode_matches_mirajazz() {
+        let format = ImageFormat {
+            mode: ImageMode::JPEG,
+            size: (60, 60),
+            rotation: ImageRotation::Rot90,
+            mirror: ImageMirroring::None,
+        };
+        let image = DynamicImage::ImageRgb8(RgbImage::from_fn(64, 64, |x, y| {
+            image::Rgb([x as u8 * 4, y as u8 * 4, 128])
+        }));
+        let expected = mirajazz::images::convert_image_with_format(format, image.clone())
+            .await
+            .unwrap();
+        assert_eq!(encode(format, image.clone(), STATIC_QUALITY).unwrap(), expected);
+        // Animation frames trade detail for a smaller payload
+        assert!(encode(format, image, 40).unwrap().len() < expected.len());
+    }
+}
@@ -0,0 +1,221 @@
+//! Copy of what the deck is currently showing
+//!
+//! The device manager mirrors every image it sends here, so the web UI can
+//! show the simulator, take snapshots, etc. without asking the device.
+
+use anyhow::{anyhow, Result};
+use image::{DynamicImage, ImageFormat, RgbImage};
+use std::collections::HashMap;
+use std::io::Cursor;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+use super::protocol::{BUTTON_HEIGHT, BUTTON_WIDTH, STRIP_HEIGHT, STRIP_WIDTH};
+
+/// Number of square LCD buttons on the deck
+pub const DISPLAY_BUTTON_COUNT: u8 = 10;
+
+/// Where an image is shown on the deck
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Target {
+    Button(u8),
+    Strip,
+}
+
+/// Saved deck look to put back on screen (see `FrameBuffer::request_restore`)
+#[derive(Debug, Clone, Default)]
+pub struct SavedFrames {
+    /// Logical button images
+    pub buttons: Vec<(u8, RgbImage)>,
+    pub strip: Option<RgbImage>,
+    pub brightness: Option<u8>,
+}
+
+#[derive(Default)]
+struct Screens {
+    /// Images written since the last flush
+    pending: Vec<(Target, RgbImage)>,
+    /// Images currently on screen
+    shown: HashMap<Target, RgbImage>,
+    brightness: u8,
+    /// Restore requested by the web API, waiting for the app loop
+    restore: Option<SavedFrames>,
+}
+
+/// Frames currently shown on the deck, shared between the app loop and the web server
+#[derive(Default)]
+pub struct FrameBuffer {
+    screens: Mutex<Screens>,
+    /// Bumped on every flush so the web UI knows when to refetch images
+    version: AtomicU64,
+}
+
+/// Convert a device display key back to a logical button (inverse of
+/// `button_to_display_key`)
+fn display_key_to_button(display_key: u8) -> Option<u8> {
+    match display_key {
+        10..=14 => Some(display_key - 10),
+        5..=9 => Some(display_key),
+        _ => None,
+    }
+}
+
+impl FrameBuffer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Queue a button image for the next flush (by display key, like the hardware)
+    pub fn set_button_image(&self, display_key: u8, image: RgbImage) -> Result<()> {
+        let button = display_key_to_button(display_key)
+            .ok_or_else(|| anyhow!("Invalid button index: {}", display_key))?;
+        self.lock_screens().pending.push((Target::Button(button), image));
+        Ok(())
+    }
+
+    /// Queue a full strip image for the next flush
+    pub fn set_strip_image(&self, image: RgbImage) {
+        self.lock_screens().pending.push((Target::Strip, image));
+    }
+
+    /// Show all pending images
+    pub fn flush(&self) {
+        let mut screens = self.lock_screens();
+        let pending = std::mem::take(&mut screens.pending);
+        if pending.is_empty() {
+            return;
+        }
+        screens.shown.extend(pending);
+        drop(screens);
+        self.version.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Clear all screens
+    pub fn reset(&self) {
+        let mut screens = self.lock_screens();
+        screens.pending.clear();
+        screens.shown.clear();
+        drop(screens);
+        self.version.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn set_brightness(&self, percent: u8) {
+        self.lock_screens().brightness = percent.min(100);
+    }
+
+    pub fn brightness(&self) -> u8 {
+        self.lock_screens().brightness
+    }
+
+    /// Display version, incremented whenever the shown images change
+    pub fn version(&self) -> u64 {
+        self.version.load(Ordering::Relaxed)
+    }
+
+    /// PNG of a logical button's current image (blank if nothing shown yet)
+    pub fn button_png(&self, button: u8) -> Result<Vec<u8>> {
+        if button >= DISPLAY_BUTTON_COUNT {
+            return Err(anyhow!("Invalid button: {}", button));
+        }
+        self.png(Target::Button(button), BUTTON_WIDTH, BUTTON_HEIGHT)
+    }
+
+    /// PNG of the LCD strip's current image
+    pub fn strip_png(&self) -> Result<Vec<u8>> {
+        self.png(Target::Strip, STRIP_WIDTH, STRIP_HEIGHT)
+    }
+
+    /// Ask the app loop to show saved frames (applied on its next iteration)
+    pub fn request_restore(&self, saved: SavedFrames) -> Result<()> {
+        for (button, image) in &saved.buttons {
+            if *button >= DISPLAY_BUTTON_COUNT {
+                return Err(anyhow!("Invalid button: {}", button));
+            }
+            check_size(image, BUTTON_WIDTH, BUTTON_HEIGHT)?;
+        }
+        if let Some(ref strip) = saved.strip {
+            check_size(strip, STRIP_WIDTH, STRIP_HEIGHT)?;
+        }
+        self.lock_screens().restore = Some(saved);
+        Ok(())
+    }
+
+    /// Take a pending restore request
+    pub fn take_restore(&self) -> Option<SavedFrames> {
+        self.lock_screens().restore.take()
+    }
+
+    fn png(&self, target: Target, width: u32, height: u32) -> Result<Vec<u8>> {
+        let image = self
+            .lock_screens()
+            .shown
+            .get(&target)
+            .cloned()
+            .unwrap_or_else(|| RgbImage::new(width, height));
+
+        let mut bytes = Vec::new();
+        DynamicImage::ImageRgb8(image).write_to(&mut Cursor::new(&mut bytes), ImageFormat::Png)?;
+        Ok(bytes)
+    }
+
+    fn lock_screens(&self) -> std::sync::MutexGuard<'_, Screens> {
+        self.screens.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+fn check_size(image: &RgbImage, width: u32, height: u32) -> Result<()> {
+    if image.dimensions() != (width, height) {
+        return Err(anyhow!(
+            "Image is {}x{}, expected {}x{}",
+            image.width(),
+            image.height(),
+            width,
+            height
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::device::button_to_display_key;
+
+    #[test]
+    fn test_images_shown_after_flush() {
+        let frames = FrameBuffer::new();
+        let blank = frames.button_png(0).unwrap();
+
+        let image = RgbImage::from_pixel(BUTTON_WIDTH, BUTTON_HEIGHT, image::Rgb([255, 0, 0]));
+        frames.set_button_image(button_to_display_key(0), image).unwrap();
+        assert_eq!(frames.button_png(0).unwrap(), blank);
+        assert_eq!(frames.version(), 0);
+
+        frames.flush();
+        assert_ne!(frames.button_png(0).unwrap(), blank);
+        assert_eq!(frames.version(), 1);
+
+        frames.reset();
+        assert_eq!(frames.button_png(0).unwrap(), blank);
+    }
+
+    #[test]
+    fn test_restore_validates_sizes() {
+        let frames = FrameBuffer::new();
+        let wrong = SavedFrames {
+            buttons: vec![(0, RgbImage::new(10, 10))],
+            ..Default::default()
+        };
+        assert!(frames.request_restore(wrong).is_err());
+        assert!(frames.take_restore().is_none());
+
+        let saved = SavedFrames {
+            strip: Some(RgbImage::new(STRIP_WIDTH, STRIP_HEIGHT)),
+            brightness: Some(40),
+            ..Default::default()
+        };
+        frames.request_restore(saved).unwrap();
+        assert_eq!(frames.take_restore().unwrap().brightness, Some(40));
+        assert!(frames.take_restore().is_none());
+    }
+}
@@ -0,0 +1,187 @@
+//! User overrides for raw HID event codes (`[device.input_map]`)
+//!
+//! Firmware variants sometimes report a key or knob under a different event
+//! code. Each entry maps a code (hex like "0x0b", or decimal) to the input it
+//! should be, and is checked before the model's own decoding:
+//!
+//! ```toml
+//! [device.input_map]
+//! "0x0b" = "button:9"
+//! "0x38" = "encoder_press:3"
+//! "0xa2" = "encoder_ccw:0"
+//! "0xa3" = "encoder_cw:0"
+//! "0x38" = "swipe_left"
+//! "0x39" = "swipe_right"
+//! "0x44" = "ignore"
+//! ```
+
+use anyhow::{anyhow, bail, Context, Result};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::RwLock;
+use tracing::{info, warn};
+
+use super::manager::{InputEvent, SwipeDirection};
+use super::protocol::{ENCODER_COUNT, STRIP_BUTTON_COUNT, STRIP_TOUCH_SLOT};
+
+/// What a remapped event code reports
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InputTarget {
+    /// Logical button (0-9, or 10-13 for the strip's touch zones)
+    Button(u8),
+    EncoderPress(u8),
+    /// Encoder and direction (1 = clockwise, -1 = counter-clockwise)
+    EncoderTwist(u8, i8),
+    /// Strip touch zone (0-3)
+    StripTouch(u8),
+    StripSwipe(SwipeDirection),
+    /// Drop the event
+    Ignore,
+}
+
+impl FromStr for InputTarget {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let s = s.trim();
+        if s.eq_ignore_ascii_case("ignore") {
+            return Ok(Self::Ignore);
+        }
+        if s.eq_ignore_ascii_case("swipe_left") {
+            return Ok(Self::StripSwipe(SwipeDirection::Left));
+        }
+        if s.eq_ignore_ascii_case("swipe_right") {
+            return Ok(Self::StripSwipe(SwipeDirection::Right));
+        }
+        let (kind, index) = s.split_once(':').context("expected <kind>:<index> or \"ignore\"")?;
+        let index: u8 = index.trim().parse().with_context(|| format!("bad index in '{}'", s))?;
+        let (target, limit) = match kind.trim() {
+            "button" => (Self::Button(index), STRIP_TOUCH_SLOT + STRIP_BUTTON_COUNT),
+            "encoder_press" => (Self::EncoderPress(index), ENCODER_COUNT),
+            "encoder_cw" => (Self::EncoderTwist(index, 1), ENCODER_COUNT),
+            "encoder_ccw" => (Self::EncoderTwist(index, -1), ENCODER_COUNT),
+            "strip_touch" => (Self::StripTouch(index), STRIP_BUTTON_COUNT),
+            other => bail!("unknown input '{}'", other),
+        };
+        if index >= limit {
+            bail!("{} is out of range for '{}' (0-{})", index, kind, limit - 1);
+        }
+        Ok(target)
+    }
+}
+
+impl fmt::Display for InputTarget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Button(button) => write!(f, "button:{}", button),
+            Self::EncoderPress(encoder) => write!(f, "encoder_press:{}", encoder),
+            Self::EncoderTwist(encoder, 1) => write!(f, "encoder_cw:{}", encoder),
+            Self::EncoderTwist(encoder, _) => write!(f, "encoder_ccw:{}", encoder),
+            Self::StripTouch(zone) => write!(f, "strip_touch:{}", zone),
+            Self::StripSwipe(SwipeDirection::Left) => write!(f, "swipe_left"),
+            Self::StripSwipe(SwipeDirection::Right) => write!(f, "swipe_right"),
+            Self::Ignore => write!(f, "ignore"),
+        }
+    }
+}
+
+impl InputTarget {
+    /// The input a decoded event came from (releases of knobs have none)
+    pub fn of(event: &InputEvent) -> Option<Self> {
+        match *event {
+            InputEvent::ButtonDown(button) | InputEvent::ButtonUp(button) => {
+                Some(Self::Button(button))
+            }
+            InputEvent::EncoderPress(encoder) => Some(Self::EncoderPress(encoder)),
+            InputEvent::EncoderRotate { encoder, direction } => {
+                Some(Self::EncoderTwist(encoder, direction.signum()))
+            }
+            InputEvent::EncoderRelease(_) => None,
+            InputEvent::StripTouch(zone) => Some(Self::StripTouch(zone)),
+            InputEvent::StripSwipe(direction) => Some(Self::StripSwipe(direction)),
+        }
+    }
+}
+
+/// Raw event code, as hex ("0x0b") or decimal ("11")
+fn parse_code(code: &str) -> Result<u8> {
+    let code = code.trim();
+    match code.strip_prefix("0x").or_else(|| code.strip_prefix("0X")) {
+        Some(hex) => u8::from_str_radix(hex, 16),
+        None => code.parse(),
+    }
+    .map_err(|_| anyhow!("'{}' is not an event code (0x00-0xff)", code))
+}
+
+/// Parse `[device.input_map]`, logging and skipping invalid entries
+pub fn parse_input_map(entries: &BTreeMap<String, String>) -> BTreeMap<u8, InputTarget> {
+    entries
+        .iter()
+        .filter_map(|(code, target)| {
+            match parse_code(code).and_then(|code| Ok((code, target.parse()?))) {
+                Ok(entry) => Some(entry),
+                Err(e) => {
+                    warn!("Ignoring [device.input_map] entry \"{}\": {}", code, e);
+                    None
+                }
+            }
+        })
+        .collect()
+}
+
+static INPUT_MAP: RwLock<BTreeMap<u8, InputTarget>> = RwLock::new(BTreeMap::new());
+
+/// Use these overrides for every connected device
+pub fn set_input_map(entries: &BTreeMap<String, String>) {
+    let map = parse_input_map(entries);
+    if !map.is_empty() {
+        info!("Remapping {} HID event code(s)", map.len());
+    }
+    *INPUT_MAP.write().unwrap_or_else(|e| e.into_inner()) = map;
+}
+
+/// The override for a raw event code, if one is configured
+pub(super) fn remapped(event_type: u8) -> Option<InputTarget> {
+    INPUT_MAP.read().unwrap_or_else(|e| e.into_inner()).get(&event_type).copied()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_input_map() {
+        let entries: BTreeMap<String, String> = [
+            ("0x0b", "button:9"),
+            ("56", "encoder_press:3"),
+            ("0xA2", "encoder_ccw:0"),
+            ("0x44", "ignore"),
+            ("0x39", "swipe_right"),
+            ("0x40", "strip_touch:2"),
+            ("0x41", "strip_touch:4"),
+            ("0x45", "button:15"),
+            ("0x46", "knob:1"),
+            ("0x100", "button:1"),
+        ]
+        .into_iter()
+        .map(|(code, target)| (code.to_string(), target.to_string()))
+        .collect();
+
+        let map = parse_input_map(&entries);
+        assert_eq!(map.len(), 6);
+        assert_eq!(map[&0x0b], InputTarget::Button(9));
+        assert_eq!(map[&0x38], InputTarget::EncoderPress(3));
+        assert_eq!(map[&0xa2], InputTarget::EncoderTwist(0, -1));
+        assert_eq!(map[&0x44], InputTarget::Ignore);
+        assert_eq!(map[&0x39], InputTarget::StripSwipe(SwipeDirection::Right));
+        assert_eq!(map[&0x40], InputTarget::StripTouch(2));
+
+        // Display writes the form `--learn` suggests, which parses back
+        for target in map.values() {
+            assert_eq!(target.to_string().parse::<InputTarget>().unwrap(), *target);
+        }
+        let twist = InputEvent::EncoderRotate { encoder: 2, direction: -1 };
+        assert_eq!(InputTarget::of(&twist).unwrap().to_string(), "encoder_ccw:2");
+    }
+}
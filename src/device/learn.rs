@@ -0,0 +1,54 @@
+//! Raw HID event capture for `claude-deck --learn`
+//!
+//! While a capture is open, every event code the device reports is copied to
+//! it before the model decodes it, so codes the deck doesn't know (and would
+//! otherwise only log) can be found and mapped in `[device.input_map]`.
+
+use std::sync::Mutex;
+use tokio::sync::mpsc;
+
+/// An event code and state as read from the device
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RawEvent {
+    pub event_type: u8,
+    pub state: u8,
+}
+
+static CAPTURE: Mutex<Option<mpsc::UnboundedSender<RawEvent>>> = Mutex::new(None);
+
+/// Start copying raw events to the returned receiver (replacing any earlier capture)
+pub fn capture_raw() -> mpsc::UnboundedReceiver<RawEvent> {
+    let (tx, rx) = mpsc::unbounded_channel();
+    *CAPTURE.lock().unwrap_or_else(|e| e.into_inner()) = Some(tx);
+    rx
+}
+
+/// Copy an event to the capture, if one is open
+pub(super) fn record(event_type: u8, state: u8) {
+    let mut capture = CAPTURE.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(ref tx) = *capture {
+        if tx.send(RawEvent { event_type, state }).is_err() {
+            *capture = None;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_capture_raw() {
+        record(0x01, 1);
+        let mut rx = capture_raw();
+        record(0x0b, 1);
+        record(0x0b, 0);
+        assert_eq!(rx.try_recv().ok(), Some(RawEvent { event_type: 0x0b, state: 1 }));
+        assert_eq!(rx.try_recv().ok(), Some(RawEvent { event_type: 0x0b, state: 0 }));
+        assert!(rx.try_recv().is_err());
+
+        drop(rx);
+        record(0x0c, 1);
+        assert!(CAPTURE.lock().unwrap().is_none());
+    }
+}
@@ -1,13 +1,48 @@
 use anyhow::{anyhow, Result};
-use image::{DynamicImage, RgbImage};
+use image::{imageops, DynamicImage, RgbImage};
 use mirajazz::{
     device::{list_devices, Device},
-    types::{DeviceInput, ImageFormat, ImageMirroring, ImageMode, ImageRotation},
+    error::MirajazzError,
+    types::{DeviceInput, ImageFormat, ImageMirroring, ImageMode},
 };
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
 use tracing::{debug, info, warn};
 
+use super::encode_cache::{encode, frame_quality, image_hash, EncodeCache, Slot, STATIC_QUALITY};
 use super::protocol::*;
+use super::frames::FrameBuffer;
+use super::models::{default_model, find_model, vendor_ids, DeviceModel};
+use super::simulator::VirtualDevice;
+
+/// Which way a finger moved along the touch strip
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SwipeDirection {
+    Left,
+    Right,
+}
+
+/// Audible confirmation of a press (`feedback` on a button)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FeedbackKind {
+    /// One press cue
+    Click,
+    /// Two in quick succession
+    Double,
+}
+
+impl FeedbackKind {
+    pub fn pulses(self) -> u8 {
+        match self {
+            FeedbackKind::Click => 1,
+            FeedbackKind::Double => 2,
+        }
+    }
+}
 
 /// Input events from the device
 #[derive(Debug, Clone)]
@@ -17,6 +52,9 @@ pub enum InputEvent {
     EncoderRotate { encoder: u8, direction: i8 },
     EncoderPress(u8),
     EncoderRelease(u8),
+    /// Touch on a strip zone (0-3, left to right), which has no release
+    StripTouch(u8),
+    StripSwipe(SwipeDirection),
 }
 
 /// Device information
@@ -42,108 +80,274 @@ impl InputState {
     }
 }
 
-/// Manages connection to the AJAZZ AKP05E / Mirabox N4
+/// The strip touch or swipe a decoded button state carries, if any
+fn strip_gesture(states: &[bool]) -> Option<InputEvent> {
+    let slot = (STRIP_TOUCH_SLOT as usize..states.len()).find(|&i| states[i])? as u8;
+    Some(match slot {
+        SWIPE_LEFT_SLOT => InputEvent::StripSwipe(SwipeDirection::Left),
+        SWIPE_RIGHT_SLOT => InputEvent::StripSwipe(SwipeDirection::Right),
+        slot => InputEvent::StripTouch(slot - STRIP_TOUCH_SLOT),
+    })
+}
+
+/// An image queued by `DeviceManager::set_images_batch`
+struct PendingImage {
+    slot: Slot,
+    key: u8,
+    hash: u64,
+    data: PendingData,
+}
+
+enum PendingData {
+    Cached(Arc<Vec<u8>>),
+    /// Not encoded yet: the image, the format and the model's size for the slot
+    Raw(ImageFormat, (u32, u32), RgbImage),
+}
+
+impl PendingImage {
+    fn encode(self) -> Result<Arc<Vec<u8>>> {
+        match self.data {
+            PendingData::Cached(data) => Ok(data),
+            PendingData::Raw(format, size, image) => {
+                let image = DynamicImage::ImageRgb8(DeviceManager::fit(image, size));
+                let data = encode(format, image, STATIC_QUALITY)
+                    .map_err(|e| anyhow!("Failed to encode image: {}", e))?;
+                Ok(Arc::new(data))
+            }
+        }
+    }
+}
+
+/// Device the manager talks to
+enum Backend {
+    /// Real device over HID
+    Hardware(Box<Device>),
+    /// In-memory device for `--simulate` (display lives only in `frames`)
+    Virtual(Arc<VirtualDevice>),
+}
+
+/// Manages connection to a supported AJAZZ / Mirabox device
 pub struct DeviceManager {
-    device: Device,
+    backend: Backend,
+    /// Detected model (the AKP05E when simulated)
+    model: &'static DeviceModel,
+    /// Serial number (None when simulated)
+    serial: Option<String>,
     input_state: InputState,
+    /// Mirror of everything sent to the display
+    frames: Arc<FrameBuffer>,
+    /// JPEGs already encoded, and what each key last got
+    encode_cache: Mutex<EncodeCache>,
 }
 
 impl DeviceManager {
-    /// Find and return device info without connecting
-    pub async fn find_device() -> Result<DeviceInfo> {
-        let devices = list_devices(&[VENDOR_ID])
+    /// Find the first connected supported model whose serial is accepted
+    async fn probe(accept: impl Fn(&str) -> bool) -> Result<(&'static DeviceModel, String)> {
+        let devices = list_devices(&vendor_ids())
+            .await
+            .map_err(|e| anyhow!("Failed to enumerate devices: {}", e))?;
+
+        devices
+            .into_iter()
+            .filter(|(_, _, serial)| accept(serial))
+            .find_map(|(vid, pid, serial)| find_model(vid, pid).map(|model| (model, serial)))
+            .ok_or_else(|| anyhow!("No compatible device found"))
+    }
+
+    /// Find and return info for every connected supported device without connecting
+    pub async fn find_devices() -> Result<Vec<DeviceInfo>> {
+        let devices = list_devices(&vendor_ids())
             .await
             .map_err(|e| anyhow!("Failed to enumerate devices: {}", e))?;
 
-        for (vid, pid, serial) in devices {
-            if vid == VENDOR_ID && pid == PRODUCT_ID {
-                return Ok(DeviceInfo {
-                    name: "AJAZZ AKP05E".to_string(),
+        let mut found: Vec<DeviceInfo> = devices
+            .into_iter()
+            .filter_map(|(vid, pid, serial)| {
+                find_model(vid, pid).map(|model| DeviceInfo {
+                    name: model.name.to_string(),
                     firmware_version: "Unknown".to_string(),
                     serial_number: serial,
-                });
-            }
+                })
+            })
+            .collect();
+        if found.is_empty() {
+            return Err(anyhow!("No compatible device found"));
         }
+        found.sort_by(|a, b| a.serial_number.cmp(&b.serial_number));
+        Ok(found)
+    }
 
-        Err(anyhow!("No compatible device found"))
+    /// Connect to the first supported device, mirroring its display into `frames`
+    pub async fn connect(frames: Arc<FrameBuffer>) -> Result<Self> {
+        Self::connect_matching(|_| true, frames).await
     }
 
-    /// Connect to the device
-    pub async fn connect() -> Result<Self> {
+    /// Connect to the first supported device whose serial number is accepted
+    pub async fn connect_matching(
+        accept: impl Fn(&str) -> bool,
+        frames: Arc<FrameBuffer>,
+    ) -> Result<Self> {
         info!("Connecting to device...");
 
-        // First, find the device serial
-        let devices = list_devices(&[VENDOR_ID])
-            .await
-            .map_err(|e| anyhow!("Failed to enumerate devices: {}", e))?;
-
-        let serial = devices
-            .iter()
-            .find(|(vid, pid, _)| *vid == VENDOR_ID && *pid == PRODUCT_ID)
-            .map(|(_, _, s)| s.clone())
-            .ok_or_else(|| anyhow!("No compatible device found"))?;
+        let (model, serial) = Self::probe(accept).await?;
+        info!("Found {} with serial: {}", model.name, serial);
 
-        info!("Found device with serial: {}", serial);
-
-        // Connect to the device
-        // N4/AKP05E: v2 protocol, supports both states, 10 keys, 4 encoders
         let device = Device::connect(
-            VENDOR_ID,
-            PRODUCT_ID,
-            serial,
-            true, // is_v2 (1024-byte packets)
-            true, // supports_both_states
-            BUTTON_COUNT as usize,
-            ENCODER_COUNT as usize,
+            model.vendor_id,
+            model.product_id,
+            serial.clone(),
+            model.is_v2,
+            model.supports_both_states,
+            model.key_count as usize,
+            model.encoder_count as usize,
         )
         .await
         .map_err(|e| anyhow!("Failed to connect to device: {}", e))?;
 
-        info!("Connected to device");
+        info!(
+            "Connected to {} ({}x{} keys, {} encoders{})",
+            model.name,
+            model.rows,
+            model.cols,
+            model.encoder_count,
+            if model.has_strip() { ", touch strip" } else { "" }
+        );
 
         let input_state = InputState::new(BUTTON_COUNT as usize, ENCODER_COUNT as usize);
 
         Ok(Self {
-            device,
+            backend: Backend::Hardware(Box::new(device)),
+            model,
+            serial: Some(serial),
             input_state,
+            frames,
+            encode_cache: Mutex::default(),
         })
     }
 
-    /// Get image format for square buttons (112x112 JPEG)
-    fn button_image_format() -> ImageFormat {
-        ImageFormat {
-            mode: ImageMode::JPEG,
-            size: (BUTTON_WIDTH as usize, BUTTON_HEIGHT as usize),
-            rotation: ImageRotation::Rot180,
-            mirror: ImageMirroring::None,
+    /// Use an in-memory virtual device instead of hardware
+    pub fn simulated(device: Arc<VirtualDevice>, frames: Arc<FrameBuffer>) -> Self {
+        info!("Using simulated device");
+        Self {
+            backend: Backend::Virtual(device),
+            model: default_model(),
+            serial: None,
+            input_state: InputState::new(BUTTON_COUNT as usize, ENCODER_COUNT as usize),
+            frames,
+            encode_cache: Mutex::default(),
         }
     }
 
-    /// Get image format for LCD strip soft buttons (112x112 JPEG)
-    fn strip_button_image_format() -> ImageFormat {
+    /// Whether this is the `--simulate` virtual device
+    pub fn is_simulated(&self) -> bool {
+        matches!(self.backend, Backend::Virtual(_))
+    }
+
+    /// The connected model
+    pub fn model(&self) -> &'static DeviceModel {
+        self.model
+    }
+
+    pub fn serial(&self) -> Option<&str> {
+        self.serial.as_deref()
+    }
+
+    /// JPEG image format for the model at `size`
+    fn image_format(&self, (width, height): (u32, u32)) -> ImageFormat {
         ImageFormat {
             mode: ImageMode::JPEG,
-            size: (STRIP_BUTTON_WIDTH as usize, STRIP_BUTTON_HEIGHT as usize),
-            rotation: ImageRotation::Rot180,
+            size: (width as usize, height as usize),
+            rotation: self.model.rotation,
             mirror: ImageMirroring::None,
         }
     }
 
+    /// Scale an image rendered for the AKP05E to the model's `size`
+    fn fit(image: RgbImage, (width, height): (u32, u32)) -> RgbImage {
+        if image.dimensions() == (width, height) {
+            image
+        } else {
+            imageops::resize(&image, width, height, imageops::FilterType::Triangle)
+        }
+    }
+
+    /// Queue an image for `key`, unless `slot` already shows it
+    ///
+    /// Images seen before reuse their encoded JPEG (`size` is the model's
+    /// size for the slot; `image` is scaled to it before encoding).
+    async fn write_image(
+        &self,
+        device: &Device,
+        slot: Slot,
+        key: u8,
+        size: (u32, u32),
+        image: RgbImage,
+        quality: u8,
+    ) -> Result<()> {
+        let format = self.image_format(size);
+        let hash = image_hash(&image, format, quality);
+        let cached = {
+            let cache = self.encode_cache.lock().unwrap();
+            if cache.is_shown(slot, hash) {
+                return Ok(());
+            }
+            cache.get(hash)
+        };
+        let data = match cached {
+            Some(data) => data,
+            None => {
+                let image = DynamicImage::ImageRgb8(Self::fit(image, size));
+                let data = Arc::new(
+                    encode(format, image, quality)
+                        .map_err(|e| anyhow!("Failed to encode image: {}", e))?,
+                );
+                self.encode_cache.lock().unwrap().insert(hash, Arc::clone(&data));
+                data
+            }
+        };
+
+        device
+            .write_image(key, &data)
+            .await
+            .map_err(|e| anyhow!("Failed to queue image: {}", e))?;
+        self.encode_cache.lock().unwrap().mark_shown(slot, hash);
+        Ok(())
+    }
+
     /// Set button image (112x112 RGB) - takes ownership to avoid clone
+    ///
+    /// `button` is an AKP05E display key (see `button_to_display_key`); other
+    /// models get the image scaled onto their matching key, if they have one.
     pub async fn set_button_image(&self, button: u8, image: RgbImage) -> Result<()> {
+        self.write_button(button, image, STATIC_QUALITY).await
+    }
+
+    /// Set a button image that is one frame of an animation
+    ///
+    /// As `set_button_image`, but encoded at the lower animation quality.
+    pub async fn set_button_frame(&self, button: u8, image: RgbImage) -> Result<()> {
+        self.write_button(button, image, frame_quality()).await
+    }
+
+    async fn write_button(&self, button: u8, image: RgbImage, quality: u8) -> Result<()> {
         if button >= BUTTON_COUNT {
             return Err(anyhow!("Invalid button index: {}", button));
         }
 
-        // Convert RgbImage to DynamicImage (no clone needed since we own the image)
-        let dynamic_image = DynamicImage::ImageRgb8(image);
-
-        self.device
-            .set_button_image(button, Self::button_image_format(), dynamic_image)
+        let device = match &self.backend {
+            Backend::Hardware(device) => device,
+            Backend::Virtual(_) => return self.frames.set_button_image(button, image),
+        };
+        self.frames.set_button_image(button, image.clone())?;
+
+        let key = display_key_to_button(button).and_then(|b| self.model.button_display_key(b));
+        let Some(key) = key else {
+            return Ok(());
+        };
+        let size = self.model.button_size;
+        self.write_image(device, Slot::Button(button), key, size, image, quality)
             .await
-            .map_err(|e| anyhow!("Failed to set button image: {}", e))?;
-
-        Ok(())
+            .map_err(|e| anyhow!("Failed to set button image: {}", e))
     }
 
     /// Set LCD strip soft button image (112x112 RGB) - legacy individual button mode
@@ -153,6 +357,12 @@ impl DeviceManager {
             return Err(anyhow!("Invalid strip button index: {}", button));
         }
 
+        let device = match &self.backend {
+            Backend::Hardware(device) if self.model.has_strip() => device,
+            // The simulator only models the continuous strip
+            _ => return Ok(()),
+        };
+
         // Display indices for strip are 0-3
         let display_key = button;
         debug!(
@@ -160,29 +370,11 @@ impl DeviceManager {
             button, display_key
         );
 
-        // Convert RgbImage to DynamicImage
-        let dynamic_image = DynamicImage::ImageRgb8(image.clone());
-
-        self.device
-            .set_button_image(
-                display_key,
-                Self::strip_button_image_format(),
-                dynamic_image,
-            )
+        let size = (STRIP_BUTTON_WIDTH, STRIP_BUTTON_HEIGHT);
+        let slot = Slot::StripButton(button);
+        self.write_image(device, slot, display_key, size, image.clone(), STATIC_QUALITY)
             .await
-            .map_err(|e| anyhow!("Failed to set strip button image: {}", e))?;
-
-        Ok(())
-    }
-
-    /// Get image format for full LCD strip (800x128 JPEG)
-    fn full_strip_image_format() -> ImageFormat {
-        ImageFormat {
-            mode: ImageMode::JPEG,
-            size: (STRIP_WIDTH as usize, STRIP_HEIGHT as usize),
-            rotation: ImageRotation::Rot180,
-            mirror: ImageMirroring::None,
-        }
+            .map_err(|e| anyhow!("Failed to set strip button image: {}", e))
     }
 
     /// Set full LCD strip image (800x128 RGB) - continuous display mode
@@ -190,28 +382,128 @@ impl DeviceManager {
     pub async fn set_strip_image(&self, image: RgbImage) -> Result<()> {
         debug!("Setting full strip image ({}x{})", image.width(), image.height());
 
-        let dynamic_image = DynamicImage::ImageRgb8(image);
+        let device = match &self.backend {
+            Backend::Hardware(device) => device,
+            Backend::Virtual(_) => {
+                self.frames.set_strip_image(image);
+                return Ok(());
+            }
+        };
+        self.frames.set_strip_image(image.clone());
 
-        self.device
-            .set_button_image(0, Self::full_strip_image_format(), dynamic_image)
+        let Some(size) = self.model.strip_size else {
+            return Ok(());
+        };
+        self.write_image(device, Slot::Strip, 0, size, image, STATIC_QUALITY)
             .await
-            .map_err(|e| anyhow!("Failed to set strip image: {}", e))?;
+            .map_err(|e| anyhow!("Failed to set strip image: {}", e))
+    }
 
-        Ok(())
+    /// Send several button images and the strip, flushing once at the end
+    ///
+    /// `buttons` are AKP05E display keys, as for `set_button_image`. Images
+    /// missing from the encode cache are encoded in parallel before any is
+    /// queued, so a full redraw doesn't wait on one JPEG encode at a time.
+    pub async fn set_images_batch(
+        &self,
+        buttons: Vec<(u8, RgbImage)>,
+        strip: Option<RgbImage>,
+    ) -> Result<()> {
+        if let Some((button, _)) = buttons.iter().find(|(button, _)| *button >= BUTTON_COUNT) {
+            return Err(anyhow!("Invalid button index: {}", button));
+        }
+
+        let device = match &self.backend {
+            Backend::Hardware(device) => device,
+            Backend::Virtual(_) => {
+                for (button, image) in buttons {
+                    self.frames.set_button_image(button, image)?;
+                }
+                if let Some(strip) = strip {
+                    self.frames.set_strip_image(strip);
+                }
+                return self.flush().await;
+            }
+        };
+
+        let mut pending = Vec::new();
+        {
+            let cache = self.encode_cache.lock().unwrap();
+            let mut add = |slot: Slot, key: u8, size: (u32, u32), image: RgbImage| {
+                let format = self.image_format(size);
+                let hash = image_hash(&image, format, STATIC_QUALITY);
+                if !cache.is_shown(slot, hash) {
+                    let data = match cache.get(hash) {
+                        Some(data) => PendingData::Cached(data),
+                        None => PendingData::Raw(format, size, image),
+                    };
+                    pending.push(PendingImage { slot, key, hash, data });
+                }
+            };
+            for (button, image) in buttons {
+                self.frames.set_button_image(button, image.clone())?;
+                let key = display_key_to_button(button);
+                if let Some(key) = key.and_then(|b| self.model.button_display_key(b)) {
+                    add(Slot::Button(button), key, self.model.button_size, image);
+                }
+            }
+            if let Some(strip) = strip {
+                self.frames.set_strip_image(strip.clone());
+                if let Some(size) = self.model.strip_size {
+                    add(Slot::Strip, 0, size, strip);
+                }
+            }
+        }
+        if pending.is_empty() {
+            return Ok(());
+        }
+
+        let encoded = tokio::task::spawn_blocking(move || {
+            pending
+                .into_par_iter()
+                .map(|image| Ok((image.slot, image.key, image.hash, image.encode()?)))
+                .collect::<Result<Vec<_>>>()
+        })
+        .await
+        .map_err(|e| anyhow!("Image encoder panicked: {}", e))??;
+
+        for (slot, key, hash, data) in encoded {
+            self.encode_cache.lock().unwrap().insert(hash, Arc::clone(&data));
+            device
+                .write_image(key, &data)
+                .await
+                .map_err(|e| anyhow!("Failed to queue image: {}", e))?;
+            self.encode_cache.lock().unwrap().mark_shown(slot, hash);
+        }
+        self.flush().await
     }
 
     /// Flush pending image updates to the device
     pub async fn flush(&self) -> Result<()> {
-        self.device
-            .flush()
-            .await
-            .map_err(|e| anyhow!("Failed to flush images: {}", e))
+        self.frames.flush();
+        let device = match &self.backend {
+            Backend::Hardware(device) => device,
+            Backend::Virtual(_) => return Ok(()),
+        };
+
+        device.flush().await.map_err(|e| {
+            // Whatever was queued may not have arrived; resend it next time
+            self.encode_cache.lock().unwrap().clear_shown();
+            anyhow!("Failed to flush images: {}", e)
+        })
     }
 
     /// Reset the device (clear display and set brightness)
     pub async fn reset(&self) -> Result<()> {
         debug!("Resetting device");
-        self.device
+        self.frames.reset();
+        self.encode_cache.lock().unwrap().clear_shown();
+        let device = match &self.backend {
+            Backend::Hardware(device) => device,
+            Backend::Virtual(_) => return Ok(()),
+        };
+
+        device
             .reset()
             .await
             .map_err(|e| anyhow!("Failed to reset device: {}", e))
@@ -219,7 +511,12 @@ impl DeviceManager {
 
     /// Send keep-alive to prevent device timeout
     pub async fn keep_alive(&self) -> Result<()> {
-        self.device
+        let device = match &self.backend {
+            Backend::Hardware(device) => device,
+            Backend::Virtual(_) => return Ok(()),
+        };
+
+        device
             .keep_alive()
             .await
             .map_err(|e| anyhow!("Failed to send keep-alive: {}", e))
@@ -229,144 +526,55 @@ impl DeviceManager {
     pub async fn set_brightness(&self, percent: u8) -> Result<()> {
         let percent = percent.min(100);
         debug!("Setting brightness to {}%", percent);
-        self.device
+        self.frames.set_brightness(percent);
+        let device = match &self.backend {
+            Backend::Hardware(device) => device,
+            Backend::Virtual(_) => return Ok(()),
+        };
+
+        device
             .set_brightness(percent)
             .await
             .map_err(|e| anyhow!("Failed to set brightness: {}", e))
     }
 
-    /// Input processing function for mirajazz
+    /// Wait for the next input event
     ///
-    /// For N4/AKP05E:
-    /// - event_type (data[9]): Action identifier
-    ///   - 0x01-0x05: Top row buttons (logical 0-4)
-    ///   - 0x06-0x0a: Bottom row buttons (logical 5-9)
-    ///   - 0x33, 0x35, 0x36, 0x37: Encoder presses (encoders 0-3)
-    ///   - 0x40-0x43: LCD strip soft buttons (0-3)
-    ///   - 0x50, 0x51: LCD strip swipe left/right
-    ///   - 0x70-0x73: Encoder rotate counter-clockwise
-    ///   - 0xa0-0xa3: Encoder rotate clockwise
-    /// - state (data[10]): 0x00 = release, non-zero = press (for buttons)
-    fn process_input(
-        event_type: u8,
-        state: u8,
-    ) -> Result<DeviceInput, mirajazz::error::MirajazzError> {
-        debug!("HID: type=0x{:02x}, state=0x{:02x}", event_type, state);
-
-        match event_type {
-            // Main buttons (IDs 1-10 → logical 0-9)
-            0x01..=0x0a => {
-                let mut buttons = vec![false; BUTTON_COUNT as usize];
-                let button_idx = (event_type - 1) as usize;
-                if button_idx < buttons.len() {
-                    buttons[button_idx] = state != 0;
-                }
-                debug!(
-                    "Button {} {}",
-                    button_idx,
-                    if state != 0 { "pressed" } else { "released" }
-                );
-                Ok(DeviceInput::ButtonStateChange(buttons))
-            }
-
-            // Encoder presses (actual IDs: 0x33, 0x35, 0x36, 0x37)
-            // Mapping based on physical wheel position (left to right: 0, 1, 2, 3)
-            0x33 | 0x35 | 0x36 | 0x37 => {
-                let mut encoders = vec![false; ENCODER_COUNT as usize];
-                let encoder_idx = match event_type {
-                    0x37 => 0, // Wheel 1 (leftmost)
-                    0x35 => 1, // Wheel 2 (model)
-                    0x33 => 2, // Wheel 3
-                    0x36 => 3, // Wheel 4 (rightmost)
-                    _ => 0,
-                };
-                if encoder_idx < encoders.len() {
-                    encoders[encoder_idx] = state != 0; // Use state param for press/release
+    /// Cancel-safe: a read dropped part way loses nothing, so callers can
+    /// wait on this in a `select!`.
+    pub async fn next_event(&mut self) -> Result<InputEvent> {
+        loop {
+            let input = match &self.backend {
+                Backend::Hardware(device) => {
+                    device.read_input(None, self.model.process_input).await
                 }
-                let action = if state != 0 { "pressed" } else { "released" };
-                debug!(
-                    "Encoder press raw: idx={}, action={}, value={}",
-                    encoder_idx, action, encoders[encoder_idx]
-                );
-                Ok(DeviceInput::EncoderStateChange(encoders))
-            }
-
-            // Encoder 3 rotation (rightmost knob)
-            // Pattern: 0x70 = CCW, 0x71 = CW
-            0x70 | 0x71 => {
-                let mut directions = vec![0i8; ENCODER_COUNT as usize];
-                let dir = if event_type & 1 == 1 { 1 } else { -1 };
-                directions[3] = dir;
-                Ok(DeviceInput::EncoderTwist(directions))
-            }
-
-            // Encoder 0 rotation (leftmost knob)
-            // Pattern: 0xa0 = CCW, 0xa1 = CW
-            0xa0 | 0xa1 => {
-                let mut directions = vec![0i8; ENCODER_COUNT as usize];
-                let dir = if event_type & 1 == 1 { 1 } else { -1 };
-                directions[0] = dir;
-                Ok(DeviceInput::EncoderTwist(directions))
+                Backend::Virtual(sim) => return Ok(sim.next_input().await),
+            };
+            let failed = input.is_err();
+            if let Some(event) = self.process_input(input)? {
+                return Ok(event);
             }
-
-            // Knob 3 rotation (0x90 CCW, 0x91 CW)
-            0x90 | 0x91 => {
-                let mut directions = vec![0i8; ENCODER_COUNT as usize];
-                directions[2] = if event_type == 0x91 { 1 } else { -1 };
-                Ok(DeviceInput::EncoderTwist(directions))
-            }
-
-            // LCD strip soft buttons (IDs 0x40-0x43)
-            0x40..=0x43 => {
-                let mut buttons = vec![false; BUTTON_COUNT as usize];
-                let button_idx = (event_type - 0x40 + 10) as usize;
-                if button_idx < buttons.len() {
-                    buttons[button_idx] = true;
-                }
-                debug!("LCD strip button {} pressed", event_type - 0x40);
-                Ok(DeviceInput::ButtonStateChange(buttons))
-            }
-
-            // Knob 2 rotation (0x50 CCW, 0x51 CW)
-            0x50 => {
-                let mut directions = vec![0i8; ENCODER_COUNT as usize];
-                directions[1] = -1; // Encoder 1
-                Ok(DeviceInput::EncoderTwist(directions))
-            }
-            0x51 => {
-                let mut directions = vec![0i8; ENCODER_COUNT as usize];
-                directions[1] = 1; // Encoder 1
-                Ok(DeviceInput::EncoderTwist(directions))
-            }
-
-            // Null/empty events (noise or padding)
-            0x00 => Ok(DeviceInput::NoData),
-
-            // Unknown event - log it for discovery
-            _ => {
-                info!(
-                    "Unknown HID event: type=0x{:02x}, state=0x{:02x}",
-                    event_type, state
-                );
-                Ok(DeviceInput::NoData)
+            if failed {
+                // Don't spin on a device that keeps failing reads
+                tokio::time::sleep(Duration::from_millis(100)).await;
             }
         }
     }
 
-    /// Poll for input events (non-blocking, 1ms timeout for responsive animations)
-    pub async fn poll_event(&mut self) -> Result<Option<InputEvent>> {
-        let timeout = Duration::from_millis(1);
-
-        match self
-            .device
-            .read_input(Some(timeout), Self::process_input)
-            .await
-        {
+    /// Turn a raw read into at most one press/release/rotate event
+    fn process_input(
+        &mut self,
+        input: Result<DeviceInput, MirajazzError>,
+    ) -> Result<Option<InputEvent>> {
+        match input {
             Ok(input) => {
                 match input {
                     DeviceInput::NoData => Ok(None),
 
                     DeviceInput::ButtonStateChange(states) => {
+                        if let Some(event) = strip_gesture(&states) {
+                            return Ok(Some(event));
+                        }
                         // Detect button press/release edges
                         for (i, &pressed) in states.iter().enumerate() {
                             if i < self.input_state.buttons.len() {
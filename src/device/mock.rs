@@ -0,0 +1,213 @@
+//! Recording deck for driving the app without hardware
+//!
+//! A `MockDevice` keeps a log of every display write the app makes and
+//! shows images on flush, as the hardware does. Input is injected with
+//! `push_input`. Clones share the same deck, so a test keeps one and hands
+//! the other to `App::for_device`.
+
+use anyhow::{bail, Result};
+use image::RgbImage;
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex, MutexGuard};
+use tokio::sync::Notify;
+
+use super::backend::DeckDevice;
+use super::frames::FrameBuffer;
+use super::manager::InputEvent;
+use super::models::{default_model, DeviceModel};
+
+/// A write to a `MockDevice`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeviceCall {
+    /// Button image for an AKP05E display key
+    ButtonImage(u8),
+    /// GIF frame for an AKP05E display key
+    ButtonFrame(u8),
+    StripImage,
+    Flush,
+    Reset,
+    Brightness(u8),
+}
+
+/// What's on the mock's display
+#[derive(Default)]
+struct Screens {
+    /// Images sent since the last flush (None for the strip)
+    pending: Vec<(Option<u8>, RgbImage)>,
+    buttons: HashMap<u8, RgbImage>,
+    strip: Option<RgbImage>,
+}
+
+#[derive(Default)]
+struct Shared {
+    calls: Mutex<Vec<DeviceCall>>,
+    screens: Mutex<Screens>,
+    input: Mutex<VecDeque<InputEvent>>,
+    /// Wakes `next_event` when an event is pushed
+    pushed: Notify,
+}
+
+/// In-memory deck that records what it's sent
+#[derive(Clone, Default)]
+pub struct MockDevice {
+    shared: Arc<Shared>,
+}
+
+/// Lock a mutex, carrying on if a panicking test poisoned it
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+impl MockDevice {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Queue an input event for the app
+    pub fn push_input(&self, event: InputEvent) {
+        lock(&self.shared.input).push_back(event);
+        self.shared.pushed.notify_one();
+    }
+
+    /// Writes since the last call, oldest first
+    pub fn take_calls(&self) -> Vec<DeviceCall> {
+        std::mem::take(&mut *lock(&self.shared.calls))
+    }
+
+    /// The button image shown for a display key
+    pub fn button_image(&self, key: u8) -> Option<RgbImage> {
+        lock(&self.shared.screens).buttons.get(&key).cloned()
+    }
+
+    /// The strip image shown
+    pub fn strip_image(&self) -> Option<RgbImage> {
+        lock(&self.shared.screens).strip.clone()
+    }
+
+    fn record(&self, call: DeviceCall) {
+        lock(&self.shared.calls).push(call);
+    }
+
+    fn queue(&self, key: Option<u8>, image: RgbImage) {
+        lock(&self.shared.screens).pending.push((key, image));
+    }
+}
+
+impl DeckDevice for MockDevice {
+    async fn connect(_claimed: Vec<String>, _frames: Arc<FrameBuffer>) -> Result<Self> {
+        bail!("A mock device can't be reconnected")
+    }
+
+    fn model(&self) -> &'static DeviceModel {
+        default_model()
+    }
+
+    fn serial(&self) -> Option<&str> {
+        None
+    }
+
+    fn is_simulated(&self) -> bool {
+        true
+    }
+
+    async fn set_button_image(&self, button: u8, image: RgbImage) -> Result<()> {
+        self.record(DeviceCall::ButtonImage(button));
+        self.queue(Some(button), image);
+        Ok(())
+    }
+
+    async fn set_button_frame(&self, button: u8, image: RgbImage) -> Result<()> {
+        self.record(DeviceCall::ButtonFrame(button));
+        self.queue(Some(button), image);
+        Ok(())
+    }
+
+    async fn set_strip_image(&self, image: RgbImage) -> Result<()> {
+        self.record(DeviceCall::StripImage);
+        self.queue(None, image);
+        Ok(())
+    }
+
+    async fn set_images_batch(
+        &self,
+        buttons: Vec<(u8, RgbImage)>,
+        strip: Option<RgbImage>,
+    ) -> Result<()> {
+        for (button, image) in buttons {
+            self.set_button_image(button, image).await?;
+        }
+        if let Some(strip) = strip {
+            self.set_strip_image(strip).await?;
+        }
+        self.flush().await
+    }
+
+    async fn flush(&self) -> Result<()> {
+        self.record(DeviceCall::Flush);
+        let mut screens = lock(&self.shared.screens);
+        for (key, image) in std::mem::take(&mut screens.pending) {
+            match key {
+                Some(key) => {
+                    screens.buttons.insert(key, image);
+                }
+                None => screens.strip = Some(image),
+            }
+        }
+        Ok(())
+    }
+
+    async fn reset(&self) -> Result<()> {
+        self.record(DeviceCall::Reset);
+        *lock(&self.shared.screens) = Screens::default();
+        Ok(())
+    }
+
+    async fn keep_alive(&self) -> Result<()> {
+        Ok(())
+    }
+
+    async fn set_brightness(&self, percent: u8) -> Result<()> {
+        self.record(DeviceCall::Brightness(percent.min(100)));
+        Ok(())
+    }
+
+    async fn next_event(&mut self) -> Result<InputEvent> {
+        loop {
+            if let Some(event) = lock(&self.shared.input).pop_front() {
+                return Ok(event);
+            }
+            self.shared.pushed.notified().await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_mock_device() {
+        let mut device = MockDevice::new();
+        let deck = device.clone();
+        let red = RgbImage::from_pixel(2, 2, image::Rgb([255, 0, 0]));
+        device.set_button_image(5, red.clone()).await.unwrap();
+        // Nothing shows until the flush
+        assert!(deck.button_image(5).is_none());
+        device.flush().await.unwrap();
+        assert_eq!(deck.button_image(5), Some(red));
+        assert_eq!(
+            deck.take_calls(),
+            [DeviceCall::ButtonImage(5), DeviceCall::Flush]
+        );
+        assert!(deck.take_calls().is_empty());
+
+        device.reset().await.unwrap();
+        assert!(deck.button_image(5).is_none());
+
+        deck.push_input(InputEvent::ButtonDown(3));
+        assert!(matches!(
+            device.next_event().await,
+            Ok(InputEvent::ButtonDown(3))
+        ));
+    }
+}
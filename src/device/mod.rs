@@ -1,7 +1,25 @@
+mod backend;
 mod buttons;
+mod encode_cache;
+mod frames;
+mod input_map;
+mod learn;
 mod manager;
+mod mock;
+mod models;
 mod protocol;
+mod simulator;
 
+pub use backend::DeckDevice;
 pub use buttons::*;
-pub use manager::{DeviceInfo, DeviceManager, InputEvent};
+pub use encode_cache::set_frame_quality;
+pub use frames::{FrameBuffer, SavedFrames, DISPLAY_BUTTON_COUNT};
+pub use input_map::{set_input_map, InputTarget};
+pub use learn::{capture_raw, RawEvent};
+pub use manager::{
+    DeviceInfo, DeviceManager, FeedbackKind, InputEvent, SwipeDirection,
+};
+pub use mock::{DeviceCall, MockDevice};
+pub use models::{DeviceModel, SUPPORTED_MODELS};
 pub use protocol::*;
+pub use simulator::VirtualDevice;
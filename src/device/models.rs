@@ -0,0 +1,369 @@
+//! Supported AJAZZ/Mirabox models
+//!
+//! The app renders for the AKP05E/N4 layout (2x5 keys, 800x128 strip, 4
+//! encoders; see `protocol`). Each model here describes how that layout maps
+//! onto its hardware: logical button `row * 5 + col` goes to the model's key at
+//! the same row and column if it has one, and its HID events are decoded back
+//! into the same logical IDs.
+
+use mirajazz::error::MirajazzError;
+use mirajazz::types::{DeviceInput, ImageRotation};
+use tracing::{debug, info};
+
+use super::input_map::{self, InputTarget};
+use super::learn;
+use super::manager::SwipeDirection;
+use super::protocol::*;
+
+/// Logical grid the app renders (AKP05E/N4 key layout)
+const LOGICAL_COLS: u8 = 5;
+const LOGICAL_ROWS: u8 = 2;
+
+/// Static description of a supported device model
+#[derive(Debug)]
+pub struct DeviceModel {
+    pub name: &'static str,
+    pub vendor_id: u16,
+    pub product_id: u16,
+    /// Key grid
+    pub rows: u8,
+    pub cols: u8,
+    /// Keys reported to the HID layer (including any without a display)
+    pub key_count: u8,
+    /// Key image size
+    pub button_size: (u32, u32),
+    /// Full touch strip size, if the model has one
+    pub strip_size: Option<(u32, u32)>,
+    pub encoder_count: u8,
+    /// Uses 1024-byte (v2) packets rather than 512
+    pub is_v2: bool,
+    /// Reports key releases as well as presses
+    pub supports_both_states: bool,
+    /// Rotation applied to images before sending them
+    pub rotation: ImageRotation,
+    /// Device display key for each key, in row-major order
+    pub display_keys: &'static [u8],
+    /// Decodes a HID event (type, state) into logical buttons/encoders
+    pub process_input: fn(u8, u8) -> Result<DeviceInput, MirajazzError>,
+}
+
+impl DeviceModel {
+    /// The model's display key for a logical button (0-9), if it has that key
+    pub fn button_display_key(&self, button: u8) -> Option<u8> {
+        let (row, col) = (button / LOGICAL_COLS, button % LOGICAL_COLS);
+        if row >= self.rows.min(LOGICAL_ROWS) || col >= self.cols {
+            return None;
+        }
+        self.display_keys.get((row * self.cols + col) as usize).copied()
+    }
+
+    /// Logical button for the model's key at row-major `index`, if it is on the grid
+    fn logical_button(&self, index: u8) -> Option<u8> {
+        let (row, col) = (index / self.cols, index % self.cols);
+        (row < LOGICAL_ROWS && col < LOGICAL_COLS).then_some(row * LOGICAL_COLS + col)
+    }
+
+    /// Whether this model has the full-width touch strip
+    pub fn has_strip(&self) -> bool {
+        self.strip_size.is_some()
+    }
+}
+
+/// AKP05E / N4 display keys: top row 10-14, bottom row 5-9
+const N4_DISPLAY_KEYS: [u8; 10] = [10, 11, 12, 13, 14, 5, 6, 7, 8, 9];
+
+/// AKP153 display keys (the panel is mounted rotated, so keys run in columns)
+const AKP153_DISPLAY_KEYS: [u8; 15] = [12, 9, 6, 3, 0, 13, 10, 7, 4, 1, 14, 11, 8, 5, 2];
+
+/// AKP03 / N3 display keys (2x3, then three keys without a display)
+const AKP03_DISPLAY_KEYS: [u8; 6] = [0, 1, 2, 3, 4, 5];
+
+const N4: DeviceModel = DeviceModel {
+    name: "AJAZZ AKP05E",
+    vendor_id: VENDOR_ID,
+    product_id: PRODUCT_ID,
+    rows: 2,
+    cols: 5,
+    key_count: BUTTON_COUNT,
+    button_size: (BUTTON_WIDTH, BUTTON_HEIGHT),
+    strip_size: Some((STRIP_WIDTH, STRIP_HEIGHT)),
+    encoder_count: ENCODER_COUNT,
+    is_v2: true,
+    supports_both_states: true,
+    rotation: ImageRotation::Rot180,
+    display_keys: &N4_DISPLAY_KEYS,
+    process_input: process_n4_input,
+};
+
+const AKP153: DeviceModel = DeviceModel {
+    name: "AJAZZ AKP153",
+    vendor_id: 0x5548,
+    product_id: 0x6674,
+    rows: 3,
+    cols: 5,
+    key_count: 15,
+    button_size: (85, 85),
+    strip_size: None,
+    encoder_count: 0,
+    is_v2: false,
+    supports_both_states: false,
+    rotation: ImageRotation::Rot90,
+    display_keys: &AKP153_DISPLAY_KEYS,
+    process_input: process_akp153_input,
+};
+
+const AKP03: DeviceModel = DeviceModel {
+    name: "AJAZZ AKP03",
+    vendor_id: 0x0300,
+    product_id: 0x1001,
+    rows: 2,
+    cols: 3,
+    key_count: 9,
+    button_size: (60, 60),
+    strip_size: None,
+    encoder_count: 3,
+    is_v2: false,
+    supports_both_states: false,
+    rotation: ImageRotation::Rot0,
+    display_keys: &AKP03_DISPLAY_KEYS,
+    process_input: process_akp03_input,
+};
+
+/// Every model probed for on connect, in order of preference
+pub static SUPPORTED_MODELS: &[DeviceModel] = &[
+    N4,
+    DeviceModel { name: "Mirabox N4", vendor_id: 0x6603, product_id: 0x1007, ..N4 },
+    AKP153,
+    DeviceModel { name: "AJAZZ AKP153E", vendor_id: 0x0300, product_id: 0x1010, ..AKP153 },
+    DeviceModel { name: "AJAZZ AKP153R", vendor_id: 0x0300, product_id: 0x1020, ..AKP153 },
+    AKP03,
+    DeviceModel { name: "AJAZZ AKP03E", product_id: 0x3002, ..AKP03 },
+    DeviceModel {
+        name: "AJAZZ AKP03R",
+        product_id: 0x1003,
+        supports_both_states: true,
+        ..AKP03
+    },
+    DeviceModel { name: "Mirabox N3", vendor_id: 0x6603, product_id: 0x1002, ..AKP03 },
+    DeviceModel { name: "Mirabox N3EN", vendor_id: 0x6603, product_id: 0x1003, ..AKP03 },
+];
+
+/// Supported model with the given USB IDs
+pub fn find_model(vendor_id: u16, product_id: u16) -> Option<&'static DeviceModel> {
+    SUPPORTED_MODELS
+        .iter()
+        .find(|m| m.vendor_id == vendor_id && m.product_id == product_id)
+}
+
+/// The AKP05E/N4, which the simulator stands in for
+pub fn default_model() -> &'static DeviceModel {
+    &SUPPORTED_MODELS[0]
+}
+
+/// USB vendor IDs of all supported models
+pub fn vendor_ids() -> Vec<u16> {
+    let mut ids: Vec<u16> = SUPPORTED_MODELS.iter().map(|m| m.vendor_id).collect();
+    ids.sort_unstable();
+    ids.dedup();
+    ids
+}
+
+fn button_change(button: u8, pressed: bool) -> DeviceInput {
+    let mut buttons = vec![false; INPUT_SLOT_COUNT as usize];
+    buttons[button as usize] = pressed;
+    DeviceInput::ButtonStateChange(buttons)
+}
+
+/// A touch on one of the strip's zones (0-3); the strip reports no release
+fn strip_touch(zone: u8) -> DeviceInput {
+    button_change(STRIP_TOUCH_SLOT + zone, true)
+}
+
+fn strip_swipe(direction: SwipeDirection) -> DeviceInput {
+    match direction {
+        SwipeDirection::Left => button_change(SWIPE_LEFT_SLOT, true),
+        SwipeDirection::Right => button_change(SWIPE_RIGHT_SLOT, true),
+    }
+}
+
+fn encoder_press(encoder: usize, pressed: bool) -> DeviceInput {
+    let mut encoders = vec![false; ENCODER_COUNT as usize];
+    encoders[encoder] = pressed;
+    DeviceInput::EncoderStateChange(encoders)
+}
+
+fn encoder_twist(encoder: usize, direction: i8) -> DeviceInput {
+    let mut directions = vec![0i8; ENCODER_COUNT as usize];
+    directions[encoder] = direction;
+    DeviceInput::EncoderTwist(directions)
+}
+
+/// Log a raw event and copy it to any `--learn` capture
+fn trace_raw(event_type: u8, state: u8) {
+    debug!("HID: type=0x{:02x}, state=0x{:02x}", event_type, state);
+    learn::record(event_type, state);
+}
+
+/// The `[device.input_map]` override for an event, if one is configured
+fn remapped_input(event_type: u8, state: u8) -> Option<DeviceInput> {
+    let input = match input_map::remapped(event_type)? {
+        InputTarget::Button(button) => button_change(button, state != 0),
+        InputTarget::EncoderPress(encoder) => encoder_press(encoder as usize, state != 0),
+        InputTarget::EncoderTwist(encoder, direction) => encoder_twist(encoder as usize, direction),
+        InputTarget::StripTouch(zone) => strip_touch(zone),
+        InputTarget::StripSwipe(direction) => strip_swipe(direction),
+        InputTarget::Ignore => DeviceInput::NoData,
+    };
+    Some(input)
+}
+
+/// Input processing function for the AKP05E / N4
+///
+/// - event_type (data[9]): Action identifier
+///   - 0x01-0x05: Top row buttons (logical 0-4)
+///   - 0x06-0x0a: Bottom row buttons (logical 5-9)
+///   - 0x33, 0x35, 0x36, 0x37: Encoder presses (encoders 0-3)
+///   - 0x40-0x43: LCD strip touch zones (0-3)
+///   - 0x50, 0x51: Encoder 1 rotate counter-clockwise/clockwise
+///   - 0x90, 0x91: Encoder 2 rotate counter-clockwise/clockwise
+///   - 0x70, 0x71: Encoder 3 rotate counter-clockwise/clockwise
+///   - 0xa0, 0xa1: Encoder 0 rotate counter-clockwise/clockwise
+/// - state (data[10]): 0x00 = release, non-zero = press (for buttons)
+fn process_n4_input(event_type: u8, state: u8) -> Result<DeviceInput, MirajazzError> {
+    trace_raw(event_type, state);
+    if let Some(input) = remapped_input(event_type, state) {
+        return Ok(input);
+    }
+
+    let input = match event_type {
+        // Main buttons (IDs 1-10 → logical 0-9)
+        0x01..=0x0a => {
+            let button = event_type - 1;
+            let action = if state != 0 { "pressed" } else { "released" };
+            debug!("Button {} {}", button, action);
+            button_change(button, state != 0)
+        }
+
+        // Encoder presses, by physical wheel position (left to right: 0, 1, 2, 3)
+        0x33 | 0x35 | 0x36 | 0x37 => {
+            let encoder = match event_type {
+                0x37 => 0, // Wheel 1 (leftmost)
+                0x35 => 1, // Wheel 2 (model)
+                0x33 => 2, // Wheel 3
+                _ => 3,    // Wheel 4 (rightmost)
+            };
+            debug!("Encoder press raw: idx={}, state={}", encoder, state);
+            encoder_press(encoder, state != 0)
+        }
+
+        // Encoder rotation: odd IDs are clockwise
+        0xa0 | 0xa1 => encoder_twist(0, if event_type & 1 == 1 { 1 } else { -1 }),
+        0x50 | 0x51 => encoder_twist(1, if event_type & 1 == 1 { 1 } else { -1 }),
+        0x90 | 0x91 => encoder_twist(2, if event_type & 1 == 1 { 1 } else { -1 }),
+        0x70 | 0x71 => encoder_twist(3, if event_type & 1 == 1 { 1 } else { -1 }),
+
+        // LCD strip touch zones (IDs 0x40-0x43 → zones 0-3, press only)
+        0x40..=0x43 => {
+            debug!("LCD strip zone {} touched", event_type - 0x40);
+            strip_touch(event_type - 0x40)
+        }
+
+        // Null/empty events (noise or padding)
+        0x00 => DeviceInput::NoData,
+
+        // Unknown event - log it for discovery
+        _ => {
+            info!("Unknown HID event: type=0x{:02x}, state=0x{:02x}", event_type, state);
+            DeviceInput::NoData
+        }
+    };
+    Ok(input)
+}
+
+/// Input processing function for the AKP153 family (keys 0x01-0x0f, no encoders)
+fn process_akp153_input(event_type: u8, state: u8) -> Result<DeviceInput, MirajazzError> {
+    trace_raw(event_type, state);
+    if let Some(input) = remapped_input(event_type, state) {
+        return Ok(input);
+    }
+
+    // Key IDs follow the display keys, so map back through the same table
+    let index = (1..=15)
+        .contains(&event_type)
+        .then(|| AKP153_DISPLAY_KEYS.iter().position(|&k| k == event_type - 1))
+        .flatten();
+    let input = match index.and_then(|i| AKP153.logical_button(i as u8)) {
+        Some(button) => button_change(button, state != 0),
+        None => DeviceInput::NoData,
+    };
+    Ok(input)
+}
+
+/// Input processing function for the AKP03 / N3 family
+///
+/// - 0x01-0x06: display keys, row-major
+/// - 0x33-0x35: encoder presses (left to right)
+/// - 0x90/0x91, 0x50/0x51, 0x60/0x61: encoders 0-2 rotate counter-clockwise/clockwise
+fn process_akp03_input(event_type: u8, state: u8) -> Result<DeviceInput, MirajazzError> {
+    trace_raw(event_type, state);
+    if let Some(input) = remapped_input(event_type, state) {
+        return Ok(input);
+    }
+
+    let input = match event_type {
+        0x01..=0x06 => match AKP03.logical_button(event_type - 1) {
+            Some(button) => button_change(button, state != 0),
+            None => DeviceInput::NoData,
+        },
+        0x33..=0x35 => encoder_press((event_type - 0x33) as usize, state != 0),
+        0x90 | 0x91 => encoder_twist(0, if event_type & 1 == 1 { 1 } else { -1 }),
+        0x50 | 0x51 => encoder_twist(1, if event_type & 1 == 1 { 1 } else { -1 }),
+        0x60 | 0x61 => encoder_twist(2, if event_type & 1 == 1 { 1 } else { -1 }),
+        0x00 => DeviceInput::NoData,
+        _ => {
+            info!("Unknown HID event: type=0x{:02x}, state=0x{:02x}", event_type, state);
+            DeviceInput::NoData
+        }
+    };
+    Ok(input)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_button_display_keys() {
+        // The AKP05E keeps the existing mapping
+        for button in 0..10 {
+            assert_eq!(N4.button_display_key(button), Some(button_to_display_key(button)));
+        }
+
+        // AKP03 shows the left three columns of both rows
+        assert_eq!(AKP03.button_display_key(2), Some(2));
+        assert_eq!(AKP03.button_display_key(3), None);
+        assert_eq!(AKP03.button_display_key(5), Some(3));
+
+        // AKP153 shows the top two rows; every key maps to a distinct display key
+        let keys: Vec<_> = (0..10).filter_map(|b| AKP153.button_display_key(b)).collect();
+        assert_eq!(keys.len(), 10);
+        assert!(keys.iter().all(|k| keys.iter().filter(|other| *other == k).count() == 1));
+    }
+
+    #[test]
+    fn test_find_model() {
+        assert_eq!(find_model(VENDOR_ID, PRODUCT_ID).unwrap().name, "AJAZZ AKP05E");
+        assert_eq!(find_model(0x0300, 0x1003).unwrap().name, "AJAZZ AKP03R");
+        assert!(find_model(0x0300, 0xffff).is_none());
+        assert_eq!(vendor_ids(), vec![0x0300, 0x5548, 0x6603]);
+    }
+
+    #[test]
+    fn test_akp03_input_maps_to_logical_grid() {
+        // Second row, first key → logical button 5
+        match process_akp03_input(0x04, 1).unwrap() {
+            DeviceInput::ButtonStateChange(buttons) => assert!(buttons[5]),
+            other => panic!("Expected button change, got {:?}", other),
+        }
+    }
+}
@@ -1,5 +1,8 @@
 //! N4/AKP05E device constants
 //!
+//! This is the layout the app renders for; `models` maps it onto other
+//! supported devices.
+//!
 //! Display index mapping (for set_button_image):
 //!   - Top row (5 buttons):    display keys 10-14
 //!   - Bottom row (5 buttons): display keys 5-9
@@ -29,6 +32,17 @@ pub const BUTTON_COUNT: u8 = 15;
 /// Number of LCD strip soft buttons
 pub const STRIP_BUTTON_COUNT: u8 = 4;
 
+/// Decoded button slots the strip's touch zones (0-3) come in on (10-13)
+///
+/// Decoders can only report buttons, so strip gestures use slots past the
+/// real buttons and `DeviceManager` turns them back into strip events.
+pub const STRIP_TOUCH_SLOT: u8 = 10;
+/// Decoded button slots for swipes along the strip
+pub const SWIPE_LEFT_SLOT: u8 = 14;
+pub const SWIPE_RIGHT_SLOT: u8 = 15;
+/// Length of a decoded button state (buttons, then strip gestures)
+pub const INPUT_SLOT_COUNT: u8 = 16;
+
 /// Number of rotary encoders
 pub const ENCODER_COUNT: u8 = 4;
 
@@ -60,3 +74,13 @@ pub fn button_to_display_key(button_id: u8) -> u8 {
         button_id // 5-9 → 5-9 (bottom row)
     }
 }
+
+/// Inverse of `button_to_display_key` (None for strip keys 0-3)
+#[inline]
+pub fn display_key_to_button(display_key: u8) -> Option<u8> {
+    match display_key {
+        10..=14 => Some(display_key - 10),
+        5..=9 => Some(display_key),
+        _ => None,
+    }
+}
@@ -0,0 +1,98 @@
+//! In-memory virtual device for `--simulate`
+//!
+//! Stands in for the AKP05E when no hardware is attached: images sent by the
+//! app only go to the shared `FrameBuffer` (served to the web UI), and input
+//! events are injected through the `/api/simulator/*` endpoints.
+
+use anyhow::{anyhow, Result};
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use tokio::sync::Notify;
+
+use super::frames::DISPLAY_BUTTON_COUNT;
+use super::manager::InputEvent;
+use super::protocol::{ENCODER_COUNT, STRIP_BUTTON_COUNT};
+
+/// Virtual device input, shared between the app loop and the web server
+#[derive(Default)]
+pub struct VirtualDevice {
+    input: Mutex<VecDeque<InputEvent>>,
+    /// Wakes `next_input` when an event is pushed
+    pushed: Notify,
+}
+
+impl VirtualDevice {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Inject an input event (validated against the simulated hardware)
+    pub fn push_input(&self, event: InputEvent) -> Result<()> {
+        let valid = match event {
+            InputEvent::ButtonDown(b) | InputEvent::ButtonUp(b) => b < DISPLAY_BUTTON_COUNT,
+            InputEvent::EncoderRotate { encoder, direction } => {
+                encoder < ENCODER_COUNT && (direction == 1 || direction == -1)
+            }
+            InputEvent::EncoderPress(e) | InputEvent::EncoderRelease(e) => e < ENCODER_COUNT,
+            InputEvent::StripTouch(zone) => zone < STRIP_BUTTON_COUNT,
+            InputEvent::StripSwipe(_) => true,
+        };
+        if !valid {
+            return Err(anyhow!("Invalid simulator input: {:?}", event));
+        }
+        self.input
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push_back(event);
+        self.pushed.notify_one();
+        Ok(())
+    }
+
+    /// Next injected input event, if any
+    pub fn pop_input(&self) -> Option<InputEvent> {
+        self.input
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .pop_front()
+    }
+
+    /// Wait for the next injected input event
+    pub async fn next_input(&self) -> InputEvent {
+        loop {
+            if let Some(event) = self.pop_input() {
+                return event;
+            }
+            self.pushed.notified().await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_input_queue_validates() {
+        let device = VirtualDevice::new();
+        device.push_input(InputEvent::ButtonDown(9)).unwrap();
+        assert!(device.push_input(InputEvent::ButtonDown(10)).is_err());
+        assert!(device
+            .push_input(InputEvent::EncoderRotate {
+                encoder: 1,
+                direction: 3
+            })
+            .is_err());
+
+        assert!(matches!(device.pop_input(), Some(InputEvent::ButtonDown(9))));
+        assert!(device.pop_input().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_next_input_waits_for_push() {
+        let device = std::sync::Arc::new(VirtualDevice::new());
+        let pusher = device.clone();
+        tokio::spawn(async move { pusher.push_input(InputEvent::ButtonUp(3)) });
+        let event = tokio::time::timeout(std::time::Duration::from_secs(1), device.next_input());
+        assert!(matches!(event.await, Ok(InputEvent::ButtonUp(3))));
+    }
+}
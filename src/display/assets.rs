@@ -0,0 +1,120 @@
+//! Uploaded button images, stored in `~/.config/claude-deck/assets/`
+//!
+//! A button's `custom_image` can be `asset://<id>` instead of a base64 data
+//! URL, keeping config.toml small. Ids are derived from the image contents,
+//! so uploading the same image twice gives the same id.
+
+use anyhow::{bail, Context, Result};
+use image::{ImageFormat, RgbaImage};
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+use tracing::warn;
+
+/// `custom_image` prefix referring to a stored asset
+pub const ASSET_SCHEME: &str = "asset://";
+
+/// Largest image accepted as an asset
+pub const MAX_ASSET_BYTES: usize = 5_000_000;
+
+/// `~/.config/claude-deck/assets`
+pub fn assets_dir() -> Result<PathBuf> {
+    let home = std::env::var("HOME").context("HOME environment variable not set")?;
+    Ok(PathBuf::from(home).join(".config/claude-deck/assets"))
+}
+
+/// Store a PNG or JPEG image in `dir`, returning its id (e.g. "3f9a…c1.png")
+pub fn save(dir: &Path, bytes: &[u8]) -> Result<String> {
+    if bytes.len() > MAX_ASSET_BYTES {
+        bail!("Image is larger than {} MB", MAX_ASSET_BYTES / 1_000_000);
+    }
+    let extension = match image::guess_format(bytes) {
+        Ok(ImageFormat::Png) => "png",
+        Ok(ImageFormat::Jpeg) => "jpg",
+        _ => bail!("Only PNG and JPEG images can be uploaded"),
+    };
+    // Reject files that only look like images
+    image::load_from_memory(bytes).context("Failed to decode image")?;
+
+    let digest = Sha256::digest(bytes);
+    let hash: String = digest[..8].iter().map(|b| format!("{:02x}", b)).collect();
+    let id = format!("{}.{}", hash, extension);
+    std::fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+    let path = dir.join(&id);
+    std::fs::write(&path, bytes).with_context(|| format!("Failed to write {}", path.display()))?;
+    Ok(id)
+}
+
+/// Path of an asset, refusing ids that aren't ones `save` hands out
+pub fn path(dir: &Path, id: &str) -> Result<PathBuf> {
+    let valid = id.split_once('.').is_some_and(|(hash, extension)| {
+        !hash.is_empty()
+            && hash.chars().all(|c| c.is_ascii_hexdigit())
+            && matches!(extension, "png" | "jpg")
+    });
+    if !valid {
+        bail!("Invalid asset id '{}'", id);
+    }
+    Ok(dir.join(id))
+}
+
+/// Ids and sizes of stored assets, sorted by id
+pub fn list(dir: &Path) -> Result<Vec<(String, u64)>> {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e).with_context(|| format!("Failed to read {}", dir.display())),
+    };
+    let mut assets: Vec<(String, u64)> = entries
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let id = entry.file_name().to_string_lossy().to_string();
+            let size = entry.metadata().ok()?.len();
+            path(dir, &id).is_ok().then_some((id, size))
+        })
+        .collect();
+    assets.sort();
+    Ok(assets)
+}
+
+/// Load a `custom_image`: an `asset://` reference or a base64 data URL
+pub fn load_custom_image(custom_image: &str) -> Option<RgbaImage> {
+    let Some(id) = custom_image.strip_prefix(ASSET_SCHEME) else {
+        return super::emoji::load_base64_image(custom_image);
+    };
+    let loaded = assets_dir()
+        .and_then(|dir| path(&dir, id))
+        .and_then(|path| Ok(image::open(&path)?.to_rgba8()));
+    match loaded {
+        Ok(img) => Some(img),
+        Err(e) => {
+            warn!("Failed to load asset '{}': {}", id, e);
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_save_list_and_validate() {
+        let dir = std::env::temp_dir().join(format!("claude-deck-assets-{}", std::process::id()));
+        let mut png = Vec::new();
+        RgbaImage::new(2, 2)
+            .write_to(&mut std::io::Cursor::new(&mut png), ImageFormat::Png)
+            .unwrap();
+
+        let id = save(&dir, &png).unwrap();
+        assert!(id.ends_with(".png"));
+        assert_eq!(save(&dir, &png).unwrap(), id, "same image, same id");
+        assert!(save(&dir, b"GIF89a").is_err());
+        assert_eq!(list(&dir).unwrap(), vec![(id.clone(), png.len() as u64)]);
+
+        assert!(path(&dir, &id).is_ok());
+        assert!(path(&dir, "../config.toml").is_err());
+        assert!(path(&dir, "abc.gif").is_err());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,70 @@
+//! Amber glow pulsed across the deck while Claude waits for input
+//! (`[attention] glow`)
+//!
+//! The glow is an amber band around the edge of every button and the strip.
+//! Its strength follows a slow pulse in a few steps, so the deck is redrawn
+//! only when the step changes.
+
+use image::{Rgb, RgbImage};
+use std::time::Duration;
+
+/// One rise and fall of the glow
+const PULSE_PERIOD: Duration = Duration::from_millis(1600);
+/// Distinct glow strengths per half pulse
+const PULSE_STEPS: u32 = 6;
+const GLOW_COLOR: Rgb<u8> = Rgb([255, 150, 20]);
+/// Width of the band, as a fraction of the image's shorter side
+const GLOW_WIDTH: f32 = 0.12;
+
+/// Glow strength (0-255) `elapsed` into waiting, peaking at `intensity` percent
+pub fn glow_level(elapsed: Duration, intensity: u8) -> u8 {
+    let phase = (elapsed.as_millis() % PULSE_PERIOD.as_millis()) as f32
+        / PULSE_PERIOD.as_millis() as f32;
+    let pulse = 0.5 - 0.5 * (phase * std::f32::consts::TAU).cos();
+    // Never fully dark while waiting, so the first step is always visible
+    let step = ((pulse * PULSE_STEPS as f32).round() as u32).max(1);
+    (step * 255 * intensity.min(100) as u32 / (PULSE_STEPS * 100)) as u8
+}
+
+/// Blend the glow band into an image at `level` (0 leaves it unchanged)
+pub fn apply_glow(image: &mut RgbImage, level: u8) {
+    if level == 0 {
+        return;
+    }
+    let (width, height) = image.dimensions();
+    let band = (width.min(height) as f32 * GLOW_WIDTH).max(1.0);
+    for (x, y, pixel) in image.enumerate_pixels_mut() {
+        let edge = x.min(y).min(width - 1 - x).min(height - 1 - y) as f32;
+        if edge >= band {
+            continue;
+        }
+        // Strongest at the edge, fading out across the band
+        let alpha = level as f32 / 255.0 * (1.0 - edge / band);
+        for (channel, glow) in pixel.0.iter_mut().zip(GLOW_COLOR.0) {
+            *channel = (*channel as f32 * (1.0 - alpha) + glow as f32 * alpha) as u8;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_glow() {
+        let peak = glow_level(PULSE_PERIOD / 2, 100);
+        assert_eq!(peak, 255);
+        assert!(glow_level(Duration::ZERO, 100) > 0);
+        assert!(glow_level(Duration::ZERO, 100) < peak);
+        assert_eq!(glow_level(PULSE_PERIOD / 2, 50), 127);
+        assert_eq!(glow_level(PULSE_PERIOD / 2, 0), 0);
+
+        let mut image = RgbImage::from_pixel(100, 100, Rgb([0, 0, 0]));
+        apply_glow(&mut image, 255);
+        assert_eq!(*image.get_pixel(0, 50), GLOW_COLOR);
+        assert_eq!(*image.get_pixel(50, 50), Rgb([0, 0, 0]));
+        let mut unchanged = RgbImage::from_pixel(10, 10, Rgb([9, 9, 9]));
+        apply_glow(&mut unchanged, 0);
+        assert_eq!(*unchanged.get_pixel(0, 0), Rgb([9, 9, 9]));
+    }
+}
@@ -5,19 +5,23 @@ use std::collections::HashMap;
 use std::sync::Mutex;
 
 use super::renderer::{button_colors, draw_text, text_width, WHITE};
+use super::theme;
 use crate::device::{BUTTON_HEIGHT, BUTTON_WIDTH};
 use crate::profiles::ButtonConfig;
 
-/// Cache for button backgrounds (gradient + border) keyed by color
+/// Background cache key: theme name and button color
+type BackgroundKey = (&'static str, u8, u8, u8);
+
+/// Cache for button backgrounds (gradient + border) keyed by theme and color
 /// Stores raw pixel data to enable fast memcpy instead of clone
-static BACKGROUND_CACHE: std::sync::OnceLock<Mutex<HashMap<(u8, u8, u8), Vec<u8>>>> =
+static BACKGROUND_CACHE: std::sync::OnceLock<Mutex<HashMap<BackgroundKey, Vec<u8>>>> =
     std::sync::OnceLock::new();
 
 /// Get or create a button with cached background for the given base color
 /// Returns a new image with the background already rendered (fast memcpy)
 fn get_button_with_background(base_color: Rgb<u8>) -> RgbImage {
     let cache = BACKGROUND_CACHE.get_or_init(|| Mutex::new(HashMap::new()));
-    let key = (base_color[0], base_color[1], base_color[2]);
+    let key = (theme::current().name, base_color[0], base_color[1], base_color[2]);
 
     if let Ok(mut guard) = cache.lock() {
         if let Some(raw_data) = guard.get(&key) {
@@ -25,7 +29,7 @@ fn get_button_with_background(base_color: Rgb<u8>) -> RgbImage {
             return RgbImage::from_raw(BUTTON_WIDTH, BUTTON_HEIGHT, raw_data.clone())
                 .unwrap_or_else(|| {
                     let mut img = RgbImage::new(BUTTON_WIDTH, BUTTON_HEIGHT);
-                    fill_gradient(&mut img, darken(base_color, 0.4), darken(base_color, 0.6));
+                    fill_resting_gradient(&mut img, base_color);
                     draw_styled_border(&mut img, base_color, false);
                     img
                 });
@@ -33,7 +37,7 @@ fn get_button_with_background(base_color: Rgb<u8>) -> RgbImage {
 
         // Create new background and cache raw bytes
         let mut img = RgbImage::new(BUTTON_WIDTH, BUTTON_HEIGHT);
-        fill_gradient(&mut img, darken(base_color, 0.4), darken(base_color, 0.6));
+        fill_resting_gradient(&mut img, base_color);
         draw_styled_border(&mut img, base_color, false);
 
         guard.insert(key, img.as_raw().clone());
@@ -41,7 +45,7 @@ fn get_button_with_background(base_color: Rgb<u8>) -> RgbImage {
     } else {
         // Fallback if lock fails - create without caching
         let mut img = RgbImage::new(BUTTON_WIDTH, BUTTON_HEIGHT);
-        fill_gradient(&mut img, darken(base_color, 0.4), darken(base_color, 0.6));
+        fill_resting_gradient(&mut img, base_color);
         draw_styled_border(&mut img, base_color, false);
         img
     }
@@ -62,49 +66,111 @@ pub fn render_button_image(
     if active {
         fill_gradient(&mut img, bright_color, base_color);
     } else {
-        fill_gradient(&mut img, darken(base_color, 0.4), darken(base_color, 0.6));
+        fill_resting_gradient(&mut img, base_color);
     }
 
     // Draw colored border (thicker on top for 3D effect)
     let border_color = if active { bright_color } else { base_color };
     draw_styled_border(&mut img, border_color, active);
 
-    // Calculate text positioning
-    let label_scale = if label.len() <= 4 {
-        20.0
-    } else if label.len() <= 6 {
-        16.0
-    } else {
-        13.0
-    };
-    let label_width = text_width(font, label, label_scale);
-    let label_x = ((BUTTON_WIDTH as i32 - label_width) / 2).max(2);
-    let label_y = (BUTTON_HEIGHT as i32 / 2) - (label_scale as i32 / 2);
-
     // Draw text with slight shadow for depth
-    let text_color = if active { WHITE } else { Rgb([220, 220, 230]) };
-    draw_text(
-        &mut img,
-        font,
-        label,
-        label_x + 1,
-        label_y + 1,
-        label_scale,
-        Rgb([0, 0, 0]),
-    ); // shadow
-    draw_text(
-        &mut img,
-        font,
-        label,
-        label_x,
-        label_y,
-        label_scale,
-        text_color,
-    );
+    let (text_color, shadow) = label_colors(active);
+    draw_label(&mut img, font, label, text_color, shadow);
 
     Ok(img)
 }
 
+/// Largest and smallest label text size
+const LABEL_MAX_SCALE: f32 = 20.0;
+const LABEL_MIN_SCALE: f32 = 9.0;
+/// Most lines a label wraps onto
+const LABEL_MAX_LINES: usize = 3;
+/// Space kept clear between a label and the button's edges
+const LABEL_PADDING: i32 = 6;
+
+/// A label broken into lines, and the size they fit at
+#[derive(Debug, Clone, PartialEq)]
+pub struct LabelLayout {
+    pub lines: Vec<String>,
+    pub scale: f32,
+}
+
+impl LabelLayout {
+    /// Distance between line tops
+    fn line_height(&self) -> i32 {
+        (self.scale * 1.15).round() as i32
+    }
+}
+
+/// Break a label into at most three lines at the largest size that fits
+///
+/// Words wrap where needed, and a newline (or a typed `\n`, as the web UI's
+/// single-line field sends it) always starts a new line. Text too long even
+/// at the smallest size is clipped after the third line.
+pub fn layout_label(font: &Font, label: &str) -> LabelLayout {
+    let label = label.replace("\\n", "\n");
+    let paragraphs: Vec<&str> = label.split('\n').map(str::trim).collect();
+    let max_width = BUTTON_WIDTH as i32 - 2 * LABEL_PADDING;
+    let max_height = BUTTON_HEIGHT as i32 - 2 * LABEL_PADDING;
+
+    let mut scale = LABEL_MAX_SCALE;
+    loop {
+        let lines: Vec<String> =
+            paragraphs.iter().flat_map(|p| wrap_words(font, p, scale, max_width)).collect();
+        let layout = LabelLayout { lines, scale };
+        let fits = layout.lines.len() <= LABEL_MAX_LINES
+            && layout.line_height() * (layout.lines.len() as i32 - 1) + scale as i32 <= max_height
+            && layout.lines.iter().all(|line| text_width(font, line, scale) <= max_width);
+        if fits || scale <= LABEL_MIN_SCALE {
+            let mut layout = layout;
+            layout.lines.truncate(LABEL_MAX_LINES);
+            return layout;
+        }
+        scale -= 1.0;
+    }
+}
+
+/// Greedily fill lines with whole words (a word wider than a line gets its own)
+pub(super) fn wrap_words(font: &Font, text: &str, scale: f32, max_width: i32) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    let mut line = String::new();
+    for word in text.split_whitespace() {
+        let candidate = if line.is_empty() {
+            word.to_string()
+        } else {
+            format!("{} {}", line, word)
+        };
+        if line.is_empty() || text_width(font, &candidate, scale) <= max_width {
+            line = candidate;
+        } else {
+            lines.push(std::mem::replace(&mut line, word.to_string()));
+        }
+    }
+    lines.push(line);
+    lines
+}
+
+/// Draw a label centered on a button, each line over a 1px shadow
+fn draw_label(img: &mut RgbImage, font: &Font, label: &str, color: Rgb<u8>, shadow: Rgb<u8>) {
+    let layout = layout_label(font, label);
+    let line_height = layout.line_height();
+    let block_height = line_height * (layout.lines.len() as i32 - 1) + layout.scale as i32;
+    let top = (BUTTON_HEIGHT as i32 - block_height) / 2;
+    for (i, line) in layout.lines.iter().enumerate() {
+        let width = text_width(font, line, layout.scale);
+        let x = ((BUTTON_WIDTH as i32 - width) / 2).max(2);
+        let y = top + i as i32 * line_height;
+        draw_text(img, font, line, x + 1, y + 1, layout.scale, shadow);
+        draw_text(img, font, line, x, y, layout.scale, color);
+    }
+}
+
+/// Fill with the theme's gradient for a button at rest
+fn fill_resting_gradient(img: &mut RgbImage, color: Rgb<u8>) {
+    let (top, bottom) = theme::button_background(theme::current(), color);
+    fill_gradient(img, top, bottom);
+}
+
 /// Fill image with vertical gradient (top to bottom)
 fn fill_gradient(img: &mut RgbImage, top_color: Rgb<u8>, bottom_color: Rgb<u8>) {
     let h = img.height() as f32;
@@ -149,29 +215,8 @@ pub fn render_button_with_color(
     // Draw styled border
     draw_styled_border(&mut img, bright, active);
 
-    // Calculate text positioning
-    let label_scale = if label.len() <= 4 {
-        20.0
-    } else if label.len() <= 6 {
-        16.0
-    } else {
-        13.0
-    };
-    let label_width = text_width(font, label, label_scale);
-    let label_x = ((BUTTON_WIDTH as i32 - label_width) / 2).max(2);
-    let label_y = (BUTTON_HEIGHT as i32 / 2) - (label_scale as i32 / 2);
-
     // Draw text with shadow
-    draw_text(
-        &mut img,
-        font,
-        label,
-        label_x + 1,
-        label_y + 1,
-        label_scale,
-        Rgb([0, 0, 0]),
-    );
-    draw_text(&mut img, font, label, label_x, label_y, label_scale, WHITE);
+    draw_label(&mut img, font, label, WHITE, Rgb([0, 0, 0]));
 
     Ok(img)
 }
@@ -182,7 +227,7 @@ fn draw_styled_border(img: &mut RgbImage, color: Rgb<u8>, active: bool) {
     let h = img.height();
 
     // Outer dark border
-    let dark = Rgb([20, 20, 30]);
+    let dark = theme::current().border;
     for x in 0..w {
         img.put_pixel(x, 0, dark);
         img.put_pixel(x, h - 1, dark);
@@ -224,6 +269,15 @@ fn draw_styled_border(img: &mut RgbImage, color: Rgb<u8>, active: bool) {
     }
 }
 
+/// Label and shadow colors: white on lit buttons, the theme's otherwise
+fn label_colors(active: bool) -> (Rgb<u8>, Rgb<u8>) {
+    if active {
+        (WHITE, Rgb([0, 0, 0]))
+    } else {
+        theme::current().button_text
+    }
+}
+
 fn brighten(color: Rgb<u8>, factor: f32) -> Rgb<u8> {
     Rgb([
         (color[0] as f32 * factor).min(255.0) as u8,
@@ -325,6 +379,22 @@ fn render_presized_image_on_button(img: &mut RgbImage, source: &image::RgbaImage
     }
 }
 
+/// Alpha-blend a centered image onto the button, keeping antialiased edges
+fn blend_image_on_button(img: &mut RgbImage, source: &image::RgbaImage) {
+    let x_offset = (BUTTON_WIDTH - source.width()) / 2;
+    let y_offset = (BUTTON_HEIGHT - source.height()) / 2;
+    for (x, y, pixel) in source.enumerate_pixels() {
+        let alpha = pixel[3] as u32;
+        if alpha == 0 {
+            continue;
+        }
+        let dst = img.get_pixel_mut(x + x_offset, y + y_offset);
+        for c in 0..3 {
+            dst[c] = ((pixel[c] as u32 * alpha + dst[c] as u32 * (255 - alpha)) / 255) as u8;
+        }
+    }
+}
+
 /// Render a button with profile-specific configuration
 pub fn render_button_with_config(
     font: &Font,
@@ -367,14 +437,16 @@ pub fn render_button_with_config_and_id(
     if active {
         fill_gradient(&mut img, bright_color, base_color);
     } else {
-        fill_gradient(&mut img, darken(base_color, 0.4), darken(base_color, 0.6));
+        fill_resting_gradient(&mut img, base_color);
     }
 
     // Draw colored border (thicker on top for 3D effect)
     let border_color = if active { bright_color } else { base_color };
     draw_styled_border(&mut img, border_color, active);
 
-    // Priority: gif_url > custom_image > emoji_image > text label
+    let (text_color, shadow) = label_colors(active);
+
+    // Priority: gif_url > custom_image > icon_svg > emoji_image > text label
     let image_rendered = if let Some(gif_url) = config.gif_url {
         // GIF from URL - use animated frame if available
         let mut frame_found = false;
@@ -406,13 +478,21 @@ pub fn render_button_with_config_and_id(
 
         frame_found
     } else if let Some(custom_image) = config.custom_image {
-        // Custom image from base64 data URL
-        if let Some(rgba_img) = super::emoji::load_base64_image(custom_image) {
+        // Custom image from an uploaded asset or base64 data URL
+        if let Some(rgba_img) = super::assets::load_custom_image(custom_image) {
             render_image_on_button(&mut img, &rgba_img);
             true
         } else {
             false
         }
+    } else if let Some(icon_svg) = config.icon_svg {
+        // SVG icon, drawn in the label color
+        if let Some(icon) = super::svg::render(icon_svg, text_color) {
+            blend_image_on_button(&mut img, &icon);
+            true
+        } else {
+            false
+        }
     } else if let Some(emoji_ref) = config.emoji_image {
         // Emoji from Twemoji
         if let Some(emoji_img) = super::emoji::get_emoji_image(emoji_ref) {
@@ -427,38 +507,8 @@ pub fn render_button_with_config_and_id(
 
     if !image_rendered {
         // Render text label if no emoji image
-        let label = config.label;
-        let label_scale = if label.len() <= 4 {
-            20.0
-        } else if label.len() <= 6 {
-            16.0
-        } else {
-            13.0
-        };
-        let label_width = text_width(font, label, label_scale);
-        let label_x = ((BUTTON_WIDTH as i32 - label_width) / 2).max(2);
-        let label_y = (BUTTON_HEIGHT as i32 / 2) - (label_scale as i32 / 2);
-
         // Draw text with slight shadow for depth
-        let text_color = if active { WHITE } else { Rgb([220, 220, 230]) };
-        draw_text(
-            &mut img,
-            font,
-            label,
-            label_x + 1,
-            label_y + 1,
-            label_scale,
-            Rgb([0, 0, 0]),
-        ); // shadow
-        draw_text(
-            &mut img,
-            font,
-            label,
-            label_x,
-            label_y,
-            label_scale,
-            text_color,
-        );
+        draw_label(&mut img, font, config.label, text_color, shadow);
     }
 
     Ok(img)
@@ -483,7 +533,7 @@ pub fn render_mic_button(
     if active || recording {
         fill_gradient(&mut img, bright_color, base_color);
     } else {
-        fill_gradient(&mut img, darken(base_color, 0.4), darken(base_color, 0.6));
+        fill_resting_gradient(&mut img, base_color);
     }
 
     // Draw styled border
@@ -495,12 +545,7 @@ pub fn render_mic_button(
     draw_styled_border(&mut img, border_color, active || recording);
 
     // Draw microphone icon
-    let icon_color = if active || recording {
-        WHITE
-    } else {
-        Rgb([220, 220, 230])
-    };
-    let shadow_color = Rgb([0, 0, 0]);
+    let (icon_color, shadow_color) = label_colors(active || recording);
 
     draw_mic_icon(&mut img, shadow_color, 1, 1, recording); // Shadow
     draw_mic_icon(&mut img, icon_color, 0, 0, recording); // Icon
@@ -629,4 +674,25 @@ mod tests {
         assert_eq!(img.width(), BUTTON_WIDTH);
         assert_eq!(img.height(), BUTTON_HEIGHT);
     }
+
+    #[test]
+    fn test_layout_label() {
+        let font_data = include_bytes!("../../assets/fonts/JetBrainsMono-Bold.ttf");
+        let font = Font::try_from_bytes(font_data as &[u8]).unwrap();
+
+        let short = layout_label(&font, "GO");
+        assert_eq!((short.lines.len(), short.scale), (1, LABEL_MAX_SCALE));
+
+        let wrapped = layout_label(&font, "DEPLOY TO STAGING");
+        assert!(wrapped.lines.len() > 1 && wrapped.lines.len() <= LABEL_MAX_LINES);
+        assert!(wrapped.lines.iter().all(|line| !line.starts_with(' ')));
+
+        // Typed and real newlines both break, however short the lines
+        assert_eq!(layout_label(&font, "A\\nB").lines, vec!["A", "B"]);
+        assert_eq!(layout_label(&font, "A\nB\nC\nD").lines.len(), LABEL_MAX_LINES);
+
+        let long = layout_label(&font, "SUPERCALIFRAGILISTIC");
+        assert!(long.scale < LABEL_MAX_SCALE);
+        assert_eq!(long.lines.len(), 1);
+    }
 }
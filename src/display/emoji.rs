@@ -1,12 +1,35 @@
 //! Emoji image fetching and caching using Twemoji CDN
+//!
+//! The Slack reactions (`assets/emoji/curated.txt`) are bundled in the
+//! binary. Anything else comes from
+//! `~/.config/claude-deck/emoji-cache/`, and an emoji that isn't there yet is
+//! fetched in the background while its button shows the label, so rendering
+//! never waits on the network. `claude-deck --prefetch-emoji` fills the cache
+//! ahead of time for offline use.
 
 use anyhow::{Context, Result};
 use image::RgbaImage;
+use std::collections::HashSet;
 use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, OnceLock};
 use tracing::{debug, info, warn};
 
 const TWEMOJI_CDN: &str = "https://cdn.jsdelivr.net/gh/twitter/twemoji@latest/assets/72x72";
 
+/// Emoji embedded in the binary, by codepoint (every PNG in `assets/emoji`, see build.rs)
+const BUNDLED: &[(&str, &[u8])] = include!(concat!(env!("OUT_DIR"), "/emoji_bundle.rs"));
+
+/// Codepoints fetched (or being fetched) in the background since startup,
+/// so a missing emoji is only requested once
+fn requested() -> &'static Mutex<HashSet<String>> {
+    static REQUESTED: OnceLock<Mutex<HashSet<String>>> = OnceLock::new();
+    REQUESTED.get_or_init(Default::default)
+}
+
+/// Set when a background fetch lands in the cache
+static FETCHED: AtomicBool = AtomicBool::new(false);
+
 /// Get the emoji cache directory
 fn cache_dir() -> Result<PathBuf> {
     let home = std::env::var("HOME").context("HOME environment variable not set")?;
@@ -36,42 +59,107 @@ pub fn is_codepoint(s: &str) -> bool {
     !s.is_empty() && s.chars().all(|c| c.is_ascii_hexdigit() || c == '-')
 }
 
-/// Get an emoji image, fetching from CDN if not cached
+/// Twemoji codepoint of an emoji reference
 ///
 /// `emoji_ref` can be:
 /// - An emoji character: "😀"
 /// - A codepoint: "1f600"
-/// - A legacy image name: "thumbsup" (falls back to assets/emoji/)
-pub fn get_emoji_image(emoji_ref: &str) -> Option<RgbaImage> {
-    // Determine if this is an emoji, codepoint, or legacy name
-    let codepoint = if is_emoji(emoji_ref) {
-        emoji_to_codepoint(emoji_ref)
+/// - A legacy image name: "thumbsup"
+fn reference_codepoint(emoji_ref: &str) -> Option<String> {
+    if is_emoji(emoji_ref) {
+        Some(emoji_to_codepoint(emoji_ref))
     } else if is_codepoint(emoji_ref) {
-        emoji_ref.to_lowercase()
+        Some(emoji_ref.to_lowercase())
     } else {
-        // Legacy: try to load from assets/emoji/{name}.png
-        return load_legacy_emoji(emoji_ref);
-    };
+        legacy_name_to_emoji(emoji_ref).map(emoji_to_codepoint)
+    }
+}
 
-    // Try to load from cache
-    if let Some(img) = load_cached_emoji(&codepoint) {
+/// Get an emoji image from the bundle or the cache, without blocking
+///
+/// An emoji in neither is fetched from the CDN in the background and None is
+/// returned for now; `take_fetched` reports when it's ready to redraw.
+pub fn get_emoji_image(emoji_ref: &str) -> Option<RgbaImage> {
+    let Some(codepoint) = reference_codepoint(emoji_ref) else {
+        warn!("Unknown legacy emoji name: {}", emoji_ref);
+        return None;
+    };
+    if let Some(img) = load_bundled_emoji(&codepoint).or_else(|| load_cached_emoji(&codepoint)) {
         return Some(img);
     }
+    fetch_in_background(codepoint);
+    None
+}
+
+/// Whether a background fetch finished since the last call
+pub fn take_fetched() -> bool {
+    FETCHED.swap(false, Ordering::Relaxed)
+}
+
+fn fetch_in_background(codepoint: String) {
+    if !requested().lock().unwrap_or_else(|e| e.into_inner()).insert(codepoint.clone()) {
+        return;
+    }
+    std::thread::spawn(move || match fetch_and_cache_emoji(&codepoint) {
+        Ok(_) => FETCHED.store(true, Ordering::Relaxed),
+        Err(e) => warn!("Failed to fetch emoji {}: {}", codepoint, e),
+    });
+}
 
-    // Fetch from CDN (blocking - we're in sync context)
-    match fetch_and_cache_emoji(&codepoint) {
-        Ok(img) => Some(img),
-        Err(e) => {
-            warn!("Failed to fetch emoji {}: {}", codepoint, e);
-            None
+/// What `prefetch` found for a set of emoji
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PrefetchReport {
+    /// Already bundled or cached
+    pub present: usize,
+    pub fetched: usize,
+    /// References that couldn't be fetched or aren't emoji
+    pub failed: Vec<String>,
+}
+
+/// Download any of these emoji that are neither bundled nor cached (blocking)
+pub fn prefetch<'a>(emoji_refs: impl IntoIterator<Item = &'a str>) -> PrefetchReport {
+    let mut report = PrefetchReport::default();
+    let mut seen = HashSet::new();
+    for emoji_ref in emoji_refs {
+        let Some(codepoint) = reference_codepoint(emoji_ref) else {
+            report.failed.push(emoji_ref.to_string());
+            continue;
+        };
+        if !seen.insert(codepoint.clone()) {
+            continue;
+        }
+        if is_bundled(&codepoint) || cached_path(&codepoint).is_some_and(|path| path.exists()) {
+            report.present += 1;
+            continue;
+        }
+        match fetch_and_cache_emoji(&codepoint) {
+            Ok(_) => report.fetched += 1,
+            Err(e) => {
+                warn!("Failed to fetch emoji {}: {:#}", emoji_ref, e);
+                report.failed.push(emoji_ref.to_string());
+            }
         }
     }
+    report
+}
+
+fn is_bundled(codepoint: &str) -> bool {
+    BUNDLED.iter().any(|(bundled, _)| *bundled == codepoint)
+}
+
+/// Decode a bundled emoji
+fn load_bundled_emoji(codepoint: &str) -> Option<RgbaImage> {
+    let (_, data) = BUNDLED.iter().find(|(bundled, _)| *bundled == codepoint)?;
+    image::load_from_memory(data).ok().map(|img| img.to_rgba8())
+}
+
+fn cached_path(codepoint: &str) -> Option<PathBuf> {
+    Some(cache_dir().ok()?.join(format!("{}.png", codepoint)))
 }
 
 /// Load emoji from local cache
 fn load_cached_emoji(codepoint: &str) -> Option<RgbaImage> {
-    let cache_path = cache_dir().ok()?;
-    let file_path = cache_path.join(format!("{}.png", codepoint));
+    let file_path = cached_path(codepoint)?;
 
     if file_path.exists() {
         debug!("Loading cached emoji: {}", codepoint);
@@ -160,29 +248,6 @@ fn legacy_name_to_emoji(name: &str) -> Option<&'static str> {
     }
 }
 
-/// Load legacy emoji by converting name to emoji and fetching from Twemoji
-fn load_legacy_emoji(name: &str) -> Option<RgbaImage> {
-    // Convert legacy name to emoji character
-    if let Some(emoji) = legacy_name_to_emoji(name) {
-        debug!("Converting legacy emoji '{}' to '{}'", name, emoji);
-        let codepoint = emoji_to_codepoint(emoji);
-
-        // Try cache first
-        if let Some(img) = load_cached_emoji(&codepoint) {
-            return Some(img);
-        }
-
-        // Fetch from CDN
-        match fetch_and_cache_emoji(&codepoint) {
-            Ok(img) => return Some(img),
-            Err(e) => warn!("Failed to fetch emoji for legacy '{}': {}", name, e),
-        }
-    }
-
-    warn!("Unknown legacy emoji name: {}", name);
-    None
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -203,6 +268,46 @@ mod tests {
         assert!(!is_emoji("1f600"));
     }
 
+    #[test]
+    fn test_bundled_emoji() {
+        // Every legacy name and its emoji render offline
+        let names = [
+            "thumbsup", "thumbsdown", "check", "eyes", "tada", "heart", "joy", "fire", "hundred",
+            "pray",
+        ];
+        for name in names {
+            let codepoint = reference_codepoint(name).unwrap();
+            assert!(load_bundled_emoji(&codepoint).is_some(), "{} isn't bundled", name);
+        }
+        assert_eq!(reference_codepoint("❤️").as_deref(), Some("2764"));
+        assert_eq!(reference_codepoint("1F600").as_deref(), Some("1f600"));
+        assert_eq!(reference_codepoint("smile"), None);
+
+        let report = prefetch(["👍", "thumbsup", "smile"]);
+        assert_eq!(report.present, 1);
+        assert_eq!(report.failed, ["smile"]);
+
+        // A truncated download would only show up as a missing image
+        for (codepoint, _) in BUNDLED {
+            assert!(load_bundled_emoji(codepoint).is_some(), "{}.png doesn't decode", codepoint);
+        }
+    }
+
+    #[test]
+    fn test_curated_list() {
+        let curated = include_str!("../../assets/emoji/curated.txt");
+        let mut seen = HashSet::new();
+        for line in curated.lines().filter(|l| !l.is_empty() && !l.starts_with('#')) {
+            let codepoint = line.split_whitespace().next().unwrap();
+            assert!(is_codepoint(codepoint), "bad codepoint {:?}", line);
+            assert!(seen.insert(codepoint), "{} is listed twice", codepoint);
+        }
+
+        // The list is exactly what's bundled, so it never promises a missing image
+        let bundled: HashSet<_> = BUNDLED.iter().map(|(codepoint, _)| *codepoint).collect();
+        assert_eq!(seen, bundled, "curated.txt and the PNGs in assets/emoji differ");
+    }
+
     #[test]
     fn test_is_codepoint() {
         assert!(is_codepoint("1f600"));
@@ -1,6 +1,7 @@
 //! GIF animation support for button displays
 
 use image::{imageops::FilterType, RgbaImage};
+use serde::Serialize;
 use std::collections::{HashMap, HashSet};
 use std::io::Read;
 use std::sync::{Arc, Mutex};
@@ -10,6 +11,9 @@ use tracing::{debug, warn};
 /// Target size for pre-resized GIF frames (buttons are 112x112, image area is 90x90)
 const FRAME_SIZE: u32 = 90;
 
+/// Decoded frame memory kept by default (`[device] gif_cache_mb`)
+const DEFAULT_CACHE_BUDGET: usize = 64 * 1024 * 1024;
+
 /// A single frame from a GIF with its display duration
 /// Frames are pre-resized to FRAME_SIZE and wrapped in Arc for zero-copy sharing
 #[derive(Clone)]
@@ -25,6 +29,32 @@ pub struct CachedGif {
     pub total_duration: Duration,
 }
 
+impl CachedGif {
+    /// Memory held by the decoded frames
+    pub fn bytes(&self) -> usize {
+        self.frames.iter().map(|f| f.image.as_raw().len()).sum()
+    }
+}
+
+/// A cache slot: the GIF (None if it failed to load) and when it was last used
+struct CacheEntry {
+    gif: Option<CachedGif>,
+    bytes: usize,
+    last_used: u64,
+}
+
+/// GIF cache usage, for `/api/cache/stats`
+#[derive(Debug, Clone, Serialize)]
+pub struct GifCacheStats {
+    /// Loaded GIFs (failed loads not counted)
+    pub gifs: usize,
+    pub frames: usize,
+    pub bytes: usize,
+    pub budget_bytes: usize,
+    /// GIFs dropped to stay under the budget since startup
+    pub evictions: u64,
+}
+
 /// Animation state for a single button
 struct ButtonAnimation {
     gif_url: String,
@@ -43,11 +73,18 @@ pub struct TickResult {
 /// Manages GIF animations for buttons
 pub struct GifAnimator {
     /// Cache of loaded GIFs by URL
-    gif_cache: HashMap<String, Option<CachedGif>>,
+    gif_cache: HashMap<String, CacheEntry>,
     /// Current animation state per button
     animations: HashMap<u8, ButtonAnimation>,
     /// URLs currently being loaded in background
     loading: HashSet<String>,
+    /// Frame memory allowed before least recently used GIFs are dropped
+    budget: usize,
+    /// Bytes held by cached frames
+    cached_bytes: usize,
+    /// Counter stamped on cache entries when used (LRU order)
+    clock: u64,
+    evictions: u64,
 }
 
 impl GifAnimator {
@@ -56,6 +93,27 @@ impl GifAnimator {
             gif_cache: HashMap::new(),
             animations: HashMap::new(),
             loading: HashSet::new(),
+            budget: DEFAULT_CACHE_BUDGET,
+            cached_bytes: 0,
+            clock: 0,
+            evictions: 0,
+        }
+    }
+
+    /// Set the frame memory budget, evicting straight away if over it
+    pub fn set_budget(&mut self, bytes: usize) {
+        self.budget = bytes;
+        self.evict();
+    }
+
+    pub fn stats(&self) -> GifCacheStats {
+        let loaded = || self.gif_cache.values().filter_map(|e| e.gif.as_ref());
+        GifCacheStats {
+            gifs: loaded().count(),
+            frames: loaded().map(|gif| gif.frames.len()).sum(),
+            bytes: self.cached_bytes,
+            budget_bytes: self.budget,
+            evictions: self.evictions,
         }
     }
 
@@ -77,7 +135,43 @@ impl GifAnimator {
     /// Store a loaded GIF in the cache (called from background task)
     pub fn store_loaded_gif(&mut self, url: String, gif: Option<CachedGif>) {
         self.loading.remove(&url);
-        self.gif_cache.insert(url, gif);
+        self.clock += 1;
+        let bytes = gif.as_ref().map_or(0, CachedGif::bytes);
+        let entry = CacheEntry {
+            gif,
+            bytes,
+            last_used: self.clock,
+        };
+        self.cached_bytes += bytes;
+        if let Some(old) = self.gif_cache.insert(url, entry) {
+            self.cached_bytes -= old.bytes;
+        }
+        self.evict();
+    }
+
+    /// Drop least recently used GIFs until under budget
+    ///
+    /// GIFs a button is showing are kept even if that leaves the cache over
+    /// budget. Failed loads hold no frames and are kept so they aren't retried.
+    fn evict(&mut self) {
+        while self.cached_bytes > self.budget {
+            let showing: HashSet<&str> =
+                self.animations.values().map(|a| a.gif_url.as_str()).collect();
+            let oldest = self
+                .gif_cache
+                .iter()
+                .filter(|(url, entry)| entry.bytes > 0 && !showing.contains(url.as_str()))
+                .min_by_key(|(_, entry)| entry.last_used)
+                .map(|(url, _)| url.clone());
+            let Some(url) = oldest else {
+                break;
+            };
+            if let Some(entry) = self.gif_cache.remove(&url) {
+                debug!("Evicting GIF {} ({} bytes)", url, entry.bytes);
+                self.cached_bytes -= entry.bytes;
+                self.evictions += 1;
+            }
+        }
     }
 
     /// Get URLs that need to be loaded for current animations
@@ -95,6 +189,10 @@ impl GifAnimator {
     /// Set up animation for a button with a GIF URL (non-blocking)
     /// GIF will be loaded in background - button renders without GIF until loaded
     pub fn set_button_gif(&mut self, button_id: u8, gif_url: &str) {
+        self.clock += 1;
+        if let Some(entry) = self.gif_cache.get_mut(gif_url) {
+            entry.last_used = self.clock;
+        }
         // Just register the animation - don't load synchronously
         self.animations.insert(
             button_id,
@@ -127,7 +225,7 @@ impl GifAnimator {
 
         for (&button_id, anim) in self.animations.iter_mut() {
             // Get the cached GIF
-            let cached = match self.gif_cache.get(&anim.gif_url).and_then(|o| o.as_ref()) {
+            let cached = match self.gif_cache.get(&anim.gif_url).and_then(|e| e.gif.as_ref()) {
                 Some(c) => c,
                 None => continue,
             };
@@ -164,10 +262,15 @@ impl GifAnimator {
     /// Get the current frame for a button's GIF animation
     pub fn get_current_frame(&self, button_id: u8) -> Option<&RgbaImage> {
         let anim = self.animations.get(&button_id)?;
-        let cached = self.gif_cache.get(&anim.gif_url)?.as_ref()?;
+        let cached = self.gif_cache.get(&anim.gif_url)?.gif.as_ref()?;
         cached.frames.get(anim.current_frame).map(|f| f.image.as_ref())
     }
 
+    /// Whether any button has an animation to tick
+    pub fn is_animating(&self) -> bool {
+        !self.animations.is_empty()
+    }
+
     /// Check if a button has an active animation
     pub fn has_animation(&self, button_id: u8) -> bool {
         self.animations.contains_key(&button_id)
@@ -179,11 +282,13 @@ impl GifAnimator {
 pub fn fetch_and_decode_gif(url: &str) -> Option<CachedGif> {
     debug!("Fetching GIF: {}", url);
 
-    // Fetch the GIF
-    let response = ureq::get(url).call().ok()?;
+    // Fetch the GIF (file:// URLs are local library GIFs, read from disk)
     let mut bytes = Vec::new();
-    response
-        .into_reader()
+    let reader: Box<dyn Read> = match url.strip_prefix("file://") {
+        Some(path) => Box::new(std::fs::File::open(path).ok()?),
+        None => ureq::get(url).call().ok()?.into_reader(),
+    };
+    reader
         .take(10_000_000) // 10MB limit
         .read_to_end(&mut bytes)
         .ok()?;
@@ -272,3 +377,45 @@ pub fn animator() -> Arc<Mutex<GifAnimator>> {
         .get_or_init(|| Arc::new(Mutex::new(GifAnimator::new())))
         .clone()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn gif(frames: usize) -> Option<CachedGif> {
+        let frame = GifFrame {
+            image: Arc::new(RgbaImage::new(FRAME_SIZE, FRAME_SIZE)),
+            delay: Duration::from_millis(100),
+        };
+        Some(CachedGif {
+            frames: vec![frame; frames],
+            total_duration: Duration::from_millis(100 * frames as u64),
+        })
+    }
+
+    #[test]
+    fn test_evicts_least_recently_used() {
+        let frame_bytes = (FRAME_SIZE * FRAME_SIZE * 4) as usize;
+        let mut animator = GifAnimator::new();
+        animator.set_budget(5 * frame_bytes);
+
+        animator.store_loaded_gif("a".into(), gif(2));
+        animator.store_loaded_gif("b".into(), gif(2));
+        animator.store_loaded_gif("broken".into(), None);
+        // Showing "a" makes "b" the least recently used
+        animator.set_button_gif(0, "a");
+        animator.store_loaded_gif("c".into(), gif(2));
+
+        assert!(animator.is_cached("a") && animator.is_cached("c"));
+        assert!(!animator.is_cached("b"));
+        assert!(animator.is_cached("broken"));
+        let stats = animator.stats();
+        assert_eq!((stats.gifs, stats.frames, stats.evictions), (2, 4, 1));
+        assert_eq!(stats.bytes, 4 * frame_bytes);
+
+        // A GIF on screen stays even when it alone is over budget
+        animator.set_budget(frame_bytes);
+        assert!(animator.is_cached("a") && !animator.is_cached("c"));
+        assert_eq!(animator.stats().bytes, 2 * frame_bytes);
+    }
+}
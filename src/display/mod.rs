@@ -1,10 +1,15 @@
+pub mod assets;
+pub mod attention;
 mod buttons;
 pub mod emoji;
 pub mod gif;
+pub mod preview;
 pub mod renderer;
+pub mod svg;
 mod strip;
+pub mod theme;
 
 pub use buttons::*;
-pub use gif::{animator as gif_animator, GifAnimator};
+pub use gif::{animator as gif_animator, GifAnimator, GifCacheStats};
 pub use renderer::DisplayRenderer;
 pub use strip::*;
@@ -0,0 +1,106 @@
+//! Headless rendering of a profile to PNG files (`--render-preview`)
+
+use anyhow::{anyhow, Context, Result};
+use image::{DynamicImage, ImageFormat, RgbImage};
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+
+use super::DisplayRenderer;
+use crate::config::Config;
+use crate::profiles::ProfileManager;
+use crate::state::AppState;
+
+/// Render a profile's 10 buttons and the strip into `out_dir`
+///
+/// Files are named `button-0.png` … `button-9.png` and `strip.png`; the paths
+/// written are returned in that order. The strip shows the idle state.
+pub fn render_preview(
+    config: &Config,
+    mut profiles: ProfileManager,
+    profile: &str,
+    out_dir: &Path,
+) -> Result<Vec<PathBuf>> {
+    if profiles.get_profile(profile).is_none() {
+        let names: Vec<&str> = profiles.get_profiles().iter().map(|p| p.name.as_str()).collect();
+        return Err(anyhow!(
+            "Unknown profile '{}' (available: {})",
+            profile,
+            names.join(", ")
+        ));
+    }
+    profiles.pin_profile(Some(profile.to_string()));
+
+    let renderer = DisplayRenderer::new(config, Arc::new(RwLock::new(profiles)))?
+        .without_gif_animation();
+    let mut state = AppState::with_config(
+        config.models.available.clone(),
+        &config.models.default,
+        config.new_session.terminal.clone(),
+        config.device.brightness,
+    );
+    state.strip_widgets = config.strip.widgets();
+    state.permissions = config.permissions.clone();
+    state.focus = config.focus.clone();
+
+    std::fs::create_dir_all(out_dir)
+        .with_context(|| format!("Failed to create {}", out_dir.display()))?;
+
+    let mut written = Vec::new();
+    for button_id in 0..10u8 {
+        let path = out_dir.join(format!("button-{}.png", button_id));
+        renderer
+            .render_button(button_id, false, &state)?
+            .save(&path)
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+        written.push(path);
+    }
+
+    let path = out_dir.join("strip.png");
+    renderer
+        .render_strip(&state)?
+        .save(&path)
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+    written.push(path);
+
+    Ok(written)
+}
+
+/// Renderer for the web UI's live mirror, following the focused app like the deck
+///
+/// Built per request so it always sees the current config; GIF buttons show
+/// their first frame.
+pub fn live_renderer(
+    config: &Config,
+    profiles: Arc<RwLock<ProfileManager>>,
+) -> Result<DisplayRenderer> {
+    Ok(DisplayRenderer::new(config, profiles)?.without_gif_animation())
+}
+
+/// Encode a rendered image as PNG bytes
+pub fn encode_png(image: RgbImage) -> Result<Vec<u8>> {
+    let mut bytes = Vec::new();
+    DynamicImage::ImageRgb8(image).write_to(&mut Cursor::new(&mut bytes), ImageFormat::Png)?;
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::profiles::generate_default_profiles;
+
+    #[test]
+    fn test_render_preview_writes_pngs() {
+        let dir = std::env::temp_dir().join(format!("claude-deck-preview-{}", std::process::id()));
+        let profiles = ProfileManager::new(generate_default_profiles());
+        let written = render_preview(&Config::default(), profiles.clone(), "claude", &dir).unwrap();
+
+        assert_eq!(written.len(), 11);
+        let strip = image::open(dir.join("strip.png")).unwrap();
+        assert_eq!((strip.width(), strip.height()), (800, 128));
+        assert!(image::open(dir.join("button-9.png")).is_ok());
+
+        assert!(render_preview(&Config::default(), profiles, "nope", &dir).is_err());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
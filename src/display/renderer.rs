@@ -1,16 +1,23 @@
 use anyhow::Result;
 use image::{Rgb, RgbImage};
 use rusttype::{Font, Scale};
+use std::collections::hash_map::DefaultHasher;
 use std::collections::HashMap;
-use std::sync::{Arc, RwLock};
-use tracing::debug;
+use std::hash::{Hash, Hasher};
+use std::sync::{Arc, Mutex, RwLock};
+use tracing::{debug, info, warn};
 
 use crate::config::Config;
+use crate::profiles::overlay::overlay_button;
+use crate::profiles::store::parse_hex_color;
 use crate::profiles::ProfileManager;
 use crate::state::AppState;
+use crate::templating;
+use crate::timer;
 
+use super::attention;
 use super::buttons::render_button_with_config_and_id;
-use super::strip::render_strip_image;
+use super::strip::{render_strip_image, strip_scrolls};
 
 /// Color constants
 pub const WHITE: Rgb<u8> = Rgb([255, 255, 255]);
@@ -51,57 +58,257 @@ pub fn button_colors(button_id: u8) -> (Rgb<u8>, Rgb<u8>) {
     }
 }
 
+/// Characters of clipboard text shown on a clipboard button
+const CLIPBOARD_PREVIEW_CHARS: usize = 12;
+
 /// Renders images for the device display
 pub struct DisplayRenderer {
+    /// `[appearance] font_path`, or the built-in font
     font: Font<'static>,
+    /// Profile fonts by path (None if the file failed to load)
+    profile_fonts: Mutex<HashMap<String, Option<Font<'static>>>>,
     #[allow(dead_code)]
     config: Config,
     icon_cache: HashMap<String, RgbImage>,
     profile_manager: Arc<RwLock<ProfileManager>>,
+    sent: Mutex<SentImages>,
+    /// Drive GIF buttons through the shared animator (only one deck can)
+    animate_gifs: bool,
+}
+
+/// Hashes of the images currently shown on the device
+#[derive(Default)]
+struct SentImages {
+    strip: Option<u64>,
+    buttons: HashMap<u8, u64>,
+}
+
+/// Hash an image's dimensions and pixels
+fn image_hash(image: &RgbImage) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    image.dimensions().hash(&mut hasher);
+    image.as_raw().hash(&mut hasher);
+    hasher.finish()
 }
 
 impl DisplayRenderer {
     pub fn new(config: &Config, profile_manager: Arc<RwLock<ProfileManager>>) -> Result<Self> {
-        // Load embedded font (or fall back to system font)
-        let font_data = include_bytes!("../../assets/fonts/JetBrainsMono-Bold.ttf");
-        let font = Font::try_from_bytes(font_data as &[u8])
-            .ok_or_else(|| anyhow::anyhow!("Failed to load font"))?;
+        super::theme::select(&config.appearance.theme);
+
+        // Load the configured font, falling back to the embedded one
+        let font_path = &config.appearance.font_path;
+        let font = match (!font_path.is_empty()).then(|| load_font(font_path)).flatten() {
+            Some(font) => font,
+            None => {
+                let font_data = include_bytes!("../../assets/fonts/JetBrainsMono-Bold.ttf");
+                Font::try_from_bytes(font_data as &[u8])
+                    .ok_or_else(|| anyhow::anyhow!("Failed to load font"))?
+            }
+        };
+
+        // Profile fonts load now too, so a bad path is reported at startup
+        let profile_fonts = profile_manager
+            .read()
+            .unwrap()
+            .get_profiles()
+            .iter()
+            .filter_map(|profile| profile.font.clone())
+            .map(|path| {
+                let font = load_font(&path);
+                (path, font)
+            })
+            .collect();
 
         Ok(Self {
             font,
+            profile_fonts: Mutex::new(profile_fonts),
             config: config.clone(),
             icon_cache: HashMap::new(),
             profile_manager,
+            sent: Mutex::new(SentImages::default()),
+            animate_gifs: true,
         })
     }
 
-    /// Render a button image
+    /// Show GIF buttons as their first frame instead of animating them
+    pub fn without_gif_animation(mut self) -> Self {
+        self.animate_gifs = false;
+        self
+    }
+
+    /// Font for the profile shown for the focused app
+    fn font_for(&self, state: &AppState) -> Font<'static> {
+        let path = {
+            let manager = self.profile_manager.read().unwrap();
+            manager.find_profile_for_app(&state.focused_window()).and_then(|p| p.font.clone())
+        };
+        let Some(path) = path else {
+            return self.font.clone();
+        };
+        // Profiles added or edited since startup load on first use
+        let mut fonts = self.profile_fonts.lock().unwrap_or_else(|e| e.into_inner());
+        fonts
+            .entry(path)
+            .or_insert_with_key(|path| load_font(path))
+            .clone()
+            .unwrap_or_else(|| self.font.clone())
+    }
+
+    /// Record a strip image as sent, returning false if the device already shows it
+    pub fn strip_changed(&self, image: &RgbImage) -> bool {
+        let hash = image_hash(image);
+        let mut sent = self.sent.lock().unwrap_or_else(|e| e.into_inner());
+        sent.strip.replace(hash) != Some(hash)
+    }
+
+    /// Record a button image as sent, returning false if the device already shows it
+    pub fn button_changed(&self, button_id: u8, image: &RgbImage) -> bool {
+        let hash = image_hash(image);
+        let mut sent = self.sent.lock().unwrap_or_else(|e| e.into_inner());
+        sent.buttons.insert(button_id, hash) != Some(hash)
+    }
+
+    /// Forget a button's sent image (after drawing to it outside the cache)
+    pub fn invalidate_button(&self, button_id: u8) {
+        let mut sent = self.sent.lock().unwrap_or_else(|e| e.into_inner());
+        sent.buttons.remove(&button_id);
+    }
+
+    /// Forget everything sent (after a device reset or animation)
+    pub fn invalidate(&self) {
+        let mut sent = self.sent.lock().unwrap_or_else(|e| e.into_inner());
+        *sent = SentImages::default();
+    }
+
+    /// Tint of the profile shown for the focused window, if it sets one
+    fn tint_for(&self, state: &AppState) -> Option<Rgb<u8>> {
+        let manager = self.profile_manager.read().unwrap();
+        let tint = manager.find_profile_for_app(&state.focused_window())?.tint.as_deref()?;
+        parse_hex_color(tint)
+    }
+
+    /// Render a button image, tinted by the profile
+    ///
+    /// A `confirm` button waiting for its second press blinks with a
+    /// "press again" banner.
     pub fn render_button(&self, button_id: u8, active: bool, state: &AppState) -> Result<RgbImage> {
+        let confirm = state
+            .confirm_view(std::time::Instant::now())
+            .filter(|(button, _)| *button == button_id);
+        let active = active || confirm.is_some_and(|(_, lit)| lit);
+        let mut image = self.render_untinted_button(button_id, active, state)?;
+        if let Some(tint) = self.tint_for(state) {
+            apply_tint(&mut image, tint);
+        }
+        if confirm.is_some() {
+            draw_confirm_banner(&mut image, &self.font_for(state));
+        }
+        attention::apply_glow(&mut image, state.attention_glow);
+        Ok(image)
+    }
+
+    fn render_untinted_button(
+        &self,
+        button_id: u8,
+        active: bool,
+        state: &AppState,
+    ) -> Result<RgbImage> {
         use crate::profiles::ButtonAction;
 
         // If screen is locked, render dimmed/disabled button
         if state.screen_locked {
             return self.render_locked_button();
         }
+        // Deck lock turns the buttons into a dimmed PIN keypad
+        if state.deck_lock.is_locked() {
+            let mut img = self.render_locked_button()?;
+            if let Some(digit) = crate::state::keypad_digit(button_id) {
+                let font = self.font_for(state);
+                let digit = digit.to_string();
+                let x = (img.width() as i32 - text_width(&font, &digit, 40.0)) / 2;
+                draw_text(&mut img, &font, &digit, x, 32, 40.0, Rgb([90, 90, 105]));
+            }
+            return Ok(img);
+        }
 
-        // Get button config from profile manager (uses configurable profiles)
-        let button_config = {
+        // Get button config from the active overlay, else the profile manager
+        // (uses configurable profiles)
+        let mut button_config = overlay_button(state, button_id).unwrap_or_else(|| {
             let manager = self.profile_manager.read().unwrap();
-            manager.get_button_config(&state.focused_app, button_id)
+            manager.get_button_config(&state.focused_window(), button_id)
+        });
+
+        if templating::has_placeholders(button_config.label) {
+            button_config.label = templating::expand_label(button_config.label, state);
+        }
+
+        // Clipboard buttons show a preview of their entry (the configured
+        // label is kept while the slot is empty)
+        if let ButtonAction::Clipboard(slot) = button_config.action {
+            if let Some(preview) = state.clipboard.preview(slot, CLIPBOARD_PREVIEW_CHARS) {
+                button_config.label = Box::leak(preview.into_boxed_str());
+            }
+        }
+
+        // Labels and colors set from outside win over the profile: a plugin's
+        // for its actions, then per-button ones (e.g. over MQTT)
+        let plugin_override = match button_config.action {
+            ButtonAction::Custom(action) => state.plugin_buttons.get(&action.to_uppercase()),
+            _ => None,
         };
+        for over in plugin_override.into_iter().chain(state.button_overrides.get(&button_id)) {
+            if let Some(ref label) = over.label {
+                button_config.label = Box::leak(label.clone().into_boxed_str());
+            }
+            if let Some(colors) = over.colors {
+                button_config.colors = colors;
+            }
+        }
+
+        // Monitor buttons are colored by their monitor's last check
+        if let ButtonAction::Monitor(ref name) = button_config.action {
+            let health = state.monitors.iter().find(|m| m.name == *name).map(|m| m.health);
+            button_config.colors = health.unwrap_or_default().colors();
+        }
+
+        let font = self.font_for(state);
 
         // Check if this button has MIC action - needs special rendering with mic icon
         if matches!(&button_config.action, ButtonAction::Custom(action) if *action == "MIC") {
             return super::buttons::render_mic_button(
-                &self.font,
+                &font,
                 active,
                 state.dictation_active,
                 button_config.colors,
             );
         }
 
+        let is_timer = matches!(
+            button_config.action,
+            ButtonAction::Custom(action) if timer::is_timer_action(action)
+        );
+        // Latched toggle buttons stay lit while on, as does the button that
+        // started text entry and PIN_PROFILE while holding; timer buttons are
+        // lit while counting down and blink once it fires
+        let is_pin = matches!(
+            button_config.action,
+            ButtonAction::Custom(action) if action.eq_ignore_ascii_case("PIN_PROFILE")
+        );
+        let active = active
+            || state.text_entry.as_ref().map(|e| e.button) == Some(button_id)
+            || (is_pin && self.profile_manager.read().unwrap().held_profile().is_some())
+            || (is_timer && state.timer.button_lit(std::time::Instant::now()))
+            || (matches!(button_config.action, ButtonAction::Toggle(_)) && {
+                let manager = self.profile_manager.read().unwrap();
+                manager
+                    .profile_name_for_app(&state.focused_window())
+                    .map(|profile| state.is_toggled(&profile, button_id))
+                    .unwrap_or(false)
+            });
+
         // Use the profile-specific button configuration (with button_id for GIF animation)
-        render_button_with_config_and_id(&self.font, &button_config, active, Some(button_id))
+        let gif_button = self.animate_gifs.then_some(button_id);
+        render_button_with_config_and_id(&font, &button_config, active, gif_button)
     }
 
     /// Render a locked/disabled button (shown when screen is locked)
@@ -147,11 +354,18 @@ impl DisplayRenderer {
         // Get button config from profile manager
         let button_config = {
             let manager = self.profile_manager.read().unwrap();
-            manager.get_button_config(&state.focused_app, button_id)
+            manager.get_button_config(&state.focused_window(), button_id)
         };
 
         // Render using the provided frame (deref Arc to get &RgbaImage)
-        super::buttons::render_button_with_gif_frame(&self.font, &button_config, gif_frame.as_ref())
+        let font = self.font_for(state);
+        let frame = gif_frame.as_ref();
+        let mut image = super::buttons::render_button_with_gif_frame(&font, &button_config, frame)?;
+        if let Some(tint) = self.tint_for(state) {
+            apply_tint(&mut image, tint);
+        }
+        attention::apply_glow(&mut image, state.attention_glow);
+        Ok(image)
     }
 
     /// Render a solid colored button (for animations)
@@ -165,9 +379,22 @@ impl DisplayRenderer {
         Ok(img)
     }
 
+    /// Render the two press-animation frames for a button (inset, then deeper inset)
+    pub fn render_press_frames(&self, button_id: u8, state: &AppState) -> Result<[RgbImage; 2]> {
+        let base = self.render_button(button_id, true, state)?;
+        Ok([inset_image(&base, 0.92), inset_image(&base, 0.84)])
+    }
+
     /// Render the full LCD strip (800x128)
     pub fn render_strip(&self, state: &AppState) -> Result<RgbImage> {
-        render_strip_image(&self.font, state)
+        let mut image = render_strip_image(&self.font_for(state), state)?;
+        attention::apply_glow(&mut image, state.attention_glow);
+        Ok(image)
+    }
+
+    /// Whether the strip has text scrolling, so needs redrawing on the marquee tick
+    pub fn strip_scrolls(&self, state: &AppState) -> bool {
+        strip_scrolls(&self.font_for(state), state)
     }
 
     /// Load and cache an icon
@@ -185,6 +412,64 @@ impl DisplayRenderer {
     }
 }
 
+/// Load a TTF/OTF font file (`~/` expands to the home directory)
+fn load_font(path: &str) -> Option<Font<'static>> {
+    let expanded = match (path.strip_prefix("~/"), std::env::var("HOME")) {
+        (Some(rest), Ok(home)) => format!("{}/{}", home, rest),
+        _ => path.to_string(),
+    };
+    let font = std::fs::read(&expanded).ok().and_then(Font::try_from_vec);
+    match font {
+        Some(_) => info!("Loaded font {}", path),
+        None => warn!("Failed to load font {} - using the built-in font", path),
+    }
+    font
+}
+
+/// Shrink an image toward its center over a dark background, like a key pushed in
+fn inset_image(image: &RgbImage, scale: f32) -> RgbImage {
+    let (width, height) = image.dimensions();
+    let inner_w = ((width as f32 * scale) as u32).max(1);
+    let inner_h = ((height as f32 * scale) as u32).max(1);
+    let inner = image::imageops::resize(
+        image,
+        inner_w,
+        inner_h,
+        image::imageops::FilterType::Triangle,
+    );
+
+    let mut frame = RgbImage::from_pixel(width, height, DARK_BG);
+    image::imageops::overlay(
+        &mut frame,
+        &inner,
+        ((width - inner_w) / 2) as i64,
+        ((height - inner_h) / 2) as i64,
+    );
+    frame
+}
+
+/// "PRESS AGAIN" across the middle of a button
+fn draw_confirm_banner(image: &mut RgbImage, font: &Font) {
+    const TEXT: &str = "PRESS AGAIN";
+    let (top, height) = (image.height() / 2 - 14, 28);
+    for y in top..top + height {
+        for x in 0..image.width() {
+            image.put_pixel(x, y, Rgb([20, 20, 24]));
+        }
+    }
+    let x = (image.width() as i32 - text_width(font, TEXT, 14.0)) / 2;
+    draw_text(image, font, TEXT, x, top as i32 + 6, 14.0, Rgb([255, 170, 60]));
+}
+
+/// Multiply an image by a tint color (white leaves it unchanged)
+pub fn apply_tint(image: &mut RgbImage, tint: Rgb<u8>) {
+    for pixel in image.pixels_mut() {
+        for (channel, tint) in pixel.0.iter_mut().zip(tint.0) {
+            *channel = (*channel as u16 * tint as u16 / 255) as u8;
+        }
+    }
+}
+
 /// Draw text onto an image
 pub fn draw_text(
     image: &mut RgbImage,
@@ -256,3 +541,72 @@ pub fn draw_filled_rect(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::profiles::generate_default_profiles;
+
+    #[test]
+    fn test_sent_image_cache() {
+        let renderer =
+            DisplayRenderer::new(&Config::default(), ProfileManager::shared(Vec::new())).unwrap();
+        let black = RgbImage::new(4, 4);
+        let white = RgbImage::from_pixel(4, 4, WHITE);
+
+        assert!(renderer.strip_changed(&black));
+        assert!(!renderer.strip_changed(&black));
+        assert!(renderer.strip_changed(&white));
+
+        assert!(renderer.button_changed(3, &black));
+        assert!(!renderer.button_changed(3, &black));
+        assert!(renderer.button_changed(4, &black));
+
+        renderer.invalidate_button(3);
+        assert!(renderer.button_changed(3, &black));
+        assert!(!renderer.button_changed(4, &black));
+
+        renderer.invalidate();
+        assert!(renderer.strip_changed(&white));
+        assert!(renderer.button_changed(4, &black));
+    }
+
+    #[test]
+    fn test_profile_font_falls_back() {
+        let font_path = concat!(env!("CARGO_MANIFEST_DIR"), "/assets/fonts/JetBrainsMono-Bold.ttf");
+        assert!(load_font(font_path).is_some());
+
+        let mut profile = generate_default_profiles().remove(0);
+        profile.font = Some("/nonexistent/font.ttf".to_string());
+        let renderer =
+            DisplayRenderer::new(&Config::default(), ProfileManager::shared(vec![profile]))
+                .unwrap();
+        let font = renderer.font_for(&AppState::new());
+        assert_eq!(font.glyph_count(), renderer.font.glyph_count());
+        assert!(renderer.profile_fonts.lock().unwrap()["/nonexistent/font.ttf"].is_none());
+    }
+
+    #[test]
+    fn test_profile_tint() {
+        let mut profile = generate_default_profiles().remove(0);
+        profile.tint = Some("#ff0000".to_string());
+        let renderer =
+            DisplayRenderer::new(&Config::default(), ProfileManager::shared(vec![profile]))
+                .unwrap();
+        let image = renderer.render_button(0, false, &AppState::new()).unwrap();
+        assert!(image.pixels().all(|p| p[1] == 0 && p[2] == 0));
+
+        let mut image = RgbImage::from_pixel(1, 1, Rgb([200, 100, 50]));
+        apply_tint(&mut image, WHITE);
+        assert_eq!(*image.get_pixel(0, 0), Rgb([200, 100, 50]));
+    }
+
+    #[test]
+    fn test_inset_image_keeps_size() {
+        let image = RgbImage::from_pixel(112, 112, WHITE);
+        let inset = inset_image(&image, 0.84);
+        assert_eq!(inset.dimensions(), (112, 112));
+        assert_eq!(*inset.get_pixel(0, 0), DARK_BG);
+        assert_eq!(*inset.get_pixel(56, 56), WHITE);
+    }
+}
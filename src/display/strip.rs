@@ -1,13 +1,19 @@
 use anyhow::Result;
-use image::{Rgb, RgbImage};
+use image::{imageops, Rgb, RgbImage};
 use rusttype::Font;
+use std::time::{Duration, Instant};
 
 use super::renderer::{
-    draw_filled_rect, draw_text, text_width, BLUE, BRIGHT_ORANGE, BRIGHT_PURPLE, GRAY, GREEN,
+    draw_filled_rect, draw_text, text_width, BLUE, BRIGHT_ORANGE, BRIGHT_PURPLE, GREEN,
     ORANGE, RED, WAITING_GLOW_BG, WHITE,
 };
+use super::buttons::wrap_words;
+use super::theme;
+use crate::config::StripWidget;
 use crate::device::{STRIP_BUTTON_HEIGHT, STRIP_BUTTON_WIDTH, STRIP_HEIGHT, STRIP_WIDTH};
-use crate::state::AppState;
+use crate::scenes::local_hour_minute;
+use crate::state::{AppState, Banner, BannerKind, DeviceHealth, Sessions, TextEntry};
+use crate::timer::{format_remaining, TimerPhase};
 
 /// Strip button labels
 pub const STRIP_BUTTON_LABELS: [&str; 4] = [
@@ -22,10 +28,11 @@ pub fn render_strip_button(font: &Font, button_id: u8, state: &AppState) -> Resu
     let mut img = RgbImage::new(STRIP_BUTTON_WIDTH, STRIP_BUTTON_HEIGHT);
 
     // Fill with gradient background
-    fill_gradient_vertical(&mut img, Rgb([20, 22, 32]), Rgb([12, 14, 20]));
+    let theme = theme::current();
+    fill_gradient_vertical(&mut img, theme.background.0, theme.background.1);
 
     // Draw styled border
-    draw_strip_button_border(&mut img, Rgb([50, 55, 70]), Rgb([30, 32, 42]));
+    draw_strip_button_border(&mut img, theme.separator.0, theme.separator.1);
 
     match button_id {
         0 => render_status_button(&mut img, font, state),
@@ -63,20 +70,22 @@ fn render_status_button(img: &mut RgbImage, font: &Font, state: &AppState) {
     }
 
     // Header with accent line
-    draw_filled_rect(img, 4, 4, STRIP_BUTTON_WIDTH - 8, 20, Rgb([30, 35, 45]));
+    draw_filled_rect(img, 4, 4, STRIP_BUTTON_WIDTH - 8, 20, theme::current().panel);
     let header_color = if flash_on { ORANGE } else { Rgb([120, 130, 150]) };
     draw_text(img, font, "STATUS", 10, 6, 11.0, header_color);
 
     // Show status with waiting state
     let (status, color) = if state.screen_locked {
-        ("LOCKED", ORANGE)
+        ("LOCKED".to_string(), ORANGE)
+    } else if state.deck_lock.is_locked() {
+        (deck_lock_status(state), ORANGE)
     } else if state.waiting_for_input {
-        if flash_on { ("WAITING", WHITE) } else { ("WAITING", ORANGE) }
-    } else if state.connected {
-        ("CONNECTED", GREEN)
+        let color = if flash_on { WHITE } else { ORANGE };
+        ("WAITING".to_string(), color)
     } else {
-        ("OFFLINE", RED)
+        device_status(state, "RETRY")
     };
+    let status = status.as_str();
 
     // Status text centered
     let status_width = text_width(font, status, 15.0);
@@ -85,7 +94,7 @@ fn render_status_button(img: &mut RgbImage, font: &Font, state: &AppState) {
 
     // Connection indicator dot (or lock/waiting symbol)
     let dot_x = (STRIP_BUTTON_WIDTH as i32 / 2) - 8;
-    if state.screen_locked {
+    if state.screen_locked || state.deck_lock.is_locked() {
         draw_text(img, font, "[X]", dot_x - 8, 78, 18.0, ORANGE);
     } else if state.waiting_for_input {
         let (symbol, sym_color) = if flash_on { (">>>", BRIGHT_ORANGE) } else { ("...", ORANGE) };
@@ -97,13 +106,21 @@ fn render_status_button(img: &mut RgbImage, font: &Font, state: &AppState) {
     }
 }
 
+/// Status while `LOCK_DECK` is on, with a dot per PIN digit keyed in
+fn deck_lock_status(state: &AppState) -> String {
+    match state.deck_lock.entered_len() {
+        0 => "ENTER PIN".to_string(),
+        digits => format!("PIN {}", "●".repeat(digits)),
+    }
+}
+
 /// Render model button (current model)
 fn render_model_button(img: &mut RgbImage, font: &Font, state: &AppState) {
     // Header
-    draw_filled_rect(img, 4, 4, STRIP_BUTTON_WIDTH - 8, 20, Rgb([30, 35, 45]));
+    draw_filled_rect(img, 4, 4, STRIP_BUTTON_WIDTH - 8, 20, theme::current().panel);
     draw_text(img, font, "MODEL", 10, 6, 11.0, Rgb([120, 130, 150]));
 
-    let model_upper = state.model.to_uppercase();
+    let model_upper = state.model_label(&state.model);
 
     if state.model_selecting {
         // Selection mode - show with highlight
@@ -123,6 +140,7 @@ fn render_model_button(img: &mut RgbImage, font: &Font, state: &AppState) {
 
 /// Render task button (current task)
 fn render_task_button(img: &mut RgbImage, font: &Font, state: &AppState) {
+    let theme = theme::current();
     let flash_on = state.waiting_for_input && state.waiting_flash_on;
 
     // Warm background glow + orange border when waiting + flash on
@@ -132,7 +150,7 @@ fn render_task_button(img: &mut RgbImage, font: &Font, state: &AppState) {
     }
 
     // Header
-    draw_filled_rect(img, 4, 4, STRIP_BUTTON_WIDTH - 8, 20, Rgb([30, 35, 45]));
+    draw_filled_rect(img, 4, 4, STRIP_BUTTON_WIDTH - 8, 20, theme.panel);
     let header_color = if flash_on { ORANGE } else { Rgb([120, 130, 150]) };
     draw_text(img, font, "TASK", 10, 6, 11.0, header_color);
 
@@ -145,14 +163,15 @@ fn render_task_button(img: &mut RgbImage, font: &Font, state: &AppState) {
     } else if state.task_name == "THINKING" {
         BRIGHT_PURPLE
     } else {
-        WHITE
+        theme.text
     };
 
     // Line 1: Task/status name (centered)
-    let task = if state.task_name.len() > 12 {
-        format!("{}...", &state.task_name[..9])
+    let task = task_text(state);
+    let task = if task.len() > 12 {
+        format!("{}...", &task[..9])
     } else {
-        state.task_name.clone()
+        task.to_string()
     };
 
     let task_width = text_width(font, &task, 14.0);
@@ -160,15 +179,15 @@ fn render_task_button(img: &mut RgbImage, font: &Font, state: &AppState) {
     draw_text(img, font, &task, x, 32, 14.0, task_color);
 
     // Line 2: Tool detail (file/command preview)
-    if let Some(ref detail) = state.tool_detail {
+    if let Some(detail) = detail_text(state) {
         let detail_str = if detail.len() > 14 {
             format!("{}...", &detail[..11])
         } else {
-            detail.clone()
+            detail.to_string()
         };
         let detail_width = text_width(font, &detail_str, 11.0);
         let x = ((STRIP_BUTTON_WIDTH as i32 - detail_width) / 2).max(4);
-        draw_text(img, font, &detail_str, x, 55, 11.0, GRAY);
+        draw_text(img, font, &detail_str, x, 55, 11.0, theme.label);
     }
 
     // Line 3: Status indicator
@@ -191,8 +210,9 @@ fn render_task_button(img: &mut RgbImage, font: &Font, state: &AppState) {
 
 /// Render mic/dictation button
 fn render_mode_button(img: &mut RgbImage, font: &Font, state: &AppState) {
+    let theme = theme::current();
     // Header
-    draw_filled_rect(img, 4, 4, STRIP_BUTTON_WIDTH - 8, 20, Rgb([30, 35, 45]));
+    draw_filled_rect(img, 4, 4, STRIP_BUTTON_WIDTH - 8, 20, theme.panel);
     draw_text(img, font, "MIC", 10, 6, 11.0, Rgb([120, 130, 150]));
 
     if state.dictation_active {
@@ -205,7 +225,7 @@ fn render_mode_button(img: &mut RgbImage, font: &Font, state: &AppState) {
     } else {
         let ready_width = text_width(font, "READY", 18.0);
         let x = ((STRIP_BUTTON_WIDTH as i32 - ready_width) / 2).max(4);
-        draw_text(img, font, "READY", x, 48, 18.0, GRAY);
+        draw_text(img, font, "READY", x, 48, 18.0, theme.label);
         draw_text(img, font, "press MIC", 32, 85, 10.0, Rgb([80, 90, 100]));
     }
 }
@@ -270,12 +290,99 @@ const LABEL_SIZE: f32 = 14.0;  // Consistent label size
 const VALUE_SIZE: f32 = 24.0;  // Consistent value size
 const PADDING: i32 = 15;       // Edge padding
 
+/// Pixels overlong strip text moves per marquee tick
+const MARQUEE_STEP: i32 = 3;
+/// Ticks the text rests at its start before each pass
+const MARQUEE_PAUSE: u32 = 15;
+/// Space between the end of scrolling text and its repeat
+const MARQUEE_GAP: i32 = 40;
+
+/// Width available to the task widget's value
+const TASK_MAX_WIDTH: i32 = QUAD_WIDTH - PADDING * 2 - 10;
+/// Width available to the detail widget's value
+const DETAIL_MAX_WIDTH: i32 = QUAD_WIDTH - PADDING - 5;
+
+/// Width available to the now playing widget's value
+const NOW_PLAYING_MAX_WIDTH: i32 = QUAD_WIDTH - PADDING * 2;
+
+/// Focus mode moon on the STATUS quadrant
+const MOON: Rgb<u8> = Rgb([235, 215, 140]);
+
+/// How long a knob's overlay stays on the strip
+pub const OVERLAY_DURATION: Duration = Duration::from_millis(1500);
+/// How long the overlay takes to slide up over the quadrants
+const OVERLAY_SLIDE: Duration = Duration::from_millis(150);
+/// Size of the overlay's value
+const OVERLAY_VALUE_SIZE: f32 = 44.0;
+/// Width of the overlay's progress bar
+const OVERLAY_BAR_WIDTH: u32 = 480;
+
+/// What a knob just changed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OverlayKind {
+    Brightness,
+    Model,
+}
+
+/// Large value and progress bar over the whole strip after a knob turn
+///
+/// It's drawn over the quadrant layout, which shows again once it expires.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StripOverlay {
+    pub kind: OverlayKind,
+    shown_at: Instant,
+}
+
+impl StripOverlay {
+    pub fn new(kind: OverlayKind, now: Instant) -> Self {
+        Self { kind, shown_at: now }
+    }
+
+    /// Whether it's still on the strip at `now`
+    pub fn is_active(&self, now: Instant) -> bool {
+        now.saturating_duration_since(self.shown_at) < OVERLAY_DURATION
+    }
+
+    /// Whether it's still sliding in at `now` (so needs a redraw every frame)
+    pub fn is_sliding(&self, now: Instant) -> bool {
+        now.saturating_duration_since(self.shown_at) < OVERLAY_SLIDE
+    }
+
+    /// Pixels it sits below its resting place at `now` (eased out)
+    fn offset(&self, now: Instant) -> i64 {
+        let t = now.saturating_duration_since(self.shown_at).as_secs_f32()
+            / OVERLAY_SLIDE.as_secs_f32();
+        let remaining = 1.0 - t.min(1.0);
+        (STRIP_HEIGHT as f32 * remaining * remaining * remaining).round() as i64
+    }
+}
+
 /// Render the LCD strip with status information (800x128)
 pub fn render_strip_image(font: &Font, state: &AppState) -> Result<RgbImage> {
+    let theme = theme::current();
     let mut img = RgbImage::new(STRIP_WIDTH, STRIP_HEIGHT);
 
     // Fill background with subtle gradient
-    fill_gradient_vertical(&mut img, Rgb([18, 20, 28]), Rgb([12, 14, 20]));
+    let (top, bottom) = theme.background;
+    fill_gradient_vertical(&mut img, top, bottom);
+
+    // Text entry takes over the whole strip
+    if let Some(ref entry) = state.text_entry {
+        draw_text_entry(&mut img, font, entry);
+        return Ok(img);
+    }
+
+    // So does the session picker
+    if let Some(picker) = state.sessions.picker() {
+        draw_session_picker(&mut img, font, &state.sessions, picker.highlight);
+        return Ok(img);
+    }
+
+    // And an error or rate limit, until the session moves on
+    if let Some(ref banner) = state.banner {
+        draw_banner(&mut img, font, banner);
+        return Ok(img);
+    }
 
     // Draw horizontal separator
     draw_separator(&mut img, QUAD_HEIGHT as u32);
@@ -283,203 +390,752 @@ pub fn render_strip_image(font: &Font, state: &AppState) -> Result<RgbImage> {
     // Draw vertical separator
     draw_vertical_separator(&mut img, QUAD_WIDTH as u32);
 
-    // Four quadrants:
-    // Top-left: Task name
-    draw_quadrant_task(&mut img, font, state);
-    // Top-right: Tool detail
-    draw_quadrant_detail(&mut img, font, state);
-    // Bottom-left: Model
-    draw_quadrant_model(&mut img, font, state);
-    // Bottom-right: Status
-    draw_quadrant_status(&mut img, font, state);
+    // "minimal" layout (set by scenes) keeps only the top-left and bottom-right widgets
+    let minimal = state.strip_layout == "minimal";
+
+    for (index, (&quad, widget)) in Quadrant::ALL.iter().zip(&state.strip_widgets).enumerate() {
+        // Encoder feedback takes over its quadrant whatever widget is configured there
+        if index == 1 && state.is_brightness_display_active() {
+            let pct = format!("{}%", state.brightness);
+            draw_meter(&mut img, font, quad, "BRIGHTNESS", &pct, state.brightness, BLUE);
+        } else if index == 2 && state.model_selecting {
+            let (x, y) = (quad.left(), quad.label_y());
+            draw_text(&mut img, font, "SELECT MODEL", x, y, LABEL_SIZE, theme.label);
+            draw_model_selector_compact(&mut img, font, state, quad);
+        } else if index == 3 && state.is_volume_display_active() {
+            draw_volume_widget(&mut img, font, state.volume, quad);
+        } else if !(minimal && (index == 1 || index == 2)) {
+            draw_widget(&mut img, font, state, widget, quad);
+        }
+    }
+
+    // A knob's overlay slides up over the quadrants until it expires
+    let now = Instant::now();
+    if let Some(overlay) = state.strip_overlay.filter(|overlay| overlay.is_active(now)) {
+        let mut layer = RgbImage::new(STRIP_WIDTH, STRIP_HEIGHT);
+        fill_gradient_vertical(&mut layer, top, bottom);
+        draw_overlay(&mut layer, font, state, overlay.kind);
+        imageops::replace(&mut img, &layer, 0, overlay.offset(now));
+    }
 
     Ok(img)
 }
 
+/// Overlay layer: label, the new value large and centered, and a bar below
+fn draw_overlay(img: &mut RgbImage, font: &Font, state: &AppState, kind: OverlayKind) {
+    let theme = theme::current();
+    let (label, value, color) = match kind {
+        OverlayKind::Brightness => ("BRIGHTNESS", format!("{}%", state.brightness), BLUE),
+        OverlayKind::Model => ("MODEL", state.model_label(&state.model), GREEN),
+    };
+    let center = |text: &str, scale: f32| (STRIP_WIDTH as i32 - text_width(font, text, scale)) / 2;
+
+    draw_text(img, font, label, center(label, LABEL_SIZE), 8, LABEL_SIZE, theme.label);
+    let max_width = STRIP_WIDTH as i32 - PADDING * 2;
+    let value = truncate_text(font, &value, OVERLAY_VALUE_SIZE, max_width);
+    let value_x = center(&value, OVERLAY_VALUE_SIZE);
+    draw_text(img, font, &value, value_x, 30, OVERLAY_VALUE_SIZE, color);
+
+    let bar_x = (STRIP_WIDTH - OVERLAY_BAR_WIDTH) / 2;
+    let (bar_y, bar_h) = (92, 16);
+    draw_filled_rect(img, bar_x, bar_y, OVERLAY_BAR_WIDTH, bar_h, theme.panel);
+    match kind {
+        OverlayKind::Brightness => {
+            let fill = OVERLAY_BAR_WIDTH * state.brightness.min(100) as u32 / 100;
+            if fill > 0 {
+                draw_filled_rect(img, bar_x, bar_y, fill, bar_h, color);
+            }
+        }
+        // One segment per model, the selected one lit
+        OverlayKind::Model => {
+            let count = state.available_models.len().max(1) as u32;
+            let gap = 6;
+            let segment = (OVERLAY_BAR_WIDTH - gap * (count - 1)) / count;
+            let x = bar_x + (segment + gap) * state.model_index as u32;
+            draw_filled_rect(img, x, bar_y, segment, bar_h, color);
+        }
+    }
+}
+
+/// Full-strip text entry: entered text on top, character wheel below
+fn draw_text_entry(img: &mut RgbImage, font: &Font, entry: &TextEntry) {
+    let theme = theme::current();
+    let width = STRIP_WIDTH as i32;
+
+    draw_text(img, font, "TEXT ENTRY", PADDING, 8, LABEL_SIZE, theme.label);
+    let hint = "KNOB: PICK / ADD   KEY: DELETE   ENTRY: DONE";
+    let hint_x = width - PADDING - text_width(font, hint, LABEL_SIZE);
+    draw_text(img, font, hint, hint_x, 8, LABEL_SIZE, theme.label);
+
+    // Entered text followed by the highlighted candidate, keeping the end visible
+    let max_width = width - PADDING * 2 - 30;
+    let mut shown = entry.buffer.as_str();
+    while text_width(font, shown, VALUE_SIZE) > max_width && !shown.is_empty() {
+        shown = &shown[1..];
+    }
+    draw_text(img, font, shown, PADDING, 28, VALUE_SIZE, theme.text);
+    let cursor_x = PADDING + text_width(font, shown, VALUE_SIZE) + 4;
+    draw_filled_rect(img, cursor_x as u32, 56, 18, 3, ORANGE);
+    draw_text(img, font, &entry.candidate().to_string(), cursor_x, 28, VALUE_SIZE, ORANGE);
+
+    draw_separator(img, QUAD_HEIGHT as u32);
+
+    // Character wheel centered on the candidate
+    const SPACING: i32 = 40;
+    let center = width / 2;
+    let y = QUAD_HEIGHT + 24;
+    for offset in -9isize..=9 {
+        let c = entry.neighbor(offset);
+        let label = if c == ' ' { "SP".to_string() } else { c.to_string() };
+        let x = center + offset as i32 * SPACING - text_width(font, &label, VALUE_SIZE) / 2;
+        if offset == 0 {
+            draw_filled_rect(img, (center - 18) as u32, (y - 6) as u32, 36, 40, Rgb([60, 40, 10]));
+        }
+        let color = if offset == 0 { BRIGHT_ORANGE } else { theme.label };
+        draw_text(img, font, &label, x, y, VALUE_SIZE, color);
+    }
+}
+
+/// Full-strip session picker: AUTO then each session, four at a time
+fn draw_session_picker(img: &mut RgbImage, font: &Font, sessions: &Sessions, highlight: usize) {
+    let theme = theme::current();
+    const CELL_WIDTH: i32 = STRIP_WIDTH as i32 / 4;
+
+    draw_text(img, font, "SESSIONS", PADDING, 8, LABEL_SIZE, theme.label);
+    let hint = "TURN: SCROLL   PRESS: SELECT";
+    let hint_x = STRIP_WIDTH as i32 - PADDING - text_width(font, hint, LABEL_SIZE);
+    draw_text(img, font, hint, hint_x, 8, LABEL_SIZE, theme.label);
+
+    let list = sessions.list();
+    let entries = list.len() + 1;
+    let first = highlight.saturating_sub(3).min(entries.saturating_sub(4));
+    let picked = sessions.current_entry();
+
+    for (column, entry) in (first..entries.min(first + 4)).enumerate() {
+        let x = column as i32 * CELL_WIDTH;
+        if entry == highlight {
+            draw_filled_rect(img, x as u32 + 4, 30, CELL_WIDTH as u32 - 8, 92, Rgb([60, 40, 10]));
+        }
+
+        let (name, task, task_color) = match entry.checked_sub(1).map(|i| &list[i]) {
+            None => ("AUTO".to_string(), "FOLLOW LATEST".to_string(), theme.label),
+            Some(session) => {
+                let status = &session.status;
+                let color = if status.waiting_for_input {
+                    ORANGE
+                } else if status.task == "READY" {
+                    GREEN
+                } else {
+                    theme.text
+                };
+                (session.label(), status.task.to_uppercase(), color)
+            }
+        };
+        let name_color = if entry == highlight { BRIGHT_ORANGE } else { theme.text };
+        let max_width = CELL_WIDTH - PADDING * 2;
+        let name = truncate_text(font, &name, VALUE_SIZE, max_width);
+        draw_text(img, font, &name, x + PADDING, 44, VALUE_SIZE, name_color);
+        let task = truncate_text(font, &task, LABEL_SIZE, max_width);
+        draw_text(img, font, &task, x + PADDING, 84, LABEL_SIZE, task_color);
+        if entry == picked {
+            draw_filled_rect(img, (x + PADDING) as u32, 108, 24, 3, BRIGHT_ORANGE);
+        }
+    }
+}
+
+/// Size of the banner's message text
+const BANNER_MESSAGE_SIZE: f32 = 20.0;
+
+/// Draw the full-width error (red) or rate limit (amber) banner
+fn draw_banner(img: &mut RgbImage, font: &Font, banner: &Banner) {
+    let (top, bottom) = match banner.kind {
+        BannerKind::Error => (Rgb([150, 25, 25]), Rgb([70, 10, 10])),
+        BannerKind::RateLimited => (Rgb([160, 95, 0]), Rgb([80, 45, 0])),
+    };
+    fill_gradient_vertical(img, top, bottom);
+
+    draw_text(img, font, banner.title(), PADDING, 12, VALUE_SIZE, WHITE);
+    let countdown = banner.retry_in(std::time::Instant::now()).map(|secs| match secs {
+        0 => "RETRY NOW".to_string(),
+        secs => format!("RETRY IN {}", format_remaining(secs)),
+    });
+    if let Some(countdown) = countdown {
+        let x = STRIP_WIDTH as i32 - PADDING - text_width(font, &countdown, VALUE_SIZE);
+        draw_text(img, font, &countdown, x, 12, VALUE_SIZE, WHITE);
+    }
+
+    // Two lines of message, the second cut short if there's more
+    let max_width = STRIP_WIDTH as i32 - PADDING * 2;
+    let lines = wrap_words(font, &banner.message, BANNER_MESSAGE_SIZE, max_width);
+    let first = truncate_text(font, &lines[0], BANNER_MESSAGE_SIZE, max_width);
+    draw_text(img, font, &first, PADDING, 54, BANNER_MESSAGE_SIZE, WHITE);
+    if lines.len() > 1 {
+        let rest = truncate_text(font, &lines[1..].join(" "), BANNER_MESSAGE_SIZE, max_width);
+        draw_text(img, font, &rest, PADDING, 84, BANNER_MESSAGE_SIZE, WHITE);
+    }
+}
+
 /// Draw vertical separator line
 fn draw_vertical_separator(img: &mut RgbImage, x: u32) {
-    let color = Rgb([45, 50, 65]);
+    let (color, shadow) = theme::current().separator;
     for y in 10..(STRIP_HEIGHT - 10) {
         img.put_pixel(x, y, color);
-        img.put_pixel(x + 1, y, Rgb([25, 28, 38])); // Shadow
+        img.put_pixel(x + 1, y, shadow);
     }
 }
 
-/// Top-left quadrant: Task name
-fn draw_quadrant_task(img: &mut RgbImage, font: &Font, state: &AppState) {
-    let x = PADDING;
-    let y_label = 8;
-    let y_value = 28;
-    let max_width = QUAD_WIDTH - PADDING * 2 - 10;
-    let flash_on = state.waiting_for_input && state.waiting_flash_on;
+/// One of the four strip quadrants, by its top-left corner
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Quadrant {
+    x: i32,
+    y: i32,
+}
 
-    // Warm background glow + orange border when waiting + flash on
-    if flash_on {
-        draw_filled_rect(img, 4, 2, (QUAD_WIDTH - 8) as u32, (QUAD_HEIGHT - 4) as u32, WAITING_GLOW_BG);
-        draw_waiting_border(img, 2, 0, (QUAD_WIDTH - 4) as u32, QUAD_HEIGHT as u32);
+impl Quadrant {
+    /// Quadrants in `[strip]` config order
+    const ALL: [Quadrant; 4] = [
+        Quadrant { x: 0, y: 0 },
+        Quadrant { x: QUAD_WIDTH, y: 0 },
+        Quadrant { x: 0, y: QUAD_HEIGHT },
+        Quadrant { x: QUAD_WIDTH, y: QUAD_HEIGHT },
+    ];
+
+    fn left(self) -> i32 {
+        self.x + PADDING
     }
 
-    // Label
-    let label_color = if flash_on { ORANGE } else { GRAY };
-    draw_text(img, font, "TASK", x, y_label, LABEL_SIZE, label_color);
+    fn right(self) -> i32 {
+        self.x + QUAD_WIDTH - PADDING
+    }
 
-    // Value with color based on state
-    let task_color = if state.task_name == "ERROR" || state.task_name == "RATE LIMITED" {
-        RED
-    } else if state.waiting_for_input {
-        if flash_on { WHITE } else { ORANGE }
-    } else if state.task_name == "THINKING" {
-        BRIGHT_PURPLE
-    } else if state.task_name == "READY" {
-        GREEN
-    } else {
-        WHITE
-    };
+    /// Label position (the top row sits a little lower, clear of the bezel)
+    fn label_y(self) -> i32 {
+        if self.y == 0 {
+            8
+        } else {
+            self.y + 6
+        }
+    }
+
+    fn value_y(self) -> i32 {
+        self.label_y() + 20
+    }
+}
 
-    let task_display = truncate_text(font, &state.task_name, VALUE_SIZE, max_width);
-    draw_text(img, font, &task_display, x, y_value, VALUE_SIZE, task_color);
+/// Draw a configured widget into a quadrant
+fn draw_widget(
+    img: &mut RgbImage,
+    font: &Font,
+    state: &AppState,
+    widget: &StripWidget,
+    quad: Quadrant,
+) {
+    let theme = theme::current();
+    match widget {
+        StripWidget::Task => draw_task_widget(img, font, state, quad),
+        StripWidget::Detail => draw_detail_widget(img, font, state, quad),
+        StripWidget::Model => {
+            draw_labeled_value(img, font, quad, "MODEL", &state.model_label(&state.model), BLUE)
+        }
+        StripWidget::Status => draw_status_widget(img, font, state, quad),
+        StripWidget::Clock => {
+            let (hour, minute) = local_hour_minute();
+            let time = format!("{:02}:{:02}", hour, minute);
+            draw_labeled_value(img, font, quad, "TIME", &time, theme.text);
+        }
+        StripWidget::Volume => draw_volume_widget(img, font, state.volume, quad),
+        StripWidget::Cpu => match state.cpu_pct {
+            Some(pct) => {
+                draw_meter(img, font, quad, "CPU", &format!("{}%", pct), pct, load_color(pct))
+            }
+            None => draw_labeled_value(img, font, quad, "CPU", "-", theme.label),
+        },
+        StripWidget::TokenCost => {
+            if has_usage(state) {
+                draw_text(img, font, "USAGE", quad.left(), quad.label_y(), LABEL_SIZE, theme.label);
+                draw_usage_meter(img, font, state, quad);
+            } else {
+                draw_labeled_value(img, font, quad, "USAGE", "-", theme.label);
+            }
+        }
+        StripWidget::FocusedApp => {
+            let (app, color) = if state.focused_app.is_empty() {
+                ("-", theme.label)
+            } else {
+                (state.focused_app.as_str(), theme.text)
+            };
+            draw_labeled_value(img, font, quad, "APP", app, color);
+        }
+        StripWidget::NowPlaying => draw_now_playing_widget(img, font, state, quad),
+        StripWidget::Timer => draw_timer_widget(img, font, state, quad),
+        StripWidget::System => draw_system_widget(img, font, state, quad),
+        StripWidget::Monitors => draw_monitors_widget(img, font, state, quad),
+        StripWidget::Git => match state.git {
+            Some(ref git) => {
+                let color = if git.dirty > 0 { ORANGE } else { GREEN };
+                draw_labeled_value(img, font, quad, &git.repo.to_uppercase(), &git.summary(), color)
+            }
+            None => draw_labeled_value(img, font, quad, "GIT", "-", theme.label),
+        },
+        StripWidget::Agents => draw_agents_widget(img, font, state, quad),
+        StripWidget::HomeAssistant => match state.home_assistant {
+            Some(ref sensor) => {
+                let label = sensor.name.to_uppercase();
+                draw_labeled_value(img, font, quad, &label, &sensor.value, theme.text)
+            }
+            None => draw_labeled_value(img, font, quad, "HOME", "-", theme.label),
+        },
+        StripWidget::Plugin(name) => match state.plugin_widgets.get(name) {
+            Some(widget) => {
+                let label = widget.label.clone().unwrap_or_else(|| name.to_uppercase());
+                let color = widget.color.unwrap_or(theme.text);
+                draw_labeled_value(img, font, quad, &label, &widget.text, color);
+            }
+            None => draw_labeled_value(img, font, quad, &name.to_uppercase(), "-", theme.label),
+        },
+        StripWidget::Empty => {}
+    }
 }
 
-/// Top-right quadrant: Tool detail (or brightness overlay)
-fn draw_quadrant_detail(img: &mut RgbImage, font: &Font, state: &AppState) {
-    // Show brightness overlay if active
-    if state.is_brightness_display_active() {
-        draw_quadrant_brightness(img, font, state);
+/// Running sub-agents, or the one scrolled to with its latest tool
+fn draw_agents_widget(img: &mut RgbImage, font: &Font, state: &AppState, quad: Quadrant) {
+    let theme = theme::current();
+    let agents = &state.agents;
+    if let Some((index, agent)) = agents.view() {
+        let count = agents.list().len();
+        let label = format!("AGENT {}/{}: {}", index + 1, count, agent.kind.to_uppercase());
+        let value = agent.tool.as_deref().unwrap_or(&agent.description);
+        let color = if agent.waiting { ORANGE } else { theme.text };
+        draw_labeled_value(img, font, quad, &label, value, color);
         return;
     }
+    match agents.summary() {
+        Some(summary) => {
+            let color = if agents.waiting() > 0 { ORANGE } else { theme.text };
+            draw_labeled_value(img, font, quad, "AGENTS", &summary, color);
+        }
+        None => draw_labeled_value(img, font, quad, "AGENTS", "-", theme.label),
+    }
+}
 
-    let x = QUAD_WIDTH + PADDING;
-    let y_label = 8;
-    let y_value = 28;
-    // Full width available for detail text (less padding)
-    let max_width = QUAD_WIDTH - PADDING - 5;
+/// CPU, memory and network columns, each a figure over a mini bar
+fn draw_system_widget(img: &mut RgbImage, font: &Font, state: &AppState, quad: Quadrant) {
+    const GAP: i32 = 12;
+    const COLUMN_WIDTH: i32 = (QUAD_WIDTH - PADDING * 2 - GAP * 2) / 3;
+    let theme = theme::current();
+    let stats = state.system_stats.unwrap_or_default();
+    let bar_y = (quad.y + 30) as u32;
+
+    let columns = [("CPU", stats.cpu_pct), ("MEM", stats.mem_pct)];
+    for (i, (label, pct)) in columns.into_iter().enumerate() {
+        let x = quad.left() + i as i32 * (COLUMN_WIDTH + GAP);
+        let value = pct.map_or("-".to_string(), |pct| format!("{}%", pct));
+        draw_text(img, font, label, x, quad.label_y(), LABEL_SIZE, theme.label);
+        let value_x = x + COLUMN_WIDTH - text_width(font, &value, LABEL_SIZE);
+        draw_text(img, font, &value, value_x, quad.label_y(), LABEL_SIZE, theme.text);
+        draw_mini_bar(img, x, bar_y, COLUMN_WIDTH as u32, 20, pct.unwrap_or(0));
+    }
 
-    // Label
-    draw_text(img, font, "DETAIL", x, y_label, LABEL_SIZE, GRAY);
+    // Network: received over sent, each on a log scale up to 100 MB/s
+    let x = quad.left() + 2 * (COLUMN_WIDTH + GAP);
+    draw_text(img, font, "NET", x, quad.label_y(), LABEL_SIZE, theme.label);
+    let (rx, tx) = stats.net_rates.unwrap_or_default();
+    let rates = match stats.net_rates {
+        Some(_) => format!("{}/{}", format_rate(rx), format_rate(tx)),
+        None => "-".to_string(),
+    };
+    let value_x = x + COLUMN_WIDTH - text_width(font, &rates, LABEL_SIZE);
+    draw_text(img, font, &rates, value_x, quad.label_y(), LABEL_SIZE, theme.text);
+    let log_pct = |rate: u64| ((rate as f32).max(1.0).log10() / 8.0 * 100.0).min(100.0) as u8;
+    draw_mini_bar(img, x, bar_y, COLUMN_WIDTH as u32, 9, log_pct(rx));
+    draw_mini_bar(img, x, bar_y + 11, COLUMN_WIDTH as u32, 9, log_pct(tx));
+}
 
-    // Value
-    if let Some(ref detail) = state.tool_detail {
-        let detail_display = truncate_text_path(font, detail, VALUE_SIZE, max_width);
-        draw_text(img, font, &detail_display, x, y_value, VALUE_SIZE, WHITE);
-    } else {
-        draw_text(img, font, "-", x, y_value, VALUE_SIZE, GRAY);
+/// A bar filled to `pct`, colored by load
+fn draw_mini_bar(img: &mut RgbImage, x: i32, y: u32, width: u32, height: u32, pct: u8) {
+    draw_filled_rect(img, x as u32, y, width, height, theme::current().panel);
+    let fill = width * pct.min(100) as u32 / 100;
+    if fill > 0 {
+        draw_filled_rect(img, x as u32, y, fill, height, load_color(pct));
     }
 }
 
-/// Top-right quadrant: Brightness overlay (shown for 2s after encoder rotation)
-fn draw_quadrant_brightness(img: &mut RgbImage, font: &Font, state: &AppState) {
-    let x = QUAD_WIDTH + PADDING;
-    let y_label = 8;
+/// Bytes per second as "512B", "80K" or "1.2M"
+fn format_rate(bytes_per_sec: u64) -> String {
+    match bytes_per_sec {
+        0..=999 => format!("{}B", bytes_per_sec),
+        1_000..=999_999 => format!("{}K", bytes_per_sec / 1_000),
+        _ => format!("{:.1}M", bytes_per_sec as f64 / 1_000_000.0),
+    }
+}
 
-    // Label + percentage
-    draw_text(img, font, "BRIGHTNESS", x, y_label, LABEL_SIZE, GRAY);
+/// Status monitors: a colored dot and name for each, left to right
+fn draw_monitors_widget(img: &mut RgbImage, font: &Font, state: &AppState, quad: Quadrant) {
+    const DOT: u32 = 12;
+    let theme = theme::current();
+    if state.monitors.is_empty() {
+        draw_labeled_value(img, font, quad, "MONITORS", "-", theme.label);
+        return;
+    }
+    draw_text(img, font, "MONITORS", quad.left(), quad.label_y(), LABEL_SIZE, theme.label);
+
+    let right = quad.right();
+    let mut x = quad.left();
+    let y = quad.value_y();
+    for monitor in &state.monitors {
+        let name = monitor.name.to_uppercase();
+        let width = DOT as i32 + 6 + text_width(font, &name, LABEL_SIZE);
+        if x + width > right {
+            break;
+        }
+        let (color, _) = monitor.health.colors();
+        draw_filled_rect(img, x as u32, (y + 6) as u32, DOT, DOT, color);
+        draw_text(img, font, &name, x + DOT as i32 + 6, y + 4, LABEL_SIZE, theme.text);
+        x += width + 14;
+    }
+}
+
+/// Now Playing widget: track and artist, dimmed while paused
+fn draw_now_playing_widget(img: &mut RgbImage, font: &Font, state: &AppState, quad: Quadrant) {
+    let theme = theme::current();
+    let Some(ref track) = state.now_playing else {
+        draw_labeled_value(img, font, quad, "NOW PLAYING", "-", theme.label);
+        return;
+    };
+    let label = if track.playing { "NOW PLAYING" } else { "PAUSED" };
+    draw_text(img, font, label, quad.left(), quad.label_y(), LABEL_SIZE, theme.label);
+    let text = Marquee {
+        text: &track.summary(),
+        x: quad.left(),
+        y: quad.value_y(),
+        max_width: NOW_PLAYING_MAX_WIDTH,
+    };
+    let color = if track.playing { theme.text } else { theme.label };
+    text.draw(img, font, VALUE_SIZE, color, state.marquee_tick);
+}
 
-    let brightness = state.brightness;
-    let pct_text = format!("{}%", brightness);
+/// Gray label with a single value below it, truncated to the quadrant
+fn draw_labeled_value(
+    img: &mut RgbImage,
+    font: &Font,
+    quad: Quadrant,
+    label: &str,
+    value: &str,
+    color: Rgb<u8>,
+) {
+    draw_text(img, font, label, quad.left(), quad.label_y(), LABEL_SIZE, theme::current().label);
+    let value = truncate_text(font, value, VALUE_SIZE, QUAD_WIDTH - PADDING * 2);
+    draw_text(img, font, &value, quad.left(), quad.value_y(), VALUE_SIZE, color);
+}
 
-    let pct_width = text_width(font, &pct_text, LABEL_SIZE);
-    let pct_x = QUAD_WIDTH * 2 - PADDING - pct_width;
-    draw_text(img, font, &pct_text, pct_x, y_label, LABEL_SIZE, BLUE);
+/// Gray label, right-aligned value and a progress bar filled to `pct`
+fn draw_meter(
+    img: &mut RgbImage,
+    font: &Font,
+    quad: Quadrant,
+    label: &str,
+    value: &str,
+    pct: u8,
+    color: Rgb<u8>,
+) {
+    let theme = theme::current();
+    draw_text(img, font, label, quad.left(), quad.label_y(), LABEL_SIZE, theme.label);
+
+    let value_x = quad.right() - text_width(font, value, LABEL_SIZE);
+    draw_text(img, font, value, value_x, quad.label_y(), LABEL_SIZE, color);
 
     // Progress bar
-    let bar_x = (QUAD_WIDTH + PADDING) as u32;
-    let bar_y = 28u32;
+    let bar_x = quad.left() as u32;
+    let bar_y = (quad.y + 28) as u32;
     let bar_w = (QUAD_WIDTH - PADDING * 2) as u32;
     let bar_h = 24u32;
 
     // Bar background
-    draw_filled_rect(img, bar_x, bar_y, bar_w, bar_h, Rgb([30, 32, 42]));
+    draw_filled_rect(img, bar_x, bar_y, bar_w, bar_h, theme.panel);
 
     // Filled portion
-    let fill_w = (bar_w as f32 * brightness as f32 / 100.0) as u32;
+    let fill_w = (bar_w as f32 * pct.min(100) as f32 / 100.0) as u32;
     if fill_w > 0 {
-        draw_filled_rect(img, bar_x, bar_y, fill_w, bar_h, BLUE);
+        draw_filled_rect(img, bar_x, bar_y, fill_w, bar_h, color);
     }
 }
 
-/// Bottom-left quadrant: Model
-fn draw_quadrant_model(img: &mut RgbImage, font: &Font, state: &AppState) {
-    let x = PADDING;
-    let y_label = QUAD_HEIGHT + 6;
-    let y_value = QUAD_HEIGHT + 26;
+/// Timer widget: time left over a bar that drains from green through orange to red
+fn draw_timer_widget(img: &mut RgbImage, font: &Font, state: &AppState, quad: Quadrant) {
+    let theme = theme::current();
+    let now = std::time::Instant::now();
+    let view = state.timer.view(now);
+    let time = format_remaining(view.secs_left);
+    match view.phase {
+        TimerPhase::Idle => draw_labeled_value(img, font, quad, "TIMER", &time, theme.label),
+        TimerPhase::Fired => {
+            if view.alarm_on {
+                draw_waiting_glow(img, quad);
+            }
+            draw_labeled_value(img, font, quad, "TIMER", "DONE", RED);
+        }
+        TimerPhase::Running | TimerPhase::Paused => {
+            let left = state.timer.fraction_left(now);
+            let color = if view.phase == TimerPhase::Paused {
+                theme.label
+            } else if left > 0.5 {
+                GREEN
+            } else if left > 0.2 {
+                ORANGE
+            } else {
+                RED
+            };
+            let pct = (left * 100.0).ceil() as u8;
+            draw_meter(img, font, quad, "TIMER", &time, pct, color);
+        }
+    }
+}
 
-    if state.model_selecting {
-        draw_text(img, font, "SELECT MODEL", x, y_label, LABEL_SIZE, GRAY);
-        draw_model_selector_compact(img, font, state, x, y_value);
+/// Green, then orange from 60%, then red from 85%
+fn load_color(pct: u8) -> Rgb<u8> {
+    if pct >= 85 {
+        RED
+    } else if pct >= 60 {
+        ORANGE
     } else {
-        draw_text(img, font, "MODEL", x, y_label, LABEL_SIZE, GRAY);
-        draw_text(img, font, &state.model.to_uppercase(), x, y_value, VALUE_SIZE, BLUE);
+        GREEN
     }
 }
 
-/// Bottom-right quadrant: Status/hints (or volume overlay)
-fn draw_quadrant_status(img: &mut RgbImage, font: &Font, state: &AppState) {
-    // Show volume overlay if active
-    if state.is_volume_display_active() {
-        draw_quadrant_volume(img, font, state);
-        return;
+/// Warm background glow + orange border drawn while waiting for input
+fn draw_waiting_glow(img: &mut RgbImage, quad: Quadrant) {
+    let (x, y) = (quad.x as u32, quad.y as u32);
+    let (w, h) = (QUAD_WIDTH as u32, QUAD_HEIGHT as u32);
+    draw_filled_rect(img, x + 4, y + 2, w - 8, h - 4, WAITING_GLOW_BG);
+    draw_waiting_border(img, x + 2, y, w - 4, h);
+}
+
+/// Task widget: current task name
+fn draw_task_widget(img: &mut RgbImage, font: &Font, state: &AppState, quad: Quadrant) {
+    let theme = theme::current();
+    let flash_on = state.waiting_for_input && state.waiting_flash_on;
+
+    if flash_on {
+        draw_waiting_glow(img, quad);
+    }
+
+    // Label
+    let label_color = if flash_on { ORANGE } else { theme.label };
+    let label = match state.approvals.len() {
+        0 => "TASK".to_string(),
+        1 => "APPROVE".to_string(),
+        queued => format!("APPROVE 1/{}", queued),
+    };
+    draw_text(img, font, &label, quad.left(), quad.label_y(), LABEL_SIZE, label_color);
+
+    // Value with color based on state
+    let task_color = if state.task_name == "ERROR" || state.task_name == "RATE LIMITED" {
+        RED
+    } else if state.waiting_for_input || !state.approvals.is_empty() {
+        if flash_on { WHITE } else { ORANGE }
+    } else if state.task_name == "THINKING" {
+        BRIGHT_PURPLE
+    } else if state.task_name == "READY" {
+        GREEN
+    } else {
+        theme.text
+    };
+
+    let text = Marquee {
+        text: task_text(state),
+        x: quad.left(),
+        y: quad.value_y(),
+        max_width: TASK_MAX_WIDTH,
+    };
+    text.draw(img, font, VALUE_SIZE, task_color, state.marquee_tick);
+}
+
+/// The task, or the tool of the permission request ACCEPT answers next
+fn task_text(state: &AppState) -> &str {
+    match state.approvals.head() {
+        Some(approval) => &approval.tool,
+        None => &state.task_name,
+    }
+}
+
+/// The tool detail, or the target of the next permission request
+fn detail_text(state: &AppState) -> Option<&str> {
+    match state.approvals.head() {
+        Some(approval) => approval.target.as_deref(),
+        None => state.tool_detail.as_deref(),
+    }
+}
+
+/// Detail widget: current tool detail
+fn draw_detail_widget(img: &mut RgbImage, font: &Font, state: &AppState, quad: Quadrant) {
+    let theme = theme::current();
+    // Label
+    draw_text(img, font, "DETAIL", quad.left(), quad.label_y(), LABEL_SIZE, theme.label);
+
+    // Value
+    if let Some(detail) = detail_text(state) {
+        let text = Marquee {
+            text: detail,
+            x: quad.left(),
+            y: quad.value_y(),
+            max_width: DETAIL_MAX_WIDTH,
+        };
+        text.draw(img, font, VALUE_SIZE, theme.text, state.marquee_tick);
+    } else {
+        draw_text(img, font, "-", quad.left(), quad.value_y(), VALUE_SIZE, theme.label);
     }
+}
 
-    let x = QUAD_WIDTH + PADDING;
-    let y_label = QUAD_HEIGHT + 6;
-    let y_value = QUAD_HEIGHT + 26;
+/// Status widget: connection status, or session usage once idle
+fn draw_status_widget(img: &mut RgbImage, font: &Font, state: &AppState, quad: Quadrant) {
+    let theme = theme::current();
     let flash_on = state.waiting_for_input && state.waiting_flash_on;
 
-    // Warm background glow + orange border when waiting + flash on
     if flash_on {
-        draw_filled_rect(
-            img,
-            QUAD_WIDTH as u32 + 4,
-            QUAD_HEIGHT as u32 + 2,
-            (QUAD_WIDTH - 8) as u32,
-            (QUAD_HEIGHT - 4) as u32,
-            WAITING_GLOW_BG,
-        );
-        draw_waiting_border(
-            img,
-            QUAD_WIDTH as u32 + 2,
-            QUAD_HEIGHT as u32,
-            (QUAD_WIDTH - 4) as u32,
-            QUAD_HEIGHT as u32,
-        );
+        draw_waiting_glow(img, quad);
+    }
+
+    if state.focus_mode.is_some() {
+        draw_moon(img, quad.right() - 8, quad.label_y() + 6, 6, MOON);
     }
 
     // Label
-    let label_color = if flash_on { ORANGE } else { GRAY };
-    draw_text(img, font, "STATUS", x, y_label, LABEL_SIZE, label_color);
+    let label_color = if flash_on { ORANGE } else { theme.label };
+
+    // Once connected and idle, the quadrant shows session usage instead of "CONNECTED"
+    let show_usage = state.connected
+        && has_usage(state)
+        && !state.screen_locked
+        && !state.deck_lock.is_locked()
+        && !state.model_selecting
+        && !state.waiting_for_input;
+    if show_usage {
+        draw_text(img, font, "USAGE", quad.left(), quad.label_y(), LABEL_SIZE, label_color);
+        draw_usage_meter(img, font, state, quad);
+        return;
+    }
+
+    draw_text(img, font, "STATUS", quad.left(), quad.label_y(), LABEL_SIZE, label_color);
 
     // Status value — text flashes to WHITE on bright phase
     let (status_text, status_color) = if state.screen_locked {
-        ("LOCKED", ORANGE)
+        ("LOCKED".to_string(), ORANGE)
+    } else if state.deck_lock.is_locked() {
+        (deck_lock_status(state), ORANGE)
     } else if state.model_selecting {
-        ("rotate to select", GRAY)
+        ("rotate to select".to_string(), theme.label)
     } else if state.waiting_for_input {
-        if flash_on { ("WAITING FOR INPUT", WHITE) } else { ("WAITING FOR INPUT", ORANGE) }
-    } else if state.connected {
-        ("CONNECTED", GREEN)
+        let color = if flash_on { WHITE } else { ORANGE };
+        ("WAITING FOR INPUT".to_string(), color)
     } else {
-        ("OFFLINE", RED)
+        device_status(state, "RECONNECTING")
     };
 
-    draw_text(img, font, status_text, x, y_value, VALUE_SIZE, status_color);
+    let (x, y) = (quad.left(), quad.value_y());
+    draw_text(img, font, &status_text, x, y, VALUE_SIZE, status_color);
+}
+
+/// Connection status text and color, numbering the next reconnect attempt
+fn device_status(state: &AppState, reconnecting: &str) -> (String, Rgb<u8>) {
+    if state.headless {
+        return ("HEADLESS".to_string(), GREEN);
+    }
+    match state.device_health {
+        DeviceHealth::Connected => ("CONNECTED".to_string(), GREEN),
+        DeviceHealth::Reconnecting { attempt } if attempt > 0 => {
+            (format!("{} #{}", reconnecting, attempt + 1), ORANGE)
+        }
+        DeviceHealth::Reconnecting { .. } | DeviceHealth::Failed { .. } => {
+            ("OFFLINE".to_string(), RED)
+        }
+    }
+}
+
+/// Crescent moon (a disc with an offset disc cut out), shown during Focus
+fn draw_moon(img: &mut RgbImage, cx: i32, cy: i32, r: i32, color: Rgb<u8>) {
+    for dy in -r..=r {
+        for dx in -r..=r {
+            let in_disc = dx * dx + dy * dy <= r * r;
+            let (cut_x, cut_y) = (dx - r / 2, dy + r / 3);
+            let in_cut = cut_x * cut_x + cut_y * cut_y <= r * r;
+            let (x, y) = (cx + dx, cy + dy);
+            let on_image = x >= 0 && y >= 0 && (x as u32) < img.width() && (y as u32) < img.height();
+            if in_disc && !in_cut && on_image {
+                img.put_pixel(x as u32, y as u32, color);
+            }
+        }
+    }
+}
+
+/// Whether hooks have reported any token/cost/context usage
+fn has_usage(state: &AppState) -> bool {
+    state.tokens.is_some() || state.cost_usd.is_some() || state.context_pct.is_some()
 }
 
-/// Bottom-right quadrant: Volume overlay (shown for 2s after encoder rotation)
-fn draw_quadrant_volume(img: &mut RgbImage, font: &Font, state: &AppState) {
-    let x = QUAD_WIDTH + PADDING;
-    let y_label = QUAD_HEIGHT + 6;
+/// Format a token count compactly (e.g., 950, 45.2k, 1.3M)
+fn format_tokens(tokens: u64) -> String {
+    if tokens >= 1_000_000 {
+        format!("{:.1}M", tokens as f64 / 1_000_000.0)
+    } else if tokens >= 1_000 {
+        format!("{:.1}k", tokens as f64 / 1_000.0)
+    } else {
+        tokens.to_string()
+    }
+}
+
+/// Cost/tokens with a context window meter
+fn draw_usage_meter(img: &mut RgbImage, font: &Font, state: &AppState, quad: Quadrant) {
+    let theme = theme::current();
+    let (x, y) = (quad.left(), quad.value_y());
+    let mut parts = Vec::new();
+    if let Some(cost) = state.cost_usd {
+        parts.push(format!("${:.2}", cost));
+    }
+    if let Some(tokens) = state.tokens {
+        parts.push(format!("{} tok", format_tokens(tokens)));
+    }
+    let text = parts.join("  ");
+    draw_text(img, font, &text, x, y, VALUE_SIZE, theme.text);
+
+    // Context meter fills whatever space is left to the right of the text
+    if let Some(pct) = state.context_pct {
+        let pct = pct.min(100);
+        let color = load_color(pct);
 
-    // Label + percentage
-    draw_text(img, font, "VOLUME", x, y_label, LABEL_SIZE, GRAY);
+        let pct_text = format!("{}%", pct);
+        let pct_width = text_width(font, &pct_text, LABEL_SIZE);
+        let pct_x = quad.right() - pct_width;
+        draw_text(img, font, &pct_text, pct_x, quad.label_y(), LABEL_SIZE, color);
 
-    let volume = state.volume;
+        let text_end = if text.is_empty() {
+            x
+        } else {
+            x + text_width(font, &text, VALUE_SIZE) + 12
+        };
+        let bar_end = quad.right();
+        if bar_end - text_end >= 40 {
+            let bar_x = text_end as u32;
+            let bar_y = (y + 6) as u32;
+            let bar_w = (bar_end - text_end) as u32;
+            let bar_h = 12u32;
+            draw_filled_rect(img, bar_x, bar_y, bar_w, bar_h, theme.panel);
+            let fill_w = (bar_w as f32 * pct as f32 / 100.0) as u32;
+            if fill_w > 0 {
+                draw_filled_rect(img, bar_x, bar_y, fill_w, bar_h, color);
+            }
+        }
+    }
+}
+
+/// Volume widget, also the overlay shown for 2s after encoder rotation
+fn draw_volume_widget(img: &mut RgbImage, font: &Font, volume: u8, quad: Quadrant) {
     let pct_text = if volume == 0 {
         "MUTED".to_string()
     } else {
         format!("{}%", volume)
     };
 
-    let pct_color = if volume == 0 {
+    let color = if volume == 0 {
         RED
     } else if volume > 80 {
         ORANGE
@@ -487,44 +1143,21 @@ fn draw_quadrant_volume(img: &mut RgbImage, font: &Font, state: &AppState) {
         GREEN
     };
 
-    let pct_width = text_width(font, &pct_text, LABEL_SIZE);
-    let pct_x = QUAD_WIDTH * 2 - PADDING - pct_width;
-    draw_text(img, font, &pct_text, pct_x, y_label, LABEL_SIZE, pct_color);
-
-    // Progress bar
-    let bar_x = (QUAD_WIDTH + PADDING) as u32;
-    let bar_y = (QUAD_HEIGHT + 28) as u32;
-    let bar_w = (QUAD_WIDTH - PADDING * 2) as u32;
-    let bar_h = 24u32;
-
-    // Bar background
-    draw_filled_rect(img, bar_x, bar_y, bar_w, bar_h, Rgb([30, 32, 42]));
-
-    // Filled portion
-    let fill_w = (bar_w as f32 * volume as f32 / 100.0) as u32;
-    if fill_w > 0 {
-        let bar_color = if volume == 0 {
-            RED
-        } else if volume > 80 {
-            ORANGE
-        } else {
-            GREEN
-        };
-        draw_filled_rect(img, bar_x, bar_y, fill_w, bar_h, bar_color);
-    }
+    draw_meter(img, font, quad, "VOLUME", &pct_text, volume, color);
 }
 
-/// Compact model selector for bottom-left quadrant
-fn draw_model_selector_compact(img: &mut RgbImage, font: &Font, state: &AppState, start_x: i32, y: i32) {
-    let mut x = start_x;
+/// Compact model selector
+fn draw_model_selector_compact(img: &mut RgbImage, font: &Font, state: &AppState, quad: Quadrant) {
+    let mut x = quad.left();
+    let y = quad.value_y();
     let scale = 18.0;
     let spacing = 15;
-    let max_x = QUAD_WIDTH - PADDING;
+    let max_x = quad.right();
 
     for (i, model) in state.available_models.iter().enumerate() {
         let is_selected = i == state.model_index;
-        let color = if is_selected { GREEN } else { GRAY };
-        let model_upper = model.to_uppercase();
+        let color = if is_selected { GREEN } else { theme::current().label };
+        let model_upper = state.model_label(model);
         let model_width = text_width(font, &model_upper, scale);
 
         if x + model_width > max_x {
@@ -540,6 +1173,56 @@ fn draw_model_selector_compact(img: &mut RgbImage, font: &Font, state: &AppState
     }
 }
 
+/// Strip value that scrolls sideways when it is wider than its space
+struct Marquee<'a> {
+    text: &'a str,
+    x: i32,
+    y: i32,
+    max_width: i32,
+}
+
+impl Marquee<'_> {
+    /// Draw the text at marquee step `tick`, clipped to its width
+    fn draw(&self, img: &mut RgbImage, font: &Font, scale: f32, color: Rgb<u8>, tick: u32) {
+        let width = text_width(font, self.text, scale);
+        if width <= self.max_width {
+            draw_text(img, font, self.text, self.x, self.y, scale, color);
+            return;
+        }
+
+        // Draw into a copy of the area behind the text so nothing spills out
+        let (x, y) = (self.x.max(0) as u32, self.y.max(0) as u32);
+        let w = (self.max_width as u32).min(img.width().saturating_sub(x));
+        let h = (scale as u32 + 8).min(img.height().saturating_sub(y));
+        let mut area = imageops::crop_imm(img, x, y, w, h).to_image();
+        let offset = marquee_offset(width, tick);
+        draw_text(&mut area, font, self.text, -offset, 0, scale, color);
+        draw_text(&mut area, font, self.text, width + MARQUEE_GAP - offset, 0, scale, color);
+        imageops::replace(img, &area, x as i64, y as i64);
+    }
+}
+
+/// Pixels scrolled at `tick` for text `width` wide: rest, scroll one loop, repeat
+fn marquee_offset(width: i32, tick: u32) -> i32 {
+    let loop_width = width + MARQUEE_GAP;
+    let scroll_ticks = (loop_width + MARQUEE_STEP - 1) / MARQUEE_STEP;
+    let step = tick % (MARQUEE_PAUSE + scroll_ticks as u32);
+    (step.saturating_sub(MARQUEE_PAUSE) as i32 * MARQUEE_STEP).min(loop_width)
+}
+
+/// Whether a configured strip widget has text too wide for it (so it scrolls)
+pub fn strip_scrolls(font: &Font, state: &AppState) -> bool {
+    state.strip_widgets.iter().any(|widget| match widget {
+        StripWidget::Task => text_width(font, task_text(state), VALUE_SIZE) > TASK_MAX_WIDTH,
+        StripWidget::Detail => detail_text(state)
+            .is_some_and(|detail| text_width(font, detail, VALUE_SIZE) > DETAIL_MAX_WIDTH),
+        StripWidget::NowPlaying => state.now_playing.as_ref().is_some_and(|track| {
+            text_width(font, &track.summary(), VALUE_SIZE) > NOW_PLAYING_MAX_WIDTH
+        }),
+        _ => false,
+    })
+}
+
 /// Truncate text to fit width, adding ".." if needed
 fn truncate_text(font: &Font, text: &str, scale: f32, max_width: i32) -> String {
     let mut display = text.to_string();
@@ -556,26 +1239,6 @@ fn truncate_text(font: &Font, text: &str, scale: f32, max_width: i32) -> String
     display
 }
 
-/// Truncate path, keeping filename visible
-fn truncate_text_path(font: &Font, text: &str, scale: f32, max_width: i32) -> String {
-    if text_width(font, text, scale) <= max_width {
-        return text.to_string();
-    }
-
-    // For paths, try to show the end (filename)
-    if let Some(idx) = text.rfind('/') {
-        let filename = &text[idx..];
-        if text_width(font, filename, scale) <= max_width {
-            let prefix = format!("..{}", filename);
-            if text_width(font, &prefix, scale) <= max_width {
-                return prefix;
-            }
-        }
-    }
-
-    truncate_text(font, text, scale, max_width)
-}
-
 /// Draw an orange accent border (2px) around a rectangular region
 fn draw_waiting_border(img: &mut RgbImage, x: u32, y: u32, w: u32, h: u32) {
     let color = ORANGE;
@@ -607,10 +1270,10 @@ fn draw_waiting_border(img: &mut RgbImage, x: u32, y: u32, w: u32, h: u32) {
 
 /// Draw a horizontal separator line
 fn draw_separator(img: &mut RgbImage, y: u32) {
-    let color = Rgb([45, 50, 65]);
+    let (color, shadow) = theme::current().separator;
     for x in 15..(STRIP_WIDTH - 15) {
         img.put_pixel(x, y, color);
-        img.put_pixel(x, y + 1, Rgb([25, 28, 38])); // Shadow
+        img.put_pixel(x, y + 1, shadow);
     }
 }
 
@@ -628,5 +1291,92 @@ mod tests {
 
         assert_eq!(img.width(), STRIP_WIDTH);
         assert_eq!(img.height(), STRIP_HEIGHT);
+
+        // Text entry replaces the quadrants entirely
+        let mut entry_state = AppState::new();
+        let mut entry = TextEntry::new(4);
+        entry.buffer = "ABC-123".repeat(10);
+        entry_state.text_entry = Some(entry);
+        let entry_img = render_strip_image(&font, &entry_state).unwrap();
+        assert_ne!(entry_img, img);
+    }
+
+    #[test]
+    fn test_strip_widgets() {
+        let font_data = include_bytes!("../../assets/fonts/JetBrainsMono-Bold.ttf");
+        let font = Font::try_from_bytes(font_data as &[u8]).unwrap();
+
+        let mut state = AppState::new();
+        state.strip_widgets = Default::default();
+        let empty = render_strip_image(&font, &state).unwrap();
+
+        state.strip_widgets[3] = StripWidget::Cpu;
+        state.cpu_pct = Some(40);
+        let cpu = render_strip_image(&font, &state).unwrap();
+        assert_ne!(cpu, empty);
+
+        // The volume overlay replaces whatever the bottom-right quadrant shows
+        state.adjust_volume(1);
+        let overlay = render_strip_image(&font, &state).unwrap();
+        state.strip_widgets[3] = StripWidget::Empty;
+        assert_eq!(render_strip_image(&font, &state).unwrap(), overlay);
+
+        state.strip_widgets[0] = StripWidget::Timer;
+        let idle = render_strip_image(&font, &state).unwrap();
+        state.timer.toggle(std::time::Instant::now());
+        assert_ne!(render_strip_image(&font, &state).unwrap(), idle);
+    }
+
+    #[test]
+    fn test_strip_overlay() {
+        let font_data = include_bytes!("../../assets/fonts/JetBrainsMono-Bold.ttf");
+        let font = Font::try_from_bytes(font_data as &[u8]).unwrap();
+
+        let mut state = AppState::new();
+        let quadrants = render_strip_image(&font, &state).unwrap();
+        state.adjust_brightness(-1);
+        let overlay = state.strip_overlay.unwrap();
+        assert_eq!(overlay.kind, OverlayKind::Brightness);
+        assert!(!state.is_brightness_display_active());
+        assert_ne!(render_strip_image(&font, &state).unwrap(), quadrants);
+
+        // Slides in from below, then rests until it expires
+        let now = Instant::now();
+        let overlay = StripOverlay::new(OverlayKind::Model, now);
+        assert_eq!(overlay.offset(now), STRIP_HEIGHT as i64);
+        assert!(overlay.is_sliding(now + OVERLAY_SLIDE / 2));
+        assert_eq!(overlay.offset(now + OVERLAY_SLIDE), 0);
+        assert!(overlay.is_active(now + OVERLAY_DURATION / 2));
+        assert!(!overlay.is_active(now + OVERLAY_DURATION));
+    }
+
+    #[test]
+    fn test_marquee_scrolls_only_overlong_text() {
+        let font_data = include_bytes!("../../assets/fonts/JetBrainsMono-Bold.ttf");
+        let font = Font::try_from_bytes(font_data as &[u8]).unwrap();
+
+        let mut state = AppState::new();
+        state.task_name = "Bash".to_string();
+        assert!(!strip_scrolls(&font, &state));
+        state.tool_detail = Some("src/display/strip.rs and a long, long tail".to_string());
+        assert!(strip_scrolls(&font, &state));
+
+        // Rests at the start, then moves, then comes back round
+        assert_eq!(marquee_offset(300, 0), 0);
+        assert_eq!(marquee_offset(300, MARQUEE_PAUSE), 0);
+        assert_eq!(marquee_offset(300, MARQUEE_PAUSE + 10), 10 * MARQUEE_STEP);
+        let loop_ticks = MARQUEE_PAUSE + (300 + MARQUEE_GAP) as u32 / MARQUEE_STEP as u32 + 1;
+        assert_eq!(marquee_offset(300, loop_ticks), 0);
+
+        let start = render_strip_image(&font, &state).unwrap();
+        state.marquee_tick = MARQUEE_PAUSE + 5;
+        assert_ne!(render_strip_image(&font, &state).unwrap(), start);
+    }
+
+    #[test]
+    fn test_format_tokens() {
+        assert_eq!(format_tokens(950), "950");
+        assert_eq!(format_tokens(45_200), "45.2k");
+        assert_eq!(format_tokens(1_300_000), "1.3M");
     }
 }
@@ -0,0 +1,1014 @@
+//! SVG icons for buttons (`icon_svg`)
+//!
+//! An icon is inline SVG markup or the path of a `.svg` file, drawn into the
+//! 90x90 image area. The built-in rasterizer only handles filled shapes, the
+//! subset flat icon sets such as Material use:
+//!
+//! - elements: `svg` (`viewBox`, or `width` and `height`), `g`, `path`, `rect`
+//!   (with `rx`/`ry`) and `circle`
+//! - paint: `fill` (`none`, `currentColor`, `#rgb`, `#rrggbb`, `black`,
+//!   `white`) and `fill-rule` (`nonzero`, `evenodd`)
+//!
+//! Anything else (strokes, transforms, opacity, `style`, gradients, text, other
+//! elements) is an error rather than being drawn wrong, and the button falls
+//! back to its label. `currentColor`, and a missing `fill`, take the button's
+//! label color so unstyled icons show up on the dark buttons.
+
+use ab_glyph_rasterizer::{point, Rasterizer};
+use anyhow::{bail, Context, Result};
+use image::{Rgb, RgbaImage};
+use std::collections::HashMap;
+use std::f32::consts::PI;
+use std::sync::{Mutex, OnceLock};
+use std::time::SystemTime;
+use tracing::warn;
+
+/// Rendered icon size (buttons are 112x112)
+pub const ICON_SIZE: u32 = 90;
+
+/// Line segments per Bézier curve
+const CURVE_STEPS: usize = 16;
+
+/// Elements that can be drawn, and the attributes each may have besides
+/// `fill`, `fill-rule` and the ones that don't affect drawing (see `check`)
+const ELEMENTS: &[(&str, &[&str])] = &[
+    ("svg", &["viewBox", "width", "height", "version"]),
+    ("g", &[]),
+    ("path", &["d"]),
+    ("rect", &["x", "y", "width", "height", "rx", "ry"]),
+    ("circle", &["cx", "cy", "r"]),
+];
+
+/// A rendered icon (`None` when rendering failed) and, for a file, the
+/// modification time it was rendered from
+type CachedIcon = (Option<SystemTime>, Option<RgbaImage>);
+
+/// Rendered icons by source and color
+type IconCache = HashMap<(String, [u8; 3]), CachedIcon>;
+
+/// Render an icon in `color`, cached by source and color
+///
+/// An icon file is rendered again once its modification time changes, so
+/// editing it (or creating a missing one) shows up without a restart.
+pub fn render(source: &str, color: Rgb<u8>) -> Option<RgbaImage> {
+    static CACHE: OnceLock<Mutex<IconCache>> = OnceLock::new();
+    let path = file_path(source);
+    let modified = path.as_ref().and_then(|path| std::fs::metadata(path).ok()?.modified().ok());
+    let key = (source.to_string(), color.0);
+    let mut cache = CACHE
+        .get_or_init(|| Mutex::new(HashMap::new()))
+        .lock()
+        .ok()?;
+    if let Some((rendered_from, icon)) = cache.get(&key) {
+        if *rendered_from == modified {
+            return icon.clone();
+        }
+    }
+    // Failures are cached too, so a broken icon is reported once per version
+    let result = load(source, path.as_deref())
+        .and_then(|markup| rasterize(&markup, color, ICON_SIZE))
+        .map_err(|e| warn!("Failed to render SVG icon: {:#}", e))
+        .ok();
+    cache.insert(key, (modified, result.clone()));
+    result
+}
+
+/// The file an icon source names (None for inline markup)
+fn file_path(source: &str) -> Option<String> {
+    let source = source.trim();
+    if source.starts_with('<') {
+        return None;
+    }
+    Some(match source.strip_prefix("~/") {
+        Some(rest) => format!("{}/{}", std::env::var("HOME").unwrap_or_default(), rest),
+        None => source.to_string(),
+    })
+}
+
+/// Inline markup, or the contents of the file it names
+fn load(source: &str, path: Option<&str>) -> Result<String> {
+    match path {
+        Some(path) => {
+            std::fs::read_to_string(path).with_context(|| format!("Failed to read {}", path))
+        }
+        None => Ok(source.trim().to_string()),
+    }
+}
+
+/// Draw SVG markup into a `size`x`size` image, scaled to fit its viewBox
+pub fn rasterize(markup: &str, color: Rgb<u8>, size: u32) -> Result<RgbaImage> {
+    let mut canvas = Canvas::new(size);
+    // Fills inherited from the open <svg> and <g> elements
+    let mut stack: Vec<Fill> = Vec::new();
+    let mut view: Option<ViewBox> = None;
+
+    for tag in tags(markup) {
+        if tag.closing {
+            stack.pop();
+            continue;
+        }
+        let parent = match (stack.last(), view) {
+            (Some(_), _) if tag.name == "svg" => bail!("nested <svg> elements are not supported"),
+            (Some(fill), _) => *fill,
+            (None, None) if tag.name == "svg" => {
+                view = Some(ViewBox::fit(&tag, size as f32));
+                Fill::ROOT
+            }
+            (None, _) => bail!("<{}> outside the <svg> element", tag.name),
+        };
+        check(&tag)?;
+        let fill = parent.apply(&tag)?;
+        if let Some(view) = view.filter(|_| tag.name != "svg" && tag.name != "g") {
+            canvas.fill(&shape(&tag), view, fill, color);
+        }
+        if !tag.empty {
+            stack.push(fill);
+        }
+    }
+
+    if view.is_none() {
+        bail!("no <svg> element");
+    }
+    Ok(canvas.into_image())
+}
+
+/// Refuse elements and attributes outside the supported subset
+fn check(tag: &Tag) -> Result<()> {
+    let Some((_, allowed)) = ELEMENTS.iter().find(|(name, _)| *name == tag.name) else {
+        bail!("<{}> is not supported", tag.name);
+    };
+    for (name, _) in &tag.attrs {
+        let harmless = ["id", "class", "fill", "fill-rule"].contains(name)
+            || ["xmlns", "data-", "aria-"].iter().any(|prefix| name.starts_with(prefix));
+        if !harmless && !allowed.contains(name) {
+            bail!("{} on <{}> is not supported", name, tag.name);
+        }
+    }
+    Ok(())
+}
+
+// --- Markup ---
+
+/// A start, end or self-closing tag
+struct Tag<'a> {
+    name: &'a str,
+    attrs: Vec<(&'a str, &'a str)>,
+    closing: bool,
+    empty: bool,
+}
+
+impl Tag<'_> {
+    fn attr(&self, name: &str) -> Option<&str> {
+        self.attrs.iter().find(|(n, _)| *n == name).map(|(_, v)| *v)
+    }
+
+    fn number(&self, name: &str) -> f32 {
+        self.attr(name)
+            .and_then(|v| numbers(v).first().copied())
+            .unwrap_or(0.0)
+    }
+}
+
+/// The tags of a document, skipping comments, declarations and text
+fn tags(markup: &str) -> Vec<Tag<'_>> {
+    let mut tags = Vec::new();
+    let mut rest = markup;
+    while let Some(start) = rest.find('<') {
+        rest = &rest[start + 1..];
+        if let Some(comment) = rest.strip_prefix("!--") {
+            rest = comment.split_once("-->").map_or("", |(_, after)| after);
+            continue;
+        }
+        let Some(end) = tag_end(rest) else {
+            break;
+        };
+        let body = &rest[..end];
+        rest = &rest[end + 1..];
+        if body.starts_with('?') || body.starts_with('!') {
+            continue;
+        }
+        let (closing, body) = match body.strip_prefix('/') {
+            Some(body) => (true, body),
+            None => (false, body),
+        };
+        let (empty, body) = match body.strip_suffix('/') {
+            Some(body) => (true, body),
+            None => (false, body),
+        };
+        let name_end = body.find(|c: char| c.is_whitespace()).unwrap_or(body.len());
+        // Namespaced names ("svg:path") are treated as plain ones
+        let name = body[..name_end].rsplit(':').next().unwrap_or_default();
+        tags.push(Tag {
+            name,
+            attrs: attributes(&body[name_end..]),
+            closing,
+            empty,
+        });
+    }
+    tags
+}
+
+/// Index of the `>` ending a tag, ignoring any inside quoted values
+fn tag_end(s: &str) -> Option<usize> {
+    let mut quote = None;
+    for (i, c) in s.char_indices() {
+        match (quote, c) {
+            (None, '"' | '\'') => quote = Some(c),
+            (Some(q), _) if q == c => quote = None,
+            (None, '>') => return Some(i),
+            _ => {}
+        }
+    }
+    None
+}
+
+fn attributes(s: &str) -> Vec<(&str, &str)> {
+    let mut attrs = Vec::new();
+    let mut rest = s;
+    while let Some((name, after)) = rest.split_once('=') {
+        let after = after.trim_start();
+        let Some(quote) = after.chars().next().filter(|c| *c == '"' || *c == '\'') else {
+            break;
+        };
+        let Some((value, after)) = after[1..].split_once(quote) else {
+            break;
+        };
+        attrs.push((name.trim(), value));
+        rest = after;
+    }
+    attrs
+}
+
+// --- Fills ---
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Paint {
+    None,
+    /// `currentColor`: the label color
+    Current,
+    Color(Rgb<u8>),
+}
+
+/// `fill-rule`: which areas enclosed by a path's subpaths are filled
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum FillRule {
+    NonZero,
+    EvenOdd,
+}
+
+impl FillRule {
+    /// Pixel coverage from the rasterizer's accumulated winding
+    fn coverage(self, winding: f32) -> f32 {
+        match self {
+            FillRule::NonZero => winding.min(1.0),
+            // Distance to the nearest even winding number
+            FillRule::EvenOdd => (winding - 2.0 * (winding / 2.0).round()).abs(),
+        }
+    }
+}
+
+/// The inherited `fill` and `fill-rule`
+#[derive(Debug, Clone, Copy)]
+struct Fill {
+    paint: Paint,
+    rule: FillRule,
+}
+
+impl Fill {
+    const ROOT: Self = Self {
+        paint: Paint::Current,
+        rule: FillRule::NonZero,
+    };
+
+    /// This fill with an element's `fill` and `fill-rule` applied
+    fn apply(mut self, tag: &Tag) -> Result<Self> {
+        if let Some(value) = tag.attr("fill") {
+            self.paint = paint(value).with_context(|| format!("fill {:?}", value))?;
+        }
+        self.rule = match tag.attr("fill-rule") {
+            None => self.rule,
+            Some("nonzero") => FillRule::NonZero,
+            Some("evenodd") => FillRule::EvenOdd,
+            Some(other) => bail!("fill-rule {:?} is not supported", other),
+        };
+        Ok(self)
+    }
+}
+
+fn paint(value: &str) -> Result<Paint> {
+    let value = value.trim();
+    let color = |r, g, b| Ok(Paint::Color(Rgb([r, g, b])));
+    match value {
+        "none" => return Ok(Paint::None),
+        "currentColor" | "inherit" => return Ok(Paint::Current),
+        "black" => return color(0, 0, 0),
+        "white" => return color(255, 255, 255),
+        _ => {}
+    }
+    let hex = value.strip_prefix('#').context("only #hex colors are supported")?;
+    let digit = |i: usize| u8::from_str_radix(hex.get(i..i + 1)?, 16).ok();
+    let byte = |i: usize| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok();
+    let rgb = match hex.len() {
+        3 => (|| Some((digit(0)? * 17, digit(1)? * 17, digit(2)? * 17)))(),
+        6 => (|| Some((byte(0)?, byte(2)?, byte(4)?)))(),
+        _ => None,
+    };
+    let (r, g, b) = rgb.context("bad hex color")?;
+    color(r, g, b)
+}
+
+// --- Geometry ---
+
+/// Maps the root's viewBox onto the image, centered and scaled to fit
+#[derive(Debug, Clone, Copy)]
+struct ViewBox {
+    scale: f32,
+    offset: (f32, f32),
+}
+
+impl ViewBox {
+    fn fit(svg: &Tag, size: f32) -> Self {
+        let view_box = svg.attr("viewBox").map(numbers).filter(|v| v.len() == 4);
+        let (x, y, w, h) = match view_box.as_deref() {
+            Some(&[x, y, w, h]) if w > 0.0 && h > 0.0 => (x, y, w, h),
+            _ => {
+                let dimension = |name| Some(svg.number(name)).filter(|v| *v > 0.0);
+                (
+                    0.0,
+                    0.0,
+                    dimension("width").unwrap_or(size),
+                    dimension("height").unwrap_or(size),
+                )
+            }
+        };
+        let scale = (size / w).min(size / h);
+        let offset = (
+            (size - w * scale) / 2.0 - x * scale,
+            (size - h * scale) / 2.0 - y * scale,
+        );
+        Self { scale, offset }
+    }
+
+    fn apply(&self, (x, y): (f32, f32)) -> (f32, f32) {
+        (self.offset.0 + x * self.scale, self.offset.1 + y * self.scale)
+    }
+}
+
+/// A run of connected points
+#[derive(Debug, Clone, Default, PartialEq)]
+struct Subpath {
+    points: Vec<(f32, f32)>,
+    closed: bool,
+}
+
+/// Turns path commands into flattened subpaths
+#[derive(Default)]
+struct PathBuilder {
+    subpaths: Vec<Subpath>,
+    current: Subpath,
+}
+
+impl PathBuilder {
+    fn position(&self) -> (f32, f32) {
+        self.current.points.last().copied().unwrap_or_default()
+    }
+
+    fn move_to(&mut self, p: (f32, f32)) {
+        self.finish();
+        self.current.points.push(p);
+    }
+
+    fn line_to(&mut self, p: (f32, f32)) {
+        if self.current.points.is_empty() {
+            self.current.points.push((0.0, 0.0));
+        }
+        self.current.points.push(p);
+    }
+
+    fn cubic_to(&mut self, c1: (f32, f32), c2: (f32, f32), p: (f32, f32)) {
+        let p0 = self.position();
+        for i in 1..=CURVE_STEPS {
+            let t = i as f32 / CURVE_STEPS as f32;
+            let u = 1.0 - t;
+            let (a, b, c, d) = (u * u * u, 3.0 * u * u * t, 3.0 * u * t * t, t * t * t);
+            self.line_to((
+                a * p0.0 + b * c1.0 + c * c2.0 + d * p.0,
+                a * p0.1 + b * c1.1 + c * c2.1 + d * p.1,
+            ));
+        }
+    }
+
+    fn quad_to(&mut self, c: (f32, f32), p: (f32, f32)) {
+        let p0 = self.position();
+        for i in 1..=CURVE_STEPS {
+            let t = i as f32 / CURVE_STEPS as f32;
+            let u = 1.0 - t;
+            self.line_to((
+                u * u * p0.0 + 2.0 * u * t * c.0 + t * t * p.0,
+                u * u * p0.1 + 2.0 * u * t * c.1 + t * t * p.1,
+            ));
+        }
+    }
+
+    /// Elliptical arc, following the SVG spec's endpoint-to-center conversion
+    fn arc_to(
+        &mut self,
+        radii: (f32, f32),
+        rotation: f32,
+        large: bool,
+        sweep: bool,
+        p: (f32, f32),
+    ) {
+        let p0 = self.position();
+        let (mut rx, mut ry) = (radii.0.abs(), radii.1.abs());
+        if rx == 0.0 || ry == 0.0 || p0 == p {
+            self.line_to(p);
+            return;
+        }
+        let (sin, cos) = rotation.to_radians().sin_cos();
+        let (dx, dy) = ((p0.0 - p.0) / 2.0, (p0.1 - p.1) / 2.0);
+        let (x1, y1) = (cos * dx + sin * dy, -sin * dx + cos * dy);
+        let lambda = (x1 * x1) / (rx * rx) + (y1 * y1) / (ry * ry);
+        if lambda > 1.0 {
+            rx *= lambda.sqrt();
+            ry *= lambda.sqrt();
+        }
+        let numerator = rx * rx * ry * ry - rx * rx * y1 * y1 - ry * ry * x1 * x1;
+        let denominator = rx * rx * y1 * y1 + ry * ry * x1 * x1;
+        let sign = if large == sweep { -1.0 } else { 1.0 };
+        let coef = sign * (numerator / denominator).max(0.0).sqrt();
+        let (cx1, cy1) = (coef * rx * y1 / ry, -coef * ry * x1 / rx);
+        let center = (
+            cos * cx1 - sin * cy1 + (p0.0 + p.0) / 2.0,
+            sin * cx1 + cos * cy1 + (p0.1 + p.1) / 2.0,
+        );
+
+        let angle =
+            |ux: f32, uy: f32, vx: f32, vy: f32| (ux * vy - uy * vx).atan2(ux * vx + uy * vy);
+        let start = angle(1.0, 0.0, (x1 - cx1) / rx, (y1 - cy1) / ry);
+        let mut delta = angle(
+            (x1 - cx1) / rx,
+            (y1 - cy1) / ry,
+            (-x1 - cx1) / rx,
+            (-y1 - cy1) / ry,
+        );
+        if !sweep && delta > 0.0 {
+            delta -= 2.0 * PI;
+        } else if sweep && delta < 0.0 {
+            delta += 2.0 * PI;
+        }
+
+        let steps = ((delta.abs() / (PI / 12.0)).ceil() as usize).max(1);
+        for i in 1..steps {
+            let (s, c) = (start + delta * i as f32 / steps as f32).sin_cos();
+            self.line_to((
+                center.0 + rx * c * cos - ry * s * sin,
+                center.1 + rx * c * sin + ry * s * cos,
+            ));
+        }
+        self.line_to(p);
+    }
+
+    /// Close the subpath; drawing continues from its start
+    fn close(&mut self) {
+        let start = self.current.points.first().copied();
+        self.current.closed = true;
+        self.finish();
+        self.current.points.extend(start);
+    }
+
+    fn finish(&mut self) {
+        let subpath = std::mem::take(&mut self.current);
+        if subpath.points.len() > 1 {
+            self.subpaths.push(subpath);
+        }
+    }
+
+    fn build(mut self) -> Vec<Subpath> {
+        self.finish();
+        self.subpaths
+    }
+}
+
+/// The outline of a `path`, `rect` or `circle`, in viewBox coordinates
+fn shape(tag: &Tag) -> Vec<Subpath> {
+    let n = |name| tag.number(name);
+    let mut path = PathBuilder::default();
+    match tag.name {
+        "path" => return parse_path(tag.attr("d").unwrap_or_default()),
+        "rect" => {
+            let (x, y, w, h) = (n("x"), n("y"), n("width"), n("height"));
+            if w <= 0.0 || h <= 0.0 {
+                return Vec::new();
+            }
+            let (rx, ry) = match (
+                tag.attr("rx").map(|_| n("rx")),
+                tag.attr("ry").map(|_| n("ry")),
+            ) {
+                (Some(rx), Some(ry)) => (rx, ry),
+                (Some(r), None) | (None, Some(r)) => (r, r),
+                (None, None) => (0.0, 0.0),
+            };
+            let r = (rx.min(w / 2.0), ry.min(h / 2.0));
+            path.move_to((x + r.0, y));
+            path.line_to((x + w - r.0, y));
+            path.arc_to(r, 0.0, false, true, (x + w, y + r.1));
+            path.line_to((x + w, y + h - r.1));
+            path.arc_to(r, 0.0, false, true, (x + w - r.0, y + h));
+            path.line_to((x + r.0, y + h));
+            path.arc_to(r, 0.0, false, true, (x, y + h - r.1));
+            path.line_to((x, y + r.1));
+            path.arc_to(r, 0.0, false, true, (x + r.0, y));
+            path.close();
+        }
+        "circle" => {
+            let (cx, cy, r) = (n("cx"), n("cy"), n("r"));
+            if r <= 0.0 {
+                return Vec::new();
+            }
+            path.move_to((cx + r, cy));
+            path.arc_to((r, r), 0.0, false, true, (cx - r, cy));
+            path.arc_to((r, r), 0.0, false, true, (cx + r, cy));
+            path.close();
+        }
+        _ => {}
+    }
+    path.build()
+}
+
+/// Reads numbers and flags out of path data and lists
+struct Scanner<'a> {
+    s: &'a [u8],
+    pos: usize,
+}
+
+impl Scanner<'_> {
+    fn skip_separators(&mut self) {
+        while self
+            .s
+            .get(self.pos)
+            .is_some_and(|c| c.is_ascii_whitespace() || *c == b',')
+        {
+            self.pos += 1;
+        }
+    }
+
+    /// The next number, allowing "1.5.5" and "10-5" style packing
+    fn number(&mut self) -> Option<f32> {
+        self.skip_separators();
+        let start = self.pos;
+        let at = |i: usize| self.s.get(i).copied().unwrap_or(0);
+        let mut i = start;
+        if matches!(at(i), b'+' | b'-') {
+            i += 1;
+        }
+        let digits = |mut i: usize| {
+            while at(i).is_ascii_digit() {
+                i += 1;
+            }
+            i
+        };
+        let int_end = digits(i);
+        let mut end = int_end;
+        if at(end) == b'.' {
+            end = digits(end + 1);
+        }
+        if end == i || (end == i + 1 && at(i) == b'.') {
+            return None;
+        }
+        if matches!(at(end), b'e' | b'E') {
+            let mut exp = end + 1;
+            if matches!(at(exp), b'+' | b'-') {
+                exp += 1;
+            }
+            if at(exp).is_ascii_digit() {
+                end = digits(exp);
+            }
+        }
+        let text = std::str::from_utf8(&self.s[start..end]).ok()?;
+        // Out of range ("1e39") is an error, as the rasterizer can't take infinities
+        let value = text.parse().ok().filter(|v: &f32| v.is_finite())?;
+        self.pos = end;
+        Some(value)
+    }
+
+    fn point(&mut self) -> Option<(f32, f32)> {
+        Some((self.number()?, self.number()?))
+    }
+
+    /// An arc flag, which needs no separator after it
+    fn flag(&mut self) -> Option<bool> {
+        self.skip_separators();
+        let flag = match self.s.get(self.pos)? {
+            b'0' => false,
+            b'1' => true,
+            _ => return None,
+        };
+        self.pos += 1;
+        Some(flag)
+    }
+}
+
+/// Every number in a list such as a viewBox or `points`
+fn numbers(s: &str) -> Vec<f32> {
+    let mut scanner = Scanner {
+        s: s.as_bytes(),
+        pos: 0,
+    };
+    std::iter::from_fn(|| scanner.number()).collect()
+}
+
+/// Path data (`d`), stopping at the first error as the spec says
+fn parse_path(d: &str) -> Vec<Subpath> {
+    let mut scanner = Scanner {
+        s: d.as_bytes(),
+        pos: 0,
+    };
+    let mut path = PathBuilder::default();
+    let mut command = b'M';
+    // Control point reflected by S and T
+    let mut last_control: Option<(u8, (f32, f32))> = None;
+
+    loop {
+        scanner.skip_separators();
+        let Some(&c) = scanner.s.get(scanner.pos) else {
+            break;
+        };
+        if c.is_ascii_alphabetic() {
+            command = c;
+            scanner.pos += 1;
+            if command.eq_ignore_ascii_case(&b'z') {
+                path.close();
+                last_control = None;
+                continue;
+            }
+        } else if command.eq_ignore_ascii_case(&b'z') {
+            break;
+        }
+
+        let origin = path.position();
+        let relative = command.is_ascii_lowercase();
+        let abs = |(x, y): (f32, f32)| {
+            if relative {
+                (origin.0 + x, origin.1 + y)
+            } else {
+                (x, y)
+            }
+        };
+        let reflected = |kinds: &[u8]| match last_control {
+            Some((kind, (x, y))) if kinds.contains(&kind) => {
+                (2.0 * origin.0 - x, 2.0 * origin.1 - y)
+            }
+            _ => origin,
+        };
+
+        let drawn = match command.to_ascii_uppercase() {
+            b'M' => scanner.point().map(|p| {
+                path.move_to(abs(p));
+                // Further pairs after a moveto are linetos
+                command = if relative { b'l' } else { b'L' };
+                None
+            }),
+            b'L' => scanner.point().map(|p| {
+                path.line_to(abs(p));
+                None
+            }),
+            b'H' => scanner.number().map(|x| {
+                path.line_to((if relative { origin.0 + x } else { x }, origin.1));
+                None
+            }),
+            b'V' => scanner.number().map(|y| {
+                path.line_to((origin.0, if relative { origin.1 + y } else { y }));
+                None
+            }),
+            b'C' => (|| Some((scanner.point()?, scanner.point()?, scanner.point()?)))().map(
+                |(c1, c2, p)| {
+                    path.cubic_to(abs(c1), abs(c2), abs(p));
+                    Some((b'C', abs(c2)))
+                },
+            ),
+            b'S' => (|| Some((scanner.point()?, scanner.point()?)))().map(|(c2, p)| {
+                path.cubic_to(reflected(b"CS"), abs(c2), abs(p));
+                Some((b'C', abs(c2)))
+            }),
+            b'Q' => (|| Some((scanner.point()?, scanner.point()?)))().map(|(c, p)| {
+                path.quad_to(abs(c), abs(p));
+                Some((b'Q', abs(c)))
+            }),
+            b'T' => scanner.point().map(|p| {
+                let c = reflected(b"Q");
+                path.quad_to(c, abs(p));
+                Some((b'Q', c))
+            }),
+            b'A' => (|| {
+                let radii = scanner.point()?;
+                let rotation = scanner.number()?;
+                Some((
+                    radii,
+                    rotation,
+                    scanner.flag()?,
+                    scanner.flag()?,
+                    scanner.point()?,
+                ))
+            })()
+            .map(|(radii, rotation, large, sweep, p)| {
+                path.arc_to(radii, rotation, large, sweep, abs(p));
+                None
+            }),
+            _ => None,
+        };
+        match drawn {
+            Some(control) => last_control = control,
+            None => break,
+        }
+    }
+    path.build()
+}
+
+// --- Rasterizing ---
+
+/// Premultiplied RGBA accumulated element by element
+struct Canvas {
+    size: u32,
+    pixels: Vec<[f32; 4]>,
+}
+
+impl Canvas {
+    fn new(size: u32) -> Self {
+        Self {
+            size,
+            pixels: vec![[0.0; 4]; (size * size) as usize],
+        }
+    }
+
+    fn fill(&mut self, subpaths: &[Subpath], view: ViewBox, fill: Fill, color: Rgb<u8>) {
+        let color = match fill.paint {
+            Paint::None => return,
+            Paint::Current => color,
+            Paint::Color(color) => color,
+        };
+        let mut raster = Rasterizer::new(self.size as usize, self.size as usize);
+        for subpath in subpaths {
+            let points: Vec<_> = subpath.points.iter().map(|p| view.apply(*p)).collect();
+            // Fills are always closed
+            for (a, b) in points.iter().zip(points.iter().cycle().skip(1)) {
+                raster.draw_line(point(a.0, a.1), point(b.0, b.1));
+            }
+        }
+
+        let rgb = color.0.map(|c| c as f32 / 255.0);
+        let size = self.size;
+        raster.for_each_pixel_2d(|x, y, coverage| {
+            let alpha = fill.rule.coverage(coverage);
+            if alpha <= 0.0 {
+                return;
+            }
+            let pixel = &mut self.pixels[(y * size + x) as usize];
+            for i in 0..3 {
+                pixel[i] = rgb[i] * alpha + pixel[i] * (1.0 - alpha);
+            }
+            pixel[3] = alpha + pixel[3] * (1.0 - alpha);
+        });
+    }
+
+    fn into_image(self) -> RgbaImage {
+        let mut image = RgbaImage::new(self.size, self.size);
+        for (pixel, [r, g, b, a]) in image.pixels_mut().zip(self.pixels) {
+            if a > 0.0 {
+                let channel = |c: f32| ((c / a).min(1.0) * 255.0).round() as u8;
+                *pixel = image::Rgba([
+                    channel(r),
+                    channel(g),
+                    channel(b),
+                    (a * 255.0).round() as u8,
+                ]);
+            }
+        }
+        image
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rasterize() {
+        assert_eq!(
+            parse_path("M10-5.5.5 0l1,1zh2"),
+            vec![
+                Subpath {
+                    points: vec![(10.0, -5.5), (0.5, 0.0), (1.5, 1.0)],
+                    closed: true,
+                },
+                Subpath {
+                    points: vec![(10.0, -5.5), (12.0, -5.5)],
+                    closed: false,
+                },
+            ]
+        );
+        let arc = parse_path("M0 0a5 5 0 104 0");
+        assert_eq!(arc[0].points.last(), Some(&(4.0, 0.0)));
+
+        let white = Rgb([255, 255, 255]);
+        // A Material-style filled icon
+        let material = r##"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 24 24">
+            <!-- comment --><path d="M0 0h24v24H0z" fill="none"/>
+            <g fill="#f00"><rect x="12" width="12" height="24"/></g></svg>"##;
+        let icon = rasterize(material, white, 90).unwrap();
+        assert_eq!(icon.get_pixel(70, 45).0, [255, 0, 0, 255]);
+        assert_eq!(icon.get_pixel(20, 45).0[3], 0);
+
+        // Unstyled shapes take the label color
+        let plain = r#"<svg viewBox="0 0 10 10"><circle cx="5" cy="5" r="4"/></svg>"#;
+        let icon = rasterize(plain, Rgb([0, 200, 100]), 10).unwrap();
+        assert_eq!(icon.get_pixel(5, 5).0, [0, 200, 100, 255]);
+
+        assert!(rasterize("<p>not an icon</p>", white, 90).is_err());
+    }
+
+    #[test]
+    fn test_unsupported() {
+        let white = Rgb([255, 255, 255]);
+        let error = |body: &str| {
+            let markup = format!(r#"<svg viewBox="0 0 24 24">{}</svg>"#, body);
+            rasterize(&markup, white, 24).unwrap_err().to_string()
+        };
+        // A Feather-style stroked icon
+        let stroked = r#"<line x1="12" y1="5" x2="12" y2="19"/>"#;
+        assert_eq!(error(stroked), "<line> is not supported");
+        let stroke = r#"<path d="M0 0h5" stroke="currentColor"/>"#;
+        assert_eq!(error(stroke), "stroke on <path> is not supported");
+        let transformed = r#"<g transform="scale(2)"><rect width="5" height="5"/></g>"#;
+        assert_eq!(error(transformed), "transform on <g> is not supported");
+        assert!(error(r#"<rect width="5" height="5" style="fill: red"/>"#).contains("style"));
+        assert!(error(r#"<rect width="5" height="5" fill="url(#g)"/>"#).contains("fill"));
+        assert!(error(r#"<rect width="5" height="5" fill-rule="odd"/>"#).contains("fill-rule"));
+        assert!(error(r#"<defs><linearGradient id="g"/></defs>"#).contains("<defs>"));
+        assert!(error(r#"<text>A</text>"#).contains("<text>"));
+        assert!(error(r#"<svg viewBox="0 0 5 5"/>"#).contains("nested"));
+
+        // Attributes that don't change the drawing are fine
+        let harmless = r#"<svg xmlns:xlink="http://www.w3.org/1999/xlink" id="icon"
+            aria-hidden="true" viewBox="0 0 24 24"><path class="a" d="M0 0h5v5z"/></svg>"#;
+        assert!(rasterize(harmless, white, 24).is_ok());
+    }
+
+    fn near((x, y): (f32, f32), (ex, ey): (f32, f32)) -> bool {
+        (x - ex).abs() < 1e-3 && (y - ey).abs() < 1e-3
+    }
+
+    /// Alpha of a pixel in `markup` drawn at `size` in white
+    fn alpha(markup: &str, size: u32, x: u32, y: u32) -> u8 {
+        rasterize(markup, Rgb([255, 255, 255]), size).unwrap().get_pixel(x, y).0[3]
+    }
+
+    #[test]
+    fn test_arcs() {
+        // Every point of a semicircle is on the circle; sweep picks the side
+        let below = &parse_path("M10 0A10 10 0 0 1 -10 0")[0].points;
+        assert!(below.iter().all(|(x, y)| ((x * x + y * y).sqrt() - 10.0).abs() < 1e-3));
+        assert!(below.iter().any(|p| near(*p, (0.0, 10.0))));
+        let above = &parse_path("M10 0A10 10 0 0 0 -10 0")[0].points;
+        assert!(above.iter().any(|p| near(*p, (0.0, -10.0))));
+
+        // The large-arc flag picks the long way round a 4-wide chord of a
+        // radius-5 circle (center 4.58 away from it), give or take flattening
+        let reach = |d| {
+            let points = &parse_path(d)[0].points;
+            points.iter().map(|(_, y)| y.abs()).fold(0.0, f32::max)
+        };
+        assert!((reach("M0 0A5 5 0 0 0 4 0") - 0.417).abs() < 0.05);
+        assert!((reach("M0 0A5 5 0 1 0 4 0") - 9.583).abs() < 0.05);
+
+        // Radii too small to reach the end are scaled up to a semicircle
+        let scaled = &parse_path("M0 0A1 1 0 0 1 10 0")[0].points;
+        assert!(scaled.iter().any(|p| near(*p, (5.0, -5.0))));
+        assert_eq!(scaled.last(), Some(&(10.0, 0.0)));
+
+        // Zero radii draw a straight line
+        assert_eq!(parse_path("M0 0A0 5 0 0 1 10 0")[0].points, [(0.0, 0.0), (10.0, 0.0)]);
+
+        // A circle is drawn from two arcs
+        let circle = r#"<svg viewBox="0 0 10 10"><circle cx="5" cy="5" r="4"/></svg>"#;
+        assert_eq!(alpha(circle, 10, 5, 5), 255);
+        assert_eq!(alpha(circle, 10, 0, 0), 0);
+    }
+
+    #[test]
+    fn test_fill_rules() {
+        // Two squares wound the same way, the small one inside the big one
+        let squares = |attrs| {
+            let path = r#"<path d="M0 0h10v10h-10z M3 3h4v4h-4z"/>"#;
+            format!(r#"<svg viewBox="0 0 10 10"><g {}>{}</g></svg>"#, attrs, path)
+        };
+        assert_eq!(alpha(&squares(""), 10, 5, 5), 255);
+        assert_eq!(alpha(&squares(r#"fill-rule="nonzero""#), 10, 5, 5), 255);
+        // The rule is inherited from the group
+        assert_eq!(alpha(&squares(r#"fill-rule="evenodd""#), 10, 5, 5), 0);
+        assert_eq!(alpha(&squares(r#"fill-rule="evenodd""#), 10, 1, 1), 255);
+
+        // Wound the other way, the inner square is a hole under both rules
+        let hole = r#"<svg viewBox="0 0 10 10"><path d="M0 0h10v10h-10z M3 3v4h4v-4z"/></svg>"#;
+        assert_eq!(alpha(hole, 10, 5, 5), 0);
+        assert_eq!(alpha(hole, 10, 1, 1), 255);
+
+        // Partial coverage at an edge survives the even-odd mapping
+        assert_eq!(FillRule::EvenOdd.coverage(0.25), 0.25);
+        assert_eq!(FillRule::EvenOdd.coverage(1.75), 0.25);
+        assert_eq!(FillRule::NonZero.coverage(1.75), 1.0);
+    }
+
+    #[test]
+    fn test_view_box() {
+        // A 24-unit icon drawn at 48 pixels scales by 2
+        let quarter = r#"<svg viewBox="0 0 24 24"><rect width="12" height="12"/></svg>"#;
+        assert_eq!(alpha(quarter, 48, 10, 10), 255);
+        assert_eq!(alpha(quarter, 48, 30, 30), 0);
+
+        // A wide viewBox fits the width and is centered vertically
+        let wide = r#"<svg viewBox="0 0 20 10"><rect width="20" height="10"/></svg>"#;
+        assert_eq!(alpha(wide, 40, 20, 15), 255);
+        assert_eq!(alpha(wide, 40, 20, 25), 255);
+        assert_eq!(alpha(wide, 40, 20, 5), 0);
+        assert_eq!(alpha(wide, 40, 20, 35), 0);
+
+        // The viewBox origin is moved to the corner
+        let offset =
+            r#"<svg viewBox="10 10 10 10"><rect x="10" y="10" width="5" height="5"/></svg>"#;
+        assert_eq!(alpha(offset, 10, 2, 2), 255);
+        assert_eq!(alpha(offset, 10, 7, 7), 0);
+
+        // Without a usable viewBox, width and height set the scale
+        let roots = [
+            r#"<svg width="20" height="20">"#,
+            r#"<svg viewBox="0 0 0 0" width="20" height="20">"#,
+        ];
+        for svg in roots {
+            let sized = format!(r#"{}<rect width="10" height="10"/></svg>"#, svg);
+            assert_eq!(alpha(&sized, 40, 15, 15), 255);
+            assert_eq!(alpha(&sized, 40, 25, 25), 0);
+        }
+    }
+
+    #[test]
+    fn test_malformed() {
+        let white = Rgb([255, 255, 255]);
+        assert!(rasterize("", white, 10).is_err());
+        assert!(rasterize("not markup at all", white, 10).is_err());
+        assert!(rasterize("<rect width=\"10\" height=\"10\"/>", white, 10).is_err());
+
+        // Path data is drawn up to its first error
+        let broken = &parse_path("M0 0L10 0L10 10Lfoo 20Z");
+        assert_eq!(broken[0].points, [(0.0, 0.0), (10.0, 0.0), (10.0, 10.0)]);
+        assert!(parse_path("10 10").is_empty());
+        assert!(parse_path("M5").is_empty());
+
+        // Nothing here should panic; what can be drawn is
+        let documents = [
+            r#"<svg viewBox="0 0 10 10"><rect width="10" height="10""#,
+            r#"<svg viewBox="0 0 10 10"><rect width='10' height="10></svg>"#,
+            r#"<svg viewBox="a b c d"><circle r="-5"/><rect width="-1" height="4"/></svg>"#,
+            r#"<svg viewBox="0 0 10 10"><path d="M0 0L1e30 1e30L-1e30 5z"/></svg>"#,
+            r##"<svg viewBox="0 0 10 10"><path d="M0 0L1e39 0L0 10z" fill="#fff"/></svg>"##,
+            r#"<svg><g><g><path d="M0 0h10v10z"/></svg>"#,
+            r#"<svg><!-- unterminated comment <rect width="10" height="10"/></svg>"#,
+        ];
+        for markup in documents {
+            assert!(rasterize(markup, white, 10).is_ok(), "{}", markup);
+        }
+        let unclosed = r#"<svg viewBox="0 0 10 10"><rect width="10" height="10"/>"#;
+        assert_eq!(alpha(unclosed, 10, 5, 5), 255);
+    }
+
+    #[test]
+    fn test_render_cache_follows_file() {
+        let dir = std::env::temp_dir().join(format!("claude-deck-svg-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("icon.svg");
+        let source = path.to_str().unwrap();
+        let icon = |fill: &str| {
+            let rect = format!(r#"<rect width="10" height="10" fill="{}"/>"#, fill);
+            format!(r#"<svg viewBox="0 0 10 10">{}</svg>"#, rect)
+        };
+        let white = Rgb([255, 255, 255]);
+
+        // A missing file isn't cached for good
+        assert!(render(source, white).is_none());
+        std::fs::write(&path, icon("#f00")).unwrap();
+        let red = render(source, white).unwrap();
+        assert_eq!(red.get_pixel(45, 45).0, [255, 0, 0, 255]);
+
+        // Edited, it's drawn again; unchanged, it comes from the cache
+        std::fs::write(&path, icon("#00f")).unwrap();
+        let file = std::fs::File::options().write(true).open(&path).unwrap();
+        file.set_modified(SystemTime::UNIX_EPOCH).unwrap();
+        let blue = render(source, white).unwrap();
+        assert_eq!(blue.get_pixel(45, 45).0, [0, 0, 255, 255]);
+        std::fs::write(&path, icon("#f00")).unwrap();
+        file.set_modified(SystemTime::UNIX_EPOCH).unwrap();
+        assert_eq!(render(source, white).unwrap(), blue);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,122 @@
+//! Display themes: the chrome around button colors and strip values
+//!
+//! A theme sets backgrounds, separators, borders and text colors. Button
+//! colors and status colors (waiting orange, recording red) are the same in
+//! every theme. Selected by `[appearance] theme`.
+
+use image::Rgb;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use tracing::warn;
+
+#[derive(Debug, Clone)]
+pub struct Theme {
+    pub name: &'static str,
+    pub description: &'static str,
+    /// Strip background gradient (top, bottom)
+    pub background: (Rgb<u8>, Rgb<u8>),
+    /// Strip separators and bevels (line, shadow)
+    pub separator: (Rgb<u8>, Rgb<u8>),
+    /// Header bars and meter tracks behind strip values
+    pub panel: Rgb<u8>,
+    /// Outer edge of every button
+    pub border: Rgb<u8>,
+    /// Strip values
+    pub text: Rgb<u8>,
+    /// Strip labels, hints and empty values
+    pub label: Rgb<u8>,
+    /// Button labels and their drop shadow
+    pub button_text: (Rgb<u8>, Rgb<u8>),
+    /// Resting buttons mix their color into this (top, bottom color amount)
+    pub button_base: (Rgb<u8>, f32, f32),
+}
+
+pub const DARK: Theme = Theme {
+    name: "dark",
+    description: "Dark backgrounds with muted labels (default)",
+    background: (Rgb([18, 20, 28]), Rgb([12, 14, 20])),
+    separator: (Rgb([45, 50, 65]), Rgb([25, 28, 38])),
+    panel: Rgb([30, 32, 42]),
+    border: Rgb([20, 20, 30]),
+    text: Rgb([255, 255, 255]),
+    label: Rgb([80, 85, 95]),
+    button_text: (Rgb([220, 220, 230]), Rgb([0, 0, 0])),
+    button_base: (Rgb([0, 0, 0]), 0.4, 0.6),
+};
+
+pub const LIGHT: Theme = Theme {
+    name: "light",
+    description: "Pale backgrounds and dark text, for bright rooms",
+    background: (Rgb([240, 242, 246]), Rgb([222, 225, 232])),
+    separator: (Rgb([190, 195, 205]), Rgb([250, 251, 253])),
+    panel: Rgb([210, 214, 222]),
+    border: Rgb([170, 175, 185]),
+    text: Rgb([25, 28, 36]),
+    label: Rgb([105, 110, 122]),
+    button_text: (Rgb([20, 22, 30]), Rgb([255, 255, 255])),
+    button_base: (Rgb([255, 255, 255]), 0.45, 0.6),
+};
+
+pub const HIGH_CONTRAST: Theme = Theme {
+    name: "high-contrast",
+    description: "Black backgrounds, white text and borders",
+    background: (Rgb([0, 0, 0]), Rgb([0, 0, 0])),
+    separator: (Rgb([255, 255, 255]), Rgb([0, 0, 0])),
+    panel: Rgb([40, 40, 40]),
+    border: Rgb([255, 255, 255]),
+    text: Rgb([255, 255, 255]),
+    label: Rgb([200, 200, 200]),
+    button_text: (Rgb([255, 255, 255]), Rgb([0, 0, 0])),
+    button_base: (Rgb([0, 0, 0]), 0.25, 0.35),
+};
+
+/// Every theme, in the order the API lists them
+pub const THEMES: [Theme; 3] = [DARK, LIGHT, HIGH_CONTRAST];
+
+/// Index into THEMES of the theme in use
+static CURRENT: AtomicUsize = AtomicUsize::new(0);
+
+/// The theme in use
+pub fn current() -> &'static Theme {
+    &THEMES[CURRENT.load(Ordering::Relaxed)]
+}
+
+pub fn find(name: &str) -> Option<&'static Theme> {
+    THEMES.iter().find(|theme| theme.name.eq_ignore_ascii_case(name))
+}
+
+/// Switch themes by name, keeping dark for unknown names
+pub fn select(name: &str) {
+    let index = THEMES.iter().position(|theme| theme.name.eq_ignore_ascii_case(name));
+    if index.is_none() {
+        warn!("Unknown theme '{}' - using dark", name);
+    }
+    CURRENT.store(index.unwrap_or(0), Ordering::Relaxed);
+}
+
+/// Resting button background gradient for a button color
+pub fn button_background(theme: &Theme, color: Rgb<u8>) -> (Rgb<u8>, Rgb<u8>) {
+    let (base, top, bottom) = theme.button_base;
+    (mix(base, color, top), mix(base, color, bottom))
+}
+
+/// `amount` of `color` over `base`
+fn mix(base: Rgb<u8>, color: Rgb<u8>, amount: f32) -> Rgb<u8> {
+    let channel = |i: usize| (base[i] as f32 * (1.0 - amount) + color[i] as f32 * amount) as u8;
+    Rgb([channel(0), channel(1), channel(2)])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_and_button_background() {
+        assert_eq!(find("High-Contrast").map(|t| t.name), Some("high-contrast"));
+        assert!(find("solarized").is_none());
+
+        let blue = Rgb([60, 120, 200]);
+        assert_eq!(button_background(&DARK, blue), (Rgb([24, 48, 80]), Rgb([36, 72, 120])));
+        let (top, _) = button_background(&LIGHT, blue);
+        assert!(top[0] > blue[0] && top[2] > blue[2]);
+    }
+}
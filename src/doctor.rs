@@ -0,0 +1,341 @@
+//! Setup checks for `--doctor` and `/api/doctor`
+//!
+//! Each check reports whether part of the setup works and, when it doesn't,
+//! what to do about it: the macOS permissions keystroke injection and the
+//! device need, the HID device itself, the Claude Code hooks, how recently a
+//! hook wrote the status file, and the web UI port.
+
+use serde::Serialize;
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+
+use crate::config::Config;
+use crate::device::DeviceManager;
+use crate::hooks::{self, HOOK_EVENTS};
+
+/// A status file older than this suggests hooks stopped running
+const STALE_STATUS: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// How a check turned out
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Outcome {
+    Pass,
+    /// Works, but something may be off
+    Warn,
+    Fail,
+    /// Not applicable on this platform
+    Skip,
+}
+
+impl Outcome {
+    pub fn symbol(self) -> &'static str {
+        match self {
+            Outcome::Pass => "✓",
+            Outcome::Warn => "⚠",
+            Outcome::Fail => "✗",
+            Outcome::Skip => "-",
+        }
+    }
+}
+
+/// Result of one check
+#[derive(Debug, Clone, Serialize)]
+pub struct Check {
+    pub name: &'static str,
+    pub outcome: Outcome,
+    /// What was found
+    pub detail: String,
+    /// How to fix it, for warnings and failures
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fix: Option<String>,
+}
+
+impl Check {
+    fn pass(name: &'static str, detail: impl Into<String>) -> Self {
+        Self {
+            name,
+            outcome: Outcome::Pass,
+            detail: detail.into(),
+            fix: None,
+        }
+    }
+
+    fn skip(name: &'static str, detail: impl Into<String>) -> Self {
+        Self {
+            name,
+            outcome: Outcome::Skip,
+            detail: detail.into(),
+            fix: None,
+        }
+    }
+
+    fn problem(
+        name: &'static str,
+        outcome: Outcome,
+        detail: impl Into<String>,
+        fix: impl Into<String>,
+    ) -> Self {
+        Self {
+            name,
+            outcome,
+            detail: detail.into(),
+            fix: Some(fix.into()),
+        }
+    }
+}
+
+/// Run every check
+///
+/// `serving` says the web server asking is this process, so its port is
+/// known to be ours.
+pub async fn run(config: &Config, serving: bool) -> Vec<Check> {
+    let home = std::env::var("HOME").unwrap_or_default();
+    let claude_dir = Path::new(&home).join(".claude");
+    vec![
+        accessibility(),
+        input_monitoring(),
+        hid_device().await,
+        hook_install(&claude_dir),
+        status_file(&hooks::status_file_path()),
+        web_port(config.web.port, serving).await,
+    ]
+}
+
+#[cfg(target_os = "macos")]
+mod tcc {
+    #[link(name = "ApplicationServices", kind = "framework")]
+    extern "C" {
+        fn AXIsProcessTrusted() -> bool;
+    }
+
+    #[link(name = "IOKit", kind = "framework")]
+    extern "C" {
+        fn IOHIDCheckAccess(request_type: u32) -> u32;
+    }
+
+    pub fn accessibility_granted() -> bool {
+        // SAFETY: AXIsProcessTrusted takes no arguments and only reads TCC state
+        unsafe { AXIsProcessTrusted() }
+    }
+
+    /// kIOHIDAccessTypeGranted (0), Denied (1) or Unknown (2, never asked)
+    pub fn input_monitoring_access() -> u32 {
+        // SAFETY: IOHIDCheckAccess only reads TCC state for the request type
+        // (1 is kIOHIDRequestTypeListenEvent)
+        unsafe { IOHIDCheckAccess(1) }
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+mod tcc {
+    pub fn accessibility_granted() -> bool {
+        false
+    }
+
+    pub fn input_monitoring_access() -> u32 {
+        2
+    }
+}
+
+/// Keystroke injection (enigo) needs Accessibility access
+fn accessibility() -> Check {
+    const NAME: &str = "Accessibility permission";
+    if !cfg!(target_os = "macos") {
+        Check::skip(NAME, "only needed on macOS")
+    } else if tcc::accessibility_granted() {
+        Check::pass(NAME, "granted")
+    } else {
+        Check::problem(
+            NAME,
+            Outcome::Fail,
+            "not granted: buttons cannot send keystrokes",
+            "System Settings → Privacy & Security → Accessibility: enable the terminal or app \
+             that runs claude-deck (remove and re-add it after upgrading the binary)",
+        )
+    }
+}
+
+/// Reading the device's input reports can need Input Monitoring access
+fn input_monitoring() -> Check {
+    const NAME: &str = "Input Monitoring permission";
+    if !cfg!(target_os = "macos") {
+        return Check::skip(NAME, "only needed on macOS");
+    }
+    match tcc::input_monitoring_access() {
+        0 => Check::pass(NAME, "granted"),
+        1 => Check::problem(
+            NAME,
+            Outcome::Fail,
+            "denied: presses and knob turns may not arrive",
+            "System Settings → Privacy & Security → Input Monitoring: enable the terminal or \
+             app that runs claude-deck",
+        ),
+        _ => Check::problem(
+            NAME,
+            Outcome::Warn,
+            "not asked yet",
+            "Run claude-deck once and allow Input Monitoring when macOS asks",
+        ),
+    }
+}
+
+async fn hid_device() -> Check {
+    const NAME: &str = "HID device";
+    match DeviceManager::find_devices().await {
+        Ok(devices) => {
+            let names: Vec<String> = devices
+                .iter()
+                .map(|d| format!("{} ({})", d.name, d.serial_number))
+                .collect();
+            Check::pass(NAME, names.join(", "))
+        }
+        Err(e) => {
+            let fix = if cfg!(target_os = "linux") {
+                "Plug the deck in (not through an unpowered hub) and give your user access to \
+                 its /dev/hidraw node with a udev rule"
+            } else {
+                "Plug the deck in directly (not through an unpowered hub); `claude-deck \
+                 --status` lists the supported models"
+            };
+            Check::problem(NAME, Outcome::Fail, e.to_string(), fix)
+        }
+    }
+}
+
+fn hook_install(claude_dir: &Path) -> Check {
+    const NAME: &str = "Claude Code hooks";
+    const FIX: &str = "Run `claude-deck --install-hooks`, then restart Claude Code";
+    let script = claude_dir.join("hooks/claude-deck-hook.sh");
+    if !script.exists() {
+        let detail = format!("no hook script at {}", script.display());
+        return Check::problem(NAME, Outcome::Fail, detail, FIX);
+    }
+    let settings = std::fs::read_to_string(claude_dir.join("settings.json")).unwrap_or_default();
+    match missing_hooks(&settings).as_slice() {
+        [] => Check::pass(NAME, format!("installed for {}", HOOK_EVENTS.join(", "))),
+        missing => {
+            let detail = format!("not registered for {}", missing.join(", "));
+            Check::problem(NAME, Outcome::Fail, detail, FIX)
+        }
+    }
+}
+
+/// Hook events whose settings.json entries don't run the claude-deck hook
+fn missing_hooks(settings: &str) -> Vec<&'static str> {
+    let settings: serde_json::Value = serde_json::from_str(settings).unwrap_or_default();
+    HOOK_EVENTS
+        .iter()
+        .copied()
+        .filter(|event| {
+            let matchers = settings
+                .pointer(&format!("/hooks/{}", event))
+                .and_then(|v| v.as_array());
+            !matchers.into_iter().flatten().any(hooks::runs_claude_deck)
+        })
+        .collect()
+}
+
+fn status_file(path: &Path) -> Check {
+    const NAME: &str = "Status file";
+    let modified = std::fs::metadata(path).and_then(|m| m.modified());
+    let Ok(modified) = modified else {
+        return Check::problem(
+            NAME,
+            Outcome::Warn,
+            format!("{} not written yet", path.display()),
+            "Start a Claude Code session after installing the hooks",
+        );
+    };
+    let age = SystemTime::now()
+        .duration_since(modified)
+        .unwrap_or_default();
+    status_age(age)
+}
+
+fn status_age(age: Duration) -> Check {
+    const NAME: &str = "Status file";
+    let detail = format!("last written {} ago", format_age(age));
+    if age > STALE_STATUS {
+        Check::problem(
+            NAME,
+            Outcome::Warn,
+            detail,
+            "If Claude Code ran since then, its hooks aren't firing: check ~/.claude/settings.json \
+             or run `claude-deck --install-hooks` again",
+        )
+    } else {
+        Check::pass(NAME, detail)
+    }
+}
+
+fn format_age(age: Duration) -> String {
+    match age.as_secs() {
+        secs if secs < 60 => format!("{}s", secs),
+        secs if secs < 3600 => format!("{}m", secs / 60),
+        secs if secs < 86400 => format!("{}h", secs / 3600),
+        secs => format!("{}d", secs / 86400),
+    }
+}
+
+async fn web_port(port: u16, serving: bool) -> Check {
+    const NAME: &str = "Web UI port";
+    if serving {
+        return Check::pass(NAME, format!("serving on 127.0.0.1:{}", port));
+    }
+    if tokio::net::TcpListener::bind(("127.0.0.1", port))
+        .await
+        .is_ok()
+    {
+        return Check::pass(NAME, format!("127.0.0.1:{} is free", port));
+    }
+    // Taken: fine if it's a running claude-deck
+    let url = format!("http://127.0.0.1:{}/api/status", port);
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(2))
+        .build();
+    let ours = match client {
+        Ok(client) => client
+            .get(&url)
+            .send()
+            .await
+            .is_ok_and(|r| r.status().is_success()),
+        Err(_) => false,
+    };
+    if ours {
+        Check::pass(
+            NAME,
+            format!("claude-deck is already serving on 127.0.0.1:{}", port),
+        )
+    } else {
+        Check::problem(
+            NAME,
+            Outcome::Fail,
+            format!("127.0.0.1:{} is used by another program", port),
+            "Stop that program or set another `port` under [web] in config.toml",
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_checks() {
+        let installed = r#"{"hooks":{"Stop":[{"hooks":[{"type":"command",
+            "command":"/Users/me/.claude/hooks/claude-deck-hook.sh"}]}],
+            "PreToolUse":[{"hooks":[{"type":"command","command":"other.sh"}]}]}}"#;
+        let missing = missing_hooks(installed);
+        assert!(!missing.contains(&"Stop"));
+        assert!(missing.contains(&"PreToolUse"));
+        assert_eq!(missing_hooks("not json").len(), HOOK_EVENTS.len());
+
+        assert_eq!(status_age(Duration::from_secs(90)).outcome, Outcome::Pass);
+        let stale = status_age(Duration::from_secs(3 * 86400));
+        assert_eq!(
+            (stale.outcome, stale.detail.as_str()),
+            (Outcome::Warn, "last written 3d ago")
+        );
+    }
+}
@@ -0,0 +1,125 @@
+//! Per-tool formatting of the DETAIL quadrant from `PreToolUse` payloads
+//!
+//! The hook script passes the raw `tool_input` through the status file; this
+//! turns it into a short line such as `Bash: npm test` or
+//! `Edit: src/lib.rs (+12/-3)`.
+
+use serde_json::Value;
+
+/// Format a one-line detail for a tool call, or None if the tool isn't known
+pub fn format_tool_detail(tool: &str, input: &Value, cwd: Option<&str>) -> Option<String> {
+    let str_field = |name: &str| input.get(name).and_then(Value::as_str);
+    let path = || str_field("file_path").map(|p| relative_path(p, cwd));
+
+    let detail = match tool {
+        "Bash" => first_line(str_field("command")?).to_string(),
+        "Read" => {
+            let path = path()?;
+            match (
+                input.get("offset").and_then(Value::as_u64),
+                input.get("limit").and_then(Value::as_u64),
+            ) {
+                (Some(offset), Some(limit)) => {
+                    format!("{}:{}-{}", path, offset, offset + limit.saturating_sub(1))
+                }
+                (Some(offset), None) => format!("{}:{}", path, offset),
+                _ => path,
+            }
+        }
+        "Write" => {
+            let lines = str_field("content").map(line_count).unwrap_or(0);
+            format!("{} ({} lines)", path()?, lines)
+        }
+        "Edit" => {
+            let (added, removed) = edit_counts(input);
+            format!("{} (+{}/-{})", path()?, added, removed)
+        }
+        "MultiEdit" => {
+            let (added, removed) = input
+                .get("edits")
+                .and_then(Value::as_array)
+                .map(|edits| {
+                    edits.iter().map(edit_counts).fold((0, 0), |(a, r), (ea, er)| (a + ea, r + er))
+                })
+                .unwrap_or((0, 0));
+            format!("{} (+{}/-{})", path()?, added, removed)
+        }
+        "Grep" => match str_field("path") {
+            Some(dir) => format!("{} in {}", str_field("pattern")?, relative_path(dir, cwd)),
+            None => str_field("pattern")?.to_string(),
+        },
+        "Glob" => str_field("pattern")?.to_string(),
+        "Task" => str_field("description")?.to_string(),
+        "WebFetch" => {
+            let url = str_field("url")?;
+            url.split("://").nth(1).unwrap_or(url).split('/').next()?.to_string()
+        }
+        "WebSearch" => str_field("query")?.to_string(),
+        "TodoWrite" => {
+            let todos = input.get("todos").and_then(Value::as_array)?;
+            format!("{} items", todos.len())
+        }
+        _ => return None,
+    };
+
+    if detail.trim().is_empty() {
+        return None;
+    }
+    Some(format!("{}: {}", tool, detail.trim()))
+}
+
+/// Lines added and removed by an Edit (or a MultiEdit entry)
+fn edit_counts(edit: &Value) -> (usize, usize) {
+    let count = |name: &str| edit.get(name).and_then(Value::as_str).map(line_count).unwrap_or(0);
+    (count("new_string"), count("old_string"))
+}
+
+fn line_count(text: &str) -> usize {
+    text.lines().count()
+}
+
+fn first_line(text: &str) -> &str {
+    text.lines().find(|l| !l.trim().is_empty()).unwrap_or("")
+}
+
+/// Strip the session's working directory from a path
+fn relative_path(path: &str, cwd: Option<&str>) -> String {
+    cwd.and_then(|cwd| path.strip_prefix(cwd.trim_end_matches('/')))
+        .and_then(|rest| rest.strip_prefix('/'))
+        .filter(|rest| !rest.is_empty())
+        .unwrap_or(path)
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_format_known_tools() {
+        let cwd = Some("/Users/me/proj");
+        let bash = json!({"command": "npm test\necho done"});
+        assert_eq!(format_tool_detail("Bash", &bash, cwd).unwrap(), "Bash: npm test");
+
+        let edit = json!({
+            "file_path": "/Users/me/proj/src/lib.rs",
+            "old_string": "a\nb\nc",
+            "new_string": "a\nb\nc\nd\ne"
+        });
+        assert_eq!(format_tool_detail("Edit", &edit, cwd).unwrap(), "Edit: src/lib.rs (+5/-3)");
+
+        let read = json!({"file_path": "/etc/hosts", "offset": 10, "limit": 20});
+        assert_eq!(format_tool_detail("Read", &read, cwd).unwrap(), "Read: /etc/hosts:10-29");
+
+        let fetch = json!({"url": "https://docs.rs/serde/latest"});
+        assert_eq!(format_tool_detail("WebFetch", &fetch, cwd).unwrap(), "WebFetch: docs.rs");
+    }
+
+    #[test]
+    fn test_unknown_or_incomplete_input() {
+        assert!(format_tool_detail("mcp__foo", &json!({}), None).is_none());
+        assert!(format_tool_detail("Bash", &json!({}), None).is_none());
+        assert!(format_tool_detail("Bash", &json!({"command": "  "}), None).is_none());
+    }
+}
@@ -1,3 +1,29 @@
+mod detail;
+mod socket;
 mod status;
 
-pub use status::{read_status, status_file_path, ClaudeStatus};
+pub use socket::{socket_path, spawn_listener};
+pub use status::{state_dir, status_file_path, ClaudeStatus, StatusReader};
+
+/// Claude Code hook events `--install-hooks` registers the hook script for
+pub const HOOK_EVENTS: [&str; 5] = [
+    "UserPromptSubmit",
+    "PreToolUse",
+    "PostToolUse",
+    "Notification",
+    "Stop",
+];
+
+/// Whether a settings.json hook matcher entry runs the claude-deck hook script
+pub fn runs_claude_deck(matcher: &serde_json::Value) -> bool {
+    matcher
+        .get("hooks")
+        .and_then(|h| h.as_array())
+        .is_some_and(|hooks| {
+            hooks.iter().any(|hook| {
+                hook.get("command")
+                    .and_then(|c| c.as_str())
+                    .is_some_and(|s| s.contains("claude-deck"))
+            })
+        })
+}
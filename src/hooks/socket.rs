@@ -0,0 +1,114 @@
+//! Push-style hook transport over a Unix domain socket
+//!
+//! The hook script sends each status update as one line of JSON to
+//! `~/.claude-deck/hook.sock`, so updates arrive immediately instead of
+//! waiting for the next status file poll. The status file is still written
+//! and used as a fallback when the socket isn't available.
+
+use anyhow::{Context, Result};
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use tokio::io::{AsyncBufReadExt, BufReader};
+use tokio::net::{UnixListener, UnixStream};
+use tokio::sync::mpsc;
+use tracing::{debug, info, warn};
+
+use super::status::ClaudeStatus;
+
+/// Hook socket location in the state directory (next to the status file)
+pub fn socket_path(dir: &Path) -> PathBuf {
+    dir.join("hook.sock")
+}
+
+/// Bind the hook socket in `dir` and forward parsed status updates to the returned channel
+pub fn spawn_listener(dir: &Path) -> Result<mpsc::Receiver<ClaudeStatus>> {
+    let path = socket_path(dir);
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create {:?}", parent))?;
+    }
+
+    // A previous run may have left the socket file behind
+    if path.exists() {
+        std::fs::remove_file(&path).with_context(|| format!("Failed to remove stale {:?}", path))?;
+    }
+
+    let listener =
+        UnixListener::bind(&path).with_context(|| format!("Failed to bind hook socket {:?}", path))?;
+
+    // In daemon mode we run as root but the hooks run as the console user
+    let mode = if crate::system::is_root() { 0o666 } else { 0o600 };
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(mode))
+        .with_context(|| format!("Failed to set permissions on {:?}", path))?;
+
+    info!("Listening for hook updates on {:?}", path);
+
+    let (tx, rx) = mpsc::channel(64);
+    tokio::spawn(async move {
+        loop {
+            match listener.accept().await {
+                Ok((stream, _)) => {
+                    tokio::spawn(handle_connection(stream, tx.clone()));
+                }
+                Err(e) => {
+                    warn!("Hook socket accept failed: {}", e);
+                    tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+                }
+            }
+        }
+    });
+
+    Ok(rx)
+}
+
+/// Read newline-delimited status JSON from one hook invocation
+async fn handle_connection(stream: UnixStream, tx: mpsc::Sender<ClaudeStatus>) {
+    let mut lines = BufReader::new(stream).lines();
+    loop {
+        match lines.next_line().await {
+            Ok(Some(line)) => {
+                if line.trim().is_empty() {
+                    continue;
+                }
+                match serde_json::from_str::<ClaudeStatus>(&line) {
+                    Ok(status) => {
+                        if tx.send(status).await.is_err() {
+                            return;
+                        }
+                    }
+                    Err(e) => warn!("Invalid hook message: {}", e),
+                }
+            }
+            Ok(None) => return,
+            Err(e) => {
+                debug!("Hook connection error: {}", e);
+                return;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::AsyncWriteExt;
+
+    #[tokio::test]
+    async fn test_handle_connection_parses_lines() {
+        let (client, server) = UnixStream::pair().unwrap();
+        let (tx, mut rx) = mpsc::channel(4);
+        let handle = tokio::spawn(handle_connection(server, tx));
+
+        let mut client = client;
+        client
+            .write_all(b"{\"task\":\"Bash\",\"timestamp\":1}\nnot json\n\n{\"task\":\"READY\"}\n")
+            .await
+            .unwrap();
+        drop(client);
+        handle.await.unwrap();
+
+        assert_eq!(rx.recv().await.unwrap().task, "Bash");
+        assert_eq!(rx.recv().await.unwrap().task, "READY");
+        assert!(rx.recv().await.is_none());
+    }
+}
@@ -1,19 +1,38 @@
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::time::{Duration, SystemTime};
 use tokio::fs;
 use tracing::{debug, warn};
 
+use super::detail::format_tool_detail;
+
+/// Directory the hooks and the app keep their runtime files in
+///
+/// When running as root (system daemon mode), hooks write into the console
+/// user's home, so follow whoever is currently logged in at the console.
+pub fn state_dir() -> PathBuf {
+    if crate::system::is_root() {
+        if let Some(home) = crate::system::console_user_home() {
+            return home.join(".claude-deck");
+        }
+    }
+    let home = std::env::var("HOME").unwrap_or_else(|_| "/tmp".to_string());
+    PathBuf::from(home).join(".claude-deck")
+}
+
 /// Status file location
 pub fn status_file_path() -> PathBuf {
-    let home = std::env::var("HOME").unwrap_or_else(|_| "/tmp".to_string());
-    PathBuf::from(home).join(".claude-deck/state.json")
+    state_dir().join("state.json")
 }
 
 /// Status information from Claude Code hooks
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct ClaudeStatus {
+    /// Hook event that sent the update (e.g. "PreToolUse"), from newer hook scripts
+    #[serde(default)]
+    pub event: Option<String>,
+
     /// Current action/task being performed
     #[serde(default)]
     pub task: String,
@@ -22,6 +41,22 @@ pub struct ClaudeStatus {
     #[serde(default)]
     pub tool_detail: Option<String>,
 
+    /// Raw `tool_input` from the hook payload (tool events only)
+    #[serde(default)]
+    pub tool_input: Option<serde_json::Value>,
+
+    /// Working directory of the Claude session (used to shorten paths)
+    #[serde(default)]
+    pub cwd: Option<String>,
+
+    /// Claude Code session the update came from
+    #[serde(default)]
+    pub session_id: Option<String>,
+
+    /// Terminal device the session runs in (e.g. "/dev/ttys003")
+    #[serde(default)]
+    pub tty: Option<String>,
+
     /// Whether Claude is waiting for user input/permission
     #[serde(default)]
     pub waiting_for_input: bool,
@@ -42,12 +77,36 @@ pub struct ClaudeStatus {
     #[serde(default)]
     pub error: Option<String>,
 
+    /// Seconds until a rate-limited request can be retried, when known
+    #[serde(default)]
+    pub retry_after_secs: Option<u64>,
+
+    /// Tokens used this session (input + output, excluding cache reads)
+    #[serde(default)]
+    pub tokens: Option<u64>,
+
+    /// Session cost in USD (when the hook payload reports it)
+    #[serde(default)]
+    pub cost_usd: Option<f64>,
+
+    /// Context window usage (0-100)
+    #[serde(default)]
+    pub context_pct: Option<u8>,
+
     /// Timestamp of last update (Unix epoch seconds)
     #[serde(default)]
     pub timestamp: u64,
 }
 
 impl ClaudeStatus {
+    /// Detail line for the strip, formatted from the raw tool input when possible
+    pub fn detail(&self) -> Option<String> {
+        self.tool_input
+            .as_ref()
+            .and_then(|input| format_tool_detail(&self.task, input, self.cwd.as_deref()))
+            .or_else(|| self.tool_detail.clone())
+    }
+
     /// Check if status is stale (older than threshold)
     pub fn is_stale(&self, max_age: Duration) -> bool {
         let now = SystemTime::now()
@@ -59,34 +118,64 @@ impl ClaudeStatus {
     }
 }
 
-/// Read status from the status file
-pub async fn read_status() -> Result<Option<ClaudeStatus>> {
-    let path = status_file_path();
+/// Status older than this is ignored (the session has probably ended)
+const MAX_STATUS_AGE: Duration = Duration::from_secs(30);
+
+/// Files modified this recently are always re-read, since a same-size
+/// rewrite within a coarse mtime tick would otherwise go unnoticed
+const MTIME_GRANULARITY: Duration = Duration::from_secs(2);
+
+/// Polls the status file, parsing it only when its mtime or size changes
+#[derive(Debug, Default)]
+pub struct StatusReader {
+    /// Path, mtime and size of the file when last parsed
+    seen: Option<(PathBuf, SystemTime, u64)>,
+    /// What it parsed to
+    status: Option<ClaudeStatus>,
+}
 
-    if !path.exists() {
-        return Ok(None);
+impl StatusReader {
+    /// Read status from the status file in `dir`
+    pub async fn read(&mut self, dir: &Path) -> Result<Option<ClaudeStatus>> {
+        self.read_from(dir.join("state.json")).await
     }
 
-    match fs::read_to_string(&path).await {
-        Ok(content) => {
-            match serde_json::from_str::<ClaudeStatus>(&content) {
-                Ok(status) => {
-                    // Check if status is too old (more than 30 seconds)
-                    if status.is_stale(Duration::from_secs(30)) {
-                        debug!("Status file is stale, ignoring");
-                        return Ok(None);
-                    }
-                    Ok(Some(status))
-                }
-                Err(e) => {
-                    warn!("Failed to parse status file: {}", e);
-                    Ok(None)
-                }
+    async fn read_from(&mut self, path: PathBuf) -> Result<Option<ClaudeStatus>> {
+        let Ok(metadata) = fs::metadata(&path).await else {
+            self.seen = None;
+            self.status = None;
+            return Ok(None);
+        };
+        let modified = metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+        let settled = modified.elapsed().is_ok_and(|age| age >= MTIME_GRANULARITY);
+        let key = (path, modified, metadata.len());
+        if !settled || self.seen.as_ref() != Some(&key) {
+            self.status = parse_status_file(&key.0).await;
+            self.seen = Some(key);
+        }
+
+        match &self.status {
+            Some(status) if status.is_stale(MAX_STATUS_AGE) => {
+                debug!("Status file is stale, ignoring");
+                Ok(None)
             }
+            status => Ok(status.clone()),
         }
+    }
+}
+
+async fn parse_status_file(path: &Path) -> Option<ClaudeStatus> {
+    match fs::read_to_string(path).await {
+        Ok(content) => match serde_json::from_str::<ClaudeStatus>(&content) {
+            Ok(status) => Some(status),
+            Err(e) => {
+                warn!("Failed to parse status file: {}", e);
+                None
+            }
+        },
         Err(e) => {
             warn!("Failed to read status file: {}", e);
-            Ok(None)
+            None
         }
     }
 }
@@ -107,13 +196,22 @@ mod tests {
     #[test]
     fn test_status_serialization() {
         let status = ClaudeStatus {
+            event: None,
             task: "Writing code".to_string(),
             tool_detail: None,
+            tool_input: None,
+            cwd: None,
+            session_id: None,
+            tty: None,
             waiting_for_input: true,
             input_type: Some("permission".to_string()),
             model: Some("opus".to_string()),
             processing: false,
             error: None,
+            retry_after_secs: None,
+            tokens: Some(45200),
+            cost_usd: Some(1.25),
+            context_pct: Some(42),
             timestamp: 1234567890,
         };
 
@@ -122,5 +220,59 @@ mod tests {
 
         assert_eq!(parsed.task, "Writing code");
         assert_eq!(parsed.model, Some("opus".to_string()));
+        assert_eq!(parsed.tokens, Some(45200));
+        assert_eq!(parsed.context_pct, Some(42));
+    }
+
+    #[test]
+    fn test_status_without_usage_fields() {
+        // Status files written by older hook scripts have no usage fields
+        let json = r#"{"task": "READY", "waiting_for_input": false, "timestamp": 1}"#;
+        let parsed: ClaudeStatus = serde_json::from_str(json).unwrap();
+
+        assert_eq!(parsed.tokens, None);
+        assert_eq!(parsed.cost_usd, None);
+        assert_eq!(parsed.context_pct, None);
+    }
+
+    #[test]
+    fn test_detail_prefers_tool_input() {
+        let json =
+            r#"{"task": "Bash", "tool_detail": "npm te", "tool_input": {"command": "npm test"}}"#;
+        let parsed: ClaudeStatus = serde_json::from_str(json).unwrap();
+        assert_eq!(parsed.detail().as_deref(), Some("Bash: npm test"));
+
+        let json = r#"{"task": "mcp__slack", "tool_detail": "general", "tool_input": {}}"#;
+        let parsed: ClaudeStatus = serde_json::from_str(json).unwrap();
+        assert_eq!(parsed.detail().as_deref(), Some("general"));
+    }
+
+    #[tokio::test]
+    async fn test_reader_skips_unchanged_file() {
+        let name = format!("claude-deck-state-{}.json", std::process::id());
+        let path = std::env::temp_dir().join(name);
+        let now = SystemTime::now();
+        let timestamp = now.duration_since(SystemTime::UNIX_EPOCH).unwrap().as_secs();
+        let write = |content: &str| {
+            std::fs::write(&path, content).unwrap();
+            // Old enough that a matching mtime and size means "unchanged"
+            let file = std::fs::File::options().write(true).open(&path).unwrap();
+            file.set_modified(now - Duration::from_secs(10)).unwrap();
+        };
+
+        let json = format!(r#"{{"task": "Read", "timestamp": {}}}"#, timestamp);
+        write(&json);
+        let mut reader = StatusReader::default();
+        assert_eq!(reader.read_from(path.clone()).await.unwrap().unwrap().task, "Read");
+
+        // Same size and mtime: served from the last parse, not re-read
+        write(&"x".repeat(json.len()));
+        assert_eq!(reader.read_from(path.clone()).await.unwrap().unwrap().task, "Read");
+
+        write(&json.replace("Read", "Edit2"));
+        assert_eq!(reader.read_from(path.clone()).await.unwrap().unwrap().task, "Edit2");
+
+        std::fs::remove_file(&path).unwrap();
+        assert!(reader.read_from(path.clone()).await.unwrap().is_none());
     }
 }
@@ -3,20 +3,93 @@ use std::collections::{HashMap, HashSet};
 use std::sync::{Arc, RwLock as StdRwLock};
 use std::time::{Duration, Instant};
 use tokio::process::Command;
-use tokio::sync::RwLock;
+use tokio::sync::{mpsc, RwLock};
 use tokio::time::sleep;
 use tracing::{debug, info, warn};
 
-use crate::device::InputEvent;
-use crate::profiles::{ButtonAction, ProfileManager};
-use crate::state::AppState;
+use crate::config::KeystrokeConfig;
+use crate::device::{InputEvent, SwipeDirection};
+use crate::integrations::git::{self, GitCommand};
+use crate::integrations::{home_assistant, obs};
+use crate::profiles::overlay::overlay_button;
+use crate::profiles::store::{ActionConfig, MacroStep};
+use crate::profiles::{ButtonAction, ButtonConfig, EncoderInput, ProfileManager, StripGesture};
+use crate::state::{approvals, Approval, AppState, Decision, Folder, SceneRequest, TextEntry};
+use crate::system::{self, MediaCommand};
+use crate::templating;
+use crate::AppCommand;
 
 use super::keystrokes::{Key, KeystrokeSender};
+use super::script::{self, ScriptContext};
 
 const LONG_PRESS_DURATION: Duration = Duration::from_secs(2);
 
+/// Hold time before a repeating button starts repeating
+const REPEAT_DELAY: Duration = Duration::from_millis(400);
+const DEFAULT_REPEAT_INTERVAL_MS: u64 = 100;
+
+/// Macro shell steps taking longer than this are killed
+const SHELL_STEP_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Most time between the two presses of a chord
+const CHORD_WINDOW: Duration = Duration::from_millis(150);
+
+/// Encoder actions run as pseudo-buttons past the real ones (10-13), so
+/// toggles and flashes don't collide with button state
+const ENCODER_BUTTON_BASE: u8 = 10;
+
+/// Pseudo-button for actions run without a button, past the encoders
+const NO_BUTTON: u8 = ENCODER_BUTTON_BASE + 4;
+
+/// Claude Code slash commands typed by built-in actions, and whether Enter
+/// follows (MEMORY leaves `/memory` on the line to edit or submit)
+const SLASH_COMMANDS: [(&str, &str, bool); 4] = [
+    ("CLEAR", "/clear", true),
+    ("COMPACT", "/compact", true),
+    ("COST", "/cost", true),
+    ("MEMORY", "/memory", false),
+];
+
+/// Play macro steps, sending keys and text to the main loop to type
+///
+/// Each step carries the timing of the action that started the macro, since
+/// the action has finished (and global timing is back) by the time it's typed.
+async fn play_macro(
+    steps: Vec<MacroStep>,
+    timing: KeystrokeConfig,
+    commands: mpsc::Sender<AppCommand>,
+) {
+    for step in steps {
+        match step {
+            MacroStep::Delay { ms } => sleep(Duration::from_millis(ms)).await,
+            MacroStep::Shell { command } => run_shell_step(&command).await,
+            step => {
+                if commands.send(AppCommand::MacroStep(step, timing)).await.is_err() {
+                    return;
+                }
+            }
+        }
+    }
+}
+
+/// Run a macro shell step, killing it after `SHELL_STEP_TIMEOUT`
+async fn run_shell_step(command: &str) {
+    debug!("Macro shell: {}", command);
+    let output = Command::new("sh").arg("-c").arg(command).kill_on_drop(true).output();
+    match tokio::time::timeout(SHELL_STEP_TIMEOUT, output).await {
+        Ok(Ok(output)) if !output.status.success() => {
+            warn!("Macro shell command exited with {}: {}", output.status, command);
+        }
+        Ok(Ok(_)) => {}
+        Ok(Err(e)) => warn!("Failed to run macro shell command: {}", e),
+        Err(_) => {
+            warn!("Macro shell command killed after {:?}: {}", SHELL_STEP_TIMEOUT, command)
+        }
+    }
+}
+
 /// Convert device button ID to logical button ID
-fn device_to_logical_button(device_id: u8) -> Option<u8> {
+pub fn device_to_logical_button(device_id: u8) -> Option<u8> {
     if device_id < 10 {
         Some(device_id)
     } else {
@@ -24,13 +97,107 @@ fn device_to_logical_button(device_id: u8) -> Option<u8> {
     }
 }
 
+/// Run an AppleScript in the background, logging failures
+fn run_applescript(script: String) {
+    tokio::spawn(async move {
+        match Command::new("osascript").arg("-e").arg(&script).output().await {
+            Ok(output) if !output.status.success() => {
+                let stderr = String::from_utf8_lossy(&output.stderr);
+                warn!("AppleScript exited with {}: {}", output.status, stderr.trim());
+            }
+            Ok(_) => {}
+            Err(e) => warn!("Failed to run osascript: {}", e),
+        }
+    });
+}
+
+/// Run a Shortcuts.app shortcut by name in the background (macOS)
+fn run_shortcut(name: String) {
+    tokio::spawn(async move {
+        match Command::new("shortcuts").args(["run", &name]).output().await {
+            Ok(output) if !output.status.success() => {
+                let stderr = String::from_utf8_lossy(&output.stderr);
+                warn!("Shortcut '{}' exited with {}: {}", name, output.status, stderr.trim());
+            }
+            Ok(_) => {}
+            Err(e) => warn!("Failed to run shortcuts: {}", e),
+        }
+    });
+}
+
+/// Open a URL in the default browser (`open` on macOS, `xdg-open` elsewhere)
+fn open_url(url: String) {
+    let opener = if cfg!(target_os = "macos") { "open" } else { "xdg-open" };
+    tokio::spawn(async move {
+        match Command::new(opener).arg(&url).output().await {
+            Ok(output) if !output.status.success() => {
+                let stderr = String::from_utf8_lossy(&output.stderr);
+                warn!("{} {} exited with {}: {}", opener, url, output.status, stderr.trim());
+            }
+            Ok(_) => {}
+            Err(e) => warn!("Failed to run {}: {}", opener, e),
+        }
+    });
+}
+
+/// Bring an app to the front, launching it if needed (`open -a` on macOS)
+///
+/// Elsewhere `wmctrl -a` focuses a window by title and launches run the path.
+fn open_app(target: String, launch: bool) {
+    tokio::spawn(async move {
+        let (program, args): (&str, Vec<&str>) = if cfg!(target_os = "macos") {
+            ("open", vec!["-a", &target])
+        } else if launch {
+            // The app outlives the press; the child is reaped by tokio once it exits
+            if let Err(e) = Command::new(&target).spawn() {
+                warn!("Failed to launch {}: {}", target, e);
+            }
+            return;
+        } else {
+            ("wmctrl", vec!["-a", &target])
+        };
+        match Command::new(program).args(&args).output().await {
+            Ok(output) if !output.status.success() => {
+                let stderr = String::from_utf8_lossy(&output.stderr);
+                warn!("{} {} exited with {}: {}", program, target, output.status, stderr.trim());
+            }
+            Ok(_) => {}
+            Err(e) => warn!("Failed to run {}: {}", program, e),
+        }
+    });
+}
+
+/// App name for an app path, as profiles match it ("/Applications/Slack.app" -> "Slack")
+pub fn app_name(path: &str) -> String {
+    std::path::Path::new(path.trim_end_matches('/'))
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_else(|| path.to_string())
+}
+
+/// "Edit src/lib.rs" for logs
+fn approval_summary(approval: &Approval) -> String {
+    match approval.target {
+        Some(ref target) => format!("{} {}", approval.tool, target),
+        None => approval.tool.clone(),
+    }
+}
+
 /// Handles input events from the device
 pub struct InputHandler {
     state: Arc<RwLock<AppState>>,
     profile_manager: Arc<StdRwLock<ProfileManager>>,
     keystroke_sender: KeystrokeSender,
+    /// Where macros running in the background send their keys and text
+    commands: mpsc::Sender<AppCommand>,
     button_press_times: HashMap<u8, Instant>,
     long_press_fired: HashSet<u8>,
+    /// Buttons that were part of a chord, ignored until released
+    chorded: HashSet<u8>,
+    /// Held `repeat_while_held` buttons, with their config and next repeat time
+    repeating: HashMap<u8, (ButtonConfig, Instant)>,
+    /// Repeating `confirm` buttons whose press only armed them, ignored until released
+    arming: HashSet<u8>,
     dictation_state: DictationState,
     last_encoder_press: HashMap<u8, Instant>,
 }
@@ -42,13 +209,22 @@ struct DictationState {
 }
 
 impl InputHandler {
-    pub fn new(state: Arc<RwLock<AppState>>, profile_manager: Arc<StdRwLock<ProfileManager>>) -> Self {
+    pub fn new(
+        state: Arc<RwLock<AppState>>,
+        profile_manager: Arc<StdRwLock<ProfileManager>>,
+        keystroke_sender: KeystrokeSender,
+        commands: mpsc::Sender<AppCommand>,
+    ) -> Self {
         Self {
             state,
             profile_manager,
-            keystroke_sender: KeystrokeSender::new(),
+            keystroke_sender,
+            commands,
             button_press_times: HashMap::new(),
             long_press_fired: HashSet::new(),
+            chorded: HashSet::new(),
+            repeating: HashMap::new(),
+            arming: HashSet::new(),
             dictation_state: DictationState {
                 active: false,
                 first_use: true,
@@ -57,6 +233,11 @@ impl InputHandler {
         }
     }
 
+    /// Whether a button is held (long-press and repeat timing need frequent checks)
+    pub fn is_holding(&self) -> bool {
+        !self.button_press_times.is_empty() || !self.repeating.is_empty()
+    }
+
     /// Check for pending long-press actions and fire them immediately
     /// Call this periodically from the main loop
     pub async fn check_long_press(&mut self) -> Result<bool> {
@@ -66,8 +247,8 @@ impl InputHandler {
         let mic_buttons = self.find_mic_buttons().await;
 
         for button in mic_buttons {
-            // Skip if already fired for this press
-            if self.long_press_fired.contains(&button) {
+            // Skip if already fired for this press, or the press was a chord
+            if self.long_press_fired.contains(&button) || self.chorded.contains(&button) {
                 continue;
             }
 
@@ -84,6 +265,25 @@ impl InputHandler {
             }
         }
 
+        // Repeat held buttons that are due
+        let now = Instant::now();
+        let due: Vec<u8> = self
+            .repeating
+            .iter()
+            .filter(|(_, (_, next))| now >= *next)
+            .map(|(&button, _)| button)
+            .collect();
+        for button in due {
+            let Some((config, next)) = self.repeating.get_mut(&button) else {
+                continue;
+            };
+            let interval = config.repeat_interval_ms.unwrap_or(DEFAULT_REPEAT_INTERVAL_MS);
+            *next = now + Duration::from_millis(interval.max(10));
+            let config = config.clone();
+            self.execute_with_timing(button, false, &config).await?;
+            action_fired = true;
+        }
+
         Ok(action_fired)
     }
 
@@ -93,10 +293,9 @@ impl InputHandler {
         let manager = self.profile_manager.read().unwrap();
 
         let mut mic_buttons = Vec::new();
-        if let Some(profile) = manager.find_profile_for_app(&state.focused_app) {
+        if let Some(profile) = manager.find_profile_for_app(&state.focused_window()) {
             for button in &profile.buttons {
-                let config = button.to_button_config();
-                if matches!(&config.action, ButtonAction::Custom(action) if *action == "MIC") {
+                if button.action.is_custom("MIC") {
                     mic_buttons.push(button.position);
                 }
             }
@@ -109,7 +308,12 @@ impl InputHandler {
         match event {
             InputEvent::ButtonDown(device_id) => {
                 if let Some(button) = device_to_logical_button(device_id) {
+                    let chord = self.find_chord(button).await;
                     self.button_press_times.insert(button, Instant::now());
+                    match chord {
+                        Some((other, action)) => self.run_chord(button, other, action).await?,
+                        None => self.handle_button_down(button).await?,
+                    }
                 }
             }
             InputEvent::ButtonUp(device_id) => {
@@ -126,10 +330,65 @@ impl InputHandler {
             InputEvent::EncoderRelease(_) => {
                 // Currently no action on encoder release
             }
+            InputEvent::StripTouch(zone) => {
+                self.handle_strip_gesture(StripGesture::Touch(zone)).await?;
+            }
+            InputEvent::StripSwipe(direction) => {
+                let gesture = match direction {
+                    SwipeDirection::Left => StripGesture::SwipeLeft,
+                    SwipeDirection::Right => StripGesture::SwipeRight,
+                };
+                self.handle_strip_gesture(gesture).await?;
+            }
         }
         Ok(())
     }
 
+    /// Run the focused profile's action for a strip touch or swipe (there's no
+    /// built-in one)
+    async fn handle_strip_gesture(&mut self, gesture: StripGesture) -> Result<()> {
+        let action = {
+            let state = self.state.read().await;
+            if state.text_entry.is_some() {
+                return Ok(());
+            }
+            let manager = self.profile_manager.read().unwrap();
+            manager.get_strip_action(&state.focused_window(), gesture)
+        };
+        let Some(action) = action else {
+            debug!("Strip {:?}: no action bound", gesture);
+            return Ok(());
+        };
+
+        info!("Strip {:?}", gesture);
+        let config = ButtonConfig::from_action(action);
+        self.execute_with_timing(NO_BUTTON, false, &config).await
+    }
+
+    /// A chord completed by pressing `button`: another button pressed just
+    /// before and still held, with a chord configured for the pair
+    async fn find_chord(&self, button: u8) -> Option<(u8, ButtonAction)> {
+        let (&other, _) = self.button_press_times.iter().find(|(&other, pressed)| {
+            other != button && pressed.elapsed() <= CHORD_WINDOW && !self.chorded.contains(&other)
+        })?;
+        let state = self.state.read().await;
+        if state.text_entry.is_some() || overlay_button(&state, button).is_some() {
+            return None;
+        }
+        let manager = self.profile_manager.read().unwrap();
+        let action = manager.get_chord_action(&state.focused_window(), other, button)?;
+        Some((other, action))
+    }
+
+    /// Run a chord's action; neither button acts when released
+    async fn run_chord(&mut self, button: u8, other: u8, action: ButtonAction) -> Result<()> {
+        info!("Chord: buttons {} + {}", other, button);
+        self.chorded.extend([button, other]);
+        self.repeating.remove(&other);
+        let config = ButtonConfig::from_action(action);
+        self.execute_with_timing(NO_BUTTON, false, &config).await
+    }
+
     /// Handle button release (determines short vs long press)
     async fn handle_button_up(&mut self, button: u8) -> Result<()> {
         let press_duration = self
@@ -138,6 +397,22 @@ impl InputHandler {
             .map(|t| t.elapsed())
             .unwrap_or_default();
 
+        if self.chorded.remove(&button) {
+            debug!("Button {} released (part of a chord)", button);
+            return Ok(());
+        }
+
+        if self.arming.remove(&button) {
+            debug!("Button {} released (waiting for confirmation)", button);
+            return Ok(());
+        }
+
+        // Repeating buttons act while held, not on release
+        if self.repeating.remove(&button).is_some() {
+            debug!("Button {} released (stopped repeating)", button);
+            return Ok(());
+        }
+
         // Check if this was a hold-to-activate button that already fired
         let already_fired = self.long_press_fired.remove(&button);
         if already_fired {
@@ -151,20 +426,130 @@ impl InputHandler {
             "Button {} released (duration: {:?}, long_press: {})",
             button, press_duration, is_long_press
         );
+        self.press(button, is_long_press).await
+    }
+
+    /// Act on a completed press of a logical button, as on release
+    pub async fn press(&mut self, button: u8, is_long_press: bool) -> Result<()> {
+        // Text entry takes over the buttons until confirmed or cancelled
+        if self.state.read().await.text_entry.is_some() {
+            self.handle_text_entry_button(button, is_long_press).await;
+            return Ok(());
+        }
+
+        // An overlay (e.g. permission quick actions) takes the button over
+        let overlay = overlay_button(&*self.state.read().await, button);
+        if let Some(config) = overlay {
+            info!("Overlay: {}", config.label);
+            return self.execute_with_timing(button, is_long_press, &config).await;
+        }
+
+        let config = self.profile_button(button).await;
+        if config.confirm && !self.state.write().await.confirm_press(button, Instant::now()) {
+            info!("Button {}: press again to confirm", button);
+            return Ok(());
+        }
+        self.run_profile_action(button, is_long_press, &config).await
+    }
+
+    /// Run an action that isn't bound to a button (e.g. from the REST API)
+    pub async fn run_action(&mut self, action: &ActionConfig) -> Result<()> {
+        let config = ButtonConfig::from_action(action.to_button_action());
+        self.execute_with_timing(NO_BUTTON, false, &config).await
+    }
 
-        // Get focused app name
-        let focused_app = {
+    /// Start a `repeat_while_held` button: act now, then repeat until release
+    /// (after the confirming press, for `confirm` buttons)
+    async fn handle_button_down(&mut self, button: u8) -> Result<()> {
+        let overlay = {
             let state = self.state.read().await;
-            state.focused_app.clone()
+            if state.text_entry.is_some() {
+                return Ok(());
+            }
+            overlay_button(&state, button)
+        };
+        let from_overlay = overlay.is_some();
+        let config = match overlay {
+            Some(config) => config,
+            None => self.profile_button(button).await,
         };
+        if !config.repeat_while_held {
+            return Ok(());
+        }
+        // A `confirm` button only starts repeating when held the second time
+        if !from_overlay
+            && config.confirm
+            && !self.state.write().await.confirm_press(button, Instant::now())
+        {
+            info!("Button {}: press again to confirm", button);
+            self.arming.insert(button);
+            return Ok(());
+        }
+
+        debug!("Button {} held: repeating {}", button, config.label);
+        if from_overlay {
+            self.execute_with_timing(button, false, &config).await?;
+        } else {
+            self.run_profile_action(button, false, &config).await?;
+        }
+        self.repeating.insert(button, (config, Instant::now() + REPEAT_DELAY));
+        Ok(())
+    }
 
-        // Get button config from ProfileManager (respects user config from web UI)
-        let config = {
+    /// Button config from ProfileManager (respects user config from web UI)
+    async fn profile_button(&self, button: u8) -> ButtonConfig {
+        let state = self.state.read().await;
+        let manager = self.profile_manager.read().unwrap();
+        manager.get_button_config(&state.focused_window(), button)
+    }
+
+    /// Count a press of a profile button, then run its action
+    async fn run_profile_action(
+        &mut self,
+        button: u8,
+        is_long_press: bool,
+        config: &ButtonConfig,
+    ) -> Result<()> {
+        let profile = {
+            let state = self.state.read().await;
             let manager = self.profile_manager.read().unwrap();
-            manager.get_button_config(&focused_app, button)
+            manager.profile_name_for_app(&state.focused_window())
         };
+        if let Some(profile) = profile {
+            self.state.write().await.record_press(&profile, button);
+        }
+        if let Some(kind) = config.feedback {
+            self.state.write().await.pending_feedback = Some(kind);
+        }
 
-        // Execute the action based on config
+        self.execute_with_timing(button, is_long_press, config).await
+    }
+
+    /// Execute an action with per-action timing overrides (restored afterwards)
+    async fn execute_with_timing(
+        &mut self,
+        button: u8,
+        is_long_press: bool,
+        config: &ButtonConfig,
+    ) -> Result<()> {
+        // Acknowledge the press on the key itself (chords and API actions have none)
+        if button < ENCODER_BUTTON_BASE {
+            self.state.write().await.flash_button(button);
+        }
+        let pre_delay = self
+            .keystroke_sender
+            .begin_action(config.pre_delay_ms, config.inter_key_delay_ms);
+        if !pre_delay.is_zero() {
+            sleep(pre_delay).await;
+        }
+
+        let result = self.execute_action(button, is_long_press, config).await;
+        self.keystroke_sender.end_action();
+        result
+    }
+
+    /// Execute a button's configured action
+    async fn execute_action(&mut self, button: u8, is_long_press: bool, config: &ButtonConfig) -> Result<()> {
         match &config.action {
             ButtonAction::Emoji { value, auto_submit } => {
                 info!("Emoji: {} -> {}{}", config.label, value, if *auto_submit { " [auto-submit]" } else { "" });
@@ -175,11 +560,29 @@ impl InputHandler {
             }
             ButtonAction::Text { value, auto_submit } => {
                 info!("Text: {}{}", value, if *auto_submit { " [auto-submit]" } else { "" });
-                self.send_text(value);
+                let value = templating::expand(value, &*self.state.read().await);
+                self.send_text(&value);
                 if *auto_submit {
                     self.send_key(&Key::Enter);
                 }
             }
+            ButtonAction::Snippet(template) => {
+                info!("Snippet: {}", template);
+                // Halves expanded apart, so a `{cursor}` in the clipboard stays text
+                let (before, after) = templating::split_cursor(template);
+                let (before, after) = {
+                    let state = self.state.read().await;
+                    (templating::expand(before, &state), templating::expand(&after, &state))
+                };
+                self.send_text(&format!("{}{}", before, after));
+                for _ in 0..after.chars().count() {
+                    self.send_key(&Key::Left);
+                }
+            }
+            ButtonAction::Scroll(ticks) => {
+                debug!("Scroll: {}", ticks);
+                self.keystroke_sender.scroll(*ticks);
+            }
             ButtonAction::Key(shortcut) => {
                 info!("Shortcut: {}", shortcut);
                 self.keystroke_sender.send_shortcut_string(shortcut);
@@ -188,11 +591,147 @@ impl InputHandler {
                 // Custom actions are handled by Claude-specific logic
                 self.handle_claude_button(button, is_long_press, action_name).await?;
             }
+            ButtonAction::Macro(steps) => {
+                info!("Macro: {} ({} steps)", config.label, steps.len());
+                self.run_macro(steps.clone());
+            }
+            ButtonAction::Scene(name) => {
+                info!("Scene: {}", name);
+                self.state.write().await.pending_scene = Some(SceneRequest::Toggle(name.clone()));
+            }
+            ButtonAction::Toggle(shortcut) => {
+                let profile = {
+                    let state = self.state.read().await;
+                    let manager = self.profile_manager.read().unwrap();
+                    manager.profile_name_for_app(&state.focused_window())
+                };
+                if let Some(profile) = profile {
+                    let on = self.state.write().await.flip_toggle(&profile, button);
+                    info!("Toggle: {} -> {}", config.label, if on { "on" } else { "off" });
+                }
+                self.keystroke_sender.send_shortcut_string(shortcut);
+            }
+            ButtonAction::AppleScript(script) => {
+                info!("AppleScript: {}", config.label);
+                run_applescript(script.clone());
+            }
+            ButtonAction::OpenUrl(url) => {
+                info!("Open URL: {}", url);
+                open_url(url.clone());
+            }
+            ButtonAction::FocusApp { name, switch_profile } => {
+                info!("Focus app: {}", name);
+                open_app(name.clone(), false);
+                if *switch_profile {
+                    self.state.write().await.pending_app = Some(name.clone());
+                }
+            }
+            ButtonAction::LaunchApp { path, switch_profile } => {
+                info!("Launch app: {}", path);
+                open_app(path.clone(), true);
+                if *switch_profile {
+                    self.state.write().await.pending_app = Some(app_name(path));
+                }
+            }
+            ButtonAction::Clipboard(slot) => {
+                let entry = self.state.read().await.clipboard.get(*slot).map(str::to_string);
+                match entry {
+                    // Put the entry back on the clipboard and paste it, so
+                    // multi-line text doesn't submit line by line
+                    Some(text) => {
+                        info!("Clipboard: pasting slot {}", slot);
+                        if system::write_clipboard(&text).await {
+                            self.keystroke_sender.send_shortcut_string("Cmd+V");
+                        } else {
+                            warn!("Failed to set clipboard for slot {}", slot);
+                        }
+                    }
+                    None => debug!("Clipboard slot {} is empty", slot),
+                }
+            }
+            ButtonAction::Obs(command) => {
+                info!("OBS: {}", command);
+                obs::run(command.clone());
+            }
+            ButtonAction::HomeAssistant { entity_id, service } => {
+                info!("Home Assistant: {} on {}", service, entity_id);
+                home_assistant::run(entity_id.clone(), service.clone());
+            }
+            ButtonAction::Monitor(name) => {
+                let url = {
+                    let state = self.state.read().await;
+                    state.monitors.iter().find(|m| m.name == *name).map(|m| m.open_url.clone())
+                };
+                match url {
+                    Some(url) => {
+                        info!("Monitor {}: opening {}", name, url);
+                        open_url(url);
+                    }
+                    None => warn!("No monitor named '{}' in [[monitors]]", name),
+                }
+            }
+            ButtonAction::Script(path) => {
+                info!("Script: {}", path);
+                let context = {
+                    let state = self.state.read().await;
+                    let manager = self.profile_manager.read().unwrap();
+                    let profile = manager.profile_name_for_app(&state.focused_window());
+                    ScriptContext::new(&state, profile, button, is_long_press)
+                };
+                // Run off the main loop; the steps play once the script exits
+                let (path, commands) = (path.clone(), self.commands.clone());
+                let timing = self.keystroke_sender.action_timing();
+                tokio::spawn(async move {
+                    match script::run(&path, &context).await {
+                        Ok(steps) => play_macro(steps, timing, commands).await,
+                        Err(e) => warn!("Script {} failed: {:#}", path, e),
+                    }
+                });
+            }
+            ButtonAction::Folder(entries) => {
+                info!("Folder: opening {} ({} buttons)", config.label, entries.len());
+                let buttons = entries
+                    .iter()
+                    .map(|entry| (entry.position, entry.to_button_config()))
+                    .collect();
+                self.state.write().await.folders.push(Folder {
+                    label: config.label.to_string(),
+                    buttons,
+                });
+            }
         }
 
         Ok(())
     }
 
+    /// Execute macro steps in order, off the main loop
+    ///
+    /// Delays and shell commands run in a background task, and keys and text
+    /// come back to the main loop as `AppCommand::MacroStep`, so a long
+    /// macro doesn't hold up input or the display.
+    fn run_macro(&self, steps: Vec<MacroStep>) {
+        let timing = self.keystroke_sender.action_timing();
+        tokio::spawn(play_macro(steps, timing, self.commands.clone()));
+    }
+
+    /// Type a key or text step of a running macro (see `run_macro`), with
+    /// the timing of the action that started it
+    pub fn run_macro_step(&mut self, step: &MacroStep, timing: KeystrokeConfig) {
+        self.keystroke_sender.resume_action(timing);
+        match step {
+            MacroStep::Key { value } => {
+                debug!("Macro key: {}", value);
+                self.keystroke_sender.send_shortcut_string(value);
+            }
+            MacroStep::Text { value } => {
+                debug!("Macro text: {}", value);
+                self.send_text(value);
+            }
+            MacroStep::Delay { .. } | MacroStep::Shell { .. } => {}
+        }
+        self.keystroke_sender.end_action();
+    }
+
     /// Handle button press in Claude mode (custom actions)
     async fn handle_claude_button(&mut self, button: u8, is_long_press: bool, action_name: &str) -> Result<()> {
         // Route based on action name (allows customization via config)
@@ -207,13 +746,73 @@ impl InputHandler {
             // Bottom row - with long-press variants
             ("TRUST", _) => self.send_trust(),
             ("TAB", false) => self.send_tab(),
-            ("TAB", true) => self.open_new_session().await,
+            ("TAB", true) => self.open_new_session("").await,
             // MIC: short press = voice input, long press = clear line (handled by check_long_press)
             ("MIC", false) => self.trigger_voice_input().await,
             ("ENTER", _) => self.send_enter(),
             ("CLEAR", _) => self.send_clear_command().await?,
+            ("COMPACT", _) | ("COST", _) | ("MEMORY", _) => self.send_slash_command(action_name),
+            ("RESUME", _) => self.open_new_session("--resume").await,
+            ("SESSION_NEXT", _) => self.state.write().await.sessions.scroll(1),
+            ("SESSION_PREV", _) => self.state.write().await.sessions.scroll(-1),
+            ("SESSION_PICK", _) => self.state.write().await.sessions.pick(),
+            ("AGENT_NEXT", _) => self.state.write().await.agents.scroll(1),
+            ("AGENT_PREV", _) => self.state.write().await.agents.scroll(-1),
+            ("MEDIA_PLAY_PAUSE", _) => self.send_media(MediaCommand::PlayPause),
+            ("MEDIA_NEXT", _) => self.send_media(MediaCommand::Next),
+            ("MEDIA_PREV", _) => self.send_media(MediaCommand::Previous),
+            ("PIN_PROFILE", _) => self.toggle_profile_hold().await,
+            ("DND", _) => {
+                let shortcut = self.state.read().await.focus.toggle_shortcut.clone();
+                info!("DND: running shortcut '{}'", shortcut);
+                run_shortcut(shortcut);
+            }
+            ("FOLDER_BACK", _) => {
+                if let Some(folder) = self.state.write().await.folders.pop() {
+                    info!("Folder: closing {}", folder.label);
+                }
+            }
+            ("TIMER", false) => {
+                let mut state = self.state.write().await;
+                state.timer.toggle(Instant::now());
+                info!("Timer: {:?}", state.timer.phase());
+            }
+            ("TIMER", true) => {
+                info!("Timer: reset");
+                self.state.write().await.timer.reset();
+            }
+            ("LOCK_DECK", false) => {
+                if self.state.write().await.deck_lock.lock() {
+                    info!("Deck locked - enter the PIN on the keypad to unlock");
+                } else {
+                    warn!("LOCK_DECK: set a numeric pin under [security] first");
+                }
+            }
+            ("GIT_PULL", false) | ("GIT_PUSH", false) => {
+                let Some(command) = GitCommand::from_action(action_name) else {
+                    return Ok(());
+                };
+                match self.state.read().await.git_dir() {
+                    Some(repo) => git::run(repo, command),
+                    None => {
+                        warn!("{}: set [git] repo or run Claude Code in a repository", action_name)
+                    }
+                }
+            }
+            ("SCREENSHOT", false) => system::screenshot::capture(None),
+            ("SCREENSHOT_SAVE", false) => match self.state.read().await.cwd.clone() {
+                Some(cwd) => system::screenshot::capture(Some(cwd.into())),
+                None => warn!("SCREENSHOT_SAVE: no Claude Code session directory reported yet"),
+            },
+            ("ENTRY", false) => {
+                info!("ENTRY: starting text entry (turn a knob to pick, press it to add)");
+                self.state.write().await.text_entry = Some(TextEntry::new(button));
+            }
             _ => {
-                debug!("Unknown custom action: {} (button {})", action_name, button);
+                let plugins = self.state.read().await.plugins.clone();
+                if !plugins.press(action_name, button, is_long_press) {
+                    debug!("Unknown custom action: {} (button {})", action_name, button);
+                }
             }
         }
 
@@ -224,6 +823,21 @@ impl InputHandler {
     async fn handle_encoder_rotate(&mut self, encoder: u8, direction: i8) -> Result<()> {
         debug!("Encoder {} rotated: {}", encoder, direction);
 
+        // Any knob scrolls characters during text entry
+        if let Some(ref mut entry) = self.state.write().await.text_entry {
+            entry.scroll(direction);
+            return Ok(());
+        }
+
+        let input = if direction > 0 {
+            EncoderInput::RotateCw
+        } else {
+            EncoderInput::RotateCcw
+        };
+        if self.run_encoder_action(encoder, input).await? {
+            return Ok(());
+        }
+
         match encoder {
             0 => self.adjust_volume(direction).await,
             1 => self.cycle_model(direction).await,
@@ -249,6 +863,16 @@ impl InputHandler {
 
         debug!("Encoder {} pressed", encoder);
 
+        // Any knob press adds the highlighted character during text entry
+        if let Some(ref mut entry) = self.state.write().await.text_entry {
+            entry.push();
+            return Ok(());
+        }
+
+        if self.run_encoder_action(encoder, EncoderInput::Press).await? {
+            return Ok(());
+        }
+
         match encoder {
             0 => {
                 // Replay intro animation
@@ -274,6 +898,51 @@ impl InputHandler {
         Ok(())
     }
 
+    /// Run the focused profile's action for an encoder input
+    ///
+    /// Returns false if the profile doesn't bind this input, so the knob keeps
+    /// its built-in behaviour.
+    async fn run_encoder_action(&mut self, encoder: u8, input: EncoderInput) -> Result<bool> {
+        let action = {
+            let state = self.state.read().await;
+            let manager = self.profile_manager.read().unwrap();
+            manager.get_encoder_action(&state.focused_window(), encoder, input)
+        };
+        let Some(action) = action else {
+            return Ok(false);
+        };
+
+        debug!("Encoder {} {:?}: profile action", encoder, input);
+        let config = ButtonConfig::from_action(action);
+        self.execute_action(ENCODER_BUTTON_BASE + encoder, false, &config).await?;
+        Ok(true)
+    }
+
+    /// Button press during text entry: the ENTRY button confirms (long press
+    /// cancels), any other button deletes the last character
+    async fn handle_text_entry_button(&mut self, button: u8, is_long_press: bool) {
+        let mut state = self.state.write().await;
+        let entry = match state.text_entry.take() {
+            Some(mut entry) if entry.button != button => {
+                entry.backspace();
+                state.text_entry = Some(entry);
+                return;
+            }
+            Some(entry) => entry,
+            None => return,
+        };
+
+        if is_long_press || entry.buffer.is_empty() {
+            info!("ENTRY: cancelled");
+            return;
+        }
+
+        info!("ENTRY: confirmed {} characters", entry.buffer.len());
+        state.last_text_entry = Some(entry.buffer.clone());
+        drop(state);
+        self.send_text(&entry.buffer);
+    }
+
     // === Helper methods ===
 
     fn send_text(&mut self, text: &str) {
@@ -287,19 +956,42 @@ impl InputHandler {
     // === Button actions ===
 
     async fn send_accept(&mut self) -> Result<()> {
-        info!("ACCEPT: sending Enter (select Yes)");
+        match self.answer_approval(Decision::Approved).await {
+            Some(approval) => info!("ACCEPT: approving {}", approval_summary(&approval)),
+            None => info!("ACCEPT: sending Enter (select Yes)"),
+        }
         self.send_key(&Key::Enter);
-        self.state.write().await.waiting_for_input = false;
         Ok(())
     }
 
     async fn send_reject(&mut self) -> Result<()> {
-        info!("REJECT: sending Escape (cancel)");
+        match self.answer_approval(Decision::Rejected).await {
+            Some(approval) => info!("REJECT: rejecting {}", approval_summary(&approval)),
+            None => info!("REJECT: sending Escape (cancel)"),
+        }
         self.send_key(&Key::Escape);
-        self.state.write().await.waiting_for_input = false;
         Ok(())
     }
 
+    /// Take the oldest queued permission request and log the answer
+    ///
+    /// With more than one session open, that request's terminal is focused
+    /// first so the key reaches the session that asked.
+    async fn answer_approval(&mut self, decision: Decision) -> Option<Approval> {
+        let (approval, entry, focus, dir) = {
+            let mut state = self.state.write().await;
+            state.waiting_for_input = false;
+            let (approval, entry) = state.approvals.answer(decision)?;
+            (approval, entry, state.sessions.list().len() > 1, state.state_dir())
+        };
+        approvals::append_log(&dir, &entry);
+        if let Some(tty) = approval.tty.as_deref().filter(|_| focus) {
+            system::focus_terminal_tty(tty).await;
+            sleep(Duration::from_millis(150)).await;
+        }
+        Some(approval)
+    }
+
     fn send_stop(&mut self) {
         info!("STOP: sending Escape");
         self.send_key(&Key::Escape);
@@ -327,6 +1019,11 @@ impl InputHandler {
         self.send_key(&Key::Tab);
     }
 
+    fn send_media(&self, command: MediaCommand) {
+        info!("MEDIA: {:?}", command);
+        system::send_media_command(command);
+    }
+
     async fn send_rewind(&mut self) {
         info!("REWIND: sending double Escape");
         self.send_key(&Key::Escape);
@@ -341,28 +1038,58 @@ impl InputHandler {
     }
 
     async fn send_clear_command(&mut self) -> Result<()> {
-        info!("CLEAR: sending /clear + Enter");
-        self.send_text("/clear");
-        self.send_key(&Key::Enter);
+        self.send_slash_command("CLEAR");
         self.state.write().await.task_name = "READY".to_string();
         Ok(())
     }
 
-    async fn open_new_session(&mut self) {
-        info!("Opening new terminal session");
+    /// PIN_PROFILE: keep the profile shown now whatever app comes to the front,
+    /// until pressed again
+    async fn toggle_profile_hold(&self) {
+        let state = self.state.read().await;
+        let mut manager = self.profile_manager.write().unwrap();
+        if manager.held_profile().is_some() {
+            info!("PIN_PROFILE: following the focused app again");
+            manager.hold_profile(None);
+            return;
+        }
+        let current = manager.profile_name_for_app(&state.focused_window());
+        match current {
+            Some(ref profile) => info!("PIN_PROFILE: holding profile '{}'", profile),
+            None => warn!("PIN_PROFILE: no profile is shown to hold"),
+        }
+        manager.hold_profile(current);
+    }
+
+    /// Type the `SLASH_COMMANDS` entry for a built-in action
+    fn send_slash_command(&mut self, action_name: &str) {
+        let Some(&(name, command, submit)) =
+            SLASH_COMMANDS.iter().find(|(name, _, _)| name.eq_ignore_ascii_case(action_name))
+        else {
+            return;
+        };
+        info!("{}: sending {}{}", name, command, if submit { " + Enter" } else { "" });
+        self.send_text(command);
+        if submit {
+            self.send_key(&Key::Enter);
+        }
+    }
+
+    /// Start `claude` with `args` (e.g. "--resume") in a new terminal window
+    async fn open_new_session(&mut self, args: &str) {
+        let mut cmd = String::from("claude");
+        if self.state.read().await.yolo_mode {
+            cmd.push_str(" --dangerously-skip-permissions");
+        }
+        if !args.is_empty() {
+            cmd.push(' ');
+            cmd.push_str(args);
+        }
+        info!("Opening new terminal session: {}", cmd);
 
         #[cfg(target_os = "macos")]
         {
-            let state = self.state.read().await;
-            let yolo = state.yolo_mode;
-            let terminal_app = state.terminal_app.clone();
-            drop(state);
-
-            let cmd = if yolo {
-                "claude --dangerously-skip-permissions"
-            } else {
-                "claude"
-            };
+            let terminal_app = self.state.read().await.terminal_app.clone();
 
             // Escape quotes in terminal app name to prevent AppleScript injection
             let escaped_terminal = terminal_app.replace('\\', "\\\\").replace('"', "\\\"");
@@ -463,3 +1190,84 @@ impl InputHandler {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::KeystrokeQueue;
+    use crate::input::keystrokes::KeyOp;
+    use crate::profiles::generate_default_profiles;
+    use crate::profiles::store::ButtonConfigEntry;
+
+    #[tokio::test]
+    async fn test_macro_step_keeps_action_timing() {
+        let queue = Arc::new(KeystrokeQueue::new());
+        let keystrokes = KeystrokeSender::remote(KeystrokeConfig::default(), Arc::clone(&queue));
+        let (commands, mut rx) = mpsc::channel(8);
+        let state = Arc::new(RwLock::new(AppState::new()));
+        let profiles = Arc::new(StdRwLock::new(ProfileManager::new(generate_default_profiles())));
+        let mut handler = InputHandler::new(state, profiles, keystrokes, commands);
+
+        let entry: ButtonConfigEntry = serde_json::from_value(serde_json::json!({
+            "position": 0, "label": "Select all", "color": "#000000", "bright_color": "#000000",
+            "action": {"type": "macro", "steps": [{"type": "key", "value": "cmd+a"}]},
+            "inter_key_delay_ms": 40,
+        }))
+        .unwrap();
+        handler.execute_with_timing(NO_BUTTON, false, &entry.to_button_config()).await.unwrap();
+
+        // The action has returned by the time the step comes back
+        let Some(AppCommand::MacroStep(step, timing)) = rx.recv().await else {
+            panic!("expected a macro step");
+        };
+        handler.run_macro_step(&step, timing);
+        let sleeps: Vec<_> = queue
+            .drain()
+            .into_iter()
+            .filter_map(|op| match op {
+                KeyOp::Sleep { ms } => Some(ms),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(sleeps, [40, 40, 80]);
+
+        // Later keystrokes are back on the global timing
+        handler.keystroke_sender.send_shortcut_string("cmd+a");
+        assert!(queue.drain().contains(&KeyOp::Sleep { ms: 20 }));
+    }
+
+    #[tokio::test]
+    async fn test_repeat_waits_for_confirm() {
+        let queue = Arc::new(KeystrokeQueue::new());
+        let keystrokes = KeystrokeSender::remote(KeystrokeConfig::default(), Arc::clone(&queue));
+        let (commands, _rx) = mpsc::channel(8);
+        let state = Arc::new(RwLock::new(AppState::new()));
+        let entry: ButtonConfigEntry = serde_json::from_value(serde_json::json!({
+            "position": 0, "label": "Undo", "color": "#000000", "bright_color": "#000000",
+            "action": {"type": "key", "value": "cmd+z"},
+            "repeat_while_held": true, "confirm": true,
+        }))
+        .unwrap();
+        let mut profiles = generate_default_profiles();
+        for profile in &mut profiles {
+            profile.buttons.retain(|button| button.position != 0);
+            profile.buttons.push(entry.clone());
+        }
+        let profiles = Arc::new(StdRwLock::new(ProfileManager::new(profiles)));
+        let mut handler = InputHandler::new(state, profiles, keystrokes, commands);
+
+        // The first hold only arms the button, and its release does nothing
+        handler.handle_button_down(0).await.unwrap();
+        handler.check_long_press().await.unwrap();
+        handler.handle_button_up(0).await.unwrap();
+        assert!(queue.drain().is_empty());
+        assert!(!handler.is_holding());
+
+        // Held again within the window, it acts and repeats
+        handler.handle_button_down(0).await.unwrap();
+        assert!(!queue.drain().is_empty());
+        assert!(handler.is_holding());
+        handler.handle_button_up(0).await.unwrap();
+        assert!(!handler.is_holding());
+    }
+}
@@ -1,7 +1,13 @@
-use enigo::{Enigo, Key as EnigoKey, Keyboard, Settings};
+use enigo::{Axis, Direction, Enigo, Key as EnigoKey, Keyboard, Mouse, Settings};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
 use std::time::Duration;
 use tracing::debug;
 
+use super::target::TargetedOutput;
+use crate::agent::KeystrokeQueue;
+use crate::config::{KeystrokeConfig, SendTarget};
+
 /// Key types for input
 #[derive(Debug, Clone)]
 pub enum Key {
@@ -116,22 +122,192 @@ pub fn string_to_key(s: &str) -> Option<Key> {
     }
 }
 
+/// A primitive keyboard operation, forwarded to a user agent in daemon mode
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum KeyOp {
+    Key { key: EnigoKey, direction: Direction },
+    Text { text: String },
+    Sleep { ms: u64 },
+    Scroll { length: i32 },
+}
+
+/// Where keystrokes end up
+enum Output {
+    /// Inject into the current session
+    Local(Enigo),
+    /// Queue for a per-user agent (system daemon mode)
+    Remote(Arc<KeystrokeQueue>),
+}
+
 /// Sends keystrokes to the focused window (attach mode)
 pub struct KeystrokeSender {
-    enigo: Enigo,
+    output: Output,
+    /// tmux pane or iTerm2 session that gets keystrokes instead of the focused window
+    target: Option<TargetedOutput>,
+    /// Global timing from config
+    default_timing: KeystrokeConfig,
+    /// Timing for the action currently being executed (global + per-action overrides)
+    timing: KeystrokeConfig,
+    /// PRNG state for jitter
+    jitter_seed: u64,
 }
 
 impl KeystrokeSender {
     pub fn new() -> Self {
+        Self::with_timing(KeystrokeConfig::default())
+    }
+
+    /// Create a sender with global keystroke timing
+    pub fn with_timing(timing: KeystrokeConfig) -> Self {
         let enigo = Enigo::new(&Settings::default()).expect("Failed to initialize Enigo");
-        Self { enigo }
+        Self::with_output(Output::Local(enigo), timing)
+    }
+
+    /// Create a sender that queues keystrokes for a user agent instead of injecting them
+    pub fn remote(timing: KeystrokeConfig, queue: Arc<KeystrokeQueue>) -> Self {
+        Self::with_output(Output::Remote(queue), timing)
+    }
+
+    fn with_output(output: Output, timing: KeystrokeConfig) -> Self {
+        let jitter_seed = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_nanos() as u64)
+            .unwrap_or(0)
+            | 1;
+        Self {
+            output,
+            target: None,
+            default_timing: timing,
+            timing,
+            jitter_seed,
+        }
+    }
+
+    /// Deliver keystrokes to `target` rather than the focused window
+    pub fn with_target(mut self, target: SendTarget) -> Self {
+        self.target = TargetedOutput::new(target);
+        self
+    }
+
+    /// Replace the global keystroke timing (e.g., after config reload)
+    pub fn set_default_timing(&mut self, timing: KeystrokeConfig) {
+        self.default_timing = timing;
+        self.timing = timing;
+    }
+
+    /// Apply per-action timing overrides on top of the global defaults
+    /// Returns the pre-delay the caller should wait before sending the first keystroke
+    pub fn begin_action(&mut self, pre_delay_ms: Option<u64>, inter_key_delay_ms: Option<u64>) -> Duration {
+        self.timing = KeystrokeConfig {
+            pre_delay_ms: pre_delay_ms.unwrap_or(self.default_timing.pre_delay_ms),
+            inter_key_delay_ms: inter_key_delay_ms.unwrap_or(self.default_timing.inter_key_delay_ms),
+            jitter_ms: self.default_timing.jitter_ms,
+        };
+        if self.timing.pre_delay_ms == 0 {
+            Duration::ZERO
+        } else {
+            Duration::from_millis(self.timing.pre_delay_ms) + next_jitter(&mut self.jitter_seed, self.timing.jitter_ms)
+        }
+    }
+
+    /// Restore global timing after an action completes
+    pub fn end_action(&mut self) {
+        self.timing = self.default_timing;
+    }
+
+    /// Timing in effect for the current action
+    pub fn action_timing(&self) -> KeystrokeConfig {
+        self.timing
+    }
+
+    /// Resume an action's timing (for macro steps typed after it returned)
+    pub fn resume_action(&mut self, timing: KeystrokeConfig) {
+        self.timing = timing;
+    }
+
+    /// Sleep for `steps` inter-key delays plus jitter
+    fn pause(&mut self, steps: u32) {
+        let base = Duration::from_millis(self.timing.inter_key_delay_ms) * steps;
+        let delay = base + next_jitter(&mut self.jitter_seed, self.timing.jitter_ms);
+        self.sleep(delay);
+    }
+
+    /// Sleep between keystrokes (forwarded so the agent replays the same timing)
+    fn sleep(&mut self, delay: Duration) {
+        if delay.is_zero() {
+            return;
+        }
+        match &mut self.output {
+            Output::Local(_) => std::thread::sleep(delay),
+            Output::Remote(queue) => queue.push(KeyOp::Sleep {
+                ms: delay.as_millis() as u64,
+            }),
+        }
+    }
+
+    /// Press, release, or click a single key
+    fn key(&mut self, key: EnigoKey, direction: Direction) {
+        if let Some(target) = &mut self.target {
+            target.key(key, direction);
+            return;
+        }
+        match &mut self.output {
+            Output::Local(enigo) => {
+                let _ = enigo.key(key, direction);
+            }
+            Output::Remote(queue) => queue.push(KeyOp::Key { key, direction }),
+        }
+    }
+
+    /// Type a string
+    fn text(&mut self, text: &str) {
+        if let Some(target) = &mut self.target {
+            target.text(text);
+            return;
+        }
+        match &mut self.output {
+            Output::Local(enigo) => {
+                let _ = enigo.text(text);
+            }
+            Output::Remote(queue) => queue.push(KeyOp::Text {
+                text: text.to_string(),
+            }),
+        }
+    }
+
+    /// Scroll the mouse wheel under the pointer (positive is down)
+    ///
+    /// Goes to whatever is under the pointer, even with a tmux or iTerm2 target.
+    pub fn scroll(&mut self, length: i32) {
+        if length == 0 {
+            return;
+        }
+        match &mut self.output {
+            Output::Local(enigo) => {
+                let _ = enigo.scroll(length, Axis::Vertical);
+            }
+            Output::Remote(queue) => queue.push(KeyOp::Scroll { length }),
+        }
+    }
+
+    /// Replay operations received from the daemon (agent mode)
+    pub fn replay(&mut self, ops: &[KeyOp]) {
+        for op in ops {
+            match op {
+                KeyOp::Key { key, direction } => self.key(*key, *direction),
+                KeyOp::Text { text } => self.text(text),
+                KeyOp::Sleep { ms } => self.sleep(Duration::from_millis(*ms)),
+                KeyOp::Scroll { length } => self.scroll(*length),
+            }
+        }
     }
 
     /// Send a single key press
     pub fn send_key(&mut self, key: &Key) {
         let enigo_key = key_to_enigo(key);
         debug!("Sending key: {:?}", enigo_key);
-        let _ = self.enigo.key(enigo_key, enigo::Direction::Click);
+        self.key(enigo_key, Direction::Click);
     }
 
     /// Send a keyboard shortcut (key with optional modifiers)
@@ -163,12 +339,12 @@ impl KeystrokeSender {
 
     /// Release all modifier keys to ensure clean state
     fn release_all_modifiers(&mut self) {
-        let _ = self.enigo.key(EnigoKey::Meta, enigo::Direction::Release);
-        let _ = self.enigo.key(EnigoKey::Control, enigo::Direction::Release);
-        let _ = self.enigo.key(EnigoKey::Alt, enigo::Direction::Release);
-        let _ = self.enigo.key(EnigoKey::Shift, enigo::Direction::Release);
-        let _ = self.enigo.key(EnigoKey::RCommand, enigo::Direction::Release);
-        let _ = self.enigo.key(EnigoKey::RControl, enigo::Direction::Release);
+        self.key(EnigoKey::Meta, Direction::Release);
+        self.key(EnigoKey::Control, Direction::Release);
+        self.key(EnigoKey::Alt, Direction::Release);
+        self.key(EnigoKey::Shift, Direction::Release);
+        self.key(EnigoKey::RCommand, Direction::Release);
+        self.key(EnigoKey::RControl, Direction::Release);
     }
 
     /// Parse and send a shortcut string like "Cmd+C" or "Enter"
@@ -185,61 +361,57 @@ impl KeystrokeSender {
     /// Send text as typed characters
     pub fn send_text(&mut self, text: &str) {
         debug!("Sending text: {}", text);
-        let _ = self.enigo.text(text);
+        self.text(text);
     }
 
     /// Send Shift+Tab
     pub fn send_shift_tab(&mut self) {
         debug!("Sending Shift+Tab");
-        let _ = self.enigo.key(EnigoKey::Shift, enigo::Direction::Press);
-        let _ = self.enigo.key(EnigoKey::Tab, enigo::Direction::Click);
-        let _ = self.enigo.key(EnigoKey::Shift, enigo::Direction::Release);
+        self.key(EnigoKey::Shift, Direction::Press);
+        self.key(EnigoKey::Tab, Direction::Click);
+        self.key(EnigoKey::Shift, Direction::Release);
     }
 
     /// Send Alt+M (Option+M on macOS) - Toggle permission modes
     pub fn send_alt_m(&mut self) {
         debug!("Sending Alt+M (toggle permission modes)");
-        let _ = self.enigo.key(EnigoKey::Alt, enigo::Direction::Press);
-        let _ = self
-            .enigo
-            .key(EnigoKey::Unicode('m'), enigo::Direction::Click);
-        let _ = self.enigo.key(EnigoKey::Alt, enigo::Direction::Release);
+        self.key(EnigoKey::Alt, Direction::Press);
+        self.key(EnigoKey::Unicode('m'), Direction::Click);
+        self.key(EnigoKey::Alt, Direction::Release);
     }
 
     /// Send Escape sequence for Alt+M (for terminals that use escape sequences)
     pub fn send_escape_m(&mut self) {
         debug!("Sending Escape+M (meta key sequence)");
-        let _ = self.enigo.key(EnigoKey::Escape, enigo::Direction::Click);
-        std::thread::sleep(Duration::from_millis(10));
-        let _ = self
-            .enigo
-            .key(EnigoKey::Unicode('m'), enigo::Direction::Click);
+        self.key(EnigoKey::Escape, Direction::Click);
+        self.pause(1);
+        self.key(EnigoKey::Unicode('m'), Direction::Click);
     }
 
     /// Send a key with modifiers
     pub fn send_key_with_modifiers(&mut self, modifiers: &[EnigoKey], key: EnigoKey) {
         // Press modifiers
         for modifier in modifiers {
-            let _ = self.enigo.key(*modifier, enigo::Direction::Press);
+            self.key(*modifier, Direction::Press);
         }
 
         // Small delay to ensure modifiers are registered
-        std::thread::sleep(Duration::from_millis(10));
+        self.pause(1);
 
         // Press and release the main key
-        let _ = self.enigo.key(key, enigo::Direction::Click);
+        self.key(key, Direction::Click);
 
         // Small delay before releasing modifiers
-        std::thread::sleep(Duration::from_millis(10));
+        self.pause(1);
 
         // Release modifiers in reverse order
         for modifier in modifiers.iter().rev() {
-            let _ = self.enigo.key(*modifier, enigo::Direction::Release);
+            self.key(*modifier, Direction::Release);
         }
 
         // Delay after releasing to ensure system processes the release
         // before any subsequent keystrokes
-        std::thread::sleep(Duration::from_millis(20));
+        self.pause(2);
     }
 
     // === Zoom controls ===
@@ -280,35 +452,35 @@ impl KeystrokeSender {
 
     pub fn send_accept(&mut self) {
         self.send_text("y");
-        std::thread::sleep(Duration::from_millis(10));
-        let _ = self.enigo.key(EnigoKey::Return, enigo::Direction::Click);
+        self.pause(1);
+        self.key(EnigoKey::Return, Direction::Click);
     }
 
     pub fn send_reject(&mut self) {
         self.send_text("n");
-        std::thread::sleep(Duration::from_millis(10));
-        let _ = self.enigo.key(EnigoKey::Return, enigo::Direction::Click);
+        self.pause(1);
+        self.key(EnigoKey::Return, Direction::Click);
     }
 
     pub fn send_stop(&mut self) {
-        let _ = self.enigo.key(EnigoKey::Escape, enigo::Direction::Click);
+        self.key(EnigoKey::Escape, Direction::Click);
     }
 
     pub fn send_retry(&mut self) {
-        let _ = self.enigo.key(EnigoKey::UpArrow, enigo::Direction::Click);
-        std::thread::sleep(Duration::from_millis(50));
-        let _ = self.enigo.key(EnigoKey::Return, enigo::Direction::Click);
+        self.key(EnigoKey::UpArrow, Direction::Click);
+        self.sleep(Duration::from_millis(50));
+        self.key(EnigoKey::Return, Direction::Click);
     }
 
     pub fn send_clear(&mut self) {
         self.send_text("/clear");
-        let _ = self.enigo.key(EnigoKey::Return, enigo::Direction::Click);
+        self.key(EnigoKey::Return, Direction::Click);
     }
 
     pub fn send_rewind(&mut self) {
-        let _ = self.enigo.key(EnigoKey::Escape, enigo::Direction::Click);
-        std::thread::sleep(Duration::from_millis(100));
-        let _ = self.enigo.key(EnigoKey::Escape, enigo::Direction::Click);
+        self.key(EnigoKey::Escape, Direction::Click);
+        self.sleep(Duration::from_millis(100));
+        self.key(EnigoKey::Escape, Direction::Click);
     }
 
     pub fn navigate_history(&mut self, direction: i8) {
@@ -317,7 +489,7 @@ impl KeystrokeSender {
         } else {
             EnigoKey::UpArrow
         };
-        let _ = self.enigo.key(key, enigo::Direction::Click);
+        self.key(key, Direction::Click);
     }
 
     pub fn scroll_output(&mut self, direction: i8) {
@@ -326,21 +498,21 @@ impl KeystrokeSender {
         } else {
             EnigoKey::PageUp
         };
-        let _ = self.enigo.key(key, enigo::Direction::Click);
+        self.key(key, Direction::Click);
     }
 
     pub fn send_model_switch(&mut self, model: &str) {
         self.send_text(&format!("/model {}", model));
-        let _ = self.enigo.key(EnigoKey::Return, enigo::Direction::Click);
+        self.key(EnigoKey::Return, Direction::Click);
     }
 
     /// Send double Right Command to trigger dictation
     pub fn send_dictation_toggle(&mut self) {
         debug!("Sending double Right Command for dictation");
         // RCommand is Right Command key
-        let _ = self.enigo.key(EnigoKey::RCommand, enigo::Direction::Click);
-        std::thread::sleep(Duration::from_millis(100));
-        let _ = self.enigo.key(EnigoKey::RCommand, enigo::Direction::Click);
+        self.key(EnigoKey::RCommand, Direction::Click);
+        self.sleep(Duration::from_millis(100));
+        self.key(EnigoKey::RCommand, Direction::Click);
     }
 }
 
@@ -350,6 +522,19 @@ impl Default for KeystrokeSender {
     }
 }
 
+/// Random delay in 0..=max_ms (xorshift64, no external RNG needed)
+fn next_jitter(seed: &mut u64, max_ms: u64) -> Duration {
+    if max_ms == 0 {
+        return Duration::ZERO;
+    }
+    let mut x = *seed;
+    x ^= x << 13;
+    x ^= x >> 7;
+    x ^= x << 17;
+    *seed = x;
+    Duration::from_millis(x % (max_ms + 1))
+}
+
 /// Convert our Key enum to Enigo's key type
 fn key_to_enigo(key: &Key) -> EnigoKey {
     match key {
@@ -382,3 +567,17 @@ fn key_to_enigo(key: &Key) -> EnigoKey {
         Key::Char(c) => EnigoKey::Unicode(*c),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_next_jitter_in_range() {
+        let mut seed = 12345;
+        assert_eq!(next_jitter(&mut seed, 0), Duration::ZERO);
+        for _ in 0..100 {
+            assert!(next_jitter(&mut seed, 15) <= Duration::from_millis(15));
+        }
+    }
+}
@@ -1,5 +1,7 @@
 mod handler;
+pub mod script;
 pub mod keystrokes;
+mod target;
 
-pub use handler::InputHandler;
+pub use handler::{device_to_logical_button, InputHandler};
 pub use keystrokes::KeystrokeSender;
@@ -0,0 +1,148 @@
+//! Script actions: user programs that decide what a button does
+//!
+//! A script is any executable in `~/.config/claude-deck/scripts/` (or an
+//! absolute path). It gets the deck's context as JSON on stdin and prints
+//! the steps to run, one per line, using the macro step types:
+//!
+//! ```text
+//! key Cmd+Shift+T
+//! text git status
+//! delay 200
+//! shell open -a Safari
+//! ```
+//!
+//! Scripts are run fresh on every press, so edits apply immediately. The
+//! handler runs them in a background task and plays the steps as a macro.
+//!
+//! This is not an embedded scripting engine: there is no Rhai (or other)
+//! interpreter in the binary, and scripts can't call back into the deck while
+//! they run. The printed steps stand in for `send_key`/`send_text`/`shell`
+//! helpers, and the JSON context for a context object.
+
+use anyhow::{bail, Context, Result};
+use serde::Serialize;
+use std::path::PathBuf;
+use std::process::Stdio;
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+use tracing::warn;
+
+use crate::profiles::store::MacroStep;
+use crate::state::AppState;
+
+/// Scripts taking longer than this are killed
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// What a script is told about the press, as JSON on stdin
+#[derive(Debug, Clone, Serialize)]
+pub struct ScriptContext {
+    pub button: u8,
+    pub long_press: bool,
+    pub focused_app: String,
+    pub bundle_id: String,
+    pub window_title: String,
+    /// Profile the button belongs to (None for built-in layouts)
+    pub profile: Option<String>,
+    pub task: String,
+    pub tool_detail: Option<String>,
+    pub model: String,
+    pub waiting_for_input: bool,
+    pub active_scene: Option<String>,
+}
+
+impl ScriptContext {
+    pub fn new(state: &AppState, profile: Option<String>, button: u8, long_press: bool) -> Self {
+        Self {
+            button,
+            long_press,
+            focused_app: state.focused_app.clone(),
+            bundle_id: state.focused_bundle_id.clone(),
+            window_title: state.focused_title.clone(),
+            profile,
+            task: state.task_name.clone(),
+            tool_detail: state.tool_detail.clone(),
+            model: state.model.clone(),
+            waiting_for_input: state.waiting_for_input,
+            active_scene: state.active_scene.clone(),
+        }
+    }
+}
+
+/// Resolve a script path: absolute paths as given, others under the scripts directory
+pub fn script_path(path: &str) -> Result<PathBuf> {
+    let path = PathBuf::from(path);
+    if path.is_absolute() {
+        return Ok(path);
+    }
+    let home = std::env::var("HOME").context("HOME environment variable not set")?;
+    Ok(PathBuf::from(home).join(".config/claude-deck/scripts").join(path))
+}
+
+/// Run a script and return the steps it printed
+pub async fn run(path: &str, context: &ScriptContext) -> Result<Vec<MacroStep>> {
+    let path = script_path(path)?;
+    let mut child = Command::new(&path)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .with_context(|| format!("Failed to start {}", path.display()))?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        // A script that never reads stdin is fine
+        stdin.write_all(&serde_json::to_vec(context)?).await.ok();
+    }
+    let output = tokio::time::timeout(TIMEOUT, child.wait_with_output())
+        .await
+        .with_context(|| format!("{} timed out", path.display()))??;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        bail!("{} exited with {}: {}", path.display(), output.status, stderr.trim());
+    }
+    Ok(parse_steps(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// Parse a script's output into steps, skipping blank and unknown lines
+pub fn parse_steps(output: &str) -> Vec<MacroStep> {
+    output
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .filter_map(|line| {
+            let (kind, arg) = line.split_once(' ').unwrap_or((line, ""));
+            let step = match kind {
+                "key" => MacroStep::Key { value: arg.trim().to_string() },
+                "text" => MacroStep::Text { value: arg.to_string() },
+                "delay" => MacroStep::Delay { ms: arg.trim().parse().ok()? },
+                "shell" => MacroStep::Shell { command: arg.to_string() },
+                _ => {
+                    warn!("Ignoring script output line: {}", line);
+                    return None;
+                }
+            };
+            Some(step)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_steps() {
+        let output = "key Cmd+Shift+T\n\ntext  two spaces\ndelay 200\ndelay soon\nbeep\n";
+        let steps = parse_steps(output);
+        assert_eq!(
+            steps,
+            vec![
+                MacroStep::Key { value: "Cmd+Shift+T".into() },
+                MacroStep::Text { value: " two spaces".into() },
+                MacroStep::Delay { ms: 200 },
+            ]
+        );
+        assert!(script_path("/opt/deck.sh").unwrap().is_absolute());
+        assert!(script_path("git.py").unwrap().ends_with(".config/claude-deck/scripts/git.py"));
+    }
+}
@@ -0,0 +1,275 @@
+//! Targeted keystrokes: deliver to a tmux pane or iTerm2 session instead of
+//! the focused window (`[target]` in the config)
+//!
+//! Key events arrive one at a time (modifier down, key click, modifier up),
+//! so held modifiers are tracked here and folded into the key they apply to.
+//! Cmd shortcuts are app-level and can't reach a terminal this way; they are
+//! dropped.
+
+use enigo::{Direction, Key as EnigoKey};
+use std::process::Command;
+use tracing::{debug, warn};
+
+use crate::config::SendTarget;
+
+/// Modifiers currently held down
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct Modifiers {
+    cmd: bool,
+    ctrl: bool,
+    alt: bool,
+    shift: bool,
+}
+
+/// Sends keystrokes to a configured tmux pane or iTerm2 session
+pub struct TargetedOutput {
+    target: SendTarget,
+    held: Modifiers,
+}
+
+impl TargetedOutput {
+    /// None for `SendTarget::Focused`, which uses synthesized key events
+    pub fn new(target: SendTarget) -> Option<Self> {
+        match target {
+            SendTarget::Focused => None,
+            target => Some(Self {
+                target,
+                held: Modifiers::default(),
+            }),
+        }
+    }
+
+    /// Press, release, or click a single key
+    pub fn key(&mut self, key: EnigoKey, direction: Direction) {
+        if let Some(flag) = self.modifier_flag(key) {
+            match direction {
+                Direction::Press => *flag = true,
+                Direction::Release => *flag = false,
+                Direction::Click => {}
+            }
+            return;
+        }
+        if direction == Direction::Release {
+            return;
+        }
+        if self.held.cmd {
+            debug!("Not sending Cmd shortcut to {:?}", self.target);
+            return;
+        }
+        match &self.target {
+            SendTarget::Focused => {}
+            SendTarget::Tmux { pane } => match tmux_key(key, self.held) {
+                Some(name) => run("tmux", &["send-keys", "-t", pane, &name]),
+                None => debug!("No tmux name for {:?}", key),
+            },
+            SendTarget::Iterm { session } => match terminal_input(key, self.held) {
+                Some(input) => run("osascript", &["-e", &iterm_script(session, &input)]),
+                None => debug!("No terminal input for {:?}", key),
+            },
+        }
+    }
+
+    /// Type a string
+    pub fn text(&mut self, text: &str) {
+        match &self.target {
+            SendTarget::Focused => {}
+            SendTarget::Tmux { pane } => run("tmux", &["send-keys", "-t", pane, "-l", "--", text]),
+            SendTarget::Iterm { session } => {
+                run("osascript", &["-e", &iterm_script(session, text)])
+            }
+        }
+    }
+
+    fn modifier_flag(&mut self, key: EnigoKey) -> Option<&mut bool> {
+        match key {
+            EnigoKey::Meta => Some(&mut self.held.cmd),
+            #[cfg(target_os = "macos")]
+            EnigoKey::RCommand => Some(&mut self.held.cmd),
+            EnigoKey::Control | EnigoKey::RControl => Some(&mut self.held.ctrl),
+            EnigoKey::Alt => Some(&mut self.held.alt),
+            EnigoKey::Shift => Some(&mut self.held.shift),
+            _ => None,
+        }
+    }
+}
+
+/// tmux key name (as accepted by `send-keys`), e.g. "Enter", "C-c", "BTab"
+fn tmux_key(key: EnigoKey, mods: Modifiers) -> Option<String> {
+    let base = match key {
+        EnigoKey::Tab if mods.shift => return Some("BTab".to_string()),
+        EnigoKey::Unicode(c) if mods.shift => c.to_uppercase().collect(),
+        EnigoKey::Unicode(c) => c.to_string(),
+        EnigoKey::Return => "Enter".to_string(),
+        EnigoKey::Escape => "Escape".to_string(),
+        EnigoKey::Tab => "Tab".to_string(),
+        EnigoKey::Space => "Space".to_string(),
+        EnigoKey::Backspace => "BSpace".to_string(),
+        EnigoKey::Delete => "DC".to_string(),
+        EnigoKey::UpArrow => "Up".to_string(),
+        EnigoKey::DownArrow => "Down".to_string(),
+        EnigoKey::LeftArrow => "Left".to_string(),
+        EnigoKey::RightArrow => "Right".to_string(),
+        EnigoKey::PageUp => "PPage".to_string(),
+        EnigoKey::PageDown => "NPage".to_string(),
+        EnigoKey::Home => "Home".to_string(),
+        EnigoKey::End => "End".to_string(),
+        other => format!("F{}", function_key_number(other)?),
+    };
+    let mut name = String::new();
+    if mods.ctrl {
+        name.push_str("C-");
+    }
+    if mods.alt {
+        name.push_str("M-");
+    }
+    if mods.shift && !matches!(key, EnigoKey::Unicode(_)) {
+        name.push_str("S-");
+    }
+    name.push_str(&base);
+    Some(name)
+}
+
+/// What a terminal receives for a key, e.g. "\r" for Enter or "\x03" for Ctrl+C
+fn terminal_input(key: EnigoKey, mods: Modifiers) -> Option<String> {
+    let input = match key {
+        EnigoKey::Unicode(c) if mods.ctrl && c.is_ascii_alphabetic() => {
+            ((c.to_ascii_lowercase() as u8 & 0x1f) as char).to_string()
+        }
+        EnigoKey::Unicode(c) if mods.shift => c.to_uppercase().collect(),
+        EnigoKey::Unicode(c) => c.to_string(),
+        EnigoKey::Tab if mods.shift => "\x1b[Z".to_string(),
+        EnigoKey::Return => "\r".to_string(),
+        EnigoKey::Escape => "\x1b".to_string(),
+        EnigoKey::Tab => "\t".to_string(),
+        EnigoKey::Space => " ".to_string(),
+        EnigoKey::Backspace => "\x7f".to_string(),
+        EnigoKey::Delete => "\x1b[3~".to_string(),
+        EnigoKey::UpArrow => "\x1b[A".to_string(),
+        EnigoKey::DownArrow => "\x1b[B".to_string(),
+        EnigoKey::RightArrow => "\x1b[C".to_string(),
+        EnigoKey::LeftArrow => "\x1b[D".to_string(),
+        EnigoKey::Home => "\x1b[H".to_string(),
+        EnigoKey::End => "\x1b[F".to_string(),
+        EnigoKey::PageUp => "\x1b[5~".to_string(),
+        EnigoKey::PageDown => "\x1b[6~".to_string(),
+        _ => return None,
+    };
+    // Alt (Option as Meta) prefixes the key with Escape
+    Some(if mods.alt { format!("\x1b{}", input) } else { input })
+}
+
+fn function_key_number(key: EnigoKey) -> Option<u8> {
+    let keys = [
+        EnigoKey::F1,
+        EnigoKey::F2,
+        EnigoKey::F3,
+        EnigoKey::F4,
+        EnigoKey::F5,
+        EnigoKey::F6,
+        EnigoKey::F7,
+        EnigoKey::F8,
+        EnigoKey::F9,
+        EnigoKey::F10,
+        EnigoKey::F11,
+        EnigoKey::F12,
+    ];
+    keys.iter().position(|&k| k == key).map(|i| i as u8 + 1)
+}
+
+/// AppleScript writing `input` (without a newline) to the iTerm2 session `id`
+fn iterm_script(id: &str, input: &str) -> String {
+    format!(
+        r#"tell application "iTerm2"
+    repeat with w in windows
+        repeat with t in tabs of w
+            repeat with s in sessions of t
+                if id of s is {} then
+                    tell s to write text {} newline NO
+                    return
+                end if
+            end repeat
+        end repeat
+    end repeat
+end tell"#,
+        applescript_string(id),
+        applescript_string(input)
+    )
+}
+
+/// AppleScript expression for `text`, with control characters as `character id`
+fn applescript_string(text: &str) -> String {
+    let mut parts = Vec::new();
+    let mut literal = String::new();
+    for c in text.chars() {
+        if c.is_control() {
+            if !literal.is_empty() {
+                parts.push(format!("\"{}\"", std::mem::take(&mut literal)));
+            }
+            parts.push(format!("(character id {})", c as u32));
+        } else {
+            if c == '"' || c == '\\' {
+                literal.push('\\');
+            }
+            literal.push(c);
+        }
+    }
+    if !literal.is_empty() || parts.is_empty() {
+        parts.push(format!("\"{}\"", literal));
+    }
+    parts.join(" & ")
+}
+
+/// Run a delivery command, logging failures (keystrokes are fire-and-forget)
+fn run(program: &str, args: &[&str]) {
+    match Command::new(program).args(args).output() {
+        Ok(output) if !output.status.success() => {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            warn!("{} failed: {} - {}", program, output.status, stderr.trim());
+        }
+        Ok(_) => {}
+        Err(e) => warn!("Failed to run {}: {}", program, e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_key_translation() {
+        let none = Modifiers::default();
+        let ctrl = Modifiers { ctrl: true, ..none };
+        let shift = Modifiers { shift: true, ..none };
+
+        assert_eq!(tmux_key(EnigoKey::Return, none).as_deref(), Some("Enter"));
+        assert_eq!(tmux_key(EnigoKey::Unicode('c'), ctrl).as_deref(), Some("C-c"));
+        assert_eq!(tmux_key(EnigoKey::Tab, shift).as_deref(), Some("BTab"));
+        assert_eq!(tmux_key(EnigoKey::UpArrow, shift).as_deref(), Some("S-Up"));
+        assert_eq!(tmux_key(EnigoKey::F5, none).as_deref(), Some("F5"));
+
+        assert_eq!(terminal_input(EnigoKey::Unicode('c'), ctrl).as_deref(), Some("\x03"));
+        let alt = Modifiers { alt: true, ..none };
+        assert_eq!(terminal_input(EnigoKey::Unicode('m'), alt).as_deref(), Some("\x1bm"));
+        assert_eq!(terminal_input(EnigoKey::F5, none), None);
+    }
+
+    #[test]
+    fn test_applescript_string() {
+        assert_eq!(applescript_string("say \"hi\""), r#""say \"hi\"""#);
+        assert_eq!(applescript_string("\x1b[A"), r#"(character id 27) & "[A""#);
+        assert_eq!(applescript_string(""), r#""""#);
+    }
+
+    #[test]
+    fn test_modifiers_fold_into_next_key() {
+        let mut output = TargetedOutput::new(SendTarget::Tmux {
+            pane: "claude".to_string(),
+        })
+        .unwrap();
+        output.key(EnigoKey::Control, Direction::Press);
+        assert!(output.held.ctrl);
+        output.key(EnigoKey::Control, Direction::Release);
+        assert_eq!(output.held, Modifiers::default());
+        assert!(TargetedOutput::new(SendTarget::Focused).is_none());
+    }
+}
@@ -0,0 +1,198 @@
+//! Git repository status for the `git` strip widget, and GIT_PULL/GIT_PUSH
+//!
+//! The repository is `[git] repo`, or else the directory the followed Claude
+//! Code session reports. Its status comes from `git status --porcelain
+//! --branch` every `interval_secs`, while the widget is on the strip.
+
+use anyhow::{bail, Result};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::process::Command;
+use tokio::sync::{mpsc, RwLock as TokioRwLock};
+use tracing::{debug, info, warn};
+
+use crate::config::{GitConfig, StripWidget};
+use crate::state::AppState;
+use crate::AppCommand;
+
+/// Branch and working tree state of a repository
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GitStatus {
+    /// Repository directory name
+    pub repo: String,
+    /// Branch name ("HEAD" when detached)
+    pub branch: String,
+    /// Changed and untracked files
+    pub dirty: usize,
+    pub ahead: u32,
+    pub behind: u32,
+}
+
+impl GitStatus {
+    /// "main ●3 ↑1 ↓2", leaving out counts that are zero
+    pub fn summary(&self) -> String {
+        let mut summary = self.branch.clone();
+        if self.dirty > 0 {
+            summary.push_str(&format!(" ●{}", self.dirty));
+        }
+        if self.ahead > 0 {
+            summary.push_str(&format!(" ↑{}", self.ahead));
+        }
+        if self.behind > 0 {
+            summary.push_str(&format!(" ↓{}", self.behind));
+        }
+        summary
+    }
+}
+
+/// Remote operations run by the git built-in actions
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GitCommand {
+    Pull,
+    Push,
+}
+
+impl GitCommand {
+    /// The command for a GIT_PULL / GIT_PUSH custom action (any case)
+    pub fn from_action(name: &str) -> Option<Self> {
+        if name.eq_ignore_ascii_case("GIT_PULL") {
+            Some(Self::Pull)
+        } else if name.eq_ignore_ascii_case("GIT_PUSH") {
+            Some(Self::Push)
+        } else {
+            None
+        }
+    }
+}
+
+/// Poll the repository status while a `git` widget is on the strip
+///
+/// Results are written to `state.git`, with a strip refresh on `redraw`
+/// when they change.
+pub fn spawn(
+    config: &GitConfig,
+    state: Arc<TokioRwLock<AppState>>,
+    redraw: mpsc::Sender<AppCommand>,
+) {
+    let mut interval = tokio::time::interval(config.interval());
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    tokio::spawn(async move {
+        loop {
+            interval.tick().await;
+            let repo = {
+                let state = state.read().await;
+                if !state.strip_widgets.contains(&StripWidget::Git) {
+                    continue;
+                }
+                state.git_dir()
+            };
+            let status = match repo {
+                Some(repo) => match status(&repo).await {
+                    Ok(status) => Some(status),
+                    Err(e) => {
+                        debug!("git status in {} failed: {}", repo.display(), e);
+                        None
+                    }
+                },
+                None => None,
+            };
+
+            let mut state = state.write().await;
+            if state.git != status {
+                state.git = status;
+                drop(state);
+                let _ = redraw.try_send(AppCommand::RefreshStrip);
+            }
+        }
+    });
+}
+
+/// Current status of the repository at `repo`
+pub async fn status(repo: &Path) -> Result<GitStatus> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(repo)
+        .args(["status", "--porcelain", "--branch"])
+        .output()
+        .await?;
+    if !output.status.success() {
+        bail!("{}", String::from_utf8_lossy(&output.stderr).trim());
+    }
+    let mut status = parse_status(&String::from_utf8_lossy(&output.stdout));
+    status.repo = repo.file_name().unwrap_or_default().to_string_lossy().to_string();
+    Ok(status)
+}
+
+/// Pull or push the repository in the background
+pub fn run(repo: PathBuf, command: GitCommand) {
+    let subcommand = match command {
+        GitCommand::Pull => "pull",
+        GitCommand::Push => "push",
+    };
+    tokio::spawn(async move {
+        info!("git {} in {}", subcommand, repo.display());
+        match Command::new("git").arg("-C").arg(&repo).arg(subcommand).output().await {
+            Ok(output) if !output.status.success() => {
+                let stderr = String::from_utf8_lossy(&output.stderr);
+                warn!("git {} exited with {}: {}", subcommand, output.status, stderr.trim());
+            }
+            Ok(_) => info!("git {} finished", subcommand),
+            Err(e) => warn!("Failed to run git: {}", e),
+        }
+    });
+}
+
+/// Parse `git status --porcelain --branch` output (repo name left empty)
+fn parse_status(output: &str) -> GitStatus {
+    let mut status = GitStatus::default();
+    for line in output.lines() {
+        let Some(header) = line.strip_prefix("## ") else {
+            if !line.trim().is_empty() {
+                status.dirty += 1;
+            }
+            continue;
+        };
+        // "main...origin/main [ahead 1, behind 2]", "No commits yet on main",
+        // "HEAD (no branch)"
+        let (branch, tracking) = header.split_once(" [").unwrap_or((header, ""));
+        let branch = branch.strip_prefix("No commits yet on ").unwrap_or(branch);
+        let branch = branch.split("...").next().unwrap_or(branch);
+        status.branch = branch.strip_suffix(" (no branch)").unwrap_or(branch).to_string();
+        for part in tracking.trim_end_matches(']').split(", ") {
+            if let Some(n) = part.strip_prefix("ahead ") {
+                status.ahead = n.parse().unwrap_or(0);
+            } else if let Some(n) = part.strip_prefix("behind ") {
+                status.behind = n.parse().unwrap_or(0);
+            }
+        }
+    }
+    status
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_status() {
+        let output = "## main...origin/main [ahead 1, behind 2]\n M src/lib.rs\n?? notes.txt\n";
+        let status = parse_status(output);
+        assert_eq!((status.branch.as_str(), status.dirty), ("main", 2));
+        assert_eq!((status.ahead, status.behind), (1, 2));
+        assert_eq!(status.summary(), "main ●2 ↑1 ↓2");
+
+        assert_eq!(parse_status("## feature\n").summary(), "feature");
+        assert_eq!(parse_status("## HEAD (no branch)\n").branch, "HEAD");
+        assert_eq!(parse_status("## No commits yet on main\n").branch, "main");
+        assert_eq!(parse_status("## main...origin/main [gone]\n").ahead, 0);
+    }
+
+    #[test]
+    fn test_command_from_action() {
+        assert_eq!(GitCommand::from_action("GIT_PULL"), Some(GitCommand::Pull));
+        assert_eq!(GitCommand::from_action("git_pull"), Some(GitCommand::Pull));
+        assert_eq!(GitCommand::from_action("Git_Pull"), Some(GitCommand::Pull));
+        assert_eq!(GitCommand::from_action("git_push"), Some(GitCommand::Push));
+        assert_eq!(GitCommand::from_action("GIT_STATUS"), None);
+    }
+}
@@ -0,0 +1,175 @@
+//! Home Assistant service calls and the `home_assistant` strip widget
+//!
+//! Actions call a service (`light.toggle`, `scene.turn_on`, ...) on one entity
+//! through the REST API, with the long-lived access token from
+//! `[home_assistant]`. The widget shows the state of `[home_assistant] sensor`,
+//! fetched every `interval_secs` while it's on the strip.
+
+use anyhow::{anyhow, bail, Result};
+use serde::Deserialize;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{mpsc, RwLock as TokioRwLock};
+use tracing::{debug, info, warn};
+
+use crate::config::{Config, HomeAssistantConfig, StripWidget};
+use crate::state::AppState;
+use crate::AppCommand;
+
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+/// State of the widget's sensor
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SensorState {
+    /// Friendly name, or the entity ID without one
+    pub name: String,
+    /// State with its unit, e.g. "21.5 °C"
+    pub value: String,
+}
+
+/// `GET /api/states/<entity_id>` response
+#[derive(Debug, Deserialize)]
+struct EntityState {
+    entity_id: String,
+    state: String,
+    #[serde(default)]
+    attributes: Attributes,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct Attributes {
+    friendly_name: Option<String>,
+    unit_of_measurement: Option<String>,
+}
+
+impl From<EntityState> for SensorState {
+    fn from(entity: EntityState) -> Self {
+        let value = match entity.attributes.unit_of_measurement {
+            Some(unit) if !unit.is_empty() => format!("{} {}", entity.state, unit),
+            _ => entity.state,
+        };
+        Self {
+            name: entity.attributes.friendly_name.unwrap_or(entity.entity_id),
+            value,
+        }
+    }
+}
+
+/// Domain and service name of a service call
+///
+/// `service` is either "domain.service" or just the service, in which case
+/// the domain is the entity's ("toggle" on "light.office" is `light.toggle`).
+fn service_call<'a>(entity_id: &'a str, service: &'a str) -> Option<(&'a str, &'a str)> {
+    let (domain, service) = match service.trim().split_once('.') {
+        Some((domain, service)) => (domain, service),
+        None => (entity_id.trim().split_once('.')?.0, service.trim()),
+    };
+    (!domain.is_empty() && !service.is_empty()).then_some((domain, service))
+}
+
+/// Call a service on an entity in the background, logging the outcome
+///
+/// `[home_assistant]` settings are read fresh so edits apply without a restart.
+pub fn run(entity_id: String, service: String) {
+    tokio::spawn(async move {
+        let config = Config::load().map(|c| c.home_assistant).unwrap_or_default();
+        match call_service(&config, &entity_id, &service).await {
+            Ok(()) => info!("Home Assistant: {} on {}", service, entity_id),
+            Err(e) => warn!("Home Assistant {} on {} failed: {}", service, entity_id, e),
+        }
+    });
+}
+
+async fn call_service(config: &HomeAssistantConfig, entity_id: &str, service: &str) -> Result<()> {
+    let (domain, name) = service_call(entity_id, service)
+        .ok_or_else(|| anyhow!("can't tell the service domain"))?;
+    let url = format!("{}/api/services/{}/{}", config.base_url()?, domain, name);
+    let response = reqwest::Client::new()
+        .post(&url)
+        .bearer_auth(&config.token)
+        .timeout(TIMEOUT)
+        .json(&serde_json::json!({ "entity_id": entity_id }))
+        .send()
+        .await?;
+    if !response.status().is_success() {
+        bail!("Home Assistant returned {}", response.status());
+    }
+    Ok(())
+}
+
+/// Current state of an entity
+async fn fetch_state(config: &HomeAssistantConfig, entity_id: &str) -> Result<SensorState> {
+    let url = format!("{}/api/states/{}", config.base_url()?, entity_id);
+    let response = reqwest::Client::new()
+        .get(&url)
+        .bearer_auth(&config.token)
+        .timeout(TIMEOUT)
+        .send()
+        .await?;
+    if !response.status().is_success() {
+        bail!("Home Assistant returned {}", response.status());
+    }
+    Ok(response.json::<EntityState>().await?.into())
+}
+
+/// Poll the sensor while a `home_assistant` widget is on the strip
+///
+/// Results are written to `state.home_assistant`, with a strip refresh on
+/// `redraw` when they change.
+pub fn spawn(
+    config: &HomeAssistantConfig,
+    state: Arc<TokioRwLock<AppState>>,
+    redraw: mpsc::Sender<AppCommand>,
+) {
+    if config.sensor.is_empty() {
+        return;
+    }
+    let config = config.clone();
+    let mut interval = tokio::time::interval(config.interval());
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    tokio::spawn(async move {
+        loop {
+            interval.tick().await;
+            let shown = state.read().await.strip_widgets.contains(&StripWidget::HomeAssistant);
+            if !shown {
+                continue;
+            }
+            let sensor = match fetch_state(&config, &config.sensor).await {
+                Ok(sensor) => Some(sensor),
+                Err(e) => {
+                    debug!("Home Assistant sensor {} failed: {}", config.sensor, e);
+                    None
+                }
+            };
+
+            let mut state = state.write().await;
+            if state.home_assistant != sensor {
+                state.home_assistant = sensor;
+                drop(state);
+                let _ = redraw.try_send(AppCommand::RefreshStrip);
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_service_call() {
+        assert_eq!(service_call("light.office", "toggle"), Some(("light", "toggle")));
+        assert_eq!(service_call("scene.focus", "scene.turn_on"), Some(("scene", "turn_on")));
+        assert_eq!(service_call("office", "toggle"), None);
+        assert_eq!(service_call("light.office", ""), None);
+
+        let entity: EntityState = serde_json::from_value(serde_json::json!({
+            "entity_id": "sensor.office_temperature",
+            "state": "21.5",
+            "attributes": { "friendly_name": "Office", "unit_of_measurement": "°C" }
+        }))
+        .unwrap();
+        let sensor = SensorState::from(entity);
+        assert_eq!((sensor.name.as_str(), sensor.value.as_str()), ("Office", "21.5 °C"));
+    }
+}
@@ -0,0 +1,6 @@
+//! Control of other apps on the local machine
+
+pub mod git;
+pub mod home_assistant;
+pub mod obs;
+pub mod websocket;
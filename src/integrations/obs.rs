@@ -0,0 +1,340 @@
+//! OBS Studio control over obs-websocket (v5, built into OBS 28+)
+//!
+//! Each command opens a short-lived connection: identify, send one request,
+//! wait for its response, close. OBS is local, so this keeps the deck free of
+//! reconnect bookkeeping for a server that is often not running.
+//!
+//! Of the protocol, only the handshake (Hello, Identify with the optional
+//! challenge/salt authentication, Identified) and a single Request and
+//! RequestResponse are used, with the JSON encoding and RPC version 1.
+//! Events, request batches, re-identifying and the MessagePack encoding
+//! aren't supported: the client subscribes to no events, and any other
+//! message from OBS, or a different RPC version, is an error.
+
+use anyhow::{anyhow, bail, Result};
+use base64::Engine;
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use std::time::Duration;
+use tracing::{debug, warn};
+
+use super::websocket::WebSocket;
+use crate::config::{Config, ObsConfig};
+
+const RPC_VERSION: u64 = 1;
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+/// A deck action for OBS, parsed from an action's `command` string
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ObsCommand {
+    /// `scene:<name>` - switch the program scene
+    Scene(String),
+    /// `start_recording`, `stop_recording`, `toggle_recording`
+    StartRecording,
+    StopRecording,
+    ToggleRecording,
+    /// `toggle_streaming`
+    ToggleStreaming,
+    /// `mute_mic` - toggle mute on `[obs] mic_input`
+    MuteMic,
+    /// `mute:<input>` - toggle mute on a named input
+    Mute(String),
+}
+
+impl ObsCommand {
+    pub fn parse(command: &str) -> Option<Self> {
+        let command = command.trim();
+        if let Some(scene) = command.strip_prefix("scene:") {
+            return Some(ObsCommand::Scene(scene.trim().to_string()));
+        }
+        if let Some(input) = command.strip_prefix("mute:") {
+            return Some(ObsCommand::Mute(input.trim().to_string()));
+        }
+        match command {
+            "start_recording" => Some(ObsCommand::StartRecording),
+            "stop_recording" => Some(ObsCommand::StopRecording),
+            "toggle_recording" => Some(ObsCommand::ToggleRecording),
+            "toggle_streaming" => Some(ObsCommand::ToggleStreaming),
+            "mute_mic" => Some(ObsCommand::MuteMic),
+            _ => None,
+        }
+    }
+
+    /// The obs-websocket request type and data for this command
+    fn request(&self, config: &ObsConfig) -> (&'static str, Value) {
+        match self {
+            ObsCommand::Scene(name) => ("SetCurrentProgramScene", json!({ "sceneName": name })),
+            ObsCommand::StartRecording => ("StartRecord", json!({})),
+            ObsCommand::StopRecording => ("StopRecord", json!({})),
+            ObsCommand::ToggleRecording => ("ToggleRecord", json!({})),
+            ObsCommand::ToggleStreaming => ("ToggleStream", json!({})),
+            ObsCommand::MuteMic => ("ToggleInputMute", json!({ "inputName": config.mic_input })),
+            ObsCommand::Mute(input) => ("ToggleInputMute", json!({ "inputName": input })),
+        }
+    }
+}
+
+/// Run an OBS action in the background, logging the outcome
+///
+/// `[obs]` settings are read fresh so edits apply without a restart.
+pub fn run(command: String) {
+    tokio::spawn(async move {
+        let Some(parsed) = ObsCommand::parse(&command) else {
+            warn!("Unknown OBS command '{}'", command);
+            return;
+        };
+        let config = Config::load().map(|c| c.obs).unwrap_or_default();
+        match tokio::time::timeout(TIMEOUT, send(&config, &parsed)).await {
+            Ok(Ok(())) => debug!("OBS confirmed {}", command),
+            Ok(Err(e)) => warn!("OBS command '{}' failed: {}", command, e),
+            Err(_) => warn!("OBS command '{}' timed out", command),
+        }
+    });
+}
+
+/// Connect, identify and run one command, waiting for OBS to confirm it
+pub async fn send(config: &ObsConfig, command: &ObsCommand) -> Result<()> {
+    let mut ws = WebSocket::connect(&config.host, config.port, Some("obswebsocket.json")).await?;
+
+    let hello: Value = serde_json::from_str(&ws.recv().await?)?;
+    if hello["op"] != 0 {
+        bail!("Expected Hello from OBS, got {}", hello);
+    }
+    let version = &hello["d"]["rpcVersion"];
+    if version.as_u64().is_some_and(|version| version < RPC_VERSION) {
+        bail!("OBS speaks RPC version {}, only {} is supported", version, RPC_VERSION);
+    }
+    let mut identify = json!({ "rpcVersion": RPC_VERSION, "eventSubscriptions": 0 });
+    if let Some(auth) = hello["d"].get("authentication") {
+        let password = config
+            .password
+            .as_deref()
+            .ok_or_else(|| anyhow!("OBS requires a password (set [obs] password)"))?;
+        let salt = auth["salt"].as_str().unwrap_or_default();
+        let challenge = auth["challenge"].as_str().unwrap_or_default();
+        identify["authentication"] = auth_response(password, salt, challenge).into();
+    }
+    let authenticating = identify.get("authentication").is_some();
+    ws.send(&json!({ "op": 1, "d": identify }).to_string()).await?;
+
+    let (request_type, request_data) = command.request(config);
+    let request_id = "claude-deck";
+    let mut identified = false;
+    loop {
+        let text = match ws.recv().await {
+            Ok(text) => text,
+            // OBS closes the connection rather than answer a wrong password
+            Err(e) if authenticating && !identified => {
+                bail!("OBS rejected the password in [obs] ({})", e)
+            }
+            Err(e) => return Err(e),
+        };
+        let message: Value = serde_json::from_str(&text)?;
+        match message["op"].as_u64() {
+            // Identified: send the request
+            Some(2) if !identified => {
+                let version = &message["d"]["negotiatedRpcVersion"];
+                if version != RPC_VERSION {
+                    bail!("OBS negotiated RPC version {}, only {} works", version, RPC_VERSION);
+                }
+                identified = true;
+                let request = json!({
+                    "op": 6,
+                    "d": {
+                        "requestType": request_type,
+                        "requestId": request_id,
+                        "requestData": request_data,
+                    }
+                });
+                ws.send(&request.to_string()).await?;
+            }
+            // RequestResponse
+            Some(7) if message["d"]["requestId"] == request_id => {
+                ws.close().await;
+                let status = &message["d"]["requestStatus"];
+                if status["result"] == true {
+                    return Ok(());
+                }
+                let comment = status["comment"].as_str().unwrap_or("no details");
+                bail!("{} failed (code {}): {}", request_type, status["code"], comment);
+            }
+            _ => bail!("Unexpected message from OBS: {}", message),
+        }
+    }
+}
+
+/// Authentication string for obs-websocket's challenge/salt handshake
+fn auth_response(password: &str, salt: &str, challenge: &str) -> String {
+    let b64 = base64::engine::general_purpose::STANDARD;
+    let secret = b64.encode(Sha256::digest(format!("{}{}", password, salt)));
+    b64.encode(Sha256::digest(format!("{}{}", secret, challenge)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::integrations::websocket;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::{TcpListener, TcpStream};
+
+    /// A fake OBS on a free local port, and settings pointing at it
+    async fn obs_server(password: Option<&str>) -> (TcpListener, ObsConfig) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let config = ObsConfig {
+            host: "127.0.0.1".to_string(),
+            port: listener.local_addr().unwrap().port(),
+            password: password.map(String::from),
+            ..Default::default()
+        };
+        (listener, config)
+    }
+
+    /// Accept a client, answer its upgrade and send Hello
+    async fn accept(listener: &TcpListener, auth: bool) -> WebSocket<TcpStream> {
+        let (mut stream, _) = listener.accept().await.unwrap();
+        let mut request = Vec::new();
+        while !request.ends_with(b"\r\n\r\n") {
+            assert_ne!(stream.read_buf(&mut request).await.unwrap(), 0);
+        }
+        let request = String::from_utf8_lossy(&request);
+        assert!(request.contains("Sec-WebSocket-Protocol: obswebsocket.json"));
+        let key = request
+            .lines()
+            .find_map(|line| line.strip_prefix("Sec-WebSocket-Key: "))
+            .unwrap();
+        let response = format!(
+            "HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\nConnection: Upgrade\r\n\
+             Sec-WebSocket-Accept: {}\r\n\r\n",
+            websocket::accept_key(key)
+        );
+        stream.write_all(response.as_bytes()).await.unwrap();
+
+        let mut ws = WebSocket::server(stream);
+        let mut hello = json!({ "op": 0, "d": { "rpcVersion": 1 } });
+        if auth {
+            hello["d"]["authentication"] = json!({ "challenge": "c", "salt": "s" });
+        }
+        ws.send(&hello.to_string()).await.unwrap();
+        ws
+    }
+
+    async fn recv_json(ws: &mut WebSocket<TcpStream>) -> Value {
+        serde_json::from_str(&ws.recv().await.unwrap()).unwrap()
+    }
+
+    /// Take the client's Identify, then answer its request with `status`
+    async fn respond(ws: &mut WebSocket<TcpStream>, status: Value) -> (Value, Value) {
+        let identify = recv_json(ws).await;
+        assert_eq!(identify["op"], 1);
+        assert_eq!(identify["d"]["eventSubscriptions"], 0);
+        let identified = json!({ "op": 2, "d": { "negotiatedRpcVersion": 1 } });
+        ws.send(&identified.to_string()).await.unwrap();
+
+        let request = recv_json(ws).await;
+        assert_eq!(request["op"], 6);
+        let response = json!({
+            "op": 7,
+            "d": {
+                "requestType": request["d"]["requestType"],
+                "requestId": request["d"]["requestId"],
+                "requestStatus": status,
+            }
+        });
+        ws.send(&response.to_string()).await.unwrap();
+        (identify["d"].clone(), request["d"].clone())
+    }
+
+    #[tokio::test]
+    async fn test_send() {
+        let (listener, config) = obs_server(Some("secret")).await;
+        let obs = async {
+            let mut ws = accept(&listener, true).await;
+            respond(&mut ws, json!({ "result": true, "code": 100 })).await
+        };
+        let command = ObsCommand::Scene("Coding".to_string());
+        let (result, (identify, request)) = tokio::join!(send(&config, &command), obs);
+        result.unwrap();
+        assert_eq!(identify["authentication"], auth_response("secret", "s", "c"));
+        assert_eq!(request["requestType"], "SetCurrentProgramScene");
+        assert_eq!(request["requestData"]["sceneName"], "Coding");
+    }
+
+    #[tokio::test]
+    async fn test_send_failures() {
+        let command = ObsCommand::ToggleRecording;
+
+        // A failed request reports OBS's reason
+        let (listener, mut config) = obs_server(None).await;
+        let obs = async {
+            let mut ws = accept(&listener, false).await;
+            let status = json!({ "result": false, "code": 501, "comment": "Not recording" });
+            respond(&mut ws, status).await
+        };
+        let (result, (identify, _)) = tokio::join!(send(&config, &command), obs);
+        let error = result.unwrap_err().to_string();
+        assert!(error.contains("code 501") && error.contains("Not recording"), "{}", error);
+        assert!(identify.get("authentication").is_none());
+
+        // OBS wants a password and none is set
+        let (result, _ws) = tokio::join!(send(&config, &command), accept(&listener, true));
+        assert!(result.unwrap_err().to_string().contains("requires a password"));
+
+        // OBS hangs up on a wrong password
+        config.password = Some("wrong".to_string());
+        let obs = async {
+            let mut ws = accept(&listener, true).await;
+            recv_json(&mut ws).await;
+        };
+        let (result, ()) = tokio::join!(send(&config, &command), obs);
+        assert!(result.unwrap_err().to_string().contains("rejected the password"));
+
+        // Events and other messages the client doesn't speak are errors
+        config.password = None;
+        let obs = async {
+            let mut ws = accept(&listener, false).await;
+            recv_json(&mut ws).await;
+            let event = json!({ "op": 5, "d": { "eventType": "ExitStarted" } });
+            ws.send(&event.to_string()).await.unwrap();
+            ws
+        };
+        let (result, _ws) = tokio::join!(send(&config, &command), obs);
+        assert!(result.unwrap_err().to_string().contains("Unexpected message"));
+
+        // As is an RPC version other than 1
+        let obs = async {
+            let mut ws = accept(&listener, false).await;
+            recv_json(&mut ws).await;
+            let identified = json!({ "op": 2, "d": { "negotiatedRpcVersion": 2 } });
+            ws.send(&identified.to_string()).await.unwrap();
+            ws
+        };
+        let (result, _ws) = tokio::join!(send(&config, &command), obs);
+        assert!(result.unwrap_err().to_string().contains("RPC version 2"));
+    }
+
+    #[test]
+    fn test_parse_commands() {
+        assert_eq!(ObsCommand::parse("scene: Coding"), Some(ObsCommand::Scene("Coding".into())));
+        assert_eq!(ObsCommand::parse("toggle_recording"), Some(ObsCommand::ToggleRecording));
+        let mute = ObsCommand::parse("mute:Desktop Audio");
+        assert_eq!(mute, Some(ObsCommand::Mute("Desktop Audio".into())));
+        assert_eq!(ObsCommand::parse("record"), None);
+
+        let (request, data) = ObsCommand::MuteMic.request(&ObsConfig::default());
+        assert_eq!(request, "ToggleInputMute");
+        assert_eq!(data["inputName"], "Mic/Aux");
+    }
+
+    #[test]
+    fn test_auth_response() {
+        // Worked example from the obs-websocket protocol docs
+        assert_eq!(
+            auth_response(
+                "supersecretpassword",
+                "lM1GncleQOaCu9lT1yeUZhFYnqhsLLP1G5lAGo3ixaI=",
+                "+IxH4CnCiqpX1rM9scsNynZzbOe4KhDeYcTNS3PDaeY="
+            ),
+            "1Ct943GAT+6YQUUX47Ia/ncufilbe6+oD6lY+5kaCu4="
+        );
+    }
+}
@@ -0,0 +1,281 @@
+//! Minimal WebSocket (RFC 6455) for local integrations and the web API
+//!
+//! Text messages only, no extensions; enough to talk JSON to a service on
+//! the local network, or to stream events to a local client once the web
+//! server has upgraded its connection. There is no TLS (`wss://`), and
+//! binary or fragmented messages and frames using extension bits are errors
+//! rather than being skipped.
+
+use anyhow::{anyhow, bail, Result};
+use base64::Engine;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::net::TcpStream;
+
+const OP_TEXT: u8 = 0x1;
+const OP_BINARY: u8 = 0x2;
+const OP_CLOSE: u8 = 0x8;
+const OP_PING: u8 = 0x9;
+const OP_PONG: u8 = 0xA;
+
+/// Appended to the client's key to prove the server speaks WebSocket
+const ACCEPT_GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+
+/// An open WebSocket connection
+pub struct WebSocket<S = TcpStream> {
+    stream: S,
+    buf: Vec<u8>,
+    /// Clients mask their frames, servers don't
+    client: bool,
+}
+
+impl WebSocket {
+    /// Connect to `ws://host:port/` and complete the opening handshake
+    pub async fn connect(host: &str, port: u16, protocol: Option<&str>) -> Result<Self> {
+        if let Some((scheme, _)) = host.split_once("://") {
+            bail!("{}:// isn't supported, only plain ws (set the host to a name)", scheme);
+        }
+        let mut stream = TcpStream::connect((host, port)).await?;
+        let key = base64::engine::general_purpose::STANDARD.encode(nonce());
+        let mut request = format!(
+            "GET / HTTP/1.1\r\nHost: {}:{}\r\nUpgrade: websocket\r\nConnection: Upgrade\r\n\
+             Sec-WebSocket-Key: {}\r\nSec-WebSocket-Version: 13\r\n",
+            host, port, key
+        );
+        if let Some(protocol) = protocol {
+            request.push_str(&format!("Sec-WebSocket-Protocol: {}\r\n", protocol));
+        }
+        request.push_str("\r\n");
+        stream.write_all(request.as_bytes()).await?;
+
+        // Read the response headers; anything after them is the first frame
+        let mut buf = Vec::new();
+        let header_end = loop {
+            if let Some(end) = buf.windows(4).position(|w| w == b"\r\n\r\n") {
+                break end + 4;
+            }
+            if buf.len() > 8192 || stream.read_buf(&mut buf).await? == 0 {
+                bail!("Server closed the connection during the handshake");
+            }
+        };
+        let headers = String::from_utf8_lossy(&buf[..header_end]);
+        let status = headers.lines().next().unwrap_or_default();
+        if status.split_whitespace().nth(1) != Some("101") {
+            bail!("WebSocket handshake refused: {}", status.trim());
+        }
+        buf.drain(..header_end);
+        Ok(Self {
+            stream,
+            buf,
+            client: true,
+        })
+    }
+}
+
+impl<S: AsyncRead + AsyncWrite + Unpin> WebSocket<S> {
+    /// Serve a connection whose handshake was already answered with `accept_key`
+    pub fn server(stream: S) -> Self {
+        Self {
+            stream,
+            buf: Vec::new(),
+            client: false,
+        }
+    }
+
+    /// Send a text message
+    pub async fn send(&mut self, text: &str) -> Result<()> {
+        self.write_frame(OP_TEXT, text.as_bytes()).await
+    }
+
+    async fn write_frame(&mut self, opcode: u8, payload: &[u8]) -> Result<()> {
+        let frame = encode_frame(opcode, payload, self.client);
+        self.stream.write_all(&frame).await?;
+        self.stream.flush().await?;
+        Ok(())
+    }
+
+    /// Receive the next text message, answering pings along the way
+    pub async fn recv(&mut self) -> Result<String> {
+        loop {
+            if let Some((opcode, payload, used)) = decode_frame(&self.buf)? {
+                self.buf.drain(..used);
+                match opcode {
+                    OP_TEXT => return Ok(String::from_utf8_lossy(&payload).into_owned()),
+                    OP_PING => self.write_frame(OP_PONG, &payload).await?,
+                    OP_CLOSE => bail!("Connection closed by the other end"),
+                    OP_BINARY => bail!("Binary WebSocket messages aren't supported"),
+                    _ => {}
+                }
+                continue;
+            }
+            if self.stream.read_buf(&mut self.buf).await? == 0 {
+                bail!("Connection closed by the other end");
+            }
+        }
+    }
+
+    /// Send a close frame (the other end's reply isn't waited for)
+    pub async fn close(mut self) {
+        self.write_frame(OP_CLOSE, &[]).await.ok();
+    }
+}
+
+/// `Sec-WebSocket-Accept` answering a client's `Sec-WebSocket-Key`
+pub fn accept_key(key: &str) -> String {
+    let digest = sha1(format!("{}{}", key.trim(), ACCEPT_GUID).as_bytes());
+    base64::engine::general_purpose::STANDARD.encode(digest)
+}
+
+/// SHA-1, which the handshake needs and nothing else here
+fn sha1(data: &[u8]) -> [u8; 20] {
+    let mut h: [u32; 5] = [0x67452301, 0xEFCDAB89, 0x98BADCFE, 0x10325476, 0xC3D2E1F0];
+    let mut message = data.to_vec();
+    message.push(0x80);
+    while message.len() % 64 != 56 {
+        message.push(0);
+    }
+    message.extend_from_slice(&((data.len() as u64) * 8).to_be_bytes());
+
+    for block in message.chunks(64) {
+        let mut w = [0u32; 80];
+        for (i, word) in block.chunks(4).enumerate() {
+            w[i] = u32::from_be_bytes([word[0], word[1], word[2], word[3]]);
+        }
+        for i in 16..80 {
+            w[i] = (w[i - 3] ^ w[i - 8] ^ w[i - 14] ^ w[i - 16]).rotate_left(1);
+        }
+        let [mut a, mut b, mut c, mut d, mut e] = h;
+        for (i, word) in w.iter().enumerate() {
+            let (f, k) = match i {
+                0..=19 => ((b & c) | (!b & d), 0x5A827999),
+                20..=39 => (b ^ c ^ d, 0x6ED9EBA1),
+                40..=59 => ((b & c) | (b & d) | (c & d), 0x8F1BBCDC),
+                _ => (b ^ c ^ d, 0xCA62C1D6),
+            };
+            let temp = a
+                .rotate_left(5)
+                .wrapping_add(f)
+                .wrapping_add(e)
+                .wrapping_add(k)
+                .wrapping_add(*word);
+            (e, d, c, b, a) = (d, c, b.rotate_left(30), a, temp);
+        }
+        for (state, value) in h.iter_mut().zip([a, b, c, d, e]) {
+            *state = state.wrapping_add(value);
+        }
+    }
+
+    let mut digest = [0u8; 20];
+    for (chunk, word) in digest.chunks_mut(4).zip(h) {
+        chunk.copy_from_slice(&word.to_be_bytes());
+    }
+    digest
+}
+
+/// Bytes that only need to differ between connections (handshake key, masks)
+fn nonce() -> [u8; 16] {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos())
+        .unwrap_or_default();
+    (nanos ^ ((std::process::id() as u128) << 64)).to_le_bytes()
+}
+
+/// A final frame, masked if sent by a client
+fn encode_frame(opcode: u8, payload: &[u8], masked: bool) -> Vec<u8> {
+    let mask_bit = if masked { 0x80 } else { 0 };
+    let mut out = vec![0x80 | opcode];
+    match payload.len() {
+        len @ 0..=125 => out.push(mask_bit | len as u8),
+        len @ 126..=0xFFFF => {
+            out.push(mask_bit | 126);
+            out.extend_from_slice(&(len as u16).to_be_bytes());
+        }
+        len => {
+            out.push(mask_bit | 127);
+            out.extend_from_slice(&(len as u64).to_be_bytes());
+        }
+    }
+    if !masked {
+        out.extend_from_slice(payload);
+        return out;
+    }
+    let mask: [u8; 4] = nonce()[..4].try_into().unwrap_or_default();
+    out.extend_from_slice(&mask);
+    out.extend(payload.iter().enumerate().map(|(i, b)| b ^ mask[i % 4]));
+    out
+}
+
+/// Decode the first frame in `buf` as (opcode, payload, bytes used)
+///
+/// Returns None until the whole frame has arrived. Fragmented messages
+/// and extensions (such as compression) aren't supported.
+fn decode_frame(buf: &[u8]) -> Result<Option<(u8, Vec<u8>, usize)>> {
+    if buf.len() < 2 {
+        return Ok(None);
+    }
+    if buf[0] & 0x80 == 0 {
+        return Err(anyhow!("Fragmented WebSocket messages aren't supported"));
+    }
+    if buf[0] & 0x70 != 0 {
+        return Err(anyhow!("WebSocket extensions aren't supported"));
+    }
+    let opcode = buf[0] & 0x0F;
+    let masked = buf[1] & 0x80 != 0;
+    let (len, mut start) = match buf[1] & 0x7F {
+        126 if buf.len() >= 4 => (u16::from_be_bytes([buf[2], buf[3]]) as usize, 4),
+        127 if buf.len() >= 10 => {
+            let len = u64::from_be_bytes(buf[2..10].try_into().unwrap_or_default());
+            (len as usize, 10)
+        }
+        126 | 127 => return Ok(None),
+        len => (len as usize, 2),
+    };
+    let mask = if masked {
+        let Some(mask) = buf.get(start..start + 4) else {
+            return Ok(None);
+        };
+        start += 4;
+        Some([mask[0], mask[1], mask[2], mask[3]])
+    } else {
+        None
+    };
+    let Some(payload) = buf.get(start..start + len) else {
+        return Ok(None);
+    };
+    let payload = match mask {
+        Some(mask) => payload.iter().enumerate().map(|(i, b)| b ^ mask[i % 4]).collect(),
+        None => payload.to_vec(),
+    };
+    Ok(Some((opcode, payload, start + len)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_frame_round_trip() {
+        let text = "x".repeat(300);
+        let frame = encode_frame(OP_TEXT, text.as_bytes(), true);
+        assert_eq!(&frame[..4], &[0x81, 0x80 | 126, 0x01, 0x2C]);
+
+        assert_eq!(decode_frame(&frame[..20]).unwrap(), None);
+        let (opcode, payload, used) = decode_frame(&frame).unwrap().unwrap();
+        assert_eq!((opcode, used), (OP_TEXT, frame.len()));
+        assert_eq!(payload, text.as_bytes());
+
+        // Server frames are unmasked
+        let (opcode, payload, _) = decode_frame(&[0x89, 0x02, b'h', b'i']).unwrap().unwrap();
+        assert_eq!((opcode, payload.as_slice()), (OP_PING, &b"hi"[..]));
+        assert_eq!(encode_frame(OP_PONG, b"hi", false), [0x8A, 0x02, b'h', b'i']);
+
+        // Only final, plain frames are understood
+        assert!(decode_frame(&[0x01, 0x02, b'h', b'i']).is_err());
+        let compressed = decode_frame(&[0xC1, 0x02, b'h', b'i']).unwrap_err();
+        assert!(compressed.to_string().contains("extensions"));
+
+        // The example handshake from RFC 6455
+        assert_eq!(accept_key("dGhlIHNhbXBsZSBub25jZQ=="), "s3pPLMBiTxaQ9kYGzzhZRbK+xOo=");
+    }
+}
@@ -1,80 +1,159 @@
+pub mod agent;
 pub mod config;
+pub mod control;
+pub mod decks;
 pub mod device;
 pub mod display;
+pub mod doctor;
 pub mod hooks;
 pub mod input;
+pub mod integrations;
+pub mod logging;
+mod main_loop;
+pub mod midi;
+pub mod models;
+pub mod monitors;
+pub mod mqtt;
+pub mod notifications;
+pub mod plugins;
 pub mod profiles;
+pub mod projects;
+pub mod scenes;
+pub mod sound;
 pub mod state;
 pub mod system;
+pub mod templating;
+pub mod timer;
 pub mod web;
 
 use anyhow::Result;
 use std::sync::{Arc, RwLock as StdRwLock};
-use tokio::sync::{mpsc, RwLock as TokioRwLock};
+use tokio::sync::{broadcast, mpsc, oneshot, RwLock as TokioRwLock};
 use tracing::{debug, error, info, warn};
 
-use config::Config;
-use device::{button_to_display_key, DeviceManager};
+use agent::KeystrokeQueue;
+use config::{Config, KeystrokeConfig};
+use decks::Deck;
+use device::{
+    button_to_display_key, DeckDevice, DeviceManager, FrameBuffer, InputEvent, SavedFrames,
+    VirtualDevice,
+};
 use display::DisplayRenderer;
-use input::InputHandler;
+use input::{InputHandler, KeystrokeSender};
+use profiles::overlay::overlay_button;
+use profiles::store::{ActionConfig, MacroStep};
 use profiles::ProfileManager;
-use state::AppState;
+use midi::MidiOutput;
+use mqtt::MqttPublisher;
+use sound::SoundEvent;
+use state::{AppState, ButtonStats, DeviceHealth, PersistedState, PinOutcome, SceneRequest};
+use web::events::DeckEvent;
+use web::{ConnectedDevice, DeviceReport};
 
 /// Command to refresh the display
 #[derive(Debug)]
 pub enum AppCommand {
     /// Redraw all buttons (e.g., after config change)
     RedrawButtons,
+    /// Press a button as if on the device (REST API)
+    Press { button: u8, long_press: bool },
+    /// Run an action without a button (REST API)
+    RunAction(ActionConfig),
+    /// Re-render the LCD strip (e.g., a plugin widget changed)
+    RefreshStrip,
+    /// Set the deck's brightness (REST API)
+    SetBrightness(u8),
+    /// Play the startup animation (REST API)
+    PlayIntro,
+    /// Clear the device and redraw everything (REST API)
+    ResetDevice,
+    /// Describe the main deck (REST API)
+    DeviceInfo(oneshot::Sender<DeviceReport>),
+    /// Type a key or text step of a macro running in the background
+    MacroStep(MacroStep, KeystrokeConfig),
 }
 
-/// Main application struct
-pub struct App {
-    #[allow(dead_code)]
+/// Main application struct, generic over the main deck's backend
+pub struct App<D: DeckDevice = DeviceManager> {
     config: Config,
     state: Arc<TokioRwLock<AppState>>,
-    device: Option<DeviceManager>,
-    display: DisplayRenderer,
+    device: Option<D>,
+    /// Shared with the blocking threads that pre-render a profile's buttons
+    display: Arc<DisplayRenderer>,
     input: InputHandler,
-    #[allow(dead_code)]
     profile_manager: Arc<StdRwLock<ProfileManager>>,
     /// Channel to receive commands (e.g., refresh from web UI)
     command_rx: mpsc::Receiver<AppCommand>,
+    /// Commands from the app's own background tasks (macro keystrokes)
+    queued_rx: mpsc::Receiver<AppCommand>,
+    /// Status updates pushed by hooks over the Unix socket (None if unavailable)
+    hook_rx: Option<mpsc::Receiver<hooks::ClaudeStatus>>,
+    /// Running on battery with low-power mode enabled
+    low_power: bool,
+    /// Mirror of the deck display (shared with the web server)
+    frames: Arc<FrameBuffer>,
+    /// Extra devices bound to a fixed profile (`[[decks]]`)
+    decks: Vec<Deck>,
+    /// Publishes button presses and state changes (`[mqtt]`)
+    mqtt: Option<MqttPublisher>,
+    /// Sends presses and knob turns to a virtual MIDI port (`[midi]`)
+    midi: Option<MidiOutput>,
+    /// Live events for web clients (`/api/events`)
+    events: Option<broadcast::Sender<DeckEvent>>,
+    /// Background reconnect while the main deck is disconnected
+    reconnect: Option<tokio::task::JoinHandle<D>>,
+    /// Fallback poll of the hooks' status file
+    status_reader: hooks::StatusReader,
 }
 
 impl App {
     /// Create the shared app state (call before web server + App so both can share it)
     pub fn create_state(config: &Config) -> Arc<TokioRwLock<AppState>> {
-        Arc::new(TokioRwLock::new(AppState::with_config(
-            config.models.available.clone(),
+        let mut state = AppState::with_config(
+            models::available(&config.models),
             &config.models.default,
             config.new_session.terminal.clone(),
             config.device.brightness,
-        )))
+        );
+        state.model_labels = config.models.labels.clone();
+        state.strip_widgets = config.strip.widgets();
+        state.permissions = config.permissions.clone();
+        state.focus = config.focus.clone();
+        state.timer = timer::Timer::new(config.timer.duration());
+        state.timer.flash = config.timer.flash;
+        state.deck_lock = state::DeckLock::new(&config.security.pin);
+        state.monitors = monitors::statuses(&config.monitors);
+        state.git_repo = config.git.repo_path();
+        Arc::new(TokioRwLock::new(state))
     }
 
     /// Create a new application instance with an existing shared state
+    ///
+    /// With a `keystroke_queue` (system daemon mode), keystrokes are queued for
+    /// the per-user agent instead of being injected directly. With a
+    /// `simulator` (`--simulate`), the virtual device is used instead of hardware.
+    /// Everything shown on the deck is mirrored into `frames`.
     pub async fn new(
         config: Config,
         profile_manager: Arc<StdRwLock<ProfileManager>>,
         command_rx: mpsc::Receiver<AppCommand>,
         state: Arc<TokioRwLock<AppState>>,
+        keystroke_queue: Option<Arc<KeystrokeQueue>>,
+        simulator: Option<Arc<VirtualDevice>>,
+        frames: Arc<FrameBuffer>,
     ) -> Result<Self> {
-
-        // Try to connect to device
-        let brightness = state.read().await.brightness;
-        let device = match DeviceManager::connect().await {
+        // Try to connect to device, leaving devices claimed by `[[decks]]` alone
+        let connection = match simulator {
+            Some(sim) => Ok(DeviceManager::simulated(sim, Arc::clone(&frames))),
+            None => {
+                let claimed = claimed_serials(&config);
+                let unclaimed = |s: &str| !claimed.iter().any(|c| c == s);
+                DeviceManager::connect_matching(unclaimed, Arc::clone(&frames)).await
+            }
+        };
+        let device = match connection {
             Ok(d) => {
                 info!("Connected to device");
-
-                // Wake up device with keep-alive and brightness
-                if let Err(e) = d.keep_alive().await {
-                    warn!("Keep-alive failed: {}", e);
-                }
-                if let Err(e) = d.set_brightness(brightness).await {
-                    warn!("Set brightness failed: {}", e);
-                }
-
-                state.write().await.connected = true;
                 Some(d)
             }
             Err(e) => {
@@ -82,6 +161,53 @@ impl App {
                 None
             }
         };
+        Self::for_device(
+            config,
+            profile_manager,
+            command_rx,
+            state,
+            keystroke_queue,
+            device,
+            frames,
+        )
+        .await
+    }
+}
+
+impl<D: DeckDevice> App<D> {
+    /// Create an application instance on an already connected main deck
+    ///
+    /// `App::new` ends up here with the hardware or simulator; tests pass a
+    /// `MockDevice`. `[[decks]]` are ignored when the deck is simulated.
+    pub async fn for_device(
+        config: Config,
+        profile_manager: Arc<StdRwLock<ProfileManager>>,
+        command_rx: mpsc::Receiver<AppCommand>,
+        state: Arc<TokioRwLock<AppState>>,
+        keystroke_queue: Option<Arc<KeystrokeQueue>>,
+        device: Option<D>,
+        frames: Arc<FrameBuffer>,
+    ) -> Result<Self> {
+        device::set_frame_quality(config.device.animation_quality);
+        // HID code overrides apply to every device, including `[[decks]]`
+        device::set_input_map(&config.device.input_map);
+
+        let (brightness, state_dir) = {
+            let state = state.read().await;
+            (state.brightness, state.state_dir())
+        };
+        let simulated = device.as_ref().is_some_and(D::is_simulated);
+        if let Some(ref d) = device {
+            // Wake up device with keep-alive and brightness
+            if let Err(e) = d.keep_alive().await {
+                warn!("Keep-alive failed: {}", e);
+            }
+            if let Err(e) = d.set_brightness(brightness).await {
+                warn!("Set brightness failed: {}", e);
+            }
+
+            state.write().await.set_device_health(DeviceHealth::Connected);
+        }
 
         // Initialize volume from system
         if let Some(vol) = system::get_system_volume().await {
@@ -89,8 +215,48 @@ impl App {
             info!("System volume initialized: {}%", vol);
         }
 
-        let display = DisplayRenderer::new(&config, Arc::clone(&profile_manager))?;
-        let input = InputHandler::new(state.clone(), Arc::clone(&profile_manager));
+        let display = Arc::new(DisplayRenderer::new(&config, Arc::clone(&profile_manager))?);
+        if let Ok(mut animator) = display::gif_animator().lock() {
+            animator.set_budget(config.device.gif_cache_mb * 1024 * 1024);
+        }
+        // Push-style hook transport; the status file poll remains as fallback
+        let hook_rx = match hooks::spawn_listener(&state_dir) {
+            Ok(rx) => Some(rx),
+            Err(e) => {
+                warn!("Hook socket unavailable, using status file polling only: {}", e);
+                None
+            }
+        };
+
+        let new_sender = || match keystroke_queue {
+            Some(ref queue) => KeystrokeSender::remote(config.keystrokes, Arc::clone(queue)),
+            None => KeystrokeSender::with_timing(config.keystrokes)
+                .with_target(config.target.clone()),
+        };
+
+        if simulated && !config.decks.is_empty() {
+            warn!("Ignoring [[decks]] in simulator mode");
+        }
+        let (queued_tx, queued_rx) = mpsc::channel(64);
+        let mut decks = Vec::new();
+        for deck in config.decks.iter().filter(|_| !simulated) {
+            let shared = profile_manager.read().unwrap().clone();
+            let deck = Deck::new(
+                deck.clone(),
+                &config,
+                &shared,
+                state.clone(),
+                new_sender(),
+                queued_tx.clone(),
+            )?;
+            decks.push(deck);
+        }
+        let input = InputHandler::new(
+            state.clone(),
+            Arc::clone(&profile_manager),
+            new_sender(),
+            queued_tx,
+        );
 
         Ok(Self {
             config,
@@ -100,11 +266,134 @@ impl App {
             input,
             profile_manager,
             command_rx,
+            queued_rx,
+            hook_rx,
+            low_power: false,
+            frames,
+            decks,
+            mqtt: None,
+            midi: None,
+            events: None,
+            reconnect: None,
+            status_reader: hooks::StatusReader::default(),
+        })
+    }
+
+    /// Keep trying to connect the main deck, backing off after each failure
+    fn spawn_reconnect(&self) -> tokio::task::JoinHandle<D> {
+        let claimed = claimed_serials(&self.config);
+        let frames = Arc::clone(&self.frames);
+        let state = Arc::clone(&self.state);
+        tokio::spawn(async move {
+            let mut attempts = 0;
+            loop {
+                let delay = state.read().await.device_health.retry_delay();
+                tokio::time::sleep(delay).await;
+                match D::connect(claimed.clone(), Arc::clone(&frames)).await {
+                    Ok(device) => return device,
+                    Err(e) => {
+                        attempts += 1;
+                        debug!("Reconnect attempt {} failed: {}", attempts, e);
+                        let health = DeviceHealth::after_attempts(attempts);
+                        state.write().await.set_device_health(health);
+                    }
+                }
+            }
         })
     }
 
+    /// Publish button presses and state changes through the MQTT bridge
+    pub fn with_mqtt(mut self, publisher: MqttPublisher) -> Self {
+        self.mqtt = Some(publisher);
+        self
+    }
+
+    /// Send presses and knob turns to a virtual MIDI port
+    pub fn with_midi(mut self, output: MidiOutput) -> Self {
+        self.midi = Some(output);
+        self
+    }
+
+    /// Stream device and hook events to web clients (`/api/events`)
+    pub fn with_events(mut self, events: broadcast::Sender<DeckEvent>) -> Self {
+        self.events = Some(events);
+        self
+    }
+
+    /// Send an event to web clients (dropped if none are listening)
+    fn emit(&self, event: DeckEvent) {
+        if let Some(ref events) = self.events {
+            let _ = events.send(event);
+        }
+    }
+
+    /// Publish the current state summary over MQTT, if enabled
+    async fn publish_state(&self) {
+        if let Some(ref mqtt) = self.mqtt {
+            let state = self.state.read().await;
+            mqtt.state(&state, &self.current_profile_name(&state));
+        }
+    }
+
+    /// Name of the profile shown for the focused app (empty if none matches)
+    fn current_profile_name(&self, state: &AppState) -> String {
+        let manager = self.profile_manager.read().unwrap();
+        manager.profile_name_for_app(&state.focused_window()).unwrap_or_default()
+    }
+
+    /// Show saved frames from a snapshot
+    ///
+    /// The renderer's sent-image cache is left alone, so the restored look stays
+    /// until the live display actually changes.
+    async fn restore_frames(&self, saved: SavedFrames) {
+        let Some(ref device) = self.device else {
+            return;
+        };
+        info!("Restoring deck snapshot");
+
+        let buttons = saved
+            .buttons
+            .into_iter()
+            .map(|(button, image)| (button_to_display_key(button), image))
+            .collect();
+        if let Err(e) = device.set_images_batch(buttons, saved.strip).await {
+            warn!("Failed to restore snapshot: {}", e);
+        }
+
+        if let Some(brightness) = saved.brightness {
+            self.state.write().await.brightness = brightness;
+            self.apply_brightness(brightness).await;
+        }
+    }
+
+    /// Save button press stats if any presses were recorded since the last save
+    async fn save_stats(&self) {
+        let stats = {
+            let mut state = self.state.write().await;
+            let pending = std::mem::take(&mut state.stats_pending);
+            pending.then(|| (state.stats.clone(), state.state_dir()))
+        };
+        if let Some((stats, dir)) = stats {
+            if let Err(e) = stats.save(&dir) {
+                warn!("Failed to save button stats: {}", e);
+            }
+        }
+    }
+
     /// Run the main application loop
     pub async fn run(&mut self) -> Result<()> {
+        // Restore latched toggles and the active scene from the last run
+        {
+            let mut state = self.state.write().await;
+            let dir = state.state_dir();
+            let saved = PersistedState::load(&dir);
+            saved.apply_toggles(&mut state);
+            if let Some(scene) = saved.active_scene {
+                state.pending_scene = Some(SceneRequest::Activate(scene));
+            }
+            state.stats = ButtonStats::load(&dir);
+        }
+
         // Initialize display with default button images
         self.render_initial_display().await?;
         self.run_main_loop().await
@@ -120,15 +409,18 @@ impl App {
         // Reset device to accept new images, then wake up
         info!("Resetting device for new session...");
         device.reset().await.ok();
+        self.display.invalidate();
         tokio::time::sleep(tokio::time::Duration::from_millis(200)).await;
 
         let brightness = self.state.read().await.brightness;
         info!("Waking up device with brightness {}%...", brightness);
-        device.set_brightness(brightness).await.ok();
+        self.apply_brightness(brightness).await;
         tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
 
-        // Play startup animation
-        self.play_startup_animation().await?;
+        // Play startup animation (nobody is watching a headless deck boot)
+        if !self.state.read().await.headless {
+            self.play_startup_animation().await?;
+        }
 
         // Get state for rendering
         let state = self.state.read().await;
@@ -137,6 +429,7 @@ impl App {
         for button_id in 0..10u8 {
             let display_key = button_to_display_key(button_id);
             let image = self.display.render_button(button_id, false, &state)?;
+            self.display.button_changed(button_id, &image);
             device.set_button_image(display_key, image).await?;
         }
 
@@ -147,6 +440,7 @@ impl App {
 
         // Render full LCD strip (800x128 continuous display)
         let strip_image = self.display.render_strip(&state)?;
+        self.display.strip_changed(&strip_image);
         device.set_strip_image(strip_image).await?;
         drop(state);
 
@@ -157,6 +451,49 @@ impl App {
         Ok(())
     }
 
+    /// Clear the device and redraw everything, as on connect but without the intro
+    async fn reset_device(&self) -> Result<()> {
+        let Some(device) = self.device.as_ref() else {
+            return Ok(());
+        };
+        device.reset().await?;
+        self.display.invalidate();
+        tokio::time::sleep(tokio::time::Duration::from_millis(200)).await;
+
+        let brightness = self.state.read().await.brightness;
+        self.apply_brightness(brightness).await;
+        self.redraw_all_buttons().await?;
+        self.update_display().await
+    }
+
+    /// The main deck's connection and model, for `GET /api/device/info`
+    async fn device_report(&self) -> DeviceReport {
+        let state = self.state.read().await;
+        let reconnect_attempts = match state.device_health {
+            DeviceHealth::Connected => 0,
+            DeviceHealth::Reconnecting { attempt } => attempt,
+            DeviceHealth::Failed { attempts } => attempts,
+        };
+        let device = self.device.as_ref().map(|device| {
+            let model = device.model();
+            ConnectedDevice {
+                model: model.name.to_string(),
+                serial_number: device.serial().map(str::to_string),
+                simulated: device.is_simulated(),
+                rows: model.rows,
+                cols: model.cols,
+                encoders: model.encoder_count,
+                strip_size: model.strip_size,
+            }
+        });
+        DeviceReport {
+            connected: device.is_some(),
+            reconnect_attempts,
+            brightness: state.brightness,
+            device,
+        }
+    }
+
     /// Play a startup animation on the device
     async fn play_startup_animation(&self) -> Result<()> {
         let device = match self.device.as_ref() {
@@ -221,356 +558,272 @@ impl App {
             tokio::time::sleep(tokio::time::Duration::from_millis(30)).await;
         }
 
+        self.display.invalidate();
         info!("Startup animation complete");
         Ok(())
     }
 
-    /// Run the main loop - handle device events and inject keystrokes
-    async fn run_main_loop(&mut self) -> Result<()> {
-        info!("Running - keystrokes will be sent to focused window");
-
-        let mut last_keepalive = std::time::Instant::now();
-        let keepalive_interval = std::time::Duration::from_secs(10);
-
-        let mut last_status_check = std::time::Instant::now();
-        let status_check_interval = std::time::Duration::from_millis(200);
-
-        let mut last_app_check = std::time::Instant::now();
-        let app_check_interval = std::time::Duration::from_millis(500);
-        let mut pending_app_check: Option<tokio::task::JoinHandle<Option<String>>> = None;
+    /// Activate, toggle, or deactivate a scene
+    async fn handle_scene_request(&self, request: SceneRequest) {
+        let active = self.state.read().await.active_scene.clone();
+        let name = match request {
+            SceneRequest::Deactivate => None,
+            SceneRequest::Activate(name) => Some(name),
+            SceneRequest::Toggle(name) => {
+                let is_active = active
+                    .as_deref()
+                    .map(|a| a.eq_ignore_ascii_case(&name))
+                    .unwrap_or(false);
+                if is_active {
+                    None
+                } else {
+                    Some(name)
+                }
+            }
+        };
 
-        let mut last_lock_check = std::time::Instant::now();
-        let lock_check_interval = std::time::Duration::from_secs(2); // Check every 2 seconds (security, not latency-critical)
+        let (all_scenes, slack, hue) = scenes::load_scene_config(&self.config);
+        let scene = match name {
+            Some(ref name) => match scenes::find_scene(&all_scenes, name) {
+                Some(scene) => Some(scene.clone()),
+                None => {
+                    warn!("Scene '{}' not found", name);
+                    return;
+                }
+            },
+            None => None,
+        };
 
-        let mut last_volume_check = std::time::Instant::now();
-        let volume_check_interval = std::time::Duration::from_secs(2); // Sync external volume changes
-        let mut pending_volume_check: Option<tokio::task::JoinHandle<Option<u8>>> = None;
+        // Profile pin and strip layout
+        {
+            let mut manager = self.profile_manager.write().unwrap();
+            manager.pin_profile(scene.as_ref().and_then(|s| s.profile.clone()));
+        }
+        let brightness = {
+            let mut state = self.state.write().await;
+            state.active_scene = scene.as_ref().map(|s| s.name.clone());
+            state.persist_pending = true;
+            state.strip_layout = scene
+                .as_ref()
+                .and_then(|s| s.strip_layout.clone())
+                .unwrap_or_else(|| "default".to_string());
+            state.brightness = scene
+                .as_ref()
+                .and_then(|s| s.brightness)
+                .unwrap_or(self.config.device.brightness)
+                .min(100);
+            state.brightness
+        };
+        self.apply_brightness(brightness).await;
 
-        let mut last_gif_tick = std::time::Instant::now();
-        let gif_tick_interval = std::time::Duration::from_millis(16); // 60 FPS tick rate
+        match scene {
+            Some(scene) => {
+                info!("Scene '{}' activated", scene.name);
+                tokio::spawn(scenes::apply_integrations(scene, slack, hue));
+            }
+            None => info!("Scene deactivated"),
+        }
 
-        let mut last_waiting_flash = std::time::Instant::now();
-        let waiting_flash_interval = std::time::Duration::from_millis(500); // Pulse every 500ms
+        if let Err(e) = self.redraw_all_buttons().await {
+            warn!("Failed to redraw buttons after scene change: {}", e);
+        }
+        if let Err(e) = self.update_display().await {
+            debug!("Failed to update display after scene change: {}", e);
+        }
+    }
 
-        // Track last device write to enforce cooldown (HID device needs time between operations)
-        let mut last_device_write = std::time::Instant::now();
-        let device_cooldown = std::time::Duration::from_millis(20); // Min gap between device operations
+    /// Idle brightness level (never brighter than the active level)
+    fn idle_brightness(&self, active: u8) -> u8 {
+        self.config.device.idle_brightness.min(active)
+    }
 
-        // Track volume/brightness overlay state to refresh display when they expire
-        let mut volume_overlay_was_active = false;
-        let mut brightness_overlay_was_active = false;
+    /// Brightness actually sent to the device (reduced in low-power mode)
+    fn power_brightness(&self, level: u8) -> u8 {
+        if self.low_power {
+            let reduced = level.saturating_sub(self.config.power.battery_brightness_reduction);
+            // Keep a lit display visible rather than turning it off
+            reduced.max(level.min(5))
+        } else {
+            level
+        }
+    }
 
-        loop {
-            // Check for commands from web UI (non-blocking)
-            while let Ok(cmd) = self.command_rx.try_recv() {
-                match cmd {
-                    AppCommand::RedrawButtons => {
-                        info!("Received redraw command from web UI");
-                        // Small delay to let any pending device operations complete
-                        tokio::time::sleep(tokio::time::Duration::from_millis(50)).await;
-                        if let Err(e) = self.redraw_all_buttons().await {
-                            warn!("Failed to redraw buttons from web UI: {}", e);
-                        }
-                        last_device_write = std::time::Instant::now();
-                    }
-                }
+    /// Switch to the `brightness` of the profile now shown
+    ///
+    /// Returns the new level if it changed. The level from before the first
+    /// profile with its own brightness comes back once one without is shown.
+    fn follow_profile_brightness(&self, state: &mut AppState) -> Option<u8> {
+        let profile_level = {
+            let manager = self.profile_manager.read().unwrap();
+            manager.find_profile_for_app(&state.focused_window()).and_then(|p| p.brightness)
+        };
+        let level = match profile_level {
+            Some(level) => {
+                state.saved_brightness.get_or_insert(state.brightness);
+                level.min(100)
             }
-            // Handle device events
-            let event = if let Some(ref mut device) = self.device {
-                // Send periodic keep-alive to prevent device timeout
-                if last_keepalive.elapsed() >= keepalive_interval {
-                    if let Err(e) = device.keep_alive().await {
-                        warn!("Keep-alive failed: {}", e);
-                    }
-                    last_keepalive = std::time::Instant::now();
-                }
-
-                match device.poll_event().await {
-                    Ok(event) => event,
-                    Err(e) => {
-                        // Check if device disconnected
-                        let error_str = format!("{}", e);
-                        if error_str.contains("disconnected") || error_str.contains("Disconnected")
-                        {
-                            warn!("Device disconnected, will try to reconnect...");
-                            self.device = None;
-                            self.state.write().await.connected = false;
-                        }
-                        None
-                    }
-                }
-            } else {
-                None
-            };
-
-            if let Some(event) = event {
-                // Skip input handling when screen is locked (security)
-                let is_locked = self.state.read().await.screen_locked;
-                if !is_locked {
-                    if let Err(e) = self.input.handle_event(event).await {
-                        warn!("Failed to handle input event: {}", e);
-                    }
-                    if let Err(e) = self.update_display().await {
-                        debug!("Failed to update display: {}", e);
-                    }
-                    last_device_write = std::time::Instant::now();
-                } else {
-                    // Silently ignore input when locked
-                    continue;
-                }
+            None => state.saved_brightness.take()?,
+        };
+        if state.brightness == level {
+            return None;
+        }
+        info!("Profile brightness: {}%", level);
+        state.brightness = level;
+        Some(level)
+    }
 
-                // Check if brightness was changed
-                let brightness_changed = {
-                    let mut state = self.state.write().await;
-                    let changed = state.brightness_changed;
-                    state.brightness_changed = false;
-                    if changed {
-                        Some(state.brightness)
-                    } else {
-                        None
-                    }
-                };
-                if let Some(brightness) = brightness_changed {
-                    if let Some(ref device) = self.device {
-                        device.set_brightness(brightness).await.ok();
-                    }
-                }
+    /// Set device brightness, applying the low-power reduction
+    async fn apply_brightness(&self, level: u8) {
+        let level = self.power_brightness(level);
+        if let Some(ref device) = self.device {
+            device.set_brightness(level).await.ok();
+        }
+        for deck in &self.decks {
+            deck.set_brightness(level).await;
+        }
+    }
 
-                // Check if volume was changed
-                let volume_changed = {
-                    let mut state = self.state.write().await;
-                    let changed = state.volume_changed;
-                    state.volume_changed = false;
-                    if changed {
-                        Some(state.volume)
-                    } else {
-                        None
-                    }
-                };
-                if let Some(volume) = volume_changed {
-                    tokio::spawn(async move {
-                        system::set_system_volume(volume).await;
-                    });
-                }
+    /// Gradually ramp device brightness between two levels
+    async fn ramp_brightness(&self, from: u8, to: u8) {
+        if self.device.is_none() {
+            return;
+        }
 
-                // Check if intro animation was requested
-                let play_intro = {
-                    let mut state = self.state.write().await;
-                    let flag = state.play_intro;
-                    state.play_intro = false;
-                    flag
-                };
-                if play_intro {
-                    self.play_startup_animation().await.ok();
-                    if let Err(e) = self.redraw_all_buttons().await {
-                        warn!("Failed to redraw buttons after intro: {}", e);
-                    }
-                    last_device_write = std::time::Instant::now();
-                }
-            } else if self.device.is_none() {
-                // Try to reconnect periodically
-                tokio::time::sleep(tokio::time::Duration::from_secs(5)).await;
-                if let Ok(d) = DeviceManager::connect().await {
-                    info!("Reconnected to device");
-                    self.device = Some(d);
-                    self.state.write().await.connected = true;
-                    if let Err(e) = self.render_initial_display().await {
-                        warn!("Failed to render initial display on reconnect: {}", e);
-                    }
-                    last_device_write = std::time::Instant::now();
-                }
-            }
+        const STEPS: i32 = 8;
+        for step in 1..=STEPS {
+            let level = from as i32 + (to as i32 - from as i32) * step / STEPS;
+            self.apply_brightness(level as u8).await;
+            tokio::time::sleep(tokio::time::Duration::from_millis(25)).await;
+        }
+    }
 
-            // Check for pending long-press actions (hold-to-activate)
-            match self.input.check_long_press().await {
-                Ok(true) => {
-                    if let Err(e) = self.update_display().await {
-                        debug!("Failed to update display after long-press: {}", e);
-                    }
-                    last_device_write = std::time::Instant::now();
-                }
-                Err(e) => warn!("Failed to check long-press: {}", e),
-                _ => {}
-            }
-
-            // Poll Claude Code status file periodically
-            if last_status_check.elapsed() >= status_check_interval {
-                last_status_check = std::time::Instant::now();
-                match self.update_from_claude_status().await {
-                    Ok(true) => {
-                        if let Err(e) = self.update_display().await {
-                            debug!("Failed to update display after status change: {}", e);
-                        }
-                        last_device_write = std::time::Instant::now();
-                    }
-                    Err(e) => debug!("Failed to update from Claude status: {}", e),
-                    _ => {}
-                }
+    /// Update display based on state changes
+    async fn update_display(&self) -> Result<()> {
+        let state = self.state.read().await;
+        for deck in &self.decks {
+            if let Err(e) = deck.update_strip(&state).await {
+                debug!("Failed to update strip on deck '{}': {}", deck.id(), e);
             }
+        }
 
-            // Poll focused app in background (osascript is slow ~144ms)
-            // Check if previous background task completed
-            if let Some(handle) = pending_app_check.take() {
-                if handle.is_finished() {
-                    if let Ok(Some(app)) = handle.await {
-                        let mut state = self.state.write().await;
-                        if state.focused_app != app {
-                            info!("Focused app changed: '{}' -> '{}'", state.focused_app, app);
-                            state.focused_app = app;
-                            drop(state); // Release lock before redraw
-                            if let Err(e) = self.redraw_all_buttons().await {
-                                warn!("Failed to redraw buttons on app change: {}", e);
-                            }
-                            last_device_write = std::time::Instant::now();
-                        }
-                    }
-                } else {
-                    // Not finished yet, put it back
-                    pending_app_check = Some(handle);
-                }
-            }
+        let device = match self.device.as_ref() {
+            Some(d) => d,
+            None => return Ok(()),
+        };
 
-            // Spawn new background check if interval elapsed and no pending check
-            if pending_app_check.is_none() && last_app_check.elapsed() >= app_check_interval {
-                last_app_check = std::time::Instant::now();
-                pending_app_check = Some(tokio::spawn(async {
-                    system::get_focused_app().await
-                }));
+        // Update full LCD strip (800x128 continuous display), skipping the
+        // JPEG encode and HID transfer when nothing on it changed (or the
+        // model has no strip)
+        let strip_image = self.display.render_strip(&state)?;
+        let strip = (device.model().has_strip() && self.display.strip_changed(&strip_image))
+            .then_some(strip_image);
+
+        // Update all MIC buttons (shows red when recording), and flash any
+        // button whose action just ran in its pressed color
+        let now = std::time::Instant::now();
+        let mut redraw = self.find_custom_buttons(&state, "MIC");
+        for button_id in state.flashed_buttons(now) {
+            if !redraw.contains(&button_id) {
+                redraw.push(button_id);
             }
-
-            // Check if screen is locked (for security - disable input when locked)
-            if last_lock_check.elapsed() >= lock_check_interval {
-                last_lock_check = std::time::Instant::now();
-                let is_locked = system::is_screen_locked().await;
-                let was_locked = self.state.read().await.screen_locked;
-                if is_locked != was_locked {
-                    self.state.write().await.screen_locked = is_locked;
-                    if is_locked {
-                        info!("Screen locked - input disabled");
-                    } else {
-                        info!("Screen unlocked - input enabled");
-                    }
-                    // Update ALL buttons and strip to show locked/unlocked state
-                    if let Err(e) = self.redraw_all_buttons().await {
-                        warn!("Failed to redraw buttons for lock state: {}", e);
-                    }
-                    if let Err(e) = self.update_display().await {
-                        warn!("Failed to update strip for lock state: {}", e);
-                    }
-                    last_device_write = std::time::Instant::now();
-                }
+        }
+        let mut buttons = Vec::new();
+        for button_id in redraw {
+            let active = state.is_button_flashed(button_id, now);
+            let image = self.display.render_button(button_id, active, &state)?;
+            if self.display.button_changed(button_id, &image) {
+                buttons.push((button_to_display_key(button_id), image));
             }
+        }
 
-            // Poll system volume in background to detect external changes
-            if let Some(handle) = pending_volume_check.take() {
-                if handle.is_finished() {
-                    if let Ok(Some(system_vol)) = handle.await {
-                        let mut state = self.state.write().await;
-                        // Only sync if not currently being adjusted via encoder
-                        if !state.is_volume_display_active() && state.volume != system_vol {
-                            debug!("System volume changed externally: {}% -> {}%", state.volume, system_vol);
-                            state.set_volume_from_system(system_vol);
-                        }
-                    }
-                } else {
-                    pending_volume_check = Some(handle);
-                }
-            }
+        if buttons.is_empty() && strip.is_none() {
+            return Ok(());
+        }
+        if let Err(e) = device.set_images_batch(buttons, strip).await {
+            self.display.invalidate();
+            return Err(e);
+        }
+        Ok(())
+    }
 
-            if pending_volume_check.is_none() && last_volume_check.elapsed() >= volume_check_interval {
-                last_volume_check = std::time::Instant::now();
-                pending_volume_check = Some(tokio::spawn(async {
-                    system::get_system_volume().await
-                }));
-            }
+    /// Push the two-frame pressed-in animation for a button
+    async fn play_press_animation(&self, button_id: u8) {
+        let device = match self.device.as_ref() {
+            Some(d) => d,
+            None => return,
+        };
 
-            // Flash the LCD strip when waiting for user input
-            if last_waiting_flash.elapsed() >= waiting_flash_interval {
-                last_waiting_flash = std::time::Instant::now();
-                let mut state = self.state.write().await;
-                if state.waiting_for_input {
-                    state.waiting_flash_on = !state.waiting_flash_on;
-                    drop(state);
-                    if let Err(e) = self.update_display().await {
-                        debug!("Failed to update display for waiting flash: {}", e);
-                    }
-                    last_device_write = std::time::Instant::now();
-                } else if state.waiting_flash_on {
-                    // Reset flash state when no longer waiting
-                    state.waiting_flash_on = false;
+        let frames = {
+            let state = self.state.read().await;
+            match self.display.render_press_frames(button_id, &state) {
+                Ok(frames) => frames,
+                Err(e) => {
+                    debug!("Failed to render press animation: {}", e);
+                    return;
                 }
             }
+        };
 
-            // Check if volume overlay just expired (transition active→inactive)
-            {
-                let volume_overlay_active = self.state.read().await.is_volume_display_active();
-                if volume_overlay_was_active && !volume_overlay_active {
-                    // Overlay just expired, refresh display to restore STATUS quadrant
-                    if let Err(e) = self.update_display().await {
-                        debug!("Failed to update display after volume overlay expired: {}", e);
-                    }
-                    last_device_write = std::time::Instant::now();
-                }
-                volume_overlay_was_active = volume_overlay_active;
-            }
+        // The device now shows something the sent-image cache doesn't know about
+        self.display.invalidate_button(button_id);
+        let display_key = button_to_display_key(button_id);
+        let [first, second] = frames;
+        if device.set_button_image(display_key, first).await.is_ok() {
+            device.flush().await.ok();
+            tokio::time::sleep(tokio::time::Duration::from_millis(30)).await;
+        }
+        if device.set_button_image(display_key, second).await.is_ok() {
+            device.flush().await.ok();
+        }
+    }
 
-            // Check if brightness overlay just expired (transition active→inactive)
-            {
-                let brightness_overlay_active = self.state.read().await.is_brightness_display_active();
-                if brightness_overlay_was_active && !brightness_overlay_active {
-                    // Overlay just expired, refresh display to restore DETAIL quadrant
-                    if let Err(e) = self.update_display().await {
-                        debug!("Failed to update display after brightness overlay expired: {}", e);
-                    }
-                    last_device_write = std::time::Instant::now();
+    /// Key in a PIN digit while the deck is locked
+    async fn enter_pin_digit(&self, button: u8) {
+        let outcome = self.state.write().await.deck_lock.press(button);
+        match outcome {
+            Some(PinOutcome::Unlocked) => {
+                info!("Deck unlocked");
+                if let Err(e) = self.redraw_all_buttons().await {
+                    warn!("Failed to redraw buttons after unlock: {}", e);
                 }
-                brightness_overlay_was_active = brightness_overlay_active;
             }
-
-            // Update GIF animations (respect device cooldown to avoid HID conflicts)
-            if last_gif_tick.elapsed() >= gif_tick_interval
-                && last_device_write.elapsed() >= device_cooldown
-            {
-                last_gif_tick = std::time::Instant::now();
-                if let Err(e) = self.update_gif_animations().await {
-                    debug!("GIF animation update skipped (device busy): {}", e);
-                } else {
-                    last_device_write = std::time::Instant::now();
-                }
+            Some(PinOutcome::Wrong) => {
+                warn!("Wrong deck PIN entered");
+                sound::play(&self.config.sound, SoundEvent::Error);
             }
-
-            tokio::time::sleep(tokio::time::Duration::from_millis(1)).await;
+            Some(PinOutcome::Entered) => sound::play(&self.config.sound, SoundEvent::Press),
+            None => {}
+        }
+        // The strip shows how many digits are in
+        if let Err(e) = self.update_display().await {
+            debug!("Failed to update display for PIN entry: {}", e);
         }
     }
 
-    /// Update display based on state changes
-    async fn update_display(&self) -> Result<()> {
+    /// Redraw a single button if its image changed
+    async fn redraw_button(&self, button_id: u8) -> Result<()> {
         let device = match self.device.as_ref() {
             Some(d) => d,
             None => return Ok(()),
         };
 
         let state = self.state.read().await;
-
-        // Update full LCD strip (800x128 continuous display)
-        let strip_image = self.display.render_strip(&state)?;
-        device.set_strip_image(strip_image).await?;
-
-        // Update all MIC buttons (shows red when recording, flashes on long-press)
-        for mic_button_id in self.find_mic_buttons(&state) {
-            let display_key = button_to_display_key(mic_button_id);
-            let mic_active = state.is_button_flashed(mic_button_id);
-            let mic_button = self.display.render_button(mic_button_id, mic_active, &state)?;
-            device.set_button_image(display_key, mic_button).await?;
+        let active = state.is_button_flashed(button_id, std::time::Instant::now());
+        let image = self.display.render_button(button_id, active, &state)?;
+        if self.display.button_changed(button_id, &image) {
+            if let Err(e) = device.set_button_image(button_to_display_key(button_id), image).await {
+                self.display.invalidate_button(button_id);
+                return Err(e);
+            }
+            device.flush().await?;
         }
-
-        device.flush().await?;
-
         Ok(())
     }
 
     /// Redraw all buttons (called when app profile changes)
     async fn redraw_all_buttons(&self) -> Result<()> {
+        self.redraw_decks().await;
+
         let device = match self.device.as_ref() {
             Some(d) => d,
             None => return Ok(()),
@@ -585,16 +838,10 @@ impl App {
             }
         }
 
-        let state = self.state.read().await;
-
-        // Render all buttons with current profile
-        for button_id in 0..10u8 {
-            let display_key = button_to_display_key(button_id);
-            let image = self.display.render_button(button_id, false, &state)?;
-            device.set_button_image(display_key, image).await?;
-        }
-
-        device.flush().await?;
+        // Render the whole layout before sending any of it, so keys don't pop
+        // in one at a time
+        let images = self.prerender_buttons().await?;
+        self.send_button_images(device, images).await?;
 
         // Spawn background tasks to load any pending GIFs (non-blocking)
         self.start_gif_background_loading();
@@ -602,6 +849,64 @@ impl App {
         Ok(())
     }
 
+    /// Render all buttons with the current profile, sending only those that changed
+    async fn send_changed_buttons(&self, device: &D) -> Result<()> {
+        let images = {
+            let state = self.state.read().await;
+            let now = std::time::Instant::now();
+            let mut images = Vec::new();
+            for button_id in 0..10u8 {
+                let active = state.is_button_flashed(button_id, now);
+                images.push((button_id, self.display.render_button(button_id, active, &state)?));
+            }
+            images
+        };
+        self.send_button_images(device, images).await
+    }
+
+    /// Render all 10 buttons in parallel on blocking threads
+    ///
+    /// A GIF button's first frame may have to be fetched, which would
+    /// otherwise hold up the main loop between keys.
+    async fn prerender_buttons(&self) -> Result<Vec<(u8, image::RgbImage)>> {
+        use rayon::prelude::*;
+
+        let display = Arc::clone(&self.display);
+        let state = self.state.read().await.clone();
+        tokio::task::spawn_blocking(move || {
+            let now = std::time::Instant::now();
+            (0..10u8)
+                .into_par_iter()
+                .map(|button_id| {
+                    let active = state.is_button_flashed(button_id, now);
+                    Ok((button_id, display.render_button(button_id, active, &state)?))
+                })
+                .collect()
+        })
+        .await
+        .map_err(|e| anyhow::anyhow!("Button renderer panicked: {}", e))?
+    }
+
+    /// Send the rendered buttons that changed as one batch, flushed once
+    async fn send_button_images(
+        &self,
+        device: &D,
+        images: Vec<(u8, image::RgbImage)>,
+    ) -> Result<()> {
+        let buttons: Vec<_> = images
+            .into_iter()
+            .filter(|(button_id, image)| self.display.button_changed(*button_id, image))
+            .map(|(button_id, image)| (button_to_display_key(button_id), image))
+            .collect();
+        if !buttons.is_empty() {
+            if let Err(e) = device.set_images_batch(buttons, None).await {
+                self.display.invalidate();
+                return Err(e);
+            }
+        }
+        Ok(())
+    }
+
     /// Start background loading for any GIFs that need to be fetched
     fn start_gif_background_loading(&self) {
         let animator = display::gif_animator();
@@ -646,80 +951,203 @@ impl App {
         }
     }
 
-    /// Find all button IDs that have a MIC action configured in the current profile
-    fn find_mic_buttons(&self, state: &state::AppState) -> Vec<u8> {
-        use profiles::ButtonAction;
+    /// Whether any profile has a clipboard history button
+    fn has_clipboard_buttons(&self) -> bool {
+        use profiles::store::ActionConfig;
 
         let manager = self.profile_manager.read().unwrap();
-        let mut mic_buttons = Vec::new();
-        if let Some(profile) = manager.find_profile_for_app(&state.focused_app) {
+        manager.get_profiles().iter().any(|profile| {
+            profile.buttons.iter().any(|button| match button.action {
+                ActionConfig::Clipboard { .. } => true,
+                ActionConfig::Text { ref value, .. } => value.contains("{clipboard}"),
+                ActionConfig::Snippet { ref template } => template.contains("{clipboard}"),
+                _ => button.label.contains("{clipboard}"),
+            })
+        })
+    }
+
+    /// Buttons in the current layout whose labels are templates
+    fn templated_buttons(&self, state: &AppState) -> Vec<u8> {
+        if let Some(folder) = state.folders.last() {
+            return folder
+                .buttons
+                .iter()
+                .filter(|(_, config)| templating::has_placeholders(config.label))
+                .map(|(id, _)| *id)
+                .collect();
+        }
+        let manager = self.profile_manager.read().unwrap();
+        manager
+            .find_profile_for_app(&state.focused_window())
+            .map(|profile| {
+                profile
+                    .buttons
+                    .iter()
+                    .filter(|button| templating::has_placeholders(&button.label))
+                    .map(|button| button.position)
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Redraw templated labels (only changed images reach the device),
+    /// returning false if there are none
+    async fn redraw_templated_buttons(&self) -> bool {
+        let buttons = self.templated_buttons(&*self.state.read().await);
+        for &button_id in &buttons {
+            if let Err(e) = self.redraw_button(button_id).await {
+                debug!("Failed to redraw templated button {}: {}", button_id, e);
+            }
+        }
+        !buttons.is_empty()
+    }
+
+    /// Find all button IDs that have the custom action `name` (e.g. "MIC")
+    /// configured in the current profile
+    fn find_custom_buttons(&self, state: &state::AppState, name: &str) -> Vec<u8> {
+        let manager = self.profile_manager.read().unwrap();
+        let mut buttons = Vec::new();
+        if let Some(profile) = manager.find_profile_for_app(&state.focused_window()) {
             for button in &profile.buttons {
-                let config = button.to_button_config();
-                if matches!(&config.action, ButtonAction::Custom(action) if *action == "MIC") {
-                    mic_buttons.push(button.position);
+                if button.action.is_custom(name) {
+                    buttons.push(button.position);
                 }
             }
         }
-        mic_buttons
+        buttons
     }
 
     /// Update state from Claude Code status file
     /// Returns true if state was updated
-    async fn update_from_claude_status(&self) -> Result<bool> {
-        if let Some(status) = hooks::read_status().await? {
+    async fn update_from_claude_status(&mut self) -> Result<bool> {
+        let dir = self.state.read().await.state_dir();
+        if let Some(status) = self.status_reader.read(&dir).await? {
+            return Ok(self.apply_claude_status(status).await);
+        }
+
+        // Even if no status file, check Claude settings for model changes
+        if let Some(model) = Self::read_claude_settings_model().await {
             let mut state = self.state.write().await;
+            if !state.model_selecting && state.model != model {
+                state.set_model(&model);
+                return Ok(true);
+            }
+        }
 
-            let mut changed = false;
+        Ok(false)
+    }
 
-            // Update task name
-            if !status.task.is_empty() && state.task_name != status.task {
-                state.task_name = status.task;
-                changed = true;
+    /// Apply a status update from the hooks (file poll or socket push)
+    /// Returns true if state was updated
+    async fn apply_claude_status(&self, status: hooks::ClaudeStatus) -> bool {
+        self.emit(DeckEvent::Status(Box::new(status.clone())));
+        let detail = status.detail();
+        let mut state = self.state.write().await;
+
+        // Permission requests from every session are queued
+        let queue_changed = match state.approvals.record(&status) {
+            Some(entry) => {
+                state::approvals::append_log(&state.state_dir(), &entry);
+                true
             }
+            None => false,
+        };
+
+        // Sub-agents are tracked for every session, like the queue
+        let queue_changed = state.agents.record(&status) || queue_changed;
+
+        // Only the picked session drives the display; the others just update
+        // the picker list
+        if !state.sessions.record(&status) {
+            return queue_changed || state.sessions.picker().is_some();
+        }
+        if status.cwd.is_some() {
+            state.cwd = status.cwd.clone();
+        }
+
+        let task = if status.task.is_empty() { &state.task_name } else { &status.task };
+        let cue = SoundEvent::from_status(
+            &state.task_name,
+            task,
+            state.waiting_for_input,
+            status.waiting_for_input,
+        );
+        if let Some(event) = cue {
+            sound::play(&self.config.sound, event);
+            let turn = state.turn_started_at.map(|started| started.elapsed());
+            notifications::notify(&self.config.notifications, event, detail.as_deref(), turn);
+        }
+
+        let mut changed = false;
 
-            // Update tool detail
-            if state.tool_detail != status.tool_detail {
-                state.tool_detail = status.tool_detail;
+        // Errors and rate limits take over the strip until the session moves on
+        match state::Banner::from_status(&status) {
+            Some(banner) if !state.banner.as_ref().is_some_and(|b| b.same_update(&banner)) => {
+                state.banner = Some(banner);
                 changed = true;
             }
-
-            // Update waiting for input
-            if state.waiting_for_input != status.waiting_for_input {
-                state.waiting_for_input = status.waiting_for_input;
-                // Convert string input_type to InputType enum
-                state.input_type =
-                    status
-                        .input_type
-                        .and_then(|s| match s.to_lowercase().as_str() {
-                            "permission" => Some(state::InputType::Permission),
-                            "yesno" | "yes_no" => Some(state::InputType::YesNo),
-                            "continue" => Some(state::InputType::Continue),
-                            _ => None,
-                        });
+            Some(_) => {}
+            None if !status.task.is_empty() && state.banner.is_some() => {
+                state.banner = None;
                 changed = true;
             }
+            None => {}
+        }
 
-            // Update model if provided (but not while user is selecting)
-            if let Some(model) = status.model {
-                if !state.model_selecting && state.model != model {
-                    state.set_model(&model);
-                    changed = true;
-                }
+        // Update task name
+        if !status.task.is_empty() && state.task_name != status.task {
+            state.task_name = status.task;
+            if state.task_name != "READY" {
+                state.task_started = true;
+                state.turn_started_at.get_or_insert_with(std::time::Instant::now);
+            } else {
+                state.turn_started_at = None;
             }
+            changed = true;
+        }
 
-            return Ok(changed);
+        // Update tool detail
+        if state.tool_detail != detail {
+            state.tool_detail = detail;
+            changed = true;
         }
 
-        // Even if no status file, check Claude settings for model changes
-        if let Some(model) = Self::read_claude_settings_model().await {
-            let mut state = self.state.write().await;
+        // Update waiting for input
+        if state.waiting_for_input != status.waiting_for_input {
+            state.waiting_for_input = status.waiting_for_input;
+            // Convert string input_type to InputType enum
+            state.input_type =
+                status
+                    .input_type
+                    .and_then(|s| match s.to_lowercase().as_str() {
+                        "permission" => Some(state::InputType::Permission),
+                        "yesno" | "yes_no" => Some(state::InputType::YesNo),
+                        "continue" => Some(state::InputType::Continue),
+                        _ => None,
+                    });
+            changed = true;
+        }
+
+        // Update token usage / cost / context meter
+        if state.tokens != status.tokens
+            || state.cost_usd != status.cost_usd
+            || state.context_pct != status.context_pct
+        {
+            state.tokens = status.tokens;
+            state.cost_usd = status.cost_usd;
+            state.context_pct = status.context_pct;
+            changed = true;
+        }
+
+        // Update model if provided (but not while user is selecting)
+        if let Some(model) = status.model {
             if !state.model_selecting && state.model != model {
                 state.set_model(&model);
-                return Ok(true);
+                changed = true;
             }
         }
 
-        Ok(false)
+        changed || queue_changed
     }
 
     /// Read model directly from Claude Code settings.json
@@ -728,8 +1156,7 @@ impl App {
         let settings_path = std::path::PathBuf::from(home).join(".claude/settings.json");
 
         let content = tokio::fs::read_to_string(&settings_path).await.ok()?;
-        let json: serde_json::Value = serde_json::from_str(&content).ok()?;
-        json.get("model")?.as_str().map(|s| s.to_string())
+        models::settings_model(&content)
     }
 
     /// Update GIF animations and redraw changed buttons
@@ -754,29 +1181,114 @@ impl App {
             return Ok(());
         }
 
-        // Update all dirty buttons
+        // Update all dirty buttons (except those an overlay covers), leaving
+        // locked buttons alone
         let state = self.state.read().await;
+        if state.screen_locked || state.deck_lock.is_locked() {
+            return Ok(());
+        }
         for result in tick_results {
+            if overlay_button(&state, result.button_id).is_some() {
+                continue;
+            }
             let display_key = button_to_display_key(result.button_id);
             let image = self
                 .display
                 .render_button_with_gif_frame(result.button_id, &state, &result.frame)?;
-            device.set_button_image(display_key, image).await?;
+            self.display.invalidate_button(result.button_id);
+            device.set_button_frame(display_key, image).await?;
         }
         device.flush().await?;
 
         Ok(())
     }
 
+    /// Redraw the extra decks after profiles or shared state changed
+    async fn redraw_decks(&self) {
+        if self.decks.is_empty() {
+            return;
+        }
+        let shared = self.profile_manager.read().unwrap().clone();
+        let state = self.state.read().await;
+        for deck in &self.decks {
+            deck.sync_profiles(&shared);
+            if let Err(e) = deck.redraw(&state).await {
+                debug!("Failed to redraw deck '{}': {}", deck.id(), e);
+            }
+        }
+    }
+
+    /// Connect and redraw the extra decks, handling `input` from one of them
+    ///
+    /// Returns true if a deck received input, so idle dimming can reset.
+    async fn service_decks(&mut self, input: Option<(usize, Result<InputEvent>)>) -> bool {
+        let brightness = self.power_brightness(self.state.read().await.brightness);
+        let locked = {
+            let state = self.state.read().await;
+            state.screen_locked || state.deck_lock.is_locked()
+        };
+        let mut had_input = false;
+
+        let mut input = input;
+        for (index, deck) in self.decks.iter_mut().enumerate() {
+            let mut redraw = deck.ensure_connected(brightness).await;
+            deck.keep_alive().await;
+            let event = match input.take_if(|(i, _)| *i == index) {
+                Some((_, Ok(event))) => Some(event),
+                Some((_, Err(_))) => {
+                    deck.disconnected();
+                    None
+                }
+                None => None,
+            };
+            if let Some(event) = event {
+                // Input is ignored while the screen or deck is locked, as on the main deck
+                if !locked {
+                    if matches!(event, InputEvent::ButtonDown(_)) {
+                        sound::play(&self.config.sound, SoundEvent::Press);
+                    }
+                    deck.handle_event(event).await;
+                    had_input = true;
+                    redraw = true;
+                }
+            }
+            redraw |= deck.check_long_press().await;
+
+            if redraw {
+                let state = self.state.read().await;
+                if let Err(e) = deck.redraw(&state).await {
+                    debug!("Failed to redraw deck '{}': {}", deck.id(), e);
+                }
+            }
+        }
+        had_input
+    }
+
     /// Gracefully shutdown the application
     pub async fn shutdown(&mut self) {
         info!("Shutting down claude-deck...");
 
-        // Drop the device to release HID connection
+        self.save_stats().await;
+
+        // Drop the devices to release their HID connections
         if let Some(device) = self.device.take() {
             device.disconnect().await;
         }
+        for deck in &mut self.decks {
+            deck.disconnect().await;
+        }
+
+        // Remove the hook socket so hooks fall back to the status file
+        if self.hook_rx.is_some() {
+            let dir = self.state.read().await.state_dir();
+            let _ = std::fs::remove_file(hooks::socket_path(&dir));
+        }
 
         info!("Shutdown complete");
     }
 }
+
+/// Serial numbers reserved for `[[decks]]`, which the main deck must not take
+fn claimed_serials(config: &Config) -> Vec<String> {
+    config.decks.iter().map(|deck| deck.serial.clone()).collect()
+}
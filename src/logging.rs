@@ -0,0 +1,251 @@
+//! Logging: stderr, a rotating JSON log file, and a level that can change at runtime
+//!
+//! Every event also goes to `~/Library/Logs/claude-deck/claude-deck.log` as
+//! one JSON object per line. The file is rotated at `MAX_FILE_SIZE`, keeping
+//! `KEEP_FILES` older ones (`claude-deck.log.1` is the newest). The filter
+//! starts from `RUST_LOG` (default "info") and can be replaced through
+//! `GET/PUT /api/loglevel`, so device problems can be debugged without
+//! restarting the LaunchAgent.
+
+use anyhow::{anyhow, Context, Result};
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tracing::field::{Field, Visit};
+use tracing::{Event, Subscriber};
+use tracing_subscriber::layer::{Context as LayerContext, SubscriberExt};
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{reload, EnvFilter, Layer, Registry};
+
+/// Size a log file grows to before it's rotated
+pub const MAX_FILE_SIZE: u64 = 10 * 1024 * 1024;
+/// Rotated files kept besides the current one
+pub const KEEP_FILES: usize = 5;
+
+const FILE_NAME: &str = "claude-deck.log";
+
+/// Swaps the filter of the running subscriber
+static FILTER: OnceLock<reload::Handle<EnvFilter, Registry>> = OnceLock::new();
+
+/// `~/Library/Logs/claude-deck`
+pub fn log_dir() -> Result<PathBuf> {
+    let home = std::env::var("HOME").context("HOME environment variable not set")?;
+    Ok(PathBuf::from(home).join("Library/Logs/claude-deck"))
+}
+
+/// Install the subscriber (stderr, plus the JSON file when it can be opened)
+pub fn init() {
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"));
+    let (filter, handle) = reload::Layer::new(filter);
+    let _ = FILTER.set(handle);
+
+    let file = log_dir().and_then(|dir| RotatingFile::open(&dir, MAX_FILE_SIZE, KEEP_FILES));
+    let (file_layer, file_error) = match file {
+        Ok(file) => (Some(JsonLayer { file: Mutex::new(file) }), None),
+        Err(e) => (None, Some(e)),
+    };
+    tracing_subscriber::registry()
+        .with(filter)
+        .with(tracing_subscriber::fmt::layer())
+        .with(file_layer)
+        .init();
+    if let Some(e) = file_error {
+        tracing::warn!("Not logging to a file: {:#}", e);
+    }
+}
+
+/// The current filter directives (e.g. "info,claude_deck::device=debug")
+pub fn level() -> Option<String> {
+    FILTER.get()?.with_current(|filter| filter.to_string()).ok()
+}
+
+/// Replace the filter with `directives` (`RUST_LOG` syntax)
+pub fn set_level(directives: &str) -> Result<String> {
+    let filter = EnvFilter::try_new(directives.trim())
+        .map_err(|e| anyhow!("Invalid log level {:?}: {}", directives, e))?;
+    let handle = FILTER.get().ok_or_else(|| anyhow!("Logging isn't initialized"))?;
+    let applied = filter.to_string();
+    handle.reload(filter)?;
+    Ok(applied)
+}
+
+/// Log file that moves aside to `.1`, `.2`, ... once it reaches `max_size`
+struct RotatingFile {
+    path: PathBuf,
+    file: File,
+    size: u64,
+    max_size: u64,
+    keep: usize,
+}
+
+impl RotatingFile {
+    fn open(dir: &Path, max_size: u64, keep: usize) -> Result<Self> {
+        std::fs::create_dir_all(dir).with_context(|| format!("Failed to create {:?}", dir))?;
+        let path = dir.join(FILE_NAME);
+        let file = Self::append(&path)?;
+        let size = file.metadata().map(|m| m.len()).unwrap_or(0);
+        Ok(Self { path, file, size, max_size, keep })
+    }
+
+    fn append(path: &Path) -> Result<File> {
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .with_context(|| format!("Failed to open {:?}", path))
+    }
+
+    /// `claude-deck.log.<n>`
+    fn rotated(&self, n: usize) -> PathBuf {
+        self.path.with_extension(format!("log.{}", n))
+    }
+
+    /// Write one line, rotating first if it would take the file past the limit
+    fn write_line(&mut self, line: &[u8]) -> Result<()> {
+        if self.size > 0 && self.size + line.len() as u64 > self.max_size {
+            self.rotate()?;
+        }
+        self.file.write_all(line)?;
+        self.size += line.len() as u64;
+        Ok(())
+    }
+
+    fn rotate(&mut self) -> Result<()> {
+        let _ = std::fs::remove_file(self.rotated(self.keep));
+        for n in (1..self.keep).rev() {
+            let _ = std::fs::rename(self.rotated(n), self.rotated(n + 1));
+        }
+        if self.keep > 0 {
+            std::fs::rename(&self.path, self.rotated(1))?;
+        } else {
+            std::fs::remove_file(&self.path)?;
+        }
+        self.file = Self::append(&self.path)?;
+        self.size = 0;
+        Ok(())
+    }
+}
+
+/// Writes each event as a JSON line to the rotating file
+struct JsonLayer {
+    file: Mutex<RotatingFile>,
+}
+
+impl<S: Subscriber> Layer<S> for JsonLayer {
+    fn on_event(&self, event: &Event<'_>, _ctx: LayerContext<'_, S>) {
+        let meta = event.metadata();
+        let mut fields = JsonFields(serde_json::Map::new());
+        event.record(&mut fields);
+        // Events from the `log` crate (e.g. enigo's) carry their real target as a field
+        let target = fields.0.remove("log.target").unwrap_or_else(|| meta.target().into());
+        fields.0.retain(|name, _| !name.starts_with("log."));
+
+        let mut entry = serde_json::Map::new();
+        entry.insert("timestamp".into(), format_utc(SystemTime::now()).into());
+        entry.insert("level".into(), meta.level().as_str().into());
+        entry.insert("target".into(), target);
+        entry.extend(fields.0);
+
+        let Ok(mut line) = serde_json::to_vec(&entry) else {
+            return;
+        };
+        line.push(b'\n');
+        if let Ok(mut file) = self.file.lock() {
+            // Nowhere left to report a failure to write the log
+            let _ = file.write_line(&line);
+        }
+    }
+}
+
+/// An event's fields as JSON values ("message" for the formatted message)
+struct JsonFields(serde_json::Map<String, serde_json::Value>);
+
+impl Visit for JsonFields {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.insert(field.name().into(), value.into());
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.0.insert(field.name().into(), value.into());
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.0.insert(field.name().into(), value.into());
+    }
+
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.0.insert(field.name().into(), value.into());
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.0.insert(field.name().into(), value.into());
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        self.0.insert(field.name().into(), format!("{:?}", value).into());
+    }
+}
+
+/// RFC 3339 UTC time with milliseconds, e.g. "2025-10-09T14:03:07.412Z"
+fn format_utc(time: SystemTime) -> String {
+    let since = time.duration_since(UNIX_EPOCH).unwrap_or_default();
+    let secs = since.as_secs();
+    let (days, rem) = (secs / 86_400, secs % 86_400);
+
+    // Civil date from days since 1970-01-01 (Howard Hinnant's algorithm)
+    let z = days as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:03}Z",
+        year,
+        month,
+        day,
+        rem / 3600,
+        rem % 3600 / 60,
+        rem % 60,
+        since.subsec_millis()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_rotating_file() {
+        let dir = std::env::temp_dir().join(format!("claude-deck-logs-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let mut file = RotatingFile::open(&dir, 10, 2).unwrap();
+        for n in 0..4 {
+            file.write_line(format!("line {}\n", n).as_bytes()).unwrap();
+        }
+        // One line per file: the newest in the log, two older kept, the first dropped
+        let read = |path: PathBuf| std::fs::read_to_string(path).unwrap_or_default();
+        assert_eq!(read(dir.join(FILE_NAME)), "line 3\n");
+        assert_eq!(read(file.rotated(1)), "line 2\n");
+        assert_eq!(read(file.rotated(2)), "line 1\n");
+        assert!(!file.rotated(3).exists());
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let time = UNIX_EPOCH + Duration::from_millis(1_760_018_587_412);
+        assert_eq!(format_utc(time), "2025-10-09T14:03:07.412Z");
+        assert_eq!(format_utc(UNIX_EPOCH), "1970-01-01T00:00:00.000Z");
+
+        let (_layer, handle) = reload::Layer::<_, Registry>::new(EnvFilter::new("info"));
+        let _ = FILTER.set(handle);
+        assert_eq!(set_level(" debug,claude_deck::device=trace ").unwrap(), level().unwrap());
+        assert!(level().unwrap().contains("claude_deck::device=trace"));
+        assert!(set_level("claude_deck=loud").is_err());
+    }
+}
@@ -1,13 +1,17 @@
-use anyhow::{Context, Result};
+use anyhow::{bail, Context, Result};
 use clap::Parser;
+use std::path::PathBuf;
 use std::sync::{Arc, RwLock as StdRwLock};
 use tokio::signal;
 use tokio::sync::{mpsc, RwLock as TokioRwLock};
 use tracing::{info, warn};
-use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, EnvFilter};
 
 use claude_deck::{
-    config::Config,
+    agent::{self, KeystrokeQueue},
+    config::{self, Config},
+    control::{self, Control, Request},
+    device::{FrameBuffer, VirtualDevice},
+    logging, midi, monitors, mqtt, plugins,
     web::{self, ConfigChangeEvent},
     App, AppCommand,
 };
@@ -21,10 +25,31 @@ struct Cli {
     #[arg(long)]
     status: bool,
 
+    /// Check permissions, the device, hooks and the web port, and suggest fixes
+    #[arg(long)]
+    doctor: bool,
+
+    /// Print the raw HID codes and decoded events of presses and turns
+    /// instead of running their actions (for `[device.input_map]`)
+    #[arg(long)]
+    learn: bool,
+
     /// Set device brightness (0-100)
     #[arg(long, value_name = "PERCENT", value_parser = clap::value_parser!(u8).range(0..=100))]
     brightness: Option<u8>,
 
+    /// Press a button (0-9) on the running instance
+    #[arg(long, value_name = "BUTTON", value_parser = clap::value_parser!(u8).range(0..=9))]
+    press: Option<u8>,
+
+    /// Hold the --press button long enough for its long-press action
+    #[arg(long, requires = "press")]
+    long: bool,
+
+    /// Make the running instance re-read config.toml
+    #[arg(long)]
+    reload: bool,
+
     /// Install autostart on login (macOS LaunchAgent)
     #[arg(long)]
     install_autostart: bool,
@@ -40,6 +65,52 @@ struct Cli {
     /// Uninstall Claude Code hooks
     #[arg(long)]
     uninstall_hooks: bool,
+
+    /// Run as a system daemon (LaunchDaemon): own the device and forward
+    /// keystrokes to per-user agents
+    #[arg(long, conflicts_with = "agent")]
+    daemon: bool,
+
+    /// Run as a per-user agent that injects keystrokes sent by the daemon
+    #[arg(long)]
+    agent: bool,
+
+    /// Install system daemon + per-user agent (run with sudo, macOS)
+    #[arg(long)]
+    install_daemon: bool,
+
+    /// Uninstall system daemon + per-user agent (run with sudo)
+    #[arg(long)]
+    uninstall_daemon: bool,
+
+    /// Import an Elgato Stream Deck profile export (.streamDeckProfile) into config
+    #[arg(long, value_name = "FILE")]
+    import_streamdeck: Option<PathBuf>,
+
+    /// Render a profile's buttons and strip to PNG files and exit
+    #[arg(long, value_name = "PROFILE")]
+    render_preview: Option<String>,
+
+    /// Download the emoji of a profile (all profiles without one) to the cache
+    #[arg(long, value_name = "PROFILE", num_args = 0..=1)]
+    prefetch_emoji: Option<Option<String>>,
+
+    /// Directory for --render-preview output
+    #[arg(long, value_name = "DIR", default_value = ".", requires = "render_preview")]
+    out: PathBuf,
+
+    /// Serve web UI assets from this directory instead of the embedded copies (debug builds)
+    #[arg(long, value_name = "DIR")]
+    dev_static: Option<PathBuf>,
+
+    /// Run against a virtual device shown in the web UI instead of hardware
+    #[arg(long)]
+    simulate: bool,
+
+    /// Run permanently without hardware: the web UI previews are the display
+    /// and /api/press the input (demos, CI checks of profiles, remote machines)
+    #[arg(long, conflicts_with = "simulate")]
+    headless: bool,
 }
 
 #[tokio::main]
@@ -53,11 +124,8 @@ async fn main() -> Result<()> {
         libc::signal(libc::SIGCHLD, libc::SIG_IGN);
     }
 
-    // Initialize logging
-    tracing_subscriber::registry()
-        .with(EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info")))
-        .with(tracing_subscriber::fmt::layer())
-        .init();
+    // Initialize logging (stderr and ~/Library/Logs/claude-deck)
+    logging::init();
 
     let cli = Cli::parse();
 
@@ -78,18 +146,76 @@ async fn main() -> Result<()> {
         return uninstall_hooks();
     }
 
+    if cli.install_daemon {
+        return install_daemon();
+    }
+
+    if cli.uninstall_daemon {
+        return uninstall_daemon();
+    }
+
+    if let Some(ref path) = cli.import_streamdeck {
+        return import_streamdeck(path);
+    }
+
+    if let Some(ref profile) = cli.render_preview {
+        return render_preview(profile, &cli.out);
+    }
+
+    if let Some(ref profile) = cli.prefetch_emoji {
+        return prefetch_emoji(profile.as_deref());
+    }
+
     if cli.status {
         return check_status().await;
     }
 
+    if cli.doctor {
+        return doctor().await;
+    }
+
+    if cli.learn {
+        return learn().await;
+    }
+
     if let Some(brightness) = cli.brightness {
         return set_brightness(brightness).await;
     }
 
+    if let Some(button) = cli.press {
+        return run_remote(Request::Press { button, long_press: cli.long }).await;
+    }
+
+    if cli.reload {
+        return run_remote(Request::Reload).await;
+    }
+
     // Load configuration
     let config = Config::load()?;
 
-    info!("Starting claude-deck");
+    if cli.agent {
+        info!("Starting claude-deck agent");
+        return agent::run_agent(config.web.port, config.keystrokes).await;
+    }
+
+    // Only one instance may own the device; the socket also serves the CLI flags above
+    let control_socket = control::bind().await?;
+
+    let mode = match (cli.daemon, cli.headless) {
+        (true, true) => " (system daemon, headless)",
+        (true, false) => " (system daemon)",
+        (false, true) => " (headless)",
+        (false, false) => "",
+    };
+    info!("Starting claude-deck{}", mode);
+
+    // In daemon mode keystrokes are queued for the agent in the active user session
+    let keystroke_queue = cli.daemon.then(|| Arc::new(KeystrokeQueue::new()));
+
+    // Simulator and headless modes replace the hardware with an in-memory device
+    // driven from the web UI
+    let simulator = (cli.simulate || cli.headless).then(|| Arc::new(VirtualDevice::new()));
+    let frames = Arc::new(FrameBuffer::new());
 
     // Initialize profile manager from config (uses std RwLock for sync access in renderer)
     let profile_manager = web::server::init_profile_manager(&config);
@@ -102,31 +228,127 @@ async fn main() -> Result<()> {
     // Create app command channel for triggering refreshes
     let (app_cmd_tx, app_cmd_rx) = mpsc::channel::<AppCommand>(16);
 
+    // Config, device and hook events streamed to web clients
+    let events = web::events::channel();
+
     // Create shared device state before web server so both can access it
     let config_snapshot = config.read().await.clone();
     let device_state = App::create_state(&config_snapshot);
+    device_state.write().await.headless = cli.headless;
 
-    // Spawn web server if enabled
+    // Spawn web server if enabled (always needed in daemon mode for agents, and
+    // in simulator and headless modes to see and drive the virtual device)
     let web_enabled = config.read().await.web.enabled;
-    if web_enabled {
+    let web_required = cli.daemon || cli.simulate || cli.headless;
+    if !web_enabled && web_required {
+        warn!("Web server is disabled in config but required by this mode - starting it anyway");
+    }
+    if web_enabled || web_required {
         let config_clone = Arc::clone(&config);
         let profile_manager_clone = Arc::clone(&profile_manager);
         let change_tx_clone = change_tx.clone();
         let device_state_clone = Arc::clone(&device_state);
+        let dev_static = cli.dev_static.clone();
+        let keystroke_queue = keystroke_queue.clone();
+        let simulator = simulator.clone();
+        let frames = Arc::clone(&frames);
+        let commands = app_cmd_tx.clone();
+        let events = events.clone();
 
         tokio::spawn(async move {
-            if let Err(e) =
-                web::start_server(config_clone, profile_manager_clone, change_tx_clone, device_state_clone).await
+            if let Err(e) = web::start_server(
+                config_clone,
+                profile_manager_clone,
+                change_tx_clone,
+                device_state_clone,
+                dev_static,
+                keystroke_queue,
+                web::DeckAccess { frames, simulator, commands, events },
+            )
+            .await
             {
                 warn!("Web server error: {}", e);
             }
         });
     }
 
+    // Reload edits made to config.toml in a text editor (our own saves compare equal)
+    let (reload_tx, mut reload_rx) = mpsc::channel::<Config>(4);
+    tokio::spawn(config::watch_config_file(Config::config_path()?, reload_tx));
+    {
+        let config = Arc::clone(&config);
+        let profile_manager = Arc::clone(&profile_manager);
+        let change_tx = change_tx.clone();
+        tokio::spawn(async move {
+            while let Some(new_config) = reload_rx.recv().await {
+                if !web::server::apply_config(&config, &profile_manager, new_config).await {
+                    continue;
+                }
+                info!("Config file changed on disk - reloaded");
+                if let Err(e) = change_tx.send(ConfigChangeEvent::Reload).await {
+                    warn!("Failed to send config change event: {}", e);
+                }
+            }
+        });
+    }
+
+    // MQTT bridge: display messages from the broker trigger redraws too
+    let mqtt = config_snapshot.mqtt.enabled.then(|| {
+        mqtt::spawn(config_snapshot.mqtt.clone(), Arc::clone(&device_state), app_cmd_tx.clone())
+    });
+
+    // Virtual MIDI port for DAWs and other MIDI-learn software
+    let midi = config_snapshot.midi.enabled.then(|| midi::spawn(&config_snapshot.midi)).flatten();
+
+    // Plugins register actions and strip widgets, and push updates for them
+    let plugin_host = plugins::spawn(Arc::clone(&device_state), app_cmd_tx.clone());
+    device_state.write().await.plugins = plugin_host;
+
+    // CPU, memory and network figures for the `system` strip widget
+    claude_deck::system::metrics::spawn(Arc::clone(&device_state), app_cmd_tx.clone());
+
+    // Volume changes made outside the deck move the strip's volume bar
+    claude_deck::system::volume::spawn(Arc::clone(&device_state), app_cmd_tx.clone());
+
+    // Branch and working tree status for the `git` strip widget
+    claude_deck::integrations::git::spawn(
+        &config_snapshot.git,
+        Arc::clone(&device_state),
+        app_cmd_tx.clone(),
+    );
+
+    // Sensor state for the `home_assistant` strip widget
+    claude_deck::integrations::home_assistant::spawn(
+        &config_snapshot.home_assistant,
+        Arc::clone(&device_state),
+        app_cmd_tx.clone(),
+    );
+
+    // Status monitors poll their URLs and recolor their buttons
+    if let Err(e) =
+        monitors::spawn(&config_snapshot.monitors, Arc::clone(&device_state), app_cmd_tx.clone())
+    {
+        warn!("Failed to start status monitors: {}", e);
+    }
+
+    control::serve(
+        control_socket,
+        Control {
+            config: Arc::clone(&config),
+            profile_manager: Arc::clone(&profile_manager),
+            change_tx: change_tx.clone(),
+            device_state: Arc::clone(&device_state),
+            commands: app_cmd_tx.clone(),
+        },
+    );
+
     // Spawn task to handle config change events and trigger display refreshes
+    let config_events = events.clone();
     tokio::spawn(async move {
         while let Some(event) = change_rx.recv().await {
             info!("Config change event: {:?}", event);
+            // Let other web clients know (no one listening is fine)
+            let _ = config_events.send(web::events::DeckEvent::Config(event));
             // Trigger display refresh for any config change
             if let Err(e) = app_cmd_tx.send(AppCommand::RedrawButtons).await {
                 warn!("Failed to send redraw command: {}", e);
@@ -135,7 +357,23 @@ async fn main() -> Result<()> {
     });
 
     // Run the application with graceful shutdown
-    let mut app = App::new(config_snapshot, Arc::clone(&profile_manager), app_cmd_rx, device_state).await?;
+    let mut app = App::new(
+        config_snapshot,
+        Arc::clone(&profile_manager),
+        app_cmd_rx,
+        device_state,
+        keystroke_queue,
+        simulator,
+        frames,
+    )
+    .await?;
+    if let Some(publisher) = mqtt {
+        app = app.with_mqtt(publisher);
+    }
+    if let Some(output) = midi {
+        app = app.with_midi(output);
+    }
+    app = app.with_events(events);
 
     // Set up signal handlers for graceful shutdown
     let mut sigterm = signal::unix::signal(signal::unix::SignalKind::terminate())?;
@@ -212,6 +450,191 @@ fn install_autostart() -> Result<()> {
     }
 }
 
+fn import_streamdeck(path: &std::path::Path) -> Result<()> {
+    use claude_deck::profiles::{generate_default_profiles, streamdeck};
+
+    let result = streamdeck::import_profile(path)?;
+    let mut config = Config::load()?;
+
+    // An empty profile list means "use built-in defaults"; keep them alongside the import
+    if config.profiles.is_empty() {
+        config.profiles = generate_default_profiles();
+    }
+
+    // Avoid clobbering an existing profile with the same name
+    let mut profile = result.profile;
+    let base_name = profile.name.clone();
+    let mut suffix = 2;
+    while config.profiles.iter().any(|p| p.name == profile.name) {
+        profile.name = format!("{}-{}", base_name, suffix);
+        suffix += 1;
+    }
+
+    println!(
+        "✓ Imported profile '{}' with {} button(s)",
+        profile.name,
+        profile.buttons.len()
+    );
+    if profile.match_apps.is_empty() {
+        println!("  No app binding found - set \"match_apps\" in the web UI to activate it");
+    }
+    for note in &result.skipped {
+        println!("  skipped {}", note);
+    }
+
+    config.profiles.push(profile);
+    config.save()?;
+    println!("Saved to {:?}", Config::config_path()?);
+    Ok(())
+}
+
+fn render_preview(profile: &str, out: &std::path::Path) -> Result<()> {
+    use claude_deck::display::{emoji, preview};
+
+    let config = Config::load()?;
+    let profiles = web::server::init_profile_manager(&config);
+    // Rendering doesn't wait for emoji downloads, so fetch them first
+    let shown = profiles.get_profiles().iter().find(|p| p.name.eq_ignore_ascii_case(profile));
+    if let Some(shown) = shown {
+        emoji::prefetch(shown.emoji_images());
+    }
+    let written = preview::render_preview(&config, profiles, profile, out)?;
+    println!("✓ Rendered profile '{}' to {}", profile, out.display());
+    for path in written {
+        println!("  {}", path.display());
+    }
+    Ok(())
+}
+
+fn prefetch_emoji(profile: Option<&str>) -> Result<()> {
+    use claude_deck::display::emoji;
+
+    let config = Config::load()?;
+    let manager = web::server::init_profile_manager(&config);
+    let profiles: Vec<_> = manager
+        .get_profiles()
+        .iter()
+        .filter(|p| profile.is_none_or(|name| p.name.eq_ignore_ascii_case(name)))
+        .collect();
+    if let (Some(name), true) = (profile, profiles.is_empty()) {
+        bail!("No profile named '{}'", name);
+    }
+
+    let report = emoji::prefetch(profiles.iter().flat_map(|p| p.emoji_images()));
+    println!(
+        "✓ {} emoji downloaded, {} already available",
+        report.fetched, report.present
+    );
+    if !report.failed.is_empty() {
+        bail!("Couldn't fetch: {}", report.failed.join(", "));
+    }
+    Ok(())
+}
+
+const DAEMON_PLIST: &str = "/Library/LaunchDaemons/com.claude-deck.daemon.plist";
+const AGENT_PLIST: &str = "/Library/LaunchAgents/com.claude-deck.agent.plist";
+
+fn install_daemon() -> Result<()> {
+    #[cfg(target_os = "macos")]
+    {
+        use std::fs;
+
+        let binary_path = std::env::current_exe().context("Failed to get current executable path")?;
+
+        // System-wide daemon: owns the device, runs at boot (including the login window)
+        let daemon_content = format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+    <key>Label</key>
+    <string>com.claude-deck.daemon</string>
+    <key>ProgramArguments</key>
+    <array>
+        <string>{}</string>
+        <string>--daemon</string>
+    </array>
+    <key>RunAtLoad</key>
+    <true/>
+    <key>KeepAlive</key>
+    <true/>
+    <key>StandardOutPath</key>
+    <string>/Library/Logs/claude-deck-daemon.log</string>
+    <key>StandardErrorPath</key>
+    <string>/Library/Logs/claude-deck-daemon.log</string>
+</dict>
+</plist>"#,
+            binary_path.display()
+        );
+
+        // Per-user agent: started in every GUI login session for keystroke injection
+        let agent_content = format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+    <key>Label</key>
+    <string>com.claude-deck.agent</string>
+    <key>ProgramArguments</key>
+    <array>
+        <string>{}</string>
+        <string>--agent</string>
+    </array>
+    <key>LimitLoadToSessionType</key>
+    <string>Aqua</string>
+    <key>RunAtLoad</key>
+    <true/>
+    <key>KeepAlive</key>
+    <true/>
+</dict>
+</plist>"#,
+            binary_path.display()
+        );
+
+        fs::write(DAEMON_PLIST, daemon_content)
+            .with_context(|| format!("Failed to write {} (run with sudo)", DAEMON_PLIST))?;
+        println!("✓ Created LaunchDaemon at {}", DAEMON_PLIST);
+
+        fs::write(AGENT_PLIST, agent_content)
+            .with_context(|| format!("Failed to write {} (run with sudo)", AGENT_PLIST))?;
+        println!("✓ Created LaunchAgent at {}", AGENT_PLIST);
+
+        println!();
+        println!("Run 'sudo launchctl load {}' to start the daemon now.", DAEMON_PLIST);
+        println!("The agent starts at next login (or 'launchctl load {}').", AGENT_PLIST);
+        println!("Remove any per-user autostart first: claude-deck --uninstall-autostart");
+        Ok(())
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    {
+        eprintln!("Daemon installation is only supported on macOS");
+        Ok(())
+    }
+}
+
+fn uninstall_daemon() -> Result<()> {
+    use std::fs;
+    use std::path::Path;
+
+    for plist in [AGENT_PLIST, DAEMON_PLIST] {
+        let path = Path::new(plist);
+        if path.exists() {
+            let _ = std::process::Command::new("launchctl")
+                .arg("unload")
+                .arg(path)
+                .output();
+
+            fs::remove_file(path)
+                .with_context(|| format!("Failed to remove {} (run with sudo)", plist))?;
+            println!("✓ Removed {}", plist);
+        } else {
+            println!("{} not found (already uninstalled?)", plist);
+        }
+    }
+    Ok(())
+}
+
 fn install_hooks() -> Result<()> {
     use std::fs;
     use std::os::unix::fs::PermissionsExt;
@@ -282,23 +705,11 @@ fn install_hooks() -> Result<()> {
         let hooks = obj.entry("hooks").or_insert(serde_json::json!({}));
         if let Some(hooks_obj) = hooks.as_object_mut() {
             // Add our hook to each event type
-            for event in &["UserPromptSubmit", "PreToolUse", "PostToolUse", "Notification", "Stop"] {
-                let event_hooks = hooks_obj.entry(*event).or_insert(serde_json::json!([]));
+            for event in claude_deck::hooks::HOOK_EVENTS {
+                let event_hooks = hooks_obj.entry(event).or_insert(serde_json::json!([]));
                 if let Some(arr) = event_hooks.as_array_mut() {
                     // Check if our hook is already there
-                    let hook_exists = arr.iter().any(|v| {
-                        v.get("hooks")
-                            .and_then(|h| h.as_array())
-                            .map(|hooks_arr| {
-                                hooks_arr.iter().any(|hook| {
-                                    hook.get("command")
-                                        .and_then(|c| c.as_str())
-                                        .map(|s| s.contains("claude-deck"))
-                                        .unwrap_or(false)
-                                })
-                            })
-                            .unwrap_or(false)
-                    });
+                    let hook_exists = arr.iter().any(claude_deck::hooks::runs_claude_deck);
                     if !hook_exists {
                         arr.push(hook_entry.clone());
                     }
@@ -317,6 +728,10 @@ fn install_hooks() -> Result<()> {
     println!();
     println!("Claude Code hooks installed successfully!");
     println!("The LCD strip will now show real-time status from Claude Code.");
+    println!(
+        "Updates are pushed over {:?} while claude-deck runs (status file as fallback).",
+        claude_deck::hooks::socket_path(&claude_deck::hooks::state_dir())
+    );
     println!();
     println!("Note: You may need to restart Claude Code for hooks to take effect.");
 
@@ -324,36 +739,141 @@ fn install_hooks() -> Result<()> {
 }
 
 async fn check_status() -> Result<()> {
-    use claude_deck::device::DeviceManager;
+    use claude_deck::device::{DeviceManager, SUPPORTED_MODELS};
+
+    // The running instance owns the device, so ask it instead
+    if let Some(response) = control::send(&Request::Status).await? {
+        let Some(report) = response.device.filter(|_| response.ok) else {
+            bail!("{}", response.message);
+        };
+        println!("✓ claude-deck is running");
+        match report.device {
+            Some(device) => {
+                println!("✓ Device connected: {}", device.model);
+                if let Some(serial) = device.serial_number {
+                    println!("  Serial: {}", serial);
+                }
+                println!("  Brightness: {}%", report.brightness);
+            }
+            None => println!(
+                "✗ Device disconnected ({} reconnect attempts)",
+                report.reconnect_attempts
+            ),
+        }
+        return Ok(());
+    }
 
     info!("Checking device status...");
 
-    match DeviceManager::find_device().await {
-        Ok(info) => {
-            println!("✓ Device found: {}", info.name);
-            println!("  Firmware: {}", info.firmware_version);
-            println!("  Serial: {}", info.serial_number);
+    match DeviceManager::find_devices().await {
+        Ok(devices) => {
+            for info in devices {
+                println!("✓ Device found: {}", info.name);
+                println!("  Firmware: {}", info.firmware_version);
+                println!("  Serial: {}", info.serial_number);
+            }
             Ok(())
         }
         Err(e) => {
             println!("✗ No device found: {}", e);
+            println!("  Supported models:");
+            for model in SUPPORTED_MODELS {
+                println!(
+                    "    {} ({:04x}:{:04x})",
+                    model.name, model.vendor_id, model.product_id
+                );
+            }
             std::process::exit(1);
         }
     }
 }
 
+async fn doctor() -> Result<()> {
+    use claude_deck::doctor::{self, Outcome};
+
+    let config = Config::load()?;
+    let checks = doctor::run(&config, false).await;
+    for check in &checks {
+        println!("{} {}: {}", check.outcome.symbol(), check.name, check.detail);
+        if let Some(fix) = &check.fix {
+            println!("    → {}", fix);
+        }
+    }
+    if checks.iter().any(|check| check.outcome == Outcome::Fail) {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+async fn learn() -> Result<()> {
+    use claude_deck::device::{self, DeviceManager, InputTarget, RawEvent};
+
+    let config = Config::load()?;
+    device::set_input_map(&config.device.input_map);
+    let mut raw = device::capture_raw();
+    let mut manager = DeviceManager::connect(Arc::default()).await?;
+    println!("Press buttons and turn knobs to see their codes (Ctrl-C to quit).");
+    println!("A code with no event under it is unknown: map it in [device.input_map].");
+
+    let print_raw = |event: RawEvent| {
+        // Type 0x00 is padding the device sends between events
+        if event.event_type != 0 {
+            println!("HID type=0x{:02x} state=0x{:02x}", event.event_type, event.state);
+        }
+    };
+    loop {
+        tokio::select! {
+            Some(event) = raw.recv() => print_raw(event),
+            event = manager.next_event() => {
+                // The codes behind an event are captured before it is decoded
+                while let Ok(event) = raw.try_recv() {
+                    print_raw(event);
+                }
+                let event = event?;
+                match InputTarget::of(&event) {
+                    Some(target) => println!("  {:?} ({})", event, target),
+                    None => println!("  {:?}", event),
+                }
+            }
+            _ = tokio::signal::ctrl_c() => return Ok(()),
+        }
+    }
+}
+
 async fn set_brightness(brightness: u8) -> Result<()> {
     use claude_deck::device::DeviceManager;
 
     // Note: brightness is already validated by clap to be 0-100
     info!("Setting brightness to {}%", brightness);
 
-    let manager = DeviceManager::connect().await?;
+    // The running instance owns the device; only open it directly without one
+    let request = Request::Brightness { percent: brightness };
+    if let Some(response) = control::send(&request).await? {
+        if !response.ok {
+            bail!("{}", response.message);
+        }
+        println!("✓ Brightness set to {}%", brightness);
+        return Ok(());
+    }
+
+    let manager = DeviceManager::connect(Arc::default()).await?;
     manager.set_brightness(brightness).await?;
     println!("✓ Brightness set to {}%", brightness);
     Ok(())
 }
 
+/// Send a request that needs the running instance
+async fn run_remote(request: Request) -> Result<()> {
+    match control::send(&request).await? {
+        Some(response) if response.ok => {
+            println!("✓ {}", response.message);
+            Ok(())
+        }
+        Some(response) => bail!("{}", response.message),
+        None => bail!("claude-deck isn't running"),
+    }
+}
+
 fn uninstall_autostart() -> Result<()> {
     #[cfg(target_os = "macos")]
     {
@@ -410,22 +930,10 @@ fn uninstall_hooks() -> Result<()> {
             let mut modified = false;
 
             if let Some(hooks) = settings.get_mut("hooks").and_then(|h| h.as_object_mut()) {
-                for event in &["UserPromptSubmit", "PreToolUse", "PostToolUse", "Notification", "Stop"] {
-                    if let Some(event_hooks) = hooks.get_mut(*event).and_then(|e| e.as_array_mut()) {
+                for event in claude_deck::hooks::HOOK_EVENTS {
+                    if let Some(event_hooks) = hooks.get_mut(event).and_then(|e| e.as_array_mut()) {
                         let original_len = event_hooks.len();
-                        event_hooks.retain(|v| {
-                            !v.get("hooks")
-                                .and_then(|h| h.as_array())
-                                .map(|hooks_arr| {
-                                    hooks_arr.iter().any(|hook| {
-                                        hook.get("command")
-                                            .and_then(|c| c.as_str())
-                                            .map(|s| s.contains("claude-deck"))
-                                            .unwrap_or(false)
-                                    })
-                                })
-                                .unwrap_or(false)
-                        });
+                        event_hooks.retain(|v| !claude_deck::hooks::runs_claude_deck(v));
                         if event_hooks.len() != original_len {
                             modified = true;
                         }
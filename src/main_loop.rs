@@ -0,0 +1,1309 @@
+//! The main event loop
+//!
+//! `run_main_loop` sleeps until input, a command or a hook update arrives, or
+//! a timer fires, hands the wake to its handler, then runs the checks that
+//! follow every wake. Whatever has to survive between wakes (poll timers,
+//! background checks, what was last drawn) lives in `LoopState`.
+
+use anyhow::Result;
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tokio::time::{Interval, MissedTickBehavior};
+use tracing::{debug, info, warn};
+
+use crate::config::StripWidget;
+use crate::decks::{self, Deck};
+use crate::device::{DeckDevice, InputEvent};
+use crate::input::device_to_logical_button;
+use crate::profiles::overlay::Overlay;
+use crate::profiles::store::ActionConfig;
+use crate::sound::{self, SoundEvent};
+use crate::state::{DeviceHealth, PersistedState, SceneRequest};
+use crate::timer::TimerView;
+use crate::web::events::DeckEvent;
+use crate::{display, hooks, notifications, projects, scenes, system, web};
+use crate::{App, AppCommand};
+
+const KEEPALIVE_INTERVAL: Duration = Duration::from_secs(10);
+/// Housekeeping also scrolls long strip text (10 FPS)
+const HOUSEKEEPING_INTERVAL: Duration = Duration::from_millis(100);
+const FRAME_INTERVAL: Duration = Duration::from_millis(16); // 60 FPS
+const WAITING_FLASH_TICKS: u32 = 5; // Pulse every 500ms
+/// Min gap between device operations (HID device needs time between operations)
+const DEVICE_COOLDOWN: Duration = Duration::from_millis(20);
+/// Status file polls pause this long after a hook pushed over the socket
+const SOCKET_QUIET_PERIOD: Duration = Duration::from_secs(30);
+const APP_CHECK_INTERVAL: Duration = Duration::from_millis(500);
+/// How long polls may still see the old app after a button switched apps
+const APP_SWITCH_GRACE: Duration = Duration::from_secs(3);
+const LOCK_CHECK_INTERVAL: Duration = Duration::from_secs(2); // Security, not latency-critical
+const FOCUS_CHECK_INTERVAL: Duration = Duration::from_secs(3);
+const CLIPBOARD_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+const IDLE_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+const SCHEDULE_CHECK_INTERVAL: Duration = Duration::from_secs(10);
+const POWER_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+const WIDGET_REFRESH_INTERVAL: Duration = Duration::from_secs(2);
+/// Press stats are batched rather than written on every press
+const STATS_SAVE_INTERVAL: Duration = Duration::from_secs(30);
+
+/// What woke the main loop
+enum Wake {
+    Command(AppCommand),
+    Input(Result<InputEvent>),
+    /// Input from the extra deck at this index
+    DeckInput(usize, Result<InputEvent>),
+    Hook(Box<hooks::ClaudeStatus>),
+    /// Slow tick for polls, overlays and flashes
+    Housekeeping,
+    /// Fast tick while GIFs play or a button is held
+    Animation,
+}
+
+/// Main loop state kept between wakes
+struct LoopState {
+    housekeeping: Interval,
+    /// Only ticks while there is something to animate
+    animation: Interval,
+    housekeeping_ticks: u32,
+    /// Last device write, to enforce `DEVICE_COOLDOWN`
+    last_device_write: Instant,
+    last_keepalive: Instant,
+
+    last_status_check: Instant,
+    status_check_interval: Duration,
+    last_socket_update: Option<Instant>,
+
+    last_app_check: Instant,
+    pending_app_check: Option<JoinHandle<Option<system::WindowInfo>>>,
+    /// An app a button focused or launched, shown until it comes to the front
+    app_switch: Option<(String, Instant)>,
+    /// A newly focused window, and since when, until it has stayed for the switch delay
+    focus_candidate: Option<(system::WindowInfo, Instant)>,
+
+    last_lock_check: Instant,
+    last_focus_check: Instant,
+
+    // Clipboard history is only tracked while a profile has clipboard buttons
+    last_clipboard_check: Instant,
+    pending_clipboard_check: Option<JoinHandle<Option<String>>>,
+
+    // Overlay state, to refresh the display when they open or expire
+    volume_overlay_was_active: bool,
+    brightness_overlay_was_active: bool,
+    button_overlay_was_active: (Option<Overlay>, usize),
+    session_picker_was_active: bool,
+    agent_view_was_active: bool,
+    last_timer_view: TimerView,
+    last_confirm_view: Option<(u8, bool)>,
+    last_banner_countdown: Option<u64>,
+    /// Claude session directory last matched against `[projects]`
+    last_cwd: Option<String>,
+    /// When the current wait for input began, for the attention glow's pulse
+    attention_started: Option<Instant>,
+
+    // Idle dimming and wake-on-hook flash
+    last_activity: Instant,
+    idle_dimmed: bool,
+    wake_flash_until: Option<Instant>,
+    last_idle_check: Instant,
+
+    // Scheduled scenes fire once when the local clock reaches their minute
+    last_schedule_check: Instant,
+    last_schedule_minute: (u32, u32),
+
+    // AC vs battery power (low-power mode slows animations and polling)
+    last_power_check: Option<Instant>,
+    pending_power_check: Option<JoinHandle<Option<bool>>>,
+
+    // Live strip widgets (clock, CPU, ...) are redrawn on a timer
+    last_widget_refresh: Instant,
+    pending_cpu_check: Option<JoinHandle<Option<u8>>>,
+    pending_now_playing_check: Option<JoinHandle<Option<system::NowPlaying>>>,
+
+    last_stats_save: Instant,
+}
+
+impl LoopState {
+    fn new(status_check_interval: Duration, timer_view: TimerView) -> Self {
+        let now = Instant::now();
+        let mut housekeeping = tokio::time::interval(HOUSEKEEPING_INTERVAL);
+        housekeeping.set_missed_tick_behavior(MissedTickBehavior::Skip);
+        Self {
+            housekeeping,
+            animation: animation_timer(FRAME_INTERVAL),
+            housekeeping_ticks: 0,
+            last_device_write: now,
+            last_keepalive: now,
+            last_status_check: now,
+            status_check_interval,
+            last_socket_update: None,
+            last_app_check: now,
+            pending_app_check: None,
+            app_switch: None,
+            focus_candidate: None,
+            last_lock_check: now,
+            last_focus_check: now,
+            last_clipboard_check: now,
+            pending_clipboard_check: None,
+            volume_overlay_was_active: false,
+            brightness_overlay_was_active: false,
+            button_overlay_was_active: (None, 0),
+            session_picker_was_active: false,
+            agent_view_was_active: false,
+            last_timer_view: timer_view,
+            last_confirm_view: None,
+            last_banner_countdown: None,
+            last_cwd: None,
+            attention_started: None,
+            last_activity: now,
+            idle_dimmed: false,
+            wake_flash_until: None,
+            last_idle_check: now,
+            last_schedule_check: now,
+            last_schedule_minute: scenes::local_hour_minute(),
+            last_power_check: None,
+            pending_power_check: None,
+            last_widget_refresh: now,
+            pending_cpu_check: None,
+            pending_now_playing_check: None,
+            last_stats_save: now,
+        }
+    }
+
+    /// Whether the device has had its cooldown since the last write
+    fn device_ready(&self) -> bool {
+        self.last_device_write.elapsed() >= DEVICE_COOLDOWN
+    }
+
+    fn wrote_device(&mut self) {
+        self.last_device_write = Instant::now();
+    }
+}
+
+impl<D: DeckDevice> App<D> {
+    /// Run the main loop - handle device events and inject keystrokes
+    pub(crate) async fn run_main_loop(&mut self) -> Result<()> {
+        info!("Running - keystrokes will be sent to focused window");
+
+        let timer_view = self.state.read().await.timer.view(Instant::now());
+        let mut ls = LoopState::new(self.config.hooks.poll_interval(), timer_view);
+
+        loop {
+            let glowing = self.config.attention.glow && {
+                let state = self.state.read().await;
+                (state.waiting_for_input && !state.focus_quiet()) || state.attention_glow > 0
+            };
+            let animating = self.input.is_holding()
+                || self.decks.iter().any(Deck::is_holding)
+                || !self.state.read().await.button_flashes.is_empty()
+                || self.state.read().await.strip_overlay.is_some_and(|overlay| {
+                    overlay.is_sliding(Instant::now())
+                })
+                || glowing
+                || display::gif_animator().lock().is_ok_and(|anim| anim.is_animating());
+            let wake = tokio::select! {
+                Some(cmd) = self.command_rx.recv() => Wake::Command(cmd),
+                Some(cmd) = self.queued_rx.recv() => Wake::Command(cmd),
+                event = next_device_event(self.device.as_mut()) => Wake::Input(event),
+                (index, event) = decks::next_input(&mut self.decks) => {
+                    Wake::DeckInput(index, event)
+                }
+                Some(status) = next_hook_status(self.hook_rx.as_mut()) => {
+                    Wake::Hook(Box::new(status))
+                }
+                _ = ls.housekeeping.tick() => Wake::Housekeeping,
+                _ = ls.animation.tick(), if animating => Wake::Animation,
+            };
+            let on_housekeeping = matches!(wake, Wake::Housekeeping);
+            let on_animation = matches!(wake, Wake::Animation);
+            if on_housekeeping {
+                ls.housekeeping_ticks = ls.housekeeping_ticks.wrapping_add(1);
+            }
+
+            let mut event = None;
+            let mut deck_input = None;
+            let mut hook_status = None;
+            match wake {
+                Wake::Command(cmd) => self.handle_command(cmd, &mut ls).await,
+                Wake::Input(Ok(input)) => event = Some(input),
+                Wake::Input(Err(_)) => self.handle_disconnect().await,
+                Wake::DeckInput(index, event) => deck_input = Some((index, event)),
+                Wake::Hook(status) => hook_status = Some(*status),
+                Wake::Housekeeping | Wake::Animation => {}
+            }
+
+            self.send_keep_alive(&mut ls).await;
+            if let Some(event) = event {
+                // Input on a locked screen or deck skips the rest of this pass
+                if !self.handle_input(event, &mut ls).await {
+                    continue;
+                }
+            } else if self.device.is_none() {
+                self.poll_reconnect(&mut ls).await;
+            }
+            self.update_decks(deck_input, &mut ls).await;
+            self.check_long_press(&mut ls).await;
+            self.handle_hook_status(hook_status, &mut ls).await;
+
+            self.apply_pending_requests(&mut ls).await;
+            self.follow_project(&mut ls).await;
+            self.show_pending_app(&mut ls).await;
+            self.restore_requested_frames(&mut ls).await;
+            self.save_runtime_state(&mut ls).await;
+            self.run_schedule(&mut ls).await;
+
+            self.poll_focused_app(&mut ls).await;
+            self.check_screen_lock(&mut ls).await;
+            self.check_focus_mode(&mut ls).await;
+            self.poll_clipboard(&mut ls).await;
+            self.refresh_widgets(&mut ls).await;
+            self.check_power(&mut ls).await;
+            self.check_idle(&mut ls).await;
+
+            if on_housekeeping {
+                self.run_housekeeping(&mut ls).await;
+            }
+            self.redraw_changed_views(on_housekeeping, on_animation, &mut ls).await;
+            if on_animation {
+                self.run_animation_frame(glowing, &mut ls).await;
+            }
+        }
+    }
+
+    /// Run a command from the web UI, the CLI or a background task
+    async fn handle_command(&mut self, cmd: AppCommand, ls: &mut LoopState) {
+        match cmd {
+            AppCommand::RedrawButtons => self.redraw_for_web().await,
+            AppCommand::Press { button, long_press } => {
+                self.remote_press(button, long_press).await
+            }
+            AppCommand::RunAction(action) => self.remote_action(&action).await,
+            AppCommand::RefreshStrip => {
+                if let Err(e) = self.update_display().await {
+                    debug!("Failed to update display: {}", e);
+                }
+            }
+            AppCommand::SetBrightness(level) => self.remote_brightness(level).await,
+            AppCommand::PlayIntro => {
+                info!("Remote intro animation");
+                self.play_intro().await;
+            }
+            AppCommand::ResetDevice => {
+                info!("Remote device reset");
+                if let Err(e) = self.reset_device().await {
+                    warn!("Failed to reset device: {}", e);
+                }
+            }
+            AppCommand::DeviceInfo(reply) => {
+                let _ = reply.send(self.device_report().await);
+                return;
+            }
+            AppCommand::MacroStep(step, timing) => {
+                self.input.run_macro_step(&step, timing);
+                return;
+            }
+        }
+        ls.wrote_device();
+    }
+
+    async fn redraw_for_web(&mut self) {
+        info!("Received redraw command from web UI");
+        // Small delay to let any pending device operations complete
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        if let Err(e) = self.redraw_all_buttons().await {
+            warn!("Failed to redraw buttons from web UI: {}", e);
+        }
+    }
+
+    async fn remote_press(&mut self, button: u8, long_press: bool) {
+        info!("Remote press of button {}", button);
+        if let Err(e) = self.input.press(button, long_press).await {
+            warn!("Failed to handle remote press: {}", e);
+        }
+        if let Err(e) = self.redraw_button(button).await {
+            debug!("Failed to redraw pressed button: {}", e);
+        }
+        if let Err(e) = self.update_display().await {
+            debug!("Failed to update display: {}", e);
+        }
+    }
+
+    async fn remote_action(&mut self, action: &ActionConfig) {
+        info!("Remote action: {:?}", action);
+        if let Err(e) = self.input.run_action(action).await {
+            warn!("Failed to run remote action: {}", e);
+        }
+        if let Err(e) = self.update_display().await {
+            debug!("Failed to update display: {}", e);
+        }
+    }
+
+    async fn remote_brightness(&mut self, level: u8) {
+        info!("Remote brightness {}%", level);
+        {
+            let mut state = self.state.write().await;
+            state.brightness = level;
+            state.brightness_display_until = Some(Instant::now() + Duration::from_secs(2));
+        }
+        self.apply_brightness(level).await;
+        if let Err(e) = self.update_display().await {
+            debug!("Failed to update display: {}", e);
+        }
+    }
+
+    async fn play_intro(&mut self) {
+        self.play_startup_animation().await.ok();
+        if let Err(e) = self.redraw_all_buttons().await {
+            warn!("Failed to redraw buttons after intro: {}", e);
+        }
+    }
+
+    /// Forget the main deck after a failed read; `poll_reconnect` brings it back
+    async fn handle_disconnect(&mut self) {
+        warn!("Device disconnected, will try to reconnect...");
+        self.device = None;
+        let health = DeviceHealth::after_attempts(0);
+        self.state.write().await.set_device_health(health);
+        self.emit(DeckEvent::Device { connected: false });
+    }
+
+    /// Send periodic keep-alive to prevent device timeout
+    async fn send_keep_alive(&mut self, ls: &mut LoopState) {
+        if let Some(ref device) = self.device {
+            if ls.last_keepalive.elapsed() >= KEEPALIVE_INTERVAL {
+                if let Err(e) = device.keep_alive().await {
+                    warn!("Keep-alive failed: {}", e);
+                }
+                ls.last_keepalive = Instant::now();
+            }
+        }
+    }
+
+    /// Handle a press, release or turn on the main deck
+    ///
+    /// Returns false if the screen or deck is locked, so the input only
+    /// keyed in a PIN digit (or nothing).
+    async fn handle_input(&mut self, event: InputEvent, ls: &mut LoopState) -> bool {
+        // Skip input handling when screen is locked (security)
+        let (is_locked, deck_locked) = {
+            let state = self.state.read().await;
+            (state.screen_locked, state.deck_lock.is_locked())
+        };
+        // While the deck is locked, buttons only key in the PIN
+        if deck_locked && !is_locked {
+            if let InputEvent::ButtonDown(id) = event {
+                if let Some(button) = device_to_logical_button(id) {
+                    self.enter_pin_digit(button).await;
+                    ls.wrote_device();
+                }
+            }
+            return false;
+        }
+        if is_locked {
+            // Silently ignore input when locked
+            return false;
+        }
+
+        // Any input wakes the display from idle
+        self.wake_from_idle(ls).await;
+
+        // Show the press before any action runs; redraw on release to undo the
+        // press animation and pick up toggle state changes
+        if matches!(event, InputEvent::ButtonDown(_)) {
+            sound::play(&self.config.sound, SoundEvent::Press);
+        }
+        if let Some(input) = web::input::Input::from_device(&event) {
+            self.emit(DeckEvent::Input(input));
+        }
+        if let Some(ref mqtt) = self.mqtt {
+            let press = match event {
+                InputEvent::ButtonDown(id) => Some((id, "press")),
+                InputEvent::ButtonUp(id) => Some((id, "release")),
+                _ => None,
+            };
+            let logical = press
+                .and_then(|(id, kind)| device_to_logical_button(id).map(|button| (button, kind)));
+            if let Some((button, kind)) = logical {
+                let profile = self.current_profile_name(&*self.state.read().await);
+                mqtt.button(button, kind, &profile);
+            }
+        }
+        if let Some(ref midi) = self.midi {
+            midi.input(&event);
+        }
+        let press_animation = self.config.device.press_animation;
+        let released = match event {
+            InputEvent::ButtonDown(id) if press_animation => {
+                if let Some(button) = device_to_logical_button(id) {
+                    self.play_press_animation(button).await;
+                }
+                None
+            }
+            InputEvent::ButtonUp(id) => device_to_logical_button(id),
+            _ => None,
+        };
+
+        if let Err(e) = self.input.handle_event(event).await {
+            warn!("Failed to handle input event: {}", e);
+        }
+        // LOCK_DECK just engaged: show the keypad
+        if self.state.read().await.deck_lock.is_locked() {
+            if let Err(e) = self.redraw_all_buttons().await {
+                warn!("Failed to redraw buttons for deck lock: {}", e);
+            }
+        }
+        if let Some(button) = released {
+            if let Err(e) = self.redraw_button(button).await {
+                debug!("Failed to restore pressed button: {}", e);
+            }
+        }
+        if let Err(e) = self.update_display().await {
+            debug!("Failed to update display: {}", e);
+        }
+        ls.wrote_device();
+
+        self.apply_input_changes(ls).await;
+        true
+    }
+
+    /// Carry out brightness, volume and intro changes an action asked for
+    async fn apply_input_changes(&mut self, ls: &mut LoopState) {
+        let (brightness, volume, play_intro) = {
+            let mut state = self.state.write().await;
+            let brightness = std::mem::take(&mut state.brightness_changed);
+            let volume = std::mem::take(&mut state.volume_changed);
+            let brightness = brightness.then_some(state.brightness);
+            let volume = volume.then_some(state.volume);
+            (brightness, volume, std::mem::take(&mut state.play_intro))
+        };
+        if let Some(brightness) = brightness {
+            self.apply_brightness(brightness).await;
+        }
+        if let Some(volume) = volume {
+            tokio::spawn(async move {
+                system::set_system_volume(volume).await;
+            });
+        }
+        if play_intro {
+            self.play_intro().await;
+            ls.wrote_device();
+        }
+    }
+
+    /// Reset the idle timer, restoring brightness if the deck had dimmed
+    async fn wake_from_idle(&mut self, ls: &mut LoopState) {
+        ls.last_activity = Instant::now();
+        ls.wake_flash_until = None;
+        if ls.idle_dimmed {
+            ls.idle_dimmed = false;
+            let brightness = self.state.read().await.brightness;
+            self.apply_brightness(brightness).await;
+        }
+    }
+
+    /// Reconnect in the background so the rest of the loop keeps running
+    async fn poll_reconnect(&mut self, ls: &mut LoopState) {
+        match self.reconnect.take() {
+            Some(task) if task.is_finished() => {
+                if let Ok(d) = task.await {
+                    info!("Reconnected to device");
+                    self.device = Some(d);
+                    self.state.write().await.set_device_health(DeviceHealth::Connected);
+                    self.emit(DeckEvent::Device { connected: true });
+                    if let Err(e) = self.render_initial_display().await {
+                        warn!("Failed to render initial display on reconnect: {}", e);
+                    }
+                    ls.wrote_device();
+                }
+            }
+            Some(task) => self.reconnect = Some(task),
+            None => self.reconnect = Some(self.spawn_reconnect()),
+        }
+    }
+
+    /// Extra decks redraw on their own; their input shows up in the shared
+    /// state (and on the main deck) like any other press
+    async fn update_decks(
+        &mut self,
+        input: Option<(usize, Result<InputEvent>)>,
+        ls: &mut LoopState,
+    ) {
+        if self.service_decks(input).await {
+            self.wake_from_idle(ls).await;
+            if let Err(e) = self.update_display().await {
+                debug!("Failed to update display: {}", e);
+            }
+            ls.wrote_device();
+        }
+    }
+
+    /// Run pending long-press actions (hold-to-activate)
+    async fn check_long_press(&mut self, ls: &mut LoopState) {
+        match self.input.check_long_press().await {
+            Ok(true) => {
+                if let Err(e) = self.update_display().await {
+                    debug!("Failed to update display after long-press: {}", e);
+                }
+                ls.wrote_device();
+            }
+            Err(e) => warn!("Failed to check long-press: {}", e),
+            _ => {}
+        }
+    }
+
+    /// Apply hook updates pushed over the socket, or poll the status file
+    /// while the socket is quiet
+    async fn handle_hook_status(
+        &mut self,
+        status: Option<hooks::ClaudeStatus>,
+        ls: &mut LoopState,
+    ) {
+        // Hook updates pushed over the socket arrive immediately
+        let mut status_result: Result<bool> = Ok(false);
+        if let Some(ref mut hook_rx) = self.hook_rx {
+            let mut pushed: Vec<_> = status.into_iter().collect();
+            while let Ok(status) = hook_rx.try_recv() {
+                pushed.push(status);
+            }
+            if !pushed.is_empty() {
+                ls.last_socket_update = Some(Instant::now());
+            }
+            let mut changed = false;
+            for status in pushed {
+                changed |= self.apply_claude_status(status).await;
+            }
+            status_result = Ok(changed);
+        }
+
+        // Poll Claude Code status file periodically (fallback while the socket is quiet)
+        let socket_active = ls
+            .last_socket_update
+            .is_some_and(|t| t.elapsed() < SOCKET_QUIET_PERIOD);
+        if !socket_active && ls.last_status_check.elapsed() >= ls.status_check_interval {
+            ls.last_status_check = Instant::now();
+            status_result = self.update_from_claude_status().await;
+        }
+
+        match status_result {
+            Ok(true) => self.show_status_change(ls).await,
+            Err(e) => debug!("Failed to update from Claude status: {}", e),
+            _ => {}
+        }
+    }
+
+    async fn show_status_change(&mut self, ls: &mut LoopState) {
+        if let Err(e) = self.update_display().await {
+            debug!("Failed to update display after status change: {}", e);
+        }
+        self.redraw_templated_buttons().await;
+        self.publish_state().await;
+        ls.wrote_device();
+
+        // Briefly brighten an idle deck so the new task is glanceable
+        let (task_started, quiet) = {
+            let mut state = self.state.write().await;
+            (std::mem::take(&mut state.task_started), state.focus_quiet())
+        };
+        if task_started && ls.idle_dimmed && self.config.device.wake_on_hook && !quiet {
+            if ls.wake_flash_until.is_none() {
+                let brightness = self.state.read().await.brightness;
+                self.ramp_brightness(self.idle_brightness(brightness), brightness)
+                    .await;
+            }
+            let flash = Duration::from_millis(self.config.device.wake_flash_ms);
+            ls.wake_flash_until = Some(Instant::now() + flash);
+        }
+    }
+
+    /// Apply scene changes and feedback requested by buttons or the web API
+    async fn apply_pending_requests(&mut self, ls: &mut LoopState) {
+        let scene_request = self.state.write().await.pending_scene.take();
+        if let Some(request) = scene_request {
+            self.handle_scene_request(request).await;
+            ls.wrote_device();
+        }
+
+        // Confirm presses of buttons with `feedback` with a sound
+        let feedback = self.state.write().await.pending_feedback.take();
+        if let Some(kind) = feedback {
+            sound::feedback(&self.config.sound, kind);
+        }
+
+    }
+
+    /// Follow the Claude session into a `[projects]` directory
+    async fn follow_project(&mut self, ls: &mut LoopState) {
+        let cwd = self.state.read().await.cwd.clone();
+        if cwd == ls.last_cwd {
+            return;
+        }
+        ls.last_cwd = cwd;
+        let project = ls
+            .last_cwd
+            .as_deref()
+            .and_then(|cwd| projects::profile_for(&self.config.projects, cwd))
+            .unwrap_or_default()
+            .to_string();
+        let mut state = self.state.write().await;
+        if state.project_profile == project {
+            return;
+        }
+        let profile = self.current_profile_name(&state);
+        state.project_profile = project;
+        if self.current_profile_name(&state) != profile {
+            info!("Switching to the profile for project {:?}", ls.last_cwd);
+            state.folders.clear();
+            let brightness = self.follow_profile_brightness(&mut state);
+            drop(state);
+            if let Err(e) = self.redraw_all_buttons().await {
+                warn!("Failed to redraw buttons on project switch: {}", e);
+            }
+            if let Some(level) = brightness.filter(|_| !ls.idle_dimmed) {
+                self.apply_brightness(level).await;
+            }
+            self.publish_state().await;
+            ls.wrote_device();
+        }
+    }
+
+    /// Show the profile of an app a button just focused or launched
+    async fn show_pending_app(&mut self, ls: &mut LoopState) {
+        let pending_app = self.state.write().await.pending_app.take();
+        let Some(app) = pending_app else {
+            return;
+        };
+        let mut state = self.state.write().await;
+        if state.focused_app != app {
+            info!("Switching to the profile for '{}'", app);
+            let profile = self.current_profile_name(&state);
+            state.focused_app = app.clone();
+            state.focused_bundle_id.clear();
+            state.focused_title.clear();
+            let mut brightness = None;
+            if self.current_profile_name(&state) != profile {
+                state.folders.clear();
+                brightness = self.follow_profile_brightness(&mut state);
+            }
+            drop(state);
+            if let Err(e) = self.redraw_all_buttons().await {
+                warn!("Failed to redraw buttons on app switch: {}", e);
+            }
+            if let Some(level) = brightness.filter(|_| !ls.idle_dimmed) {
+                self.apply_brightness(level).await;
+            }
+            self.publish_state().await;
+            ls.wrote_device();
+        }
+        ls.app_switch = Some((app, Instant::now() + APP_SWITCH_GRACE));
+    }
+
+    /// Put back a snapshot restored through the web API
+    async fn restore_requested_frames(&mut self, ls: &mut LoopState) {
+        if let Some(saved) = self.frames.take_restore() {
+            self.restore_frames(saved).await;
+            ls.wrote_device();
+        }
+    }
+
+    /// Save toggle/scene state so a restart doesn't reset it, and press stats
+    /// every so often
+    async fn save_runtime_state(&mut self, ls: &mut LoopState) {
+        let persisted = {
+            let mut state = self.state.write().await;
+            let pending = std::mem::take(&mut state.persist_pending);
+            pending.then(|| (PersistedState::from_state(&state), state.state_dir()))
+        };
+        if let Some((persisted, dir)) = persisted {
+            if let Err(e) = persisted.save(&dir) {
+                warn!("Failed to save runtime state: {}", e);
+            }
+        }
+
+        if ls.last_stats_save.elapsed() >= STATS_SAVE_INTERVAL {
+            ls.last_stats_save = Instant::now();
+            self.save_stats().await;
+        }
+    }
+
+    /// Activate scenes scheduled for the current minute
+    async fn run_schedule(&mut self, ls: &mut LoopState) {
+        if ls.last_schedule_check.elapsed() < SCHEDULE_CHECK_INTERVAL {
+            return;
+        }
+        ls.last_schedule_check = Instant::now();
+        let now = scenes::local_hour_minute();
+        if now == ls.last_schedule_minute {
+            return;
+        }
+        ls.last_schedule_minute = now;
+        let (all_scenes, _, _) = scenes::load_scene_config(&self.config);
+        if let Some(scene) = scenes::scenes_due(&all_scenes, now.0, now.1).last() {
+            info!("Scheduled scene '{}' at {:02}:{:02}", scene.name, now.0, now.1);
+            self.handle_scene_request(SceneRequest::Activate(scene.name.clone()))
+                .await;
+            ls.wrote_device();
+        }
+    }
+
+    /// Poll focused app in background (osascript is slow ~144ms)
+    async fn poll_focused_app(&mut self, ls: &mut LoopState) {
+        // Check if previous background task completed
+        if let Some(handle) = ls.pending_app_check.take() {
+            if handle.is_finished() {
+                // Until a switched-to app comes up, polls still see the old one
+                let polled = handle.await.ok().flatten().filter(|window| match ls.app_switch {
+                    Some((ref app, until)) => window.app == *app || Instant::now() >= until,
+                    None => true,
+                });
+                if let Some(window) = polled {
+                    self.follow_focused_window(window, ls).await;
+                }
+            } else {
+                // Not finished yet, put it back
+                ls.pending_app_check = Some(handle);
+            }
+        }
+
+        // Spawn new background check if interval elapsed and no pending check
+        if ls.pending_app_check.is_none() && ls.last_app_check.elapsed() >= APP_CHECK_INTERVAL {
+            ls.last_app_check = Instant::now();
+            ls.pending_app_check = Some(tokio::spawn(async {
+                system::get_focused_window_info().await
+            }));
+        }
+    }
+
+    /// Switch profiles once a newly focused window has stayed for the switch delay
+    async fn follow_focused_window(&mut self, window: system::WindowInfo, ls: &mut LoopState) {
+        let switch_delay = Duration::from_millis(self.config.profile_switch.delay_ms);
+        let mut state = self.state.write().await;
+        let changed = state.focused_app != window.app
+            || state.focused_bundle_id != window.bundle_id
+            || state.focused_title != window.title;
+        let settled = match ls.focus_candidate {
+            _ if !changed || switch_delay.is_zero() => true,
+            Some((ref seen, since)) if *seen == window => since.elapsed() >= switch_delay,
+            _ => {
+                ls.focus_candidate = Some((window.clone(), Instant::now()));
+                false
+            }
+        };
+        if settled {
+            ls.focus_candidate = None;
+        }
+        if !(changed && settled) {
+            return;
+        }
+        let profile = self.current_profile_name(&state);
+        if state.focused_app != window.app {
+            info!("Focused app changed: '{}' -> '{}'", state.focused_app, window.app);
+        }
+        state.focused_app = window.app;
+        state.focused_bundle_id = window.bundle_id;
+        state.focused_title = window.title;
+        // Folders belong to the profile they were opened from
+        let mut brightness = None;
+        if self.current_profile_name(&state) != profile {
+            state.folders.clear();
+            brightness = self.follow_profile_brightness(&mut state);
+        }
+        drop(state); // Release lock before redraw
+        if let Err(e) = self.redraw_all_buttons().await {
+            warn!("Failed to redraw buttons on app change: {}", e);
+        }
+        if let Some(level) = brightness.filter(|_| !ls.idle_dimmed) {
+            self.apply_brightness(level).await;
+        }
+        self.publish_state().await;
+        ls.wrote_device();
+    }
+
+    /// Check if screen is locked (for security - disable input when locked)
+    async fn check_screen_lock(&mut self, ls: &mut LoopState) {
+        if ls.last_lock_check.elapsed() < LOCK_CHECK_INTERVAL {
+            return;
+        }
+        ls.last_lock_check = Instant::now();
+        let is_locked = system::is_screen_locked().await;
+        let was_locked = self.state.read().await.screen_locked;
+        if is_locked != was_locked {
+            self.state.write().await.screen_locked = is_locked;
+            if is_locked {
+                info!("Screen locked - input disabled");
+            } else {
+                info!("Screen unlocked - input enabled");
+            }
+            // Update ALL buttons and strip to show locked/unlocked state
+            if let Err(e) = self.redraw_all_buttons().await {
+                warn!("Failed to redraw buttons for lock state: {}", e);
+            }
+            if let Err(e) = self.update_display().await {
+                warn!("Failed to update strip for lock state: {}", e);
+            }
+            ls.wrote_device();
+        }
+    }
+
+    /// Follow the macOS Focus mode (moon on the strip, quieter flashes)
+    async fn check_focus_mode(&mut self, ls: &mut LoopState) {
+        if ls.last_focus_check.elapsed() < FOCUS_CHECK_INTERVAL {
+            return;
+        }
+        ls.last_focus_check = Instant::now();
+        let focus_mode = system::get_focus_mode().await;
+        let mut state = self.state.write().await;
+        if state.focus_mode != focus_mode {
+            info!("Focus mode: {}", focus_mode.as_deref().unwrap_or("off"));
+            state.focus_mode = focus_mode;
+            if state.focus_quiet() {
+                state.waiting_flash_on = false;
+            }
+            drop(state);
+            if let Err(e) = self.update_display().await {
+                warn!("Failed to update strip for focus mode: {}", e);
+            }
+            ls.wrote_device();
+        }
+    }
+
+    /// Record new clipboard text and refresh the clipboard button previews
+    async fn poll_clipboard(&mut self, ls: &mut LoopState) {
+        if let Some(handle) = ls.pending_clipboard_check.take() {
+            if handle.is_finished() {
+                if let Ok(Some(text)) = handle.await {
+                    let changed = self.state.write().await.clipboard.record(&text);
+                    if changed {
+                        if let Err(e) = self.redraw_all_buttons().await {
+                            debug!("Failed to redraw clipboard buttons: {}", e);
+                        }
+                        ls.wrote_device();
+                    }
+                }
+            } else {
+                ls.pending_clipboard_check = Some(handle);
+            }
+        }
+
+        if ls.pending_clipboard_check.is_none()
+            && ls.last_clipboard_check.elapsed() >= CLIPBOARD_CHECK_INTERVAL
+            && self.has_clipboard_buttons()
+        {
+            ls.last_clipboard_check = Instant::now();
+            ls.pending_clipboard_check = Some(tokio::spawn(system::read_clipboard()));
+        }
+    }
+
+    /// Refresh live strip widgets (update_display skips unchanged strips)
+    async fn refresh_widgets(&mut self, ls: &mut LoopState) {
+        if let Some(handle) = ls.pending_cpu_check.take() {
+            if handle.is_finished() {
+                if let Ok(cpu) = handle.await {
+                    self.state.write().await.cpu_pct = cpu;
+                }
+            } else {
+                ls.pending_cpu_check = Some(handle);
+            }
+        }
+
+        if let Some(handle) = ls.pending_now_playing_check.take() {
+            if handle.is_finished() {
+                if let Ok(track) = handle.await {
+                    self.state.write().await.now_playing = track;
+                }
+            } else {
+                ls.pending_now_playing_check = Some(handle);
+            }
+        }
+
+        if ls.last_widget_refresh.elapsed() < WIDGET_REFRESH_INTERVAL {
+            return;
+        }
+        ls.last_widget_refresh = Instant::now();
+        let widgets = self.state.read().await.strip_widgets.clone();
+        if widgets.contains(&StripWidget::Cpu) && ls.pending_cpu_check.is_none() {
+            ls.pending_cpu_check = Some(tokio::spawn(system::get_cpu_usage()));
+        }
+        if widgets.contains(&StripWidget::NowPlaying) && ls.pending_now_playing_check.is_none() {
+            ls.pending_now_playing_check = Some(tokio::spawn(system::get_now_playing()));
+        }
+        if widgets.iter().any(|w| w.is_live()) {
+            if let Err(e) = self.update_display().await {
+                warn!("Failed to refresh strip widgets: {}", e);
+            }
+            ls.wrote_device();
+        }
+        // Clock and clipboard placeholders change without an event
+        if self.redraw_templated_buttons().await {
+            ls.wrote_device();
+        }
+    }
+
+    /// Switch low-power mode when the power source changes
+    async fn check_power(&mut self, ls: &mut LoopState) {
+        if let Some(handle) = ls.pending_power_check.take() {
+            if handle.is_finished() {
+                let on_battery = handle.await.ok().flatten().unwrap_or(false);
+                self.set_low_power(on_battery && self.config.power.low_power_on_battery, ls)
+                    .await;
+            } else {
+                ls.pending_power_check = Some(handle);
+            }
+        }
+
+        let power_check_due = ls
+            .last_power_check
+            .is_none_or(|t| t.elapsed() >= POWER_CHECK_INTERVAL);
+        if ls.pending_power_check.is_none() && power_check_due {
+            ls.last_power_check = Some(Instant::now());
+            ls.pending_power_check = Some(tokio::spawn(system::is_on_battery()));
+        }
+    }
+
+    async fn set_low_power(&mut self, low_power: bool, ls: &mut LoopState) {
+        if low_power == self.low_power {
+            return;
+        }
+        self.low_power = low_power;
+        let power = &self.config.power;
+        if low_power {
+            info!("On battery - entering low-power mode");
+            ls.animation = animation_timer(Duration::from_millis(power.battery_gif_interval_ms));
+            ls.status_check_interval = Duration::from_millis(power.battery_status_poll_ms);
+        } else {
+            info!("On AC power - leaving low-power mode");
+            ls.animation = animation_timer(FRAME_INTERVAL);
+            ls.status_check_interval = self.config.hooks.poll_interval();
+        }
+
+        let brightness = self.state.read().await.brightness;
+        let level = if ls.idle_dimmed {
+            self.idle_brightness(brightness)
+        } else {
+            brightness
+        };
+        self.apply_brightness(level).await;
+        ls.wrote_device();
+    }
+
+    /// Dim the display after idle timeout, and end wake flashes
+    async fn check_idle(&mut self, ls: &mut LoopState) {
+        if ls.last_idle_check.elapsed() < IDLE_CHECK_INTERVAL {
+            return;
+        }
+        ls.last_idle_check = Instant::now();
+        let brightness = self.state.read().await.brightness;
+        let idle_timeout = Duration::from_secs(self.config.device.idle_timeout as u64);
+        if let Some(until) = ls.wake_flash_until {
+            if Instant::now() >= until {
+                ls.wake_flash_until = None;
+                self.ramp_brightness(brightness, self.idle_brightness(brightness))
+                    .await;
+                ls.wrote_device();
+            }
+        } else if !ls.idle_dimmed
+            && self.config.device.idle_timeout > 0
+            && ls.last_activity.elapsed() >= idle_timeout
+        {
+            info!(
+                "Idle for {}s - dimming display",
+                self.config.device.idle_timeout
+            );
+            ls.idle_dimmed = true;
+            self.apply_brightness(self.idle_brightness(brightness)).await;
+            ls.wrote_device();
+        }
+    }
+
+    /// Slow-tick work: the waiting flash, marquee, timer, fetched emoji and
+    /// banner countdown
+    async fn run_housekeeping(&mut self, ls: &mut LoopState) {
+        // Flash the LCD strip when waiting for user input (not during Focus)
+        if ls.housekeeping_ticks.is_multiple_of(WAITING_FLASH_TICKS) {
+            let mut state = self.state.write().await;
+            if state.waiting_for_input && !state.focus_quiet() {
+                state.waiting_flash_on = !state.waiting_flash_on;
+                drop(state);
+                if let Err(e) = self.update_display().await {
+                    debug!("Failed to update display for waiting flash: {}", e);
+                }
+                ls.wrote_device();
+            } else if state.waiting_flash_on {
+                // Reset flash state when no longer waiting
+                state.waiting_flash_on = false;
+            }
+        }
+
+        // Scroll strip text that doesn't fit its quadrant
+        if ls.device_ready() {
+            let scrolls = {
+                let mut state = self.state.write().await;
+                let scrolls = self.display.strip_scrolls(&state);
+                // Start from the beginning whenever text starts overflowing
+                state.marquee_tick = if scrolls { state.marquee_tick.wrapping_add(1) } else { 0 };
+                scrolls
+            };
+            if scrolls {
+                if let Err(e) = self.update_display().await {
+                    debug!("Failed to update display for marquee: {}", e);
+                }
+                ls.wrote_device();
+            }
+        }
+
+        self.count_down_timer(ls).await;
+
+        // Show emoji that finished downloading in the background
+        if display::emoji::take_fetched() {
+            if let Err(e) = self.redraw_all_buttons().await {
+                debug!("Failed to redraw buttons for fetched emoji: {}", e);
+            }
+            ls.wrote_device();
+        }
+
+        // Count down the banner's retry delay
+        let countdown = {
+            let state = self.state.read().await;
+            let banner = state.banner.as_ref();
+            banner.and_then(|b| b.retry_in(Instant::now()))
+        };
+        if countdown != ls.last_banner_countdown {
+            ls.last_banner_countdown = countdown;
+            if let Err(e) = self.update_display().await {
+                debug!("Failed to update display for banner: {}", e);
+            }
+            ls.wrote_device();
+        }
+    }
+
+    /// Count the timer down, redrawing as its display changes and alerting
+    /// once it fires
+    async fn count_down_timer(&mut self, ls: &mut LoopState) {
+        let (fired, view) = {
+            let mut state = self.state.write().await;
+            let now = Instant::now();
+            (state.timer.poll(now), state.timer.view(now))
+        };
+        if fired {
+            info!("Timer finished");
+            if self.config.timer.notify {
+                notifications::notify_timer(self.config.timer.duration());
+            }
+        }
+        if view != ls.last_timer_view {
+            ls.last_timer_view = view;
+            if let Err(e) = self.update_display().await {
+                debug!("Failed to update display for timer: {}", e);
+            }
+            let buttons = self.find_custom_buttons(&*self.state.read().await, "TIMER");
+            for button_id in buttons {
+                if let Err(e) = self.redraw_button(button_id).await {
+                    debug!("Failed to redraw timer button {}: {}", button_id, e);
+                }
+            }
+            ls.wrote_device();
+        }
+    }
+
+    /// Redraw whatever a confirm, session switch, picker or overlay changed
+    async fn redraw_changed_views(
+        &mut self,
+        on_housekeeping: bool,
+        on_animation: bool,
+        ls: &mut LoopState,
+    ) {
+        // Blink a `confirm` button until its second press or the window ends
+        let confirm_view = self.state.read().await.confirm_view(Instant::now());
+        if confirm_view != ls.last_confirm_view {
+            let buttons = [ls.last_confirm_view, confirm_view].into_iter().flatten();
+            let mut buttons: Vec<u8> = buttons.map(|(button, _)| button).collect();
+            buttons.dedup();
+            ls.last_confirm_view = confirm_view;
+            for button_id in buttons {
+                if let Err(e) = self.redraw_button(button_id).await {
+                    debug!("Failed to redraw confirm button {}: {}", button_id, e);
+                }
+            }
+            ls.wrote_device();
+        }
+
+        self.show_switched_session(ls).await;
+
+        // Redraw the strip when the session picker opens or closes
+        let session_picker_active = self.state.read().await.sessions.picker().is_some();
+        if session_picker_active != ls.session_picker_was_active {
+            if let Err(e) = self.update_display().await {
+                debug!("Failed to update display for session picker: {}", e);
+            }
+            ls.wrote_device();
+        }
+        ls.session_picker_was_active = session_picker_active;
+
+        // Redraw the strip when the agents widget goes back to its summary
+        let agent_view_active = self.state.read().await.agents.view().is_some();
+        if ls.agent_view_was_active && !agent_view_active {
+            if let Err(e) = self.update_display().await {
+                debug!("Failed to update display after agent view: {}", e);
+            }
+            ls.wrote_device();
+        }
+        ls.agent_view_was_active = agent_view_active;
+
+        // Check if volume overlay just expired (transition active→inactive)
+        let volume_overlay_active = self.state.read().await.is_volume_display_active();
+        if ls.volume_overlay_was_active && !volume_overlay_active {
+            // Overlay just expired, refresh display to restore STATUS quadrant
+            if let Err(e) = self.update_display().await {
+                debug!("Failed to update display after volume overlay expired: {}", e);
+            }
+            ls.wrote_device();
+        }
+        ls.volume_overlay_was_active = volume_overlay_active;
+
+        // Check if brightness overlay just expired (transition active→inactive)
+        let brightness_overlay_active = self.state.read().await.is_brightness_display_active();
+        if ls.brightness_overlay_was_active && !brightness_overlay_active {
+            // Overlay just expired, refresh display to restore DETAIL quadrant
+            if let Err(e) = self.update_display().await {
+                debug!("Failed to update display after brightness overlay expired: {}", e);
+            }
+            ls.wrote_device();
+        }
+        ls.brightness_overlay_was_active = brightness_overlay_active;
+
+        // Slide a knob's strip overlay in, and restore the quadrants once it expires
+        if on_housekeeping || on_animation {
+            let now = Instant::now();
+            let overlay = self.state.read().await.strip_overlay;
+            let expired = overlay.is_some_and(|overlay| !overlay.is_active(now));
+            if expired {
+                self.state.write().await.strip_overlay = None;
+            }
+            let sliding = on_animation
+                && overlay.is_some_and(|overlay| overlay.is_sliding(now))
+                && ls.device_ready();
+            if expired || sliding {
+                if let Err(e) = self.update_display().await {
+                    debug!("Failed to update display for the strip overlay: {}", e);
+                }
+                ls.wrote_device();
+            }
+        }
+
+        // Swap to/from the permission quick actions or a folder
+        let button_overlay_active = {
+            let state = self.state.read().await;
+            (Overlay::active(&state), state.folders.len())
+        };
+        if button_overlay_active != ls.button_overlay_was_active {
+            if let Err(e) = self.redraw_all_buttons().await {
+                debug!("Failed to redraw buttons for overlay change: {}", e);
+            }
+            ls.wrote_device();
+        }
+        ls.button_overlay_was_active = button_overlay_active;
+    }
+
+    /// Show the session picked on the strip, and give it keyboard focus
+    async fn show_switched_session(&mut self, ls: &mut LoopState) {
+        let switched = {
+            let mut state = self.state.write().await;
+            std::mem::take(&mut state.sessions.switched)
+                .then(|| state.sessions.selected().map(|s| s.status.clone()))
+        };
+        let Some(picked) = switched else {
+            return;
+        };
+        if let Some(status) = picked {
+            if let Some(tty) = status.tty.clone() {
+                tokio::spawn(async move { system::focus_terminal_tty(&tty).await });
+            }
+            self.apply_claude_status(status).await;
+        }
+        if let Err(e) = self.update_display().await {
+            debug!("Failed to update display after session switch: {}", e);
+        }
+        self.publish_state().await;
+        ls.wrote_device();
+    }
+
+    /// Fast-tick work: the attention glow, button flashes and GIF frames,
+    /// each waiting out the device cooldown to avoid HID conflicts
+    async fn run_animation_frame(&mut self, glowing: bool, ls: &mut LoopState) {
+        if glowing && ls.device_ready() {
+            self.pulse_attention_glow(ls).await;
+        }
+
+        // Put flashed buttons back once their flash has shown
+        if ls.device_ready() {
+            let expired = self.state.write().await.take_expired_flashes(Instant::now());
+            for button_id in &expired {
+                if let Err(e) = self.redraw_button(*button_id).await {
+                    debug!("Failed to redraw button {} after flash: {}", button_id, e);
+                }
+            }
+            if !expired.is_empty() {
+                ls.wrote_device();
+            }
+        }
+
+        if ls.device_ready() {
+            if let Err(e) = self.update_gif_animations().await {
+                debug!("GIF animation update skipped (device busy): {}", e);
+            } else {
+                ls.wrote_device();
+            }
+        }
+    }
+
+    /// Pulse the attention glow while waiting for input (not during Focus)
+    async fn pulse_attention_glow(&mut self, ls: &mut LoopState) {
+        let level = {
+            let state = self.state.read().await;
+            if state.waiting_for_input && !state.focus_quiet() {
+                let started = *ls.attention_started.get_or_insert_with(Instant::now);
+                display::attention::glow_level(started.elapsed(), self.config.attention.intensity)
+            } else {
+                ls.attention_started = None;
+                0
+            }
+        };
+        let changed = {
+            let mut state = self.state.write().await;
+            std::mem::replace(&mut state.attention_glow, level) != level
+        };
+        if changed {
+            if let Some(device) = self.device.as_ref() {
+                if let Err(e) = self.send_changed_buttons(device).await {
+                    debug!("Failed to redraw buttons for attention glow: {}", e);
+                }
+            }
+            if let Err(e) = self.update_display().await {
+                debug!("Failed to update display for attention glow: {}", e);
+            }
+            ls.wrote_device();
+        }
+    }
+}
+
+/// Next input from the main deck (never resolves while disconnected)
+async fn next_device_event<D: DeckDevice>(device: Option<&mut D>) -> Result<InputEvent> {
+    match device {
+        Some(device) => device.next_event().await,
+        None => std::future::pending().await,
+    }
+}
+
+/// Next status pushed over the hook socket (None once the listener is gone)
+async fn next_hook_status(
+    hook_rx: Option<&mut mpsc::Receiver<hooks::ClaudeStatus>>,
+) -> Option<hooks::ClaudeStatus> {
+    hook_rx?.recv().await
+}
+
+/// Timer for GIF frames and held buttons, skipping ticks missed while busy
+fn animation_timer(period: Duration) -> Interval {
+    let mut timer = tokio::time::interval(period);
+    timer.set_missed_tick_behavior(MissedTickBehavior::Skip);
+    timer
+}
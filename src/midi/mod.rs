@@ -0,0 +1,174 @@
+//! MIDI output of deck events on a virtual port
+//!
+//! With `[midi] enabled = true` the deck shows up as a MIDI source named
+//! `port_name`, so DAWs and anything with MIDI learn can map it:
+//!
+//! - buttons 0-9 send Note On (velocity 127) when pressed and Note Off when
+//!   released, from `base_note` up
+//! - encoder presses do the same on the next four notes
+//! - encoder turns send a control change on `base_cc` + encoder, either
+//!   relative (64 plus the detents turned) or an absolute 0-127 value
+//!
+//! Messages go out on the main deck's input only, and not while it's locked.
+
+mod port;
+
+use std::sync::mpsc;
+use tracing::{debug, info, warn};
+
+use crate::config::{MidiConfig, MidiEncoderMode};
+use crate::device::InputEvent;
+
+/// Notes above `base_note` taken by the buttons before the encoder presses
+const BUTTON_NOTES: u8 = 10;
+/// Encoders with a control change each
+const ENCODERS: usize = 4;
+
+/// A message sent to the port
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MidiMessage {
+    NoteOn { note: u8, velocity: u8 },
+    NoteOff { note: u8 },
+    ControlChange { controller: u8, value: u8 },
+}
+
+impl MidiMessage {
+    /// Wire bytes on `channel` (1-16)
+    pub fn bytes(self, channel: u8) -> [u8; 3] {
+        let channel = channel.clamp(1, 16) - 1;
+        match self {
+            Self::NoteOn { note, velocity } => [0x90 | channel, note & 0x7F, velocity & 0x7F],
+            Self::NoteOff { note } => [0x80 | channel, note & 0x7F, 0],
+            Self::ControlChange { controller, value } => {
+                [0xB0 | channel, controller & 0x7F, value & 0x7F]
+            }
+        }
+    }
+}
+
+/// Turns deck input into messages, keeping absolute encoder values
+struct Mapping {
+    config: MidiConfig,
+    values: [u8; ENCODERS],
+}
+
+impl Mapping {
+    fn new(config: MidiConfig) -> Self {
+        Self {
+            config,
+            values: [64; ENCODERS],
+        }
+    }
+
+    /// The message for an input, if it has one
+    fn message(&mut self, event: &InputEvent) -> Option<MidiMessage> {
+        let note = |offset: u8| {
+            let note = self.config.base_note.checked_add(offset)?;
+            (note <= 127).then_some(note)
+        };
+        match *event {
+            InputEvent::ButtonDown(id) if id < BUTTON_NOTES => {
+                Some(MidiMessage::NoteOn { note: note(id)?, velocity: 127 })
+            }
+            InputEvent::ButtonUp(id) if id < BUTTON_NOTES => {
+                Some(MidiMessage::NoteOff { note: note(id)? })
+            }
+            InputEvent::EncoderPress(encoder) => Some(MidiMessage::NoteOn {
+                note: note(BUTTON_NOTES.checked_add(encoder)?)?,
+                velocity: 127,
+            }),
+            InputEvent::EncoderRelease(encoder) => Some(MidiMessage::NoteOff {
+                note: note(BUTTON_NOTES.checked_add(encoder)?)?,
+            }),
+            InputEvent::EncoderRotate { encoder, direction } => {
+                let controller = self.config.base_cc.checked_add(encoder)?;
+                if controller > 127 || direction == 0 {
+                    return None;
+                }
+                let value = match self.config.encoder_mode {
+                    MidiEncoderMode::Relative => (64 + direction as i16).clamp(1, 127) as u8,
+                    MidiEncoderMode::Absolute => {
+                        let value = self.values.get_mut(encoder as usize)?;
+                        *value = (*value as i16 + direction as i16).clamp(0, 127) as u8;
+                        *value
+                    }
+                };
+                Some(MidiMessage::ControlChange { controller, value })
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Handle for sending deck input to the MIDI port; cheap to clone
+#[derive(Debug, Clone)]
+pub struct MidiOutput {
+    tx: mpsc::Sender<InputEvent>,
+}
+
+impl MidiOutput {
+    /// Send the message for an input event, if it has one
+    pub fn input(&self, event: &InputEvent) {
+        let _ = self.tx.send(event.clone());
+    }
+}
+
+/// Create the virtual port and a thread writing to it
+///
+/// Returns None (after logging why) when the port can't be created.
+pub fn spawn(config: &MidiConfig) -> Option<MidiOutput> {
+    let port = match port::VirtualPort::create(&config.port_name) {
+        Ok(port) => port,
+        Err(e) => {
+            warn!("MIDI output disabled: {:#}", e);
+            return None;
+        }
+    };
+    info!("MIDI output on virtual port '{}'", config.port_name);
+
+    let (tx, rx) = mpsc::channel::<InputEvent>();
+    let channel = config.channel;
+    let mut mapping = Mapping::new(config.clone());
+    std::thread::spawn(move || {
+        for event in rx {
+            let Some(message) = mapping.message(&event) else {
+                continue;
+            };
+            if let Err(e) = port.send(&message.bytes(channel)) {
+                debug!("MIDI send failed: {:#}", e);
+            }
+        }
+    });
+    Some(MidiOutput { tx })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_midi_mapping() {
+        let mut mapping = Mapping::new(MidiConfig::default());
+        let down = mapping.message(&InputEvent::ButtonDown(3)).unwrap();
+        assert_eq!(down.bytes(1), [0x90, 39, 127]);
+        let up = mapping.message(&InputEvent::ButtonUp(3)).unwrap();
+        assert_eq!(up.bytes(16), [0x8F, 39, 0]);
+        let press = mapping.message(&InputEvent::EncoderPress(1)).unwrap();
+        assert_eq!(press, MidiMessage::NoteOn { note: 47, velocity: 127 });
+
+        let turn = InputEvent::EncoderRotate { encoder: 2, direction: -1 };
+        assert_eq!(mapping.message(&turn).unwrap().bytes(1), [0xB0, 22, 63]);
+        assert_eq!(mapping.message(&InputEvent::StripTouch(0)), None);
+
+        let mut absolute = Mapping::new(MidiConfig {
+            encoder_mode: MidiEncoderMode::Absolute,
+            base_note: 120,
+            ..MidiConfig::default()
+        });
+        absolute.message(&turn);
+        let value = absolute.message(&turn).unwrap();
+        assert_eq!(value, MidiMessage::ControlChange { controller: 22, value: 62 });
+        // Past note 127 there's nothing to send
+        assert_eq!(absolute.message(&InputEvent::ButtonDown(9)), None);
+    }
+}
@@ -0,0 +1,150 @@
+//! The virtual MIDI source other apps connect to (CoreMIDI on macOS)
+
+#[cfg(target_os = "macos")]
+mod coremidi {
+    use anyhow::{bail, Result};
+    use std::ffi::{c_void, CString};
+    use std::os::raw::c_char;
+
+    type OSStatus = i32;
+    type MIDIObjectRef = u32;
+    type CFStringRef = *const c_void;
+
+    /// kCFStringEncodingUTF8
+    const UTF8: u32 = 0x0800_0100;
+
+    #[link(name = "CoreFoundation", kind = "framework")]
+    extern "C" {
+        fn CFStringCreateWithCString(
+            alloc: *const c_void,
+            c_str: *const c_char,
+            encoding: u32,
+        ) -> CFStringRef;
+        fn CFRelease(cf: *const c_void);
+    }
+
+    #[link(name = "CoreMIDI", kind = "framework")]
+    extern "C" {
+        fn MIDIClientCreate(
+            name: CFStringRef,
+            notify_proc: *const c_void,
+            notify_ref_con: *mut c_void,
+            out_client: *mut MIDIObjectRef,
+        ) -> OSStatus;
+        fn MIDISourceCreate(
+            client: MIDIObjectRef,
+            name: CFStringRef,
+            out_src: *mut MIDIObjectRef,
+        ) -> OSStatus;
+        fn MIDIPacketListInit(packet_list: *mut c_void) -> *mut c_void;
+        fn MIDIPacketListAdd(
+            packet_list: *mut c_void,
+            list_size: usize,
+            current: *mut c_void,
+            time: u64,
+            data_len: usize,
+            data: *const u8,
+        ) -> *mut c_void;
+        fn MIDIReceived(src: MIDIObjectRef, packet_list: *const c_void) -> OSStatus;
+        fn MIDIEndpointDispose(endpoint: MIDIObjectRef) -> OSStatus;
+        fn MIDIClientDispose(client: MIDIObjectRef) -> OSStatus;
+    }
+
+    /// A MIDI source named after the deck, removed again when dropped
+    pub struct VirtualPort {
+        client: MIDIObjectRef,
+        source: MIDIObjectRef,
+    }
+
+    impl VirtualPort {
+        pub fn create(name: &str) -> Result<Self> {
+            let c_name = CString::new(name)?;
+            // SAFETY: c_name is a valid NUL-terminated string; the returned
+            // CFString is released below once CoreMIDI has copied it
+            let cf_name =
+                unsafe { CFStringCreateWithCString(std::ptr::null(), c_name.as_ptr(), UTF8) };
+            if cf_name.is_null() {
+                bail!("invalid MIDI port name {:?}", name);
+            }
+            let (mut client, mut source) = (0, 0);
+            // SAFETY: no notification callback is registered, and the out
+            // pointers are valid for the calls
+            let status = unsafe {
+                let notify = (std::ptr::null(), std::ptr::null_mut());
+                let status = MIDIClientCreate(cf_name, notify.0, notify.1, &mut client);
+                if status == 0 {
+                    MIDISourceCreate(client, cf_name, &mut source)
+                } else {
+                    status
+                }
+            };
+            // SAFETY: created above and not used again
+            unsafe { CFRelease(cf_name) };
+            if status != 0 {
+                if client != 0 {
+                    // SAFETY: the client was created above
+                    unsafe { MIDIClientDispose(client) };
+                }
+                bail!("CoreMIDI couldn't create the port (OSStatus {})", status);
+            }
+            Ok(Self { client, source })
+        }
+
+        /// Send one message to everything listening to the port
+        pub fn send(&self, message: &[u8]) -> Result<()> {
+            // A MIDIPacketList with room for one short packet (4-byte aligned)
+            let mut buffer = [0u32; 16];
+            let size = std::mem::size_of_val(&buffer);
+            let list = buffer.as_mut_ptr() as *mut c_void;
+            // SAFETY: the list points at `size` writable bytes, and CoreMIDI
+            // fills in the packet within them (time 0 is "now")
+            let status = unsafe {
+                let packet = MIDIPacketListInit(list);
+                let packet =
+                    MIDIPacketListAdd(list, size, packet, 0, message.len(), message.as_ptr());
+                if packet.is_null() {
+                    bail!("MIDI message too long ({} bytes)", message.len());
+                }
+                MIDIReceived(self.source, list)
+            };
+            if status != 0 {
+                bail!("MIDIReceived failed (OSStatus {})", status);
+            }
+            Ok(())
+        }
+    }
+
+    impl Drop for VirtualPort {
+        fn drop(&mut self) {
+            // SAFETY: both were created by `create` and are disposed once
+            unsafe {
+                MIDIEndpointDispose(self.source);
+                MIDIClientDispose(self.client);
+            }
+        }
+    }
+}
+
+#[cfg(target_os = "macos")]
+pub use coremidi::VirtualPort;
+
+#[cfg(not(target_os = "macos"))]
+mod unsupported {
+    use anyhow::{bail, Result};
+
+    /// Virtual ports need CoreMIDI, so there's none to create here
+    pub struct VirtualPort;
+
+    impl VirtualPort {
+        pub fn create(_name: &str) -> Result<Self> {
+            bail!("virtual MIDI ports are only supported on macOS")
+        }
+
+        pub fn send(&self, _message: &[u8]) -> Result<()> {
+            Ok(())
+        }
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+pub use unsupported::VirtualPort;
@@ -0,0 +1,155 @@
+//! Entries for the model selector, and the labels they are shown with
+//!
+//! The list starts with `[models] available`. With `from_claude_code`, it
+//! also gets the `model` set in `~/.claude/settings.json` and the models used
+//! in recent Claude Code sessions (read from the transcripts under
+//! `~/.claude/projects`). Full model ids get a short label such as "OPUS 4.6"
+//! unless `[models.labels]` names one; the id is still what `/model` is sent.
+
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use tracing::debug;
+
+use crate::config::ModelsConfig;
+
+/// Most recently written transcripts checked for models
+const RECENT_SESSIONS: usize = 10;
+/// Only the end of each transcript is read
+const TRANSCRIPT_TAIL_BYTES: u64 = 256 * 1024;
+
+/// Models for the selector, in order and without repeats
+pub fn available(config: &ModelsConfig) -> Vec<String> {
+    let mut models = config.available.clone();
+    if config.from_claude_code {
+        if let Some(dir) = claude_dir() {
+            let settings = std::fs::read_to_string(dir.join("settings.json")).ok();
+            models.extend(settings.as_deref().and_then(settings_model));
+            models.extend(recent_session_models(&dir.join("projects")));
+        }
+    }
+    let mut seen = HashSet::new();
+    models.retain(|model| !model.is_empty() && seen.insert(model.clone()));
+    models
+}
+
+/// Label for a model: a `[models.labels]` entry, else a short form of a full id
+pub fn label(model: &str, labels: &BTreeMap<String, String>) -> String {
+    if let Some(label) = labels.get(model) {
+        return label.clone();
+    }
+    let Some(id) = model.strip_prefix("claude-") else {
+        return model.to_string();
+    };
+    // "opus-4-6", "sonnet-4-5-20250929", "3-5-haiku-20241022"
+    let (mut family, mut version) = (Vec::new(), Vec::new());
+    for part in id.split('-') {
+        match part.parse::<u32>() {
+            Ok(_) if part.len() <= 2 => version.push(part),
+            Ok(_) => {} // release date
+            Err(_) => family.push(part),
+        }
+    }
+    match (family.is_empty(), version.is_empty()) {
+        (false, false) => format!("{} {}", family.join(" "), version.join(".")),
+        (false, true) => family.join(" "),
+        _ => model.to_string(),
+    }
+}
+
+/// `~/.claude`
+fn claude_dir() -> Option<PathBuf> {
+    std::env::var("HOME").ok().map(|home| Path::new(&home).join(".claude"))
+}
+
+/// The `model` set in Claude Code's settings.json
+pub fn settings_model(settings: &str) -> Option<String> {
+    let json: serde_json::Value = serde_json::from_str(settings).ok()?;
+    json.get("model")?.as_str().map(str::to_string)
+}
+
+/// Models used in the most recent session transcripts, latest first
+fn recent_session_models(projects: &Path) -> Vec<String> {
+    let mut transcripts: Vec<(SystemTime, PathBuf)> = std::fs::read_dir(projects)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter_map(|project| std::fs::read_dir(project.path()).ok())
+        .flatten()
+        .flatten()
+        .filter(|entry| entry.path().extension().is_some_and(|ext| ext == "jsonl"))
+        .filter_map(|entry| Some((entry.metadata().ok()?.modified().ok()?, entry.path())))
+        .collect();
+    transcripts.sort_by_key(|(modified, _)| std::cmp::Reverse(*modified));
+
+    let mut models = Vec::new();
+    for (_, path) in transcripts.into_iter().take(RECENT_SESSIONS) {
+        match read_tail(&path) {
+            Ok(text) => models.extend(transcript_models(&text)),
+            Err(e) => debug!("Skipping transcript {}: {}", path.display(), e),
+        }
+    }
+    models
+}
+
+fn read_tail(path: &Path) -> std::io::Result<String> {
+    use std::io::{Read, Seek, SeekFrom};
+    let mut file = std::fs::File::open(path)?;
+    let len = file.metadata()?.len();
+    file.seek(SeekFrom::Start(len.saturating_sub(TRANSCRIPT_TAIL_BYTES)))?;
+    let mut bytes = Vec::new();
+    file.read_to_end(&mut bytes)?;
+    Ok(String::from_utf8_lossy(&bytes).to_string())
+}
+
+/// Models of the assistant messages in a transcript, latest first
+fn transcript_models(text: &str) -> Vec<String> {
+    let mut models: Vec<String> = Vec::new();
+    for line in text.lines().rev() {
+        let Ok(entry) = serde_json::from_str::<serde_json::Value>(line) else {
+            continue; // including a line cut off by the tail read
+        };
+        let Some(model) = entry.pointer("/message/model").and_then(|m| m.as_str()) else {
+            continue;
+        };
+        // Messages Claude Code writes itself (e.g. errors) are "<synthetic>"
+        if !model.starts_with('<') && !models.iter().any(|m| m == model) {
+            models.push(model.to_string());
+        }
+    }
+    models
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_models() {
+        let transcript = r#"ion":"1"}
+{"type":"assistant","message":{"model":"claude-sonnet-4-5-20250929","role":"assistant"}}
+{"type":"user","message":{"role":"user","content":"hi"}}
+{"type":"assistant","message":{"model":"<synthetic>"}}
+{"type":"assistant","message":{"model":"claude-opus-4-6"}}
+{"type":"assistant","message":{"model":"claude-opus-4-6"}}"#;
+        assert_eq!(
+            transcript_models(transcript),
+            vec!["claude-opus-4-6", "claude-sonnet-4-5-20250929"]
+        );
+        assert_eq!(settings_model(r#"{"model":"opus","env":{}}"#).as_deref(), Some("opus"));
+
+        let mut labels = BTreeMap::new();
+        labels.insert("opusplan".to_string(), "Plan".to_string());
+        assert_eq!(label("claude-opus-4-6", &labels), "opus 4.6");
+        assert_eq!(label("claude-sonnet-4-5-20250929", &labels), "sonnet 4.5");
+        assert_eq!(label("claude-3-5-haiku-20241022", &labels), "haiku 3.5");
+        assert_eq!(label("opusplan", &labels), "Plan");
+        assert_eq!(label("sonnet", &labels), "sonnet");
+
+        let config = ModelsConfig {
+            available: vec!["opus".into(), "sonnet".into(), "opus".into()],
+            ..ModelsConfig::default()
+        };
+        assert_eq!(available(&config), vec!["opus", "sonnet"]);
+    }
+}
@@ -0,0 +1,216 @@
+//! HTTP status monitors (`[[monitors]]`), e.g. CI runs or service health
+//!
+//! Each monitor fetches its URL on an interval and reduces the response to
+//! green, yellow or red. Errors and non-2xx responses are red. For a JSON
+//! body, the field at `json_pointer` decides, else the first `conclusion` or
+//! `status` found, so GitHub Actions runs and typical health endpoints work
+//! without configuration. Buttons with a `monitor` action take the color and
+//! open the monitor's page when pressed; the `monitors` strip widget shows
+//! them all.
+
+use anyhow::{bail, Result};
+use image::Rgb;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{mpsc, RwLock as TokioRwLock};
+use tracing::{debug, info};
+
+use crate::config::MonitorConfig;
+use crate::display::renderer::{
+    BRIGHT_GRAY, BRIGHT_GREEN, BRIGHT_ORANGE, BRIGHT_RED, GRAY, GREEN, ORANGE, RED,
+};
+use crate::state::AppState;
+use crate::AppCommand;
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Fields checked for a status word when no `json_pointer` is set
+const STATUS_FIELDS: [&str; 2] = ["conclusion", "status"];
+
+/// What a monitor's last check found
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Health {
+    /// Not checked yet
+    #[default]
+    Unknown,
+    /// Green
+    Up,
+    /// Yellow: running, queued or degraded
+    Pending,
+    /// Red: failing, unreachable or an error response
+    Down,
+}
+
+impl Health {
+    /// Normal and bright colors for a monitor button
+    pub fn colors(self) -> (Rgb<u8>, Rgb<u8>) {
+        match self {
+            Health::Unknown => (GRAY, BRIGHT_GRAY),
+            Health::Up => (GREEN, BRIGHT_GREEN),
+            Health::Pending => (ORANGE, BRIGHT_ORANGE),
+            Health::Down => (RED, BRIGHT_RED),
+        }
+    }
+
+    /// Health named by a status word (e.g. "success", "in_progress", "failure")
+    fn from_word(word: &str) -> Option<Self> {
+        match word.to_lowercase().as_str() {
+            "success" | "ok" | "up" | "healthy" | "pass" | "passed" | "green"
+            | "operational" | "completed" | "neutral" | "skipped" => Some(Health::Up),
+            "pending" | "queued" | "in_progress" | "running" | "waiting" | "requested"
+            | "degraded" | "warn" | "warning" | "yellow" => Some(Health::Pending),
+            "failure" | "failed" | "fail" | "error" | "down" | "unhealthy" | "critical"
+            | "cancelled" | "timed_out" | "action_required" | "red" => Some(Health::Down),
+            _ => None,
+        }
+    }
+}
+
+/// A monitor's latest result, shown on buttons and the strip
+#[derive(Debug, Clone, PartialEq)]
+pub struct MonitorStatus {
+    pub name: String,
+    /// Page opened by pressing a monitor button
+    pub open_url: String,
+    pub health: Health,
+    /// Status word or error from the last check
+    pub detail: String,
+}
+
+/// Start polling every configured monitor
+///
+/// Results are written to `state.monitors`, with a redraw sent on `redraw`
+/// whenever a monitor's health changes.
+pub fn spawn(
+    configs: &[MonitorConfig],
+    state: Arc<TokioRwLock<AppState>>,
+    redraw: mpsc::Sender<AppCommand>,
+) -> Result<()> {
+    let client = reqwest::Client::builder()
+        .user_agent(concat!("claude-deck/", env!("CARGO_PKG_VERSION")))
+        .timeout(REQUEST_TIMEOUT)
+        .build()?;
+    for (index, config) in configs.iter().enumerate() {
+        info!("Monitoring '{}' every {}s", config.name, config.interval().as_secs());
+        tokio::spawn(poll(
+            index,
+            config.clone(),
+            client.clone(),
+            Arc::clone(&state),
+            redraw.clone(),
+        ));
+    }
+    Ok(())
+}
+
+/// Initial (unchecked) statuses, in config order
+pub fn statuses(configs: &[MonitorConfig]) -> Vec<MonitorStatus> {
+    configs
+        .iter()
+        .map(|config| MonitorStatus {
+            name: config.name.clone(),
+            open_url: config.open_url.clone().unwrap_or_else(|| config.url.clone()),
+            health: Health::Unknown,
+            detail: String::new(),
+        })
+        .collect()
+}
+
+async fn poll(
+    index: usize,
+    config: MonitorConfig,
+    client: reqwest::Client,
+    state: Arc<TokioRwLock<AppState>>,
+    redraw: mpsc::Sender<AppCommand>,
+) {
+    let mut interval = tokio::time::interval(config.interval());
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    loop {
+        interval.tick().await;
+        let (health, detail) = match check(&client, &config).await {
+            Ok(result) => result,
+            Err(e) => (Health::Down, e.to_string()),
+        };
+        debug!("Monitor '{}': {:?} ({})", config.name, health, detail);
+
+        let changed = {
+            let mut state = state.write().await;
+            let Some(status) = state.monitors.get_mut(index) else {
+                return;
+            };
+            let changed = status.health != health;
+            status.health = health;
+            status.detail = detail;
+            changed
+        };
+        if changed {
+            let _ = redraw.try_send(AppCommand::RedrawButtons);
+            let _ = redraw.try_send(AppCommand::RefreshStrip);
+        }
+    }
+}
+
+async fn check(client: &reqwest::Client, config: &MonitorConfig) -> Result<(Health, String)> {
+    let mut request = client.get(&config.url);
+    for (name, value) in &config.headers {
+        request = request.header(name, value);
+    }
+    let response = request.send().await?;
+    let status = response.status();
+    if !status.is_success() {
+        bail!("HTTP {}", status.as_u16());
+    }
+    let body = response.text().await?;
+    Ok(classify(&body, config.json_pointer.as_deref()))
+}
+
+/// Health of a successful response's body
+fn classify(body: &str, json_pointer: Option<&str>) -> (Health, String) {
+    let Ok(json) = serde_json::from_str::<serde_json::Value>(body) else {
+        return (Health::Up, "OK".to_string());
+    };
+    let word = match json_pointer {
+        Some(pointer) => json.pointer(pointer).and_then(|v| v.as_str()),
+        None => find_status(&json),
+    };
+    match word {
+        Some(word) => (Health::from_word(word).unwrap_or(Health::Up), word.to_string()),
+        None => (Health::Up, "OK".to_string()),
+    }
+}
+
+/// First status word in a JSON document, checking each object's own fields
+/// before the objects inside it
+fn find_status(json: &serde_json::Value) -> Option<&str> {
+    match json {
+        serde_json::Value::Object(map) => STATUS_FIELDS
+            .iter()
+            .find_map(|field| map.get(*field).and_then(|v| v.as_str()))
+            .or_else(|| map.values().find_map(find_status)),
+        serde_json::Value::Array(items) => items.iter().find_map(find_status),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify() {
+        let run = |status: &str, conclusion: &str| {
+            format!(
+                r#"{{"total_count":1,"workflow_runs":[{{"status":"{}","conclusion":{}}}]}}"#,
+                status, conclusion
+            )
+        };
+        assert_eq!(classify(&run("completed", r#""success""#), None).0, Health::Up);
+        assert_eq!(classify(&run("completed", r#""failure""#), None).0, Health::Down);
+        assert_eq!(classify(&run("in_progress", "null"), None).0, Health::Pending);
+
+        let health = r#"{"checks":{"db":"ok"},"state":"degraded"}"#;
+        assert_eq!(classify(health, None), (Health::Up, "OK".to_string()));
+        assert_eq!(classify(health, Some("/state")).0, Health::Pending);
+        assert_eq!(classify("<html>fine</html>", None).0, Health::Up);
+    }
+}
@@ -0,0 +1,512 @@
+//! MQTT bridge for home automation
+//!
+//! Publishes button presses to `<prefix>/button/<id>/press|release` and the
+//! Claude/app state as retained JSON on `<prefix>/state`. Messages under
+//! `<prefix>/display/#` set button labels and colors or force a redraw:
+//!
+//! - `display/button/<id>/label` - label text (empty payload clears it)
+//! - `display/button/<id>/color` - `#RRGGBB`, optionally `,#RRGGBB` for the
+//!   pressed color (empty payload clears it)
+//! - `display/clear` - drop every override
+//! - `display/redraw` - redraw all buttons
+//!
+//! The client is the small MQTT 3.1.1 subset in `packet` (QoS 0, plain
+//! TCP), which is all the bridge uses. Settings that need more (a `mqtts://`
+//! or `ws://` host, the TLS port 8883) are refused at startup, and a broker
+//! that sends a QoS 1/2 message anyway has its connection dropped.
+
+mod packet;
+
+use anyhow::{anyhow, bail, Result};
+use image::Rgb;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio::sync::{mpsc, RwLock as TokioRwLock};
+use tracing::{debug, info, warn};
+
+use crate::config::MqttConfig;
+use crate::profiles::store::parse_hex_color;
+use crate::state::{AppState, ButtonOverride};
+use crate::AppCommand;
+use packet::Packet;
+
+const KEEP_ALIVE_SECS: u16 = 30;
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+const QUEUE_SIZE: usize = 64;
+
+/// A message waiting to be published
+#[derive(Debug)]
+struct Message {
+    topic: String,
+    payload: Vec<u8>,
+    retain: bool,
+}
+
+/// Handle for publishing deck events; cheap to clone
+#[derive(Debug, Clone)]
+pub struct MqttPublisher {
+    tx: mpsc::Sender<Message>,
+    prefix: String,
+}
+
+impl MqttPublisher {
+    /// Publish a button event ("press" or "release"), with the profile as payload
+    pub fn button(&self, button_id: u8, event: &str, profile: &str) {
+        self.send(format!("button/{}/{}", button_id, event), profile.into(), false);
+    }
+
+    /// Publish the retained state summary (skipped by the bridge if unchanged)
+    pub fn state(&self, state: &AppState, profile: &str) {
+        let summary = serde_json::json!({
+            "task": state.task_name,
+            "tool_detail": state.tool_detail,
+            "waiting_for_input": state.waiting_for_input,
+            "model": state.model,
+            "focused_app": state.focused_app,
+            "profile": profile,
+            "active_scene": state.active_scene,
+            "connected": state.connected,
+        });
+        self.send("state".to_string(), summary.to_string().into_bytes(), true);
+    }
+
+    fn send(&self, subtopic: String, payload: Vec<u8>, retain: bool) {
+        let message = Message {
+            topic: format!("{}/{}", self.prefix, subtopic),
+            payload,
+            retain,
+        };
+        // Drop events rather than block the main loop if the broker is slow
+        if self.tx.try_send(message).is_err() {
+            debug!("MQTT queue full or closed, dropping message");
+        }
+    }
+}
+
+/// Start the bridge in the background, returning a publisher for deck events
+///
+/// Reconnects every few seconds while the broker is unreachable; events
+/// published while disconnected are dropped, except the retained state,
+/// which is resent on reconnect.
+pub fn spawn(
+    config: MqttConfig,
+    state: Arc<TokioRwLock<AppState>>,
+    redraw: mpsc::Sender<AppCommand>,
+) -> MqttPublisher {
+    let (tx, rx) = mpsc::channel(QUEUE_SIZE);
+    let publisher = MqttPublisher {
+        tx,
+        prefix: config.topic_prefix.clone(),
+    };
+    tokio::spawn(run(config, state, rx, redraw));
+    publisher
+}
+
+async fn run(
+    config: MqttConfig,
+    state: Arc<TokioRwLock<AppState>>,
+    mut outgoing: mpsc::Receiver<Message>,
+    redraw: mpsc::Sender<AppCommand>,
+) {
+    if let Err(e) = check_config(&config) {
+        warn!("MQTT bridge disabled: {}", e);
+        return;
+    }
+    let mut bridge = Bridge {
+        config,
+        state,
+        redraw,
+        retained: HashMap::new(),
+        connected: false,
+        ping_every: Duration::from_secs(KEEP_ALIVE_SECS as u64 / 2),
+    };
+    loop {
+        match bridge.session(&mut outgoing).await {
+            Ok(()) => return, // the app shut down
+            Err(e) if bridge.connected => warn!("MQTT connection lost: {}", e),
+            Err(e) => debug!("MQTT connect failed: {}", e),
+        }
+        bridge.connected = false;
+
+        tokio::time::sleep(RECONNECT_DELAY).await;
+        while let Ok(message) = outgoing.try_recv() {
+            bridge.remember(&message);
+        }
+    }
+}
+
+/// Refuse settings that need protocol features the client doesn't have
+fn check_config(config: &MqttConfig) -> Result<()> {
+    if let Some((scheme, _)) = config.host.split_once("://") {
+        bail!("{}:// brokers aren't supported, only plain TCP (set host to a name)", scheme);
+    }
+    if config.port == 8883 {
+        bail!("port 8883 is MQTT over TLS, which isn't supported");
+    }
+    let strings = [
+        ("client_id", Some(&config.client_id)),
+        ("topic_prefix", Some(&config.topic_prefix)),
+        ("username", config.username.as_ref()),
+        ("password", config.password.as_ref()),
+    ];
+    for (name, value) in strings {
+        if value.is_some_and(|value| value.len() > u16::MAX as usize) {
+            bail!("{} is longer than MQTT allows", name);
+        }
+    }
+    Ok(())
+}
+
+struct Bridge {
+    config: MqttConfig,
+    state: Arc<TokioRwLock<AppState>>,
+    redraw: mpsc::Sender<AppCommand>,
+    /// Last retained payload per topic, to skip repeats and resend on reconnect
+    retained: HashMap<String, Vec<u8>>,
+    connected: bool,
+    /// How often to PINGREQ; a broker that misses one reply is dropped
+    ping_every: Duration,
+}
+
+impl Bridge {
+    /// Connect and pump messages until the connection fails (Err) or the app exits (Ok)
+    async fn session(&mut self, outgoing: &mut mpsc::Receiver<Message>) -> Result<()> {
+        let address = (self.config.host.as_str(), self.config.port);
+        let mut stream = TcpStream::connect(address).await?;
+        stream
+            .write_all(&packet::connect(
+                &self.config.client_id,
+                self.config.username.as_deref(),
+                self.config.password.as_deref(),
+                KEEP_ALIVE_SECS,
+            ))
+            .await?;
+
+        let mut buf = Vec::new();
+        match read_packet(&mut stream, &mut buf).await? {
+            Packet::ConnAck { code: 0 } => {}
+            Packet::ConnAck { code } => {
+                return Err(anyhow!("Broker refused connection (code {})", code))
+            }
+            other => return Err(anyhow!("Expected CONNACK, got {:?}", other)),
+        }
+        let filter = format!("{}/display/#", self.config.topic_prefix);
+        stream.write_all(&packet::subscribe(1, &filter)).await?;
+        // Set when a PINGREQ goes out, cleared by the PINGRESP
+        let mut awaiting_pong = false;
+        for (topic, payload) in &self.retained {
+            stream.write_all(&packet::publish(topic, payload, true)).await?;
+        }
+        info!("Connected to MQTT broker at {}:{}", self.config.host, self.config.port);
+        self.connected = true;
+
+        let mut ping = tokio::time::interval(self.ping_every);
+        ping.tick().await;
+        loop {
+            tokio::select! {
+                message = outgoing.recv() => {
+                    let Some(message) = message else {
+                        stream.write_all(&packet::DISCONNECT).await.ok();
+                        return Ok(());
+                    };
+                    if self.remember(&message) {
+                        let bytes = packet::publish(&message.topic, &message.payload, message.retain);
+                        stream.write_all(&bytes).await?;
+                    }
+                }
+                read = stream.read_buf(&mut buf) => {
+                    if read? == 0 {
+                        return Err(anyhow!("Broker closed the connection"));
+                    }
+                    while let Some((packet, used)) = packet::decode(&buf)? {
+                        buf.drain(..used);
+                        match packet {
+                            Packet::Publish { topic, payload } => {
+                                self.handle_display(&topic, &payload).await;
+                            }
+                            Packet::SubAck { granted: 0x80 } => {
+                                warn!("MQTT broker refused the subscription to {}", filter);
+                            }
+                            Packet::PingResp => awaiting_pong = false,
+                            _ => {}
+                        }
+                    }
+                }
+                _ = ping.tick() => {
+                    // A half-open connection never errors, so give up on silence
+                    if awaiting_pong {
+                        return Err(anyhow!("Broker stopped answering pings"));
+                    }
+                    stream.write_all(&packet::PINGREQ).await?;
+                    awaiting_pong = true;
+                }
+            }
+        }
+    }
+
+    /// Track retained payloads, returning false if this one repeats the last
+    fn remember(&mut self, message: &Message) -> bool {
+        if !message.retain {
+            return true;
+        }
+        if self.retained.get(&message.topic) == Some(&message.payload) {
+            return false;
+        }
+        self.retained.insert(message.topic.clone(), message.payload.clone());
+        true
+    }
+
+    /// Apply a message received under `<prefix>/display/`
+    async fn handle_display(&self, topic: &str, payload: &[u8]) {
+        let Some(command) = topic
+            .strip_prefix(&self.config.topic_prefix)
+            .and_then(|t| t.strip_prefix("/display/"))
+            .and_then(|t| DisplayCommand::parse(t, &String::from_utf8_lossy(payload)))
+        else {
+            debug!("Ignoring MQTT message on {}", topic);
+            return;
+        };
+
+        command.apply(&mut self.state.write().await.button_overrides);
+        if let Err(e) = self.redraw.try_send(AppCommand::RedrawButtons) {
+            debug!("Failed to request redraw: {}", e);
+        }
+    }
+}
+
+/// Read until one whole packet has arrived
+async fn read_packet(stream: &mut TcpStream, buf: &mut Vec<u8>) -> Result<Packet> {
+    loop {
+        if let Some((packet, used)) = packet::decode(buf)? {
+            buf.drain(..used);
+            return Ok(packet);
+        }
+        if stream.read_buf(buf).await? == 0 {
+            return Err(anyhow!("Broker closed the connection"));
+        }
+    }
+}
+
+/// A change requested under `<prefix>/display/`
+#[derive(Debug, Clone, PartialEq)]
+enum DisplayCommand {
+    Label(u8, Option<String>),
+    Colors(u8, Option<(Rgb<u8>, Rgb<u8>)>),
+    Clear,
+    Redraw,
+}
+
+impl DisplayCommand {
+    /// Parse a topic (after `display/`) and payload
+    fn parse(topic: &str, payload: &str) -> Option<Self> {
+        let payload = payload.trim();
+        let parts: Vec<&str> = topic.split('/').collect();
+        match parts.as_slice() {
+            ["clear"] => Some(DisplayCommand::Clear),
+            ["redraw"] => Some(DisplayCommand::Redraw),
+            ["button", id, field] => {
+                let id: u8 = id.parse().ok().filter(|id| *id < 10)?;
+                match *field {
+                    "label" if payload.is_empty() => Some(DisplayCommand::Label(id, None)),
+                    "label" => Some(DisplayCommand::Label(id, Some(payload.to_string()))),
+                    "color" if payload.is_empty() => Some(DisplayCommand::Colors(id, None)),
+                    "color" => {
+                        let mut colors = payload.split(',').map(|c| parse_hex_color(c.trim()));
+                        let color = colors.next()??;
+                        let bright = match colors.next() {
+                            Some(bright) => bright?,
+                            None => brighter(color),
+                        };
+                        Some(DisplayCommand::Colors(id, Some((color, bright))))
+                    }
+                    _ => None,
+                }
+            }
+            _ => None,
+        }
+    }
+
+    fn apply(self, overrides: &mut HashMap<u8, ButtonOverride>) {
+        match self {
+            DisplayCommand::Label(id, label) => overrides.entry(id).or_default().label = label,
+            DisplayCommand::Colors(id, colors) => overrides.entry(id).or_default().colors = colors,
+            DisplayCommand::Clear => overrides.clear(),
+            DisplayCommand::Redraw => {}
+        }
+        overrides.retain(|_, over| *over != ButtonOverride::default());
+    }
+}
+
+/// Pressed color for an override that only gives one
+pub(crate) fn brighter(color: Rgb<u8>) -> Rgb<u8> {
+    Rgb(color.0.map(|c| c.saturating_add(40)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::net::TcpListener;
+
+    /// A bridge pointed at a fake broker on a free local port
+    async fn bridge() -> (Bridge, TcpListener, mpsc::Receiver<AppCommand>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let config = MqttConfig {
+            host: "127.0.0.1".to_string(),
+            port: listener.local_addr().unwrap().port(),
+            topic_prefix: "test".to_string(),
+            ..Default::default()
+        };
+        let (redraw, redraw_rx) = mpsc::channel(8);
+        let bridge = Bridge {
+            config,
+            state: Arc::new(TokioRwLock::new(AppState::default())),
+            redraw,
+            retained: HashMap::new(),
+            connected: false,
+            ping_every: Duration::from_millis(100),
+        };
+        (bridge, listener, redraw_rx)
+    }
+
+    /// Read the next packet the bridge sends, as bytes
+    async fn next_packet(broker: &mut TcpStream, buf: &mut Vec<u8>) -> Vec<u8> {
+        loop {
+            if let Some((_, used)) = packet::decode(buf).unwrap() {
+                return buf.drain(..used).collect();
+            }
+            assert_ne!(broker.read_buf(buf).await.unwrap(), 0, "bridge hung up");
+        }
+    }
+
+    /// The next packet that isn't a PINGREQ
+    async fn skip_pings(broker: &mut TcpStream, buf: &mut Vec<u8>) -> Vec<u8> {
+        loop {
+            let sent = next_packet(broker, buf).await;
+            if sent != packet::PINGREQ {
+                return sent;
+            }
+        }
+    }
+
+    /// Accept the bridge and answer its CONNECT with `code`
+    async fn accept(listener: &TcpListener, code: u8) -> (TcpStream, Vec<u8>) {
+        let (mut broker, _) = listener.accept().await.unwrap();
+        let mut buf = Vec::new();
+        let connect = next_packet(&mut broker, &mut buf).await;
+        assert_eq!(connect, packet::connect("claude-deck", None, None, KEEP_ALIVE_SECS));
+        broker.write_all(&[0x20, 0x02, 0x00, code]).await.unwrap();
+        (broker, buf)
+    }
+
+    #[tokio::test]
+    async fn test_session() {
+        let (mut bridge, listener, mut redraw) = bridge().await;
+        let state = bridge.state.clone();
+        bridge.retained.insert("test/state".to_string(), b"{}".to_vec());
+        let (tx, mut rx) = mpsc::channel(8);
+        let publisher = MqttPublisher {
+            tx,
+            prefix: "test".to_string(),
+        };
+        let session = tokio::spawn(async move { bridge.session(&mut rx).await });
+
+        let (mut broker, mut buf) = accept(&listener, 0).await;
+        let subscribe = next_packet(&mut broker, &mut buf).await;
+        assert_eq!(subscribe, packet::subscribe(1, "test/display/#"));
+        broker.write_all(&[0x90, 0x03, 0x00, 0x01, 0x00]).await.unwrap();
+        // Retained state is resent on connect
+        let resent = next_packet(&mut broker, &mut buf).await;
+        assert_eq!(resent, packet::publish("test/state", b"{}", true));
+
+        // Pings are answered, so the session stays up across several
+        for _ in 0..3 {
+            assert_eq!(next_packet(&mut broker, &mut buf).await, packet::PINGREQ);
+            broker.write_all(&[0xD0, 0x00]).await.unwrap();
+        }
+
+        let label = packet::publish("test/display/button/3/label", b"Hi", false);
+        broker.write_all(&label).await.unwrap();
+        assert!(matches!(redraw.recv().await, Some(AppCommand::RedrawButtons)));
+        let over = state.read().await.button_overrides[&3].clone();
+        assert_eq!(over.label.as_deref(), Some("Hi"));
+
+        publisher.button(3, "press", "default");
+        // An unchanged retained payload isn't sent again
+        publisher.send("state".to_string(), b"{}".to_vec(), true);
+        drop(publisher);
+        let sent = skip_pings(&mut broker, &mut buf).await;
+        assert_eq!(sent, packet::publish("test/button/3/press", b"default", false));
+        assert_eq!(skip_pings(&mut broker, &mut buf).await, packet::DISCONNECT);
+        session.await.unwrap().unwrap();
+    }
+
+    #[test]
+    fn test_check_config() {
+        assert!(check_config(&MqttConfig::default()).is_ok());
+        let config = |host: &str, port| MqttConfig {
+            host: host.to_string(),
+            port,
+            ..Default::default()
+        };
+        let error = check_config(&config("mqtts://broker.local", 1883)).unwrap_err();
+        assert!(error.to_string().contains("mqtts:// brokers aren't supported"));
+        assert!(check_config(&config("ws://broker.local", 1883)).is_err());
+        let tls = check_config(&config("broker.local", 8883)).unwrap_err();
+        assert!(tls.to_string().contains("TLS"));
+        let long = MqttConfig {
+            topic_prefix: "x".repeat(70_000),
+            ..Default::default()
+        };
+        assert!(check_config(&long).unwrap_err().to_string().contains("topic_prefix"));
+    }
+
+    #[tokio::test]
+    async fn test_session_failures() {
+        let (mut bridge, listener, _redraw) = bridge().await;
+        let (_tx, mut rx) = mpsc::channel(8);
+
+        let broker = accept(&listener, 5);
+        let (result, _broker) = tokio::join!(bridge.session(&mut rx), broker);
+        assert!(result.unwrap_err().to_string().contains("code 5"));
+        assert!(!bridge.connected);
+
+        // A broker that goes quiet is dropped after one unanswered ping
+        let broker = async {
+            let (mut broker, mut buf) = accept(&listener, 0).await;
+            next_packet(&mut broker, &mut buf).await;
+            assert_eq!(next_packet(&mut broker, &mut buf).await, packet::PINGREQ);
+            broker
+        };
+        let (result, _broker) = tokio::join!(bridge.session(&mut rx), broker);
+        assert!(result.unwrap_err().to_string().contains("stopped answering pings"));
+        assert!(bridge.connected);
+    }
+
+    #[test]
+    fn test_display_commands() {
+        let mut overrides = HashMap::new();
+        let label = DisplayCommand::parse("button/3/label", "Build ✓").unwrap();
+        assert_eq!(label, DisplayCommand::Label(3, Some("Build ✓".to_string())));
+        label.apply(&mut overrides);
+
+        let color = DisplayCommand::parse("button/3/color", "#102030").unwrap();
+        color.apply(&mut overrides);
+        let over = &overrides[&3];
+        assert_eq!(over.colors, Some((Rgb([16, 32, 48]), Rgb([56, 72, 88]))));
+
+        assert!(DisplayCommand::parse("button/3/color", "#102030,nope").is_none());
+        assert!(DisplayCommand::parse("button/10/label", "x").is_none());
+        assert!(DisplayCommand::parse("button/3/emoji", "x").is_none());
+
+        DisplayCommand::parse("button/3/label", "").unwrap().apply(&mut overrides);
+        DisplayCommand::parse("button/3/color", "").unwrap().apply(&mut overrides);
+        assert!(overrides.is_empty());
+
+        DisplayCommand::parse("button/0/label", "A").unwrap().apply(&mut overrides);
+        DisplayCommand::parse("clear", "").unwrap().apply(&mut overrides);
+        assert!(overrides.is_empty());
+    }
+}
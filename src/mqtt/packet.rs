@@ -0,0 +1,227 @@
+//! The subset of MQTT 3.1.1 the bridge speaks
+//!
+//! The bridge only needs fire-and-forget events, one retained state topic
+//! and one subscription, so it speaks this much of the protocol over plain
+//! TCP:
+//!
+//! - CONNECT with a clean session, keep-alive, and optional username and
+//!   password (no will message); the CONNACK return code is checked
+//! - PUBLISH at QoS 0, optionally retained, both ways; with a QoS 0
+//!   subscription brokers only send QoS 0, so an incoming QoS 1/2 PUBLISH
+//!   (which would need acknowledging) is an error
+//! - SUBSCRIBE to a single filter at QoS 0, with the SUBACK's grant decoded
+//! - PINGREQ/PINGRESP for keep-alive, and DISCONNECT on shutdown
+//!
+//! Not supported: TLS, QoS 1 and 2 publishing (and so PUBACK and friends),
+//! persistent sessions, will messages, MQTT 5 and WebSocket transport.
+
+use anyhow::{anyhow, bail, Result};
+
+pub const PINGREQ: [u8; 2] = [0xC0, 0x00];
+pub const DISCONNECT: [u8; 2] = [0xE0, 0x00];
+
+/// A packet received from the broker
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Packet {
+    ConnAck { code: u8 },
+    Publish { topic: String, payload: Vec<u8> },
+    /// `granted` is the QoS granted, or 0x80 if the subscription was refused
+    SubAck { granted: u8 },
+    PingResp,
+    /// Anything else (ignored)
+    Other(u8),
+}
+
+/// CONNECT with a clean session
+pub fn connect(
+    client_id: &str,
+    username: Option<&str>,
+    password: Option<&str>,
+    keep_alive_secs: u16,
+) -> Vec<u8> {
+    let mut flags = 0x02;
+    if username.is_some() {
+        flags |= 0x80;
+    }
+    if password.is_some() {
+        flags |= 0x40;
+    }
+
+    let mut body = Vec::new();
+    put_str(&mut body, "MQTT");
+    body.push(4); // protocol level 3.1.1
+    body.push(flags);
+    body.extend_from_slice(&keep_alive_secs.to_be_bytes());
+    put_str(&mut body, client_id);
+    for field in [username, password].into_iter().flatten() {
+        put_str(&mut body, field);
+    }
+    frame(0x10, body)
+}
+
+/// PUBLISH at QoS 0
+pub fn publish(topic: &str, payload: &[u8], retain: bool) -> Vec<u8> {
+    let mut body = Vec::new();
+    put_str(&mut body, topic);
+    body.extend_from_slice(payload);
+    frame(0x30 | retain as u8, body)
+}
+
+/// SUBSCRIBE to one filter at QoS 0
+pub fn subscribe(packet_id: u16, filter: &str) -> Vec<u8> {
+    let mut body = packet_id.to_be_bytes().to_vec();
+    put_str(&mut body, filter);
+    body.push(0);
+    frame(0x82, body)
+}
+
+/// Decode the first packet in `buf`, returning it and the bytes it used
+///
+/// Returns None until the whole packet has arrived.
+pub fn decode(buf: &[u8]) -> Result<Option<(Packet, usize)>> {
+    let Some(&header) = buf.first() else {
+        return Ok(None);
+    };
+    let Some((len, len_bytes)) = decode_length(&buf[1..])? else {
+        return Ok(None);
+    };
+    let start = 1 + len_bytes;
+    let end = start + len;
+    if buf.len() < end {
+        return Ok(None);
+    }
+    let body = &buf[start..end];
+
+    let packet = match header >> 4 {
+        2 => Packet::ConnAck {
+            code: *body.get(1).ok_or_else(|| anyhow!("Short CONNACK"))?,
+        },
+        3 => {
+            let topic_len = u16::from_be_bytes([
+                *body.first().ok_or_else(|| anyhow!("Short PUBLISH"))?,
+                *body.get(1).ok_or_else(|| anyhow!("Short PUBLISH"))?,
+            ]) as usize;
+            let topic = body
+                .get(2..2 + topic_len)
+                .ok_or_else(|| anyhow!("Short PUBLISH topic"))?;
+            let qos = (header >> 1) & 0x03;
+            if qos > 0 {
+                bail!("QoS {} PUBLISH isn't supported", qos);
+            }
+            Packet::Publish {
+                topic: String::from_utf8_lossy(topic).into_owned(),
+                payload: body.get(2 + topic_len..).unwrap_or_default().to_vec(),
+            }
+        }
+        9 => Packet::SubAck {
+            granted: *body.get(2).ok_or_else(|| anyhow!("Short SUBACK"))?,
+        },
+        13 => Packet::PingResp,
+        other => Packet::Other(other),
+    };
+    Ok(Some((packet, end)))
+}
+
+fn put_str(out: &mut Vec<u8>, s: &str) {
+    out.extend_from_slice(&(s.len() as u16).to_be_bytes());
+    out.extend_from_slice(s.as_bytes());
+}
+
+fn frame(header: u8, body: Vec<u8>) -> Vec<u8> {
+    let mut out = vec![header];
+    let mut len = body.len();
+    loop {
+        let mut byte = (len % 128) as u8;
+        len /= 128;
+        if len > 0 {
+            byte |= 0x80;
+        }
+        out.push(byte);
+        if len == 0 {
+            break;
+        }
+    }
+    out.extend(body);
+    out
+}
+
+/// Remaining length and the number of bytes it took, or None if incomplete
+fn decode_length(buf: &[u8]) -> Result<Option<(usize, usize)>> {
+    let mut len = 0usize;
+    for (i, &byte) in buf.iter().enumerate().take(4) {
+        len |= ((byte & 0x7F) as usize) << (7 * i);
+        if byte & 0x80 == 0 {
+            return Ok(Some((len, i + 1)));
+        }
+    }
+    if buf.len() >= 4 {
+        return Err(anyhow!("Malformed packet length"));
+    }
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_publish_round_trip() {
+        let payload = vec![b'x'; 300];
+        let bytes = publish("claude-deck/display/button/3/label", &payload, true);
+        assert_eq!(bytes[0], 0x31);
+        // 300 bytes + topic needs a two-byte length
+        assert_eq!(&bytes[1..3], &[0xD0, 0x02]);
+
+        assert_eq!(decode(&bytes[..10]).unwrap(), None);
+        let (packet, used) = decode(&bytes).unwrap().unwrap();
+        assert_eq!(used, bytes.len());
+        assert_eq!(
+            packet,
+            Packet::Publish {
+                topic: "claude-deck/display/button/3/label".to_string(),
+                payload,
+            }
+        );
+    }
+
+    #[test]
+    fn test_connect_and_acks() {
+        let bytes = connect("deck", Some("user"), None, 30);
+        assert_eq!(&bytes[..2], &[0x10, 22]);
+        assert_eq!(&bytes[2..8], b"\x00\x04MQTT");
+        assert_eq!(bytes[9], 0x82);
+
+        let mut buf = vec![0x20, 0x02, 0x00, 0x05];
+        buf.extend_from_slice(&[0xD0, 0x00]);
+        let (packet, used) = decode(&buf).unwrap().unwrap();
+        assert_eq!(packet, Packet::ConnAck { code: 5 });
+        assert_eq!(decode(&buf[used..]).unwrap().unwrap().0, Packet::PingResp);
+
+        // Keep-alive, then username and password after the client id
+        let bytes = connect("deck", Some("user"), Some("pw"), 300);
+        assert_eq!(bytes[9], 0xC2);
+        assert_eq!(&bytes[10..12], &300u16.to_be_bytes());
+        assert!(bytes.ends_with(b"\x00\x04deck\x00\x04user\x00\x02pw"));
+
+        let (packet, _) = decode(&[0x90, 0x03, 0x00, 0x01, 0x80]).unwrap().unwrap();
+        assert_eq!(packet, Packet::SubAck { granted: 0x80 });
+        assert!(decode(&[0x90, 0x02, 0x00, 0x01]).is_err());
+    }
+
+    #[test]
+    fn test_subscribe_and_qos() {
+        let bytes = subscribe(7, "a/#");
+        assert_eq!(bytes, b"\x82\x08\x00\x07\x00\x03a/#\x00");
+
+        // QoS 1 and 2 would need acknowledging, which the client can't do
+        let qos1 = b"\x32\x09\x00\x03a/b\x00\x09hi";
+        assert!(decode(qos1).unwrap_err().to_string().contains("QoS 1"));
+        assert!(decode(b"\x34\x09\x00\x03a/b\x00\x09hi").is_err());
+
+        // Lengths take at most four bytes
+        assert!(decode(&[0x30, 0xFF, 0xFF, 0xFF, 0xFF, 0x01]).is_err());
+        assert_eq!(decode(&[0x30, 0xFF, 0xFF]).unwrap(), None);
+        assert!(decode(&[0x30, 0x01, 0x00]).is_err());
+        assert_eq!(decode(&[0xB0, 0x02, 0x00, 0x01]).unwrap().unwrap().0, Packet::Other(11));
+    }
+}
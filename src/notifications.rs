@@ -0,0 +1,131 @@
+//! Notification Center alerts for Claude events (macOS `osascript`)
+//!
+//! Posted for the same status changes that play sound cues, so a glance at
+//! the screen corner is enough when the deck is out of sight.
+
+use std::time::Duration;
+
+use crate::config::NotificationsConfig;
+use crate::sound::SoundEvent;
+
+/// Title and message to post for an event, or None if it's turned off
+///
+/// `detail` is the tool detail the hooks reported; `turn` is how long the
+/// turn ran, for completion alerts.
+pub fn message_for(
+    config: &NotificationsConfig,
+    event: SoundEvent,
+    detail: Option<&str>,
+    turn: Option<Duration>,
+) -> Option<(&'static str, String)> {
+    if !config.enabled {
+        return None;
+    }
+    let detail = detail.filter(|d| !d.is_empty());
+    match event {
+        SoundEvent::Waiting if config.waiting => {
+            let message = detail.unwrap_or("Claude is waiting for your input").to_string();
+            Some(("Claude needs you", message))
+        }
+        SoundEvent::Error if config.error => {
+            let message = detail.unwrap_or("The hooks reported an error").to_string();
+            Some(("Claude hit an error", message))
+        }
+        SoundEvent::Complete if config.complete => {
+            let turn = turn?;
+            if turn < Duration::from_secs(config.long_task_secs) {
+                return None;
+            }
+            Some(("Claude finished", format!("Done after {}", format_duration(turn))))
+        }
+        _ => None,
+    }
+}
+
+/// Post an event's notification in the background (no-op when turned off)
+pub fn notify(
+    config: &NotificationsConfig,
+    event: SoundEvent,
+    detail: Option<&str>,
+    turn: Option<Duration>,
+) {
+    if let Some((title, message)) = message_for(config, event, detail, turn) {
+        post(title, &message);
+    }
+}
+
+/// Post the alert for a countdown timer reaching zero
+pub fn notify_timer(duration: Duration) {
+    post("Timer finished", &format!("Your {} timer is up", format_duration(duration)));
+}
+
+/// "45s", "3m 12s" or "1h 05m"
+fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    match secs {
+        0..=59 => format!("{}s", secs),
+        60..=3599 => format!("{}m {:02}s", secs / 60, secs % 60),
+        _ => format!("{}h {:02}m", secs / 3600, secs % 3600 / 60),
+    }
+}
+
+/// AppleScript posting a notification
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+fn notification_script(title: &str, message: &str) -> String {
+    let quote = |text: &str| format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""));
+    format!("display notification {} with title {}", quote(message), quote(title))
+}
+
+#[cfg(target_os = "macos")]
+fn post(title: &str, message: &str) {
+    use tracing::debug;
+
+    // The child is reaped by tokio once it exits
+    if let Err(e) = tokio::process::Command::new("osascript")
+        .arg("-e")
+        .arg(notification_script(title, message))
+        .spawn()
+    {
+        debug!("Failed to post notification: {}", e);
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+fn post(_title: &str, _message: &str) {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_messages_respect_flags_and_threshold() {
+        let mut config = NotificationsConfig::default();
+        let waiting = |config: &NotificationsConfig| {
+            message_for(config, SoundEvent::Waiting, Some("Bash: rm -rf target"), None)
+        };
+        assert!(waiting(&config).is_none());
+
+        config.enabled = true;
+        assert_eq!(waiting(&config).unwrap().1, "Bash: rm -rf target");
+        let error = message_for(&config, SoundEvent::Error, Some(""), None).unwrap();
+        assert_eq!(error.1, "The hooks reported an error");
+        assert!(message_for(&config, SoundEvent::Press, None, None).is_none());
+
+        // Only turns that ran past the threshold are worth an alert
+        let turn = |secs| Some(Duration::from_secs(secs));
+        assert!(message_for(&config, SoundEvent::Complete, None, turn(5)).is_none());
+        let done = message_for(&config, SoundEvent::Complete, None, turn(192)).unwrap();
+        assert_eq!(done.1, "Done after 3m 12s");
+
+        config.waiting = false;
+        assert!(waiting(&config).is_none());
+    }
+
+    #[test]
+    fn test_script_escapes_quotes() {
+        assert_eq!(
+            notification_script("Claude", r#"say "hi" \o/"#),
+            r#"display notification "say \"hi\" \\o/" with title "Claude""#
+        );
+    }
+}
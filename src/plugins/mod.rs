@@ -0,0 +1,361 @@
+//! External plugins speaking JSON over stdio
+//!
+//! Every executable in `~/.config/claude-deck/plugins/` is started with the
+//! deck and exchanges one JSON object per line over its stdin and stdout
+//! (stderr is inherited). Messages from the deck:
+//!
+//! - `{"type":"hello","version":1}` - sent once the plugin starts
+//! - `{"type":"press","action":"WEATHER","button":3,"long_press":false}` - a
+//!   button bound to one of the plugin's actions was pressed
+//!
+//! Messages from the plugin:
+//!
+//! - `{"type":"register","actions":["WEATHER"],"widgets":["weather"]}` -
+//!   claim custom actions (`action = "WEATHER"` on a button) and strip
+//!   widgets (`top_left = { plugin = "weather" }` under `[strip]`)
+//! - `{"type":"widget","name":"weather","text":"18°C","color":"#4A9EFF"}` -
+//!   set what a registered widget shows (`label`, replacing the widget name
+//!   above the text, and `color` are optional)
+//! - `{"type":"button","action":"WEATHER","label":"18°C","color":"#204060"}` -
+//!   relabel and recolor every button bound to a registered action (omitted
+//!   fields go back to the profile's)
+//! - `{"type":"log","message":"..."}` - write a line to the deck's log
+//!
+//! A plugin that exits is restarted after a short delay, unless it keeps
+//! exiting straight after starting.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use std::process::{ExitStatus, Stdio};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::process::Command;
+use tokio::sync::{mpsc, RwLock as TokioRwLock};
+use tracing::{debug, info, warn};
+
+use crate::mqtt::brighter;
+use crate::profiles::store::parse_hex_color;
+use crate::state::{AppState, ButtonOverride, PluginWidget};
+use crate::AppCommand;
+
+/// Protocol version sent in `hello`
+const PROTOCOL_VERSION: u32 = 1;
+const QUEUE_SIZE: usize = 64;
+const RESTART_DELAY: Duration = Duration::from_secs(2);
+/// A run shorter than this counts as a crash
+const STABLE_RUN: Duration = Duration::from_secs(30);
+/// Crashes in a row before a plugin is left stopped
+const MAX_CRASHES: u32 = 5;
+
+/// `~/.config/claude-deck/plugins`
+pub fn plugins_dir() -> Result<PathBuf> {
+    let home = std::env::var("HOME").context("HOME environment variable not set")?;
+    Ok(PathBuf::from(home).join(".config/claude-deck/plugins"))
+}
+
+/// A line sent to a plugin
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum DeckMessage {
+    Hello { version: u32 },
+    Press { action: String, button: u8, long_press: bool },
+}
+
+/// A line received from a plugin
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum PluginMessage {
+    Register {
+        #[serde(default)]
+        actions: Vec<String>,
+        #[serde(default)]
+        widgets: Vec<String>,
+    },
+    Widget {
+        name: String,
+        label: Option<String>,
+        #[serde(default)]
+        text: String,
+        color: Option<String>,
+    },
+    Button {
+        action: String,
+        label: Option<String>,
+        color: Option<String>,
+    },
+    Log {
+        message: String,
+    },
+}
+
+/// Routes presses of plugin actions to the plugin that registered them; cheap to clone
+#[derive(Debug, Clone, Default)]
+pub struct PluginHost {
+    /// Upper-cased action name to the owning plugin's stdin queue
+    actions: Arc<RwLock<HashMap<String, mpsc::Sender<DeckMessage>>>>,
+}
+
+impl PluginHost {
+    /// Forward a press to the plugin owning `action`; false if none does
+    pub fn press(&self, action: &str, button: u8, long_press: bool) -> bool {
+        let actions = self.actions.read().unwrap();
+        let Some(tx) = actions.get(&action.to_uppercase()) else {
+            return false;
+        };
+        let message = DeckMessage::Press {
+            action: action.to_string(),
+            button,
+            long_press,
+        };
+        // Drop presses rather than block input if the plugin stops reading
+        if tx.try_send(message).is_err() {
+            warn!("Plugin for {} isn't keeping up, dropping press", action);
+        }
+        true
+    }
+
+    fn register(&self, action: &str, tx: &mpsc::Sender<DeckMessage>) {
+        self.actions.write().unwrap().insert(action.to_uppercase(), tx.clone());
+    }
+
+    fn unregister(&self, actions: &[String]) {
+        let mut registered = self.actions.write().unwrap();
+        for action in actions {
+            registered.remove(&action.to_uppercase());
+        }
+    }
+}
+
+/// Start every executable in the plugins directory
+///
+/// Widget and button updates are written to `state`, followed by a redraw
+/// command on `redraw`.
+pub fn spawn(state: Arc<TokioRwLock<AppState>>, redraw: mpsc::Sender<AppCommand>) -> PluginHost {
+    let host = PluginHost::default();
+    let paths = match plugins_dir().and_then(|dir| executables(&dir)) {
+        Ok(paths) => paths,
+        Err(e) => {
+            warn!("Failed to list plugins: {}", e);
+            Vec::new()
+        }
+    };
+    for path in paths {
+        let plugin = Plugin {
+            name: path.file_name().unwrap_or_default().to_string_lossy().to_string(),
+            host: host.clone(),
+            state: Arc::clone(&state),
+            redraw: redraw.clone(),
+            actions: Vec::new(),
+            widgets: Vec::new(),
+        };
+        tokio::spawn(plugin.supervise(path));
+    }
+    host
+}
+
+/// Executable files in `dir`, sorted (a missing directory has none)
+fn executables(dir: &Path) -> Result<Vec<PathBuf>> {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e).with_context(|| format!("Failed to read {}", dir.display())),
+    };
+    let mut paths: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| {
+            let name = path.file_name().unwrap_or_default().to_string_lossy();
+            let executable = path
+                .metadata()
+                .is_ok_and(|meta| meta.is_file() && meta.permissions().mode() & 0o111 != 0);
+            executable && !name.starts_with('.')
+        })
+        .collect();
+    paths.sort();
+    Ok(paths)
+}
+
+/// One plugin process and what it has registered
+struct Plugin {
+    name: String,
+    host: PluginHost,
+    state: Arc<TokioRwLock<AppState>>,
+    redraw: mpsc::Sender<AppCommand>,
+    actions: Vec<String>,
+    widgets: Vec<String>,
+}
+
+impl Plugin {
+    /// Run the plugin, restarting it when it exits
+    async fn supervise(mut self, path: PathBuf) {
+        let mut crashes = 0;
+        loop {
+            let started = Instant::now();
+            match self.run(&path).await {
+                Ok(status) => info!("Plugin '{}' exited ({})", self.name, status),
+                Err(e) => warn!("Plugin '{}' failed: {}", self.name, e),
+            }
+            self.forget().await;
+
+            crashes = if started.elapsed() < STABLE_RUN { crashes + 1 } else { 0 };
+            if crashes >= MAX_CRASHES {
+                warn!("Plugin '{}' keeps exiting, not restarting it", self.name);
+                return;
+            }
+            tokio::time::sleep(RESTART_DELAY).await;
+        }
+    }
+
+    /// Spawn the process and handle its messages until it closes stdout
+    async fn run(&mut self, path: &Path) -> Result<ExitStatus> {
+        let mut child = Command::new(path)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .with_context(|| format!("Failed to start {}", path.display()))?;
+        info!("Started plugin '{}'", self.name);
+
+        let mut stdin = child.stdin.take().context("Plugin stdin not piped")?;
+        let stdout = child.stdout.take().context("Plugin stdout not piped")?;
+
+        let (tx, mut rx) = mpsc::channel::<DeckMessage>(QUEUE_SIZE);
+        let writer = tokio::spawn(async move {
+            while let Some(message) = rx.recv().await {
+                let mut line = serde_json::to_string(&message).unwrap_or_default();
+                line.push('\n');
+                if stdin.write_all(line.as_bytes()).await.is_err() {
+                    return;
+                }
+            }
+        });
+        let _ = tx.try_send(DeckMessage::Hello {
+            version: PROTOCOL_VERSION,
+        });
+
+        let mut lines = BufReader::new(stdout).lines();
+        while let Some(line) = lines.next_line().await? {
+            if line.trim().is_empty() {
+                continue;
+            }
+            match serde_json::from_str::<PluginMessage>(&line) {
+                Ok(message) => self.handle(message, &tx).await,
+                Err(e) => warn!("Invalid message from plugin '{}': {}", self.name, e),
+            }
+        }
+
+        writer.abort();
+        Ok(child.wait().await?)
+    }
+
+    async fn handle(&mut self, message: PluginMessage, tx: &mpsc::Sender<DeckMessage>) {
+        match message {
+            PluginMessage::Register { actions, widgets } => {
+                let name = &self.name;
+                info!("Plugin '{}' registered actions {:?}, widgets {:?}", name, actions, widgets);
+                for action in &actions {
+                    self.host.register(action, tx);
+                }
+                self.actions.extend(actions);
+                self.widgets.extend(widgets);
+            }
+            PluginMessage::Widget { name, label, text, color } => {
+                if !self.widgets.contains(&name) {
+                    warn!("Plugin '{}' updated unregistered widget '{}'", self.name, name);
+                    return;
+                }
+                let widget = PluginWidget {
+                    label,
+                    text,
+                    color: color.as_deref().and_then(parse_hex_color),
+                };
+                self.state.write().await.plugin_widgets.insert(name, widget);
+                self.request(AppCommand::RefreshStrip);
+            }
+            PluginMessage::Button { action, label, color } => {
+                if !self.actions.iter().any(|a| a.eq_ignore_ascii_case(&action)) {
+                    warn!("Plugin '{}' updated unregistered action '{}'", self.name, action);
+                    return;
+                }
+                let over = ButtonOverride {
+                    label,
+                    colors: color.as_deref().and_then(parse_hex_color).map(|c| (c, brighter(c))),
+                };
+                let mut state = self.state.write().await;
+                if over == ButtonOverride::default() {
+                    state.plugin_buttons.remove(&action.to_uppercase());
+                } else {
+                    state.plugin_buttons.insert(action.to_uppercase(), over);
+                }
+                drop(state);
+                self.request(AppCommand::RedrawButtons);
+            }
+            PluginMessage::Log { message } => info!("[{}] {}", self.name, message),
+        }
+    }
+
+    /// Drop everything the plugin registered once it has exited
+    async fn forget(&mut self) {
+        self.host.unregister(&self.actions);
+        let mut state = self.state.write().await;
+        for widget in self.widgets.drain(..) {
+            state.plugin_widgets.remove(&widget);
+        }
+        for action in self.actions.drain(..) {
+            state.plugin_buttons.remove(&action.to_uppercase());
+        }
+        drop(state);
+        self.request(AppCommand::RefreshStrip);
+        self.request(AppCommand::RedrawButtons);
+    }
+
+    fn request(&self, command: AppCommand) {
+        if self.redraw.try_send(command).is_err() {
+            debug!("Command queue full, skipping plugin redraw");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_protocol_messages() {
+        let register = r#"{"type":"register","actions":["WEATHER"]}"#;
+        assert_eq!(
+            serde_json::from_str::<PluginMessage>(register).unwrap(),
+            PluginMessage::Register {
+                actions: vec!["WEATHER".to_string()],
+                widgets: Vec::new(),
+            }
+        );
+        let widget = r##"{"type":"widget","name":"weather","text":"18°C","color":"#4A9EFF"}"##;
+        let PluginMessage::Widget { label, color, .. } = serde_json::from_str(widget).unwrap() else {
+            panic!("expected a widget update");
+        };
+        assert_eq!((label, color.as_deref()), (None, Some("#4A9EFF")));
+        assert!(serde_json::from_str::<PluginMessage>(r#"{"type":"explode"}"#).is_err());
+
+        let press = DeckMessage::Press {
+            action: "weather".to_string(),
+            button: 3,
+            long_press: false,
+        };
+        assert_eq!(
+            serde_json::to_string(&press).unwrap(),
+            r#"{"type":"press","action":"weather","button":3,"long_press":false}"#
+        );
+
+        let host = PluginHost::default();
+        let (tx, mut rx) = mpsc::channel(1);
+        host.register("WEATHER", &tx);
+        assert!(host.press("weather", 3, false));
+        assert_eq!(rx.try_recv().unwrap(), press);
+        host.unregister(&["Weather".to_string()]);
+        assert!(!host.press("weather", 3, false));
+    }
+}
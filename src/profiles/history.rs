@@ -0,0 +1,165 @@
+//! Earlier versions of each profile, kept in `~/.config/claude-deck/history/`
+//!
+//! When the web UI saves the config, every profile it changed (or deleted)
+//! has its previous version written to `history/<profile>/<version>.toml`,
+//! keeping the last `MAX_VERSIONS`. Rolling back saves the config again, so
+//! the version rolled back from lands in the history too and can be restored.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use tracing::warn;
+
+use super::store::ProfileConfig;
+
+/// Versions kept per profile
+pub const MAX_VERSIONS: usize = 20;
+
+/// A profile as it was before a save
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProfileVersion {
+    /// Counts up from 1 for each profile
+    pub version: u64,
+    /// When it was replaced (Unix epoch seconds)
+    pub saved_at: u64,
+    pub profile: ProfileConfig,
+}
+
+/// `~/.config/claude-deck/history`
+pub fn history_dir() -> Result<PathBuf> {
+    let home = std::env::var("HOME").context("HOME environment variable not set")?;
+    Ok(PathBuf::from(home).join(".config/claude-deck/history"))
+}
+
+/// Directory of one profile's versions (names are made safe for a path)
+fn profile_dir(dir: &Path, name: &str) -> PathBuf {
+    let safe: String = name
+        .to_lowercase()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+        .collect();
+    dir.join(safe)
+}
+
+/// A profile's versions in `dir`, oldest first
+pub fn versions(dir: &Path, name: &str) -> Vec<ProfileVersion> {
+    let Ok(entries) = std::fs::read_dir(profile_dir(dir, name)) else {
+        return Vec::new();
+    };
+    let mut versions: Vec<ProfileVersion> = entries
+        .flatten()
+        .filter(|entry| entry.path().extension().is_some_and(|e| e == "toml"))
+        .filter_map(|entry| {
+            let contents = std::fs::read_to_string(entry.path()).ok()?;
+            toml::from_str::<ProfileVersion>(&contents).ok()
+        })
+        .filter(|version| version.profile.name.eq_ignore_ascii_case(name))
+        .collect();
+    versions.sort_by_key(|version| version.version);
+    versions
+}
+
+/// One version of a profile
+pub fn get(dir: &Path, name: &str, version: u64) -> Option<ProfileVersion> {
+    versions(dir, name).into_iter().find(|v| v.version == version)
+}
+
+/// Add `profile` as its newest version, unless that's what the newest already is
+///
+/// Returns the version number written, dropping versions past `MAX_VERSIONS`.
+pub fn record(dir: &Path, profile: &ProfileConfig) -> Result<Option<u64>> {
+    let existing = versions(dir, &profile.name);
+    let same = |v: &ProfileVersion| {
+        toml::to_string(&v.profile).ok() == toml::to_string(profile).ok()
+    };
+    if existing.last().is_some_and(same) {
+        return Ok(None);
+    }
+
+    let version = existing.last().map_or(1, |v| v.version + 1);
+    let saved_at = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let entry = ProfileVersion {
+        version,
+        saved_at,
+        profile: profile.clone(),
+    };
+    let profile_dir = profile_dir(dir, &profile.name);
+    std::fs::create_dir_all(&profile_dir)
+        .with_context(|| format!("Failed to create {:?}", profile_dir))?;
+    let path = profile_dir.join(format!("{}.toml", version));
+    std::fs::write(&path, toml::to_string_pretty(&entry)?)
+        .with_context(|| format!("Failed to write {:?}", path))?;
+
+    let stale = (existing.len() + 1).saturating_sub(MAX_VERSIONS);
+    for old in existing.iter().take(stale) {
+        let _ = std::fs::remove_file(profile_dir.join(format!("{}.toml", old.version)));
+    }
+    Ok(Some(version))
+}
+
+/// Record the previous version of every profile a save changes or removes
+pub fn record_changes(dir: &Path, before: &[ProfileConfig], after: &[ProfileConfig]) {
+    for previous in before {
+        let current = after.iter().find(|p| p.name.eq_ignore_ascii_case(&previous.name));
+        let unchanged = current
+            .is_some_and(|current| toml::to_string(current).ok() == toml::to_string(previous).ok());
+        if unchanged {
+            continue;
+        }
+        if let Err(e) = record(dir, previous) {
+            warn!("Failed to keep a version of profile '{}': {:#}", previous.name, e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn profile(name: &str, apps: &[&str]) -> ProfileConfig {
+        ProfileConfig {
+            name: name.to_string(),
+            match_apps: apps.iter().map(|a| a.to_string()).collect(),
+            match_bundle_ids: Vec::new(),
+            match_title_regex: None,
+            buttons: Vec::new(),
+            encoders: Vec::new(),
+            chords: Vec::new(),
+            strip: Default::default(),
+            font: None,
+            brightness: None,
+            tint: None,
+        }
+    }
+
+    #[test]
+    fn test_history() {
+        let dir = std::env::temp_dir().join(format!("claude-deck-history-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let first = profile("Work/Code", &["Code"]);
+        let second = profile("Work/Code", &["Code", "Cursor"]);
+        let other = profile("slack", &["Slack"]);
+        record_changes(&dir, &[first.clone(), other.clone()], &[second.clone(), other.clone()]);
+        assert_eq!(record(&dir, &first).unwrap(), None);
+        assert_eq!(record(&dir, &second).unwrap(), Some(2));
+        assert!(versions(&dir, "slack").is_empty());
+        assert_eq!(get(&dir, "work/code", 1).unwrap().profile.match_apps, ["Code"]);
+
+        for n in 0..MAX_VERSIONS {
+            record(&dir, &profile("Work/Code", &[&n.to_string()])).unwrap();
+        }
+        let kept = versions(&dir, "Work/Code");
+        assert_eq!(kept.len(), MAX_VERSIONS);
+        assert_eq!(kept[0].version, 3);
+
+        // A deleted profile keeps its last version
+        record_changes(&dir, &[other], &[]);
+        assert_eq!(versions(&dir, "slack").len(), 1);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -1,16 +1,21 @@
 //! App profiles for context-aware button configurations
 
+pub mod history;
+pub mod overlay;
 pub mod store;
+pub mod streamdeck;
 
 use image::Rgb;
-use std::sync::{Arc, RwLock};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, RwLock};
 
+use crate::device::FeedbackKind;
 use crate::display::renderer::{
     BLUE, BRIGHT_BLUE, BRIGHT_GRAY, BRIGHT_GREEN, BRIGHT_PURPLE, BRIGHT_RED, GRAY, GREEN, ORANGE,
     PURPLE, RED,
 };
 
-use store::ProfileConfig;
+use store::{ButtonConfigEntry, MacroStep, ProfileConfig};
 
 /// Application profile types
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -28,10 +33,80 @@ pub enum ButtonAction {
     Key(String),
     /// Type text directly (with optional auto-submit)
     Text { value: String, auto_submit: bool },
+    /// Type a template, leaving the cursor at its `{cursor}` marker
+    Snippet(String),
     /// Emoji shortcode (types `:emoji:`) (with optional auto-submit)
     Emoji { value: String, auto_submit: bool },
     /// Custom action handled by the input handler
     Custom(&'static str),
+    /// Sequence of key/text/delay/shell steps run in order
+    Macro(Vec<MacroStep>),
+    /// Toggle a named scene
+    Scene(String),
+    /// Send a shortcut and latch the button on/off
+    Toggle(String),
+    /// Run an AppleScript via `osascript`
+    AppleScript(String),
+    /// Open a URL in the default browser
+    OpenUrl(String),
+    /// Paste the Nth most recent clipboard entry (1-based)
+    Clipboard(usize),
+    /// OBS Studio command (see `integrations::obs`)
+    Obs(String),
+    /// Open a nested layout (see `overlay::Overlay::Folder`)
+    Folder(Vec<ButtonConfigEntry>),
+    /// Script path (see `input::script`)
+    Script(String),
+    /// Bring an app to the front by name, optionally switching to its profile now
+    FocusApp { name: String, switch_profile: bool },
+    /// Launch an app by path, optionally switching to its profile now
+    LaunchApp { path: String, switch_profile: bool },
+    /// Show a status monitor's health and open its page (see `monitors`)
+    Monitor(String),
+    /// Call a Home Assistant service on an entity (see `integrations::home_assistant`)
+    HomeAssistant { entity_id: String, service: String },
+    /// Scroll the mouse wheel by this many lines (negative scrolls up)
+    Scroll(i32),
+}
+
+/// What profiles are matched against: the focused app and its front window
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FocusedWindow<'a> {
+    /// Application name (e.g., "Terminal")
+    pub app: &'a str,
+    /// Bundle ID (e.g., "com.apple.Terminal"), empty if unknown
+    pub bundle_id: &'a str,
+    /// Front window title, empty if unknown
+    pub title: &'a str,
+    /// Profile `[projects]` picks for the Claude session's directory, empty if none
+    pub project_profile: &'a str,
+}
+
+impl<'a> FocusedWindow<'a> {
+    /// A window known only by its app name
+    pub fn app(app: &'a str) -> Self {
+        Self {
+            app,
+            ..Self::default()
+        }
+    }
+}
+
+/// Encoder input that a profile can bind an action to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EncoderInput {
+    RotateCw,
+    RotateCcw,
+    Press,
+}
+
+/// Touch strip gesture that a profile can bind an action to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StripGesture {
+    SwipeLeft,
+    SwipeRight,
+    /// Touch on a zone (0-3, left to right)
+    Touch(u8),
 }
 
 /// Button configuration for rendering and actions
@@ -44,8 +119,51 @@ pub struct ButtonConfig {
     pub emoji_image: Option<&'static str>,
     /// Optional custom image (base64 data URL)
     pub custom_image: Option<&'static str>,
+    /// Optional SVG icon (inline markup or a file path)
+    pub icon_svg: Option<&'static str>,
     /// Optional GIF URL for animated button
     pub gif_url: Option<&'static str>,
+    /// Override for the global keystroke pre-delay
+    pub pre_delay_ms: Option<u64>,
+    /// Override for the global inter-key delay
+    pub inter_key_delay_ms: Option<u64>,
+    /// Run the action repeatedly while the button is held
+    pub repeat_while_held: bool,
+    /// Time between repeats (default 100ms)
+    pub repeat_interval_ms: Option<u64>,
+    /// The first press asks for a second one, which runs the action
+    pub confirm: bool,
+    /// Play the press cue when the action runs
+    pub feedback: Option<FeedbackKind>,
+}
+
+impl ButtonConfig {
+    /// Unlabelled config that only carries an action (e.g. for encoder bindings)
+    pub fn from_action(action: ButtonAction) -> Self {
+        Self {
+            label: "",
+            colors: (GRAY, BRIGHT_GRAY),
+            action,
+            emoji_image: None,
+            custom_image: None,
+            icon_svg: None,
+            gif_url: None,
+            pre_delay_ms: None,
+            inter_key_delay_ms: None,
+            repeat_while_held: false,
+            repeat_interval_ms: None,
+            confirm: false,
+            feedback: None,
+        }
+    }
+
+    /// Placeholder for a position with nothing configured
+    pub fn empty() -> Self {
+        Self {
+            label: "---",
+            ..Self::from_action(ButtonAction::Custom(""))
+        }
+    }
 }
 
 /// Manager for profile configurations
@@ -53,12 +171,36 @@ pub struct ButtonConfig {
 #[derive(Debug, Clone, Default)]
 pub struct ProfileManager {
     profiles: Vec<ProfileConfig>,
+    /// Profile forced by the active scene, regardless of focused app
+    pinned: Option<String>,
+    /// Profile held by PIN_PROFILE until it is pressed again
+    held: Option<String>,
+    converted: ButtonCache,
+}
+
+/// Each profile's buttons as runtime configs, converted on first use
+///
+/// Converting an entry leaks its strings (`ButtonConfig` holds `&'static
+/// str`), so it happens once per profile load rather than on every redraw.
+/// Emptied whenever the profiles may have changed.
+#[derive(Debug, Default)]
+struct ButtonCache(Mutex<HashMap<String, HashMap<u8, ButtonConfig>>>);
+
+impl Clone for ButtonCache {
+    fn clone(&self) -> Self {
+        Self(Mutex::new(self.0.lock().unwrap_or_else(|e| e.into_inner()).clone()))
+    }
 }
 
 impl ProfileManager {
     /// Create a new profile manager with profiles from config
     pub fn new(profiles: Vec<ProfileConfig>) -> Self {
-        Self { profiles }
+        Self {
+            profiles,
+            pinned: None,
+            held: None,
+            converted: ButtonCache::default(),
+        }
     }
 
     /// Create a shared profile manager
@@ -69,6 +211,7 @@ impl ProfileManager {
     /// Update profiles (e.g., after config reload)
     pub fn set_profiles(&mut self, profiles: Vec<ProfileConfig>) {
         self.profiles = profiles;
+        self.converted = ButtonCache::default();
     }
 
     /// Get all profiles
@@ -83,42 +226,106 @@ impl ProfileManager {
 
     /// Get a mutable profile by name
     pub fn get_profile_mut(&mut self, name: &str) -> Option<&mut ProfileConfig> {
+        self.converted = ButtonCache::default();
         self.profiles.iter_mut().find(|p| p.name == name)
     }
 
-    /// Find the profile that matches an application name
-    pub fn find_profile_for_app(&self, app_name: &str) -> Option<&ProfileConfig> {
-        // First check for specific app matches (non-wildcard)
+    /// Pin a profile (used by scenes), or None to follow the focused app again
+    pub fn pin_profile(&mut self, name: Option<String>) {
+        self.pinned = name;
+    }
+
+    /// Hold a profile (PIN_PROFILE), or None to follow the focused app again
+    ///
+    /// An active scene's profile still takes precedence.
+    pub fn hold_profile(&mut self, name: Option<String>) {
+        self.held = name;
+    }
+
+    pub fn held_profile(&self) -> Option<&str> {
+        self.held.as_deref()
+    }
+
+    /// Find the profile that matches the focused window
+    ///
+    /// The most specific match wins (a profile matching app and title beats
+    /// one matching the app alone, which beats the wildcard); ties go to the
+    /// profile listed first. A `[projects]` profile takes the wildcard's place.
+    pub fn find_profile_for_app(&self, window: &FocusedWindow) -> Option<&ProfileConfig> {
+        // A pinned or held profile wins over app matching
+        let forced = [&self.pinned, &self.held].into_iter().flatten();
+        if let Some(profile) = forced.filter_map(|name| self.get_profile(name)).next() {
+            return Some(profile);
+        }
+
+        let mut best: Option<(&ProfileConfig, u8)> = None;
         for profile in &self.profiles {
-            if profile.match_apps.iter().any(|p| p != "*" && p.eq_ignore_ascii_case(app_name)) {
+            if let Some(score) = profile.match_score(window) {
+                if best.is_none_or(|(_, best_score)| score > best_score) {
+                    best = Some((profile, score));
+                }
+            }
+        }
+        if best.is_none_or(|(_, score)| score == 0) {
+            if let Some(profile) = self.get_profile(window.project_profile) {
                 return Some(profile);
             }
         }
-        // Fall back to wildcard profile
-        self.profiles.iter().find(|p| p.match_apps.contains(&"*".to_string()))
+        best.map(|(profile, _)| profile)
+    }
+
+    /// Name of the profile used for the focused window, if any
+    pub fn profile_name_for_app(&self, window: &FocusedWindow) -> Option<String> {
+        self.find_profile_for_app(window).map(|p| p.name.clone())
     }
 
-    /// Get button config for an app, falling back to hardcoded defaults
-    pub fn get_button_config(&self, app_name: &str, button_id: u8) -> ButtonConfig {
+    /// Get a profile's action for an encoder input (None means use the built-in behaviour)
+    pub fn get_encoder_action(
+        &self,
+        window: &FocusedWindow,
+        encoder: u8,
+        input: EncoderInput,
+    ) -> Option<ButtonAction> {
+        self.find_profile_for_app(window)?
+            .get_encoder_action(encoder, input)
+    }
+
+    /// Get a profile's action for a touch strip gesture
+    pub fn get_strip_action(
+        &self,
+        window: &FocusedWindow,
+        gesture: StripGesture,
+    ) -> Option<ButtonAction> {
+        self.find_profile_for_app(window)?.get_strip_action(gesture)
+    }
+
+    /// Get a profile's action for two buttons pressed together
+    pub fn get_chord_action(&self, window: &FocusedWindow, a: u8, b: u8) -> Option<ButtonAction> {
+        self.find_profile_for_app(window)?.get_chord_action(a, b)
+    }
+
+    /// Get button config for the focused window, falling back to hardcoded defaults
+    pub fn get_button_config(&self, window: &FocusedWindow, button_id: u8) -> ButtonConfig {
         // Try to find a matching profile with this button configured
-        if let Some(profile) = self.find_profile_for_app(app_name) {
-            if let Some(config) = profile.get_button(button_id) {
-                return config;
+        if let Some(profile) = self.find_profile_for_app(window) {
+            let mut converted = self.converted.0.lock().unwrap_or_else(|e| e.into_inner());
+            if !converted.contains_key(&profile.name) {
+                let mut buttons = HashMap::new();
+                for entry in &profile.buttons {
+                    buttons.entry(entry.position).or_insert_with(|| entry.to_button_config());
+                }
+                converted.insert(profile.name.clone(), buttons);
             }
             // Profile exists but button not configured - return empty button
             // (don't fall back to hardcoded defaults)
-            return ButtonConfig {
-                label: "---",
-                colors: (GRAY, BRIGHT_GRAY),
-                action: ButtonAction::Custom(""),
-                emoji_image: None,
-                custom_image: None,
-                gif_url: None,
-            };
+            return converted[&profile.name]
+                .get(&button_id)
+                .cloned()
+                .unwrap_or_else(ButtonConfig::empty);
         }
 
         // No profile found at all - fall back to hardcoded defaults
-        let profile = get_profile_for_app(app_name);
+        let profile = get_profile_for_app(window.app);
         profile.button_config(button_id)
     }
 }
@@ -188,7 +395,14 @@ impl AppProfile {
                         },
                         emoji_image: Some(image),
                         custom_image: None,
+                        icon_svg: None,
                         gif_url: None,
+                        pre_delay_ms: None,
+                        inter_key_delay_ms: None,
+                        repeat_while_held: false,
+                        repeat_interval_ms: None,
+                        confirm: false,
+                        feedback: None,
                     }
                 } else {
                     // Fallback for any unmapped buttons
@@ -201,7 +415,14 @@ impl AppProfile {
                         },
                         emoji_image: None,
                         custom_image: None,
+                        icon_svg: None,
                         gif_url: None,
+                        pre_delay_ms: None,
+                        inter_key_delay_ms: None,
+                        repeat_while_held: false,
+                        repeat_interval_ms: None,
+                        confirm: false,
+                        feedback: None,
                     }
                 }
             }
@@ -215,7 +436,14 @@ impl AppProfile {
                     action: ButtonAction::Custom(label),
                     emoji_image: None,
                     custom_image: None,
+                    icon_svg: None,
                     gif_url: None,
+                    pre_delay_ms: None,
+                    inter_key_delay_ms: None,
+                    repeat_while_held: false,
+                    repeat_interval_ms: None,
+                    confirm: false,
+                    feedback: None,
                 }
             }
         }
@@ -256,7 +484,14 @@ pub fn generate_default_profiles() -> Vec<ProfileConfig> {
                 },
                 emoji_image: None,
                 custom_image: None,
+                icon_svg: None,
                 gif_url: None,
+                pre_delay_ms: None,
+                inter_key_delay_ms: None,
+                repeat_while_held: false,
+                repeat_interval_ms: None,
+                confirm: false,
+                feedback: None,
             }
         })
         .collect();
@@ -275,7 +510,14 @@ pub fn generate_default_profiles() -> Vec<ProfileConfig> {
             },
             emoji_image: Some(image.to_string()),
             custom_image: None,
+            icon_svg: None,
             gif_url: None,
+            pre_delay_ms: None,
+            inter_key_delay_ms: None,
+            repeat_while_held: false,
+            repeat_interval_ms: None,
+            confirm: false,
+            feedback: None,
         })
         .collect();
 
@@ -283,12 +525,53 @@ pub fn generate_default_profiles() -> Vec<ProfileConfig> {
         ProfileConfig {
             name: "claude".to_string(),
             match_apps: vec!["*".to_string()],
+            match_bundle_ids: Vec::new(),
+            match_title_regex: None,
             buttons: claude_buttons,
+            encoders: Vec::new(),
+            chords: Vec::new(),
+            strip: Default::default(),
+            font: None,
+            brightness: None,
+            tint: None,
         },
         ProfileConfig {
             name: "slack".to_string(),
             match_apps: vec!["Slack".to_string()],
+            match_bundle_ids: Vec::new(),
+            match_title_regex: None,
             buttons: slack_buttons,
+            encoders: Vec::new(),
+            chords: Vec::new(),
+            strip: Default::default(),
+            font: None,
+            brightness: None,
+            tint: None,
         },
     ]
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_button_configs_converted_once() {
+        let mut manager = ProfileManager::new(generate_default_profiles());
+        let name = manager.get_profiles()[0].name.clone();
+        manager.pin_profile(Some(name.clone()));
+        let window = FocusedWindow::app("Terminal");
+        let position = manager.get_profiles()[0].buttons[0].position;
+
+        // Redraws reuse the converted config rather than leaking a new label
+        let first = manager.get_button_config(&window, position);
+        let again = manager.get_button_config(&window, position);
+        assert!(std::ptr::eq(first.label, again.label));
+        assert_eq!(manager.get_button_config(&window, 99).label, "---");
+
+        // Edits through the web API are picked up
+        let profile = manager.get_profile_mut(&name).unwrap();
+        profile.buttons[0].label = "EDITED".to_string();
+        assert_eq!(manager.get_button_config(&window, position).label, "EDITED");
+    }
+}
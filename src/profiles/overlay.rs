@@ -0,0 +1,135 @@
+//! Temporary button layers shown over the profile layout
+//!
+//! While an overlay is active the renderer draws its buttons and the input
+//! handler runs their actions instead of the profile's; buttons the overlay
+//! doesn't cover keep working as usual. Overlays stack: the permission
+//! quick actions sit above an open folder, which sits above the profile.
+
+use super::{ButtonAction, ButtonConfig};
+use crate::display::renderer::{
+    BLUE, BRIGHT_BLUE, BRIGHT_GRAY, BRIGHT_GREEN, BRIGHT_PURPLE, BRIGHT_RED, GRAY, GREEN, PURPLE,
+    RED,
+};
+use crate::state::{AppState, InputType};
+
+/// A layer of buttons that temporarily replaces part of the layout
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Overlay {
+    /// Allow once / Allow always / Deny / Show diff while Claude asks for permission
+    Permission,
+    /// The innermost open folder, with BACK on button 0
+    Folder,
+}
+
+/// Button the folder overlay puts its BACK action on
+pub const FOLDER_BACK_BUTTON: u8 = 0;
+
+impl Overlay {
+    /// Overlays shown for the current state, topmost first
+    fn shown(state: &AppState) -> impl Iterator<Item = Self> {
+        let permission_prompt =
+            state.waiting_for_input && state.input_type == Some(InputType::Permission);
+        let permission = permission_prompt && state.permissions.quick_actions;
+        let folder = !state.folders.is_empty();
+        [
+            permission.then_some(Overlay::Permission),
+            folder.then_some(Overlay::Folder),
+        ]
+        .into_iter()
+        .flatten()
+    }
+
+    /// The topmost overlay for the current state, if any
+    pub fn active(state: &AppState) -> Option<Self> {
+        Self::shown(state).next()
+    }
+
+    /// The overlay's config for a button, or None if the profile button shows through
+    pub fn button(&self, state: &AppState, button_id: u8) -> Option<ButtonConfig> {
+        match self {
+            Overlay::Permission => {
+                let keys = &state.permissions;
+                let (label, colors, key) = match button_id {
+                    0 => ("ALLOW", (GREEN, BRIGHT_GREEN), &keys.allow_once),
+                    1 => ("ALWAYS", (BLUE, BRIGHT_BLUE), &keys.allow_always),
+                    2 => ("DENY", (RED, BRIGHT_RED), &keys.deny),
+                    3 => ("DIFF", (PURPLE, BRIGHT_PURPLE), &keys.show_diff),
+                    _ => return None,
+                };
+                let mut config = ButtonConfig::from_action(ButtonAction::Key(key.clone()));
+                config.label = label;
+                config.colors = colors;
+                Some(config)
+            }
+            Overlay::Folder => {
+                let folder = state.folders.last()?;
+                if button_id == FOLDER_BACK_BUTTON {
+                    let mut config = ButtonConfig::from_action(ButtonAction::Custom("FOLDER_BACK"));
+                    config.label = "BACK";
+                    config.colors = (GRAY, BRIGHT_GRAY);
+                    return Some(config);
+                }
+                // The whole deck belongs to the folder, so gaps show as empty
+                Some(folder.buttons.get(&button_id).cloned().unwrap_or_else(ButtonConfig::empty))
+            }
+        }
+    }
+}
+
+/// The topmost overlay's config for a button, if one covers it
+pub fn overlay_button(state: &AppState, button_id: u8) -> Option<ButtonConfig> {
+    Overlay::shown(state).find_map(|overlay| overlay.button(state, button_id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::Folder;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_permission_overlay_covers_top_row_only() {
+        let mut state = AppState::new();
+        assert!(overlay_button(&state, 0).is_none());
+
+        state.waiting_for_input = true;
+        state.input_type = Some(InputType::YesNo);
+        assert!(overlay_button(&state, 0).is_none());
+
+        state.input_type = Some(InputType::Permission);
+        assert_eq!(Overlay::active(&state), Some(Overlay::Permission));
+        let allow = overlay_button(&state, 0).unwrap();
+        assert_eq!(allow.label, "ALLOW");
+        assert!(matches!(allow.action, ButtonAction::Key(ref key) if key == "1"));
+        assert!(overlay_button(&state, 3).is_some());
+        assert!(overlay_button(&state, 4).is_none());
+        assert!(overlay_button(&state, 5).is_none());
+
+        state.permissions.quick_actions = false;
+        assert!(overlay_button(&state, 0).is_none());
+    }
+
+    #[test]
+    fn test_folder_covers_deck_below_permission_overlay() {
+        let mut state = AppState::new();
+        let mut open = ButtonConfig::from_action(ButtonAction::Key("Cmd+O".into()));
+        open.label = "OPEN";
+        state.folders.push(Folder {
+            label: "FILES".into(),
+            buttons: HashMap::from([(5, open)]),
+        });
+        assert_eq!(Overlay::active(&state), Some(Overlay::Folder));
+
+        let back = overlay_button(&state, 0).unwrap();
+        assert!(matches!(back.action, ButtonAction::Custom("FOLDER_BACK")));
+        assert_eq!(overlay_button(&state, 5).unwrap().label, "OPEN");
+        assert_eq!(overlay_button(&state, 4).unwrap().label, "---");
+
+        // Permission quick actions take the top row; the folder keeps the rest
+        state.waiting_for_input = true;
+        state.input_type = Some(InputType::Permission);
+        assert_eq!(Overlay::active(&state), Some(Overlay::Permission));
+        assert_eq!(overlay_button(&state, 0).unwrap().label, "ALLOW");
+        assert_eq!(overlay_button(&state, 5).unwrap().label, "OPEN");
+    }
+}
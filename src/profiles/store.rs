@@ -4,9 +4,14 @@
 //! and can be loaded/saved by the web UI.
 
 use image::Rgb;
+use regex_automata::meta::Regex;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+use tracing::warn;
 
-use super::{ButtonAction, ButtonConfig};
+use super::{ButtonAction, ButtonConfig, EncoderInput, FocusedWindow, StripGesture};
+use crate::device::FeedbackKind;
 
 /// Action configuration for buttons (serializable)
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -20,6 +25,8 @@ pub enum ActionConfig {
         #[serde(default)]
         auto_submit: bool,
     },
+    /// Type a template and move the cursor back to its `{cursor}` marker
+    Snippet { template: String },
     /// Emoji shortcode (types `:emoji:`)
     #[serde(alias = "slack_emoji")]  // Backwards compatibility
     Emoji {
@@ -29,9 +36,66 @@ pub enum ActionConfig {
     },
     /// Custom action handled by the input handler
     Custom { value: String },
+    /// Sequence of steps executed in order
+    Macro { steps: Vec<MacroStep> },
+    /// Toggle a named scene (see `[[scenes]]` in config)
+    Scene { value: String },
+    /// Send a shortcut and latch the button on/off (state survives restarts)
+    Toggle { value: String },
+    /// Run an AppleScript via `osascript`
+    #[serde(rename = "applescript")]
+    AppleScript { script: String },
+    /// Open a URL in the default browser
+    OpenUrl { url: String },
+    /// Paste an entry from the clipboard history (slot 1 = most recent)
+    Clipboard { slot: usize },
+    /// Control OBS Studio (e.g., "scene:Coding", "toggle_recording", "mute_mic")
+    Obs { command: String },
+    /// Open a nested layout of buttons (button 0 becomes BACK)
+    Folder { buttons: Vec<ButtonConfigEntry> },
+    /// Run a script that prints the steps to perform (see `input::script`)
+    Script { path: String },
+    /// Bring a running app to the front, launching it if needed
+    FocusApp {
+        name: String,
+        /// Show the app's profile right away instead of on the next focus poll
+        #[serde(default)]
+        switch_profile: bool,
+    },
+    /// Launch an app by path (e.g. "/Applications/Firefox Developer Edition.app")
+    LaunchApp {
+        path: String,
+        #[serde(default)]
+        switch_profile: bool,
+    },
+    /// Show a `[[monitors]]` entry's health and open its page
+    Monitor { name: String },
+    /// Call a Home Assistant service (e.g., "toggle", "scene.turn_on") on an entity
+    HomeAssistant { entity_id: String, service: String },
+    /// Scroll the mouse wheel (positive ticks scroll down, negative up)
+    Scroll { ticks: i32 },
+}
+
+/// A single step in a macro sequence
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum MacroStep {
+    /// Send a keyboard shortcut (e.g., "Enter", "Cmd+C")
+    Key { value: String },
+    /// Type text directly
+    Text { value: String },
+    /// Wait before running the next step
+    Delay { ms: u64 },
+    /// Run a shell command and wait for it to finish
+    Shell { command: String },
 }
 
 impl ActionConfig {
+    /// Whether this is the built-in custom action `name` (e.g. "MIC")
+    pub fn is_custom(&self, name: &str) -> bool {
+        matches!(self, ActionConfig::Custom { value } if value == name)
+    }
+
     /// Convert to runtime ButtonAction
     pub fn to_button_action(&self) -> ButtonAction {
         match self {
@@ -52,6 +116,30 @@ impl ActionConfig {
                 // This is acceptable since profiles are loaded once at startup
                 ButtonAction::Custom(Box::leak(value.clone().into_boxed_str()))
             }
+            ActionConfig::Snippet { template } => ButtonAction::Snippet(template.clone()),
+            ActionConfig::Macro { steps } => ButtonAction::Macro(steps.clone()),
+            ActionConfig::Scene { value } => ButtonAction::Scene(value.clone()),
+            ActionConfig::Toggle { value } => ButtonAction::Toggle(value.clone()),
+            ActionConfig::AppleScript { script } => ButtonAction::AppleScript(script.clone()),
+            ActionConfig::OpenUrl { url } => ButtonAction::OpenUrl(url.clone()),
+            ActionConfig::Clipboard { slot } => ButtonAction::Clipboard(*slot),
+            ActionConfig::Obs { command } => ButtonAction::Obs(command.clone()),
+            ActionConfig::Folder { buttons } => ButtonAction::Folder(buttons.clone()),
+            ActionConfig::Script { path } => ButtonAction::Script(path.clone()),
+            ActionConfig::FocusApp { name, switch_profile } => ButtonAction::FocusApp {
+                name: name.clone(),
+                switch_profile: *switch_profile,
+            },
+            ActionConfig::LaunchApp { path, switch_profile } => ButtonAction::LaunchApp {
+                path: path.clone(),
+                switch_profile: *switch_profile,
+            },
+            ActionConfig::Monitor { name } => ButtonAction::Monitor(name.clone()),
+            ActionConfig::HomeAssistant { entity_id, service } => ButtonAction::HomeAssistant {
+                entity_id: entity_id.clone(),
+                service: service.clone(),
+            },
+            ActionConfig::Scroll { ticks } => ButtonAction::Scroll(*ticks),
         }
     }
 
@@ -72,6 +160,44 @@ impl ActionConfig {
             ButtonAction::Custom(value) => ActionConfig::Custom {
                 value: value.to_string(),
             },
+            ButtonAction::Snippet(template) => ActionConfig::Snippet {
+                template: template.clone(),
+            },
+            ButtonAction::Macro(steps) => ActionConfig::Macro {
+                steps: steps.clone(),
+            },
+            ButtonAction::Scene(name) => ActionConfig::Scene {
+                value: name.clone(),
+            },
+            ButtonAction::Toggle(shortcut) => ActionConfig::Toggle {
+                value: shortcut.clone(),
+            },
+            ButtonAction::AppleScript(script) => ActionConfig::AppleScript {
+                script: script.clone(),
+            },
+            ButtonAction::OpenUrl(url) => ActionConfig::OpenUrl { url: url.clone() },
+            ButtonAction::Clipboard(slot) => ActionConfig::Clipboard { slot: *slot },
+            ButtonAction::Obs(command) => ActionConfig::Obs {
+                command: command.clone(),
+            },
+            ButtonAction::Folder(buttons) => ActionConfig::Folder {
+                buttons: buttons.clone(),
+            },
+            ButtonAction::Script(path) => ActionConfig::Script { path: path.clone() },
+            ButtonAction::FocusApp { name, switch_profile } => ActionConfig::FocusApp {
+                name: name.clone(),
+                switch_profile: *switch_profile,
+            },
+            ButtonAction::LaunchApp { path, switch_profile } => ActionConfig::LaunchApp {
+                path: path.clone(),
+                switch_profile: *switch_profile,
+            },
+            ButtonAction::Monitor(name) => ActionConfig::Monitor { name: name.clone() },
+            ButtonAction::HomeAssistant { entity_id, service } => ActionConfig::HomeAssistant {
+                entity_id: entity_id.clone(),
+                service: service.clone(),
+            },
+            ButtonAction::Scroll(ticks) => ActionConfig::Scroll { ticks: *ticks },
         }
     }
 }
@@ -95,9 +221,30 @@ pub struct ButtonConfigEntry {
     /// Optional custom image (base64 data URL)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub custom_image: Option<String>,
+    /// Optional SVG icon: inline `<svg>` markup or the path of a .svg file
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub icon_svg: Option<String>,
     /// Optional GIF URL for animated button
     #[serde(skip_serializing_if = "Option::is_none")]
     pub gif_url: Option<String>,
+    /// Delay before this action's first keystroke (overrides global setting)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pre_delay_ms: Option<u64>,
+    /// Delay between keys within a shortcut (overrides global setting)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub inter_key_delay_ms: Option<u64>,
+    /// Repeat the action until the button is released (e.g. arrow keys)
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub repeat_while_held: bool,
+    /// Time between repeats while held
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub repeat_interval_ms: Option<u64>,
+    /// Only act on a second press within 2 seconds (for destructive actions)
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub confirm: bool,
+    /// Play the press cue when the action runs ("click" or "double")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub feedback: Option<FeedbackKind>,
 }
 
 impl ButtonConfigEntry {
@@ -118,10 +265,20 @@ impl ButtonConfigEntry {
                 .custom_image
                 .as_ref()
                 .map(|s| Box::leak(s.clone().into_boxed_str()) as &'static str),
+            icon_svg: self
+                .icon_svg
+                .as_ref()
+                .map(|s| Box::leak(s.clone().into_boxed_str()) as &'static str),
             gif_url: self
                 .gif_url
                 .as_ref()
                 .map(|s| Box::leak(s.clone().into_boxed_str()) as &'static str),
+            pre_delay_ms: self.pre_delay_ms,
+            inter_key_delay_ms: self.inter_key_delay_ms,
+            repeat_while_held: self.repeat_while_held,
+            repeat_interval_ms: self.repeat_interval_ms,
+            confirm: self.confirm,
+            feedback: self.feedback,
         }
     }
 
@@ -135,11 +292,131 @@ impl ButtonConfigEntry {
             action: ActionConfig::from_button_action(&config.action),
             emoji_image: config.emoji_image.map(|s| s.to_string()),
             custom_image: config.custom_image.map(|s| s.to_string()),
+            icon_svg: config.icon_svg.map(|s| s.to_string()),
             gif_url: config.gif_url.map(|s| s.to_string()),
+            pre_delay_ms: config.pre_delay_ms,
+            inter_key_delay_ms: config.inter_key_delay_ms,
+            repeat_while_held: config.repeat_while_held,
+            repeat_interval_ms: config.repeat_interval_ms,
+            confirm: config.confirm,
+            feedback: config.feedback,
         }
     }
 }
 
+/// Built-in behaviour a knob's rotation can be given as a whole
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum EncoderMapping {
+    /// Turning scrolls the mouse wheel instead of sending PageUp/PageDown
+    #[serde(rename = "SCROLL")]
+    Scroll,
+}
+
+fn default_ticks_per_detent() -> u32 {
+    1
+}
+
+/// Encoder (knob) configuration entry
+///
+/// Inputs left unset keep the built-in behaviour for that knob.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EncoderConfigEntry {
+    /// Encoder index (0-3, left to right)
+    pub encoder: u8,
+    /// Mapping for both rotations (`rotate_cw`/`rotate_ccw` still take precedence)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mapping: Option<EncoderMapping>,
+    /// Scroll lines per detent for the `SCROLL` mapping
+    #[serde(default = "default_ticks_per_detent")]
+    pub ticks_per_detent: u32,
+    /// Action for one clockwise detent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rotate_cw: Option<ActionConfig>,
+    /// Action for one counter-clockwise detent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rotate_ccw: Option<ActionConfig>,
+    /// Action when the knob is pressed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub press: Option<ActionConfig>,
+}
+
+impl EncoderConfigEntry {
+    /// Configured action for an input, if any
+    pub fn action(&self, input: EncoderInput) -> Option<ActionConfig> {
+        let action = match input {
+            EncoderInput::RotateCw => self.rotate_cw.as_ref(),
+            EncoderInput::RotateCcw => self.rotate_ccw.as_ref(),
+            EncoderInput::Press => self.press.as_ref(),
+        };
+        if let Some(action) = action {
+            return Some(action.clone());
+        }
+        let direction = match input {
+            EncoderInput::RotateCw => 1,
+            EncoderInput::RotateCcw => -1,
+            EncoderInput::Press => return None,
+        };
+        let ticks = i32::try_from(self.ticks_per_detent).unwrap_or(i32::MAX);
+        match self.mapping? {
+            EncoderMapping::Scroll => Some(ActionConfig::Scroll { ticks: direction * ticks }),
+        }
+    }
+}
+
+/// Actions for touch strip gestures (`[profiles.strip]`)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct StripGestureConfig {
+    /// Action for a swipe to the left
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub swipe_left: Option<ActionConfig>,
+    /// Action for a swipe to the right
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub swipe_right: Option<ActionConfig>,
+    /// Actions for touching a zone of the strip
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub touch: Vec<StripTouchEntry>,
+}
+
+/// Action for touching one zone of the strip
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StripTouchEntry {
+    /// Zone (0-3, left to right, under the strip's quadrants)
+    pub zone: u8,
+    pub action: ActionConfig,
+}
+
+impl StripGestureConfig {
+    pub fn is_empty(&self) -> bool {
+        self.swipe_left.is_none() && self.swipe_right.is_none() && self.touch.is_empty()
+    }
+
+    /// Configured action for a gesture, if any
+    pub fn action(&self, gesture: StripGesture) -> Option<&ActionConfig> {
+        match gesture {
+            StripGesture::SwipeLeft => self.swipe_left.as_ref(),
+            StripGesture::SwipeRight => self.swipe_right.as_ref(),
+            StripGesture::Touch(zone) => {
+                self.touch.iter().find(|t| t.zone == zone).map(|t| &t.action)
+            }
+        }
+    }
+}
+
+/// Two buttons pressed together, running their own action instead of either button's
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChordConfigEntry {
+    /// Button positions (0-9), in either order
+    pub buttons: [u8; 2],
+    pub action: ActionConfig,
+}
+
+impl ChordConfigEntry {
+    /// Whether this chord is the two given buttons
+    pub fn matches(&self, a: u8, b: u8) -> bool {
+        self.buttons == [a, b] || self.buttons == [b, a]
+    }
+}
+
 /// Profile configuration for an application
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ProfileConfig {
@@ -147,28 +424,109 @@ pub struct ProfileConfig {
     pub name: String,
     /// Applications this profile matches (e.g., ["Slack"], ["*"] for default)
     pub match_apps: Vec<String>,
+    /// Bundle IDs this profile matches (e.g., ["com.googlecode.iterm2"])
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub match_bundle_ids: Vec<String>,
+    /// Regex the front window's title must match (e.g., "prod")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub match_title_regex: Option<String>,
     /// Button configurations
     pub buttons: Vec<ButtonConfigEntry>,
+    /// Encoder overrides (knobs not listed keep their built-in behaviour)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub encoders: Vec<EncoderConfigEntry>,
+    /// Button pairs with an action of their own (e.g. ACCEPT+ENTER)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub chords: Vec<ChordConfigEntry>,
+    /// Touch strip swipes and zone touches
+    #[serde(default, skip_serializing_if = "StripGestureConfig::is_empty")]
+    pub strip: StripGestureConfig,
+    /// Font file used while this profile is shown (overrides `[appearance] font_path`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub font: Option<String>,
+    /// Deck brightness (0-100) while this profile is shown; the earlier level
+    /// comes back when a profile without one is shown
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub brightness: Option<u8>,
+    /// Hex color multiplied over rendered buttons (e.g. "#ffd8a0" for warmer)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tint: Option<String>,
 }
 
 impl ProfileConfig {
     /// Check if this profile matches an application name
     pub fn matches_app(&self, app_name: &str) -> bool {
-        self.match_apps.iter().any(|pattern| {
-            if pattern == "*" {
-                true
-            } else {
-                pattern.eq_ignore_ascii_case(app_name)
+        self.match_score(&FocusedWindow::app(app_name)).is_some()
+    }
+
+    /// How specifically this profile matches a window
+    ///
+    /// Every matcher that is set must match. Returns None if one doesn't,
+    /// otherwise one point per app name, bundle ID or title matcher (so the
+    /// `*` wildcard alone scores 0).
+    pub fn match_score(&self, window: &FocusedWindow) -> Option<u8> {
+        let wildcard = self.match_apps.iter().any(|p| p == "*");
+        let mut score = 0;
+
+        if !self.match_apps.is_empty() {
+            if self.match_apps.iter().any(|p| p != "*" && p.eq_ignore_ascii_case(window.app)) {
+                score += 1;
+            } else if !wildcard {
+                return None;
             }
-        })
+        }
+        if !self.match_bundle_ids.is_empty() {
+            if !self.match_bundle_ids.iter().any(|id| id.eq_ignore_ascii_case(window.bundle_id)) {
+                return None;
+            }
+            score += 1;
+        }
+        if let Some(ref pattern) = self.match_title_regex {
+            if !title_matches(pattern, window.title) {
+                return None;
+            }
+            score += 1;
+        }
+
+        // A profile without any matchers matches nothing
+        (score > 0 || wildcard).then_some(score)
     }
 
-    /// Get button config for a position, if defined
-    pub fn get_button(&self, position: u8) -> Option<ButtonConfig> {
-        self.buttons
+    /// Get the configured action for an encoder input, if defined
+    pub fn get_encoder_action(&self, encoder: u8, input: EncoderInput) -> Option<ButtonAction> {
+        self.encoders
             .iter()
-            .find(|b| b.position == position)
-            .map(|b| b.to_button_config())
+            .find(|e| e.encoder == encoder)
+            .and_then(|e| e.action(input))
+            .map(|action| action.to_button_action())
+    }
+
+    /// Emoji shown on the profile's buttons, including those in folders
+    pub fn emoji_images(&self) -> Vec<&str> {
+        fn collect<'a>(buttons: &'a [ButtonConfigEntry], emoji: &mut Vec<&'a str>) {
+            for button in buttons {
+                emoji.extend(button.emoji_image.as_deref());
+                if let ActionConfig::Folder { ref buttons } = button.action {
+                    collect(buttons, emoji);
+                }
+            }
+        }
+        let mut emoji = Vec::new();
+        collect(&self.buttons, &mut emoji);
+        emoji
+    }
+
+    /// Get the configured action for a touch strip gesture, if defined
+    pub fn get_strip_action(&self, gesture: StripGesture) -> Option<ButtonAction> {
+        self.strip.action(gesture).map(|action| action.to_button_action())
+    }
+
+    /// Get the action for two buttons pressed together, if defined
+    pub fn get_chord_action(&self, a: u8, b: u8) -> Option<ButtonAction> {
+        self.chords
+            .iter()
+            .find(|chord| chord.matches(a, b))
+            .map(|chord| chord.action.to_button_action())
     }
 }
 
@@ -192,6 +550,26 @@ pub fn rgb_to_hex(color: Rgb<u8>) -> String {
 }
 
 
+/// Whether a window title matches a profile's title regex
+///
+/// Compiled patterns are cached; an invalid pattern is logged once and never matches.
+fn title_matches(pattern: &str, title: &str) -> bool {
+    static PATTERNS: OnceLock<Mutex<HashMap<String, Option<Regex>>>> = OnceLock::new();
+
+    let mut patterns = PATTERNS
+        .get_or_init(Default::default)
+        .lock()
+        .unwrap_or_else(|e| e.into_inner());
+    let regex = patterns.entry(pattern.to_string()).or_insert_with(|| match Regex::new(pattern) {
+        Ok(regex) => Some(regex),
+        Err(e) => {
+            warn!("Invalid match_title_regex '{}': {}", pattern, e);
+            None
+        }
+    });
+    regex.as_ref().is_some_and(|regex| regex.is_match(title))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -216,7 +594,15 @@ mod tests {
         let profile = ProfileConfig {
             name: "test".to_string(),
             match_apps: vec!["Slack".to_string(), "Discord".to_string()],
+            match_bundle_ids: Vec::new(),
+            match_title_regex: None,
             buttons: vec![],
+            encoders: vec![],
+            chords: vec![],
+            strip: Default::default(),
+            font: None,
+            brightness: None,
+            tint: None,
         };
 
         assert!(profile.matches_app("Slack"));
@@ -225,12 +611,291 @@ mod tests {
         assert!(!profile.matches_app("Terminal"));
     }
 
+    #[test]
+    fn test_profile_matches_bundle_and_title() {
+        let mut profile = ProfileConfig {
+            name: "prod".to_string(),
+            match_apps: vec!["iTerm2".to_string()],
+            match_bundle_ids: vec!["com.googlecode.iterm2".to_string()],
+            match_title_regex: Some("(?i)\\bprod\\b".to_string()),
+            buttons: vec![],
+            encoders: vec![],
+            chords: vec![],
+            strip: Default::default(),
+            font: None,
+            brightness: None,
+            tint: None,
+        };
+        let window = |title| FocusedWindow {
+            app: "iTerm2",
+            bundle_id: "com.googlecode.iterm2",
+            title,
+            project_profile: "",
+        };
+
+        assert_eq!(profile.match_score(&window("ssh PROD-db")), Some(3));
+        assert_eq!(profile.match_score(&window("reproduce bug")), None);
+        assert_eq!(profile.match_score(&window("staging")), None);
+        assert!(!profile.matches_app("iTerm2"));
+
+        // Title-only profile alongside the wildcard
+        profile.match_apps = vec!["*".to_string()];
+        profile.match_bundle_ids.clear();
+        assert_eq!(profile.match_score(&window("prod")), Some(1));
+
+        profile.match_title_regex = Some("(".to_string());
+        assert_eq!(profile.match_score(&window("prod")), None);
+    }
+
+    #[test]
+    fn test_button_feedback_toml() {
+        let entry: ButtonConfigEntry = toml::from_str(
+            r##"
+            position = 2
+            label = "SAVE"
+            color = "#00C864"
+            bright_color = "#00FF80"
+            feedback = "double"
+            action = { type = "key", value = "s" }
+        "##,
+        )
+        .unwrap();
+        let config = entry.to_button_config();
+        assert_eq!(config.feedback, Some(FeedbackKind::Double));
+        let saved = toml::to_string(&ButtonConfigEntry::from_button_config(2, &config)).unwrap();
+        assert!(saved.contains(r#"feedback = "double""#));
+
+        let plain = ButtonConfig { feedback: None, ..config };
+        let saved = toml::to_string(&ButtonConfigEntry::from_button_config(2, &plain)).unwrap();
+        assert!(!saved.contains("feedback"));
+    }
+
+    #[test]
+    fn test_macro_action_toml() {
+        let toml_str = r#"
+            type = "macro"
+
+            [[steps]]
+            type = "text"
+            value = "/compact"
+
+            [[steps]]
+            type = "delay"
+            ms = 200
+
+            [[steps]]
+            type = "key"
+            value = "Enter"
+        "#;
+
+        let action: ActionConfig = toml::from_str(toml_str).unwrap();
+        match action.to_button_action() {
+            ButtonAction::Macro(steps) => {
+                assert_eq!(steps.len(), 3);
+                assert_eq!(steps[1], MacroStep::Delay { ms: 200 });
+            }
+            other => panic!("Expected macro action, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_folder_action_toml() {
+        let toml_str = r##"
+            position = 9
+            label = "GIT"
+            color = "#F05032"
+            bright_color = "#FF7050"
+
+            [action]
+            type = "folder"
+
+            [[action.buttons]]
+            position = 1
+            label = "STATUS"
+            color = "#505560"
+            bright_color = "#6E7380"
+            action = { type = "text", value = "git status", auto_submit = true }
+        "##;
+
+        let entry: ButtonConfigEntry = toml::from_str(toml_str).unwrap();
+        match entry.to_button_config().action {
+            ButtonAction::Folder(buttons) => {
+                assert_eq!(buttons.len(), 1);
+                assert_eq!(buttons[0].label, "STATUS");
+                assert!(matches!(buttons[0].action, ActionConfig::Text { auto_submit: true, .. }));
+            }
+            other => panic!("Expected folder action, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_encoder_overrides_toml() {
+        let toml_str = r#"
+            name = "browser"
+            match_apps = ["Safari"]
+            buttons = []
+
+            [[encoders]]
+            encoder = 2
+            rotate_cw = { type = "key", value = "Ctrl+Tab" }
+            rotate_ccw = { type = "key", value = "Ctrl+Shift+Tab" }
+        "#;
+
+        let profile: ProfileConfig = toml::from_str(toml_str).unwrap();
+        assert!(matches!(
+            profile.get_encoder_action(2, EncoderInput::RotateCw),
+            Some(ButtonAction::Key(ref k)) if k == "Ctrl+Tab"
+        ));
+        assert!(profile.get_encoder_action(2, EncoderInput::Press).is_none());
+        assert!(profile.get_encoder_action(0, EncoderInput::RotateCw).is_none());
+    }
+
+    #[test]
+    fn test_encoder_scroll_mapping_toml() {
+        let toml_str = r#"
+            name = "browser"
+            match_apps = ["Safari"]
+            buttons = []
+
+            [[encoders]]
+            encoder = 1
+            mapping = "SCROLL"
+            ticks_per_detent = 3
+
+            [[encoders]]
+            encoder = 2
+            mapping = "SCROLL"
+            rotate_ccw = { type = "key", value = "Home" }
+        "#;
+
+        let profile: ProfileConfig = toml::from_str(toml_str).unwrap();
+        assert!(matches!(
+            profile.get_encoder_action(1, EncoderInput::RotateCw),
+            Some(ButtonAction::Scroll(3))
+        ));
+        assert!(matches!(
+            profile.get_encoder_action(1, EncoderInput::RotateCcw),
+            Some(ButtonAction::Scroll(-3))
+        ));
+        assert!(profile.get_encoder_action(1, EncoderInput::Press).is_none());
+        assert!(matches!(
+            profile.get_encoder_action(2, EncoderInput::RotateCw),
+            Some(ButtonAction::Scroll(1))
+        ));
+        assert!(matches!(
+            profile.get_encoder_action(2, EncoderInput::RotateCcw),
+            Some(ButtonAction::Key(ref k)) if k == "Home"
+        ));
+    }
+
+    #[test]
+    fn test_chords_toml() {
+        let toml_str = r#"
+            name = "claude"
+            match_apps = ["*"]
+            buttons = []
+
+            [[chords]]
+            buttons = [0, 6]
+            action = { type = "text", value = "yes to all", auto_submit = true }
+        "#;
+
+        let profile: ProfileConfig = toml::from_str(toml_str).unwrap();
+        assert!(matches!(profile.get_chord_action(6, 0), Some(ButtonAction::Text { .. })));
+        assert!(profile.get_chord_action(0, 1).is_none());
+    }
+
+    #[test]
+    fn test_strip_gestures_toml() {
+        let toml_str = r#"
+            name = "claude"
+            match_apps = ["*"]
+            buttons = []
+
+            [strip]
+            swipe_left = { type = "custom", value = "SESSION_PREV" }
+
+            [[strip.touch]]
+            zone = 3
+            action = { type = "key", value = "End" }
+        "#;
+
+        let profile: ProfileConfig = toml::from_str(toml_str).unwrap();
+        assert!(matches!(
+            profile.get_strip_action(StripGesture::SwipeLeft),
+            Some(ButtonAction::Custom("SESSION_PREV"))
+        ));
+        assert!(profile.get_strip_action(StripGesture::SwipeRight).is_none());
+        assert!(matches!(
+            profile.get_strip_action(StripGesture::Touch(3)),
+            Some(ButtonAction::Key(ref k)) if k == "End"
+        ));
+        assert!(profile.get_strip_action(StripGesture::Touch(0)).is_none());
+
+        // Profiles without gestures don't write an empty [strip] table
+        let plain: ProfileConfig =
+            toml::from_str("name = \"a\"\nmatch_apps = []\nbuttons = []").unwrap();
+        assert!(!toml::to_string(&plain).unwrap().contains("strip"));
+    }
+
+    #[test]
+    fn test_applescript_action_toml() {
+        let toml_str = r#"
+            type = "applescript"
+            script = 'tell application "Music" to playpause'
+        "#;
+
+        let action: ActionConfig = toml::from_str(toml_str).unwrap();
+        match action.to_button_action() {
+            ButtonAction::AppleScript(script) => assert!(script.contains("playpause")),
+            other => panic!("Expected AppleScript action, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_app_actions_toml() {
+        let focus: ActionConfig = toml::from_str(
+            r#"
+            type = "focus_app"
+            name = "Slack"
+            switch_profile = true
+        "#,
+        )
+        .unwrap();
+        assert!(matches!(
+            focus.to_button_action(),
+            ButtonAction::FocusApp { ref name, switch_profile: true } if name == "Slack"
+        ));
+
+        let launch: ActionConfig = toml::from_str(
+            r#"
+            type = "launch_app"
+            path = "/Applications/Firefox Developer Edition.app"
+        "#,
+        )
+        .unwrap();
+        let action = launch.to_button_action();
+        assert!(matches!(action, ButtonAction::LaunchApp { switch_profile: false, .. }));
+        assert!(matches!(
+            ActionConfig::from_button_action(&action),
+            ActionConfig::LaunchApp { ref path, .. } if path.ends_with("Edition.app")
+        ));
+    }
+
     #[test]
     fn test_profile_wildcard() {
         let profile = ProfileConfig {
             name: "default".to_string(),
             match_apps: vec!["*".to_string()],
+            match_bundle_ids: Vec::new(),
+            match_title_regex: None,
             buttons: vec![],
+            encoders: vec![],
+            chords: vec![],
+            strip: Default::default(),
+            font: None,
+            brightness: None,
+            tint: None,
         };
 
         assert!(profile.matches_app("Slack"));
@@ -0,0 +1,387 @@
+//! Import layouts from exported Elgato Stream Deck profiles
+//!
+//! A `.streamDeckProfile` export is a zip archive containing one or more
+//! `manifest.json` files (one per page). Each manifest maps `"col,row"` keys
+//! to actions identified by plugin UUID. We convert the built-in actions we
+//! have equivalents for and report the rest as skipped.
+
+use anyhow::{bail, Context, Result};
+use base64::Engine;
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+use tracing::debug;
+
+use super::store::{ActionConfig, ButtonConfigEntry, MacroStep, ProfileConfig};
+
+/// Deck layout: 5 columns x 2 rows (positions 0-9)
+const COLUMNS: u64 = 5;
+const ROWS: u64 = 2;
+
+/// Result of converting a Stream Deck profile
+#[derive(Debug)]
+pub struct ImportResult {
+    pub profile: ProfileConfig,
+    /// Human-readable notes about keys that couldn't be converted
+    pub skipped: Vec<String>,
+}
+
+/// Import a `.streamDeckProfile` file (zip) or an already-extracted directory
+pub fn import_profile(path: &Path) -> Result<ImportResult> {
+    if path.is_dir() {
+        return import_from_dir(path);
+    }
+
+    // Exports are zip archives; extract with the system unzip tool
+    let extract_dir = std::env::temp_dir().join(format!("claude-deck-import-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&extract_dir);
+    std::fs::create_dir_all(&extract_dir)
+        .with_context(|| format!("Failed to create {:?}", extract_dir))?;
+
+    let status = std::process::Command::new("unzip")
+        .arg("-q")
+        .arg("-o")
+        .arg(path)
+        .arg("-d")
+        .arg(&extract_dir)
+        .status()
+        .context("Failed to run unzip")?;
+    if !status.success() {
+        bail!("Failed to extract {:?} (is it a .streamDeckProfile export?)", path);
+    }
+
+    let result = import_from_dir(&extract_dir);
+    let _ = std::fs::remove_dir_all(&extract_dir);
+    result
+}
+
+/// Import from an extracted profile directory
+fn import_from_dir(dir: &Path) -> Result<ImportResult> {
+    let mut manifests = Vec::new();
+    find_manifests(dir, &mut manifests);
+
+    // Multi-page profiles have a manifest per page; use the one with the most keys
+    let mut best: Option<(PathBuf, Value, usize)> = None;
+    for path in manifests {
+        let content = std::fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {:?}", path))?;
+        let manifest: Value = match serde_json::from_str(&content) {
+            Ok(v) => v,
+            Err(e) => {
+                debug!("Skipping unparseable manifest {:?}: {}", path, e);
+                continue;
+            }
+        };
+        let count = keypad_actions(&manifest).map(|a| a.len()).unwrap_or(0);
+        if best.as_ref().map(|(_, _, c)| count > *c).unwrap_or(true) {
+            best = Some((path, manifest, count));
+        }
+    }
+
+    let (path, manifest, _) = best.context("No Stream Deck manifest.json found")?;
+    let base_dir = path.parent().unwrap_or(dir);
+    Ok(convert_manifest(&manifest, base_dir))
+}
+
+/// Recursively collect manifest.json files
+fn find_manifests(dir: &Path, out: &mut Vec<PathBuf>) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            find_manifests(&path, out);
+        } else if path.file_name().and_then(|n| n.to_str()) == Some("manifest.json") {
+            out.push(path);
+        }
+    }
+}
+
+/// Keypad actions map: top-level `Actions` (v1) or the Keypad controller (v2)
+fn keypad_actions(manifest: &Value) -> Option<&serde_json::Map<String, Value>> {
+    if let Some(actions) = manifest.get("Actions").and_then(|a| a.as_object()) {
+        return Some(actions);
+    }
+    manifest
+        .get("Controllers")?
+        .as_array()?
+        .iter()
+        .find(|c| c.get("Type").and_then(|t| t.as_str()) == Some("Keypad"))?
+        .get("Actions")?
+        .as_object()
+}
+
+/// Convert a parsed manifest into a profile
+pub fn convert_manifest(manifest: &Value, base_dir: &Path) -> ImportResult {
+    let name = manifest
+        .get("Name")
+        .and_then(|n| n.as_str())
+        .filter(|n| !n.is_empty())
+        .unwrap_or("streamdeck")
+        .to_lowercase();
+
+    // Profiles bound to an app store its bundle path, e.g. "/Applications/Slack.app"
+    let match_apps = manifest
+        .get("AppIdentifier")
+        .and_then(|a| a.as_str())
+        .and_then(|a| Path::new(a).file_stem())
+        .map(|s| vec![s.to_string_lossy().into_owned()])
+        .unwrap_or_default();
+
+    let mut buttons = Vec::new();
+    let mut skipped = Vec::new();
+
+    let mut keys: Vec<(&String, &Value)> = keypad_actions(manifest)
+        .map(|a| a.iter().collect())
+        .unwrap_or_default();
+    keys.sort_by(|a, b| a.0.cmp(b.0));
+
+    for (coords, action) in keys {
+        let title = key_title(action);
+        let Some(position) = parse_position(coords) else {
+            skipped.push(format!("{} ({}): outside the 5x2 layout", coords, title));
+            continue;
+        };
+
+        let uuid = action.get("UUID").and_then(|u| u.as_str()).unwrap_or("");
+        let Some(action_config) = convert_action(uuid, action.get("Settings")) else {
+            skipped.push(format!("{} ({}): unsupported action {}", coords, title, uuid));
+            continue;
+        };
+
+        buttons.push(ButtonConfigEntry {
+            position,
+            label: title,
+            color: "#505560".to_string(),
+            bright_color: "#6E737D".to_string(),
+            action: action_config,
+            emoji_image: None,
+            custom_image: key_image(action, base_dir),
+            icon_svg: None,
+            gif_url: None,
+            pre_delay_ms: None,
+            inter_key_delay_ms: None,
+            repeat_while_held: false,
+            repeat_interval_ms: None,
+            confirm: false,
+            feedback: None,
+        });
+    }
+
+    ImportResult {
+        profile: ProfileConfig {
+            name,
+            match_apps,
+            match_bundle_ids: Vec::new(),
+            match_title_regex: None,
+            buttons,
+            encoders: Vec::new(),
+            chords: Vec::new(),
+            strip: Default::default(),
+            font: None,
+            brightness: None,
+            tint: None,
+        },
+        skipped,
+    }
+}
+
+/// Map "col,row" to a deck position
+fn parse_position(coords: &str) -> Option<u8> {
+    let (col, row) = coords.split_once(',')?;
+    let col: u64 = col.trim().parse().ok()?;
+    let row: u64 = row.trim().parse().ok()?;
+    if col >= COLUMNS || row >= ROWS {
+        return None;
+    }
+    Some((row * COLUMNS + col) as u8)
+}
+
+/// Title of the key's current state, falling back to the action name
+fn key_title(action: &Value) -> String {
+    let state_index = action.get("State").and_then(|s| s.as_u64()).unwrap_or(0) as usize;
+    action
+        .get("States")
+        .and_then(|s| s.get(state_index))
+        .and_then(|s| s.get("Title"))
+        .and_then(|t| t.as_str())
+        .filter(|t| !t.trim().is_empty())
+        .or_else(|| action.get("Name").and_then(|n| n.as_str()))
+        .unwrap_or("")
+        .replace('\n', " ")
+        .trim()
+        .to_string()
+}
+
+/// Load the key's custom image as a data URL
+fn key_image(action: &Value, base_dir: &Path) -> Option<String> {
+    let state_index = action.get("State").and_then(|s| s.as_u64()).unwrap_or(0) as usize;
+    let image = action
+        .get("States")?
+        .get(state_index)?
+        .get("Image")?
+        .as_str()?;
+    let data = std::fs::read(base_dir.join(image)).ok()?;
+    let mime = mime_guess::from_path(image).first_or_octet_stream();
+    Some(format!(
+        "data:{};base64,{}",
+        mime,
+        base64::engine::general_purpose::STANDARD.encode(data)
+    ))
+}
+
+/// Convert a built-in Stream Deck action to an ActionConfig
+fn convert_action(uuid: &str, settings: Option<&Value>) -> Option<ActionConfig> {
+    let settings = settings.cloned().unwrap_or(Value::Null);
+    match uuid {
+        "com.elgato.streamdeck.system.hotkey" => {
+            let hotkey = settings.get("Hotkeys")?.as_array()?.first()?;
+            Some(ActionConfig::Key {
+                value: hotkey_to_shortcut(hotkey)?,
+            })
+        }
+        "com.elgato.streamdeck.system.text" => Some(ActionConfig::Text {
+            value: settings.get("pastedText")?.as_str()?.to_string(),
+            auto_submit: settings
+                .get("isSendingEnter")
+                .and_then(|v| v.as_bool())
+                .unwrap_or(false),
+        }),
+        "com.elgato.streamdeck.system.website" => {
+            let url = settings.get("path")?.as_str()?.trim();
+            if url.is_empty() {
+                return None;
+            }
+            Some(ActionConfig::OpenUrl {
+                url: url.to_string(),
+            })
+        }
+        "com.elgato.streamdeck.system.open" => {
+            let target = settings.get("path")?.as_str()?.trim();
+            if target.is_empty() {
+                return None;
+            }
+            Some(ActionConfig::Macro {
+                steps: vec![MacroStep::Shell {
+                    command: format!("open {}", shell_quote(target)),
+                }],
+            })
+        }
+        _ => None,
+    }
+}
+
+/// Convert a Stream Deck hotkey (Qt key code + modifier flags) to "Cmd+Shift+K" form
+pub fn hotkey_to_shortcut(hotkey: &Value) -> Option<String> {
+    let flag = |name: &str| hotkey.get(name).and_then(|v| v.as_bool()).unwrap_or(false);
+    let code = hotkey.get("QTKeyCode").and_then(|v| v.as_u64())?;
+    let key = qt_key_name(code)?;
+
+    let mut parts = Vec::new();
+    if flag("KeyCmd") {
+        parts.push("Cmd".to_string());
+    }
+    if flag("KeyCtrl") {
+        parts.push("Ctrl".to_string());
+    }
+    if flag("KeyOption") {
+        parts.push("Alt".to_string());
+    }
+    if flag("KeyShift") {
+        parts.push("Shift".to_string());
+    }
+    parts.push(key);
+    Some(parts.join("+"))
+}
+
+/// Qt::Key code to our key names
+fn qt_key_name(code: u64) -> Option<String> {
+    let name = match code {
+        0x0100_0000 => "Escape",
+        0x0100_0001 => "Tab",
+        0x0100_0003 => "Backspace",
+        0x0100_0004 | 0x0100_0005 => "Enter",
+        0x0100_0007 => "Delete",
+        0x0100_0010 => "Home",
+        0x0100_0011 => "End",
+        0x0100_0012 => "Left",
+        0x0100_0013 => "Up",
+        0x0100_0014 => "Right",
+        0x0100_0015 => "Down",
+        0x0100_0016 => "PageUp",
+        0x0100_0017 => "PageDown",
+        0x20 => "Space",
+        0x0100_0030..=0x0100_003B => return Some(format!("F{}", code - 0x0100_0030 + 1)),
+        // Printable ASCII (Qt uses uppercase codes for letters)
+        0x21..=0x7E => {
+            let c = char::from_u32(code as u32)?.to_ascii_lowercase();
+            return Some(c.to_string());
+        }
+        _ => return None,
+    };
+    Some(name.to_string())
+}
+
+/// Single-quote a string for sh
+fn shell_quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', r"'\''"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_hotkey_to_shortcut() {
+        let hotkey = json!({"KeyCmd": true, "KeyShift": true, "QTKeyCode": 0x43});
+        assert_eq!(hotkey_to_shortcut(&hotkey), Some("Cmd+Shift+c".to_string()));
+
+        let hotkey = json!({"QTKeyCode": 0x0100_0004});
+        assert_eq!(hotkey_to_shortcut(&hotkey), Some("Enter".to_string()));
+
+        let hotkey = json!({"QTKeyCode": 0x0100_0031});
+        assert_eq!(hotkey_to_shortcut(&hotkey), Some("F2".to_string()));
+    }
+
+    #[test]
+    fn test_convert_manifest() {
+        let manifest = json!({
+            "Name": "Work",
+            "AppIdentifier": "/Applications/Slack.app",
+            "Actions": {
+                "0,0": {
+                    "UUID": "com.elgato.streamdeck.system.text",
+                    "Settings": {"pastedText": "hello", "isSendingEnter": true},
+                    "States": [{"Title": "Hi"}]
+                },
+                "1,1": {
+                    "UUID": "com.elgato.streamdeck.system.website",
+                    "Name": "Website",
+                    "Settings": {"path": "https://example.com"}
+                },
+                "2,0": {"UUID": "com.example.plugin.thing", "Name": "Plugin"},
+                "0,2": {"UUID": "com.elgato.streamdeck.system.text", "Settings": {"pastedText": "x"}}
+            }
+        });
+
+        let result = convert_manifest(&manifest, Path::new("/nonexistent"));
+        assert_eq!(result.profile.name, "work");
+        assert_eq!(result.profile.match_apps, vec!["Slack".to_string()]);
+        assert_eq!(result.profile.buttons.len(), 2);
+        assert_eq!(result.skipped.len(), 2);
+
+        let hi = &result.profile.buttons[0];
+        assert_eq!(hi.position, 0);
+        assert_eq!(hi.label, "Hi");
+        assert!(matches!(hi.action, ActionConfig::Text { auto_submit: true, .. }));
+
+        let site = &result.profile.buttons[1];
+        assert_eq!(site.position, 6);
+        assert_eq!(site.label, "Website");
+        assert!(matches!(
+            site.action,
+            ActionConfig::OpenUrl { ref url } if url == "https://example.com"
+        ));
+    }
+}
@@ -0,0 +1,89 @@
+//! Profiles picked by the Claude Code session's working directory (`[projects]`)
+//!
+//! Each entry maps a directory pattern to a profile name. A pattern matches
+//! that directory and everything below it; `*` matches within one path
+//! component and `**` any number of them, and `~/` is the home directory.
+//! The longest matching pattern wins. A project's profile replaces the
+//! wildcard profile (the terminal running Claude), not app-specific ones.
+
+use std::collections::BTreeMap;
+
+/// The profile for a working directory, if a pattern matches it
+pub fn profile_for<'a>(projects: &'a BTreeMap<String, String>, cwd: &str) -> Option<&'a str> {
+    let home = std::env::var("HOME").unwrap_or_default();
+    projects
+        .iter()
+        .filter(|(pattern, _)| {
+            let pattern = match pattern.strip_prefix("~/") {
+                Some(rest) if !home.is_empty() => format!("{}/{}", home, rest),
+                _ => pattern.to_string(),
+            };
+            path_matches(&pattern, cwd)
+        })
+        .max_by_key(|(pattern, _)| pattern.len())
+        .map(|(_, profile)| profile.as_str())
+}
+
+/// Whether `path` is the directory `pattern` names, or inside it
+fn path_matches(pattern: &str, path: &str) -> bool {
+    prefix_matches(&components(pattern), &components(path))
+}
+
+fn components(path: &str) -> Vec<&str> {
+    path.split('/').filter(|c| !c.is_empty()).collect()
+}
+
+fn prefix_matches(pattern: &[&str], path: &[&str]) -> bool {
+    match pattern.split_first() {
+        None => true,
+        Some((&"**", rest)) => (0..=path.len()).any(|skip| prefix_matches(rest, &path[skip..])),
+        Some((first, rest)) => match path.split_first() {
+            Some((component, path)) => {
+                component_matches(first, component) && prefix_matches(rest, path)
+            }
+            None => false,
+        },
+    }
+}
+
+/// Match one path component against a pattern where `*` is any run of characters
+fn component_matches(pattern: &str, component: &str) -> bool {
+    match pattern.split_once('*') {
+        None => pattern == component,
+        Some((prefix, rest)) => {
+            let Some(tail) = component.strip_prefix(prefix) else {
+                return false;
+            };
+            (0..=tail.len())
+                .filter(|&i| tail.is_char_boundary(i))
+                .any(|i| component_matches(rest, &tail[i..]))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_profile_for() {
+        let projects: BTreeMap<String, String> = [
+            ("/work/*", "work"),
+            ("/work/api", "api"),
+            ("/src/**/docs", "docs"),
+            ("/tmp/scratch-*", "scratch"),
+        ]
+        .into_iter()
+        .map(|(pattern, profile)| (pattern.to_string(), profile.to_string()))
+        .collect();
+
+        assert_eq!(profile_for(&projects, "/work/api"), Some("api"));
+        assert_eq!(profile_for(&projects, "/work/api/src/"), Some("api"));
+        assert_eq!(profile_for(&projects, "/work/web"), Some("work"));
+        assert_eq!(profile_for(&projects, "/work"), None);
+        assert_eq!(profile_for(&projects, "/src/a/b/docs/guide"), Some("docs"));
+        assert_eq!(profile_for(&projects, "/src/docs"), Some("docs"));
+        assert_eq!(profile_for(&projects, "/tmp/scratch-42"), Some("scratch"));
+        assert_eq!(profile_for(&projects, "/tmp/other"), None);
+    }
+}
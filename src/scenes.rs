@@ -0,0 +1,172 @@
+//! Scenes: named deck-wide states (profile, brightness, strip layout, Slack
+//! status, Hue lights) activated together by a button, schedule, or API call
+
+use anyhow::{bail, Result};
+use tracing::{info, warn};
+
+use crate::config::{Config, HueConfig, SceneConfig, SlackConfig};
+
+/// Find a scene by name (case-insensitive)
+pub fn find_scene<'a>(scenes: &'a [SceneConfig], name: &str) -> Option<&'a SceneConfig> {
+    scenes.iter().find(|s| s.name.eq_ignore_ascii_case(name))
+}
+
+/// Scenes, Slack and Hue settings as currently saved on disk
+///
+/// Scenes can be edited from the web UI while we run, so read them fresh
+/// rather than relying on the startup config snapshot.
+pub fn load_scene_config(fallback: &Config) -> (Vec<SceneConfig>, SlackConfig, HueConfig) {
+    match Config::load() {
+        Ok(config) => (config.scenes, config.slack, config.hue),
+        Err(e) => {
+            warn!("Failed to reload scenes from config, using startup config: {}", e);
+            (
+                fallback.scenes.clone(),
+                fallback.slack.clone(),
+                fallback.hue.clone(),
+            )
+        }
+    }
+}
+
+/// Parse a schedule entry like "09:30" into (hour, minute)
+pub fn parse_schedule_time(s: &str) -> Option<(u32, u32)> {
+    let (h, m) = s.trim().split_once(':')?;
+    let h: u32 = h.parse().ok()?;
+    let m: u32 = m.parse().ok()?;
+    if h < 24 && m < 60 {
+        Some((h, m))
+    } else {
+        None
+    }
+}
+
+/// Scenes scheduled to start at the given local time
+pub fn scenes_due(scenes: &[SceneConfig], hour: u32, minute: u32) -> Vec<&SceneConfig> {
+    scenes
+        .iter()
+        .filter(|s| {
+            s.schedule
+                .iter()
+                .any(|t| parse_schedule_time(t) == Some((hour, minute)))
+        })
+        .collect()
+}
+
+/// Current local time as (hour, minute)
+pub fn local_hour_minute() -> (u32, u32) {
+    // SAFETY: time(NULL) and localtime_r only write to the tm we pass in
+    unsafe {
+        let now = libc::time(std::ptr::null_mut());
+        let mut tm: libc::tm = std::mem::zeroed();
+        libc::localtime_r(&now, &mut tm);
+        (tm.tm_hour as u32, tm.tm_min as u32)
+    }
+}
+
+/// Apply the external parts of a scene (Slack status, Hue lights)
+pub async fn apply_integrations(scene: SceneConfig, slack: SlackConfig, hue: HueConfig) {
+    if let Some(ref status) = scene.slack_status {
+        match set_slack_status(&slack, &status.text, &status.emoji).await {
+            Ok(()) => info!("Scene '{}': Slack status set", scene.name),
+            Err(e) => warn!("Scene '{}': failed to set Slack status: {}", scene.name, e),
+        }
+    }
+
+    if let Some(ref hue_scene) = scene.hue_scene {
+        match recall_hue_scene(&hue, &hue_scene.group, &hue_scene.scene).await {
+            Ok(()) => info!("Scene '{}': Hue scene recalled", scene.name),
+            Err(e) => warn!("Scene '{}': failed to recall Hue scene: {}", scene.name, e),
+        }
+    }
+}
+
+/// Set the user's Slack status via users.profile.set
+async fn set_slack_status(slack: &SlackConfig, text: &str, emoji: &str) -> Result<()> {
+    if slack.token.is_empty() {
+        bail!("no [slack] token configured");
+    }
+
+    let body = serde_json::json!({
+        "profile": {
+            "status_text": text,
+            "status_emoji": emoji,
+            "status_expiration": 0,
+        }
+    });
+
+    let response: serde_json::Value = reqwest::Client::new()
+        .post("https://slack.com/api/users.profile.set")
+        .bearer_auth(&slack.token)
+        .json(&body)
+        .send()
+        .await?
+        .json()
+        .await?;
+
+    // Slack returns 200 with {"ok": false, "error": "..."} on failure
+    if response.get("ok").and_then(|v| v.as_bool()) != Some(true) {
+        let error = response
+            .get("error")
+            .and_then(|v| v.as_str())
+            .unwrap_or("unknown error");
+        bail!("Slack API error: {}", error);
+    }
+    Ok(())
+}
+
+/// Recall a scene on a group via the Hue bridge v1 API
+async fn recall_hue_scene(hue: &HueConfig, group: &str, scene: &str) -> Result<()> {
+    if hue.bridge.is_empty() || hue.username.is_empty() {
+        bail!("no [hue] bridge/username configured");
+    }
+
+    let url = format!(
+        "http://{}/api/{}/groups/{}/action",
+        hue.bridge, hue.username, group
+    );
+    let response = reqwest::Client::new()
+        .put(&url)
+        .json(&serde_json::json!({ "scene": scene }))
+        .send()
+        .await?;
+
+    if !response.status().is_success() {
+        bail!("Hue bridge returned {}", response.status());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_schedule_time() {
+        assert_eq!(parse_schedule_time("09:30"), Some((9, 30)));
+        assert_eq!(parse_schedule_time(" 18:05 "), Some((18, 5)));
+        assert_eq!(parse_schedule_time("24:00"), None);
+        assert_eq!(parse_schedule_time("noon"), None);
+    }
+
+    #[test]
+    fn test_scenes_due() {
+        let scenes = vec![
+            SceneConfig {
+                name: "Deep Work".to_string(),
+                schedule: vec!["09:00".to_string()],
+                ..Default::default()
+            },
+            SceneConfig {
+                name: "Meeting".to_string(),
+                ..Default::default()
+            },
+        ];
+
+        let due = scenes_due(&scenes, 9, 0);
+        assert_eq!(due.len(), 1);
+        assert_eq!(due[0].name, "Deep Work");
+        assert!(scenes_due(&scenes, 9, 1).is_empty());
+        assert!(find_scene(&scenes, "meeting").is_some());
+    }
+}
@@ -0,0 +1,137 @@
+//! Short audio cues for button presses and Claude events (macOS `afplay`)
+
+use crate::config::{SoundConfig, SoundCue};
+use crate::device::FeedbackKind;
+use std::time::Duration;
+
+/// Something that can play a cue
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SoundEvent {
+    /// A deck button was pressed
+    Press,
+    /// Claude started waiting for input or permission
+    Waiting,
+    /// Claude finished its turn
+    Complete,
+    /// Claude reported an error
+    Error,
+}
+
+impl SoundEvent {
+    /// The event a Claude status change should sound, if any
+    pub fn from_status(
+        old_task: &str,
+        new_task: &str,
+        was_waiting: bool,
+        waiting: bool,
+    ) -> Option<Self> {
+        if waiting && !was_waiting {
+            Some(SoundEvent::Waiting)
+        } else if new_task == old_task {
+            None
+        } else if new_task == "ERROR" {
+            Some(SoundEvent::Error)
+        } else if new_task == "READY" && !old_task.is_empty() {
+            Some(SoundEvent::Complete)
+        } else {
+            None
+        }
+    }
+}
+
+/// The file and volume (0.0-1.0) to play for an event, or None if muted
+pub fn cue_for(config: &SoundConfig, event: SoundEvent) -> Option<(&str, f32)> {
+    let cue: &SoundCue = match event {
+        SoundEvent::Press => &config.press,
+        SoundEvent::Waiting => &config.waiting,
+        SoundEvent::Complete => &config.complete,
+        SoundEvent::Error => &config.error,
+    };
+    if !config.enabled || !cue.enabled || cue.file.is_empty() {
+        return None;
+    }
+    let volume = cue.volume.unwrap_or(config.volume).clamp(0.0, 1.0);
+    Some((cue.file.as_str(), volume))
+}
+
+/// Play an event's cue in the background (no-op when muted)
+pub fn play(config: &SoundConfig, event: SoundEvent) {
+    if let Some((file, volume)) = cue_for(config, event) {
+        play_file(file, volume);
+    }
+}
+
+/// Time between the cues of a `double`
+const FEEDBACK_GAP: Duration = Duration::from_millis(120);
+
+/// Confirm a button's action with the press cue, once per pulse
+///
+/// Played even with cues turned off, since the button asked for it.
+pub fn feedback(config: &SoundConfig, kind: FeedbackKind) {
+    if config.press.file.is_empty() {
+        return;
+    }
+    let file = config.press.file.clone();
+    let volume = config.press.volume.unwrap_or(config.volume).clamp(0.0, 1.0);
+    tokio::spawn(async move {
+        for pulse in 0..kind.pulses() {
+            if pulse > 0 {
+                tokio::time::sleep(FEEDBACK_GAP).await;
+            }
+            play_file(&file, volume);
+        }
+    });
+}
+
+#[cfg(target_os = "macos")]
+fn play_file(file: &str, volume: f32) {
+    use tracing::debug;
+
+    // The child is reaped by tokio once it exits
+    if let Err(e) = tokio::process::Command::new("afplay")
+        .arg("-v")
+        .arg(volume.to_string())
+        .arg(file)
+        .spawn()
+    {
+        debug!("Failed to play {}: {}", file, e);
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+fn play_file(_file: &str, _volume: f32) {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_status_events() {
+        let event = |old, new, was_waiting, waiting| {
+            SoundEvent::from_status(old, new, was_waiting, waiting)
+        };
+        assert_eq!(event("Bash", "PERMISSION", false, true), Some(SoundEvent::Waiting));
+        assert_eq!(event("PERMISSION", "PERMISSION", true, true), None);
+        assert_eq!(event("Bash", "READY", false, false), Some(SoundEvent::Complete));
+        assert_eq!(event("", "READY", false, false), None);
+        assert_eq!(event("READY", "READY", false, false), None);
+        assert_eq!(event("Edit", "ERROR", false, false), Some(SoundEvent::Error));
+        assert_eq!(event("READY", "Bash", false, false), None);
+    }
+
+    #[test]
+    fn test_cue_respects_enable_flags_and_volume() {
+        let mut config = SoundConfig::default();
+        assert!(cue_for(&config, SoundEvent::Complete).is_none());
+
+        config.enabled = true;
+        let (file, volume) = cue_for(&config, SoundEvent::Complete).unwrap();
+        assert_eq!(file, config.complete.file);
+        assert_eq!(volume, config.volume);
+        // Press clicks are off unless asked for
+        assert!(cue_for(&config, SoundEvent::Press).is_none());
+
+        config.error.volume = Some(3.0);
+        assert_eq!(cue_for(&config, SoundEvent::Error).unwrap().1, 1.0);
+    }
+}
@@ -0,0 +1,263 @@
+//! Sub-agents Claude Code is running, per session
+//!
+//! A `Task` tool call starts a sub-agent and its `PostToolUse` ends it, so
+//! agents hang off the session that started them (sub-agents can't start
+//! their own). Tool calls made while a session has agents running are theirs;
+//! hooks don't say which agent made a call, so with several running at once
+//! it goes to the most recently started one. A permission prompt in the
+//! meantime marks that agent waiting until its next tool call.
+
+use std::time::{Duration, Instant};
+
+use crate::hooks::ClaudeStatus;
+
+/// Agents not heard from this long are dropped (their end was missed)
+const AGENT_EXPIRY: Duration = Duration::from_secs(30 * 60);
+/// How long the widget shows the agent scrolled to
+const VIEW_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// A running sub-agent
+#[derive(Debug, Clone, PartialEq)]
+pub struct Agent {
+    pub session_id: Option<String>,
+    /// Sub-agent type (e.g. "Explore", "general-purpose")
+    pub kind: String,
+    /// The task's short description
+    pub description: String,
+    /// Its latest tool call, e.g. "Read: src/lib.rs"
+    pub tool: Option<String>,
+    /// Waiting on a permission prompt
+    pub waiting: bool,
+    /// Hook timestamp of the call that started it
+    started_at: u64,
+    last_update: Instant,
+}
+
+/// The agent shown in the widget after scrolling
+#[derive(Debug, Clone)]
+struct AgentView {
+    index: usize,
+    until: Instant,
+}
+
+/// Running sub-agents across sessions
+#[derive(Debug, Clone, Default)]
+pub struct Agents {
+    /// Grouped by session, each in the order started
+    agents: Vec<Agent>,
+    view: Option<AgentView>,
+}
+
+/// Whether a tool name starts sub-agents
+fn is_agent_tool(tool: &str) -> bool {
+    tool == "Task" || tool == "Agent"
+}
+
+impl Agents {
+    /// Track a hook update, returning true if the agents changed
+    ///
+    /// Needs the hook event name, which older hook scripts don't send.
+    pub fn record(&mut self, status: &ClaudeStatus) -> bool {
+        let Some(event) = status.event.as_deref() else {
+            return false;
+        };
+        let before = self.agents.clone();
+        self.agents
+            .retain(|agent| agent.last_update.elapsed() < AGENT_EXPIRY);
+        let session = &status.session_id;
+
+        if status.task == "READY" {
+            self.agents.retain(|agent| agent.session_id != *session);
+        } else if is_agent_tool(&status.task) {
+            let input = status.tool_input.as_ref();
+            let field = |name: &str| {
+                let value = input
+                    .and_then(|input| input.get(name))
+                    .and_then(|v| v.as_str());
+                value.unwrap_or_default().to_string()
+            };
+            let description = field("description");
+            let same =
+                |agent: &Agent| agent.session_id == *session && agent.description == description;
+            match event {
+                "PreToolUse" => {
+                    // The same update can arrive twice (socket and status file)
+                    let seen = self
+                        .agents
+                        .iter()
+                        .any(|a| same(a) && a.started_at == status.timestamp);
+                    if !seen {
+                        let kind = Some(field("subagent_type")).filter(|k| !k.is_empty());
+                        self.insert(Agent {
+                            session_id: session.clone(),
+                            kind: kind.unwrap_or_else(|| "agent".to_string()),
+                            description,
+                            tool: None,
+                            waiting: false,
+                            started_at: status.timestamp,
+                            last_update: Instant::now(),
+                        });
+                    }
+                }
+                "PostToolUse" => {
+                    if let Some(index) = self.agents.iter().position(same) {
+                        self.agents.remove(index);
+                    }
+                }
+                _ => {}
+            }
+        } else if let Some(agent) = self.latest_mut(session) {
+            if status.waiting_for_input && status.input_type.as_deref() == Some("permission") {
+                agent.waiting = true;
+            } else if event == "PreToolUse" {
+                agent.tool = status.detail().or_else(|| Some(status.task.clone()));
+                agent.waiting = false;
+                agent.last_update = Instant::now();
+            }
+        }
+
+        if let Some(ref mut view) = self.view {
+            view.index = view.index.min(self.agents.len().saturating_sub(1));
+        }
+        self.agents != before
+    }
+
+    /// Add an agent after the others of its session
+    fn insert(&mut self, agent: Agent) {
+        let index = self
+            .agents
+            .iter()
+            .rposition(|a| a.session_id == agent.session_id)
+            .map_or(self.agents.len(), |i| i + 1);
+        self.agents.insert(index, agent);
+    }
+
+    /// The session's most recently started agent
+    fn latest_mut(&mut self, session: &Option<String>) -> Option<&mut Agent> {
+        self.agents
+            .iter_mut()
+            .rev()
+            .find(|a| a.session_id == *session)
+    }
+
+    pub fn list(&self) -> &[Agent] {
+        &self.agents
+    }
+
+    /// Agents waiting on a permission prompt
+    pub fn waiting(&self) -> usize {
+        self.agents.iter().filter(|a| a.waiting).count()
+    }
+
+    /// Agents still working
+    pub fn running(&self) -> usize {
+        self.agents.len() - self.waiting()
+    }
+
+    /// Show the next (or previous) agent in the widget
+    pub fn scroll(&mut self, direction: i8) {
+        if self.agents.is_empty() {
+            return;
+        }
+        let count = self.agents.len() as isize;
+        let index = match self.view() {
+            Some((index, _)) => (index as isize + direction as isize).rem_euclid(count) as usize,
+            None if direction < 0 => self.agents.len() - 1,
+            None => 0,
+        };
+        self.view = Some(AgentView {
+            index,
+            until: Instant::now() + VIEW_TIMEOUT,
+        });
+    }
+
+    /// The agent scrolled to and its index, for a few seconds after scrolling
+    pub fn view(&self) -> Option<(usize, &Agent)> {
+        let view = self.view.as_ref().filter(|v| Instant::now() < v.until)?;
+        self.agents.get(view.index).map(|agent| (view.index, agent))
+    }
+
+    /// "2 running, 1 waiting", or None with no agents
+    pub fn summary(&self) -> Option<String> {
+        if self.agents.is_empty() {
+            return None;
+        }
+        Some(match self.waiting() {
+            0 => format!("{} running", self.running()),
+            waiting => format!("{} running, {} waiting", self.running(), waiting),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn update(event: &str, task: &str, input: serde_json::Value, timestamp: u64) -> ClaudeStatus {
+        ClaudeStatus {
+            event: Some(event.to_string()),
+            session_id: Some("s1".to_string()),
+            task: task.to_string(),
+            tool_input: Some(input),
+            timestamp,
+            ..Default::default()
+        }
+    }
+
+    fn task(event: &str, description: &str, timestamp: u64) -> ClaudeStatus {
+        let input = serde_json::json!({
+            "description": description, "prompt": "...", "subagent_type": "Explore"
+        });
+        update(event, "Task", input, timestamp)
+    }
+
+    #[test]
+    fn test_agents() {
+        let mut agents = Agents::default();
+        assert!(agents.record(&task("PreToolUse", "Find the parser", 1)));
+        assert!(!agents.record(&task("PreToolUse", "Find the parser", 1)));
+        assert!(agents.record(&task("PreToolUse", "Check the tests", 2)));
+        assert_eq!(agents.summary().as_deref(), Some("2 running"));
+
+        // Tool calls and prompts go to the latest agent
+        let read = update(
+            "PreToolUse",
+            "Grep",
+            serde_json::json!({"pattern": "fn parse"}),
+            3,
+        );
+        agents.record(&read);
+        let asking = ClaudeStatus {
+            waiting_for_input: true,
+            input_type: Some("permission".to_string()),
+            ..update("Notification", "PERMISSION", serde_json::Value::Null, 4)
+        };
+        agents.record(&asking);
+        let latest = &agents.list()[1];
+        assert_eq!(
+            (latest.kind.as_str(), latest.tool.as_deref(), latest.waiting),
+            ("Explore", Some("Grep: fn parse"), true)
+        );
+        assert_eq!(agents.summary().as_deref(), Some("1 running, 1 waiting"));
+
+        agents.scroll(-1);
+        assert_eq!(
+            agents.view().map(|(i, a)| (i, a.description.as_str())),
+            Some((1, "Check the tests"))
+        );
+        agents.scroll(1);
+        assert_eq!(agents.view().unwrap().0, 0);
+
+        assert!(agents.record(&task("PostToolUse", "Find the parser", 5)));
+        assert_eq!(agents.list().len(), 1);
+        agents.record(&update("Stop", "READY", serde_json::Value::Null, 6));
+        assert_eq!(agents.summary(), None);
+
+        // Older hooks don't name the event
+        let old = ClaudeStatus {
+            event: None,
+            ..task("PreToolUse", "Find the parser", 7)
+        };
+        assert!(!agents.record(&old));
+    }
+}
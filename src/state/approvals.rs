@@ -0,0 +1,269 @@
+//! Permission requests waiting on the deck, oldest first
+//!
+//! Claude Code asks for permission in a `Notification` hook that doesn't name
+//! the tool, so each request takes its tool and target from the session's
+//! last tool call. ACCEPT and REJECT answer the head of the queue in that
+//! session's terminal. A request also leaves the queue once its session
+//! moves on, having been answered there. Requests and answers are appended
+//! to `~/.claude-deck/approvals.log`, one JSON object per line.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use tracing::warn;
+
+use crate::hooks::ClaudeStatus;
+
+/// Log entries returned by `GET /api/approvals`
+pub const HISTORY_LEN: usize = 50;
+
+/// A permission request waiting for an answer
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Approval {
+    pub session_id: Option<String>,
+    /// Tool asking for permission ("Bash", "Edit", ...)
+    pub tool: String,
+    /// File, command or URL it wants to use
+    pub target: Option<String>,
+    pub cwd: Option<String>,
+    /// Terminal of the session, focused before answering
+    pub tty: Option<String>,
+    /// When it was asked (Unix epoch seconds)
+    pub requested_at: u64,
+}
+
+/// What happened to a request
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Decision {
+    Requested,
+    /// ACCEPT on the deck
+    Approved,
+    /// REJECT on the deck
+    Rejected,
+    /// The session moved on without the deck (answered in the terminal)
+    Answered,
+}
+
+/// One line of the audit log
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LogEntry {
+    /// Unix epoch seconds
+    pub time: u64,
+    pub decision: Decision,
+    pub tool: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cwd: Option<String>,
+}
+
+impl Approval {
+    fn log_entry(&self, decision: Decision, time: u64) -> LogEntry {
+        LogEntry {
+            time,
+            decision,
+            tool: self.tool.clone(),
+            target: self.target.clone(),
+            session_id: self.session_id.clone(),
+            cwd: self.cwd.clone(),
+        }
+    }
+}
+
+/// What the queue knows about one session
+#[derive(Debug, Clone, Default)]
+struct SessionTrack {
+    /// Tool and target of its latest tool call
+    last_tool: Option<(String, Option<String>)>,
+    /// Timestamp of the permission prompt last queued, so the same update
+    /// arriving again (socket and status file) isn't queued twice
+    asked_at: Option<u64>,
+}
+
+/// Pending permission requests across sessions
+#[derive(Debug, Clone, Default)]
+pub struct Approvals {
+    queue: VecDeque<Approval>,
+    /// Keyed by session id (None for hooks that don't send one)
+    sessions: HashMap<Option<String>, SessionTrack>,
+}
+
+impl Approvals {
+    /// Track a hook update, returning what to log if the queue changed
+    pub fn record(&mut self, status: &ClaudeStatus) -> Option<LogEntry> {
+        let key = status.session_id.clone();
+        let track = self.sessions.entry(key.clone()).or_default();
+        let queued = self.queue.iter().position(|a| a.session_id == key);
+
+        let asking = status.waiting_for_input && status.input_type.as_deref() == Some("permission");
+        if !asking {
+            if status.task == "READY" {
+                self.sessions.remove(&key);
+            } else if status.tool_input.is_some() && !status.task.is_empty() {
+                let target = status.detail().map(|detail| {
+                    let prefix = format!("{}: ", status.task);
+                    detail.strip_prefix(&prefix).unwrap_or(&detail).to_string()
+                });
+                track.last_tool = Some((status.task.clone(), target));
+            }
+            let answered = self.queue.remove(queued?)?;
+            return Some(answered.log_entry(Decision::Answered, now()));
+        }
+
+        if track.asked_at == Some(status.timestamp) {
+            return None;
+        }
+        track.asked_at = Some(status.timestamp);
+        let (tool, target) = track
+            .last_tool
+            .clone()
+            .unwrap_or_else(|| ("tool".to_string(), None));
+        let approval = Approval {
+            session_id: key,
+            tool,
+            target,
+            cwd: status.cwd.clone(),
+            tty: status.tty.clone(),
+            requested_at: now(),
+        };
+        let entry = approval.log_entry(Decision::Requested, approval.requested_at);
+        // A session only shows one prompt at a time
+        match queued {
+            Some(index) => self.queue[index] = approval,
+            None => self.queue.push_back(approval),
+        }
+        Some(entry)
+    }
+
+    /// Take the oldest request to answer it from the deck
+    pub fn answer(&mut self, decision: Decision) -> Option<(Approval, LogEntry)> {
+        let approval = self.queue.pop_front()?;
+        let entry = approval.log_entry(decision, now());
+        Some((approval, entry))
+    }
+
+    /// The request ACCEPT and REJECT answer next
+    pub fn head(&self) -> Option<&Approval> {
+        self.queue.front()
+    }
+
+    pub fn len(&self) -> usize {
+        self.queue.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.queue.is_empty()
+    }
+
+    pub fn pending(&self) -> impl Iterator<Item = &Approval> {
+        self.queue.iter()
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Audit log location in the state directory
+pub fn log_path(dir: &Path) -> PathBuf {
+    dir.join("approvals.log")
+}
+
+/// Append an entry to the audit log in `dir`
+pub fn append_log(dir: &Path, entry: &LogEntry) {
+    let path = log_path(dir);
+    let result = serde_json::to_string(entry)
+        .map_err(std::io::Error::from)
+        .and_then(|line| {
+            let mut file = std::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&path)?;
+            writeln!(file, "{}", line)
+        });
+    if let Err(e) = result {
+        warn!("Failed to write {:?}: {}", path, e);
+    }
+}
+
+/// The latest `limit` log entries in `dir`, oldest first
+pub fn history(dir: &Path, limit: usize) -> Vec<LogEntry> {
+    let contents = std::fs::read_to_string(log_path(dir)).unwrap_or_default();
+    let mut entries: Vec<LogEntry> = contents
+        .lines()
+        .rev()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .take(limit)
+        .collect();
+    entries.reverse();
+    entries
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn status(session: &str, task: &str, timestamp: u64) -> ClaudeStatus {
+        ClaudeStatus {
+            session_id: Some(session.to_string()),
+            task: task.to_string(),
+            timestamp,
+            ..Default::default()
+        }
+    }
+
+    fn asking(session: &str, timestamp: u64) -> ClaudeStatus {
+        ClaudeStatus {
+            waiting_for_input: true,
+            input_type: Some("permission".to_string()),
+            tty: Some("/dev/ttys001".to_string()),
+            ..status(session, "PERMISSION", timestamp)
+        }
+    }
+
+    #[test]
+    fn test_queue() {
+        let mut approvals = Approvals::default();
+        let edit = ClaudeStatus {
+            tool_input: Some(serde_json::json!({
+                "file_path": "/src/api/lib.rs", "old_string": "a", "new_string": "b"
+            })),
+            cwd: Some("/src/api".to_string()),
+            ..status("a", "Edit", 1)
+        };
+        assert_eq!(approvals.record(&edit), None);
+        let requested = approvals.record(&asking("a", 2)).unwrap();
+        assert_eq!(requested.decision, Decision::Requested);
+        assert_eq!(
+            (requested.tool.as_str(), requested.target.as_deref()),
+            ("Edit", Some("lib.rs (+1/-1)"))
+        );
+        // The same update again from the status file
+        assert_eq!(approvals.record(&asking("a", 2)), None);
+
+        approvals.record(&asking("b", 3)).unwrap();
+        assert_eq!(approvals.len(), 2);
+        assert_eq!(approvals.head().unwrap().session_id.as_deref(), Some("a"));
+
+        // b is answered in its terminal; a from the deck
+        let answered = approvals.record(&status("b", "Bash", 4)).unwrap();
+        assert_eq!(answered.decision, Decision::Answered);
+        let (approval, entry) = approvals.answer(Decision::Approved).unwrap();
+        assert_eq!(approval.tty.as_deref(), Some("/dev/ttys001"));
+        assert_eq!(entry.decision, Decision::Approved);
+        assert!(approvals.is_empty());
+        assert_eq!(approvals.record(&status("a", "Edit", 5)), None);
+
+        let line = serde_json::to_string(&entry).unwrap();
+        assert!(line.contains(r#""decision":"approved""#));
+        assert_eq!(serde_json::from_str::<LogEntry>(&line).unwrap(), entry);
+    }
+}
@@ -0,0 +1,146 @@
+//! Error and rate-limit banner shown across the whole strip
+//!
+//! A hook update reporting an error or a rate limit raises the banner, and
+//! the next ordinary update from the session clears it. When the retry delay
+//! is known (`retry_after_secs`, or "in 5 minutes" / "retry after 30" in the
+//! message) the banner counts it down.
+
+use std::time::{Duration, Instant};
+
+use crate::hooks::ClaudeStatus;
+
+/// What the banner reports
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BannerKind {
+    Error,
+    RateLimited,
+}
+
+/// An error or rate limit the strip is showing
+#[derive(Debug, Clone, PartialEq)]
+pub struct Banner {
+    pub kind: BannerKind,
+    pub message: String,
+    /// When Claude Code can retry, if the hook said
+    pub retry_at: Option<Instant>,
+    /// Hook timestamp of the update that raised it
+    timestamp: u64,
+}
+
+impl Banner {
+    /// The banner a hook update raises, if it reports an error or rate limit
+    pub fn from_status(status: &ClaudeStatus) -> Option<Self> {
+        let limited = status.task == "RATE LIMITED" || status.retry_after_secs.is_some();
+        if !limited && status.task != "ERROR" && status.error.is_none() {
+            return None;
+        }
+        let message = status.error.clone().or_else(|| status.detail());
+        let text = message.as_deref().unwrap_or_default();
+        let retry_after = status.retry_after_secs.or_else(|| parse_retry_after(text));
+        let lower = text.to_lowercase();
+        let kind = if limited || lower.contains("rate limit") || lower.contains("usage limit") {
+            BannerKind::RateLimited
+        } else {
+            BannerKind::Error
+        };
+        let message = message.unwrap_or_else(|| match kind {
+            BannerKind::Error => "Claude Code reported an error".to_string(),
+            BannerKind::RateLimited => "Claude Code is rate limited".to_string(),
+        });
+        Some(Self {
+            kind,
+            message,
+            retry_at: retry_after.map(|secs| Instant::now() + Duration::from_secs(secs)),
+            timestamp: status.timestamp,
+        })
+    }
+
+    /// Whether it was raised by the same update (re-read from the status
+    /// file), which mustn't restart the countdown
+    pub fn same_update(&self, other: &Banner) -> bool {
+        self.timestamp == other.timestamp && self.message == other.message
+    }
+
+    /// Whole seconds left before the retry (0 once it's due)
+    pub fn retry_in(&self, now: Instant) -> Option<u64> {
+        let left = self.retry_at?.saturating_duration_since(now);
+        Some(left.as_millis().div_ceil(1000) as u64)
+    }
+
+    pub fn title(&self) -> &'static str {
+        match self.kind {
+            BannerKind::Error => "ERROR",
+            BannerKind::RateLimited => "RATE LIMITED",
+        }
+    }
+}
+
+/// Seconds from "retry after 30", "in 45 seconds", "in 5 minutes" or "in 2 hours"
+fn parse_retry_after(text: &str) -> Option<u64> {
+    let lower = text.to_lowercase();
+    let mut words: Vec<&str> = lower
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .collect();
+    // So a number at the very end still has a (missing) unit after it
+    words.push("");
+    words.windows(3).find_map(|w| {
+        if w[0] != "after" && w[0] != "in" {
+            return None;
+        }
+        let number = w[1].parse::<u64>().ok()?;
+        let scale = match w[2] {
+            "s" | "sec" | "secs" | "second" | "seconds" => 1,
+            "m" | "min" | "mins" | "minute" | "minutes" => 60,
+            "h" | "hr" | "hrs" | "hour" | "hours" => 3600,
+            // A bare "retry after 30" is seconds, as in the HTTP header
+            _ if w[0] == "after" => 1,
+            _ => return None,
+        };
+        Some(number * scale)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_banner() {
+        let limited = ClaudeStatus {
+            task: "RATE LIMITED".to_string(),
+            error: Some("Rate limit reached, try again in 5 minutes".to_string()),
+            ..Default::default()
+        };
+        let banner = Banner::from_status(&limited).unwrap();
+        assert_eq!(banner.kind, BannerKind::RateLimited);
+        assert_eq!(banner.retry_in(Instant::now()), Some(300));
+
+        let error = ClaudeStatus {
+            task: "ERROR".to_string(),
+            ..Default::default()
+        };
+        let banner = Banner::from_status(&error).unwrap();
+        assert_eq!(
+            (banner.kind, banner.retry_in(Instant::now())),
+            (BannerKind::Error, None)
+        );
+
+        let explicit = ClaudeStatus {
+            task: "Bash".to_string(),
+            retry_after_secs: Some(30),
+            ..Default::default()
+        };
+        let banner = Banner::from_status(&explicit).unwrap();
+        assert_eq!(banner.kind, BannerKind::RateLimited);
+        assert_eq!(banner.retry_in(Instant::now() + Duration::from_secs(60)), Some(0));
+
+        assert_eq!(parse_retry_after("429: retry after 12"), Some(12));
+        assert_eq!(parse_retry_after("in a minute"), None);
+        let read = ClaudeStatus {
+            task: "Read".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(Banner::from_status(&read), None);
+    }
+}
@@ -0,0 +1,109 @@
+//! Deck lock: `LOCK_DECK` makes the buttons inert until a PIN is keyed in
+//!
+//! Independent of the screen lock. While locked, the ten buttons are a
+//! keypad numbered 1-9 then 0 in reading order, and `[security] pin` is the
+//! digit sequence that unlocks.
+
+/// Result of a keypad press while locked
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PinOutcome {
+    /// Digit added, PIN not complete yet
+    Entered,
+    /// Digits didn't match; entry starts over
+    Wrong,
+    Unlocked,
+}
+
+/// Keypad digit of a button while the deck is locked
+pub fn keypad_digit(button: u8) -> Option<char> {
+    match button {
+        0..=8 => char::from_digit(button as u32 + 1, 10),
+        9 => Some('0'),
+        _ => None,
+    }
+}
+
+/// Whether `pin` can unlock the deck (digits only, at least one)
+pub fn valid_pin(pin: &str) -> bool {
+    !pin.is_empty() && pin.chars().all(|c| c.is_ascii_digit())
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct DeckLock {
+    pin: String,
+    locked: bool,
+    /// Digits keyed in so far
+    entered: String,
+}
+
+impl DeckLock {
+    pub fn new(pin: &str) -> Self {
+        Self {
+            pin: pin.trim().to_string(),
+            ..Self::default()
+        }
+    }
+
+    /// Lock the deck; refused (false) when no valid PIN is configured
+    pub fn lock(&mut self) -> bool {
+        if !valid_pin(&self.pin) {
+            return false;
+        }
+        self.locked = true;
+        self.entered.clear();
+        true
+    }
+
+    pub fn is_locked(&self) -> bool {
+        self.locked
+    }
+
+    /// Number of digits keyed in so far
+    pub fn entered_len(&self) -> usize {
+        self.entered.len()
+    }
+
+    /// Key in the digit on `button` (None if unlocked or not a keypad button)
+    pub fn press(&mut self, button: u8) -> Option<PinOutcome> {
+        if !self.locked {
+            return None;
+        }
+        self.entered.push(keypad_digit(button)?);
+        if self.entered == self.pin {
+            self.locked = false;
+            self.entered.clear();
+            Some(PinOutcome::Unlocked)
+        } else if self.entered.len() >= self.pin.len() {
+            self.entered.clear();
+            Some(PinOutcome::Wrong)
+        } else {
+            Some(PinOutcome::Entered)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pin_unlocks() {
+        let mut lock = DeckLock::new("");
+        assert!(!lock.lock(), "no PIN configured");
+
+        let mut lock = DeckLock::new("190");
+        assert!(lock.lock());
+        // 1, 9, 0 are buttons 0, 8, 9
+        assert_eq!(lock.press(0), Some(PinOutcome::Entered));
+        assert_eq!(lock.press(1), Some(PinOutcome::Entered));
+        assert_eq!(lock.press(9), Some(PinOutcome::Wrong));
+        assert_eq!(lock.entered_len(), 0);
+        assert!(lock.is_locked());
+
+        assert_eq!(lock.press(0), Some(PinOutcome::Entered));
+        assert_eq!(lock.press(8), Some(PinOutcome::Entered));
+        assert_eq!(lock.press(9), Some(PinOutcome::Unlocked));
+        assert!(!lock.is_locked());
+        assert_eq!(lock.press(0), None);
+    }
+}
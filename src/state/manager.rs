@@ -1,9 +1,61 @@
+use image::Rgb;
 use serde::{Deserialize, Serialize};
-use std::time::Instant;
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use super::agents::Agents;
+use super::banner::Banner;
+use super::approvals::Approvals;
+use super::lock::DeckLock;
+use super::sessions::Sessions;
+use super::stats::ButtonStats;
+use crate::config::{FocusConfig, PermissionsConfig, StripConfig, StripWidget};
+use crate::device::FeedbackKind;
+use crate::display::{OverlayKind, StripOverlay};
+use crate::integrations::git::GitStatus;
+use crate::integrations::home_assistant::SensorState;
+use crate::monitors::MonitorStatus;
+use crate::plugins::PluginHost;
+use crate::profiles::{ButtonConfig, FocusedWindow};
+use crate::system::{ClipboardHistory, NowPlaying, SystemStats};
+use crate::timer::Timer;
+use super::text_entry::TextEntry;
 
 /// Default models for the model selector (used if config not provided)
 pub const DEFAULT_MODELS: &[&str] = &["opus", "sonnet", "haiku"];
 
+/// How long a `confirm` button waits for its second press
+pub const CONFIRM_WINDOW: Duration = Duration::from_secs(2);
+
+/// How long a button shows its pressed color after its action runs
+pub const BUTTON_FLASH: Duration = Duration::from_millis(150);
+
+/// Label and/or colors replacing a button's profile config until cleared
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ButtonOverride {
+    pub label: Option<String>,
+    /// Normal and bright colors
+    pub colors: Option<(Rgb<u8>, Rgb<u8>)>,
+}
+
+/// What a plugin's strip widget shows
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PluginWidget {
+    /// Replaces the widget name as its label
+    pub label: Option<String>,
+    pub text: String,
+    pub color: Option<Rgb<u8>>,
+}
+
+/// An open button folder: its buttons replace the profile layout
+#[derive(Debug, Clone)]
+pub struct Folder {
+    /// Label of the button that opened it
+    pub label: String,
+    pub buttons: HashMap<u8, ButtonConfig>,
+}
+
 /// Type of input the system is waiting for
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum InputType {
@@ -15,6 +67,67 @@ pub enum InputType {
     Permission,
 }
 
+/// Scene change requested by a button or the web API (consumed by the main loop)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SceneRequest {
+    /// Activate a scene by name
+    Activate(String),
+    /// Activate a scene, or deactivate it if it's already active
+    Toggle(String),
+    /// Return to normal (follow focused app, config brightness, default strip)
+    Deactivate,
+}
+
+/// Main deck connection, as the reconnect task sees it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeviceHealth {
+    Connected,
+    /// Disconnected; `attempt` reconnects have failed so far
+    Reconnecting { attempt: u32 },
+    /// Still disconnected after many attempts; retrying at the slowest rate
+    Failed { attempts: u32 },
+}
+
+impl Default for DeviceHealth {
+    fn default() -> Self {
+        DeviceHealth::Reconnecting { attempt: 0 }
+    }
+}
+
+impl DeviceHealth {
+    /// First retry delay, doubled after each failed attempt
+    const FIRST_RETRY: Duration = Duration::from_secs(1);
+    const MAX_RETRY: Duration = Duration::from_secs(60);
+    /// Failed attempts before reporting Failed
+    const FAILED_AFTER: u32 = 8;
+
+    /// Health after `attempts` failed reconnects
+    pub fn after_attempts(attempts: u32) -> Self {
+        if attempts >= Self::FAILED_AFTER {
+            DeviceHealth::Failed { attempts }
+        } else {
+            DeviceHealth::Reconnecting { attempt: attempts }
+        }
+    }
+
+    /// How long to wait before the next reconnect attempt
+    pub fn retry_delay(self) -> Duration {
+        let attempts = match self {
+            DeviceHealth::Connected => 0,
+            DeviceHealth::Reconnecting { attempt } => attempt,
+            DeviceHealth::Failed { attempts } => attempts,
+        };
+        Self::FIRST_RETRY
+            .saturating_mul(1 << attempts.min(16))
+            .min(Self::MAX_RETRY)
+    }
+}
+
+/// Key identifying a toggle button's state ("profile:position")
+pub fn toggle_key(profile: &str, button: u8) -> String {
+    format!("{}:{}", profile, button)
+}
+
 /// Application state shared across components
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AppState {
@@ -33,33 +146,181 @@ pub struct AppState {
     pub waiting_for_input: bool,
     /// Type of input being waited for
     pub input_type: Option<InputType>,
+    /// Tokens used this session (from hooks)
+    pub tokens: Option<u64>,
+    /// Session cost in USD (from hooks)
+    pub cost_usd: Option<f64>,
+    /// Context window usage percentage (from hooks)
+    pub context_pct: Option<u8>,
 
     // App state
     /// YOLO mode enabled (--dangerously-skip-permissions)
     pub yolo_mode: bool,
     /// Device is connected
     pub connected: bool,
+    /// Connection detail behind `connected`, set through `set_device_health`
+    #[serde(skip)]
+    pub device_health: DeviceHealth,
+    /// Running with `--headless`: no hardware, only the web UI's virtual deck
+    pub headless: bool,
+    /// Where runtime files go instead of `~/.claude-deck` (tests)
+    #[serde(skip)]
+    pub state_dir: Option<PathBuf>,
     /// Dictation/voice input is active
     pub dictation_active: bool,
-    /// Button that was just activated (for visual feedback), with timestamp
+    /// Buttons whose action just ran (for visual feedback), with when
+    #[serde(skip)]
+    pub button_flashes: HashMap<u8, Instant>,
+    /// `confirm` button waiting for its second press, and when it was first pressed
     #[serde(skip)]
-    pub button_flash: Option<(u8, Instant)>,
+    pub confirm: Option<(u8, Instant)>,
     /// Currently focused application name (e.g., "Slack", "Terminal", "Code")
     pub focused_app: String,
+    /// Bundle ID of the focused application (empty if unknown)
+    #[serde(skip)]
+    pub focused_bundle_id: String,
+    /// Title of the focused application's front window (empty if unknown)
+    #[serde(skip)]
+    pub focused_title: String,
     /// Flag to trigger intro animation replay
     #[serde(skip)]
     pub play_intro: bool,
     /// Screen is locked - input disabled for security
     #[serde(skip)]
     pub screen_locked: bool,
+    /// Active macOS Focus mode (e.g. "Do Not Disturb"), None when off
+    #[serde(skip)]
+    pub focus_mode: Option<String>,
+    /// Focus mode behaviour (from config)
+    #[serde(skip)]
+    pub focus: FocusConfig,
     /// Flash toggle for waiting-for-input animation (alternates on/off)
     #[serde(skip)]
     pub waiting_flash_on: bool,
+    /// Strength of the `[attention]` glow drawn over the deck (0 = none)
+    #[serde(skip)]
+    pub attention_glow: u8,
+    /// Animation step for strip text too long for its quadrant (see `display::strip`)
+    #[serde(skip)]
+    pub marquee_tick: u32,
+    /// Flag set when hooks report a new task starting (consumed by wake-on-hook)
+    #[serde(skip)]
+    pub task_started: bool,
+    /// When the current turn started (for long-task notifications)
+    #[serde(skip)]
+    pub turn_started_at: Option<Instant>,
+    /// Currently active scene name
+    pub active_scene: Option<String>,
+    /// Pending scene change to apply
+    #[serde(skip)]
+    pub pending_scene: Option<SceneRequest>,
+    /// Feedback a pressed button asked for, given by the main loop
+    #[serde(skip)]
+    pub pending_feedback: Option<FeedbackKind>,
+    /// App a focus/launch action switched to, to show its profile before the next focus poll
+    #[serde(skip)]
+    pub pending_app: Option<String>,
+    /// Brightness to return to after leaving profiles that set their own
+    #[serde(skip)]
+    pub saved_brightness: Option<u8>,
+    /// Countdown started by `TIMER` buttons
+    #[serde(skip)]
+    pub timer: Timer,
+    /// `LOCK_DECK` lock, released by keying in the PIN
+    #[serde(skip)]
+    pub deck_lock: DeckLock,
+    /// LCD strip layout ("default" or "minimal", set by scenes)
+    #[serde(skip)]
+    pub strip_layout: String,
+    /// Widget shown in each strip quadrant (from config)
+    #[serde(skip)]
+    pub strip_widgets: [StripWidget; 4],
+    /// CPU usage across all cores, sampled while a CPU widget is shown
+    #[serde(skip)]
+    pub cpu_pct: Option<u8>,
+    /// Current media track, polled while a Now Playing widget is shown
+    #[serde(skip)]
+    pub now_playing: Option<NowPlaying>,
+    /// Recently copied text, for clipboard buttons
+    #[serde(skip)]
+    pub clipboard: ClipboardHistory,
+    /// Permission prompt quick actions (from config)
+    #[serde(skip)]
+    pub permissions: PermissionsConfig,
+    /// Labels and colors set from outside (e.g. over MQTT), keyed by button
+    #[serde(skip)]
+    pub button_overrides: HashMap<u8, ButtonOverride>,
+    /// Forwards presses of plugin actions
+    #[serde(skip)]
+    pub plugins: PluginHost,
+    /// Strip widget contents pushed by plugins, keyed by widget name
+    #[serde(skip)]
+    pub plugin_widgets: HashMap<String, PluginWidget>,
+    /// Labels and colors pushed by plugins, keyed by upper-cased action
+    #[serde(skip)]
+    pub plugin_buttons: HashMap<String, ButtonOverride>,
+    /// Latest results of the `[[monitors]]` HTTP checks, in config order
+    #[serde(skip)]
+    pub monitors: Vec<MonitorStatus>,
+    /// CPU, memory and network figures, while a `system` widget is shown
+    #[serde(skip)]
+    pub system_stats: Option<SystemStats>,
+    /// Working directory reported by the followed Claude Code session
+    #[serde(skip)]
+    pub cwd: Option<String>,
+    /// Profile `[projects]` maps `cwd` to (empty if none)
+    #[serde(skip)]
+    pub project_profile: String,
+    /// Repository from `[git] repo` (None follows `cwd`)
+    #[serde(skip)]
+    pub git_repo: Option<PathBuf>,
+    /// Latest status of the git widget's repository
+    #[serde(skip)]
+    pub git: Option<GitStatus>,
+    /// Latest state of the `[home_assistant] sensor` entity
+    #[serde(skip)]
+    pub home_assistant: Option<SensorState>,
+    /// Open button folders, innermost last
+    #[serde(skip)]
+    pub folders: Vec<Folder>,
+    /// Claude Code sessions seen through the hooks, and the picked one
+    #[serde(skip)]
+    pub sessions: Sessions,
+    /// Permission requests ACCEPT and REJECT answer, oldest first
+    #[serde(skip)]
+    pub approvals: Approvals,
+    /// Sub-agents running in each session
+    #[serde(skip)]
+    pub agents: Agents,
+    /// Error or rate limit taking over the strip until the session moves on
+    #[serde(skip)]
+    pub banner: Option<Banner>,
+    /// Latched on/off state of toggle buttons, keyed by `toggle_key`
+    #[serde(skip)]
+    pub toggles: HashMap<String, bool>,
+    /// Flag set when toggles or the active scene change and need saving
+    #[serde(skip)]
+    pub persist_pending: bool,
+    /// On-device text entry in progress (takes over encoders and strip)
+    #[serde(skip)]
+    pub text_entry: Option<TextEntry>,
+    /// Last confirmed text entry (substituted for `{entry}` in text actions)
+    #[serde(skip)]
+    pub last_text_entry: Option<String>,
+    /// Button press statistics
+    #[serde(skip)]
+    pub stats: ButtonStats,
+    /// Flag set when stats change and need saving
+    #[serde(skip)]
+    pub stats_pending: bool,
 
     // Configuration
     /// Available models (from config)
     #[serde(skip)]
     pub available_models: Vec<String>,
+    /// `[models.labels]`: names shown instead of model ids
+    #[serde(skip)]
+    pub model_labels: BTreeMap<String, String>,
     /// Terminal app for new sessions (from config)
     #[serde(skip)]
     pub terminal_app: String,
@@ -85,6 +346,9 @@ pub struct AppState {
     /// When to stop showing the brightness overlay on the LCD strip
     #[serde(skip)]
     pub brightness_display_until: Option<Instant>,
+    /// Knob overlay over the whole strip (brightness or model change)
+    #[serde(skip)]
+    pub strip_overlay: Option<StripOverlay>,
 }
 
 impl Default for AppState {
@@ -106,15 +370,66 @@ impl AppState {
             model_selecting: false,
             waiting_for_input: false,
             input_type: None,
+            tokens: None,
+            cost_usd: None,
+            context_pct: None,
             yolo_mode: false,
             connected: false,
+            device_health: DeviceHealth::default(),
+            headless: false,
+            state_dir: None,
             dictation_active: false,
-            button_flash: None,
+            button_flashes: HashMap::new(),
+            confirm: None,
             focused_app: String::new(),
+            focused_bundle_id: String::new(),
+            focused_title: String::new(),
             play_intro: false,
             screen_locked: false,
+            focus_mode: None,
+            focus: FocusConfig::default(),
             waiting_flash_on: false,
+            attention_glow: 0,
+            marquee_tick: 0,
+            task_started: false,
+            turn_started_at: None,
+            active_scene: None,
+            pending_scene: None,
+            pending_feedback: None,
+            pending_app: None,
+            saved_brightness: None,
+            timer: Timer::default(),
+            deck_lock: DeckLock::default(),
+            toggles: HashMap::new(),
+            persist_pending: false,
+            text_entry: None,
+            last_text_entry: None,
+            stats: ButtonStats::default(),
+            stats_pending: false,
+            strip_layout: "default".to_string(),
+            strip_widgets: StripConfig::default().widgets(),
+            cpu_pct: None,
+            now_playing: None,
+            clipboard: ClipboardHistory::default(),
+            permissions: PermissionsConfig::default(),
+            button_overrides: HashMap::new(),
+            plugins: PluginHost::default(),
+            plugin_widgets: HashMap::new(),
+            plugin_buttons: HashMap::new(),
+            monitors: Vec::new(),
+            system_stats: None,
+            cwd: None,
+            project_profile: String::new(),
+            git_repo: None,
+            git: None,
+            home_assistant: None,
+            folders: Vec::new(),
+            sessions: Sessions::default(),
+            approvals: Approvals::default(),
+            agents: Agents::default(),
+            banner: None,
             available_models: default_models,
+            model_labels: BTreeMap::new(),
             terminal_app: "Terminal".to_string(),
             brightness: 80,
             brightness_changed: false,
@@ -122,6 +437,7 @@ impl AppState {
             volume_changed: false,
             volume_display_until: None,
             brightness_display_until: None,
+            strip_overlay: None,
         }
     }
 
@@ -149,15 +465,66 @@ impl AppState {
             model_selecting: false,
             waiting_for_input: false,
             input_type: None,
+            tokens: None,
+            cost_usd: None,
+            context_pct: None,
             yolo_mode: false,
             connected: false,
+            device_health: DeviceHealth::default(),
+            headless: false,
+            state_dir: None,
             dictation_active: false,
-            button_flash: None,
+            button_flashes: HashMap::new(),
+            confirm: None,
             focused_app: String::new(),
+            focused_bundle_id: String::new(),
+            focused_title: String::new(),
             play_intro: false,
             screen_locked: false,
+            focus_mode: None,
+            focus: FocusConfig::default(),
             waiting_flash_on: false,
+            attention_glow: 0,
+            marquee_tick: 0,
+            task_started: false,
+            turn_started_at: None,
+            active_scene: None,
+            pending_scene: None,
+            pending_feedback: None,
+            pending_app: None,
+            saved_brightness: None,
+            timer: Timer::default(),
+            deck_lock: DeckLock::default(),
+            toggles: HashMap::new(),
+            persist_pending: false,
+            text_entry: None,
+            last_text_entry: None,
+            stats: ButtonStats::default(),
+            stats_pending: false,
+            strip_layout: "default".to_string(),
+            strip_widgets: StripConfig::default().widgets(),
+            cpu_pct: None,
+            now_playing: None,
+            clipboard: ClipboardHistory::default(),
+            permissions: PermissionsConfig::default(),
+            button_overrides: HashMap::new(),
+            plugins: PluginHost::default(),
+            plugin_widgets: HashMap::new(),
+            plugin_buttons: HashMap::new(),
+            monitors: Vec::new(),
+            system_stats: None,
+            cwd: None,
+            project_profile: String::new(),
+            git_repo: None,
+            git: None,
+            home_assistant: None,
+            folders: Vec::new(),
+            sessions: Sessions::default(),
+            approvals: Approvals::default(),
+            agents: Agents::default(),
+            banner: None,
             available_models,
+            model_labels: BTreeMap::new(),
             terminal_app,
             brightness,
             brightness_changed: false,
@@ -165,9 +532,20 @@ impl AppState {
             volume_changed: false,
             volume_display_until: None,
             brightness_display_until: None,
+            strip_overlay: None,
         }
     }
 
+    /// Directory for runtime state, the hook socket and logs
+    pub fn state_dir(&self) -> PathBuf {
+        self.state_dir.clone().unwrap_or_else(crate::hooks::state_dir)
+    }
+
+    pub fn set_device_health(&mut self, health: DeviceHealth) {
+        self.device_health = health;
+        self.connected = health == DeviceHealth::Connected;
+    }
+
     /// Adjust brightness by a delta (positive or negative)
     /// Returns the new brightness value
     pub fn adjust_brightness(&mut self, delta: i8) -> u8 {
@@ -178,8 +556,8 @@ impl AppState {
             self.brightness = new_brightness;
             self.brightness_changed = true;
         }
-        // Always refresh the overlay timer (even if brightness didn't change, user is interacting)
-        self.brightness_display_until = Some(Instant::now() + std::time::Duration::from_secs(2));
+        // Always restart the overlay (even if brightness didn't change, user is interacting)
+        self.strip_overlay = Some(StripOverlay::new(OverlayKind::Brightness, Instant::now()));
         self.brightness
     }
 
@@ -198,6 +576,26 @@ impl AppState {
         self.volume
     }
 
+    /// Take a volume read from the system, showing the overlay if it moved
+    ///
+    /// Ignored while the knob is being turned or its change hasn't been
+    /// applied yet, so a stale reading can't undo it. Returns whether the
+    /// volume changed.
+    pub fn sync_volume_from_system(&mut self, volume: u8) -> bool {
+        let volume = volume.min(100);
+        if self.volume_changed || self.is_volume_display_active() || self.volume == volume {
+            return false;
+        }
+        self.volume = volume;
+        self.volume_display_until = Some(Instant::now() + std::time::Duration::from_secs(2));
+        true
+    }
+
+    /// Whether flashes are held back for the active Focus mode
+    pub fn focus_quiet(&self) -> bool {
+        self.focus_mode.is_some() && self.focus.quiet_animations
+    }
+
     /// Check if the volume overlay should be displayed on the LCD strip
     pub fn is_volume_display_active(&self) -> bool {
         self.volume_display_until
@@ -219,17 +617,83 @@ impl AppState {
 
     /// Flash a button for visual feedback (shows as active briefly)
     pub fn flash_button(&mut self, button: u8) {
-        self.button_flash = Some((button, Instant::now()));
+        self.button_flashes.insert(button, Instant::now());
+    }
+
+    /// Check if a button should show as flashed (within `BUTTON_FLASH` of activation)
+    pub fn is_button_flashed(&self, button: u8, now: Instant) -> bool {
+        self.button_flashes
+            .get(&button)
+            .is_some_and(|&at| now.duration_since(at) < BUTTON_FLASH)
+    }
+
+    /// Buttons showing the flash now
+    pub fn flashed_buttons(&self, now: Instant) -> Vec<u8> {
+        let mut buttons: Vec<u8> = self
+            .button_flashes
+            .keys()
+            .copied()
+            .filter(|&button| self.is_button_flashed(button, now))
+            .collect();
+        buttons.sort_unstable();
+        buttons
     }
 
-    /// Check if a button should show as flashed (within 300ms of activation)
-    pub fn is_button_flashed(&self, button: u8) -> bool {
-        if let Some((flashed_button, instant)) = self.button_flash {
-            if flashed_button == button && instant.elapsed().as_millis() < 300 {
-                return true;
+    /// Forget flashes that have run their course, returning the buttons to redraw
+    pub fn take_expired_flashes(&mut self, now: Instant) -> Vec<u8> {
+        let mut expired: Vec<u8> = self
+            .button_flashes
+            .iter()
+            .filter(|(_, &at)| now.duration_since(at) >= BUTTON_FLASH)
+            .map(|(&button, _)| button)
+            .collect();
+        for button in &expired {
+            self.button_flashes.remove(button);
+        }
+        expired.sort_unstable();
+        expired
+    }
+
+    /// Press a `confirm` button: true on the second press within
+    /// `CONFIRM_WINDOW` (run the action), otherwise it starts waiting
+    pub fn confirm_press(&mut self, button: u8, now: Instant) -> bool {
+        match self.confirm.take() {
+            Some((armed, at)) if armed == button && now.duration_since(at) < CONFIRM_WINDOW => true,
+            _ => {
+                self.confirm = Some((button, now));
+                false
             }
         }
-        false
+    }
+
+    /// Button showing "press again", and whether its blink is lit
+    pub fn confirm_view(&self, now: Instant) -> Option<(u8, bool)> {
+        let (button, at) = self.confirm?;
+        let elapsed = now.duration_since(at);
+        let lit = (elapsed.as_millis() / 250).is_multiple_of(2);
+        (elapsed < CONFIRM_WINDOW).then_some((button, lit))
+    }
+
+    /// Whether a toggle button is currently latched on
+    pub fn is_toggled(&self, profile: &str, button: u8) -> bool {
+        self.toggles
+            .get(&toggle_key(profile, button))
+            .copied()
+            .unwrap_or(false)
+    }
+
+    /// Flip a toggle button, returning its new state
+    pub fn flip_toggle(&mut self, profile: &str, button: u8) -> bool {
+        let on = !self.is_toggled(profile, button);
+        self.toggles.insert(toggle_key(profile, button), on);
+        self.persist_pending = true;
+        on
+    }
+
+    /// Count a button press in the stats
+    pub fn record_press(&mut self, profile: &str, button: u8) {
+        self.stats.record(profile, button);
+        self.stats_pending = true;
     }
 
     /// Cycle through available models
@@ -248,6 +712,12 @@ impl AppState {
         }
 
         self.model = self.available_models[self.model_index].clone();
+        self.strip_overlay = Some(StripOverlay::new(OverlayKind::Model, Instant::now()));
+    }
+
+    /// How a model is shown on the deck (upper-cased)
+    pub fn model_label(&self, model: &str) -> String {
+        crate::models::label(model, &self.model_labels).to_uppercase()
     }
 
     /// Confirm model selection (called when encoder is pressed)
@@ -263,6 +733,22 @@ impl AppState {
         }
     }
 
+    /// The focused window, as profiles match it
+    /// Repository for the git widget and actions: the configured one, else
+    /// the session's working directory
+    pub fn git_dir(&self) -> Option<PathBuf> {
+        self.git_repo.clone().or_else(|| self.cwd.as_ref().map(PathBuf::from))
+    }
+
+    pub fn focused_window(&self) -> FocusedWindow<'_> {
+        FocusedWindow {
+            app: &self.focused_app,
+            bundle_id: &self.focused_bundle_id,
+            title: &self.focused_title,
+            project_profile: &self.project_profile,
+        }
+    }
+
     /// Reset to initial state
     pub fn reset(&mut self) {
         self.task_name = "READY".to_string();
@@ -276,6 +762,38 @@ impl AppState {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_reconnect_backoff() {
+        let delays: Vec<u64> = (0..9)
+            .map(|n| DeviceHealth::after_attempts(n).retry_delay().as_secs())
+            .collect();
+        assert_eq!(delays, [1, 2, 4, 8, 16, 32, 60, 60, 60]);
+        assert_eq!(DeviceHealth::after_attempts(7), DeviceHealth::Reconnecting { attempt: 7 });
+        assert_eq!(DeviceHealth::after_attempts(8), DeviceHealth::Failed { attempts: 8 });
+        assert_eq!(DeviceHealth::after_attempts(500).retry_delay().as_secs(), 60);
+
+        let mut state = AppState::new();
+        state.set_device_health(DeviceHealth::Connected);
+        assert!(state.connected);
+        state.set_device_health(DeviceHealth::after_attempts(1));
+        assert!(!state.connected);
+    }
+
+    #[test]
+    fn test_sync_volume_from_system() {
+        let mut state = AppState::new();
+        state.set_volume_from_system(40);
+        assert!(state.sync_volume_from_system(60));
+        assert_eq!(state.volume, 60);
+        assert!(state.is_volume_display_active());
+
+        // A reading taken before a knob turn is applied doesn't undo it
+        state.volume_display_until = None;
+        state.adjust_volume(1);
+        assert!(!state.sync_volume_from_system(60));
+        assert_eq!(state.volume, 65);
+    }
+
     #[test]
     fn test_cycle_model_forward() {
         let mut state = AppState::new();
@@ -331,4 +849,37 @@ mod tests {
         assert_eq!(state.terminal_app, "iTerm");
         assert_eq!(state.brightness, 75);
     }
+
+    #[test]
+    fn test_confirm_press() {
+        let mut state = AppState::new();
+        let start = Instant::now();
+        assert!(!state.confirm_press(3, start));
+        assert_eq!(state.confirm_view(start), Some((3, true)));
+        assert!(state.confirm_press(3, start + Duration::from_secs(1)));
+        assert_eq!(state.confirm_view(start), None);
+
+        // Too late, or a different button, starts over
+        assert!(!state.confirm_press(3, start));
+        assert!(!state.confirm_press(3, start + CONFIRM_WINDOW));
+        assert!(!state.confirm_press(4, start + CONFIRM_WINDOW));
+        assert_eq!(state.confirm_view(start + CONFIRM_WINDOW * 2), None);
+    }
+
+    #[test]
+    fn test_button_flash() {
+        let mut state = AppState::new();
+        state.flash_button(4);
+        state.flash_button(1);
+        let now = Instant::now();
+        assert!(state.is_button_flashed(4, now));
+        assert!(!state.is_button_flashed(2, now));
+        assert_eq!(state.flashed_buttons(now), vec![1, 4]);
+        assert!(state.take_expired_flashes(now).is_empty());
+
+        let later = now + BUTTON_FLASH;
+        assert!(state.flashed_buttons(later).is_empty());
+        assert_eq!(state.take_expired_flashes(later), vec![1, 4]);
+        assert!(state.button_flashes.is_empty());
+    }
 }
@@ -1,3 +1,22 @@
+mod agents;
+pub mod approvals;
+mod banner;
+mod lock;
 mod manager;
+mod persist;
+mod sessions;
+mod stats;
+mod text_entry;
 
-pub use manager::{AppState, InputType, DEFAULT_MODELS};
+pub use agents::{Agent, Agents};
+pub use approvals::{Approval, Approvals, Decision};
+pub use banner::{Banner, BannerKind};
+pub use lock::{keypad_digit, DeckLock, PinOutcome};
+pub use manager::{
+    toggle_key, AppState, ButtonOverride, DeviceHealth, Folder, InputType, PluginWidget,
+    SceneRequest, DEFAULT_MODELS,
+};
+pub use persist::PersistedState;
+pub use sessions::{Session, SessionPicker, Sessions};
+pub use stats::{ButtonStats, ButtonUsage};
+pub use text_entry::{TextEntry, TEXT_ENTRY_CHARSET};
@@ -0,0 +1,112 @@
+//! Runtime state that survives restarts and device reconnects
+//!
+//! Toggle buttons and the active scene are saved to
+//! `~/.claude-deck/runtime.json` whenever they change and restored on startup,
+//! so latched indicators (mute, plan mode, ...) don't silently reset.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use tracing::warn;
+
+use super::manager::AppState;
+
+/// Persisted subset of `AppState`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PersistedState {
+    /// Toggle button states, keyed by `toggle_key`
+    pub toggles: BTreeMap<String, bool>,
+    /// Scene that was active when last saved
+    pub active_scene: Option<String>,
+}
+
+impl PersistedState {
+    /// Runtime state file location in the state directory
+    pub fn path(dir: &Path) -> PathBuf {
+        dir.join("runtime.json")
+    }
+
+    /// Load saved state, or defaults if missing or unreadable
+    pub fn load(dir: &Path) -> Self {
+        let path = Self::path(dir);
+        match std::fs::read_to_string(&path) {
+            Ok(contents) => serde_json::from_str(&contents).unwrap_or_else(|e| {
+                warn!("Ignoring invalid runtime state {:?}: {}", path, e);
+                Self::default()
+            }),
+            Err(_) => Self::default(),
+        }
+    }
+
+    /// Capture the persisted fields from the app state
+    pub fn from_state(state: &AppState) -> Self {
+        Self {
+            toggles: state
+                .toggles
+                .iter()
+                .filter(|(_, on)| **on)
+                .map(|(key, on)| (key.clone(), *on))
+                .collect(),
+            active_scene: state.active_scene.clone(),
+        }
+    }
+
+    /// Restore toggles into the app state
+    ///
+    /// The active scene is not applied here since activating a scene has side
+    /// effects; callers queue it as a scene request instead.
+    pub fn apply_toggles(&self, state: &mut AppState) {
+        state.toggles = self.toggles.clone().into_iter().collect();
+    }
+
+    /// Save to the runtime state file (written atomically via rename)
+    pub fn save(&self, dir: &Path) -> Result<()> {
+        let path = Self::path(dir);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create {:?}", parent))?;
+        }
+
+        let contents = serde_json::to_string_pretty(self)?;
+        let tmp = path.with_extension("json.tmp");
+        std::fs::write(&tmp, contents).with_context(|| format!("Failed to write {:?}", tmp))?;
+        std::fs::rename(&tmp, &path).with_context(|| format!("Failed to replace {:?}", path))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip_through_state() {
+        let mut state = AppState::new();
+        state.flip_toggle("claude", 3);
+        state.flip_toggle("claude", 4);
+        state.flip_toggle("claude", 4);
+        state.active_scene = Some("Deep Work".to_string());
+
+        let saved = PersistedState::from_state(&state);
+        assert_eq!(saved.toggles.len(), 1);
+        assert_eq!(saved.active_scene.as_deref(), Some("Deep Work"));
+
+        let json = serde_json::to_string(&saved).unwrap();
+        let loaded: PersistedState = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded, saved);
+
+        let mut restored = AppState::new();
+        loaded.apply_toggles(&mut restored);
+        assert!(restored.is_toggled("claude", 3));
+        assert!(!restored.is_toggled("claude", 4));
+    }
+
+    #[test]
+    fn test_missing_fields_default() {
+        let loaded: PersistedState = serde_json::from_str("{}").unwrap();
+        assert!(loaded.toggles.is_empty());
+        assert!(loaded.active_scene.is_none());
+    }
+}
@@ -0,0 +1,188 @@
+//! Concurrent Claude Code sessions, and which one the deck follows
+//!
+//! Every hook update carries its session id. By default the deck shows
+//! whichever session reported last; picking a session on the strip makes the
+//! deck show only that one's status until AUTO is picked again.
+
+use std::time::{Duration, Instant};
+
+use crate::hooks::ClaudeStatus;
+
+/// Sessions idle this long are dropped from the list (unless picked)
+const SESSION_EXPIRY: Duration = Duration::from_secs(60 * 60);
+/// The picker closes after this long without input
+const PICKER_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// One Claude Code session seen through the hooks
+#[derive(Debug, Clone)]
+pub struct Session {
+    pub id: String,
+    /// Latest status reported by this session
+    pub status: ClaudeStatus,
+    pub last_update: Instant,
+}
+
+impl Session {
+    /// Short name for the picker: the project directory, else the id
+    pub fn label(&self) -> String {
+        self.status
+            .cwd
+            .as_deref()
+            .and_then(|cwd| cwd.trim_end_matches('/').rsplit('/').next())
+            .filter(|name| !name.is_empty())
+            .map(str::to_string)
+            .unwrap_or_else(|| self.id.chars().take(8).collect())
+    }
+}
+
+/// Picker state while it is shown on the strip
+#[derive(Debug, Clone)]
+pub struct SessionPicker {
+    /// Highlighted entry: 0 is AUTO, then sessions in list order
+    pub highlight: usize,
+    pub until: Instant,
+}
+
+/// Known sessions, the picked one and the picker
+#[derive(Debug, Clone, Default)]
+pub struct Sessions {
+    /// In order first seen
+    sessions: Vec<Session>,
+    /// Session picked on the strip (None follows the latest update)
+    selected: Option<String>,
+    picker: Option<SessionPicker>,
+    /// Set when the pick changes, for the app loop to show that session
+    pub switched: bool,
+}
+
+impl Sessions {
+    /// Record a status update, returning true if the deck should show it
+    ///
+    /// Updates without a session id (older hooks) are always shown.
+    pub fn record(&mut self, status: &ClaudeStatus) -> bool {
+        let Some(ref id) = status.session_id else {
+            return true;
+        };
+        let now = Instant::now();
+        match self.sessions.iter_mut().find(|s| &s.id == id) {
+            Some(session) => {
+                session.status = status.clone();
+                session.last_update = now;
+            }
+            None => self.sessions.push(Session {
+                id: id.clone(),
+                status: status.clone(),
+                last_update: now,
+            }),
+        }
+
+        let selected = self.selected.clone();
+        self.sessions.retain(|s| {
+            s.last_update.elapsed() < SESSION_EXPIRY || Some(&s.id) == selected.as_ref()
+        });
+        self.selected.as_ref().is_none_or(|selected| selected == id)
+    }
+
+    pub fn list(&self) -> &[Session] {
+        &self.sessions
+    }
+
+    /// The picked session, if one is picked
+    pub fn selected(&self) -> Option<&Session> {
+        let id = self.selected.as_ref()?;
+        self.sessions.iter().find(|s| &s.id == id)
+    }
+
+    /// The picker, while it is showing
+    pub fn picker(&self) -> Option<&SessionPicker> {
+        self.picker.as_ref().filter(|p| Instant::now() < p.until)
+    }
+
+    /// Open the picker, or move its highlight by `direction` entries
+    pub fn scroll(&mut self, direction: i8) {
+        let entries = self.sessions.len() + 1;
+        let current = self.current_entry();
+        let highlight = match self.picker().cloned() {
+            Some(picker) => {
+                (picker.highlight as isize + direction as isize).rem_euclid(entries as isize)
+                    as usize
+            }
+            None => current,
+        };
+        self.picker = Some(SessionPicker {
+            highlight,
+            until: Instant::now() + PICKER_TIMEOUT,
+        });
+    }
+
+    /// Pick the highlighted entry, or open the picker if it is closed
+    pub fn pick(&mut self) {
+        let Some(picker) = self.picker().cloned() else {
+            self.scroll(0);
+            return;
+        };
+        self.picker = None;
+        let selected = picker
+            .highlight
+            .checked_sub(1)
+            .and_then(|i| self.sessions.get(i))
+            .map(|s| s.id.clone());
+        if selected != self.selected {
+            self.selected = selected;
+            self.switched = true;
+        }
+    }
+
+    /// Picker entry for the current pick (0 = AUTO)
+    pub fn current_entry(&self) -> usize {
+        self.selected
+            .as_ref()
+            .and_then(|id| self.sessions.iter().position(|s| &s.id == id))
+            .map_or(0, |i| i + 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn status(id: &str, cwd: &str, task: &str) -> ClaudeStatus {
+        ClaudeStatus {
+            session_id: Some(id.to_string()),
+            cwd: Some(cwd.to_string()),
+            task: task.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_follows_latest_until_a_session_is_picked() {
+        let mut sessions = Sessions::default();
+        assert!(sessions.record(&status("a", "/src/api", "Bash")));
+        assert!(sessions.record(&status("b", "/src/web/", "Edit")));
+        assert!(sessions.record(&ClaudeStatus::default()));
+        assert_eq!(sessions.list()[1].label(), "web");
+
+        // Open on AUTO, move to the first session and pick it
+        sessions.pick();
+        assert_eq!(sessions.picker().unwrap().highlight, 0);
+        sessions.scroll(1);
+        sessions.pick();
+        assert!(std::mem::take(&mut sessions.switched));
+        assert_eq!(sessions.selected().unwrap().id, "a");
+        assert!(!sessions.record(&status("b", "/src/web", "READY")));
+        assert!(sessions.record(&status("a", "/src/api", "READY")));
+
+        // Scrolling back past AUTO wraps to the last session
+        sessions.scroll(0);
+        assert_eq!(sessions.picker().unwrap().highlight, 1);
+        sessions.scroll(-1);
+        sessions.scroll(-1);
+        assert_eq!(sessions.picker().unwrap().highlight, 2);
+        sessions.scroll(1);
+        sessions.pick();
+        assert!(sessions.switched);
+        assert!(sessions.selected().is_none());
+        assert!(sessions.record(&status("b", "/src/web", "Bash")));
+    }
+}
@@ -0,0 +1,102 @@
+//! Button press statistics
+//!
+//! Counts presses per profile and button, with the last time each was used,
+//! so users can see which bindings they actually use. Saved to
+//! `~/.claude-deck/stats.json` and served at `GET /api/stats`.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use tracing::warn;
+
+/// Usage of a single button
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ButtonUsage {
+    pub presses: u64,
+    /// Last press (Unix epoch seconds)
+    pub last_used: u64,
+}
+
+/// Press counts keyed by profile name, then button position
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ButtonStats {
+    pub profiles: BTreeMap<String, BTreeMap<u8, ButtonUsage>>,
+}
+
+impl ButtonStats {
+    /// Stats file location in the state directory
+    pub fn path(dir: &Path) -> PathBuf {
+        dir.join("stats.json")
+    }
+
+    /// Load saved stats, or empty stats if missing or unreadable
+    pub fn load(dir: &Path) -> Self {
+        let path = Self::path(dir);
+        match std::fs::read_to_string(&path) {
+            Ok(contents) => serde_json::from_str(&contents).unwrap_or_else(|e| {
+                warn!("Ignoring invalid stats file {:?}: {}", path, e);
+                Self::default()
+            }),
+            Err(_) => Self::default(),
+        }
+    }
+
+    /// Record a press of `button` in `profile`
+    pub fn record(&mut self, profile: &str, button: u8) {
+        let now = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        self.record_at(profile, button, now);
+    }
+
+    fn record_at(&mut self, profile: &str, button: u8, timestamp: u64) {
+        let usage = self
+            .profiles
+            .entry(profile.to_string())
+            .or_default()
+            .entry(button)
+            .or_default();
+        usage.presses += 1;
+        usage.last_used = timestamp;
+    }
+
+    /// Save to the stats file (written atomically via rename)
+    pub fn save(&self, dir: &Path) -> Result<()> {
+        let path = Self::path(dir);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create {:?}", parent))?;
+        }
+
+        let contents = serde_json::to_string_pretty(self)?;
+        let tmp = path.with_extension("json.tmp");
+        std::fs::write(&tmp, contents).with_context(|| format!("Failed to write {:?}", tmp))?;
+        std::fs::rename(&tmp, &path).with_context(|| format!("Failed to replace {:?}", path))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_and_round_trip() {
+        let mut stats = ButtonStats::default();
+        stats.record_at("claude", 2, 100);
+        stats.record_at("claude", 2, 250);
+        stats.record_at("default", 7, 300);
+
+        let usage = &stats.profiles["claude"][&2];
+        assert_eq!(usage.presses, 2);
+        assert_eq!(usage.last_used, 250);
+
+        let json = serde_json::to_string(&stats).unwrap();
+        let loaded: ButtonStats = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded, stats);
+    }
+}
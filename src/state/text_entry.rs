@@ -0,0 +1,86 @@
+//! On-device text entry: encoders scroll through characters shown on the
+//! strip, encoder presses add them, and the ENTRY button confirms
+
+/// Characters offered by text entry, in scroll order
+pub const TEXT_ENTRY_CHARSET: &str =
+    "0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz-_.@ ";
+
+/// Text entry in progress
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextEntry {
+    /// Characters entered so far
+    pub buffer: String,
+    /// Index of the highlighted candidate in `TEXT_ENTRY_CHARSET`
+    pub cursor: usize,
+    /// Button that started entry (press again to confirm)
+    pub button: u8,
+}
+
+impl TextEntry {
+    pub fn new(button: u8) -> Self {
+        Self {
+            buffer: String::new(),
+            cursor: 0,
+            button,
+        }
+    }
+
+    /// Highlighted candidate character
+    pub fn candidate(&self) -> char {
+        char_at(self.cursor as isize)
+    }
+
+    /// Candidate `offset` positions away from the cursor (wraps around)
+    pub fn neighbor(&self, offset: isize) -> char {
+        char_at(self.cursor as isize + offset)
+    }
+
+    /// Move the cursor by one encoder detent (wraps around)
+    pub fn scroll(&mut self, direction: i8) {
+        let len = TEXT_ENTRY_CHARSET.len();
+        self.cursor = if direction > 0 {
+            (self.cursor + 1) % len
+        } else {
+            self.cursor.checked_sub(1).unwrap_or(len - 1)
+        };
+    }
+
+    /// Append the highlighted candidate
+    pub fn push(&mut self) {
+        self.buffer.push(self.candidate());
+    }
+
+    /// Remove the last character, returning false if there was nothing to remove
+    pub fn backspace(&mut self) -> bool {
+        self.buffer.pop().is_some()
+    }
+}
+
+fn char_at(index: isize) -> char {
+    let bytes = TEXT_ENTRY_CHARSET.as_bytes();
+    bytes[index.rem_euclid(bytes.len() as isize) as usize] as char
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scroll_wraps_and_push() {
+        let mut entry = TextEntry::new(4);
+        assert_eq!(entry.candidate(), '0');
+
+        entry.scroll(-1);
+        assert_eq!(entry.candidate(), ' ');
+        assert_eq!(entry.neighbor(1), '0');
+        entry.scroll(1);
+        entry.scroll(1);
+        entry.push();
+        entry.push();
+        assert_eq!(entry.buffer, "11");
+
+        assert!(entry.backspace());
+        assert!(entry.backspace());
+        assert!(!entry.backspace());
+    }
+}
@@ -0,0 +1,123 @@
+//! Clipboard access and a short history of copied text (macOS `pbpaste`/`pbcopy`)
+
+use std::collections::VecDeque;
+
+/// Number of clipboard entries kept
+pub const HISTORY_SIZE: usize = 10;
+
+/// Most recent distinct clipboard texts, newest first
+#[derive(Debug, Clone, Default)]
+pub struct ClipboardHistory {
+    entries: VecDeque<String>,
+}
+
+impl ClipboardHistory {
+    /// Record the current clipboard text, returning true if the history changed
+    ///
+    /// Text already in the history moves to the front rather than repeating.
+    pub fn record(&mut self, text: &str) -> bool {
+        if text.trim().is_empty() || self.entries.front().is_some_and(|e| e == text) {
+            return false;
+        }
+        self.entries.retain(|e| e != text);
+        self.entries.push_front(text.to_string());
+        self.entries.truncate(HISTORY_SIZE);
+        true
+    }
+
+    /// Entry for a 1-based slot (1 = most recent)
+    pub fn get(&self, slot: usize) -> Option<&str> {
+        self.entries.get(slot.checked_sub(1)?).map(String::as_str)
+    }
+
+    /// Single-line preview of a slot for a button label
+    pub fn preview(&self, slot: usize, max_chars: usize) -> Option<String> {
+        let text = self.get(slot)?;
+        let line = text.split_whitespace().collect::<Vec<_>>().join(" ");
+        if line.chars().count() <= max_chars {
+            return Some(line);
+        }
+        let mut preview: String = line.chars().take(max_chars.saturating_sub(1)).collect();
+        preview.push('…');
+        Some(preview)
+    }
+}
+
+/// Read the clipboard as text (None if empty, not text, or unavailable)
+#[cfg(target_os = "macos")]
+pub async fn read_clipboard() -> Option<String> {
+    let output = tokio::process::Command::new("pbpaste").output().await.ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    String::from_utf8(output.stdout).ok().filter(|text| !text.is_empty())
+}
+
+#[cfg(not(target_os = "macos"))]
+pub async fn read_clipboard() -> Option<String> {
+    None
+}
+
+/// Replace the clipboard contents, returning false on failure
+#[cfg(target_os = "macos")]
+pub async fn write_clipboard(text: &str) -> bool {
+    use std::process::Stdio;
+    use tokio::io::AsyncWriteExt;
+    use tokio::process::Command;
+    use tracing::warn;
+
+    let mut child = match Command::new("pbcopy").stdin(Stdio::piped()).spawn() {
+        Ok(child) => child,
+        Err(e) => {
+            warn!("Failed to run pbcopy: {}", e);
+            return false;
+        }
+    };
+    if let Some(mut stdin) = child.stdin.take() {
+        if let Err(e) = stdin.write_all(text.as_bytes()).await {
+            warn!("Failed to write to pbcopy: {}", e);
+            return false;
+        }
+    }
+    matches!(child.wait().await, Ok(status) if status.success())
+}
+
+#[cfg(not(target_os = "macos"))]
+pub async fn write_clipboard(_text: &str) -> bool {
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_history_dedupes_and_orders() {
+        let mut history = ClipboardHistory::default();
+        assert!(history.record("first"));
+        assert!(history.record("second"));
+        assert!(!history.record("second"));
+        assert!(!history.record("  "));
+        assert!(history.record("first"));
+
+        assert_eq!(history.get(1), Some("first"));
+        assert_eq!(history.get(2), Some("second"));
+        assert_eq!(history.get(3), None);
+        assert_eq!(history.get(0), None);
+
+        for i in 0..20 {
+            history.record(&format!("entry {}", i));
+        }
+        assert_eq!(history.get(HISTORY_SIZE), Some("entry 10"));
+        assert_eq!(history.get(HISTORY_SIZE + 1), None);
+    }
+
+    #[test]
+    fn test_preview_is_single_line_and_truncated() {
+        let mut history = ClipboardHistory::default();
+        history.record("fn main() {\n    println!(\"hi\");\n}");
+        assert_eq!(history.preview(1, 40).unwrap(), "fn main() { println!(\"hi\"); }");
+        assert_eq!(history.preview(1, 8).unwrap(), "fn main…");
+        assert!(history.preview(2, 8).is_none());
+    }
+}
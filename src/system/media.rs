@@ -0,0 +1,129 @@
+//! Now Playing and media keys
+//!
+//! macOS goes through `nowplaying-cli` (`brew install nowplaying-cli`), which
+//! reads the system Now Playing info any media app reports. Elsewhere
+//! `playerctl` talks MPRIS to the active player.
+
+use tokio::process::Command;
+use tracing::{debug, warn};
+
+/// The track the active media player reports
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NowPlaying {
+    pub title: String,
+    /// Empty if the player doesn't report one
+    pub artist: String,
+    pub playing: bool,
+}
+
+impl NowPlaying {
+    /// "Title - Artist", or just the title
+    pub fn summary(&self) -> String {
+        if self.artist.is_empty() {
+            self.title.clone()
+        } else {
+            format!("{} - {}", self.title, self.artist)
+        }
+    }
+}
+
+/// Player control sent by the media built-in actions
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MediaCommand {
+    PlayPause,
+    Next,
+    Previous,
+}
+
+impl MediaCommand {
+    /// Subcommand for the platform's media tool
+    fn arg(self) -> &'static str {
+        match self {
+            MediaCommand::PlayPause if cfg!(target_os = "macos") => "togglePlayPause",
+            MediaCommand::PlayPause => "play-pause",
+            MediaCommand::Next => "next",
+            MediaCommand::Previous => "previous",
+        }
+    }
+}
+
+/// Media tool for this platform
+fn media_tool() -> &'static str {
+    if cfg!(target_os = "macos") {
+        "nowplaying-cli"
+    } else {
+        "playerctl"
+    }
+}
+
+/// The current track, or None when nothing is playing (or the tool is missing)
+pub async fn get_now_playing() -> Option<NowPlaying> {
+    let mut command = Command::new(media_tool());
+    if cfg!(target_os = "macos") {
+        command.args(["get", "title", "artist", "playbackRate"]);
+    } else {
+        command.args(["metadata", "--format", "{{title}}\n{{artist}}\n{{status}}"]);
+    }
+    let output = command.output().await.ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    parse_now_playing(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// Parse title / artist / state lines (`nowplaying-cli` prints "null" for
+/// missing fields and a playback rate; `playerctl` prints a status word)
+pub fn parse_now_playing(output: &str) -> Option<NowPlaying> {
+    let mut lines = output.lines().map(|line| match line.trim() {
+        "null" => "",
+        line => line,
+    });
+    let title = lines.next().filter(|title| !title.is_empty())?.to_string();
+    let artist = lines.next().unwrap_or_default().to_string();
+    let state = lines.next().unwrap_or_default();
+    let playing = state == "Playing" || state.parse::<f32>().is_ok_and(|rate| rate > 0.0);
+    Some(NowPlaying {
+        title,
+        artist,
+        playing,
+    })
+}
+
+/// Send a media command in the background, logging failures
+pub fn send_media_command(command: MediaCommand) {
+    tokio::spawn(async move {
+        let tool = media_tool();
+        match Command::new(tool).arg(command.arg()).output().await {
+            Ok(output) if !output.status.success() => {
+                let stderr = String::from_utf8_lossy(&output.stderr);
+                let arg = command.arg();
+                debug!("{} {} exited with {}: {}", tool, arg, output.status, stderr.trim());
+            }
+            Ok(_) => {}
+            Err(e) => warn!("Failed to run {} (is it installed?): {}", tool, e),
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_now_playing() {
+        let track = parse_now_playing("Windowlicker\nAphex Twin\n1\n").unwrap();
+        assert!(track.playing);
+        assert_eq!(track.summary(), "Windowlicker - Aphex Twin");
+
+        let paused = parse_now_playing("Some Podcast\nnull\n0\n").unwrap();
+        assert!(!paused.playing);
+        assert_eq!(paused.summary(), "Some Podcast");
+
+        let mpris = parse_now_playing("Intro\nThe xx\nPaused\n").unwrap();
+        assert!(!mpris.playing);
+        assert_eq!(parse_now_playing("Outro\n\nPlaying").map(|t| t.playing), Some(true));
+
+        assert!(parse_now_playing("null\nnull\n0\n").is_none());
+        assert!(parse_now_playing("").is_none());
+    }
+}
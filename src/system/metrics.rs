@@ -0,0 +1,270 @@
+//! CPU, memory and network figures for the `system` strip widget
+//!
+//! Linux reads `/proc`; macOS asks `ps`, `sysctl`, `vm_stat` and `netstat`.
+//! CPU time (on Linux) and network throughput come from the difference
+//! between two samples, so the first reading has neither.
+//!
+//! These are read directly rather than through a crate like sysinfo, which
+//! covers more ground: here Windows and the BSDs get no figures (the widget
+//! shows `-`), and on macOS the CPU figure is `ps`'s per-process average,
+//! which lags a few seconds behind a tick-based reading, at the cost of
+//! four short-lived processes a second while the widget is shown.
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::process::Command;
+use tokio::sync::{mpsc, RwLock as TokioRwLock};
+
+use crate::config::StripWidget;
+use crate::state::AppState;
+use crate::AppCommand;
+
+const SAMPLE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Latest system figures
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SystemStats {
+    /// CPU usage across all cores (0-100)
+    pub cpu_pct: Option<u8>,
+    /// Memory in use (0-100)
+    pub mem_pct: Option<u8>,
+    /// Bytes per second received and sent, on every interface but loopback
+    pub net_rates: Option<(u64, u64)>,
+}
+
+/// Counters read at one moment
+#[derive(Debug, Clone, Copy, Default)]
+struct Sample {
+    /// Busy and total CPU ticks (Linux)
+    cpu_ticks: Option<(u64, u64)>,
+    /// Usage already averaged by the tool that reported it (macOS)
+    cpu_pct: Option<u8>,
+    mem_pct: Option<u8>,
+    /// Bytes received and sent since boot
+    net_bytes: Option<(u64, u64)>,
+}
+
+/// Sample once a second while a `system` widget is on the strip
+///
+/// Figures are written to `state.system_stats`, followed by a strip refresh.
+pub fn spawn(state: Arc<TokioRwLock<AppState>>, redraw: mpsc::Sender<AppCommand>) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(SAMPLE_INTERVAL);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        let mut last: Option<(Instant, Sample)> = None;
+        loop {
+            interval.tick().await;
+            if !state.read().await.strip_widgets.contains(&StripWidget::System) {
+                last = None;
+                continue;
+            }
+
+            let now = Instant::now();
+            let sample = read_sample().await;
+            let stats = match last {
+                Some((then, previous)) => stats_between(&previous, &sample, now - then),
+                None => SystemStats {
+                    cpu_pct: sample.cpu_pct,
+                    mem_pct: sample.mem_pct,
+                    net_rates: None,
+                },
+            };
+            last = Some((now, sample));
+
+            state.write().await.system_stats = Some(stats);
+            if redraw.send(AppCommand::RefreshStrip).await.is_err() {
+                return;
+            }
+        }
+    });
+}
+
+/// Figures for the time between two samples
+fn stats_between(previous: &Sample, current: &Sample, elapsed: Duration) -> SystemStats {
+    let cpu_pct = match (previous.cpu_ticks, current.cpu_ticks) {
+        (Some((busy_then, total_then)), Some((busy, total))) => {
+            percent(busy.saturating_sub(busy_then), total.saturating_sub(total_then))
+        }
+        _ => current.cpu_pct,
+    };
+    let secs = elapsed.as_secs_f64().max(0.001);
+    let net_rates = match (previous.net_bytes, current.net_bytes) {
+        (Some((rx_then, tx_then)), Some((rx, tx))) => Some((
+            (rx.saturating_sub(rx_then) as f64 / secs) as u64,
+            (tx.saturating_sub(tx_then) as f64 / secs) as u64,
+        )),
+        _ => None,
+    };
+    SystemStats {
+        cpu_pct,
+        mem_pct: current.mem_pct,
+        net_rates,
+    }
+}
+
+async fn read_sample() -> Sample {
+    if cfg!(target_os = "linux") {
+        let read = |path: &'static str| async move { tokio::fs::read_to_string(path).await.ok() };
+        Sample {
+            cpu_ticks: read("/proc/stat").await.as_deref().and_then(parse_proc_stat),
+            cpu_pct: None,
+            mem_pct: read("/proc/meminfo").await.as_deref().and_then(parse_meminfo),
+            net_bytes: read("/proc/net/dev").await.as_deref().and_then(parse_net_dev),
+        }
+    } else if cfg!(target_os = "macos") {
+        let total = command_output("sysctl", &["-n", "hw.memsize"])
+            .await
+            .and_then(|out| out.trim().parse::<u64>().ok());
+        let vm_stat = command_output("vm_stat", &[]).await;
+        Sample {
+            cpu_ticks: None,
+            cpu_pct: super::get_cpu_usage().await,
+            mem_pct: vm_stat.zip(total).and_then(|(out, total)| parse_vm_stat(&out, total)),
+            net_bytes: command_output("netstat", &["-ib"]).await.as_deref().and_then(parse_netstat),
+        }
+    } else {
+        Sample::default()
+    }
+}
+
+async fn command_output(program: &str, args: &[&str]) -> Option<String> {
+    let output = Command::new(program).args(args).output().await.ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+fn percent(part: u64, whole: u64) -> Option<u8> {
+    (whole > 0).then(|| (part.min(whole) * 100 / whole) as u8)
+}
+
+/// Busy and total ticks from the `cpu` line of /proc/stat (idle and iowait count as idle)
+fn parse_proc_stat(stat: &str) -> Option<(u64, u64)> {
+    let line = stat.lines().find(|line| line.starts_with("cpu "))?;
+    let ticks: Vec<u64> = line
+        .split_whitespace()
+        .skip(1)
+        .take(8)
+        .filter_map(|field| field.parse().ok())
+        .collect();
+    if ticks.len() < 5 {
+        return None;
+    }
+    let total: u64 = ticks.iter().sum();
+    Some((total - ticks[3] - ticks[4], total))
+}
+
+/// Memory in use from /proc/meminfo: MemTotal less MemAvailable
+fn parse_meminfo(meminfo: &str) -> Option<u8> {
+    let field = |name: &str| {
+        meminfo.lines().find_map(|line| {
+            line.strip_prefix(name)?.split_whitespace().next()?.parse::<u64>().ok()
+        })
+    };
+    let total = field("MemTotal:")?;
+    percent(total.saturating_sub(field("MemAvailable:")?), total)
+}
+
+/// Bytes received and sent on all interfaces but loopback, from /proc/net/dev
+fn parse_net_dev(dev: &str) -> Option<(u64, u64)> {
+    let mut totals = None;
+    for line in dev.lines().skip(2) {
+        let Some((name, counters)) = line.split_once(':') else {
+            continue;
+        };
+        let counters: Vec<u64> =
+            counters.split_whitespace().filter_map(|field| field.parse().ok()).collect();
+        if name.trim() == "lo" || counters.len() < 9 {
+            continue;
+        }
+        let (rx, tx) = totals.unwrap_or((0, 0));
+        totals = Some((rx + counters[0], tx + counters[8]));
+    }
+    totals
+}
+
+/// Memory in use from `vm_stat`: active, wired and compressed pages
+fn parse_vm_stat(vm_stat: &str, total: u64) -> Option<u8> {
+    let page_size: u64 = vm_stat
+        .lines()
+        .next()?
+        .split("page size of ")
+        .nth(1)?
+        .split_whitespace()
+        .next()?
+        .parse()
+        .ok()?;
+    let pages = |name: &str| {
+        vm_stat
+            .lines()
+            .find_map(|line| line.strip_prefix(name)?.trim().trim_end_matches('.').parse().ok())
+            .unwrap_or(0u64)
+    };
+    let used = pages("Pages active:")
+        + pages("Pages wired down:")
+        + pages("Pages occupied by compressor:");
+    percent(used * page_size, total)
+}
+
+/// Bytes in and out on all interfaces but loopback, from the link rows of `netstat -ib`
+fn parse_netstat(netstat: &str) -> Option<(u64, u64)> {
+    let mut totals = None;
+    for line in netstat.lines().skip(1) {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        let n = fields.len();
+        if n < 8 || fields[0].starts_with("lo") || !fields[2].starts_with("<Link#") {
+            continue;
+        }
+        // Name Mtu Network [Address] Ipkts Ierrs Ibytes Opkts Oerrs Obytes Coll
+        let (Ok(rx), Ok(tx)) = (fields[n - 5].parse::<u64>(), fields[n - 2].parse::<u64>()) else {
+            continue;
+        };
+        let (rx_total, tx_total) = totals.unwrap_or((0, 0));
+        totals = Some((rx_total + rx, tx_total + tx));
+    }
+    totals
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_metrics() {
+        let stat = "cpu  100 0 50 800 50 0 0 0 0 0\ncpu0 50 0 25 400 25 0 0 0 0 0\n";
+        assert_eq!(parse_proc_stat(stat), Some((150, 1000)));
+        let meminfo = "MemTotal:       16000000 kB\nMemFree: 1 kB\nMemAvailable:    4000000 kB\n";
+        assert_eq!(parse_meminfo(meminfo), Some(75));
+        let dev = "Inter-|   Receive\n face |bytes packets\n\
+            \x20   lo: 999 1 0 0 0 0 0 0 999 1 0 0 0 0 0 0\n\
+            \x20 eth0: 2000 10 0 0 0 0 0 0 500 5 0 0 0 0 0 0\n";
+        assert_eq!(parse_net_dev(dev), Some((2000, 500)));
+
+        let vm_stat = "Mach Virtual Memory Statistics: (page size of 16384 bytes)\n\
+            Pages free:                               10000.\n\
+            Pages active:                            200000.\n\
+            Pages wired down:                        100000.\n\
+            Pages occupied by compressor:            100000.\n";
+        assert_eq!(parse_vm_stat(vm_stat, 16384 * 800_000), Some(50));
+        let netstat = "Name Mtu Network Address Ipkts Ierrs Ibytes Opkts Oerrs Obytes Coll\n\
+            lo0 16384 <Link#1> 100 0 9000 100 0 9000 0\n\
+            en0 1500 <Link#6> a4:83:e7:00:00:01 500 0 70000 400 0 30000 0\n\
+            en0 1500 192.168.1 192.168.1.20 500 - 70000 400 - 30000 -\n";
+        assert_eq!(parse_netstat(netstat), Some((70000, 30000)));
+
+        let previous = Sample {
+            cpu_ticks: Some((100, 1000)),
+            net_bytes: Some((1000, 100)),
+            ..Sample::default()
+        };
+        let current = Sample {
+            cpu_ticks: Some((150, 1100)),
+            net_bytes: Some((3000, 100)),
+            ..Sample::default()
+        };
+        let stats = stats_between(&previous, &current, Duration::from_secs(2));
+        assert_eq!(stats.cpu_pct, Some(50));
+        assert_eq!(stats.net_rates, Some((1000, 0)));
+    }
+}
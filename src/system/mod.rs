@@ -1,14 +1,144 @@
 //! System utilities for macOS integration
 
+mod clipboard;
+mod media;
+pub mod metrics;
+pub mod screenshot;
+pub mod volume;
+
+pub use clipboard::{read_clipboard, write_clipboard, ClipboardHistory};
+pub use media::{get_now_playing, send_media_command, MediaCommand, NowPlaying};
+pub use metrics::SystemStats;
+
+use anyhow::{Context, Result};
+use std::path::{Path, PathBuf};
 use tokio::process::Command;
 use tracing::warn;
 
-/// Get the name of the currently focused application on macOS
+/// Name and home directory of the user logged in at the console
+/// (None at the login window, where /dev/console is owned by root)
+fn console_owner() -> Option<(String, PathBuf)> {
+    use std::ffi::CStr;
+
+    let uid = console_uid()?;
+
+    // SAFETY: getpwuid returns a pointer to static storage (or null); we copy
+    // the fields out immediately and never hold the pointer.
+    unsafe {
+        let pw = libc::getpwuid(uid);
+        if pw.is_null() {
+            return None;
+        }
+        let name = CStr::from_ptr((*pw).pw_name).to_string_lossy().into_owned();
+        let home = CStr::from_ptr((*pw).pw_dir).to_string_lossy().into_owned();
+        Some((name, PathBuf::from(home)))
+    }
+}
+
+/// User id of the user logged in at the console (None at the login window)
+pub fn console_uid() -> Option<u32> {
+    use std::os::unix::fs::MetadataExt;
+
+    let uid = std::fs::metadata("/dev/console").ok()?.uid();
+    (uid != 0).then_some(uid)
+}
+
+/// Name of the user currently at the console (active fast-user-switch session)
+pub fn console_user() -> Option<String> {
+    console_owner().map(|(name, _)| name)
+}
+
+/// Home directory of the user currently at the console
+pub fn console_user_home() -> Option<PathBuf> {
+    console_owner().map(|(_, home)| home)
+}
+
+/// Whether we're running as root (e.g., as a LaunchDaemon)
+pub fn is_root() -> bool {
+    // SAFETY: geteuid has no preconditions and cannot fail
+    unsafe { libc::geteuid() == 0 }
+}
+
+/// 32 random bytes from /dev/urandom, as hex
+pub fn random_token() -> Result<String> {
+    use std::io::Read;
+
+    let mut bytes = [0u8; 32];
+    std::fs::File::open("/dev/urandom")
+        .and_then(|mut random| random.read_exact(&mut bytes))
+        .context("Failed to read /dev/urandom")?;
+    Ok(bytes.iter().map(|b| format!("{:02x}", b)).collect())
+}
+
+/// Write a secret to `path`, readable only by its owner (mode 0600)
+///
+/// Running as root, the file is given to the owner of its directory, so a
+/// daemon can hand a secret to the user whose home it's in.
+pub fn write_secret_file(path: &Path, secret: &str) -> Result<()> {
+    use std::io::Write;
+    use std::os::unix::fs::{MetadataExt, OpenOptionsExt, PermissionsExt};
+
+    let dir = path.parent().context("Secret file has no directory")?;
+    if !dir.exists() {
+        std::fs::create_dir_all(dir)?;
+        // A daemon creating the directory in a user's home gives it to them
+        if let (true, Some(parent)) = (is_root(), dir.parent()) {
+            let owner = std::fs::metadata(parent)?;
+            std::os::unix::fs::chown(dir, Some(owner.uid()), Some(owner.gid()))?;
+        }
+    }
+    let mut file = std::fs::OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .mode(0o600)
+        .open(path)
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+    // An older file may have been created with looser permissions
+    file.set_permissions(std::fs::Permissions::from_mode(0o600))?;
+    file.write_all(secret.as_bytes())?;
+    if is_root() {
+        let owner = std::fs::metadata(dir)?;
+        std::os::unix::fs::chown(path, Some(owner.uid()), Some(owner.gid()))?;
+    }
+    Ok(())
+}
+
+/// The frontmost application and its front window
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WindowInfo {
+    /// Application name (e.g., "Terminal")
+    pub app: String,
+    /// Bundle ID (e.g., "com.apple.Terminal"), empty if unknown
+    pub bundle_id: String,
+    /// Front window title, empty if the app has no window or access is denied
+    pub title: String,
+}
+
+/// AppleScript printing the frontmost app name, bundle ID and window title, one per line
 #[cfg(target_os = "macos")]
-pub async fn get_focused_app() -> Option<String> {
+const FOCUSED_WINDOW_SCRIPT: &str = r#"tell application "System Events"
+    set frontApp to first process whose frontmost is true
+    set windowTitle to ""
+    try
+        set windowTitle to name of front window of frontApp
+    end try
+    set bundleId to ""
+    try
+        set bundleId to bundle identifier of frontApp
+    end try
+    return (name of frontApp) & linefeed & bundleId & linefeed & windowTitle
+end tell"#;
+
+/// Get the focused application, its bundle ID and front window title on macOS
+///
+/// The window title needs Accessibility access for System Events; without it
+/// the title is empty and profiles still match on app name and bundle ID.
+#[cfg(target_os = "macos")]
+pub async fn get_focused_window_info() -> Option<WindowInfo> {
     let output = match Command::new("osascript")
         .arg("-e")
-        .arg("tell application \"System Events\" to get name of first process whose frontmost is true")
+        .arg(FOCUSED_WINDOW_SCRIPT)
         .output()
         .await
     {
@@ -20,8 +150,7 @@ pub async fn get_focused_app() -> Option<String> {
     };
 
     if output.status.success() {
-        let app_name = String::from_utf8_lossy(&output.stdout).trim().to_string();
-        Some(app_name)
+        parse_window_info(&String::from_utf8_lossy(&output.stdout))
     } else {
         let stderr = String::from_utf8_lossy(&output.stderr);
         warn!("osascript failed: {} - {}", output.status, stderr);
@@ -30,10 +159,24 @@ pub async fn get_focused_app() -> Option<String> {
 }
 
 #[cfg(not(target_os = "macos"))]
-pub async fn get_focused_app() -> Option<String> {
+pub async fn get_focused_window_info() -> Option<WindowInfo> {
     None
 }
 
+/// Parse the app / bundle ID / title lines printed by the focused-window script
+pub fn parse_window_info(output: &str) -> Option<WindowInfo> {
+    let mut lines = output.trim_end_matches(['\r', '\n']).splitn(3, '\n');
+    let app = lines.next()?.trim().to_string();
+    if app.is_empty() {
+        return None;
+    }
+    Some(WindowInfo {
+        app,
+        bundle_id: lines.next().unwrap_or("").trim().to_string(),
+        title: lines.next().unwrap_or("").trim().to_string(),
+    })
+}
+
 /// Check if the macOS screen is locked via IOConsoleLocked (~28ms)
 #[cfg(target_os = "macos")]
 pub async fn is_screen_locked() -> bool {
@@ -50,6 +193,92 @@ pub async fn is_screen_locked() -> bool {
     false
 }
 
+/// Whether the Mac is running on battery (None if unknown, e.g. desktops)
+#[cfg(target_os = "macos")]
+pub async fn is_on_battery() -> Option<bool> {
+    let output = Command::new("pmset").args(["-g", "batt"]).output().await.ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    parse_power_source(&String::from_utf8_lossy(&output.stdout))
+}
+
+#[cfg(not(target_os = "macos"))]
+pub async fn is_on_battery() -> Option<bool> {
+    None
+}
+
+/// Parse `pmset -g batt` output ("Now drawing from 'Battery Power'")
+pub fn parse_power_source(output: &str) -> Option<bool> {
+    if output.contains("'Battery Power'") {
+        Some(true)
+    } else if output.contains("'AC Power'") {
+        Some(false)
+    } else {
+        None
+    }
+}
+
+/// Name of the active Focus mode (e.g. "Do Not Disturb"), None when off
+///
+/// Read from the Focus database in `~/Library/DoNotDisturb`, which records
+/// modes turned on from Control Center or a shortcut (scheduled Focus isn't
+/// seen). Reading it may need Full Disk Access.
+#[cfg(target_os = "macos")]
+pub async fn get_focus_mode() -> Option<String> {
+    let home = if is_root() {
+        console_user_home()?
+    } else {
+        PathBuf::from(std::env::var("HOME").ok()?)
+    };
+    let db = home.join("Library/DoNotDisturb/DB");
+    let assertions = tokio::fs::read_to_string(db.join("Assertions.json")).await.ok()?;
+    let modes = tokio::fs::read_to_string(db.join("ModeConfigurations.json"))
+        .await
+        .unwrap_or_default();
+    parse_focus_mode(&assertions, &modes)
+}
+
+#[cfg(not(target_os = "macos"))]
+pub async fn get_focus_mode() -> Option<String> {
+    None
+}
+
+/// Parse the Focus database: the asserted mode, named from the mode configurations
+pub fn parse_focus_mode(assertions: &str, modes: &str) -> Option<String> {
+    let assertions: serde_json::Value = serde_json::from_str(assertions).ok()?;
+    let mode_id = assertions["data"]
+        .as_array()?
+        .iter()
+        .filter_map(|data| data["storeAssertionRecords"].as_array())
+        .flatten()
+        .find_map(|record| record["assertionDetails"]["assertionDetailsModeIdentifier"].as_str())?;
+
+    let modes: serde_json::Value = serde_json::from_str(modes).unwrap_or_default();
+    let name = modes["data"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .find_map(|data| data["modeConfigurations"][mode_id]["mode"]["name"].as_str());
+    Some(name.unwrap_or("Focus").to_string())
+}
+
+/// CPU usage across all cores (0-100), from per-process `ps` figures
+pub async fn get_cpu_usage() -> Option<u8> {
+    let output = Command::new("ps").args(["-A", "-o", "%cpu="]).output().await.ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let cores = std::thread::available_parallelism().map_or(1, |n| n.get());
+    Some(parse_cpu_usage(&String::from_utf8_lossy(&output.stdout), cores))
+}
+
+/// Sum `ps -o %cpu=` lines (100 per busy core) into a whole-machine percentage
+pub fn parse_cpu_usage(output: &str, cores: usize) -> u8 {
+    let total: f32 = output.lines().filter_map(|l| l.trim().parse::<f32>().ok()).sum();
+    (total / cores.max(1) as f32).round().clamp(0.0, 100.0) as u8
+}
+
 /// Get the current system output volume (0-100)
 #[cfg(target_os = "macos")]
 pub async fn get_system_volume() -> Option<u8> {
@@ -95,3 +324,109 @@ pub async fn set_system_volume(volume: u8) {
 
 #[cfg(not(target_os = "macos"))]
 pub async fn set_system_volume(_volume: u8) {}
+
+/// AppleScript bringing the iTerm2 session or Terminal tab on a tty to the front
+#[cfg(target_os = "macos")]
+const FOCUS_TTY_SCRIPT: &str = r#"on run argv
+    set targetTty to item 1 of argv
+    if application "iTerm2" is running then
+        tell application "iTerm2"
+            repeat with w in windows
+                repeat with t in tabs of w
+                    repeat with s in sessions of t
+                        if tty of s is targetTty then
+                            select w
+                            select t
+                            select s
+                            activate
+                            return
+                        end if
+                    end repeat
+                end repeat
+            end repeat
+        end tell
+    end if
+    if application "Terminal" is running then
+        tell application "Terminal"
+            repeat with w in windows
+                repeat with t in tabs of w
+                    if tty of t is targetTty then
+                        set selected of t to true
+                        set index of w to 1
+                        activate
+                        return
+                    end if
+                end repeat
+            end repeat
+        end tell
+    end if
+end run"#;
+
+/// Focus the terminal tab running on `tty` (iTerm2 or Terminal), so
+/// keystrokes reach that Claude session
+#[cfg(target_os = "macos")]
+pub async fn focus_terminal_tty(tty: &str) {
+    match Command::new("osascript")
+        .arg("-e")
+        .arg(FOCUS_TTY_SCRIPT)
+        .arg(tty)
+        .output()
+        .await
+    {
+        Ok(output) if !output.status.success() => {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            warn!("Failed to focus terminal on {}: {}", tty, stderr.trim());
+        }
+        Ok(_) => {}
+        Err(e) => warn!("osascript command failed: {}", e),
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+pub async fn focus_terminal_tty(_tty: &str) {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_window_info() {
+        let info = parse_window_info("iTerm2\ncom.googlecode.iterm2\nssh prod-db\n").unwrap();
+        assert_eq!(info.app, "iTerm2");
+        assert_eq!(info.bundle_id, "com.googlecode.iterm2");
+        assert_eq!(info.title, "ssh prod-db");
+
+        let no_window = parse_window_info("Finder\ncom.apple.finder\n\n").unwrap();
+        assert_eq!(no_window.title, "");
+        assert!(parse_window_info("\n").is_none());
+    }
+
+    #[test]
+    fn test_parse_power_source() {
+        let battery = "Now drawing from 'Battery Power'\n -InternalBattery-0\t87%; discharging";
+        assert_eq!(parse_power_source(battery), Some(true));
+        assert_eq!(parse_power_source("Now drawing from 'AC Power'\n"), Some(false));
+        assert_eq!(parse_power_source(""), None);
+    }
+
+    #[test]
+    fn test_parse_focus_mode() {
+        let assertions = r#"{"data":[{"storeAssertionRecords":[{"assertionDetails":
+            {"assertionDetailsModeIdentifier":"com.apple.focus.work"}}]}]}"#;
+        let modes = r#"{"data":[{"modeConfigurations":{"com.apple.focus.work":
+            {"mode":{"name":"Work","modeIdentifier":"com.apple.focus.work"}}}}]}"#;
+        assert_eq!(parse_focus_mode(assertions, modes).as_deref(), Some("Work"));
+        assert_eq!(parse_focus_mode(assertions, "").as_deref(), Some("Focus"));
+
+        // Turning Focus off leaves the file with no assertion records
+        assert_eq!(parse_focus_mode(r#"{"data":[{}]}"#, modes), None);
+        assert_eq!(parse_focus_mode("", modes), None);
+    }
+
+    #[test]
+    fn test_parse_cpu_usage() {
+        assert_eq!(parse_cpu_usage(" 50.0\n 25.5\n  0.0\n 24.5\n", 4), 25);
+        assert_eq!(parse_cpu_usage("900.0\n", 8), 100);
+        assert_eq!(parse_cpu_usage("", 8), 0);
+    }
+}
@@ -0,0 +1,70 @@
+//! Interactive screenshots with `screencapture` (macOS)
+//!
+//! The user drags out an area (or presses Space to pick a window), so a
+//! capture taken from the deck is as quick to annotate and paste into Claude
+//! Code as one taken with Cmd+Shift+4.
+
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use tokio::process::Command;
+use tracing::{info, warn};
+
+use super::write_clipboard;
+
+/// `screenshot-<unix time>.png` in `dir`
+fn screenshot_path(dir: &Path, time: u64) -> PathBuf {
+    dir.join(format!("screenshot-{}.png", time))
+}
+
+/// Capture a part of the screen in the background
+///
+/// Without a directory the image goes to the clipboard. With one it's saved
+/// there and its path goes to the clipboard instead, to paste as a reference
+/// Claude Code can read.
+pub fn capture(dir: Option<PathBuf>) {
+    tokio::spawn(async move {
+        let path = dir.map(|dir| {
+            let time = SystemTime::now()
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0);
+            screenshot_path(&dir, time)
+        });
+        let mut command = Command::new("screencapture");
+        match path {
+            Some(ref path) => command.arg("-i").arg(path),
+            None => command.arg("-ic"),
+        };
+        match command.output().await {
+            Ok(output) if !output.status.success() => {
+                let stderr = String::from_utf8_lossy(&output.stderr);
+                warn!("screencapture exited with {}: {}", output.status, stderr.trim());
+            }
+            Ok(_) => match path {
+                // Escape during the selection writes nothing
+                Some(path) if path.exists() => {
+                    info!("Screenshot saved to {}", path.display());
+                    if !write_clipboard(&path.to_string_lossy()).await {
+                        warn!("Failed to copy the screenshot path to the clipboard");
+                    }
+                }
+                Some(_) => info!("Screenshot cancelled"),
+                None => info!("Screenshot taken (on the clipboard)"),
+            },
+            Err(e) => warn!("Failed to run screencapture: {}", e),
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_screenshot_path() {
+        assert_eq!(
+            screenshot_path(Path::new("/src/app"), 1760000000),
+            PathBuf::from("/src/app/screenshot-1760000000.png")
+        );
+    }
+}
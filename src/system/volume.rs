@@ -0,0 +1,37 @@
+//! Keeps `state.volume` in step with the system output volume
+//!
+//! Knob turns set the system volume from the main loop; this task reads it
+//! back every couple of seconds, so changes made with the keyboard or the
+//! menu bar move the strip's volume bar too.
+
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{mpsc, RwLock as TokioRwLock};
+use tracing::debug;
+
+use crate::state::AppState;
+use crate::AppCommand;
+
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Poll the system volume, refreshing the strip when it changed elsewhere
+pub fn spawn(state: Arc<TokioRwLock<AppState>>, redraw: mpsc::Sender<AppCommand>) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(POLL_INTERVAL);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            interval.tick().await;
+            let Some(volume) = super::get_system_volume().await else {
+                continue;
+            };
+            let previous = state.read().await.volume;
+            if !state.write().await.sync_volume_from_system(volume) {
+                continue;
+            }
+            debug!("System volume changed externally: {}% -> {}%", previous, volume);
+            if redraw.send(AppCommand::RefreshStrip).await.is_err() {
+                return;
+            }
+        }
+    });
+}
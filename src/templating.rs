@@ -0,0 +1,147 @@
+//! `{placeholder}` expansion for button labels and Text actions
+//!
+//! | Placeholder        | Expands to                                      |
+//! |--------------------|-------------------------------------------------|
+//! | `{model}`          | Selected model                                  |
+//! | `{task}`           | Current task                                    |
+//! | `{detail}`         | Current tool detail                             |
+//! | `{focused_app}`    | Frontmost application                           |
+//! | `{scene}`          | Active scene                                    |
+//! | `{clipboard}`      | Most recently copied text                       |
+//! | `{entry}`          | Last confirmed text entry                       |
+//! | `{date:<format>}`  | Local time in strftime format (`{date}` = `%H:%M`) |
+//!
+//! Labels are expanded when a button is drawn, Text actions when pressed.
+//! Unknown placeholders are left as written. Snippet actions also take a
+//! `{cursor}` marker, where the cursor is left once the snippet is typed.
+
+use std::collections::HashSet;
+use std::ffi::CString;
+use std::sync::{Mutex, OnceLock};
+
+use crate::state::AppState;
+
+/// Whether `text` may contain placeholders (cheap pre-check)
+pub fn has_placeholders(text: &str) -> bool {
+    text.contains('{')
+}
+
+/// Expand every known placeholder in `text`
+pub fn expand(text: &str, state: &AppState) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('{') {
+        out.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let Some(end) = rest.find('}') else {
+            break;
+        };
+        let placeholder = &rest[..=end];
+        match lookup(&placeholder[1..end], state) {
+            Some(value) => out.push_str(&value),
+            None => out.push_str(placeholder),
+        }
+        rest = &rest[end + 1..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Where Snippet actions leave the cursor
+pub const CURSOR_MARKER: &str = "{cursor}";
+
+/// Split a snippet at its first `{cursor}` marker, dropping any others
+///
+/// Without a marker everything comes before the cursor.
+pub fn split_cursor(template: &str) -> (&str, String) {
+    match template.split_once(CURSOR_MARKER) {
+        Some((before, after)) => (before, after.replace(CURSOR_MARKER, "")),
+        None => (template, String::new()),
+    }
+}
+
+/// Expand a label into a `&'static str` for `ButtonConfig`
+///
+/// Results are interned, so redrawing a templated label doesn't leak a new
+/// string each time.
+pub fn expand_label(label: &'static str, state: &AppState) -> &'static str {
+    static INTERNED: OnceLock<Mutex<HashSet<&'static str>>> = OnceLock::new();
+    let expanded = expand(label, state);
+    let mut interned = INTERNED
+        .get_or_init(Default::default)
+        .lock()
+        .unwrap_or_else(|e| e.into_inner());
+    match interned.get(expanded.as_str()) {
+        Some(label) => label,
+        None => {
+            let label: &'static str = Box::leak(expanded.into_boxed_str());
+            interned.insert(label);
+            label
+        }
+    }
+}
+
+fn lookup(name: &str, state: &AppState) -> Option<String> {
+    let value = match name {
+        "model" => state.model.clone(),
+        "task" => state.task_name.clone(),
+        "detail" => state.tool_detail.clone().unwrap_or_default(),
+        "focused_app" => state.focused_app.clone(),
+        "scene" => state.active_scene.clone().unwrap_or_default(),
+        "clipboard" => state.clipboard.get(1).unwrap_or_default().to_string(),
+        "entry" => state.last_text_entry.clone().unwrap_or_default(),
+        "date" => format_local_time("%H:%M"),
+        _ => format_local_time(name.strip_prefix("date:")?),
+    };
+    Some(value)
+}
+
+/// Format the local time with strftime
+fn format_local_time(format: &str) -> String {
+    let Ok(format) = CString::new(format) else {
+        return String::new();
+    };
+    let mut buf = [0u8; 128];
+    // SAFETY: time(NULL) and localtime_r only write to the tm we pass in, and
+    // strftime writes at most buf.len() bytes, returning how many it wrote
+    let len = unsafe {
+        let now = libc::time(std::ptr::null_mut());
+        let mut tm: libc::tm = std::mem::zeroed();
+        libc::localtime_r(&now, &mut tm);
+        libc::strftime(buf.as_mut_ptr().cast(), buf.len(), format.as_ptr(), &tm)
+    };
+    String::from_utf8_lossy(&buf[..len]).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expand() {
+        let mut state = AppState::new();
+        state.model = "sonnet".to_string();
+        state.clipboard.record("tests/parser.rs");
+        let text = "Fix the failing test in {clipboard} ({model}, {unknown}) {";
+        let expanded = "Fix the failing test in tests/parser.rs (sonnet, {unknown}) {";
+        assert_eq!(expand(text, &state), expanded);
+        assert_eq!(expand("{entry}", &state), "");
+
+        let year = expand("{date:%Y}", &state);
+        assert_eq!(year.len(), 4);
+        assert!(year.chars().all(|c| c.is_ascii_digit()));
+        assert_eq!(expand("{date}", &state).len(), 5);
+
+        let label = expand_label("{model}", &state);
+        assert_eq!(label, "sonnet");
+        assert!(std::ptr::eq(label, expand_label("{model}", &state)));
+    }
+
+    #[test]
+    fn test_split_cursor() {
+        let (before, after) = split_cursor("Refactor {cursor} to use async/await");
+        assert_eq!((before, after.as_str()), ("Refactor ", " to use async/await"));
+        assert_eq!(split_cursor("a{cursor}b{cursor}c"), ("a", "bc".to_string()));
+        assert_eq!(split_cursor("/review"), ("/review", String::new()));
+    }
+}
@@ -0,0 +1,205 @@
+//! Countdown timer (pomodoro) driven by the `TIMER` button action
+//!
+//! A short press starts or pauses the countdown, a long press resets it. The
+//! `timer` strip widget shows the time left, and once it reaches zero the
+//! widget and `TIMER` buttons blink for a few seconds.
+
+use std::time::{Duration, Instant};
+
+/// How long the widget and buttons blink after the timer fires
+const ALARM_DURATION: Duration = Duration::from_secs(10);
+
+/// Blink period while the alarm is on
+const ALARM_BLINK: Duration = Duration::from_millis(500);
+
+/// Where the countdown is
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimerPhase {
+    /// Not started (or reset)
+    Idle,
+    Running,
+    Paused,
+    /// Reached zero, until started again or reset
+    Fired,
+}
+
+/// What the timer looks like at an instant, to tell when a redraw is needed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimerView {
+    pub phase: TimerPhase,
+    /// Whole seconds left (rounded up, so 00:00 only shows once fired)
+    pub secs_left: u64,
+    /// Whether the alarm blink is in its bright half
+    pub alarm_on: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct Timer {
+    duration: Duration,
+    /// Blink once fired (`timer.flash`)
+    pub flash: bool,
+    /// Time counted by earlier runs (kept across pauses)
+    elapsed: Duration,
+    /// When the current run started, None unless running
+    started: Option<Instant>,
+    /// When the countdown reached zero
+    fired_at: Option<Instant>,
+}
+
+impl Default for Timer {
+    fn default() -> Self {
+        Self::new(Duration::from_secs(25 * 60))
+    }
+}
+
+impl Timer {
+    pub fn new(duration: Duration) -> Self {
+        Self {
+            duration,
+            flash: true,
+            elapsed: Duration::ZERO,
+            started: None,
+            fired_at: None,
+        }
+    }
+
+    pub fn phase(&self) -> TimerPhase {
+        if self.fired_at.is_some() {
+            TimerPhase::Fired
+        } else if self.started.is_some() {
+            TimerPhase::Running
+        } else if self.elapsed > Duration::ZERO {
+            TimerPhase::Paused
+        } else {
+            TimerPhase::Idle
+        }
+    }
+
+    /// Start, pause or resume; starting after the timer fired begins a new countdown
+    pub fn toggle(&mut self, now: Instant) {
+        match self.phase() {
+            TimerPhase::Running => {
+                self.elapsed += self.started.take().map(|s| now - s).unwrap_or_default();
+            }
+            TimerPhase::Fired => {
+                self.reset();
+                self.started = Some(now);
+            }
+            TimerPhase::Idle | TimerPhase::Paused => self.started = Some(now),
+        }
+    }
+
+    /// Stop and go back to the full duration
+    pub fn reset(&mut self) {
+        self.elapsed = Duration::ZERO;
+        self.started = None;
+        self.fired_at = None;
+    }
+
+    /// Time left on the countdown
+    pub fn remaining(&self, now: Instant) -> Duration {
+        let running = self.started.map(|s| now.saturating_duration_since(s)).unwrap_or_default();
+        self.duration.saturating_sub(self.elapsed + running)
+    }
+
+    /// Fraction of the countdown left (1.0 = full, 0.0 = done)
+    pub fn fraction_left(&self, now: Instant) -> f32 {
+        if self.duration.is_zero() {
+            return 0.0;
+        }
+        self.remaining(now).as_secs_f32() / self.duration.as_secs_f32()
+    }
+
+    /// Note a running countdown reaching zero, returning true the once it does
+    pub fn poll(&mut self, now: Instant) -> bool {
+        if self.started.is_none() || !self.remaining(now).is_zero() {
+            return false;
+        }
+        self.started = None;
+        self.elapsed = self.duration;
+        self.fired_at = Some(now);
+        true
+    }
+
+    /// Whether the alarm blink is lit at `now`
+    pub fn alarm_on(&self, now: Instant) -> bool {
+        self.flash
+            && self.fired_at.is_some_and(|fired| {
+                let since = now.saturating_duration_since(fired);
+                let half_periods = since.as_millis() / ALARM_BLINK.as_millis();
+                since < ALARM_DURATION && half_periods.is_multiple_of(2)
+            })
+    }
+
+    /// Whether `TIMER` buttons show lit: while counting down, and on the alarm's bright half
+    pub fn button_lit(&self, now: Instant) -> bool {
+        self.phase() == TimerPhase::Running || self.alarm_on(now)
+    }
+
+    pub fn view(&self, now: Instant) -> TimerView {
+        let remaining = self.remaining(now);
+        let whole = remaining.as_secs();
+        let secs_left = if remaining.subsec_nanos() > 0 { whole + 1 } else { whole };
+        TimerView {
+            phase: self.phase(),
+            secs_left,
+            alarm_on: self.alarm_on(now),
+        }
+    }
+}
+
+/// Whether a custom action name is the timer's
+pub fn is_timer_action(name: &str) -> bool {
+    name.eq_ignore_ascii_case("TIMER")
+}
+
+/// "MM:SS", or "H:MM:SS" from an hour up
+pub fn format_remaining(secs: u64) -> String {
+    if secs >= 3600 {
+        format!("{}:{:02}:{:02}", secs / 3600, secs % 3600 / 60, secs % 60)
+    } else {
+        format!("{:02}:{:02}", secs / 60, secs % 60)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_countdown_pause_and_fire() {
+        let start = Instant::now();
+        let at = |secs| start + Duration::from_secs(secs);
+        let mut timer = Timer::new(Duration::from_secs(60));
+        assert_eq!(timer.phase(), TimerPhase::Idle);
+
+        timer.toggle(start);
+        assert_eq!(timer.view(at(20)).secs_left, 40);
+
+        // Paused time doesn't count
+        timer.toggle(at(20));
+        assert_eq!(timer.phase(), TimerPhase::Paused);
+        assert_eq!(timer.remaining(at(500)), Duration::from_secs(40));
+        timer.toggle(at(500));
+        assert!(!timer.poll(at(530)));
+
+        assert!(timer.poll(at(540)));
+        assert!(!timer.poll(at(541)), "fires only once");
+        assert_eq!(timer.phase(), TimerPhase::Fired);
+        assert!(timer.alarm_on(at(540)));
+        assert!(!timer.alarm_on(at(560)));
+
+        // Starting again after firing begins a fresh countdown
+        timer.toggle(at(600));
+        assert_eq!(timer.view(at(600)).secs_left, 60);
+        timer.reset();
+        assert_eq!(timer.phase(), TimerPhase::Idle);
+    }
+
+    #[test]
+    fn test_format_remaining() {
+        assert_eq!(format_remaining(25 * 60), "25:00");
+        assert_eq!(format_remaining(59), "00:59");
+        assert_eq!(format_remaining(3725), "1:02:05");
+    }
+}
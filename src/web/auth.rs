@@ -0,0 +1,191 @@
+//! Who may use the API
+//!
+//! The server only listens on loopback, but any web page open in the
+//! user's browser can still send it requests. So every request must name
+//! this machine in `Host` (which stops DNS rebinding), browsers may only
+//! read responses from the web UI's own origin, and anything that changes
+//! state (every method but GET and HEAD) must carry the per-launch token
+//! in `X-Deck-Token`. A custom header can't be sent cross-origin without a
+//! CORS preflight, which a foreign origin fails.
+//!
+//! The web UI gets the token embedded in its page. Scripts and other local
+//! tools read it from `~/.claude-deck/web-token`, written at startup and
+//! readable only by its owner.
+
+use anyhow::Result;
+use axum::{
+    extract::{Request, State},
+    http::{header, HeaderMap, HeaderValue, Method, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use super::types::ApiResponse;
+
+/// Header carrying the API token
+pub const TOKEN_HEADER: &str = "x-deck-token";
+
+/// Token file name in the state directory
+const TOKEN_FILE: &str = "web-token";
+
+/// Host names that mean this machine
+const LOOPBACK_HOSTS: [&str; 3] = ["localhost", "127.0.0.1", "[::1]"];
+
+/// The per-launch token and the port it guards
+pub struct ApiAuth {
+    token: String,
+    port: u16,
+}
+
+impl ApiAuth {
+    /// A fresh random token for the server on `port`
+    pub fn generate(port: u16) -> Result<Self> {
+        let token = crate::system::random_token()?;
+        Ok(Self { token, port })
+    }
+
+    pub fn token(&self) -> &str {
+        &self.token
+    }
+
+    /// Write the token where local tools can read it (mode 0600)
+    ///
+    /// Running as root, the file is given to the owner of `dir`, the
+    /// console user whose session the deck serves.
+    pub fn write_token_file(&self, dir: &Path) -> Result<PathBuf> {
+        let path = token_path(dir);
+        crate::system::write_secret_file(&path, &self.token)?;
+        Ok(path)
+    }
+
+    /// Origins the web UI is served from
+    pub fn origins(&self) -> Vec<HeaderValue> {
+        LOOPBACK_HOSTS
+            .iter()
+            .filter_map(|host| format!("http://{}:{}", host, self.port).parse().ok())
+            .collect()
+    }
+
+    /// Why a request is refused, if it is
+    fn check(
+        &self,
+        method: &Method,
+        headers: &HeaderMap,
+    ) -> Result<(), (StatusCode, &'static str)> {
+        let host = headers.get(header::HOST).and_then(|v| v.to_str().ok());
+        if !host.is_some_and(|host| self.is_local_host(host)) {
+            return Err((StatusCode::FORBIDDEN, "Host must be localhost"));
+        }
+        if let Some(origin) = headers.get(header::ORIGIN) {
+            if !self.origins().contains(origin) {
+                return Err((StatusCode::FORBIDDEN, "Cross-origin requests are not allowed"));
+            }
+        }
+        if method == Method::GET || method == Method::HEAD || method == Method::OPTIONS {
+            return Ok(());
+        }
+        let token = headers.get(TOKEN_HEADER).map(HeaderValue::as_bytes);
+        if !token.is_some_and(|token| constant_time_eq(token, self.token.as_bytes())) {
+            return Err((StatusCode::UNAUTHORIZED, "Missing or wrong X-Deck-Token"));
+        }
+        Ok(())
+    }
+
+    /// Whether a `Host` header names this machine on our port
+    fn is_local_host(&self, host: &str) -> bool {
+        let (name, port) = match host.rsplit_once(':') {
+            // A bare IPv6 address has colons but no port
+            Some((name, port)) if !port.ends_with(']') => (name, Some(port)),
+            _ => (host, None),
+        };
+        let port_ok = port.is_none_or(|port| port.parse() == Ok(self.port));
+        port_ok && LOOPBACK_HOSTS.iter().any(|local| name.eq_ignore_ascii_case(local))
+    }
+}
+
+/// Token file location in a state directory
+pub fn token_path(dir: &Path) -> PathBuf {
+    dir.join(TOKEN_FILE)
+}
+
+/// Middleware refusing requests that fail `ApiAuth::check`
+pub async fn guard(State(auth): State<Arc<ApiAuth>>, request: Request, next: Next) -> Response {
+    match auth.check(request.method(), request.headers()) {
+        Ok(()) => next.run(request).await,
+        Err((status, message)) => (status, Json(ApiResponse::<()>::error(message))).into_response(),
+    }
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::PermissionsExt;
+
+    fn headers(pairs: &[(&'static str, &str)]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for (name, value) in pairs {
+            headers.insert(*name, value.parse().unwrap());
+        }
+        headers
+    }
+
+    #[test]
+    fn test_check() {
+        let auth = ApiAuth::generate(9845).unwrap();
+        assert_eq!(auth.token().len(), 64);
+        let token = auth.token().to_string();
+        let local = [("host", "localhost:9845")];
+
+        assert!(auth.check(&Method::GET, &headers(&local)).is_ok());
+        assert!(auth.check(&Method::GET, &headers(&[("host", "127.0.0.1:9845")])).is_ok());
+        assert!(auth.check(&Method::GET, &headers(&[("host", "[::1]:9845")])).is_ok());
+        // DNS rebinding: an attacker's name resolving to 127.0.0.1
+        let rebound = auth.check(&Method::GET, &headers(&[("host", "evil.example:9845")]));
+        assert_eq!(rebound.unwrap_err().0, StatusCode::FORBIDDEN);
+        assert!(auth.check(&Method::GET, &headers(&[("host", "localhost:1234")])).is_err());
+        assert!(auth.check(&Method::GET, &HeaderMap::new()).is_err());
+
+        // Changes need the token
+        let post = auth.check(&Method::POST, &headers(&local));
+        assert_eq!(post.unwrap_err().0, StatusCode::UNAUTHORIZED);
+        let wrong = headers(&[("host", "localhost:9845"), (TOKEN_HEADER, "0000")]);
+        assert!(auth.check(&Method::POST, &wrong).is_err());
+        let good = headers(&[("host", "localhost:9845"), (TOKEN_HEADER, &token)]);
+        assert!(auth.check(&Method::POST, &good).is_ok());
+        assert!(auth.check(&Method::DELETE, &good).is_ok());
+
+        // Only the UI's own origin, even with the token
+        let mut foreign = good.clone();
+        foreign.insert(header::ORIGIN, "https://evil.example".parse().unwrap());
+        assert!(auth.check(&Method::POST, &foreign).is_err());
+        let mut own = good.clone();
+        own.insert(header::ORIGIN, "http://localhost:9845".parse().unwrap());
+        assert!(auth.check(&Method::POST, &own).is_ok());
+        let ws = headers(&[("host", "localhost:9845"), ("origin", "http://evil.example")]);
+        assert!(auth.check(&Method::GET, &ws).is_err());
+    }
+
+    #[test]
+    fn test_token_file() {
+        let dir = std::env::temp_dir().join(format!("claude-deck-token-{}", std::process::id()));
+        let auth = ApiAuth::generate(9845).unwrap();
+        let path = auth.write_token_file(&dir).unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), auth.token());
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+
+        // A new launch replaces it
+        let next = ApiAuth::generate(9845).unwrap();
+        assert_ne!(next.token(), auth.token());
+        next.write_token_file(&dir).unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), next.token());
+        std::fs::remove_dir_all(dir).ok();
+    }
+}
@@ -0,0 +1,96 @@
+//! Live events for web clients (`GET /api/events`, server-sent events)
+//!
+//! Each event's SSE name is its kind (`config`, `device`, `status`, `input`) and its
+//! data is JSON. A client that falls behind gets a `lagged` event and should
+//! refetch whatever it shows.
+
+use serde_json::json;
+use tokio::sync::broadcast;
+
+use crate::hooks::ClaudeStatus;
+
+use super::input::Input;
+use super::types::ConfigChangeEvent;
+
+/// Events buffered per client before it counts as lagging
+const CAPACITY: usize = 64;
+
+/// Something web clients may want to refresh for
+#[derive(Debug, Clone)]
+pub enum DeckEvent {
+    /// The config changed (through the API or on disk)
+    Config(ConfigChangeEvent),
+    /// The main deck connected or disconnected
+    Device { connected: bool },
+    /// A status update from the Claude Code hooks
+    Status(Box<ClaudeStatus>),
+    /// A button or knob on the main deck
+    Input(Input),
+}
+
+impl DeckEvent {
+    /// SSE event name
+    pub fn name(&self) -> &'static str {
+        match self {
+            DeckEvent::Config(_) => "config",
+            DeckEvent::Device { .. } => "device",
+            DeckEvent::Status(_) => "status",
+            DeckEvent::Input(_) => "input",
+        }
+    }
+
+    /// SSE event data
+    pub fn data(&self) -> String {
+        let data = match self {
+            DeckEvent::Config(change) => config_data(change),
+            DeckEvent::Device { connected } => json!({ "connected": connected }),
+            DeckEvent::Status(status) => json!(status),
+            DeckEvent::Input(input) => json!(input),
+        };
+        data.to_string()
+    }
+}
+
+/// `{"change": ..., }` naming what changed
+fn config_data(change: &ConfigChangeEvent) -> serde_json::Value {
+    match change {
+        ConfigChangeEvent::ProfileUpdated(profile) => {
+            json!({ "change": "profile_updated", "profile": profile })
+        }
+        ConfigChangeEvent::ButtonUpdated { profile, position } => {
+            json!({ "change": "button_updated", "profile": profile, "position": position })
+        }
+        ConfigChangeEvent::Reload => json!({ "change": "reload" }),
+        ConfigChangeEvent::SceneUpdated(scene) => {
+            json!({ "change": "scene_updated", "scene": scene })
+        }
+    }
+}
+
+/// Sender shared by everything that emits events
+pub fn channel() -> broadcast::Sender<DeckEvent> {
+    broadcast::channel(CAPACITY).0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_event_names_and_data() {
+        let change = DeckEvent::Config(ConfigChangeEvent::ButtonUpdated {
+            profile: "slack".to_string(),
+            position: 3,
+        });
+        assert_eq!(change.name(), "config");
+        assert_eq!(
+            change.data(),
+            r#"{"change":"button_updated","position":3,"profile":"slack"}"#
+        );
+        let reload = DeckEvent::Config(ConfigChangeEvent::Reload);
+        assert_eq!(reload.data(), r#"{"change":"reload"}"#);
+
+        let device = DeckEvent::Device { connected: false };
+        assert_eq!((device.name(), device.data().as_str()), ("device", r#"{"connected":false}"#));
+    }
+}
@@ -0,0 +1,322 @@
+//! GIF search providers for the web UI's GIF picker
+//!
+//! `[gifs] provider` picks Giphy (the default), Tenor, or the local library
+//! in `~/.config/claude-deck/gifs`. Local GIFs are referenced by `file://`
+//! URL, which the GIF decoder reads straight from disk, and are previewed in
+//! the browser through `/api/gifs/local/<file>`.
+
+use anyhow::{bail, Context, Result};
+use futures_util::future::BoxFuture;
+use std::path::{Path, PathBuf};
+
+use crate::config::{Config, GifProvider};
+
+use super::types::SearchGif;
+
+/// Largest GIF accepted into the local library
+pub const MAX_UPLOAD_BYTES: usize = 10_000_000;
+
+/// Somewhere to search for GIFs
+pub trait GifSource: Send + Sync {
+    /// Provider name for error messages
+    fn name(&self) -> &'static str;
+
+    /// Up to `limit` GIFs matching `query`
+    fn search<'a>(&'a self, query: &'a str, limit: u32) -> BoxFuture<'a, Result<Vec<SearchGif>>>;
+}
+
+/// The provider selected in the config
+pub fn from_config(config: &Config) -> Result<Box<dyn GifSource>> {
+    Ok(match config.gifs.provider {
+        GifProvider::Giphy => {
+            if config.giphy.api_key.is_empty() {
+                bail!("Giphy API key not configured (set api_key under [giphy])");
+            }
+            Box::new(Giphy {
+                api_key: config.giphy.api_key.clone(),
+            })
+        }
+        GifProvider::Tenor => {
+            if config.gifs.tenor_api_key.is_empty() {
+                bail!("Tenor API key not configured (set tenor_api_key under [gifs])");
+            }
+            Box::new(Tenor {
+                api_key: config.gifs.tenor_api_key.clone(),
+            })
+        }
+        GifProvider::Local => Box::new(LocalLibrary { dir: library_dir()? }),
+    })
+}
+
+/// `~/.config/claude-deck/gifs`
+pub fn library_dir() -> Result<PathBuf> {
+    let home = std::env::var("HOME").context("HOME environment variable not set")?;
+    Ok(PathBuf::from(home).join(".config/claude-deck/gifs"))
+}
+
+/// Fetch a provider's JSON response
+async fn get_json(provider: &str, url: &str) -> Result<serde_json::Value> {
+    let response = reqwest::Client::new()
+        .get(url)
+        .send()
+        .await
+        .with_context(|| format!("Failed to fetch from {}", provider))?;
+    if !response.status().is_success() {
+        bail!("{} API error: {}", provider, response.status());
+    }
+    response
+        .json()
+        .await
+        .with_context(|| format!("Failed to parse {} response", provider))
+}
+
+pub struct Giphy {
+    api_key: String,
+}
+
+impl GifSource for Giphy {
+    fn name(&self) -> &'static str {
+        "Giphy"
+    }
+
+    fn search<'a>(&'a self, query: &'a str, limit: u32) -> BoxFuture<'a, Result<Vec<SearchGif>>> {
+        Box::pin(async move {
+            let url = format!(
+                "https://api.giphy.com/v1/gifs/search?api_key={}&q={}&limit={}&rating=g",
+                self.api_key,
+                urlencoding::encode(query),
+                limit
+            );
+            Ok(parse_giphy_response(&get_json(self.name(), &url).await?))
+        })
+    }
+}
+
+pub struct Tenor {
+    api_key: String,
+}
+
+impl GifSource for Tenor {
+    fn name(&self) -> &'static str {
+        "Tenor"
+    }
+
+    fn search<'a>(&'a self, query: &'a str, limit: u32) -> BoxFuture<'a, Result<Vec<SearchGif>>> {
+        Box::pin(async move {
+            let url = format!(
+                "https://tenor.googleapis.com/v2/search?key={}&client_key=claude-deck&q={}\
+                 &limit={}&media_filter=gif,tinygif&contentfilter=high",
+                self.api_key,
+                urlencoding::encode(query),
+                limit
+            );
+            Ok(parse_tenor_response(&get_json(self.name(), &url).await?))
+        })
+    }
+}
+
+/// GIFs saved in a directory, matched by file name
+pub struct LocalLibrary {
+    dir: PathBuf,
+}
+
+impl GifSource for LocalLibrary {
+    fn name(&self) -> &'static str {
+        "local library"
+    }
+
+    fn search<'a>(&'a self, query: &'a str, limit: u32) -> BoxFuture<'a, Result<Vec<SearchGif>>> {
+        Box::pin(async move {
+            let dir = self.dir.clone();
+            let query = query.to_string();
+            tokio::task::spawn_blocking(move || search_dir(&dir, &query, limit)).await?
+        })
+    }
+}
+
+/// GIFs in `dir` whose name contains `query` (case-insensitive; empty lists all)
+fn search_dir(dir: &Path, query: &str, limit: u32) -> Result<Vec<SearchGif>> {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e).with_context(|| format!("Failed to read {}", dir.display())),
+    };
+    let query = query.to_lowercase();
+    let mut paths: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("gif")))
+        .filter(|path| {
+            let stem = path.file_stem().unwrap_or_default().to_string_lossy().to_lowercase();
+            stem.contains(&query)
+        })
+        .collect();
+    paths.sort();
+    Ok(paths.iter().take(limit as usize).filter_map(|path| local_gif(path)).collect())
+}
+
+/// A library GIF as a search result
+pub fn local_gif(path: &Path) -> Option<SearchGif> {
+    let file = path.file_name()?.to_string_lossy().to_string();
+    let (width, height) = image::image_dimensions(path).unwrap_or((200, 200));
+    Some(SearchGif {
+        id: file.clone(),
+        title: path.file_stem()?.to_string_lossy().to_string(),
+        preview_url: format!("/api/gifs/local/{}", urlencoding::encode(&file)),
+        url: format!("file://{}", path.display()),
+        width,
+        height,
+    })
+}
+
+/// Path in `dir` for a library file name, refusing anything that isn't a
+/// plain `.gif` file name (no directories)
+pub fn library_path(dir: &Path, name: &str) -> Result<PathBuf> {
+    let name = name.trim();
+    let plain = Path::new(name).file_name().is_some_and(|file| file == name);
+    if !plain || name.starts_with('.') {
+        bail!("Invalid GIF name '{}'", name);
+    }
+    let file = if name.to_lowercase().ends_with(".gif") {
+        name.to_string()
+    } else {
+        format!("{}.gif", name)
+    };
+    Ok(dir.join(file))
+}
+
+/// Add a GIF to the library at `dir`, returning where it was saved
+pub fn save_to_library(dir: &Path, name: &str, bytes: &[u8]) -> Result<PathBuf> {
+    if bytes.len() > MAX_UPLOAD_BYTES {
+        bail!("GIF is larger than {} MB", MAX_UPLOAD_BYTES / 1_000_000);
+    }
+    if !bytes.starts_with(b"GIF8") {
+        bail!("Not a GIF file");
+    }
+    let path = library_path(dir, name)?;
+    std::fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+    std::fs::write(&path, bytes).with_context(|| format!("Failed to write {}", path.display()))?;
+    Ok(path)
+}
+
+/// Parse Giphy API response into search results
+fn parse_giphy_response(json: &serde_json::Value) -> Vec<SearchGif> {
+    let mut gifs = Vec::new();
+
+    if let Some(data) = json.get("data").and_then(|d| d.as_array()) {
+        for item in data {
+            let id = item.get("id").and_then(|v| v.as_str()).unwrap_or_default();
+            let title = item.get("title").and_then(|v| v.as_str()).unwrap_or_default();
+
+            // Get the fixed_width version for consistent sizing
+            let images = item.get("images");
+
+            // Preview: use fixed_width_small for grid display
+            let preview = images
+                .and_then(|i| i.get("fixed_width_small"))
+                .or_else(|| images.and_then(|i| i.get("fixed_width")));
+
+            // Full: use fixed_width for button display (200px width)
+            let full = images.and_then(|i| i.get("fixed_width"));
+
+            if let (Some(preview), Some(full)) = (preview, full) {
+                let preview_url = preview.get("url").and_then(|v| v.as_str()).unwrap_or_default();
+                let url = full.get("url").and_then(|v| v.as_str()).unwrap_or_default();
+                let width: u32 = full
+                    .get("width")
+                    .and_then(|v| v.as_str())
+                    .and_then(|s| s.parse().ok())
+                    .unwrap_or(200);
+                let height: u32 = full
+                    .get("height")
+                    .and_then(|v| v.as_str())
+                    .and_then(|s| s.parse().ok())
+                    .unwrap_or(200);
+
+                if !url.is_empty() {
+                    gifs.push(SearchGif {
+                        id: id.to_string(),
+                        title: title.to_string(),
+                        preview_url: preview_url.to_string(),
+                        url: url.to_string(),
+                        width,
+                        height,
+                    });
+                }
+            }
+        }
+    }
+
+    gifs
+}
+
+/// Parse Tenor v2 API response: `tinygif` for the preview, `gif` for the button
+fn parse_tenor_response(json: &serde_json::Value) -> Vec<SearchGif> {
+    let results = json.get("results").and_then(|r| r.as_array());
+    results
+        .into_iter()
+        .flatten()
+        .filter_map(|item| {
+            let formats = item.get("media_formats")?;
+            let full = formats.get("gif")?;
+            let url = full.get("url")?.as_str()?;
+            let preview_url = formats
+                .get("tinygif")
+                .and_then(|p| p.get("url"))
+                .and_then(|u| u.as_str())
+                .unwrap_or(url);
+            let dims = full.get("dims").and_then(|d| d.as_array());
+            let dim = |i: usize| {
+                dims.and_then(|d| d.get(i)).and_then(|v| v.as_u64()).unwrap_or(200) as u32
+            };
+            let text = |key: &str| item.get(key).and_then(|v| v.as_str()).unwrap_or_default();
+            Some(SearchGif {
+                id: text("id").to_string(),
+                title: text("content_description").to_string(),
+                preview_url: preview_url.to_string(),
+                url: url.to_string(),
+                width: dim(0),
+                height: dim(1),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_tenor_response() {
+        let json = json!({ "results": [{
+            "id": "123",
+            "content_description": "party parrot",
+            "media_formats": {
+                "gif": { "url": "https://media.tenor.com/a.gif", "dims": [220, 180] },
+                "tinygif": { "url": "https://media.tenor.com/a-tiny.gif", "dims": [110, 90] }
+            }
+        }, { "id": "no-media" }] });
+        let gifs = parse_tenor_response(&json);
+        assert_eq!(gifs.len(), 1);
+        assert_eq!(gifs[0].preview_url, "https://media.tenor.com/a-tiny.gif");
+        assert_eq!((gifs[0].width, gifs[0].height), (220, 180));
+    }
+
+    #[test]
+    fn test_library_save_and_search() {
+        let dir = std::env::temp_dir().join(format!("claude-deck-gifs-{}", std::process::id()));
+        let gif = b"GIF89a\x01\x00\x01\x00\x00\x00\x00;";
+        save_to_library(&dir, "Party Parrot", gif).unwrap();
+        save_to_library(&dir, "ship-it.gif", gif).unwrap();
+        assert!(save_to_library(&dir, "../escape", gif).is_err());
+        assert!(save_to_library(&dir, "png", b"\x89PNG").is_err());
+
+        let found = search_dir(&dir, "parrot", 12).unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].preview_url, "/api/gifs/local/Party%20Parrot.gif");
+        assert!(found[0].url.starts_with("file://"));
+        assert_eq!(search_dir(&dir, "", 12).unwrap().len(), 2);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
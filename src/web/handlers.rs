@@ -1,23 +1,51 @@
 //! API endpoint handlers
 
 use axum::{
-    extract::{Path, Query, State},
+    body::Bytes,
+    extract::{Path, Query, Request, State},
+    http::{header, HeaderMap, HeaderValue, Method, StatusCode},
+    middleware::Next,
+    response::sse::{Event, KeepAlive, Sse},
+    response::{IntoResponse, Response},
     Json,
 };
+use futures_util::stream::{self, Stream};
+use image::RgbImage;
+use std::convert::Infallible;
 use std::sync::{Arc, RwLock as StdRwLock};
-use tokio::sync::{mpsc, RwLock as TokioRwLock};
-use tracing::{info, warn};
-
-use crate::config::Config;
-use crate::profiles::store::ButtonConfigEntry;
-use crate::profiles::{generate_default_profiles, ProfileManager};
-
+use std::time::Duration;
+use tokio::sync::broadcast::{self, error::RecvError};
+use tokio::sync::{mpsc, oneshot, RwLock as TokioRwLock};
+use tracing::{debug, info, warn};
+
+use crate::agent::{KeystrokeQueue, AGENT_TOKEN_HEADER, POLL_TIMEOUT};
+use crate::config::{self, Config, ConfigWriter, SceneConfig};
+use crate::device::{
+    FrameBuffer, InputEvent, SavedFrames, VirtualDevice, DISPLAY_BUTTON_COUNT,
+};
+use crate::doctor::{self, Check};
+use crate::integrations::websocket::{self, WebSocket};
+use crate::display::{assets, preview, theme, DisplayRenderer, GifCacheStats};
+use crate::input::keystrokes::KeyOp;
+use crate::profiles::store::{ActionConfig, ButtonConfigEntry};
+use crate::profiles::{generate_default_profiles, history, ProfileManager};
+use crate::state::{approvals, ButtonStats, SceneRequest};
+use crate::AppCommand;
+
+use super::events::DeckEvent;
+use super::gifsource;
+use super::input::{self, InputFeed, InputRecord};
+use super::multipart;
 use super::types::{
     get_action_types, get_available_keys, get_builtin_actions, get_color_presets,
-    get_modifier_keys, ActionsResponse, ApiResponse, AppsResponse, ColorsResponse,
-    ConfigChangeEvent, CreateProfileRequest, GiphyGif, GiphySearchQuery, GiphySearchResponse,
-    HasDefaultsResponse, InstalledApp, ProfileResponse, ProfileSummary, UpdateButtonRequest,
-    UpdateProfileRequest,
+    get_modifier_keys, ActionsResponse, AgentPollQuery, ApiResponse, ApprovalsResponse,
+    AppsResponse, BrightnessRequest, ColorsResponse, DeviceReport,
+    ConfigChangeEvent, ConfigExportQuery, ConfigImportResponse, CreateProfileRequest, DeckSnapshot, GifSearchQuery, GifSearchResponse,
+    HasDefaultsResponse, InputPollQuery, InstalledApp, LogLevel, PressQuery, ProfileHistoryResponse,
+    ProfileResponse, ProfileSummary, ProfileVersionInfo,
+    ScenesResponse,
+    SimulatorInput, SimulatorStatus, ThemeInfo, ThemesResponse, UpdateButtonRequest,
+    AssetInfo, SearchGif, UpdateProfileRequest, UploadGifRequest,
 };
 
 /// Shared application state for web handlers
@@ -26,6 +54,62 @@ pub struct AppState {
     pub profile_manager: Arc<StdRwLock<ProfileManager>>,
     pub change_tx: mpsc::Sender<ConfigChangeEvent>,
     pub device_state: Arc<TokioRwLock<crate::state::AppState>>,
+    /// Keystrokes waiting for a user agent (system daemon mode only)
+    pub keystroke_queue: Option<Arc<KeystrokeQueue>>,
+    /// Virtual device (`--simulate` and `--headless` only)
+    pub simulator: Option<Arc<VirtualDevice>>,
+    /// Mirror of the deck display
+    pub frames: Arc<FrameBuffer>,
+    /// Serializes config saves and tracks the config version
+    pub config_writer: ConfigWriter,
+    /// Commands for the main loop (remote presses and actions)
+    pub commands: mpsc::Sender<AppCommand>,
+    /// Live events for `/api/events`
+    pub events: broadcast::Sender<DeckEvent>,
+    /// Recent deck input for `/api/input`
+    pub input: Arc<InputFeed>,
+}
+
+/// Middleware for the config routes: `ETag`/`If-Match` versioning
+///
+/// Responses carry the config version as an `ETag`. Mutations run one at a
+/// time and are rejected with 412 if `If-Match` names a stale version.
+pub async fn config_version_guard(
+    State(state): State<Arc<AppState>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let writer = &state.config_writer;
+    let _lock = if request.method() == Method::GET {
+        None
+    } else {
+        Some(writer.lock().await)
+    };
+
+    let stale = request
+        .headers()
+        .get(header::IF_MATCH)
+        .is_some_and(|tag| !etag_matches(tag.to_str().unwrap_or(""), config::version()));
+    let mut response = if stale {
+        let message = "Config changed since it was read; reload and retry";
+        (StatusCode::PRECONDITION_FAILED, Json(ApiResponse::<()>::error(message))).into_response()
+    } else {
+        next.run(request).await
+    };
+
+    if let Ok(etag) = HeaderValue::from_str(&format!("\"{}\"", config::version())) {
+        response.headers_mut().insert(header::ETAG, etag);
+    }
+    response
+}
+
+/// Whether an `If-Match` header value names `version` (`*` matches any)
+fn etag_matches(if_match: &str, version: u64) -> bool {
+    let current = format!("\"{}\"", version);
+    if_match
+        .split(',')
+        .map(str::trim)
+        .any(|tag| tag == "*" || tag.trim_start_matches("W/") == current)
 }
 
 /// GET /api/profiles - List all profiles
@@ -143,6 +227,13 @@ pub async fn update_button(
                                 Some(custom_image)
                             };
                         }
+                        if let Some(icon_svg) = request.icon_svg {
+                            button.icon_svg = if icon_svg.is_empty() {
+                                None
+                            } else {
+                                Some(icon_svg)
+                            };
+                        }
                         if let Some(gif_url) = request.gif_url {
                             button.gif_url = if gif_url.is_empty() {
                                 None
@@ -193,21 +284,7 @@ pub async fn reload_config(State(state): State<Arc<AppState>>) -> Json<ApiRespon
     // Reload config from disk
     match Config::load() {
         Ok(new_config) => {
-            let profiles = if new_config.profiles.is_empty() {
-                generate_default_profiles()
-            } else {
-                new_config.profiles.clone()
-            };
-
-            // Update state
-            {
-                let mut config = state.config.write().await;
-                *config = new_config;
-            }
-            {
-                let mut manager = state.profile_manager.write().unwrap();
-                manager.set_profiles(profiles);
-            }
+            super::server::apply_config(&state.config, &state.profile_manager, new_config).await;
 
             // Notify of change
             if let Err(e) = state.change_tx.send(ConfigChangeEvent::Reload).await {
@@ -220,6 +297,125 @@ pub async fn reload_config(State(state): State<Arc<AppState>>) -> Json<ApiRespon
     }
 }
 
+/// GET /api/config/export - Download the full config (`?format=json` for JSON)
+pub async fn export_config(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<ConfigExportQuery>,
+) -> Response {
+    let mut config = state.config.read().await.clone();
+    config.profiles = state.profile_manager.read().unwrap().get_profiles().to_vec();
+
+    let json = query.format.as_deref() == Some("json");
+    let body = if json {
+        serde_json::to_string_pretty(&config).map_err(anyhow::Error::from)
+    } else {
+        toml::to_string_pretty(&config).map_err(anyhow::Error::from)
+    };
+    let (content_type, extension) = if json {
+        ("application/json", "json")
+    } else {
+        ("application/toml", "toml")
+    };
+    match body {
+        Ok(body) => (
+            [
+                (header::CONTENT_TYPE, content_type.to_string()),
+                (
+                    header::CONTENT_DISPOSITION,
+                    format!("attachment; filename=\"claude-deck-config.{}\"", extension),
+                ),
+            ],
+            body,
+        )
+            .into_response(),
+        Err(e) => {
+            let message = format!("Failed to serialize config: {}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::error(message)))
+                .into_response()
+        }
+    }
+}
+
+/// POST /api/config/import - Replace the whole config
+///
+/// The body is `application/toml` or `application/json`, and `If-Match`
+/// must name the current config version (`*` to overwrite regardless). It
+/// must parse as a config before anything changes; the current file is then
+/// copied to `config.toml.bak-<timestamp>` and the new one applied.
+pub async fn import_config(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    body: String,
+) -> (StatusCode, Json<ApiResponse<ConfigImportResponse>>) {
+    let json = match import_format(&headers) {
+        Ok(json) => json,
+        Err((status, message)) => return (status, Json(ApiResponse::error(message))),
+    };
+    let parsed = if json {
+        serde_json::from_str::<Config>(&body).map_err(|e| e.to_string())
+    } else {
+        toml::from_str::<Config>(&body).map_err(|e| e.to_string())
+    };
+    let new_config = match parsed {
+        Ok(config) => config,
+        Err(e) => {
+            let message = format!("Invalid config: {}", e);
+            return (StatusCode::BAD_REQUEST, Json(ApiResponse::error(message)));
+        }
+    };
+
+    let backup = match Config::config_path().and_then(|path| Config::backup(&path)) {
+        Ok(backup) => backup,
+        Err(e) => {
+            let message = format!("{:#}", e);
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::error(message)));
+        }
+    };
+    if let Some(ref backup) = backup {
+        info!("Importing config (previous config saved to {:?})", backup);
+    }
+
+    super::server::apply_config(&state.config, &state.profile_manager, new_config).await;
+    let mut saved = state.config.read().await.clone();
+    saved.profiles = state.profile_manager.read().unwrap().get_profiles().to_vec();
+    if let Err(e) = state.config_writer.save(saved).await {
+        let message = format!("Failed to save imported config: {}", e);
+        return (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::error(message)));
+    }
+    if let Err(e) = state.change_tx.send(ConfigChangeEvent::Reload).await {
+        warn!("Failed to send config change event: {}", e);
+    }
+
+    let response = ConfigImportResponse {
+        backup: backup.map(|path| path.display().to_string()),
+    };
+    (StatusCode::OK, Json(ApiResponse::ok(response)))
+}
+
+/// Whether an import is JSON (else TOML), or why its headers are refused
+///
+/// Both content types need a CORS preflight, so a form on another site
+/// can't post one; a stale `If-Match` is caught by `config_version_guard`.
+fn import_format(headers: &HeaderMap) -> Result<bool, (StatusCode, &'static str)> {
+    let content_type = headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or_default();
+    let json = match content_type.split(';').next().unwrap_or_default().trim() {
+        "application/json" => true,
+        "application/toml" => false,
+        _ => {
+            let message = "Send the config as application/toml or application/json";
+            return Err((StatusCode::UNSUPPORTED_MEDIA_TYPE, message));
+        }
+    };
+    if !headers.contains_key(header::IF_MATCH) {
+        let message = "Import needs If-Match with the ETag from /api/config/export (or *)";
+        return Err((StatusCode::PRECONDITION_REQUIRED, message));
+    }
+    Ok(json)
+}
+
 /// GET /api/colors - Get available color presets
 pub async fn get_colors() -> Json<ApiResponse<ColorsResponse>> {
     Json(ApiResponse::ok(ColorsResponse {
@@ -237,20 +433,86 @@ pub async fn get_actions() -> Json<ApiResponse<ActionsResponse>> {
     }))
 }
 
-/// Save current config to disk
+/// Save current config to disk through the config writer
+///
+/// Profiles the save changes keep their previous version in the history.
 async fn save_config(state: &AppState) {
-    let config = state.config.read().await;
-    let manager = state.profile_manager.read().unwrap();
-
-    // Create new config with updated profiles
-    let mut new_config = config.clone();
-    new_config.profiles = manager.get_profiles().to_vec();
+    let mut new_config = state.config.read().await.clone();
+    new_config.profiles = state.profile_manager.read().unwrap().get_profiles().to_vec();
+    let profiles = new_config.profiles.clone();
+    // What's on disk is what this save replaces
+    let previous = Config::config_path().and_then(|path| Config::load_from(&path));
 
-    if let Err(e) = new_config.save() {
+    if let Err(e) = state.config_writer.save(new_config).await {
         warn!("Failed to save config: {}", e);
     } else {
         info!("Config saved to disk");
+        if let (Ok(previous), Ok(dir)) = (previous, history::history_dir()) {
+            history::record_changes(&dir, &previous.profiles, &profiles);
+        }
+    }
+}
+
+/// GET /api/profiles/:name/history - Earlier versions of a profile, oldest first
+pub async fn get_profile_history(
+    Path(name): Path<String>,
+) -> Json<ApiResponse<ProfileHistoryResponse>> {
+    match history::history_dir() {
+        Ok(dir) => {
+            let versions = history::versions(&dir, &name);
+            Json(ApiResponse::ok(ProfileHistoryResponse {
+                versions: versions.iter().map(ProfileVersionInfo::from).collect(),
+            }))
+        }
+        Err(e) => Json(ApiResponse::error(format!("{:#}", e))),
+    }
+}
+
+/// POST /api/profiles/:name/rollback/:version - Restore an earlier version of a profile
+///
+/// A deleted profile is recreated. The version rolled back from is kept in
+/// the history, so the rollback can be undone the same way.
+pub async fn rollback_profile(
+    State(state): State<Arc<AppState>>,
+    Path((name, version)): Path<(String, u64)>,
+) -> Json<ApiResponse<ProfileResponse>> {
+    let restored = match history::history_dir().map(|dir| history::get(&dir, &name, version)) {
+        Ok(Some(restored)) => restored.profile,
+        Ok(None) => {
+            return Json(ApiResponse::error(format!(
+                "Version {} of profile '{}' not found",
+                version, name
+            )))
+        }
+        Err(e) => return Json(ApiResponse::error(format!("{:#}", e))),
+    };
+    let response = ProfileResponse::from(&restored);
+
+    {
+        let mut manager = state.profile_manager.write().unwrap();
+        let existing = manager
+            .get_profiles()
+            .iter()
+            .position(|p| p.name.eq_ignore_ascii_case(&restored.name));
+        let mut profiles = manager.get_profiles().to_vec();
+        match existing {
+            Some(index) => profiles[index] = restored,
+            None => profiles.push(restored),
+        }
+        manager.set_profiles(profiles);
+    }
+
+    if let Err(e) = state
+        .change_tx
+        .send(ConfigChangeEvent::ProfileUpdated(name.clone()))
+        .await
+    {
+        warn!("Failed to send config change event: {}", e);
     }
+    save_config(&state).await;
+
+    info!("Rolled back profile '{}' to version {}", name, version);
+    Json(ApiResponse::ok(response))
 }
 
 /// Built-in profile names that have known default configurations
@@ -424,7 +686,14 @@ pub async fn create_profile(
                 },
                 emoji_image: None,
                 custom_image: None,
+                icon_svg: None,
                 gif_url: None,
+                pre_delay_ms: None,
+                inter_key_delay_ms: None,
+                repeat_while_held: false,
+                repeat_interval_ms: None,
+                confirm: false,
+                feedback: None,
             })
             .collect()
     };
@@ -433,7 +702,15 @@ pub async fn create_profile(
     let new_profile = crate::profiles::store::ProfileConfig {
         name: name.clone(),
         match_apps: request.match_apps,
+        match_bundle_ids: Vec::new(),
+        match_title_regex: None,
         buttons,
+        encoders: Vec::new(),
+        chords: Vec::new(),
+        strip: Default::default(),
+        font: None,
+        brightness: None,
+        tint: None,
     };
 
     let response = ProfileResponse::from(&new_profile);
@@ -523,7 +800,14 @@ pub async fn reset_button(
                     },
                     emoji_image: None,
                     custom_image: None,
+                    icon_svg: None,
                     gif_url: None,
+                    pre_delay_ms: None,
+                    inter_key_delay_ms: None,
+                    repeat_while_held: false,
+                    repeat_interval_ms: None,
+                    confirm: false,
+                    feedback: None,
                 };
 
                 // Find and replace the button
@@ -638,48 +922,657 @@ pub async fn swap_buttons(
     }
 }
 
-/// GET /api/giphy/search - Search for GIFs
-pub async fn search_giphy(
+/// GET /api/gifs/search - Search for GIFs with the configured provider
+pub async fn search_gifs(
     State(state): State<Arc<AppState>>,
-    Query(query): Query<GiphySearchQuery>,
-) -> Json<ApiResponse<GiphySearchResponse>> {
-    let api_key = {
-        let config = state.config.read().await;
-        config.giphy.api_key.clone()
+    Query(query): Query<GifSearchQuery>,
+) -> Json<ApiResponse<GifSearchResponse>> {
+    let source = match gifsource::from_config(&*state.config.read().await) {
+        Ok(source) => source,
+        Err(e) => return Json(ApiResponse::error(e.to_string())),
     };
 
-    if api_key.is_empty() {
-        return Json(ApiResponse::error(
-            "Giphy API key not configured. This shouldn't happen - try restarting the app.",
-        ));
+    match source.search(&query.q, query.limit).await {
+        Ok(gifs) => Json(ApiResponse::ok(GifSearchResponse {
+            provider: source.name().to_string(),
+            gifs,
+        })),
+        Err(e) => Json(ApiResponse::error(format!("{:#}", e))),
     }
+}
 
-    let url = format!(
-        "https://api.giphy.com/v1/gifs/search?api_key={}&q={}&limit={}&rating=g",
-        api_key,
-        urlencoding::encode(&query.q),
-        query.limit
-    );
+/// POST /api/gifs/upload - Add a GIF to the local library
+pub async fn upload_gif(Json(req): Json<UploadGifRequest>) -> Json<ApiResponse<SearchGif>> {
+    use base64::{engine::general_purpose::STANDARD, Engine};
 
-    let client = reqwest::Client::new();
-    match client.get(&url).send().await {
-        Ok(response) => {
-            if !response.status().is_success() {
-                return Json(ApiResponse::error(format!(
-                    "Giphy API error: {}",
-                    response.status()
-                )));
+    // Accept a data URL ("data:image/gif;base64,...") or bare base64
+    let encoded = req.data.split_once(',').map_or(req.data.as_str(), |(_, data)| data);
+    let bytes = match STANDARD.decode(encoded.trim()) {
+        Ok(bytes) => bytes,
+        Err(e) => return Json(ApiResponse::error(format!("Invalid base64 data: {}", e))),
+    };
+
+    let saved = gifsource::library_dir()
+        .and_then(|dir| gifsource::save_to_library(&dir, &req.name, &bytes));
+    match saved {
+        Ok(path) => {
+            info!("Added {} to the GIF library", path.display());
+            match gifsource::local_gif(&path) {
+                Some(gif) => Json(ApiResponse::ok(gif)),
+                None => Json(ApiResponse::error("Saved GIF has no file name")),
             }
+        }
+        Err(e) => Json(ApiResponse::error(format!("{:#}", e))),
+    }
+}
 
-            match response.json::<serde_json::Value>().await {
-                Ok(json) => {
-                    let gifs = parse_giphy_response(&json);
-                    Json(ApiResponse::ok(GiphySearchResponse { gifs }))
-                }
-                Err(e) => Json(ApiResponse::error(format!("Failed to parse Giphy response: {}", e))),
+/// GET /api/gifs/local/{file} - A GIF from the local library, for previews
+pub async fn get_local_gif(Path(file): Path<String>) -> Response {
+    let path = match gifsource::library_dir().and_then(|dir| gifsource::library_path(&dir, &file)) {
+        Ok(path) => path,
+        Err(e) => return (StatusCode::BAD_REQUEST, e.to_string()).into_response(),
+    };
+    match tokio::fs::read(&path).await {
+        Ok(bytes) => ([(header::CONTENT_TYPE, "image/gif")], bytes).into_response(),
+        Err(_) => StatusCode::NOT_FOUND.into_response(),
+    }
+}
+
+/// POST /api/assets - Upload PNG/JPEG button images (multipart, one per file field)
+pub async fn upload_assets(headers: HeaderMap, body: Bytes) -> Json<ApiResponse<Vec<AssetInfo>>> {
+    let content_type = headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or_default();
+    let Some(boundary) = multipart::boundary(content_type) else {
+        return Json(ApiResponse::error("Expected a multipart/form-data upload"));
+    };
+    let parts = match multipart::parse(&body, &boundary) {
+        Ok(parts) => parts,
+        Err(e) => return Json(ApiResponse::error(format!("Invalid upload: {}", e))),
+    };
+    let dir = match assets::assets_dir() {
+        Ok(dir) => dir,
+        Err(e) => return Json(ApiResponse::error(e.to_string())),
+    };
+
+    let mut uploaded = Vec::new();
+    for part in parts.into_iter().filter(|part| part.filename.is_some()) {
+        let name = part.filename.unwrap_or_default();
+        match assets::save(&dir, &part.data) {
+            Ok(id) => {
+                info!("Stored asset {} ({})", id, name);
+                uploaded.push(AssetInfo::new(id, part.data.len() as u64));
             }
+            Err(e) => return Json(ApiResponse::error(format!("{}: {:#}", name, e))),
+        }
+    }
+    if uploaded.is_empty() {
+        return Json(ApiResponse::error("No files in upload"));
+    }
+    Json(ApiResponse::ok(uploaded))
+}
+
+/// GET /api/assets - Stored button images
+pub async fn list_assets() -> Json<ApiResponse<Vec<AssetInfo>>> {
+    match assets::assets_dir().and_then(|dir| assets::list(&dir)) {
+        Ok(list) => {
+            let list = list.into_iter().map(|(id, size)| AssetInfo::new(id, size)).collect();
+            Json(ApiResponse::ok(list))
+        }
+        Err(e) => Json(ApiResponse::error(format!("{:#}", e))),
+    }
+}
+
+/// GET /api/assets/{id} - A stored image, for previews
+pub async fn get_asset(Path(id): Path<String>) -> Response {
+    let path = match assets::assets_dir().and_then(|dir| assets::path(&dir, &id)) {
+        Ok(path) => path,
+        Err(e) => return (StatusCode::BAD_REQUEST, e.to_string()).into_response(),
+    };
+    let mime = if id.ends_with(".png") { "image/png" } else { "image/jpeg" };
+    match tokio::fs::read(&path).await {
+        Ok(bytes) => ([(header::CONTENT_TYPE, mime)], bytes).into_response(),
+        Err(_) => StatusCode::NOT_FOUND.into_response(),
+    }
+}
+
+/// DELETE /api/assets/{id} - Remove a stored image
+pub async fn delete_asset(Path(id): Path<String>) -> Json<ApiResponse<String>> {
+    let path = match assets::assets_dir().and_then(|dir| assets::path(&dir, &id)) {
+        Ok(path) => path,
+        Err(e) => return Json(ApiResponse::error(e.to_string())),
+    };
+    match tokio::fs::remove_file(&path).await {
+        Ok(()) => {
+            info!("Deleted asset {}", id);
+            Json(ApiResponse::ok(format!("Deleted {}", id)))
+        }
+        Err(e) => Json(ApiResponse::error(format!("Failed to delete {}: {}", id, e))),
+    }
+}
+
+/// GET /api/stats - Button press counts and last-used times per profile
+pub async fn get_stats(State(state): State<Arc<AppState>>) -> Json<ApiResponse<ButtonStats>> {
+    let stats = state.device_state.read().await.stats.clone();
+    Json(ApiResponse::ok(stats))
+}
+
+/// GET /api/scenes - List scenes and the active one
+pub async fn list_scenes(State(state): State<Arc<AppState>>) -> Json<ApiResponse<ScenesResponse>> {
+    let scenes = state.config.read().await.scenes.clone();
+    let active = state.device_state.read().await.active_scene.clone();
+    Json(ApiResponse::ok(ScenesResponse { scenes, active }))
+}
+
+/// PUT /api/scenes/:name - Create or replace a scene
+pub async fn put_scene(
+    State(state): State<Arc<AppState>>,
+    Path(name): Path<String>,
+    Json(mut scene): Json<SceneConfig>,
+) -> Json<ApiResponse<SceneConfig>> {
+    scene.name = name.clone();
+
+    if let Some(ref layout) = scene.strip_layout {
+        if layout != "default" && layout != "minimal" {
+            return Json(ApiResponse::error(format!(
+                "Unknown strip layout '{}' (expected 'default' or 'minimal')",
+                layout
+            )));
+        }
+    }
+    if let Some(bad) = scene
+        .schedule
+        .iter()
+        .find(|t| crate::scenes::parse_schedule_time(t).is_none())
+    {
+        return Json(ApiResponse::error(format!(
+            "Invalid schedule time '{}' (expected HH:MM)",
+            bad
+        )));
+    }
+
+    {
+        let mut config = state.config.write().await;
+        match config
+            .scenes
+            .iter_mut()
+            .find(|s| s.name.eq_ignore_ascii_case(&name))
+        {
+            Some(existing) => *existing = scene.clone(),
+            None => config.scenes.push(scene.clone()),
         }
-        Err(e) => Json(ApiResponse::error(format!("Failed to fetch from Giphy: {}", e))),
+    }
+
+    if let Err(e) = state.change_tx.send(ConfigChangeEvent::SceneUpdated(name)).await {
+        warn!("Failed to send config change event: {}", e);
+    }
+
+    save_config(&state).await;
+
+    Json(ApiResponse::ok(scene))
+}
+
+/// DELETE /api/scenes/:name - Delete a scene
+pub async fn delete_scene(
+    State(state): State<Arc<AppState>>,
+    Path(name): Path<String>,
+) -> Json<ApiResponse<String>> {
+    let removed = {
+        let mut config = state.config.write().await;
+        let before = config.scenes.len();
+        config.scenes.retain(|s| !s.name.eq_ignore_ascii_case(&name));
+        config.scenes.len() < before
+    };
+
+    if !removed {
+        return Json(ApiResponse::error(format!("Scene '{}' not found", name)));
+    }
+
+    if let Err(e) = state.change_tx.send(ConfigChangeEvent::SceneUpdated(name.clone())).await {
+        warn!("Failed to send config change event: {}", e);
+    }
+
+    save_config(&state).await;
+
+    Json(ApiResponse::ok(format!("Scene '{}' deleted", name)))
+}
+
+/// POST /api/scenes/:name/activate - Activate a scene
+pub async fn activate_scene(
+    State(state): State<Arc<AppState>>,
+    Path(name): Path<String>,
+) -> Json<ApiResponse<String>> {
+    let exists = crate::scenes::find_scene(&state.config.read().await.scenes, &name).is_some();
+    if !exists {
+        return Json(ApiResponse::error(format!("Scene '{}' not found", name)));
+    }
+
+    state.device_state.write().await.pending_scene = Some(SceneRequest::Activate(name.clone()));
+    Json(ApiResponse::ok(format!("Scene '{}' activating", name)))
+}
+
+/// POST /api/scenes/deactivate - Leave the active scene
+pub async fn deactivate_scene(State(state): State<Arc<AppState>>) -> Json<ApiResponse<String>> {
+    state.device_state.write().await.pending_scene = Some(SceneRequest::Deactivate);
+    Json(ApiResponse::ok("Scene deactivating".to_string()))
+}
+
+/// GET /api/themes - Display themes for `[appearance] theme`
+pub async fn get_themes() -> Json<ApiResponse<ThemesResponse>> {
+    let themes = theme::THEMES
+        .iter()
+        .map(|t| ThemeInfo {
+            name: t.name.to_string(),
+            description: t.description.to_string(),
+        })
+        .collect();
+    let current = theme::current().name.to_string();
+    Json(ApiResponse::ok(ThemesResponse { themes, current }))
+}
+
+/// GET /api/cache/stats - GIF frame cache usage
+pub async fn get_cache_stats() -> Json<ApiResponse<GifCacheStats>> {
+    match crate::display::gif_animator().lock() {
+        Ok(animator) => Json(ApiResponse::ok(animator.stats())),
+        Err(_) => Json(ApiResponse::error("GIF cache unavailable")),
+    }
+}
+
+/// GET /api/doctor - Setup checks, as `claude-deck --doctor` prints them
+pub async fn get_doctor(State(state): State<Arc<AppState>>) -> Json<ApiResponse<Vec<Check>>> {
+    let config = state.config.read().await.clone();
+    Json(ApiResponse::ok(doctor::run(&config, true).await))
+}
+
+/// GET /api/loglevel - The log filter in use
+pub async fn get_log_level() -> (StatusCode, Json<ApiResponse<LogLevel>>) {
+    match crate::logging::level() {
+        Some(level) => (StatusCode::OK, Json(ApiResponse::ok(LogLevel { level }))),
+        None => (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(ApiResponse::error("Logging isn't initialized")),
+        ),
+    }
+}
+
+/// PUT /api/loglevel - Replace the log filter until the next restart (`{"level": "debug"}`)
+pub async fn put_log_level(
+    Json(request): Json<LogLevel>,
+) -> (StatusCode, Json<ApiResponse<LogLevel>>) {
+    match crate::logging::set_level(&request.level) {
+        Ok(level) => {
+            info!("Log level set to {}", level);
+            (StatusCode::OK, Json(ApiResponse::ok(LogLevel { level })))
+        }
+        Err(e) => (StatusCode::BAD_REQUEST, Json(ApiResponse::error(format!("{:#}", e)))),
+    }
+}
+
+/// GET /api/approvals - Queued permission requests and the latest audit log entries
+pub async fn get_approvals(
+    State(state): State<Arc<AppState>>,
+) -> Json<ApiResponse<ApprovalsResponse>> {
+    let (pending, dir) = {
+        let device_state = state.device_state.read().await;
+        (device_state.approvals.pending().cloned().collect(), device_state.state_dir())
+    };
+    let history =
+        tokio::task::spawn_blocking(move || approvals::history(&dir, approvals::HISTORY_LEN))
+        .await
+        .unwrap_or_default();
+    Json(ApiResponse::ok(ApprovalsResponse { pending, history }))
+}
+
+/// POST /api/press/{button} - Press a button as if on the device (`?long=true` for a long press)
+pub async fn press_button(
+    State(state): State<Arc<AppState>>,
+    Path(button): Path<u8>,
+    Query(query): Query<PressQuery>,
+) -> (StatusCode, Json<ApiResponse<String>>) {
+    if button >= DISPLAY_BUTTON_COUNT {
+        let message = format!("Button must be 0-{}", DISPLAY_BUTTON_COUNT - 1);
+        return (StatusCode::BAD_REQUEST, Json(ApiResponse::error(message)));
+    }
+    send_command(&state, AppCommand::Press { button, long_press: query.long }).await
+}
+
+/// POST /api/action - Run any action, given as a button's `action` object
+pub async fn run_action(
+    State(state): State<Arc<AppState>>,
+    Json(action): Json<ActionConfig>,
+) -> (StatusCode, Json<ApiResponse<String>>) {
+    send_command(&state, AppCommand::RunAction(action)).await
+}
+
+/// POST /api/device/brightness - Set the deck's brightness (`{"brightness": 60}`)
+pub async fn set_device_brightness(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<BrightnessRequest>,
+) -> (StatusCode, Json<ApiResponse<String>>) {
+    if request.brightness > 100 {
+        return (StatusCode::BAD_REQUEST, Json(ApiResponse::error("Brightness must be 0-100")));
+    }
+    send_command(&state, AppCommand::SetBrightness(request.brightness)).await
+}
+
+/// POST /api/device/intro - Play the startup animation
+pub async fn play_device_intro(
+    State(state): State<Arc<AppState>>,
+) -> (StatusCode, Json<ApiResponse<String>>) {
+    send_command(&state, AppCommand::PlayIntro).await
+}
+
+/// POST /api/device/reset - Clear the device and redraw every key and the strip
+pub async fn reset_device(
+    State(state): State<Arc<AppState>>,
+) -> (StatusCode, Json<ApiResponse<String>>) {
+    send_command(&state, AppCommand::ResetDevice).await
+}
+
+/// GET /api/device/info - The main deck's model, connection and brightness
+pub async fn get_device_info(
+    State(state): State<Arc<AppState>>,
+) -> (StatusCode, Json<ApiResponse<DeviceReport>>) {
+    let (reply, report) = oneshot::channel();
+    if state.commands.send(AppCommand::DeviceInfo(reply)).await.is_ok() {
+        if let Ok(report) = report.await {
+            return (StatusCode::OK, Json(ApiResponse::ok(report)));
+        }
+    }
+    (StatusCode::SERVICE_UNAVAILABLE, Json(ApiResponse::error("Deck is not running")))
+}
+
+/// Queue a command for the main loop, refusing while the screen or deck is locked
+async fn send_command(
+    state: &AppState,
+    command: AppCommand,
+) -> (StatusCode, Json<ApiResponse<String>>) {
+    let locked = {
+        let device_state = state.device_state.read().await;
+        device_state.screen_locked || device_state.deck_lock.is_locked()
+    };
+    if locked {
+        return (StatusCode::LOCKED, Json(ApiResponse::error("Deck is locked")));
+    }
+    match state.commands.send(command).await {
+        Ok(()) => (StatusCode::ACCEPTED, Json(ApiResponse::ok("Queued".to_string()))),
+        Err(e) => {
+            let message = format!("Deck is not running: {}", e);
+            (StatusCode::SERVICE_UNAVAILABLE, Json(ApiResponse::error(message)))
+        }
+    }
+}
+
+/// GET /api/agent/keystrokes - Long-poll for keystrokes to inject (daemon mode)
+///
+/// Only the console user's agent gets keystrokes, and it must send the token
+/// the daemon issued to that user (see `agent`).
+pub async fn agent_keystrokes(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<AgentPollQuery>,
+    headers: HeaderMap,
+) -> (StatusCode, Json<ApiResponse<Vec<KeyOp>>>) {
+    let queue = match state.keystroke_queue {
+        Some(ref queue) => Arc::clone(queue),
+        None => {
+            let message = "Not running in daemon mode";
+            return (StatusCode::NOT_FOUND, Json(ApiResponse::error(message)));
+        }
+    };
+
+    // Keystrokes belong to the active session; other agents just idle
+    let console = crate::system::console_user().zip(crate::system::console_user_home());
+    let Some((user, home)) = console.filter(|(user, _)| *user == query.user) else {
+        tokio::time::sleep(POLL_TIMEOUT).await;
+        return (StatusCode::OK, Json(ApiResponse::ok(Vec::new())));
+    };
+
+    let token = headers.get(AGENT_TOKEN_HEADER).and_then(|v| v.to_str().ok());
+    match queue.check_agent(&user, &home, token) {
+        Ok(true) => {}
+        Ok(false) => {
+            let message = "Agent token missing or wrong (see ~/.claude-deck/agent-token)";
+            return (StatusCode::UNAUTHORIZED, Json(ApiResponse::error(message)));
+        }
+        Err(e) => {
+            let message = format!("Failed to issue an agent token: {:#}", e);
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::error(message)));
+        }
+    }
+
+    (StatusCode::OK, Json(ApiResponse::ok(queue.wait_drain(POLL_TIMEOUT).await)))
+}
+
+/// GET /api/input/next - Long-poll for button and knob input
+///
+/// Returns the inputs after `after` as soon as there are any, or an empty
+/// list once `timeout` passes.
+pub async fn next_input(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<InputPollQuery>,
+) -> Json<ApiResponse<Vec<InputRecord>>> {
+    let timeout = query
+        .timeout
+        .map_or(POLL_TIMEOUT, |secs| Duration::from_secs(secs.min(60)));
+    Json(ApiResponse::ok(state.input.wait(query.after, timeout).await))
+}
+
+/// GET /api/input/ws - The same input over a WebSocket, one JSON message per event
+pub async fn input_socket(State(state): State<Arc<AppState>>, request: Request) -> Response {
+    let headers = request.headers();
+    let upgrade = headers.get(header::UPGRADE).and_then(|v| v.to_str().ok());
+    let key = headers.get(header::SEC_WEBSOCKET_KEY).and_then(|v| v.to_str().ok());
+    let (Some(true), Some(key)) = (upgrade.map(|u| u.eq_ignore_ascii_case("websocket")), key)
+    else {
+        let message = "Expected a WebSocket upgrade (use /api/input/next to long-poll)";
+        return (StatusCode::BAD_REQUEST, Json(ApiResponse::<()>::error(message))).into_response();
+    };
+    let accept = websocket::accept_key(key);
+
+    let feed = Arc::clone(&state.input);
+    tokio::spawn(async move {
+        match hyper::upgrade::on(request).await {
+            Ok(upgraded) => {
+                let socket = WebSocket::server(hyper_util::rt::TokioIo::new(upgraded));
+                input::stream(socket, feed).await;
+            }
+            Err(e) => debug!("Input WebSocket upgrade failed: {}", e),
+        }
+    });
+    (
+        StatusCode::SWITCHING_PROTOCOLS,
+        [
+            (header::UPGRADE, "websocket".to_string()),
+            (header::CONNECTION, "Upgrade".to_string()),
+            (header::SEC_WEBSOCKET_ACCEPT, accept),
+        ],
+    )
+        .into_response()
+}
+
+/// GET /api/simulator - Whether the simulator is running, and its display version
+pub async fn get_simulator(State(state): State<Arc<AppState>>) -> Json<ApiResponse<SimulatorStatus>> {
+    let status = match state.simulator {
+        Some(_) => SimulatorStatus {
+            enabled: true,
+            version: state.frames.version(),
+            brightness: state.frames.brightness(),
+        },
+        None => SimulatorStatus {
+            enabled: false,
+            version: 0,
+            brightness: 0,
+        },
+    };
+    Json(ApiResponse::ok(status))
+}
+
+/// Serve PNG bytes, uncached
+fn png_response(result: anyhow::Result<Vec<u8>>) -> Response {
+    match result {
+        Ok(png) => (
+            [(header::CONTENT_TYPE, "image/png"), (header::CACHE_CONTROL, "no-store")],
+            png,
+        )
+            .into_response(),
+        Err(e) => (StatusCode::BAD_REQUEST, e.to_string()).into_response(),
+    }
+}
+
+/// Serve simulator PNG bytes (or 404 when not simulating)
+fn simulator_png(result: Option<anyhow::Result<Vec<u8>>>) -> Response {
+    match result {
+        Some(result) => png_response(result),
+        None => {
+            (StatusCode::NOT_FOUND, "Not running with --simulate or --headless").into_response()
+        }
+    }
+}
+
+/// GET /api/simulator/buttons/{button} - Current image of a simulated button (PNG)
+pub async fn simulator_button(
+    State(state): State<Arc<AppState>>,
+    Path(button): Path<u8>,
+) -> Response {
+    simulator_png(state.simulator.as_ref().map(|_| state.frames.button_png(button)))
+}
+
+/// GET /api/simulator/strip - Current image of the simulated LCD strip (PNG)
+pub async fn simulator_strip(State(state): State<Arc<AppState>>) -> Response {
+    simulator_png(state.simulator.as_ref().map(|_| state.frames.strip_png()))
+}
+
+/// POST /api/simulator/input - Inject a synthetic button or knob event
+pub async fn simulator_input(
+    State(state): State<Arc<AppState>>,
+    Json(input): Json<SimulatorInput>,
+) -> Json<ApiResponse<String>> {
+    let sim = match state.simulator {
+        Some(ref sim) => Arc::clone(sim),
+        None => return Json(ApiResponse::error("Not running with --simulate or --headless")),
+    };
+
+    let result = match input {
+        SimulatorInput::Press { button, hold_ms } => {
+            sim.push_input(InputEvent::ButtonDown(button)).map(|()| {
+                // Release after the hold so long-press handling sees a real duration
+                tokio::spawn(async move {
+                    tokio::time::sleep(std::time::Duration::from_millis(hold_ms)).await;
+                    sim.push_input(InputEvent::ButtonUp(button)).ok();
+                });
+            })
+        }
+        SimulatorInput::ButtonDown { button } => sim.push_input(InputEvent::ButtonDown(button)),
+        SimulatorInput::ButtonUp { button } => sim.push_input(InputEvent::ButtonUp(button)),
+        SimulatorInput::Rotate { encoder, direction } => {
+            sim.push_input(InputEvent::EncoderRotate { encoder, direction })
+        }
+        SimulatorInput::EncoderPress { encoder } => sim.push_input(InputEvent::EncoderPress(encoder)),
+        SimulatorInput::StripTouch { zone } => sim.push_input(InputEvent::StripTouch(zone)),
+        SimulatorInput::StripSwipe { direction } => {
+            sim.push_input(InputEvent::StripSwipe(direction))
+        }
+    };
+
+    match result {
+        Ok(()) => Json(ApiResponse::ok("Input queued".to_string())),
+        Err(e) => Json(ApiResponse::error(e.to_string())),
+    }
+}
+
+/// Render the live app state with the current config and profiles
+async fn render_live(
+    state: &AppState,
+    render: impl FnOnce(&DisplayRenderer, &crate::state::AppState) -> anyhow::Result<RgbImage>,
+) -> anyhow::Result<Vec<u8>> {
+    let renderer = {
+        let config = state.config.read().await;
+        preview::live_renderer(&config, Arc::clone(&state.profile_manager))?
+    };
+    let device_state = state.device_state.read().await;
+    preview::encode_png(render(&renderer, &device_state)?)
+}
+
+/// GET /api/preview/buttons/{id}.png - Button rendered from the live state (PNG)
+pub async fn preview_button(
+    State(state): State<Arc<AppState>>,
+    Path(file): Path<String>,
+) -> Response {
+    // The router can't match a parameter with a suffix, so strip `.png` here
+    let button = match file.strip_suffix(".png").map(str::parse::<u8>) {
+        Some(Ok(button)) if button < DISPLAY_BUTTON_COUNT => button,
+        _ => return (StatusCode::NOT_FOUND, format!("No such button image: {}", file)).into_response(),
+    };
+    png_response(render_live(&state, |r, s| r.render_button(button, false, s)).await)
+}
+
+/// GET /api/preview/strip.png - LCD strip rendered from the live state (PNG)
+pub async fn preview_strip(State(state): State<Arc<AppState>>) -> Response {
+    png_response(render_live(&state, |r, s| r.render_strip(s)).await)
+}
+
+/// PNG bytes as a data URL
+fn png_data_url(png: &[u8]) -> String {
+    use base64::{engine::general_purpose::STANDARD, Engine};
+    format!("data:image/png;base64,{}", STANDARD.encode(png))
+}
+
+/// GET /api/snapshot - All button and strip images plus the app state
+pub async fn get_snapshot(State(state): State<Arc<AppState>>) -> Json<ApiResponse<DeckSnapshot>> {
+    let build = || -> anyhow::Result<(Vec<String>, String)> {
+        let buttons = (0..DISPLAY_BUTTON_COUNT)
+            .map(|b| state.frames.button_png(b).map(|png| png_data_url(&png)))
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        let strip = png_data_url(&state.frames.strip_png()?);
+        Ok((buttons, strip))
+    };
+
+    match build() {
+        Ok((buttons, strip)) => {
+            let device_state = state.device_state.read().await;
+            let snapshot = DeckSnapshot {
+                buttons,
+                strip,
+                brightness: device_state.brightness,
+                state: serde_json::to_value(&*device_state).unwrap_or_default(),
+            };
+            Json(ApiResponse::ok(snapshot))
+        }
+        Err(e) => Json(ApiResponse::error(format!("Failed to capture snapshot: {}", e))),
+    }
+}
+
+/// POST /api/snapshot/restore - Put a saved snapshot back on the deck
+pub async fn restore_snapshot(
+    State(state): State<Arc<AppState>>,
+    Json(snapshot): Json<DeckSnapshot>,
+) -> Json<ApiResponse<String>> {
+    let decode = |data_url: &str| {
+        crate::display::emoji::load_base64_image(data_url)
+            .map(|img| image::DynamicImage::ImageRgba8(img).to_rgb8())
+    };
+
+    let mut buttons = Vec::new();
+    for (button, data_url) in snapshot.buttons.iter().enumerate() {
+        match decode(data_url) {
+            Some(img) => buttons.push((button as u8, img)),
+            None => return Json(ApiResponse::error(format!("Invalid image for button {}", button))),
+        }
+    }
+    let strip = match decode(&snapshot.strip) {
+        Some(img) => img,
+        None => return Json(ApiResponse::error("Invalid strip image")),
+    };
+
+    let saved = SavedFrames {
+        buttons,
+        strip: Some(strip),
+        brightness: Some(snapshot.brightness.min(100)),
+    };
+    match state.frames.request_restore(saved) {
+        Ok(()) => Json(ApiResponse::ok("Snapshot restored".to_string())),
+        Err(e) => Json(ApiResponse::error(e.to_string())),
     }
 }
 
@@ -687,8 +1580,7 @@ pub async fn search_giphy(
 pub async fn get_status(
     State(state): State<Arc<AppState>>,
 ) -> Json<ApiResponse<serde_json::Value>> {
-    let home = std::env::var("HOME").unwrap_or_else(|_| "/tmp".to_string());
-    let state_path = std::path::PathBuf::from(home).join(".claude-deck/state.json");
+    let state_path = crate::hooks::status_file_path();
 
     let mut status = match std::fs::read_to_string(&state_path) {
         Ok(content) => match serde_json::from_str::<serde_json::Value>(&content) {
@@ -725,53 +1617,54 @@ pub async fn get_status(
     Json(ApiResponse::ok(status))
 }
 
-/// Parse Giphy API response into our GiphyGif format
-fn parse_giphy_response(json: &serde_json::Value) -> Vec<GiphyGif> {
-    let mut gifs = Vec::new();
-
-    if let Some(data) = json.get("data").and_then(|d| d.as_array()) {
-        for item in data {
-            let id = item.get("id").and_then(|v| v.as_str()).unwrap_or_default();
-            let title = item.get("title").and_then(|v| v.as_str()).unwrap_or_default();
-
-            // Get the fixed_width version for consistent sizing
-            let images = item.get("images");
-
-            // Preview: use fixed_width_small for grid display
-            let preview = images
-                .and_then(|i| i.get("fixed_width_small"))
-                .or_else(|| images.and_then(|i| i.get("fixed_width")));
-
-            // Full: use fixed_width for button display (200px width)
-            let full = images.and_then(|i| i.get("fixed_width"));
-
-            if let (Some(preview), Some(full)) = (preview, full) {
-                let preview_url = preview.get("url").and_then(|v| v.as_str()).unwrap_or_default();
-                let url = full.get("url").and_then(|v| v.as_str()).unwrap_or_default();
-                let width: u32 = full
-                    .get("width")
-                    .and_then(|v| v.as_str())
-                    .and_then(|s| s.parse().ok())
-                    .unwrap_or(200);
-                let height: u32 = full
-                    .get("height")
-                    .and_then(|v| v.as_str())
-                    .and_then(|s| s.parse().ok())
-                    .unwrap_or(200);
-
-                if !url.is_empty() {
-                    gifs.push(GiphyGif {
-                        id: id.to_string(),
-                        title: title.to_string(),
-                        preview_url: preview_url.to_string(),
-                        url: url.to_string(),
-                        width,
-                        height,
-                    });
-                }
+/// GET /api/events - Server-sent config, device and hook events
+pub async fn event_stream(
+    State(state): State<Arc<AppState>>,
+) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    let events = stream::unfold(state.events.subscribe(), |mut rx| async move {
+        let event = match rx.recv().await {
+            Ok(event) => Event::default().event(event.name()).data(event.data()),
+            Err(RecvError::Lagged(missed)) => {
+                debug!("Event stream client missed {} events", missed);
+                Event::default().event("lagged").data(missed.to_string())
             }
+            Err(RecvError::Closed) => return None,
+        };
+        Some((Ok(event), rx))
+    });
+    Sse::new(events).keep_alive(KeepAlive::default())
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_import_format() {
+        let headers = |pairs: &[(header::HeaderName, &str)]| {
+            let mut headers = HeaderMap::new();
+            for (name, value) in pairs {
+                headers.insert(name.clone(), value.parse().unwrap());
+            }
+            headers
+        };
+        let toml = (header::CONTENT_TYPE, "application/toml");
+        let json = (header::CONTENT_TYPE, "application/json; charset=utf-8");
+        let if_match = (header::IF_MATCH, "\"3\"");
+
+        assert_eq!(import_format(&headers(&[toml.clone(), if_match.clone()])), Ok(false));
+        assert_eq!(import_format(&headers(&[json.clone(), if_match.clone()])), Ok(true));
+
+        // A form post from another site can only send these without a preflight
+        for simple in ["text/plain", "application/x-www-form-urlencoded"] {
+            let form = headers(&[(header::CONTENT_TYPE, simple), if_match.clone()]);
+            let status = import_format(&form).unwrap_err().0;
+            assert_eq!(status, StatusCode::UNSUPPORTED_MEDIA_TYPE);
         }
-    }
+        assert!(import_format(&headers(&[if_match])).is_err());
 
-    gifs
+        let blind = import_format(&headers(&[toml])).unwrap_err().0;
+        assert_eq!(blind, StatusCode::PRECONDITION_REQUIRED);
+    }
 }
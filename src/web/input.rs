@@ -0,0 +1,219 @@
+//! Deck input for external software (`/api/input/next`, `/api/input/ws`)
+//!
+//! Every button press and release, knob turn, knob press and strip gesture on
+//! the main deck is numbered and kept in a short backlog, so the deck can be used as a
+//! generic input device. A long-poll client passes the last `seq` it saw as
+//! `after` and gets everything since, without gaps between polls; the
+//! WebSocket sends each event as one JSON text message. Input is not shown
+//! while the screen or deck is locked, so PIN digits don't leak.
+
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::sync::broadcast::{self, error::RecvError};
+use tokio::sync::Notify;
+use tracing::debug;
+
+use crate::device::{InputEvent, SwipeDirection};
+use crate::input::device_to_logical_button;
+use crate::integrations::websocket::WebSocket;
+
+use super::events::DeckEvent;
+
+/// Events kept for long-poll clients catching up
+const BACKLOG: usize = 64;
+
+/// One input, with buttons numbered as in the rest of the API (0-9)
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Input {
+    ButtonDown { button: u8 },
+    ButtonUp { button: u8 },
+    /// `direction` is 1 per clockwise step, -1 per counter-clockwise
+    EncoderRotate { encoder: u8, direction: i8 },
+    EncoderPress { encoder: u8 },
+    EncoderRelease { encoder: u8 },
+    /// Touch strip zone 0-3, left to right
+    StripTouch { zone: u8 },
+    StripSwipe { direction: SwipeDirection },
+}
+
+impl Input {
+    /// The API's view of a device event (None for keys without a button number)
+    pub fn from_device(event: &InputEvent) -> Option<Self> {
+        Some(match *event {
+            InputEvent::ButtonDown(id) => Input::ButtonDown {
+                button: device_to_logical_button(id)?,
+            },
+            InputEvent::ButtonUp(id) => Input::ButtonUp {
+                button: device_to_logical_button(id)?,
+            },
+            InputEvent::EncoderRotate { encoder, direction } => {
+                Input::EncoderRotate { encoder, direction }
+            }
+            InputEvent::EncoderPress(encoder) => Input::EncoderPress { encoder },
+            InputEvent::EncoderRelease(encoder) => Input::EncoderRelease { encoder },
+            InputEvent::StripTouch(zone) => Input::StripTouch { zone },
+            InputEvent::StripSwipe(direction) => Input::StripSwipe { direction },
+        })
+    }
+}
+
+/// An input as sent to clients
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct InputRecord {
+    /// Counts up from 1 for each event since startup
+    pub seq: u64,
+    /// Unix epoch milliseconds
+    pub time: u64,
+    #[serde(flatten)]
+    pub input: Input,
+}
+
+#[derive(Default)]
+struct Backlog {
+    records: VecDeque<InputRecord>,
+    last_seq: u64,
+}
+
+/// Recent input, for any number of waiting clients
+#[derive(Default)]
+pub struct InputFeed {
+    backlog: Mutex<Backlog>,
+    notify: Notify,
+}
+
+impl InputFeed {
+    /// Number an input and wake every waiting client
+    pub fn push(&self, input: Input) {
+        let time = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or(0);
+        {
+            let mut backlog = self.backlog.lock().unwrap();
+            backlog.last_seq += 1;
+            let seq = backlog.last_seq;
+            if backlog.records.len() == BACKLOG {
+                backlog.records.pop_front();
+            }
+            backlog.records.push_back(InputRecord { seq, time, input });
+        }
+        self.notify.notify_waiters();
+    }
+
+    /// `seq` of the latest input (0 before any)
+    pub fn last_seq(&self) -> u64 {
+        self.backlog.lock().unwrap().last_seq
+    }
+
+    /// Inputs after `after` that are still in the backlog
+    ///
+    /// A client that fell further behind sees a jump in `seq`.
+    pub fn since(&self, after: u64) -> Vec<InputRecord> {
+        let backlog = self.backlog.lock().unwrap();
+        backlog
+            .records
+            .iter()
+            .filter(|record| record.seq > after)
+            .cloned()
+            .collect()
+    }
+
+    /// Wait up to `timeout` for inputs after `after` (or after now, if None)
+    pub async fn wait(&self, after: Option<u64>, timeout: Duration) -> Vec<InputRecord> {
+        let after = after.unwrap_or_else(|| self.last_seq());
+        let deadline = tokio::time::Instant::now() + timeout;
+        loop {
+            // Registered before checking, so a push in between isn't missed
+            let notified = self.notify.notified();
+            tokio::pin!(notified);
+            notified.as_mut().enable();
+            let records = self.since(after);
+            if !records.is_empty() {
+                return records;
+            }
+            if tokio::time::timeout_at(deadline, notified).await.is_err() {
+                return Vec::new();
+            }
+        }
+    }
+}
+
+/// Copy input events from the deck's event channel into the feed
+pub async fn record(mut events: broadcast::Receiver<DeckEvent>, feed: Arc<InputFeed>) {
+    loop {
+        match events.recv().await {
+            Ok(DeckEvent::Input(input)) => feed.push(input),
+            Ok(_) => {}
+            Err(RecvError::Lagged(missed)) => debug!("Input feed missed {} events", missed),
+            Err(RecvError::Closed) => return,
+        }
+    }
+}
+
+/// Send inputs to a WebSocket client until it goes away
+///
+/// Anything the client sends is ignored (pings are answered).
+pub async fn stream<S: AsyncRead + AsyncWrite + Unpin>(
+    mut socket: WebSocket<S>,
+    feed: Arc<InputFeed>,
+) {
+    let mut after = feed.last_seq();
+    loop {
+        tokio::select! {
+            records = feed.wait(Some(after), Duration::from_secs(60)) => {
+                for record in records {
+                    after = record.seq;
+                    let Ok(text) = serde_json::to_string(&record) else {
+                        continue;
+                    };
+                    if socket.send(&text).await.is_err() {
+                        return;
+                    }
+                }
+            }
+            received = socket.recv() => {
+                if let Err(e) = received {
+                    debug!("Input WebSocket closed: {}", e);
+                    return;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_feed() {
+        let feed = InputFeed::default();
+        let rotate = InputEvent::EncoderRotate {
+            encoder: 1,
+            direction: -1,
+        };
+        feed.push(Input::from_device(&InputEvent::ButtonDown(3)).unwrap());
+        feed.push(Input::from_device(&rotate).unwrap());
+        assert_eq!(Input::from_device(&InputEvent::ButtonUp(12)), None);
+
+        let records = feed.since(1);
+        assert_eq!(records.len(), 1);
+        let json = serde_json::to_value(&records[0]).unwrap();
+        assert_eq!(json["seq"], 2);
+        assert_eq!(json["type"], "encoder_rotate");
+        assert_eq!((json["encoder"].as_u64(), json["direction"].as_i64()), (Some(1), Some(-1)));
+
+        // Without `after`, only what happens next
+        assert!(feed.wait(None, Duration::from_millis(10)).await.is_empty());
+        assert_eq!(feed.wait(Some(0), Duration::ZERO).await.len(), 2);
+
+        for button in 0..BACKLOG as u8 {
+            feed.push(Input::ButtonUp { button: button % 10 });
+        }
+        assert_eq!(feed.since(0)[0].seq, 3);
+    }
+}
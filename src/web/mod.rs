@@ -1,9 +1,14 @@
 //! Web server for configuration UI
 
+mod auth;
+pub mod events;
+mod gifsource;
 mod handlers;
+pub mod input;
+mod multipart;
 pub mod server;
 mod static_files;
 mod types;
 
-pub use server::start_server;
-pub use types::ConfigChangeEvent;
+pub use server::{start_server, DeckAccess};
+pub use types::{ApiResponse, ConfigChangeEvent, ConnectedDevice, DeviceReport};
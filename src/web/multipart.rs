@@ -0,0 +1,111 @@
+//! Minimal `multipart/form-data` parsing for uploads
+//!
+//! Bodies are small (a few images) and arrive whole, so this splits the
+//! buffered body on its boundary rather than streaming.
+
+use anyhow::{bail, Context, Result};
+
+/// One part of a form
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Part {
+    /// Form field name
+    pub name: String,
+    /// Original file name, for file fields
+    pub filename: Option<String>,
+    pub data: Vec<u8>,
+}
+
+/// The boundary from a `Content-Type: multipart/form-data; boundary=...` header
+pub fn boundary(content_type: &str) -> Option<String> {
+    let (mime, params) = content_type.split_once(';')?;
+    if !mime.trim().eq_ignore_ascii_case("multipart/form-data") {
+        return None;
+    }
+    param(params, "boundary")
+}
+
+/// Split a form body into its parts
+pub fn parse(body: &[u8], boundary: &str) -> Result<Vec<Part>> {
+    let delimiter = format!("--{}", boundary).into_bytes();
+    let first = find(body, &delimiter, 0).context("Missing multipart boundary")?;
+    let mut pos = first + delimiter.len();
+    let mut next_delimiter = b"\r\n".to_vec();
+    next_delimiter.extend_from_slice(&delimiter);
+
+    let mut parts = Vec::new();
+    // "--" after a delimiter closes the body
+    while !body[pos..].starts_with(b"--") {
+        let Some(start) = body[pos..].strip_prefix(b"\r\n") else {
+            bail!("Malformed multipart delimiter");
+        };
+        let start = body.len() - start.len();
+        let headers_end = find(body, b"\r\n\r\n", start).context("Unterminated part headers")?;
+        let headers = String::from_utf8_lossy(&body[start..headers_end]);
+        let data_start = headers_end + 4;
+        let data_end = find(body, &next_delimiter, data_start).context("Unterminated part")?;
+
+        let disposition = headers
+            .lines()
+            .find_map(|line| {
+                let (name, value) = line.split_once(':')?;
+                name.trim().eq_ignore_ascii_case("content-disposition").then_some(value)
+            })
+            .context("Part without Content-Disposition")?;
+        parts.push(Part {
+            name: param(disposition, "name").unwrap_or_default(),
+            filename: param(disposition, "filename"),
+            data: body[data_start..data_end].to_vec(),
+        });
+        pos = data_end + next_delimiter.len();
+    }
+    Ok(parts)
+}
+
+/// `key=value` or `key="value"` from a `;`-separated header parameter list
+fn param(params: &str, key: &str) -> Option<String> {
+    params.split(';').find_map(|param| {
+        let (name, value) = param.split_once('=')?;
+        if !name.trim().eq_ignore_ascii_case(key) {
+            return None;
+        }
+        let value = value.trim();
+        Some(value.strip_prefix('"').and_then(|v| v.strip_suffix('"')).unwrap_or(value).to_string())
+    })
+}
+
+fn find(haystack: &[u8], needle: &[u8], from: usize) -> Option<usize> {
+    haystack
+        .get(from..)?
+        .windows(needle.len())
+        .position(|window| window == needle)
+        .map(|i| from + i)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_form() {
+        let content_type = "multipart/form-data; boundary=----x1";
+        let boundary = boundary(content_type).unwrap();
+        assert_eq!(boundary, "----x1");
+        assert_eq!(super::boundary("application/json"), None);
+
+        let body = b"------x1\r\n\
+            Content-Disposition: form-data; name=\"label\"\r\n\r\n\
+            Ship it\r\n\
+            ------x1\r\n\
+            Content-Disposition: form-data; name=\"file\"; filename=\"icon.png\"\r\n\
+            Content-Type: image/png\r\n\r\n\
+            \x89PNG\r\n\x1a\n\r\n\
+            ------x1--\r\n";
+        let parts = parse(body, &boundary).unwrap();
+        assert_eq!(parts.len(), 2);
+        assert_eq!((parts[0].name.as_str(), parts[0].data.as_slice()), ("label", &b"Ship it"[..]));
+        assert_eq!(parts[1].filename.as_deref(), Some("icon.png"));
+        assert_eq!(parts[1].data, b"\x89PNG\r\n\x1a\n");
+
+        assert!(parse(b"------x1\r\nno headers", &boundary).is_err());
+    }
+}
@@ -1,50 +1,103 @@
 //! Axum web server setup
 
 use axum::{
-    extract::Request,
+    extract::{DefaultBodyLimit, Request},
+    middleware,
     routing::{delete, get, post, put},
     Router,
 };
 use std::net::SocketAddr;
+use std::path::PathBuf;
 use std::sync::{Arc, RwLock as StdRwLock};
-use tokio::sync::{mpsc, RwLock as TokioRwLock};
-use tower_http::cors::{Any, CorsLayer};
-use tracing::info;
+use tokio::sync::{broadcast, mpsc, RwLock as TokioRwLock};
+use axum::http::{header, HeaderName, Method};
+use tower_http::cors::CorsLayer;
+use tracing::{info, warn};
 
-use crate::config::Config;
+use crate::agent::KeystrokeQueue;
+use crate::config::{Config, ConfigWriter};
+use crate::device::{FrameBuffer, VirtualDevice};
 use crate::profiles::{generate_default_profiles, ProfileManager};
+use crate::AppCommand;
 
+use super::auth::{self, ApiAuth};
+use super::events::DeckEvent;
 use super::handlers::{
     self, AppState,
 };
+use super::input::{self, InputFeed};
 use super::static_files::serve_static;
 use super::types::ConfigChangeEvent;
 
+/// Request size allowed for GIF uploads (base64 of the 10 MB GIF limit)
+const UPLOAD_BODY_LIMIT: usize = 16 * 1024 * 1024;
+
+/// What the web server can see and drive on the deck
+pub struct DeckAccess {
+    /// Mirror of the deck display (simulator view, snapshots)
+    pub frames: Arc<FrameBuffer>,
+    /// Virtual device accepting input under `/api/simulator` (`--simulate`, `--headless`)
+    pub simulator: Option<Arc<VirtualDevice>>,
+    /// Commands for the main loop (`/api/press`, `/api/action`)
+    pub commands: mpsc::Sender<AppCommand>,
+    /// Live events streamed from `/api/events`
+    pub events: broadcast::Sender<DeckEvent>,
+}
+
 /// Start the web server
+///
+/// `dev_static` serves web assets from a directory on disk instead of the
+/// embedded copies (debug builds only). `keystroke_queue` is set in system
+/// daemon mode so user agents can fetch keystrokes to inject.
 pub async fn start_server(
     config: Arc<TokioRwLock<Config>>,
     profile_manager: Arc<StdRwLock<ProfileManager>>,
     change_tx: mpsc::Sender<ConfigChangeEvent>,
     device_state: Arc<TokioRwLock<crate::state::AppState>>,
+    dev_static: Option<PathBuf>,
+    keystroke_queue: Option<Arc<KeystrokeQueue>>,
+    deck: DeckAccess,
 ) -> anyhow::Result<()> {
     let port = config.read().await.web.port;
     let addr = SocketAddr::from(([127, 0, 0, 1], port));
 
+    let auth = Arc::new(ApiAuth::generate(port)?);
+    let state_dir = device_state.read().await.state_dir();
+    match auth.write_token_file(&state_dir) {
+        Ok(path) => info!("API token written to {}", path.display()),
+        Err(e) => warn!("Failed to write the API token file: {:#}", e),
+    }
+
+    let input = Arc::new(InputFeed::default());
+    tokio::spawn(input::record(deck.events.subscribe(), Arc::clone(&input)));
+
     let app_state = Arc::new(AppState {
         config,
         profile_manager,
         change_tx,
         device_state,
+        keystroke_queue,
+        simulator: deck.simulator,
+        frames: deck.frames,
+        config_writer: ConfigWriter::spawn(Config::config_path()?),
+        commands: deck.commands,
+        events: deck.events,
+        input,
     });
 
-    // CORS layer for development
+    // Only the web UI's own origin may read responses
     let cors = CorsLayer::new()
-        .allow_origin(Any)
-        .allow_methods(Any)
-        .allow_headers(Any);
+        .allow_origin(auth.origins())
+        .allow_methods([Method::GET, Method::POST, Method::PUT, Method::DELETE])
+        .allow_headers([
+            header::CONTENT_TYPE,
+            header::IF_MATCH,
+            HeaderName::from_static(auth::TOKEN_HEADER),
+        ])
+        .expose_headers([header::ETAG]);
 
-    // API routes
-    let api_routes = Router::new()
+    // Routes that read or change the saved config, versioned with ETag/If-Match
+    let config_routes = Router::new()
         .route("/profiles", get(handlers::list_profiles))
         .route("/profiles", post(handlers::create_profile))
         .route("/profiles/{name}", get(handlers::get_profile))
@@ -67,24 +120,103 @@ pub async fn start_server(
             get(handlers::has_profile_defaults),
         )
         .route("/profiles/{name}/reset", post(handlers::reset_profile))
-        .route("/apps", get(handlers::list_apps))
+        .route("/profiles/{name}/history", get(handlers::get_profile_history))
+        .route(
+            "/profiles/{name}/rollback/{version}",
+            post(handlers::rollback_profile),
+        )
         .route("/reload", post(handlers::reload_config))
+        .route("/config/export", get(handlers::export_config))
+        .route("/config/import", post(handlers::import_config))
+        .route("/scenes", get(handlers::list_scenes))
+        .route("/scenes/{name}", put(handlers::put_scene))
+        .route("/scenes/{name}", delete(handlers::delete_scene))
+        .route_layer(middleware::from_fn_with_state(
+            app_state.clone(),
+            handlers::config_version_guard,
+        ));
+
+    // API routes
+    let api_routes = Router::new()
+        .merge(config_routes)
+        .route("/apps", get(handlers::list_apps))
         .route("/colors", get(handlers::get_colors))
+        .route("/themes", get(handlers::get_themes))
         .route("/actions", get(handlers::get_actions))
-        .route("/giphy/search", get(handlers::search_giphy))
+        .route("/gifs/search", get(handlers::search_gifs))
+        // Kept for older UI builds
+        .route("/giphy/search", get(handlers::search_gifs))
+        .route(
+            "/gifs/upload",
+            post(handlers::upload_gif).layer(DefaultBodyLimit::max(UPLOAD_BODY_LIMIT)),
+        )
+        .route("/gifs/local/{file}", get(handlers::get_local_gif))
+        .route(
+            "/assets",
+            post(handlers::upload_assets).layer(DefaultBodyLimit::max(UPLOAD_BODY_LIMIT)),
+        )
+        .route("/assets", get(handlers::list_assets))
+        .route("/assets/{id}", get(handlers::get_asset))
+        .route("/assets/{id}", delete(handlers::delete_asset))
         .route("/status", get(handlers::get_status))
+        .route("/events", get(handlers::event_stream))
+        .route("/stats", get(handlers::get_stats))
+        .route("/cache/stats", get(handlers::get_cache_stats))
+        .route("/doctor", get(handlers::get_doctor))
+        .route("/loglevel", get(handlers::get_log_level))
+        .route("/loglevel", put(handlers::put_log_level))
+        .route("/approvals", get(handlers::get_approvals))
+        .route("/snapshot", get(handlers::get_snapshot))
+        .route("/snapshot/restore", post(handlers::restore_snapshot))
+        .route("/scenes/deactivate", post(handlers::deactivate_scene))
+        .route("/scenes/{name}/activate", post(handlers::activate_scene))
+        .route("/press/{button}", post(handlers::press_button))
+        .route("/action", post(handlers::run_action))
+        .route("/device/info", get(handlers::get_device_info))
+        .route("/device/brightness", post(handlers::set_device_brightness))
+        .route("/device/intro", post(handlers::play_device_intro))
+        .route("/device/reset", post(handlers::reset_device))
+        .route("/agent/keystrokes", get(handlers::agent_keystrokes))
+        .route("/input/next", get(handlers::next_input))
+        .route("/input/ws", get(handlers::input_socket))
+        .route("/preview/buttons/{file}", get(handlers::preview_button))
+        .route("/preview/strip.png", get(handlers::preview_strip))
+        .route("/simulator", get(handlers::get_simulator))
+        .route("/simulator/strip", get(handlers::simulator_strip))
+        .route("/simulator/buttons/{button}", get(handlers::simulator_button))
+        .route("/simulator/input", post(handlers::simulator_input))
         .with_state(app_state);
 
-    // Static file fallback handler
-    let static_handler = |req: Request| async move {
-        let path = req.uri().path();
-        serve_static(path).await
+    // Disk-backed assets are a development aid; release builds always use embedded files
+    let dev_static = if cfg!(debug_assertions) {
+        dev_static
+    } else {
+        if dev_static.is_some() {
+            warn!("--dev-static is ignored in release builds, using embedded assets");
+        }
+        None
+    };
+    if let Some(ref dir) = dev_static {
+        info!("Serving web assets from {}", dir.display());
+    }
+    let dev_static = Arc::new(dev_static);
+
+    // Static file fallback handler (the page carries the API token)
+    let token = auth.token().to_string();
+    let static_handler = move |req: Request| {
+        let dev_static = Arc::clone(&dev_static);
+        let token = token.clone();
+        async move {
+            let path = req.uri().path();
+            serve_static(path, dev_static.as_deref(), &token).await
+        }
     };
 
     // Combine routes
     let app = Router::new()
         .nest("/api", api_routes)
         .fallback(static_handler)
+        .layer(middleware::from_fn_with_state(auth, auth::guard))
         .layer(cors);
 
     info!("Web UI available at http://localhost:{}", port);
@@ -95,6 +227,38 @@ pub async fn start_server(
     Ok(())
 }
 
+/// Swap in a config reloaded from disk, along with its profiles
+///
+/// Returns false, changing nothing, if it matches what is already loaded
+/// (e.g., when the file changed because we saved it).
+pub async fn apply_config(
+    config: &TokioRwLock<Config>,
+    profile_manager: &StdRwLock<ProfileManager>,
+    new_config: Config,
+) -> bool {
+    let mut current = config.write().await;
+    let unchanged = {
+        let manager = profile_manager.read().unwrap();
+        let mut loaded = current.clone();
+        loaded.profiles = manager.get_profiles().to_vec();
+        toml::to_string(&loaded).ok() == toml::to_string(&new_config).ok()
+    };
+    if unchanged {
+        return false;
+    }
+
+    let profiles = if new_config.profiles.is_empty() {
+        generate_default_profiles()
+    } else {
+        new_config.profiles.clone()
+    };
+    crate::display::theme::select(&new_config.appearance.theme);
+    *current = new_config;
+    profile_manager.write().unwrap().set_profiles(profiles);
+    crate::config::bump_version();
+    true
+}
+
 /// Initialize profile manager with profiles from config or defaults
 pub fn init_profile_manager(config: &Config) -> ProfileManager {
     let profiles = if config.profiles.is_empty() {
@@ -6,13 +6,21 @@ use axum::{
     response::IntoResponse,
 };
 use rust_embed::RustEmbed;
+use std::path::{Component, Path};
+use tracing::debug;
 
 #[derive(RustEmbed)]
 #[folder = "assets/web/"]
 pub struct WebAssets;
 
-/// Serve embedded static files
-pub async fn serve_static(path: &str) -> impl IntoResponse {
+/// Placeholder in `index.html` replaced with the API token
+const TOKEN_PLACEHOLDER: &str = "{{DECK_TOKEN}}";
+
+/// Serve static files, preferring `dev_dir` on disk when set so UI changes
+/// show up on reload without a rebuild. Falls back to the embedded assets.
+///
+/// `token` is written into `index.html` for the UI's API calls.
+pub async fn serve_static(path: &str, dev_dir: Option<&Path>, token: &str) -> impl IntoResponse {
     // Default to index.html for root
     let path = if path.is_empty() || path == "/" {
         "index.html"
@@ -20,6 +28,12 @@ pub async fn serve_static(path: &str) -> impl IntoResponse {
         path.trim_start_matches('/')
     };
 
+    if let Some(dir) = dev_dir {
+        if let Some(response) = read_from_disk(dir, path, token).await {
+            return response;
+        }
+    }
+
     match WebAssets::get(path) {
         Some(content) => {
             let mime = mime_guess::from_path(path)
@@ -29,7 +43,8 @@ pub async fn serve_static(path: &str) -> impl IntoResponse {
             Response::builder()
                 .status(StatusCode::OK)
                 .header(header::CONTENT_TYPE, mime)
-                .body(Body::from(content.data.to_vec()))
+                .header(header::CACHE_CONTROL, "no-store")
+                .body(Body::from(with_token(path, content.data.to_vec(), token)))
                 .unwrap()
         }
         None => {
@@ -39,7 +54,8 @@ pub async fn serve_static(path: &str) -> impl IntoResponse {
                     return Response::builder()
                         .status(StatusCode::OK)
                         .header(header::CONTENT_TYPE, "text/html")
-                        .body(Body::from(content.data.to_vec()))
+                        .header(header::CACHE_CONTROL, "no-store")
+                        .body(Body::from(with_token("index.html", content.data.to_vec(), token)))
                         .unwrap();
                 }
             }
@@ -51,3 +67,42 @@ pub async fn serve_static(path: &str) -> impl IntoResponse {
         }
     }
 }
+
+/// Read an asset from the dev static directory (no caching, so reloads pick up edits)
+async fn read_from_disk(dir: &Path, path: &str, token: &str) -> Option<Response<Body>> {
+    // Only allow plain relative paths inside the asset directory
+    let relative = Path::new(path);
+    if !relative
+        .components()
+        .all(|c| matches!(c, Component::Normal(_)))
+    {
+        return None;
+    }
+
+    let file_path = dir.join(relative);
+    let data = tokio::fs::read(&file_path).await.ok()?;
+    debug!("Serving {} from disk", file_path.display());
+
+    let mime = mime_guess::from_path(path)
+        .first_or_octet_stream()
+        .to_string();
+
+    Some(
+        Response::builder()
+            .status(StatusCode::OK)
+            .header(header::CONTENT_TYPE, mime)
+            .header(header::CACHE_CONTROL, "no-store")
+            .body(Body::from(with_token(path, data, token)))
+            .unwrap(),
+    )
+}
+
+/// Fill in the API token if this is the UI page
+fn with_token(path: &str, data: Vec<u8>, token: &str) -> Vec<u8> {
+    if path != "index.html" {
+        return data;
+    }
+    String::from_utf8_lossy(&data)
+        .replace(TOKEN_PLACEHOLDER, token)
+        .into_bytes()
+}
@@ -2,7 +2,12 @@
 
 use serde::{Deserialize, Serialize};
 
+use crate::config::SceneConfig;
+use crate::device::SwipeDirection;
+use crate::profiles::history::ProfileVersion;
 use crate::profiles::store::{ActionConfig, ButtonConfigEntry, ProfileConfig};
+use crate::state::approvals::LogEntry;
+use crate::state::Approval;
 
 /// Event emitted when configuration changes
 #[derive(Debug, Clone)]
@@ -13,6 +18,8 @@ pub enum ConfigChangeEvent {
     ButtonUpdated { profile: String, position: u8 },
     /// Full config reload requested
     Reload,
+    /// A scene was created, updated, or deleted
+    SceneUpdated(String),
 }
 
 /// Profile summary for listing
@@ -51,6 +58,31 @@ impl From<&ProfileConfig> for ProfileResponse {
     }
 }
 
+/// One earlier version of a profile
+#[derive(Debug, Clone, Serialize)]
+pub struct ProfileVersionInfo {
+    pub version: u64,
+    /// When it was replaced (Unix epoch seconds)
+    pub saved_at: u64,
+    pub profile: ProfileResponse,
+}
+
+impl From<&ProfileVersion> for ProfileVersionInfo {
+    fn from(version: &ProfileVersion) -> Self {
+        Self {
+            version: version.version,
+            saved_at: version.saved_at,
+            profile: ProfileResponse::from(&version.profile),
+        }
+    }
+}
+
+/// Result of `GET /api/profiles/:name/history`, oldest first
+#[derive(Debug, Clone, Serialize)]
+pub struct ProfileHistoryResponse {
+    pub versions: Vec<ProfileVersionInfo>,
+}
+
 /// Request to update a profile
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UpdateProfileRequest {
@@ -77,6 +109,9 @@ pub struct UpdateButtonRequest {
     /// Custom image (base64 data URL) - empty string means "clear/remove"
     #[serde(default)]
     pub custom_image: Option<String>,
+    /// SVG icon markup or file path - empty string means "clear/remove"
+    #[serde(default)]
+    pub icon_svg: Option<String>,
     /// GIF URL - empty string means "clear/remove"
     #[serde(default)]
     pub gif_url: Option<String>,
@@ -265,11 +300,71 @@ pub fn get_action_types() -> Vec<ActionType> {
             description: "Type text directly".to_string(),
             action_type: "text".to_string(),
         },
+        ActionType {
+            name: "Snippet".to_string(),
+            description: "Type a template, leaving the cursor at {cursor}".to_string(),
+            action_type: "snippet".to_string(),
+        },
         ActionType {
             name: "Emoji".to_string(),
             description: "Type an emoji shortcode (e.g. :+1:)".to_string(),
             action_type: "emoji".to_string(),
         },
+        ActionType {
+            name: "Scene".to_string(),
+            description: "Toggle a named scene".to_string(),
+            action_type: "scene".to_string(),
+        },
+        ActionType {
+            name: "Toggle".to_string(),
+            description: "Send a key and latch the button on/off".to_string(),
+            action_type: "toggle".to_string(),
+        },
+        ActionType {
+            name: "AppleScript".to_string(),
+            description: "Run an AppleScript via osascript".to_string(),
+            action_type: "applescript".to_string(),
+        },
+        ActionType {
+            name: "Open URL".to_string(),
+            description: "Open a URL in the default browser".to_string(),
+            action_type: "open_url".to_string(),
+        },
+        ActionType {
+            name: "Focus app".to_string(),
+            description: "Bring an app to the front, launching it if needed".to_string(),
+            action_type: "focus_app".to_string(),
+        },
+        ActionType {
+            name: "Launch app".to_string(),
+            description: "Launch an app by path".to_string(),
+            action_type: "launch_app".to_string(),
+        },
+        ActionType {
+            name: "Clipboard".to_string(),
+            description: "Paste an entry from the clipboard history".to_string(),
+            action_type: "clipboard".to_string(),
+        },
+        ActionType {
+            name: "Scroll".to_string(),
+            description: "Scroll the mouse wheel under the pointer".to_string(),
+            action_type: "scroll".to_string(),
+        },
+        ActionType {
+            name: "OBS".to_string(),
+            description: "Switch scenes, record or mute in OBS Studio".to_string(),
+            action_type: "obs".to_string(),
+        },
+        ActionType {
+            name: "Home Assistant".to_string(),
+            description: "Call a Home Assistant service on an entity".to_string(),
+            action_type: "home_assistant".to_string(),
+        },
+        ActionType {
+            name: "Script".to_string(),
+            description: "Run a script from the scripts folder".to_string(),
+            action_type: "script".to_string(),
+        },
     ]
 }
 
@@ -436,6 +531,113 @@ pub fn get_builtin_actions() -> Vec<BuiltinAction> {
             value: "CLEAR".to_string(),
             description: "Clear the current input".to_string(),
         },
+        BuiltinAction {
+            name: "Compact".to_string(),
+            value: "COMPACT".to_string(),
+            description: "Summarize the conversation to free context (/compact)".to_string(),
+        },
+        BuiltinAction {
+            name: "Cost".to_string(),
+            value: "COST".to_string(),
+            description: "Show the session's token usage and cost (/cost)".to_string(),
+        },
+        BuiltinAction {
+            name: "Memory".to_string(),
+            value: "MEMORY".to_string(),
+            description: "Type /memory to edit CLAUDE.md memory files".to_string(),
+        },
+        BuiltinAction {
+            name: "Resume".to_string(),
+            value: "RESUME".to_string(),
+            description: "Open a new terminal with claude --resume".to_string(),
+        },
+        BuiltinAction {
+            name: "Text entry".to_string(),
+            value: "ENTRY".to_string(),
+            description: "Enter text with the knobs, press again to type it".to_string(),
+        },
+        BuiltinAction {
+            name: "Timer".to_string(),
+            value: "TIMER".to_string(),
+            description: "Start/pause the countdown timer (long-press resets)".to_string(),
+        },
+        BuiltinAction {
+            name: "Pin profile".to_string(),
+            value: "PIN_PROFILE".to_string(),
+            description: "Keep this profile whatever app is focused, until pressed again"
+                .to_string(),
+        },
+        BuiltinAction {
+            name: "Lock deck".to_string(),
+            value: "LOCK_DECK".to_string(),
+            description: "Disable the buttons until the [security] PIN is keyed in".to_string(),
+        },
+        BuiltinAction {
+            name: "Git pull".to_string(),
+            value: "GIT_PULL".to_string(),
+            description: "Run git pull in the git widget's repository".to_string(),
+        },
+        BuiltinAction {
+            name: "Git push".to_string(),
+            value: "GIT_PUSH".to_string(),
+            description: "Run git push in the git widget's repository".to_string(),
+        },
+        BuiltinAction {
+            name: "Screenshot".to_string(),
+            value: "SCREENSHOT".to_string(),
+            description: "Capture part of the screen to the clipboard".to_string(),
+        },
+        BuiltinAction {
+            name: "Screenshot to project".to_string(),
+            value: "SCREENSHOT_SAVE".to_string(),
+            description: "Save a screenshot in the Claude session's directory, copying its path"
+                .to_string(),
+        },
+        BuiltinAction {
+            name: "Next session".to_string(),
+            value: "SESSION_NEXT".to_string(),
+            description: "Open the session picker / highlight the next session".to_string(),
+        },
+        BuiltinAction {
+            name: "Previous session".to_string(),
+            value: "SESSION_PREV".to_string(),
+            description: "Open the session picker / highlight the previous session".to_string(),
+        },
+        BuiltinAction {
+            name: "Pick session".to_string(),
+            value: "SESSION_PICK".to_string(),
+            description: "Follow the highlighted Claude session".to_string(),
+        },
+        BuiltinAction {
+            name: "Next agent".to_string(),
+            value: "AGENT_NEXT".to_string(),
+            description: "Show the next running sub-agent on the agents widget".to_string(),
+        },
+        BuiltinAction {
+            name: "Previous agent".to_string(),
+            value: "AGENT_PREV".to_string(),
+            description: "Show the previous running sub-agent on the agents widget".to_string(),
+        },
+        BuiltinAction {
+            name: "Play/Pause".to_string(),
+            value: "MEDIA_PLAY_PAUSE".to_string(),
+            description: "Play or pause the active media player".to_string(),
+        },
+        BuiltinAction {
+            name: "Next track".to_string(),
+            value: "MEDIA_NEXT".to_string(),
+            description: "Skip to the next track".to_string(),
+        },
+        BuiltinAction {
+            name: "Previous track".to_string(),
+            value: "MEDIA_PREV".to_string(),
+            description: "Go back to the previous track".to_string(),
+        },
+        BuiltinAction {
+            name: "Do Not Disturb".to_string(),
+            value: "DND".to_string(),
+            description: "Toggle Do Not Disturb (via a Shortcuts.app shortcut)".to_string(),
+        },
     ]
 }
 
@@ -473,21 +675,204 @@ pub struct SwapButtonsRequest {
     pub position2: u8,
 }
 
-/// Giphy search query parameters
+/// Query parameters for `GET /api/config/export`
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ConfigExportQuery {
+    /// "toml" (default) or "json"
+    #[serde(default)]
+    pub format: Option<String>,
+}
+
+/// Result of `POST /api/config/import`
+#[derive(Debug, Clone, Serialize)]
+pub struct ConfigImportResponse {
+    /// Where the replaced config was copied (None if there was no file yet)
+    pub backup: Option<String>,
+}
+
+/// GIF search query parameters
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct GiphySearchQuery {
+pub struct GifSearchQuery {
     pub q: String,
-    #[serde(default = "default_giphy_limit")]
+    #[serde(default = "default_gif_limit")]
     pub limit: u32,
 }
 
-fn default_giphy_limit() -> u32 {
+fn default_gif_limit() -> u32 {
     12
 }
 
-/// A single GIF from Giphy
+/// An uploaded button image (`POST /api/assets`, `GET /api/assets`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AssetInfo {
+    pub id: String,
+    /// Value for a button's `custom_image` (`asset://<id>`)
+    pub reference: String,
+    /// Where the browser can load it
+    pub url: String,
+    pub size: u64,
+}
+
+impl AssetInfo {
+    pub fn new(id: String, size: u64) -> Self {
+        Self {
+            reference: format!("{}{}", crate::display::assets::ASSET_SCHEME, id),
+            url: format!("/api/assets/{}", id),
+            id,
+            size,
+        }
+    }
+}
+
+/// A GIF to add to the local library
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UploadGifRequest {
+    /// File name (".gif" is added if missing)
+    pub name: String,
+    /// GIF contents as a base64 data URL or plain base64
+    pub data: String,
+}
+
+/// Scenes list with the currently active scene
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScenesResponse {
+    pub scenes: Vec<SceneConfig>,
+    pub active: Option<String>,
+}
+
+/// Pending permission requests, oldest first, and the audit log's tail
+#[derive(Debug, Clone, Serialize)]
+pub struct ApprovalsResponse {
+    pub pending: Vec<Approval>,
+    pub history: Vec<LogEntry>,
+}
+
+/// Agent keystroke poll query parameters
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AgentPollQuery {
+    /// Login name of the user the agent runs as
+    #[serde(default)]
+    pub user: String,
+}
+
+/// Input long-poll query parameters
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InputPollQuery {
+    /// `seq` of the last input seen; without it, only new input is returned
+    #[serde(default)]
+    pub after: Option<u64>,
+    /// Seconds to wait for input before returning empty (at most 60)
+    #[serde(default)]
+    pub timeout: Option<u64>,
+}
+
+/// A display theme, for `/api/themes`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ThemeInfo {
+    pub name: String,
+    pub description: String,
+}
+
+/// Available themes and the one in use
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ThemesResponse {
+    pub themes: Vec<ThemeInfo>,
+    pub current: String,
+}
+
+/// Remote button press query parameters
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PressQuery {
+    /// Act as a long press instead of a short one
+    #[serde(default)]
+    pub long: bool,
+}
+
+/// Body of `POST /api/device/brightness`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BrightnessRequest {
+    /// 0-100
+    pub brightness: u8,
+}
+
+/// Body of `PUT /api/loglevel`, and its response
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LogLevel {
+    /// Filter directives in `RUST_LOG` syntax (e.g. "info,claude_deck::device=debug")
+    pub level: String,
+}
+
+/// The main deck, from `GET /api/device/info`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeviceReport {
+    pub connected: bool,
+    /// Failed reconnects so far while disconnected
+    pub reconnect_attempts: u32,
+    pub brightness: u8,
+    /// None while disconnected
+    pub device: Option<ConnectedDevice>,
+}
+
+/// Model details of the connected deck
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConnectedDevice {
+    pub model: String,
+    /// None for the simulated device
+    pub serial_number: Option<String>,
+    pub simulated: bool,
+    pub rows: u8,
+    pub cols: u8,
+    pub encoders: u8,
+    /// Touch strip size in pixels, if the model has one
+    pub strip_size: Option<(u32, u32)>,
+}
+
+/// Simulator availability and display version (`--simulate` and `--headless`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SimulatorStatus {
+    pub enabled: bool,
+    /// Incremented whenever the simulated display changes
+    pub version: u64,
+    pub brightness: u8,
+}
+
+/// Synthetic input for the simulated device
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SimulatorInput {
+    /// Press and release a button (hold_ms >= 2000 for a long press)
+    Press {
+        button: u8,
+        #[serde(default)]
+        hold_ms: u64,
+    },
+    ButtonDown { button: u8 },
+    ButtonUp { button: u8 },
+    /// Turn a knob one detent (direction 1 = clockwise, -1 = counter-clockwise)
+    Rotate { encoder: u8, direction: i8 },
+    EncoderPress { encoder: u8 },
+    /// Touch a strip zone (0-3, left to right)
+    StripTouch { zone: u8 },
+    /// Swipe along the strip ("left" or "right")
+    StripSwipe { direction: SwipeDirection },
+}
+
+/// Everything the deck is showing, for `/api/snapshot`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeckSnapshot {
+    /// Button images as PNG data URLs, indexed by button position
+    pub buttons: Vec<String>,
+    /// LCD strip image as a PNG data URL
+    pub strip: String,
+    pub brightness: u8,
+    /// App state at the time of the snapshot (informational, not restored)
+    #[serde(default)]
+    pub state: serde_json::Value,
+}
+
+/// A single GIF search result
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct GiphyGif {
+pub struct SearchGif {
     pub id: String,
     pub title: String,
     /// Small preview URL (for grid display)
@@ -500,8 +885,10 @@ pub struct GiphyGif {
     pub height: u32,
 }
 
-/// Giphy search response
+/// GIF search response
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct GiphySearchResponse {
-    pub gifs: Vec<GiphyGif>,
+pub struct GifSearchResponse {
+    /// Provider that answered ("Giphy", "Tenor" or "local library")
+    pub provider: String,
+    pub gifs: Vec<SearchGif>,
 }
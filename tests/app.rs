@@ -0,0 +1,107 @@
+//! End-to-end tests of `App::run` on a mock deck
+//!
+//! Each test runs the real main loop and checks what it sends to the deck
+//! through the mock's call log and the images it shows.
+
+mod common;
+
+use claude_deck::device::{button_to_display_key, DeviceCall, InputEvent};
+use common::{run_until, Harness};
+use tokio::io::AsyncWriteExt;
+use tokio::net::UnixStream;
+
+const PROFILES: &str = r##"
+[[profiles]]
+name = "default"
+match_apps = ["*"]
+
+[[profiles.buttons]]
+position = 0
+label = "HOME"
+color = "#505560"
+bright_color = "#6E7380"
+action = { type = "text", value = "home" }
+
+[[profiles]]
+name = "slack"
+match_apps = ["Slack"]
+
+[[profiles.buttons]]
+position = 0
+label = "SLACK"
+color = "#4A154B"
+bright_color = "#6E2A70"
+action = { type = "text", value = "slack" }
+"##;
+
+#[tokio::test]
+async fn test_profile_switch() {
+    let (mut app, mut deck) = Harness::new("profile", PROFILES).await;
+    let key = button_to_display_key(0);
+
+    run_until(&mut app, async {
+        deck.wait_for_startup().await;
+        let home = deck.device.button_image(key).unwrap();
+
+        // As when a button focuses another app
+        deck.state.write().await.pending_app = Some("Slack".to_string());
+        deck.wait_for(|calls| calls.contains(&DeviceCall::ButtonImage(key)))
+            .await;
+        deck.wait_for_flush().await;
+        assert_ne!(deck.device.button_image(key).unwrap(), home);
+        assert_eq!(deck.state.read().await.focused_app, "Slack");
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_hook_update() {
+    let (mut app, mut deck) = Harness::new("hook", PROFILES).await;
+    let socket = deck.dir.join("hook.sock");
+
+    run_until(&mut app, async {
+        deck.wait_for_startup().await;
+        let ready = deck.device.strip_image().unwrap();
+
+        let mut hook = UnixStream::connect(&socket).await.unwrap();
+        let update = r#"{"event":"PreToolUse","task":"Bash","tool_detail":"cargo test"}"#;
+        hook.write_all(format!("{}\n", update).as_bytes())
+            .await
+            .unwrap();
+        drop(hook);
+
+        deck.wait_for(|calls| calls.contains(&DeviceCall::StripImage))
+            .await;
+        deck.wait_for_flush().await;
+        assert_ne!(deck.device.strip_image().unwrap(), ready);
+        let state = deck.state.read().await;
+        assert_eq!(state.task_name, "Bash");
+        assert_eq!(state.tool_detail.as_deref(), Some("cargo test"));
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_button_press() {
+    let (mut app, mut deck) = Harness::new("press", PROFILES).await;
+    let key = button_to_display_key(0);
+
+    run_until(&mut app, async {
+        deck.wait_for_startup().await;
+        let idle = deck.device.button_image(key).unwrap();
+
+        // Pressing lights the button up until it's released
+        deck.device.push_input(InputEvent::ButtonDown(0));
+        deck.wait_for(|calls| calls.contains(&DeviceCall::ButtonImage(key)))
+            .await;
+        deck.wait_for_flush().await;
+        assert_ne!(deck.device.button_image(key).unwrap(), idle);
+        deck.clear();
+
+        deck.device.push_input(InputEvent::ButtonUp(0));
+        deck.wait_for(|calls| calls.contains(&DeviceCall::ButtonImage(key)))
+            .await;
+        deck.wait_for_flush().await;
+    })
+    .await;
+}
@@ -0,0 +1,118 @@
+//! Harness for running `App` on a `MockDevice`
+//!
+//! Each app keeps its hook socket, saved state and stats in its own scratch
+//! directory (`AppState::state_dir`) and queues keystrokes rather than
+//! typing them, so tests run in parallel without touching the user's files.
+
+// Each test binary uses a different part of the harness
+#![allow(dead_code)]
+
+use std::future::Future;
+use std::path::PathBuf;
+use std::sync::{Arc, RwLock as StdRwLock};
+use std::time::Duration;
+
+use claude_deck::agent::KeystrokeQueue;
+use claude_deck::config::Config;
+use claude_deck::device::{DeviceCall, FrameBuffer, MockDevice};
+use claude_deck::state::AppState;
+use claude_deck::{web, App, AppCommand};
+use tokio::sync::{mpsc, RwLock as TokioRwLock};
+
+/// How long a test waits for the deck to show what it expects
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The deck side of a running app
+pub struct Harness {
+    pub dir: PathBuf,
+    pub device: MockDevice,
+    pub state: Arc<TokioRwLock<AppState>>,
+    pub commands: mpsc::Sender<AppCommand>,
+    /// Display writes seen so far (drained from the device)
+    pub calls: Vec<DeviceCall>,
+}
+
+impl Harness {
+    /// Build an app for `config` (with `$DIR` standing for the scratch directory)
+    pub async fn new(name: &str, config: &str) -> (App<MockDevice>, Self) {
+        let dir = format!("claude-deck-{}-{}", name, std::process::id());
+        let dir = std::env::temp_dir().join(dir);
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let config = config.replace("$DIR", dir.to_str().unwrap());
+        let config: Config = toml::from_str(&config).unwrap();
+        let profile_manager = web::server::init_profile_manager(&config);
+        let state = App::create_state(&config);
+        {
+            let mut state = state.write().await;
+            state.headless = true;
+            state.state_dir = Some(dir.clone());
+        }
+        let device = MockDevice::new();
+        let (commands, command_rx) = mpsc::channel(16);
+
+        let app = App::for_device(
+            config,
+            Arc::new(StdRwLock::new(profile_manager)),
+            command_rx,
+            Arc::clone(&state),
+            Some(Arc::new(KeystrokeQueue::new())),
+            Some(device.clone()),
+            Arc::new(FrameBuffer::new()),
+        )
+        .await
+        .unwrap();
+        let harness = Self {
+            dir,
+            device,
+            state,
+            commands,
+            calls: Vec::new(),
+        };
+        (app, harness)
+    }
+
+    /// Wait until the writes since the last `clear` satisfy `done`
+    pub async fn wait_for(&mut self, done: impl Fn(&[DeviceCall]) -> bool) {
+        loop {
+            self.calls.extend(self.device.take_calls());
+            if done(&self.calls) {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+    }
+
+    /// Wait until the writes end with a flush, so they're all on screen
+    pub async fn wait_for_flush(&mut self) {
+        self.wait_for(|calls| calls.last() == Some(&DeviceCall::Flush))
+            .await;
+    }
+
+    /// Wait for the first full render (ending with the strip's flush)
+    pub async fn wait_for_startup(&mut self) {
+        self.wait_for(|calls| calls.ends_with(&[DeviceCall::StripImage, DeviceCall::Flush]))
+            .await;
+        self.clear();
+    }
+
+    pub fn clear(&mut self) {
+        self.calls.clear();
+    }
+}
+
+impl Drop for Harness {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.dir);
+    }
+}
+
+/// Run the app's main loop until `script` finishes
+pub async fn run_until<F: Future>(app: &mut App<MockDevice>, script: F) -> F::Output {
+    let script = tokio::time::timeout(TIMEOUT, script);
+    tokio::select! {
+        result = app.run() => panic!("App::run returned early: {:?}", result),
+        output = script => output.expect("timed out waiting for the deck"),
+    }
+}
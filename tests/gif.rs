@@ -0,0 +1,68 @@
+//! GIF playback through `App::run` on a mock deck
+//!
+//! Kept in its own test binary: the GIF animator is shared by the process
+//! and every full redraw clears it.
+
+mod common;
+
+use std::path::Path;
+use std::time::Duration;
+
+use claude_deck::device::{button_to_display_key, DeviceCall};
+use claude_deck::AppCommand;
+use common::{run_until, Harness};
+use image::codecs::gif::GifEncoder;
+use image::{Delay, Frame, Rgba, RgbaImage};
+
+/// A two-frame GIF (red, then blue) at 50ms a frame
+fn write_gif(path: &Path) {
+    let file = std::fs::File::create(path).unwrap();
+    let mut encoder = GifEncoder::new(file);
+    encoder
+        .set_repeat(image::codecs::gif::Repeat::Infinite)
+        .unwrap();
+    for color in [Rgba([255, 0, 0, 255]), Rgba([0, 0, 255, 255])] {
+        let image = RgbaImage::from_pixel(16, 16, color);
+        let delay = Delay::from_saturating_duration(Duration::from_millis(50));
+        encoder
+            .encode_frame(Frame::from_parts(image, 0, 0, delay))
+            .unwrap();
+    }
+}
+
+#[tokio::test]
+async fn test_gif_tick() {
+    let config = r##"
+[[profiles]]
+name = "default"
+match_apps = ["*"]
+
+[[profiles.buttons]]
+position = 4
+label = "SPIN"
+color = "#505560"
+bright_color = "#6E7380"
+gif_url = "file://$DIR/spin.gif"
+action = { type = "text", value = "spin" }
+"##;
+    let (mut app, mut deck) = Harness::new("gif", config).await;
+    write_gif(&deck.dir.join("spin.gif"));
+    let key = button_to_display_key(4);
+
+    run_until(&mut app, async {
+        deck.wait_for_startup().await;
+        // GIFs start loading on the first full redraw (here from the web UI)
+        deck.commands.send(AppCommand::RedrawButtons).await.unwrap();
+        // Once loaded, each frame is sent on the animation tick
+        let frame = DeviceCall::ButtonFrame(key);
+        deck.wait_for(|calls| calls.iter().filter(|c| **c == frame).count() >= 4)
+            .await;
+        deck.wait_for_flush().await;
+        let first = deck.device.button_image(key).unwrap();
+        deck.clear();
+        deck.wait_for(|calls| calls.contains(&frame)).await;
+        deck.wait_for_flush().await;
+        assert_ne!(deck.device.button_image(key).unwrap(), first);
+    })
+    .await;
+}